@@ -5,6 +5,7 @@ use std::sync::atomic::AtomicBool;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
 use chess_engine::board::{find_best_move, Board, SearchState, DEFAULT_TT_MB};
+use chess_engine::tt::{BoundType, TranspositionTable};
 
 fn bench_perft(c: &mut Criterion) {
     let mut group = c.benchmark_group("perft");
@@ -116,11 +117,52 @@ fn bench_eval(c: &mut Criterion) {
     group.finish();
 }
 
+/// Xorshift step for TT benchmark keys
+fn next_key(key: &mut u64) -> u64 {
+    *key ^= *key << 13;
+    *key ^= *key >> 7;
+    *key ^= *key << 17;
+    *key
+}
+
+fn bench_tt_probe(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tt_probe");
+
+    // Every probe hits a fresh random bucket, so once the table outgrows
+    // the cache the probe latency is dominated by memory access
+    for size_mb in [16, 256, 1024] {
+        let tt = TranspositionTable::new(size_mb);
+        let mut key = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..100_000 {
+            tt.store(next_key(&mut key), 8, 0, BoundType::Exact, None, 1);
+        }
+
+        group.bench_with_input(BenchmarkId::new("probe", size_mb), &tt, |b, tt| {
+            let mut key = 1u64;
+            b.iter(|| black_box(tt.probe(next_key(&mut key))));
+        });
+        // Prefetch one probe ahead, as the search does before make_move
+        group.bench_with_input(BenchmarkId::new("prefetched", size_mb), &tt, |b, tt| {
+            let mut key = 1u64;
+            let mut next = next_key(&mut key);
+            b.iter(|| {
+                let current = next;
+                next = next_key(&mut key);
+                tt.prefetch(next);
+                black_box(tt.probe(current))
+            });
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_perft,
     bench_movegen,
     bench_search,
-    bench_eval
+    bench_eval,
+    bench_tt_probe
 );
 criterion_main!(benches);
@@ -0,0 +1,27 @@
+//! Fuzz `Board::try_from_fen`: any accepted FEN must give a usable board
+//! whose own FEN parses back to the same position.
+
+#![no_main]
+
+use chess_engine::board::Board;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(fen) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(mut board) = Board::try_from_fen(fen) else {
+        return;
+    };
+
+    let moves = board.generate_moves();
+    let _ = board.evaluate();
+    if let Some(mv) = moves.iter().next() {
+        let mut child = board.clone();
+        let _ = child.make_move_uci(&mv.to_string());
+        let _ = child.generate_moves();
+    }
+
+    let round_trip = Board::try_from_fen(&board.to_fen()).expect("to_fen output must parse");
+    assert_eq!(round_trip.hash(), board.hash());
+});
@@ -0,0 +1,31 @@
+//! Fuzz `Board::parse_san` from a few positions with castling, promotion
+//! and en passant available.
+
+#![no_main]
+
+use chess_engine::board::Board;
+use libfuzzer_sys::fuzz_target;
+
+const FENS: [&str; 3] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/pP1p2pp/8/2pPp3/8/8/P1P2PpP/R3K2R w KQkq c6 0 1",
+    "r3k2r/pP1p2pp/8/8/2pPp3/8/P1P2PpP/R3K2R b KQkq d3 0 1",
+];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, rest)) = data.split_first() else {
+        return;
+    };
+    let Ok(san) = std::str::from_utf8(rest) else {
+        return;
+    };
+
+    let mut board = Board::from_fen(FENS[selector as usize % FENS.len()]);
+    if let Ok(mv) = board.parse_san(san) {
+        let legal = board.generate_moves();
+        assert!(
+            legal.iter().any(|&m| m == mv),
+            "parse_san returned an illegal move"
+        );
+    }
+});
@@ -0,0 +1,30 @@
+//! Fuzz `parse_uci_command` and the argument parsers behind `position` and
+//! `go`, i.e. everything a GUI can send before a search starts.
+
+#![no_main]
+
+use chess_engine::board::Board;
+use chess_engine::uci::command::{parse_go_params, parse_uci_command, UciCommand};
+use chess_engine::uci::try_parse_position_command;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(line) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    match parse_uci_command(line) {
+        Some(UciCommand::Position(parts)) => {
+            let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+            let mut board = Board::new();
+            if try_parse_position_command(&mut board, &parts).is_ok() {
+                let _ = board.generate_moves();
+            }
+        }
+        Some(UciCommand::Go(parts)) => {
+            let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+            let _ = parse_go_params(&parts);
+        }
+        _ => {}
+    }
+});
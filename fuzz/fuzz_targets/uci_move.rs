@@ -0,0 +1,21 @@
+//! Fuzz `Board::parse_move` with UCI long algebraic notation.
+
+#![no_main]
+
+use chess_engine::board::Board;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(uci) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let mut board = Board::from_fen("r3k2r/pP1p2pp/8/2pPp3/8/8/P1P2PpP/R3K2R w KQkq c6 0 1");
+    // Promotion letters are accepted in either case and written lowercase
+    if let Ok(mv) = board.parse_move(uci) {
+        assert!(
+            mv.to_string().eq_ignore_ascii_case(uci),
+            "parse_move must round-trip: {mv} from {uci}"
+        );
+    }
+});
@@ -8,7 +8,7 @@
 
 mod tables;
 
-pub(crate) use tables::{KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
+pub(crate) use tables::{BETWEEN, KING_ATTACKS, KNIGHT_ATTACKS, LINE, PAWN_ATTACKS};
 
 use std::sync::LazyLock;
 
@@ -179,6 +179,13 @@ pub(crate) fn rook_attacks(square: usize, occupancy: u64) -> u64 {
     file_attacks(occupancy, square) | rank_attacks(occupancy, square)
 }
 
+/// Whether `c` lies on the line through `a` and `b`.
+/// Used to test whether a pinned piece stays on its pin ray.
+#[inline]
+pub(crate) fn aligned(a: usize, b: usize, c: usize) -> bool {
+    LINE[a][b] & (1u64 << c) != 0
+}
+
 /// Get queen attacks (all 8 directions)
 #[inline]
 pub(crate) fn queen_attacks(square: usize, occupancy: u64) -> u64 {
@@ -1,4 +1,7 @@
-//! Precomputed attack tables for leaper pieces (knights, kings, pawns).
+//! Precomputed attack tables for leaper pieces (knights, kings, pawns)
+//! and square-pair geometry (`BETWEEN`, `LINE`).
+
+use super::{bishop_attacks, rook_attacks};
 
 pub(crate) static KNIGHT_ATTACKS: std::sync::LazyLock<[u64; 64]> = std::sync::LazyLock::new(|| {
     let mut attacks = [0u64; 64];
@@ -96,3 +99,46 @@ pub(crate) static PAWN_ATTACKS: std::sync::LazyLock<[[u64; 64]; 2]> =
         }
         attacks
     });
+
+/// `BETWEEN[a][b]`: squares strictly between `a` and `b` when they share a
+/// rank, file or diagonal; empty otherwise (and for adjacent squares).
+pub(crate) static BETWEEN: std::sync::LazyLock<Box<[[u64; 64]; 64]>> =
+    std::sync::LazyLock::new(|| {
+        let mut between = Box::new([[0u64; 64]; 64]);
+        for a in 0..64 {
+            for b in 0..64 {
+                if a == b {
+                    continue;
+                }
+                let b_bit = 1u64 << b;
+                let a_bit = 1u64 << a;
+                if rook_attacks(a, 0) & b_bit != 0 {
+                    between[a][b] = rook_attacks(a, b_bit) & rook_attacks(b, a_bit);
+                } else if bishop_attacks(a, 0) & b_bit != 0 {
+                    between[a][b] = bishop_attacks(a, b_bit) & bishop_attacks(b, a_bit);
+                }
+            }
+        }
+        between
+    });
+
+/// `LINE[a][b]`: the full edge-to-edge line through `a` and `b` (including
+/// both squares) when they are aligned; empty otherwise.
+pub(crate) static LINE: std::sync::LazyLock<Box<[[u64; 64]; 64]>> =
+    std::sync::LazyLock::new(|| {
+        let mut line = Box::new([[0u64; 64]; 64]);
+        for a in 0..64 {
+            for b in 0..64 {
+                if a == b {
+                    continue;
+                }
+                let ends = (1u64 << a) | (1u64 << b);
+                if rook_attacks(a, 0) & (1u64 << b) != 0 {
+                    line[a][b] = (rook_attacks(a, 0) & rook_attacks(b, 0)) | ends;
+                } else if bishop_attacks(a, 0) & (1u64 << b) != 0 {
+                    line[a][b] = (bishop_attacks(a, 0) & bishop_attacks(b, 0)) | ends;
+                }
+            }
+        }
+        line
+    });
@@ -11,10 +11,12 @@
 //!     .piece(Square::new(7, 4), Color::Black, Piece::King)
 //!     .piece(Square::new(1, 0), Color::White, Piece::Pawn)
 //!     .side_to_move(Color::White)
-//!     .build();
+//!     .build()
+//!     .expect("valid position");
 //! ```
 
-use super::{Board, CastlingRights, Color, Piece, Square};
+use super::state::RepetitionTable;
+use super::{Board, BuilderError, CastlingRights, Color, Piece, Square};
 
 /// A fluent builder for constructing `Board` positions.
 #[derive(Clone, Debug)]
@@ -165,11 +167,28 @@ impl BoardBuilder {
         self
     }
 
-    /// Build the board.
+    /// Set the halfmove clock. Shorthand for [`BoardBuilder::halfmove_clock`].
+    #[must_use]
+    pub const fn halfmove(self, clock: u32) -> Self {
+        self.halfmove_clock(clock)
+    }
+
+    /// Build the board, validating the position first.
+    ///
+    /// Rejects positions without exactly one king per side, with more than
+    /// 16 pieces or 8 pawns per side, with pawns on the back ranks, or with
+    /// an en passant target that no double pawn push could have produced.
+    pub fn build(self) -> Result<Board, BuilderError> {
+        self.validate()?;
+        Ok(self.build_unchecked())
+    }
+
+    /// Build the board without validation.
     ///
-    /// Creates a Board with all the specified pieces and settings.
+    /// Useful for king-less sparring positions and other deliberately
+    /// non-standard setups.
     #[must_use]
-    pub fn build(self) -> Board {
+    pub fn build_unchecked(self) -> Board {
         let mut board = Board::empty();
 
         for (square, color, piece) in self.pieces {
@@ -181,11 +200,90 @@ impl BoardBuilder {
         board.en_passant_target = self.en_passant_target;
         board.halfmove_clock = self.halfmove_clock;
         board.hash = board.calculate_initial_hash();
-        board.repetition_counts.set(board.hash, 1);
+        board.repetition_counts = RepetitionTable::starting_at(board.hash);
         board.recalculate_incremental_eval();
+        board.refresh_checkers();
 
         board
     }
+
+    fn validate(&self) -> Result<(), BuilderError> {
+        for color in Color::BOTH {
+            let count_of = |piece: Piece| {
+                self.pieces
+                    .iter()
+                    .filter(|&&(_, c, p)| c == color && p == piece)
+                    .count() as u32
+            };
+
+            match count_of(Piece::King) {
+                0 => return Err(BuilderError::MissingKing { color }),
+                1 => {}
+                count => return Err(BuilderError::TooManyKings { color, count }),
+            }
+
+            let pawns = count_of(Piece::Pawn);
+            if pawns > 8 {
+                return Err(BuilderError::TooManyPawns {
+                    color,
+                    count: pawns,
+                });
+            }
+
+            let total = self.pieces.iter().filter(|&&(_, c, _)| c == color).count() as u32;
+            if total > 16 {
+                return Err(BuilderError::TooManyPieces {
+                    color,
+                    count: total,
+                });
+            }
+        }
+
+        if let Some(&(square, _, _)) = self
+            .pieces
+            .iter()
+            .find(|&&(sq, _, p)| p == Piece::Pawn && (sq.rank() == 0 || sq.rank() == 7))
+        {
+            return Err(BuilderError::PawnOnBackRank { square });
+        }
+
+        if let Some(target) = self.en_passant_target {
+            let occupant = |sq: Square| {
+                self.pieces
+                    .iter()
+                    .find(|&&(at, _, _)| at == sq)
+                    .map(|&(_, c, p)| (c, p))
+            };
+            if !en_passant_is_consistent(self.side_to_move, target, occupant) {
+                return Err(BuilderError::InvalidEnPassant { square: target });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `target` can be the en passant target with `side_to_move` to
+/// play, given the piece on each square: the target must sit behind an
+/// enemy pawn that just double-pushed, with both the target and the pawn's
+/// origin square empty.
+pub(super) fn en_passant_is_consistent(
+    side_to_move: Color,
+    target: Square,
+    occupant: impl Fn(Square) -> Option<(Color, Piece)>,
+) -> bool {
+    let (target_rank, pawn_rank, origin_rank, pusher) = match side_to_move {
+        Color::White => (5, 4, 6, Color::Black),
+        Color::Black => (2, 3, 1, Color::White),
+    };
+    if target.rank() != target_rank {
+        return false;
+    }
+
+    let file = target.file();
+    occupant(Square::new(pawn_rank, file)) == Some((pusher, Piece::Pawn))
+        && occupant(target).is_none()
+        && occupant(Square::new(origin_rank, file)).is_none()
 }
 
 #[cfg(test)]
@@ -194,7 +292,7 @@ mod tests {
 
     #[test]
     fn test_starting_position() {
-        let built = BoardBuilder::starting_position().build();
+        let built = BoardBuilder::starting_position().build().unwrap();
         let standard = Board::new();
 
         // Compare FEN representations
@@ -206,7 +304,8 @@ mod tests {
         let board = BoardBuilder::new()
             .piece(Square::new(0, 4), Color::White, Piece::King)
             .piece(Square::new(7, 4), Color::Black, Piece::King)
-            .build();
+            .build()
+            .unwrap();
 
         // Should have only two kings
         assert!(board.piece_at(Square::new(0, 4)).is_some());
@@ -219,7 +318,8 @@ mod tests {
         let board = BoardBuilder::starting_position()
             .no_castling_rights()
             .castle_kingside(Color::White)
-            .build();
+            .build()
+            .unwrap();
 
         let rights = CastlingRights::from_u8(board.castling_rights);
         assert!(rights.has(Color::White, true)); // Kingside
@@ -234,7 +334,8 @@ mod tests {
             .piece(Square::new(0, 4), Color::White, Piece::King)
             .piece(Square::new(7, 4), Color::Black, Piece::King)
             .side_to_move(Color::Black)
-            .build();
+            .build()
+            .unwrap();
 
         assert!(!board.white_to_move());
     }
@@ -243,7 +344,8 @@ mod tests {
     fn test_clear_square() {
         let board = BoardBuilder::starting_position()
             .clear(Square::new(0, 0)) // Remove white rook on a1
-            .build();
+            .build()
+            .unwrap();
 
         assert!(board.piece_at(Square::new(0, 0)).is_none());
         assert!(board.piece_at(Square::new(0, 1)).is_some()); // Knight still there
@@ -257,7 +359,8 @@ mod tests {
             .piece(Square::new(4, 3), Color::White, Piece::Pawn)
             .piece(Square::new(4, 4), Color::Black, Piece::Pawn)
             .en_passant(Square::new(5, 4)) // e6
-            .build();
+            .build()
+            .unwrap();
 
         assert_eq!(board.en_passant_target, Some(Square::new(5, 4)));
     }
@@ -269,7 +372,8 @@ mod tests {
             .piece(Square::new(7, 4), Color::Black, Piece::King)
             .en_passant(Square::new(5, 4))
             .clear_en_passant()
-            .build();
+            .build()
+            .unwrap();
 
         assert!(board.en_passant_target.is_none());
     }
@@ -280,7 +384,8 @@ mod tests {
             .piece(Square::new(0, 4), Color::White, Piece::King)
             .piece(Square::new(7, 4), Color::Black, Piece::King)
             .halfmove_clock(50)
-            .build();
+            .build()
+            .unwrap();
 
         assert_eq!(board.halfmove_clock, 50);
     }
@@ -290,7 +395,8 @@ mod tests {
         let board = BoardBuilder::starting_position()
             .no_castling_rights()
             .castle_queenside(Color::Black)
-            .build();
+            .build()
+            .unwrap();
 
         let rights = CastlingRights::from_u8(board.castling_rights);
         assert!(!rights.has(Color::White, true));
@@ -304,7 +410,8 @@ mod tests {
         let board = BoardBuilder::starting_position()
             .no_castling_rights()
             .all_castling_rights()
-            .build();
+            .build()
+            .unwrap();
 
         let rights = CastlingRights::from_u8(board.castling_rights);
         assert!(rights.has(Color::White, true));
@@ -321,7 +428,8 @@ mod tests {
             .piece(Square::new(7, 4), Color::Black, Piece::King)
             .piece(Square::new(3, 3), Color::White, Piece::Pawn)
             .piece(Square::new(3, 3), Color::White, Piece::Queen) // Replace pawn with queen
-            .build();
+            .build()
+            .unwrap();
 
         let (color, piece) = board.piece_at(Square::new(3, 3)).unwrap();
         assert_eq!(color, Color::White);
@@ -334,9 +442,106 @@ mod tests {
         let board = builder
             .piece(Square::new(0, 4), Color::White, Piece::King)
             .piece(Square::new(7, 4), Color::Black, Piece::King)
-            .build();
+            .build()
+            .unwrap();
 
         // Default is white to move
         assert!(board.white_to_move());
     }
+
+    #[test]
+    fn test_halfmove_alias() {
+        let board = BoardBuilder::starting_position()
+            .halfmove(12)
+            .build()
+            .unwrap();
+
+        assert_eq!(board.halfmove_clock, 12);
+    }
+
+    #[test]
+    fn test_missing_king_rejected() {
+        let result = BoardBuilder::new()
+            .piece(Square::new(0, 4), Color::White, Piece::King)
+            .build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            BuilderError::MissingKing {
+                color: Color::Black
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_king_allowed_unchecked() {
+        let board = BoardBuilder::new()
+            .piece(Square::new(0, 4), Color::White, Piece::King)
+            .build_unchecked();
+
+        assert!(!board.has_king(Color::Black));
+    }
+
+    #[test]
+    fn test_too_many_kings_rejected() {
+        let result = BoardBuilder::starting_position()
+            .piece(Square::new(3, 3), Color::White, Piece::King)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(BuilderError::TooManyKings { count: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_too_many_pawns_rejected() {
+        let result = BoardBuilder::starting_position()
+            .piece(Square::new(2, 0), Color::White, Piece::Pawn)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(BuilderError::TooManyPawns { count: 9, .. })
+        ));
+    }
+
+    #[test]
+    fn test_pawn_on_back_rank_rejected() {
+        let result = BoardBuilder::new()
+            .piece(Square::new(0, 4), Color::White, Piece::King)
+            .piece(Square::new(7, 4), Color::Black, Piece::King)
+            .piece(Square::new(7, 0), Color::White, Piece::Pawn)
+            .build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            BuilderError::PawnOnBackRank {
+                square: Square::new(7, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_en_passant_rejected() {
+        // No black pawn on e5 that could have just double-pushed
+        let result = BoardBuilder::new()
+            .piece(Square::new(0, 4), Color::White, Piece::King)
+            .piece(Square::new(7, 4), Color::Black, Piece::King)
+            .en_passant(Square::new(5, 4))
+            .build();
+
+        assert!(matches!(result, Err(BuilderError::InvalidEnPassant { .. })));
+
+        // Wrong rank for the side to move
+        let result = BoardBuilder::new()
+            .piece(Square::new(0, 4), Color::White, Piece::King)
+            .piece(Square::new(7, 4), Color::Black, Piece::King)
+            .piece(Square::new(4, 4), Color::Black, Piece::Pawn)
+            .en_passant(Square::new(5, 4))
+            .side_to_move(Color::Black)
+            .build();
+
+        assert!(result.is_err());
+    }
 }
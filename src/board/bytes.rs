@@ -0,0 +1,154 @@
+//! Compact binary encoding of a position.
+//!
+//! [`Board::to_bytes`] packs a position into 32 bytes, a fraction of its
+//! FEN, for network play and database storage:
+//!
+//! | Bytes | Content |
+//! |-------|---------|
+//! | 0-7   | Occupied squares, as a little-endian bitboard |
+//! | 8-23  | A 4-bit code for each occupied square in square order, low nibble first: `color * 6 + piece`, pawn 0 to king 5 |
+//! | 24    | Bit 0 black to move, bits 1-4 castling rights (`KQkq`), bits 5-6 variant |
+//! | 25    | En passant square plus one, 0 for none |
+//! | 26-27 | Halfmove clock, little-endian |
+//! | 28    | Three-check checks given, White's in the low nibble |
+//! | 29-31 | Zero |
+//!
+//! Everything [`Board::to_fen`] writes is kept, and the variant besides;
+//! like a FEN, the encoding leaves out the repetition history and the
+//! moves played.
+
+use super::error::BytesError;
+use super::state::RepetitionTable;
+use super::{Bitboard, Board, Color, Piece, Square, Variant, ALL_CASTLING_RIGHTS, CHECKS_TO_WIN};
+
+/// Length of the encoding
+const LEN: usize = 32;
+/// Most pieces the piece codes have room for
+const MAX_PIECES: usize = 32;
+const PIECES: usize = 8;
+const FLAGS: usize = 24;
+const EN_PASSANT: usize = 25;
+const HALFMOVE: usize = 26;
+const CHECKS: usize = 28;
+const PADDING: usize = 29;
+
+impl Board {
+    /// The position in 32 bytes, read back by [`Board::from_bytes`].
+    ///
+    /// Halfmove clocks above 65535 are stored as 65535.
+    ///
+    /// # Errors
+    /// Returns [`BytesError::TooManyPieces`] for a position with more than
+    /// 32 pieces, which only board editing can set up.
+    ///
+    /// # Example
+    /// ```
+    /// use chess_engine::board::Board;
+    ///
+    /// let board = Board::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+    /// let bytes = board.to_bytes().unwrap();
+    /// assert_eq!(bytes.len(), 32);
+    /// let decoded = Board::from_bytes(&bytes).unwrap();
+    /// assert_eq!(decoded.to_fen(), board.to_fen());
+    /// ```
+    pub fn to_bytes(&self) -> Result<[u8; LEN], BytesError> {
+        let occupied = self.all_occupied;
+        let count = occupied.popcount();
+        if count as usize > MAX_PIECES {
+            return Err(BytesError::TooManyPieces { count });
+        }
+
+        let mut bytes = [0u8; LEN];
+        bytes[..PIECES].copy_from_slice(&occupied.0.to_le_bytes());
+        for (i, sq) in occupied.iter().enumerate() {
+            let (color, piece) = self.mailbox[sq.index()].expect("occupied square has a piece");
+            let code = (color.index() * 6 + piece.index()) as u8;
+            bytes[PIECES + i / 2] |= code << (4 * (i % 2));
+        }
+
+        let variant = Variant::ALL
+            .iter()
+            .position(|&v| v == self.variant)
+            .unwrap_or(0) as u8;
+        bytes[FLAGS] = u8::from(!self.white_to_move) | self.castling_rights << 1 | variant << 5;
+        bytes[EN_PASSANT] = self.en_passant_target.map_or(0, |sq| sq.index() as u8 + 1);
+        let halfmove = u16::try_from(self.halfmove_clock).unwrap_or(u16::MAX);
+        bytes[HALFMOVE..CHECKS].copy_from_slice(&halfmove.to_le_bytes());
+        // Counters left over from a three-check game that changed variant
+        // are not part of the position
+        if self.variant == Variant::ThreeCheck {
+            bytes[CHECKS] = self.checks_given[0] | self.checks_given[1] << 4;
+        }
+        Ok(bytes)
+    }
+
+    /// Read a position written by [`Board::to_bytes`].
+    ///
+    /// # Errors
+    /// Returns a [`BytesError`] unless `bytes` is 32 bytes long with every
+    /// field in range; unused piece codes and the padding must be zero.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BytesError> {
+        let bytes: &[u8; LEN] = bytes
+            .try_into()
+            .map_err(|_| BytesError::WrongLength { len: bytes.len() })?;
+        let invalid = |offset: usize| BytesError::InvalidField {
+            offset,
+            value: bytes[offset],
+        };
+
+        let mut occupied = [0u8; PIECES];
+        occupied.copy_from_slice(&bytes[..PIECES]);
+        let occupied = Bitboard(u64::from_le_bytes(occupied));
+        let count = occupied.popcount();
+        if count as usize > MAX_PIECES {
+            return Err(BytesError::TooManyPieces { count });
+        }
+
+        let mut board = Board::empty();
+        for (i, sq) in occupied.iter().enumerate() {
+            let code = bytes[PIECES + i / 2] >> (4 * (i % 2)) & 0xF;
+            let color = match code / 6 {
+                0 => Color::White,
+                1 => Color::Black,
+                _ => return Err(BytesError::InvalidPiece { code }),
+            };
+            board.set_piece(sq, color, Piece::ALL[usize::from(code % 6)]);
+        }
+        // The nibble after an odd number of pieces and the bytes after it
+        if count % 2 == 1 && bytes[PIECES + count as usize / 2] >> 4 != 0 {
+            return Err(invalid(PIECES + count as usize / 2));
+        }
+        let unused = PIECES + (count as usize).div_ceil(2)..FLAGS;
+        if let Some(offset) = unused.chain(PADDING..LEN).find(|&i| bytes[i] != 0) {
+            return Err(invalid(offset));
+        }
+
+        let flags = bytes[FLAGS];
+        let variant = *Variant::ALL
+            .get(usize::from(flags >> 5))
+            .ok_or_else(|| invalid(FLAGS))?;
+        board.white_to_move = flags & 1 == 0;
+        board.castling_rights = (flags >> 1) & ALL_CASTLING_RIGHTS;
+        board.en_passant_target = match bytes[EN_PASSANT] {
+            0 => None,
+            sq @ 1..=64 => Some(Square::from_index(usize::from(sq - 1))),
+            _ => return Err(invalid(EN_PASSANT)),
+        };
+        board.halfmove_clock =
+            u32::from(u16::from_le_bytes([bytes[HALFMOVE], bytes[HALFMOVE + 1]]));
+
+        let checks = [bytes[CHECKS] & 0xF, bytes[CHECKS] >> 4];
+        if variant == Variant::ThreeCheck && checks.iter().all(|&c| c <= CHECKS_TO_WIN) {
+            board.set_checks_given(checks);
+        } else if checks != [0, 0] {
+            return Err(invalid(CHECKS));
+        }
+        board.variant = variant;
+
+        board.hash = board.calculate_initial_hash();
+        board.repetition_counts = RepetitionTable::starting_at(board.hash);
+        board.recalculate_incremental_eval();
+        board.refresh_checkers();
+        Ok(board)
+    }
+}
@@ -0,0 +1,207 @@
+//! Position editing, for board editors in GUIs and the `XBoard` edit mode.
+//!
+//! Every edit keeps the board consistent with itself: the Zobrist hash,
+//! incremental evaluation and cached checkers are updated in place, castling
+//! rights whose king or rook has left its home square are dropped, and an en
+//! passant target that no longer fits the position is cleared. An edited
+//! position has no game history, so the repetition history restarts from it.
+//!
+//! Edits may leave the position illegal (no king, a side not to move in
+//! check, ...) while the user is still setting it up; validate the result
+//! with [`BoardBuilder`](super::BoardBuilder) rules before searching it.
+
+use super::builder::en_passant_is_consistent;
+use super::error::BuilderError;
+use super::state::RepetitionTable;
+use super::{
+    Bitboard, Board, CastlingRights, Color, Piece, Square, CASTLE_BLACK_K, CASTLE_BLACK_Q,
+    CASTLE_WHITE_K, CASTLE_WHITE_Q,
+};
+use crate::zobrist::ZOBRIST;
+
+/// Each castling right: its bit, colour and whether it is kingside
+const CASTLING: [(u8, Color, bool); 4] = [
+    (CASTLE_WHITE_K, Color::White, true),
+    (CASTLE_WHITE_Q, Color::White, false),
+    (CASTLE_BLACK_K, Color::Black, true),
+    (CASTLE_BLACK_Q, Color::Black, false),
+];
+
+impl Board {
+    /// Current castling rights
+    #[inline]
+    #[must_use]
+    pub fn castling_rights(&self) -> CastlingRights {
+        CastlingRights::from_u8(self.castling_rights)
+    }
+
+    /// Square a pawn can capture en passant onto, if any
+    #[inline]
+    #[must_use]
+    pub fn en_passant_target(&self) -> Option<Square> {
+        self.en_passant_target
+    }
+
+    /// Clear all pieces from the board, keeping the side to move
+    pub fn clear(&mut self) {
+        self.pieces = [[Bitboard(0); 6]; 2];
+        self.occupied = [Bitboard(0); 2];
+        self.all_occupied = Bitboard(0);
+        self.castling_rights = 0;
+        self.en_passant_target = None;
+        self.halfmove_clock = 0;
+        self.eval_mg = [0, 0];
+        self.eval_eg = [0, 0];
+        self.game_phase = [0, 0];
+        self.mailbox = [None; 64];
+        self.checkers = Bitboard(0);
+        self.checks_given = [0, 0];
+        self.hash = self.calculate_initial_hash();
+        self.restart_history();
+    }
+
+    /// Flip the side to move.
+    ///
+    /// The en passant target belonged to the previous side to move, so it
+    /// is cleared.
+    pub fn flip_side_to_move(&mut self) {
+        self.white_to_move = !self.white_to_move;
+        self.hash ^= ZOBRIST.black_to_move_key;
+        self.hash ^= en_passant_hash(self.en_passant_target.take());
+        self.refresh_checkers();
+        self.restart_history();
+    }
+
+    /// Place a piece on `sq`, replacing whatever stood there
+    pub fn place_piece(&mut self, sq: Square, color: Color, piece: Piece) {
+        self.take_piece(sq);
+        self.set_piece(sq, color, piece);
+        self.hash ^= ZOBRIST.piece_keys[piece.index()][color.index()][sq.index()];
+        self.add_piece_eval(color, piece, sq);
+        self.after_edit();
+    }
+
+    /// Remove the piece on `sq`, if any
+    pub fn remove_piece_at(&mut self, sq: Square) {
+        if self.take_piece(sq).is_some() {
+            self.after_edit();
+        }
+    }
+
+    /// Move the piece on `from` to `to` as a board editor drag does,
+    /// replacing any piece on `to`.
+    ///
+    /// This is not a chess move: it need not be legal, the side to move and
+    /// clocks are unchanged, and nothing is promoted. Returns the piece
+    /// moved, or `None` if `from` was empty.
+    pub fn drag_piece(&mut self, from: Square, to: Square) -> Option<(Color, Piece)> {
+        if from == to {
+            return self.piece_at(from);
+        }
+        let (color, piece) = self.take_piece(from)?;
+        self.place_piece(to, color, piece);
+        Some((color, piece))
+    }
+
+    /// Set the castling rights.
+    ///
+    /// Every right needs its king and rook on their home squares; otherwise
+    /// the rights are left unchanged and an error names the first one that
+    /// is not possible.
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) -> Result<(), BuilderError> {
+        let rights = rights.as_u8();
+        if let Some(&(_, color, kingside)) = CASTLING.iter().find(|&&(bit, color, kingside)| {
+            rights & bit != 0 && !self.castling_possible(color, kingside)
+        }) {
+            return Err(BuilderError::InvalidCastling { color, kingside });
+        }
+        self.hash ^= castling_hash(self.castling_rights) ^ castling_hash(rights);
+        self.castling_rights = rights;
+        self.restart_history();
+        Ok(())
+    }
+
+    /// Set or clear the en passant target square.
+    ///
+    /// The target must sit behind an enemy pawn that could just have
+    /// double-pushed, with the target and the pawn's origin square empty;
+    /// otherwise the target is left unchanged and an error is returned.
+    pub fn set_en_passant(&mut self, target: Option<Square>) -> Result<(), BuilderError> {
+        if let Some(square) = target {
+            if !self.en_passant_possible(square) {
+                return Err(BuilderError::InvalidEnPassant { square });
+            }
+        }
+        self.hash ^= en_passant_hash(self.en_passant_target) ^ en_passant_hash(target);
+        self.en_passant_target = target;
+        self.restart_history();
+        Ok(())
+    }
+
+    /// Remove the piece on `sq` with its hash and evaluation terms, without
+    /// the follow-up consistency checks
+    fn take_piece(&mut self, sq: Square) -> Option<(Color, Piece)> {
+        let (color, piece) = self.piece_at(sq)?;
+        self.remove_piece(sq, color, piece);
+        self.hash ^= ZOBRIST.piece_keys[piece.index()][color.index()][sq.index()];
+        self.remove_piece_eval(color, piece, sq);
+        Some((color, piece))
+    }
+
+    /// Drop castling rights and the en passant target the edited position
+    /// no longer supports, then refresh the cached state
+    fn after_edit(&mut self) {
+        let kept = CASTLING
+            .iter()
+            .filter(|&&(bit, color, kingside)| {
+                self.castling_rights & bit != 0 && self.castling_possible(color, kingside)
+            })
+            .fold(0, |rights, &(bit, ..)| rights | bit);
+        self.hash ^= castling_hash(self.castling_rights) ^ castling_hash(kept);
+        self.castling_rights = kept;
+
+        if let Some(target) = self.en_passant_target {
+            if !self.en_passant_possible(target) {
+                self.hash ^= en_passant_hash(Some(target));
+                self.en_passant_target = None;
+            }
+        }
+
+        self.refresh_checkers();
+        self.restart_history();
+    }
+
+    /// Whether the king and the castling rook of `color` are at home
+    fn castling_possible(&self, color: Color, kingside: bool) -> bool {
+        let rank = if color == Color::White { 0 } else { 7 };
+        let rook_file = if kingside { 7 } else { 0 };
+        self.piece_at(Square::new(rank, 4)) == Some((color, Piece::King))
+            && self.piece_at(Square::new(rank, rook_file)) == Some((color, Piece::Rook))
+    }
+
+    /// Whether `target` could be an en passant target for the side to move
+    fn en_passant_possible(&self, target: Square) -> bool {
+        en_passant_is_consistent(self.side_to_move(), target, |sq| self.piece_at(sq))
+    }
+
+    /// Start the repetition history afresh from the current position
+    fn restart_history(&mut self) {
+        self.repetition_counts = RepetitionTable::starting_at(self.hash);
+        self.game = None;
+    }
+}
+
+/// Zobrist contribution of castling rights `rights`
+fn castling_hash(rights: u8) -> u64 {
+    CASTLING
+        .iter()
+        .filter(|&&(bit, ..)| rights & bit != 0)
+        .fold(0, |hash, &(_, color, kingside)| {
+            hash ^ ZOBRIST.castling_keys[color.index()][usize::from(!kingside)]
+        })
+}
+
+/// Zobrist contribution of en passant target `target`
+fn en_passant_hash(target: Option<Square>) -> u64 {
+    target.map_or(0, |sq| ZOBRIST.en_passant_keys[sq.file()])
+}
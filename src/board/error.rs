@@ -2,6 +2,8 @@
 
 use std::fmt;
 
+use super::{Color, Square};
+
 /// Error type for FEN parsing failures
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FenError {
@@ -19,6 +21,10 @@ pub enum FenError {
     InvalidRank { rank: usize },
     /// Too many files in a rank
     TooManyFiles { rank: usize, files: usize },
+    /// Invalid three-check counter field
+    InvalidCheckCount { found: String },
+    /// Invalid repetition history field of an extended FEN
+    InvalidHistory { found: String },
 }
 
 impl fmt::Display for FenError {
@@ -45,6 +51,12 @@ impl fmt::Display for FenError {
             FenError::TooManyFiles { rank, files } => {
                 write!(f, "Too many files ({files}) in rank {rank}")
             }
+            FenError::InvalidCheckCount { found } => {
+                write!(f, "Invalid check count '{found}'")
+            }
+            FenError::InvalidHistory { found } => {
+                write!(f, "Invalid repetition history '{found}'")
+            }
         }
     }
 }
@@ -161,6 +173,92 @@ impl fmt::Display for SanError {
 
 impl std::error::Error for SanError {}
 
+/// Error type for `BoardBuilder` validation failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    /// A side has no king
+    MissingKing { color: Color },
+    /// A side has more than one king
+    TooManyKings { color: Color, count: u32 },
+    /// A side has more than 16 pieces
+    TooManyPieces { color: Color, count: u32 },
+    /// A side has more than 8 pawns
+    TooManyPawns { color: Color, count: u32 },
+    /// A pawn is placed on the first or eighth rank
+    PawnOnBackRank { square: Square },
+    /// En passant target is inconsistent with the position
+    InvalidEnPassant { square: Square },
+    /// A castling right whose king or rook is not on its home square
+    InvalidCastling { color: Color, kingside: bool },
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuilderError::MissingKing { color } => {
+                write!(f, "{color} has no king")
+            }
+            BuilderError::TooManyKings { color, count } => {
+                write!(f, "{color} has {count} kings, expected exactly 1")
+            }
+            BuilderError::TooManyPieces { color, count } => {
+                write!(f, "{color} has {count} pieces, at most 16 allowed")
+            }
+            BuilderError::TooManyPawns { color, count } => {
+                write!(f, "{color} has {count} pawns, at most 8 allowed")
+            }
+            BuilderError::PawnOnBackRank { square } => {
+                write!(f, "Pawn on back rank at {square}")
+            }
+            BuilderError::InvalidEnPassant { square } => {
+                write!(f, "Invalid en passant square {square}")
+            }
+            BuilderError::InvalidCastling { color, kingside } => {
+                let side = if *kingside { "kingside" } else { "queenside" };
+                write!(f, "{color} cannot castle {side}: king or rook not at home")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+/// Error type for the compact binary encoding of [`Board::to_bytes`]
+///
+/// [`Board::to_bytes`]: super::Board::to_bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytesError {
+    /// Encoding is not exactly 32 bytes long
+    WrongLength { len: usize },
+    /// More than 32 pieces, which do not fit the encoding
+    TooManyPieces { count: u32 },
+    /// Piece code outside 0-11
+    InvalidPiece { code: u8 },
+    /// Byte out of range for its field (flags, en passant, counters or padding)
+    InvalidField { offset: usize, value: u8 },
+}
+
+impl fmt::Display for BytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytesError::WrongLength { len } => {
+                write!(f, "Encoded position must be 32 bytes, found {len}")
+            }
+            BytesError::TooManyPieces { count } => {
+                write!(f, "{count} pieces, at most 32 can be encoded")
+            }
+            BytesError::InvalidPiece { code } => {
+                write!(f, "Invalid piece code {code}")
+            }
+            BytesError::InvalidField { offset, value } => {
+                write!(f, "Invalid value {value} at byte {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BytesError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -267,6 +365,32 @@ mod tests {
         assert!(err.to_string().contains("Qh7"));
     }
 
+    // BuilderError tests
+    #[test]
+    fn test_builder_error_missing_king() {
+        let err = BuilderError::MissingKing {
+            color: Color::Black,
+        };
+        assert!(err.to_string().contains("no king"));
+    }
+
+    #[test]
+    fn test_builder_error_pawn_on_back_rank() {
+        let err = BuilderError::PawnOnBackRank {
+            square: Square::new(0, 0),
+        };
+        assert!(err.to_string().contains("a1"));
+    }
+
+    #[test]
+    fn test_builder_error_invalid_castling() {
+        let err = BuilderError::InvalidCastling {
+            color: Color::White,
+            kingside: false,
+        };
+        assert!(err.to_string().contains("castle queenside"));
+    }
+
     #[test]
     fn test_error_clone() {
         let err = FenError::InvalidPiece { char: 'x' };
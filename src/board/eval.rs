@@ -21,7 +21,7 @@ const BISHOP_PAIR_BONUS: i32 = 18;
 const TEMPO_BONUS: i32 = 19;
 
 /// Total phase value (sum of all pieces' phase weights at game start)
-const PHASE_TOTAL: i32 = 24;
+pub(crate) const PHASE_TOTAL: i32 = 24;
 
 /// Bishop vs Knight imbalance bonus per pawn difference from 8.
 /// Bishops are worth more in open positions (fewer pawns).
@@ -125,6 +125,13 @@ impl PhaseFactors {
 }
 
 impl Board {
+    /// Game phase from 0 (kings and pawns only) to `PHASE_TOTAL` (all
+    /// pieces on the board), by the non-pawn material of both sides
+    #[must_use]
+    pub fn phase(&self) -> i32 {
+        (self.game_phase[0] + self.game_phase[1]).min(PHASE_TOTAL)
+    }
+
     /// Evaluate the position from the side-to-move's perspective.
     ///
     /// Uses tapered evaluation to interpolate between middlegame and endgame scores
@@ -192,25 +199,20 @@ impl Board {
         total += self.eval_initiative(&ctx).into();
 
         // Tapered evaluation
-        let mut score = phase.taper(total.mg, total.eg) + TEMPO_BONUS;
-
-        // Apply draw multiplier in endgames
-        if phase.endphase > 12 {
-            let strong = if score > 0 {
-                Color::White
-            } else {
-                Color::Black
-            };
-            let mul = self.get_draw_multiplier(strong);
-            score = score * mul / 64;
-        }
+        let mut score = phase.taper(total.mg, total.eg);
 
-        // Return from side-to-move perspective
-        if self.white_to_move {
-            score
+        // Scale drawish endgames toward zero
+        let strong = if score > 0 {
+            Color::White
         } else {
-            -score
-        }
+            Color::Black
+        };
+        score = score * self.drawish_scale(strong) / 64;
+
+        // Return from side-to-move perspective; the tempo bonus belongs
+        // to the side to move, not to white
+        let score = if self.white_to_move { score } else { -score };
+        score + TEMPO_BONUS
     }
 
     /// Simple/fast evaluation for quiescence or pruning decisions.
@@ -262,6 +264,78 @@ impl Board {
         (white_features, black_features)
     }
 
+    /// Apply the feature changes of `m` to an NNUE accumulator.
+    /// Must be called BEFORE `make_move()` while the board is in the pre-move state.
+    pub(crate) fn update_nnue_accumulator(
+        &self,
+        acc: &mut super::nnue::NnueAccumulator,
+        m: super::Move,
+        moving_piece: Piece,
+        moving_color: Color,
+        nnue: &super::nnue::NnueNetwork,
+    ) {
+        use super::nnue::network::feature_index;
+        use super::Square;
+
+        // Helper: compute feature indices for both perspectives
+        let feat = |piece: Piece, color: Color, sq: usize| -> (usize, usize) {
+            (
+                feature_index(piece.index(), color.index(), sq, 0),
+                feature_index(piece.index(), color.index(), sq, 1),
+            )
+        };
+
+        if m.is_castling() {
+            // King: from -> to
+            let (wf, bf) = feat(Piece::King, moving_color, m.from().index());
+            acc.sub_feature(wf, bf, nnue);
+            let (wf, bf) = feat(Piece::King, moving_color, m.to().index());
+            acc.add_feature(wf, bf, nnue);
+
+            // Rook: determine from/to based on king destination file
+            let (rook_from_file, rook_to_file) = if m.to().file() == 6 {
+                (7, 5) // Kingside
+            } else {
+                (0, 3) // Queenside
+            };
+            let rank = m.from().rank();
+            let rook_from = Square::new(rank, rook_from_file).index();
+            let rook_to = Square::new(rank, rook_to_file).index();
+            let (wf, bf) = feat(Piece::Rook, moving_color, rook_from);
+            acc.sub_feature(wf, bf, nnue);
+            let (wf, bf) = feat(Piece::Rook, moving_color, rook_to);
+            acc.add_feature(wf, bf, nnue);
+        } else {
+            // Remove captured piece if any
+            if m.is_en_passant() {
+                // En passant: captured pawn is on a different square
+                let cap_rank = if moving_color == Color::White {
+                    m.to().rank() - 1
+                } else {
+                    m.to().rank() + 1
+                };
+                let cap_sq = Square::new(cap_rank, m.to().file()).index();
+                let (wf, bf) = feat(Piece::Pawn, moving_color.opponent(), cap_sq);
+                acc.sub_feature(wf, bf, nnue);
+            } else if m.is_capture() {
+                // Normal capture: captured piece is on m.to()
+                if let Some((cap_color, cap_piece)) = self.piece_at(m.to()) {
+                    let (wf, bf) = feat(cap_piece, cap_color, m.to().index());
+                    acc.sub_feature(wf, bf, nnue);
+                }
+            }
+
+            // Remove moving piece from source
+            let (wf, bf) = feat(moving_piece, moving_color, m.from().index());
+            acc.sub_feature(wf, bf, nnue);
+
+            // Add piece to destination (may be promoted piece)
+            let placed_piece = m.promotion().unwrap_or(moving_piece);
+            let (wf, bf) = feat(placed_piece, moving_color, m.to().index());
+            acc.add_feature(wf, bf, nnue);
+        }
+    }
+
     /// Evaluate position using NNUE network.
     /// Returns score in centipawns from side-to-move perspective.
     #[must_use]
@@ -191,7 +191,9 @@ impl Board {
                 sqs
             }
         };
-        count += self.pieces_of(color, Piece::Pawn).intersect_popcount(Bitboard(defending_pawn_sqs)) as i32;
+        count += self
+            .pieces_of(color, Piece::Pawn)
+            .intersect_popcount(Bitboard(defending_pawn_sqs)) as i32;
 
         // Check knight defenders
         for knight_sq in self.pieces_of(color, Piece::Knight).iter() {
@@ -1,9 +1,13 @@
 //! Drawish endgame detection.
 //!
 //! Detects positions that are likely draws and scales the evaluation accordingly.
+//! Besides the material-only draws of [`Board::get_draw_multiplier`], this
+//! recognises opposite-colored bishop endings and locked pawn fortresses, and
+//! keeps shrinking their scores while the fifty-move counter runs, so the
+//! search sees that an extra pawn in such a position is not worth much.
 
 use crate::board::state::Board;
-use crate::board::types::{Color, Piece};
+use crate::board::types::{Bitboard, Color, Piece};
 
 /// Multiplier for certain draws (KK, KNK, KBK)
 const DRAW_CERTAIN: i32 = 0;
@@ -14,6 +18,121 @@ const DRAW_LIKELY: i32 = 16;
 /// Multiplier for normal positions (no draw scaling)
 const NO_DRAW_SCALING: i32 = 64;
 
+/// Combined phase (see `PHASE_WEIGHTS`) above which no ending is drawish
+const DRAWISH_MAX_PHASE: i32 = 11;
+
+/// Pure opposite-colored bishop ending with at most one extra pawn
+const OCB_PURE: i32 = 12;
+
+/// Extra multiplier per additional pawn of advantage in a pure OCB ending
+const OCB_PER_PAWN: i32 = 10;
+
+/// Opposite-colored bishops with one rook each
+const OCB_WITH_ROOKS: i32 = 44;
+
+/// Strong side's pawns are all blocked and it has no extra piece material
+const LOCKED_FORTRESS: i32 = 16;
+
+impl Board {
+    /// Scale a side-to-move score toward zero in drawish endings.
+    ///
+    /// Cheap enough for every search evaluation: positions with more than a
+    /// rook and minor per side return immediately.
+    #[must_use]
+    pub fn scale_drawish(&self, score: i32) -> i32 {
+        let strong = if score > 0 {
+            self.side_to_move()
+        } else {
+            self.side_to_move().opponent()
+        };
+        score * self.drawish_scale(strong) / NO_DRAW_SCALING
+    }
+
+    /// Draw multiplier (0-64) for `strong`, combining material draws,
+    /// opposite-colored bishops and locked fortresses. Once a pattern
+    /// applies, the multiplier decays further as the fifty-move counter
+    /// grows without progress.
+    #[must_use]
+    pub fn drawish_scale(&self, strong: Color) -> i32 {
+        if self.game_phase[0] + self.game_phase[1] > DRAWISH_MAX_PHASE {
+            return NO_DRAW_SCALING;
+        }
+
+        let scale = self
+            .get_draw_multiplier(strong)
+            .min(self.ocb_scale(strong))
+            .min(self.fortress_scale(strong));
+        if scale >= NO_DRAW_SCALING {
+            return NO_DRAW_SCALING;
+        }
+
+        let clock = self.halfmove_clock.min(100) as i32;
+        scale * (128 - clock) / 128
+    }
+
+    /// Multiplier for opposite-colored bishop endings
+    fn ocb_scale(&self, strong: Color) -> i32 {
+        let weak = strong.opponent();
+        let strong_bishops = self.pieces_of(strong, Piece::Bishop);
+        let weak_bishops = self.pieces_of(weak, Piece::Bishop);
+        if !strong_bishops.is_single() || !weak_bishops.is_single() {
+            return NO_DRAW_SCALING;
+        }
+        let strong_light = strong_bishops.intersects(Bitboard::LIGHT_SQUARES);
+        let weak_light = weak_bishops.intersects(Bitboard::LIGHT_SQUARES);
+        if strong_light == weak_light {
+            return NO_DRAW_SCALING;
+        }
+
+        let knights =
+            self.piece_count(strong, Piece::Knight) + self.piece_count(weak, Piece::Knight);
+        let queens = self.piece_count(strong, Piece::Queen) + self.piece_count(weak, Piece::Queen);
+        if knights > 0 || queens > 0 {
+            return NO_DRAW_SCALING;
+        }
+
+        match (
+            self.piece_count(strong, Piece::Rook),
+            self.piece_count(weak, Piece::Rook),
+        ) {
+            (0, 0) => {
+                let extra = self.piece_count(strong, Piece::Pawn) as i32
+                    - self.piece_count(weak, Piece::Pawn) as i32;
+                (OCB_PURE + OCB_PER_PAWN * (extra - 1).max(0)).min(NO_DRAW_SCALING)
+            }
+            (1, 1) => OCB_WITH_ROOKS,
+            _ => NO_DRAW_SCALING,
+        }
+    }
+
+    /// Multiplier for locked pawn fortresses: every pawn of the strong side
+    /// is blocked by an enemy pawn with nothing to capture, so it has no
+    /// pawn break, and it has no rook or queen and no more piece material
+    /// than the defender with which to force progress.
+    fn fortress_scale(&self, strong: Color) -> i32 {
+        let weak = strong.opponent();
+        let strong_pawns = self.pieces_of(strong, Piece::Pawn);
+        if strong_pawns.is_empty() {
+            return NO_DRAW_SCALING;
+        }
+
+        let majors = self.piece_count(strong, Piece::Rook) + self.piece_count(strong, Piece::Queen);
+        if majors > 0 || self.game_phase[strong.index()] > self.game_phase[weak.index()] {
+            return NO_DRAW_SCALING;
+        }
+
+        let weak_pawns = self.pieces_of(weak, Piece::Pawn);
+        let blocked = strong_pawns.shift_forward(strong).and(weak_pawns);
+        // A pawn that can capture a weak pawn still has a break
+        let breaks = strong_pawns.pawn_attacks(strong).and(weak_pawns);
+        if blocked.popcount() == strong_pawns.popcount() && breaks.is_empty() {
+            LOCKED_FORTRESS
+        } else {
+            NO_DRAW_SCALING
+        }
+    }
+}
+
 impl Board {
     /// Get draw multiplier for endgame evaluation.
     /// Returns 0-64, where 0 = certain draw, 64 = no draw scaling.
@@ -141,4 +260,53 @@ mod tests {
         // Strong side has major piece, should not be draw
         assert_eq!(board.get_draw_multiplier(Color::White), NO_DRAW_SCALING);
     }
+
+    #[test]
+    fn test_pure_ocb_pawn_up_is_drawish() {
+        // Light-squared Bd3 vs dark-squared Bd6, white one pawn up
+        let board: Board = "8/4k3/3b1p2/8/8/2PB4/3PK3/8 w - - 0 1".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), OCB_PURE);
+
+        // Two extra pawns leave more winning chances
+        let board: Board = "8/4k3/3b4/8/8/2PB4/3PK3/8 w - - 0 1".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), OCB_PURE + OCB_PER_PAWN);
+    }
+
+    #[test]
+    fn test_same_colored_bishops_not_scaled() {
+        let board: Board = "8/4k3/4bp2/8/8/2PB4/3PK3/8 w - - 0 1".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), NO_DRAW_SCALING);
+    }
+
+    #[test]
+    fn test_ocb_with_rooks_mildly_drawish() {
+        let board: Board = "r7/4k3/3b1p2/8/8/2PB4/3PK3/R7 w - - 0 1".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), OCB_WITH_ROOKS);
+    }
+
+    #[test]
+    fn test_locked_pawns_are_fortress() {
+        let board: Board = "8/8/1k6/p1p1p3/P1P1P3/8/5K2/8 w - - 0 1".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), LOCKED_FORTRESS);
+
+        // A pawn that can capture still has a break
+        let board: Board = "8/8/1k6/p1p1p3/P1P1PP2/8/5K2/8 w - - 0 1".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), NO_DRAW_SCALING);
+    }
+
+    #[test]
+    fn test_drawish_scale_decays_with_fifty_move_counter() {
+        let board: Board = "8/4k3/3b1p2/8/8/2PB4/3PK3/8 w - - 64 80".parse().unwrap();
+        assert_eq!(board.drawish_scale(Color::White), OCB_PURE / 2);
+    }
+
+    #[test]
+    fn test_scale_drawish_uses_leading_side() {
+        let board: Board = "8/4k3/3b1p2/8/8/2PB4/3PK3/8 b - - 0 1".parse().unwrap();
+        // Black to move and worse: white is the strong side
+        assert_eq!(board.scale_drawish(-128), -128 * OCB_PURE / NO_DRAW_SCALING);
+        // A pure OCB ending is just as drawish for the pawn-down side
+        assert_eq!(board.scale_drawish(128), 128 * OCB_PURE / NO_DRAW_SCALING);
+        assert_eq!(Board::new().scale_drawish(100), 100);
+    }
 }
@@ -3,11 +3,15 @@
 //! Implements:
 //! - Rook endgame activity (rook cutting off king)
 //! - King centralization (stronger in endgame)
+//! - King activity and opposition once few pieces remain
+//! - Pawn races between unstoppable passers
 //! - Wrong bishop detection (can't control rook pawn promotion square)
 //! - Fortress detection (recognize drawable patterns)
 
 use crate::board::state::Board;
-use crate::board::types::{Bitboard, Color, Piece};
+use crate::board::types::{Bitboard, Color, Piece, Square};
+
+use super::passed_pawns::UNSTOPPABLE_PASSER_EG;
 
 /// Rook activity bonus for cutting off enemy king
 pub const ROOK_CUT_OFF_EG: i32 = 15;
@@ -18,6 +22,19 @@ pub const KING_CENTER_EG: i32 = 8;
 /// Wrong bishop penalty (can't control promotion square)
 pub const WRONG_BISHOP_EG: i32 = -50;
 
+/// Game phase (both sides, out of 24) below which king activity counts,
+/// growing to full weight as the last pieces come off
+pub const KING_ACTIVITY_PHASE: i32 = 8;
+
+/// Extra king centralization bonus at low phase (per square closer to center)
+pub const KING_ACTIVITY_EG: i32 = 6;
+
+/// Bonus per square closer to the nearest pawn for the king at low phase
+pub const KING_PAWN_PROXIMITY_EG: i32 = 3;
+
+/// Bonus for holding the opposition in king and pawn endgames
+pub const OPPOSITION_EG: i32 = 20;
+
 impl Board {
     /// Evaluate endgame patterns.
     ///
@@ -35,6 +52,16 @@ impl Board {
 
         eg += w_eg - b_eg;
 
+        let phase = self.game_phase[0] + self.game_phase[1];
+        if phase < KING_ACTIVITY_PHASE {
+            let activity = self.king_activity(Color::White) - self.king_activity(Color::Black);
+            eg += activity * (KING_ACTIVITY_PHASE - phase) / KING_ACTIVITY_PHASE;
+        }
+        if phase == 0 {
+            eg += self.eval_opposition();
+            eg += self.eval_pawn_race();
+        }
+
         // Check for fortress patterns
         if self.is_fortress() {
             // Reduce score toward draw
@@ -79,6 +106,93 @@ impl Board {
         (6 - center_dist) * KING_CENTER_EG
     }
 
+    /// King activity at low phase: centralization and closeness to the
+    /// nearest pawn, where the king does its work in the endgame.
+    fn king_activity(&self, color: Color) -> i32 {
+        let Some(king) = self.find_king(color) else {
+            return 0;
+        };
+        let center = Self::king_centralization_bonus(king.index()) / KING_CENTER_EG;
+        let pawns = Bitboard(
+            self.pieces_of(Color::White, Piece::Pawn).0
+                | self.pieces_of(Color::Black, Piece::Pawn).0,
+        );
+        let pawn_distance = pawns
+            .iter()
+            .map(|sq| king.chebyshev_distance(sq))
+            .min()
+            .unwrap_or(7);
+        center * KING_ACTIVITY_EG + (7 - pawn_distance) * KING_PAWN_PROXIMITY_EG
+    }
+
+    /// Opposition in king and pawn endgames, from white's perspective.
+    ///
+    /// Kings facing each other on a file, rank or diagonal with an odd
+    /// number of squares between them: the side not to move holds the
+    /// opposition, and the other king has to give way.
+    fn eval_opposition(&self) -> i32 {
+        let (Some(white), Some(black)) =
+            (self.find_king(Color::White), self.find_king(Color::Black))
+        else {
+            return 0;
+        };
+        if self.pieces_of(Color::White, Piece::Pawn).is_empty()
+            && self.pieces_of(Color::Black, Piece::Pawn).is_empty()
+        {
+            return 0;
+        }
+
+        let files = white.file_distance(black);
+        let ranks = (white.rank() as i32 - black.rank() as i32).abs();
+        let distance = files.max(ranks);
+        let aligned = files == 0 || ranks == 0 || files == ranks;
+        if !aligned || distance % 2 != 0 {
+            return 0;
+        }
+        match self.side_to_move() {
+            Color::White => -OPPOSITION_EG,
+            Color::Black => OPPOSITION_EG,
+        }
+    }
+
+    /// Pawn race between unstoppable passers, from white's perspective.
+    ///
+    /// When both sides have a passer the enemy king cannot catch, the side
+    /// that queens first takes the race: its passer keeps its bonus and the
+    /// opponent's is cancelled.
+    fn eval_pawn_race(&self) -> i32 {
+        let (Some(white), Some(black)) =
+            (self.find_king(Color::White), self.find_king(Color::Black))
+        else {
+            return 0;
+        };
+        let (Some(white_moves), Some(black_moves)) = (
+            self.fastest_unstoppable_passer(Color::White, black),
+            self.fastest_unstoppable_passer(Color::Black, white),
+        ) else {
+            return 0;
+        };
+
+        let white_first = match self.side_to_move() {
+            Color::White => white_moves <= black_moves,
+            Color::Black => white_moves < black_moves,
+        };
+        if white_first {
+            UNSTOPPABLE_PASSER_EG
+        } else {
+            -UNSTOPPABLE_PASSER_EG
+        }
+    }
+
+    /// Fewest moves any unstoppable passer of `color` needs to promote
+    fn fastest_unstoppable_passer(&self, color: Color, enemy_king: Square) -> Option<i32> {
+        self.pieces_of(color, Piece::Pawn)
+            .iter()
+            .filter(|&sq| self.is_passed_pawn(sq, color))
+            .filter_map(|sq| self.unstoppable_passer_moves(sq, color, enemy_king))
+            .min()
+    }
+
     /// Evaluate rook cutting off enemy king.
     fn eval_rook_cut_off(rooks: Bitboard, enemy_king_sq: usize, color: Color) -> i32 {
         let enemy_king_rank = enemy_king_sq / 8;
@@ -134,7 +248,9 @@ impl Board {
         // Check for rook pawn only situation
         let a_pawns = pawns.intersects(Bitboard::FILE_A);
         let h_pawns = pawns.intersects(Bitboard::FILE_H);
-        let other_pawns = !pawns.and(Bitboard::FILE_A.or(Bitboard::FILE_H).not()).is_empty();
+        let other_pawns = !pawns
+            .and(Bitboard::FILE_A.or(Bitboard::FILE_H).not())
+            .is_empty();
 
         // Only relevant if we only have rook pawns
         if other_pawns {
@@ -204,6 +320,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_king_activity_only_at_low_phase() {
+        let pawns_only: Board = "k7/8/8/3K4/8/8/6p1/8 w - - 0 1".parse().unwrap();
+        let with_queens: Board = "k6q/8/8/3K4/8/8/6p1/Q7 w - - 0 1".parse().unwrap();
+        let activity =
+            pawns_only.king_activity(Color::White) - pawns_only.king_activity(Color::Black);
+        assert!(activity > 0, "d5 king is more active than a8: {activity}");
+        assert_eq!(
+            pawns_only.eval_endgame_patterns().1 - with_queens.eval_endgame_patterns().1,
+            activity
+        );
+    }
+
+    #[test]
+    fn test_opposition() {
+        let opposition = |fen: &str| Board::from_fen(fen).eval_opposition();
+        // Direct opposition: the side not to move holds it
+        assert_eq!(opposition("8/8/4k3/8/4K3/4P3/8/8 b - - 0 1"), OPPOSITION_EG);
+        assert_eq!(
+            opposition("8/8/4k3/8/4K3/4P3/8/8 w - - 0 1"),
+            -OPPOSITION_EG
+        );
+        // Distant and diagonal opposition
+        assert_eq!(opposition("4k3/8/8/8/4K3/4P3/8/8 b - - 0 1"), OPPOSITION_EG);
+        assert_eq!(opposition("8/8/2k5/8/4K3/4P3/8/8 b - - 0 1"), OPPOSITION_EG);
+        // Kings not aligned, or an even number of squares between them
+        assert_eq!(opposition("8/8/3k4/8/4K3/4P3/8/8 b - - 0 1"), 0);
+        assert_eq!(opposition("8/4k3/8/8/4K3/4P3/8/8 b - - 0 1"), 0);
+    }
+
+    #[test]
+    fn test_pawn_race() {
+        let race = |fen: &str| Board::from_fen(fen).eval_pawn_race();
+        // Both passers need two moves: the side to move queens first
+        assert_eq!(
+            race("6K1/8/P6k/8/8/7p/8/8 w - - 0 1"),
+            UNSTOPPABLE_PASSER_EG
+        );
+        assert_eq!(
+            race("6K1/8/P6k/8/8/7p/8/8 b - - 0 1"),
+            -UNSTOPPABLE_PASSER_EG
+        );
+        // One move behind, even with the move
+        assert_eq!(
+            race("6K1/8/7k/P7/8/7p/8/8 w - - 0 1"),
+            -UNSTOPPABLE_PASSER_EG
+        );
+        // No race when only one side has an unstoppable passer
+        assert_eq!(race("6K1/8/7k/P7/8/8/8/8 w - - 0 1"), 0);
+    }
+
     #[test]
     fn test_wrong_bishop() {
         // White has h-pawn and dark-squared bishop (h8 is light, so wrong)
@@ -1,154 +1,135 @@
-//! Structural imbalances evaluation.
+//! Material imbalance evaluation.
 //!
-//! Implements:
-//! - Knight vs Bishop by pawn count
-//! - Rook pair vs Queen
-//! - Minor piece pair values
+//! A Kaufman-style quadratic table: every piece type's value is adjusted by
+//! the counts of our own and the opponent's piece types, so redundancy
+//! (two knights, rook and queen), pawn dependence (rooks lose value with
+//! more pawns) and pair interactions (two rooks against a queen) come from
+//! one set of tunable coefficients instead of special cases. The base eval
+//! already scores the bishop pair and bishop-vs-knight openness; the table
+//! only adds the interactions on top of those.
 
 use crate::board::state::Board;
 use crate::board::types::{Color, Piece};
 
-/// Knight preference bonus per pawn on board (knights better with more pawns)
-pub const KNIGHT_PAWN_BONUS: i32 = 3;
-
-/// Bishop preference in open positions (fewer pawns)
-pub const BISHOP_OPEN_BONUS: i32 = 5;
-
-/// Two rooks vs queen adjustment
-pub const ROOK_PAIR_VS_QUEEN_MG: i32 = 10;
-pub const ROOK_PAIR_VS_QUEEN_EG: i32 = 20;
-
-/// Two bishops bonus (already in base eval, but adjusted here)
-pub const BISHOP_PAIR_EXTRA_MG: i32 = 5;
-pub const BISHOP_PAIR_EXTRA_EG: i32 = 10;
-
-/// Two knights slight penalty (they don't coordinate as well)
-pub const KNIGHT_PAIR_PENALTY_MG: i32 = -5;
-
-/// Neutral pawn count (8 pawns per side is balanced)
-const NEUTRAL_PAWN_COUNT: i32 = 8;
-
-/// Divisor for scaling minor piece bonuses
-const IMBALANCE_SCALE_DIVISOR: i32 = 4;
+/// Rows and columns of the imbalance table: bishop pair, pawn, knight,
+/// bishop, rook, queen
+pub const IMBALANCE_TERMS: usize = 6;
+
+/// Table coefficients are in 1/16 centipawn
+const IMBALANCE_DIVISOR: i32 = 16;
+
+const PAIR: usize = 0;
+const PAWN: usize = 1;
+const KNIGHT: usize = 2;
+const ROOK: usize = 4;
+const QUEEN: usize = 5;
+
+/// Quadratic material imbalance coefficients, in 1/16 centipawn.
+///
+/// For each of our piece types `i` present, its count is multiplied by
+/// `linear[i] + sum_j(ours[i][j] * our_count[j] + theirs[i][j] * their_count[j])`.
+/// Index 0 counts the bishop pair (0 or 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImbalanceTable {
+    pub linear: [i32; IMBALANCE_TERMS],
+    pub ours: [[i32; IMBALANCE_TERMS]; IMBALANCE_TERMS],
+    pub theirs: [[i32; IMBALANCE_TERMS]; IMBALANCE_TERMS],
+}
 
-/// Threshold for considering a position "open" (few pawns)
-const OPEN_POSITION_PAWN_THRESHOLD: u32 = 6;
+impl ImbalanceTable {
+    /// Default coefficients. Linear terms cancel the quadratic ones for a
+    /// single piece with five own pawns, so the table only shifts value
+    /// between material configurations.
+    pub const DEFAULT: Self = {
+        let mut linear = [0; IMBALANCE_TERMS];
+        let mut ours = [[0; IMBALANCE_TERMS]; IMBALANCE_TERMS];
+        let mut theirs = [[0; IMBALANCE_TERMS]; IMBALANCE_TERMS];
+
+        // Extra bishop pair value, less against an opposing bishop pair
+        linear[PAIR] = 120;
+        theirs[PAIR][3] = -40;
+
+        // Knight pair redundancy: -5 cp for the second knight
+        linear[KNIGHT] = 40;
+        ours[KNIGHT][KNIGHT] = -40;
+
+        // Rooks: -4 cp per own pawn beyond five, -10 cp for the pair
+        linear[ROOK] = 5 * 64 + 80;
+        ours[ROOK][PAWN] = -64;
+        ours[ROOK][ROOK] = -80;
+
+        // Queen: redundant with our rooks, weaker against theirs
+        ours[QUEEN][ROOK] = -80;
+        theirs[QUEEN][ROOK] = -80;
+
+        ImbalanceTable {
+            linear,
+            ours,
+            theirs,
+        }
+    };
+}
 
-/// Bishop advantage bonus in open positions
-const BISHOP_ADVANTAGE_OPEN_MG: i32 = 10;
-const BISHOP_ADVANTAGE_OPEN_EG: i32 = 15;
+impl Default for ImbalanceTable {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
 
 impl Board {
-    /// Evaluate structural imbalances.
+    /// Evaluate material imbalances with the default table.
     ///
     /// Returns (middlegame, endgame) score from white's perspective.
     #[must_use]
     pub fn eval_imbalances(&self) -> (i32, i32) {
-        let mut mg = 0;
-        let mut eg = 0;
-
-        let (w_mg, w_eg) = self.eval_imbalances_for_color(Color::White);
-        let (b_mg, b_eg) = self.eval_imbalances_for_color(Color::Black);
-
-        mg += w_mg - b_mg;
-        eg += w_eg - b_eg;
-
-        // Cross-side imbalances
-        let (cross_mg, cross_eg) = self.eval_cross_imbalances();
-        mg += cross_mg;
-        eg += cross_eg;
+        self.eval_imbalances_with(&ImbalanceTable::DEFAULT)
+    }
 
-        (mg, eg)
+    /// Evaluate material imbalances with `table`, e.g. candidate
+    /// coefficients during tuning
+    #[must_use]
+    pub fn eval_imbalances_with(&self, table: &ImbalanceTable) -> (i32, i32) {
+        let score = (self.imbalance(table, Color::White) - self.imbalance(table, Color::Black))
+            / IMBALANCE_DIVISOR;
+        (score, score)
     }
 
+    #[cfg(test)]
     fn eval_imbalances_for_color(&self, color: Color) -> (i32, i32) {
-        let mut mg = 0;
-        let mut eg = 0;
-
-        let knights = self.piece_count(color, Piece::Knight);
-        let bishops = self.piece_count(color, Piece::Bishop);
-
-        // Minor piece pair adjustments
-        if knights >= 2 {
-            mg += KNIGHT_PAIR_PENALTY_MG;
-        }
-
-        if bishops >= 2 {
-            // Extra bishop pair bonus (on top of base eval)
-            mg += BISHOP_PAIR_EXTRA_MG;
-            eg += BISHOP_PAIR_EXTRA_EG;
-        }
-
-        // Knight vs bishop based on pawn count
-        let total_pawns = self.piece_count(Color::White, Piece::Pawn)
-            + self.piece_count(Color::Black, Piece::Pawn);
-
-        // Knights are better with more pawns (closed positions)
-        // Bishops are better with fewer pawns (open positions)
-        let pawn_factor = total_pawns as i32 - NEUTRAL_PAWN_COUNT;
-
-        if knights > 0 && bishops == 0 {
-            // Only knights - bonus in closed positions
-            mg += knights as i32 * pawn_factor * KNIGHT_PAWN_BONUS / IMBALANCE_SCALE_DIVISOR;
-        } else if bishops > 0 && knights == 0 {
-            // Only bishops - bonus in open positions
-            mg -= pawn_factor * BISHOP_OPEN_BONUS / IMBALANCE_SCALE_DIVISOR;
-            eg -= pawn_factor * BISHOP_OPEN_BONUS / IMBALANCE_SCALE_DIVISOR;
-        }
-
-        (mg, eg)
+        let score = self.imbalance(&ImbalanceTable::DEFAULT, color) / IMBALANCE_DIVISOR;
+        (score, score)
     }
 
-    /// Evaluate cross-side imbalances (comparing piece compositions).
-    fn eval_cross_imbalances(&self) -> (i32, i32) {
-        let mut mg = 0;
-        let mut eg = 0;
-
-        let white_queens = self.piece_count(Color::White, Piece::Queen);
-        let black_queens = self.piece_count(Color::Black, Piece::Queen);
-        let white_rooks = self.piece_count(Color::White, Piece::Rook);
-        let black_rooks = self.piece_count(Color::Black, Piece::Rook);
-
-        // Two rooks vs queen
-        // White has 2 rooks, black has queen (and no rooks)
-        if white_rooks >= 2 && black_queens >= 1 && white_queens == 0 && black_rooks == 0 {
-            mg += ROOK_PAIR_VS_QUEEN_MG;
-            eg += ROOK_PAIR_VS_QUEEN_EG;
-        }
+    /// Piece counts in table order
+    fn imbalance_counts(&self, color: Color) -> [i32; IMBALANCE_TERMS] {
+        let bishops = self.piece_count(color, Piece::Bishop) as i32;
+        [
+            i32::from(bishops >= 2),
+            self.piece_count(color, Piece::Pawn) as i32,
+            self.piece_count(color, Piece::Knight) as i32,
+            bishops,
+            self.piece_count(color, Piece::Rook) as i32,
+            self.piece_count(color, Piece::Queen) as i32,
+        ]
+    }
 
-        // Black has 2 rooks, white has queen
-        if black_rooks >= 2 && white_queens >= 1 && black_queens == 0 && white_rooks == 0 {
-            mg -= ROOK_PAIR_VS_QUEEN_MG;
-            eg -= ROOK_PAIR_VS_QUEEN_EG;
-        }
+    /// `color`'s imbalance in table units
+    fn imbalance(&self, table: &ImbalanceTable, color: Color) -> i32 {
+        let ours = self.imbalance_counts(color);
+        let theirs = self.imbalance_counts(color.opponent());
 
-        // Bishop vs knight imbalances
-        let white_knights = self.piece_count(Color::White, Piece::Knight);
-        let black_knights = self.piece_count(Color::Black, Piece::Knight);
-        let white_bishops = self.piece_count(Color::White, Piece::Bishop);
-        let black_bishops = self.piece_count(Color::Black, Piece::Bishop);
-
-        // Minor piece imbalance (one side has bishop, other has knight)
-        // Value depends on pawn structure
-        let total_pawns = self.piece_count(Color::White, Piece::Pawn)
-            + self.piece_count(Color::Black, Piece::Pawn);
-
-        // In very open positions (few pawns), bishop > knight
-        // In very closed positions (many pawns), knight might be better
-        if total_pawns <= OPEN_POSITION_PAWN_THRESHOLD {
-            // Open position - bishop advantage
-            if white_bishops > black_bishops && white_knights < black_knights {
-                // White has bishop advantage
-                mg += BISHOP_ADVANTAGE_OPEN_MG;
-                eg += BISHOP_ADVANTAGE_OPEN_EG;
-            } else if black_bishops > white_bishops && black_knights < white_knights {
-                // Black has bishop advantage
-                mg -= BISHOP_ADVANTAGE_OPEN_MG;
-                eg -= BISHOP_ADVANTAGE_OPEN_EG;
+        let mut total = 0;
+        for i in 0..IMBALANCE_TERMS {
+            if ours[i] == 0 {
+                continue;
             }
+            let mut value = table.linear[i];
+            for j in 0..IMBALANCE_TERMS {
+                value += table.ours[i][j] * ours[j] + table.theirs[i][j] * theirs[j];
+            }
+            total += ours[i] * value;
         }
-
-        (mg, eg)
+        total
     }
 }
 
@@ -215,6 +196,24 @@ mod tests {
         assert!(eg.abs() < 20, "symmetric imbalances eg: {eg}");
     }
 
+    #[test]
+    fn test_rooks_lose_value_with_pawns() {
+        let few: Board = "4k3/pp6/8/8/8/8/PP6/R3K3 w - - 0 1".parse().unwrap();
+        let many: Board = "4k3/pp6/8/8/8/8/PPPPPPP1/R3K3 w - - 0 1".parse().unwrap();
+        assert!(few.eval_imbalances_for_color(Color::White).0 > 0);
+        assert!(many.eval_imbalances_for_color(Color::White).0 < 0);
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let board: Board = "4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1".parse().unwrap();
+        let mut table = ImbalanceTable::DEFAULT;
+        table.ours[KNIGHT][KNIGHT] = -160;
+        // Two knights: 2 * (40 - 2 * 160) / 16
+        assert_eq!(board.eval_imbalances_with(&table), (-35, -35));
+        assert_eq!(board.eval_imbalances(), (-5, -5));
+    }
+
     #[test]
     fn test_bishop_advantage_open() {
         // Open position: white has bishop, black has knight
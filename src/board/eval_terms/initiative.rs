@@ -105,13 +105,17 @@ impl Board {
         }
 
         for sq in self.pieces_of(color, Piece::Bishop).iter() {
-            if crate::board::Bitboard(slider_attacks(sq.index(), self.all_occupied.0, true)).intersects(king_zone_bb) {
+            if crate::board::Bitboard(slider_attacks(sq.index(), self.all_occupied.0, true))
+                .intersects(king_zone_bb)
+            {
                 attackers += 1;
             }
         }
 
         for sq in self.pieces_of(color, Piece::Rook).iter() {
-            if crate::board::Bitboard(slider_attacks(sq.index(), self.all_occupied.0, false)).intersects(king_zone_bb) {
+            if crate::board::Bitboard(slider_attacks(sq.index(), self.all_occupied.0, false))
+                .intersects(king_zone_bb)
+            {
                 attackers += 1;
             }
         }
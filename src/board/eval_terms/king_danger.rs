@@ -50,6 +50,10 @@ impl Board {
         let mut mg = 0;
         let eg = 0; // Most king danger terms are MG only
 
+        if !self.has_king(color) {
+            return (0, 0);
+        }
+
         let king_sq = self.king_square_index(color);
         let king_file = king_sq % 8;
 
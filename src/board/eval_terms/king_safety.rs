@@ -42,6 +42,11 @@ impl Board {
             let sign = color.sign();
             let color_idx = color.index();
 
+            // King-less sides (sparring positions) have nothing to protect
+            if !self.has_king(color) {
+                continue;
+            }
+
             // Get king square index
             let king_sq_idx = self.king_square_index(color);
 
@@ -137,6 +142,11 @@ impl Board {
             let sign = color.sign();
             let color_idx = color.index();
 
+            // King-less sides (sparring positions) have nothing to protect
+            if !self.has_king(color) {
+                continue;
+            }
+
             // Get king square index
             let king_sq_idx = self.king_square_index(color);
             let king_file = king_sq_idx % 8;
@@ -2,17 +2,20 @@
 //!
 //! Evaluates:
 //! - Knight outposts (knights on strong squares protected by pawns)
+//! - Knights on the rim, graded by distance from the edge
 //! - Bishop outposts
-//! - Bad bishop penalty (bishop blocked by own pawns on same color)
+//! - Bad bishop penalty (own pawns fixed on the bishop's colour, worse when
+//!   they block the centre)
 
-use crate::board::masks::ADJACENT_FILES;
 use crate::board::state::Board;
 use crate::board::types::{Bitboard, Color, Piece, Square};
 
 use super::helpers::AttackContext;
 use super::tables::{
-    BAD_BISHOP_EG, BAD_BISHOP_MG, BISHOP_OUTPOST_EG, BISHOP_OUTPOST_MG, KNIGHT_OUTPOST_EG,
-    KNIGHT_OUTPOST_MG,
+    BAD_BISHOP_BLOCKED_CENTER_EG, BAD_BISHOP_BLOCKED_CENTER_MG, BAD_BISHOP_FIXED_EG,
+    BAD_BISHOP_FIXED_MG, BAD_BISHOP_MOBILE_EG, BAD_BISHOP_MOBILE_MG, BISHOP_OUTPOST_EG,
+    BISHOP_OUTPOST_MG, KNIGHT_CORNER_EG, KNIGHT_CORNER_MG, KNIGHT_OUTPOST_EG, KNIGHT_OUTPOST_MG,
+    KNIGHT_RIM_EG, KNIGHT_RIM_MG,
 };
 
 /// Outpost masks - squares that can be outposts for each color
@@ -28,47 +31,60 @@ const OUTPOST_RANKS: [Bitboard; 2] = [
 const CENTRAL_FILES: Bitboard =
     Bitboard(Bitboard::FILE_C.0 | Bitboard::FILE_D.0 | Bitboard::FILE_E.0 | Bitboard::FILE_F.0);
 
-/// Check if a square is a protected outpost (on outpost rank, protected by pawn,
-/// cannot be attacked by enemy pawns).
+/// Bad bishop weights as `(middlegame, endgame)` pairs, each applied per
+/// own pawn on the bishop's colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadBishopWeights {
+    /// A pawn an enemy pawn stands directly in front of
+    pub fixed: (i32, i32),
+    /// Any other pawn
+    pub mobile: (i32, i32),
+    /// Extra for a pawn on the c-f files whose push square is occupied
+    pub blocked_center: (i32, i32),
+}
+
+impl BadBishopWeights {
+    /// The weights the evaluation uses
+    pub const DEFAULT: Self = BadBishopWeights {
+        fixed: (BAD_BISHOP_FIXED_MG, BAD_BISHOP_FIXED_EG),
+        mobile: (BAD_BISHOP_MOBILE_MG, BAD_BISHOP_MOBILE_EG),
+        blocked_center: (BAD_BISHOP_BLOCKED_CENTER_MG, BAD_BISHOP_BLOCKED_CENTER_EG),
+    };
+}
+
+impl Default for BadBishopWeights {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Check if a square is a protected outpost: on an outpost rank, protected
+/// by our pawn, and outside `enemy_span`, the squares enemy pawns could ever
+/// attack as they advance.
 fn is_protected_outpost(
     sq: Square,
     color: Color,
     our_pawn_attacks: Bitboard,
-    enemy_pawns: Bitboard,
+    enemy_span: Bitboard,
 ) -> bool {
     let sq_bb = Bitboard::from_square(sq);
-    let color_idx = color.index();
-
-    // Must be on outpost rank
-    if sq_bb.is_disjoint(OUTPOST_RANKS[color_idx]) {
-        return false;
-    }
+    sq_bb.intersects(OUTPOST_RANKS[color.index()])
+        && sq_bb.intersects(our_pawn_attacks)
+        && sq_bb.is_disjoint(enemy_span)
+}
 
-    // Must be protected by our pawn
-    if sq_bb.is_disjoint(our_pawn_attacks) {
-        return false;
+/// Rim penalty `(mg, eg)` for a knight on `sq`, growing toward the edge
+/// and largest in the corners
+fn knight_rim_penalty(sq: Square) -> (i32, i32) {
+    let file_edge = sq.file().min(7 - sq.file());
+    let rank_edge = sq.rank().min(7 - sq.rank());
+    let ring = file_edge.min(rank_edge);
+    let (mut mg, mut eg) = (KNIGHT_RIM_MG[ring], KNIGHT_RIM_EG[ring]);
+    if file_edge == 0 && rank_edge == 0 {
+        mg += KNIGHT_CORNER_MG;
+        eg += KNIGHT_CORNER_EG;
     }
-
-    // Check if can be attacked by enemy pawns on adjacent files
-    // For White outposts: check for Black pawns ABOVE (they attack downward)
-    // For Black outposts: check for White pawns BELOW (they attack upward)
-    let file = sq.file();
-    let adj_files = ADJACENT_FILES[file];
-
-    let can_be_attacked = match color {
-        Color::White => {
-            // Black pawns above this square can attack it (pawns attack diagonally forward)
-            let mask = Bitboard(adj_files.0 & (u64::MAX << (sq.rank() * 8)));
-            enemy_pawns.intersects(mask)
-        }
-        Color::Black => {
-            // White pawns below this square can attack it
-            let mask = Bitboard(adj_files.0 & !(u64::MAX << ((sq.rank() + 1) * 8)));
-            enemy_pawns.intersects(mask)
-        }
-    };
-
-    !can_be_attacked
+    (mg, eg)
 }
 
 impl Board {
@@ -83,12 +99,17 @@ impl Board {
             let sign = color.sign();
 
             let our_pawn_attacks = ctx.pawn_attacks(color);
-            let our_pawns = self.pieces_of(color, Piece::Pawn);
-            let enemy_pawns = self.opponent_pieces(color, Piece::Pawn);
+            let enemy_span = self
+                .opponent_pieces(color, Piece::Pawn)
+                .pawn_attacks_span(color.opponent());
 
-            // Knight outposts
+            // Knight outposts and rim penalties
             for sq in self.pieces_of(color, Piece::Knight).iter() {
-                if is_protected_outpost(sq, color, our_pawn_attacks, enemy_pawns) {
+                let (rim_mg, rim_eg) = knight_rim_penalty(sq);
+                mg += sign * rim_mg;
+                eg += sign * rim_eg;
+
+                if is_protected_outpost(sq, color, our_pawn_attacks, enemy_span) {
                     mg += sign * KNIGHT_OUTPOST_MG;
                     eg += sign * KNIGHT_OUTPOST_EG;
 
@@ -103,33 +124,51 @@ impl Board {
 
             // Bishop outposts (similar logic but smaller bonus)
             for sq in self.pieces_of(color, Piece::Bishop).iter() {
-                if is_protected_outpost(sq, color, our_pawn_attacks, enemy_pawns) {
+                if is_protected_outpost(sq, color, our_pawn_attacks, enemy_span) {
                     mg += sign * BISHOP_OUTPOST_MG;
                     eg += sign * BISHOP_OUTPOST_EG;
                 }
+            }
 
-                // Bad bishop penalty
-                let sq_idx = sq.as_index();
-                let is_light_square = ((sq_idx / 8) + (sq_idx % 8)) % 2 == 1;
+            let (bad_mg, bad_eg) = self.bad_bishop(color, &BadBishopWeights::DEFAULT);
+            mg += sign * bad_mg;
+            eg += sign * bad_eg;
+        }
 
-                // Count our pawns on same color squares
-                let same_color_squares = if is_light_square {
-                    Bitboard::LIGHT_SQUARES
-                } else {
-                    Bitboard::DARK_SQUARES
-                };
+        (mg, eg)
+    }
 
-                let blocked_pawns = our_pawns.intersect_popcount(same_color_squares) as i32;
+    /// Bad bishop penalty of `color`'s bishops with `weights`, e.g.
+    /// candidate weights during tuning.
+    ///
+    /// Own pawns on a bishop's colour take squares from it, most of all
+    /// when an enemy pawn fixes them there, and blocked central pawns keep
+    /// the position closed so the bishop never gets out.
+    #[must_use]
+    pub fn bad_bishop(&self, color: Color, weights: &BadBishopWeights) -> (i32, i32) {
+        let our_pawns = self.pieces_of(color, Piece::Pawn);
+        let behind = |bb: Bitboard| bb.shift_forward(color.opponent());
+        let fixed = Bitboard(our_pawns.0 & behind(self.opponent_pieces(color, Piece::Pawn)).0);
+        let blocked_center = Bitboard(our_pawns.0 & behind(self.all_occupied).0 & CENTRAL_FILES.0);
 
-                // Penalty scales with number of blocking pawns (3+ is bad)
-                if blocked_pawns >= 3 {
-                    let penalty = (blocked_pawns - 2) * BAD_BISHOP_MG;
-                    mg += sign * penalty;
-                    eg += sign * (blocked_pawns - 2) * BAD_BISHOP_EG;
-                }
-            }
+        let mut mg = 0;
+        let mut eg = 0;
+        for sq in self.pieces_of(color, Piece::Bishop).iter() {
+            let squares = if Bitboard::LIGHT_SQUARES.contains(sq) {
+                Bitboard::LIGHT_SQUARES
+            } else {
+                Bitboard::DARK_SQUARES
+            };
+            let fixed_count = fixed.intersect_popcount(squares) as i32;
+            let mobile_count = our_pawns.intersect_popcount(squares) as i32 - fixed_count;
+            let center_count = blocked_center.intersect_popcount(squares) as i32;
+            mg += fixed_count * weights.fixed.0
+                + mobile_count * weights.mobile.0
+                + center_count * weights.blocked_center.0;
+            eg += fixed_count * weights.fixed.1
+                + mobile_count * weights.mobile.1
+                + center_count * weights.blocked_center.1;
         }
-
         (mg, eg)
     }
 }
@@ -169,6 +208,44 @@ mod tests {
         assert_eq!(mg, 0, "attackable knight should have no outpost bonus");
     }
 
+    #[test]
+    fn test_outpost_uses_pawn_attack_span() {
+        // A black pawn beside or behind d5 can never attack it
+        for fen in [
+            "8/8/8/2pN4/2P5/8/8/8 w - - 0 1",
+            "8/8/8/3N4/2P1p3/8/8/8 w - - 0 1",
+        ] {
+            let board: Board = fen.parse().unwrap();
+            let (mg, _) = board.eval_minor_pieces(&board.compute_attack_context());
+            assert!(mg > 0, "{fen}: {mg}");
+        }
+
+        // One still able to advance to c6 or e6 can
+        let board: Board = "8/4p3/8/3N4/2P5/8/8/8 w - - 0 1".parse().unwrap();
+        let (mg, _) = board.eval_minor_pieces(&board.compute_attack_context());
+        assert_eq!(mg, 0);
+
+        // Mirrored for Black
+        let board: Board = "8/8/8/2p5/2Pn4/8/8/8 w - - 0 1".parse().unwrap();
+        let (mg, _) = board.eval_minor_pieces(&board.compute_attack_context());
+        assert!(mg < 0, "{mg}");
+    }
+
+    #[test]
+    fn test_knight_rim_penalty_is_graduated() {
+        let mg = |fen: &str| {
+            let board: Board = fen.parse().unwrap();
+            board.eval_minor_pieces(&board.compute_attack_context()).0
+        };
+        let corner = mg("8/8/8/8/8/8/8/N7 w - - 0 1");
+        let rim = mg("8/8/8/8/N7/8/8/8 w - - 0 1");
+        let near_rim = mg("8/8/8/8/1N6/8/8/8 w - - 0 1");
+        let centre = mg("8/8/8/8/3N4/8/8/8 w - - 0 1");
+        assert!(corner < rim && rim < near_rim && near_rim < centre);
+        assert_eq!(centre, 0);
+        assert_eq!(mg("8/8/8/8/8/8/8/n7 w - - 0 1"), -corner);
+    }
+
     #[test]
     fn test_bad_bishop() {
         // White bishop on c1 (dark square) with many pawns on dark squares
@@ -190,6 +267,42 @@ mod tests {
         assert!(mg >= 0, "good bishop should have non-negative mg: {mg}");
     }
 
+    #[test]
+    fn test_fixed_pawns_make_bishop_worse() {
+        let weights = BadBishopWeights::DEFAULT;
+        // Same pawns on the c1 bishop's colour, mobile and then rammed
+        let mobile: Board = "4k3/8/8/8/3P4/2P5/8/2B1K3 w - - 0 1".parse().unwrap();
+        let rammed: Board = "4k3/8/8/3p4/3P4/2P5/8/2B1K3 w - - 0 1".parse().unwrap();
+        let (mobile_mg, mobile_eg) = mobile.bad_bishop(Color::White, &weights);
+        let (rammed_mg, rammed_eg) = rammed.bad_bishop(Color::White, &weights);
+        assert!(rammed_mg < mobile_mg && rammed_eg < mobile_eg);
+
+        // Pawns on the other colour do not hurt the bishop
+        let other: Board = "4k3/8/8/3p4/4P3/3P4/8/2B1K3 w - - 0 1".parse().unwrap();
+        assert_eq!(other.bad_bishop(Color::White, &weights), (0, 0));
+    }
+
+    #[test]
+    fn test_french_light_bishop_is_bad() {
+        // French Advance with the dark-squared bishops traded: Black's
+        // pawns on e6 and d5 are rammed on light squares, shutting in the
+        // c8 bishop, while White's f1 bishop is free
+        let board: Board = "rnbqk1nr/pp3ppp/4p3/2ppP3/3P4/2P5/PP3PPP/RN1QKBNR b KQkq - 0 6"
+            .parse()
+            .unwrap();
+        let weights = BadBishopWeights::DEFAULT;
+        let (black_mg, _) = board.bad_bishop(Color::Black, &weights);
+        let (white_mg, _) = board.bad_bishop(Color::White, &weights);
+        assert!(black_mg < white_mg, "black {black_mg} white {white_mg}");
+
+        // Tuning can switch the centre term off
+        let no_center = BadBishopWeights {
+            blocked_center: (0, 0),
+            ..weights
+        };
+        assert!(board.bad_bishop(Color::Black, &no_center).0 > black_mg);
+    }
+
     #[test]
     fn test_central_outpost_bonus() {
         // Knight on d5 (central file) vs knight on a5 (edge file)
@@ -15,7 +15,7 @@
 //! - Space control
 //! - Advanced threats (forks, pins, skewers)
 //! - Piece quality (active/passive, trapped)
-//! - Imbalances (knight vs bishop, etc.)
+//! - Material imbalance table
 //! - Initiative
 
 mod combined;
@@ -41,6 +41,10 @@ mod threats_advanced;
 mod tropism;
 mod weak_squares;
 
+pub use imbalances::{ImbalanceTable, IMBALANCE_TERMS};
+pub use minor_pieces::BadBishopWeights;
+pub use space_control::SpaceWeights;
+
 #[cfg(test)]
 mod tests {
     use crate::board::state::Board;
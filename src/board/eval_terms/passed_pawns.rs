@@ -1,9 +1,11 @@
 //! Passed pawn evaluation.
 //!
-//! Evaluates passed pawns with bonuses based on advancement and control of stop square.
+//! Evaluates passed pawns with bonuses based on advancement and control of stop square,
+//! king proximity to the stop square, the piece blockading it, and passers the enemy
+//! king cannot catch in pawn endgames.
 
 use crate::board::masks::{
-    fill_backward, relative_rank, FILES, PASSED_PAWN_BONUS_EG, PASSED_PAWN_BONUS_MG,
+    fill_backward, fill_forward, relative_rank, FILES, PASSED_PAWN_BONUS_EG, PASSED_PAWN_BONUS_MG,
     PASSED_PAWN_MASK,
 };
 use crate::board::state::Board;
@@ -17,6 +19,25 @@ const PASSER_MULTIPLIER_BASE: i32 = 100;
 const PASSER_CONTROL_BONUS: i32 = 33;
 const PASSER_BLOCKED_PENALTY: i32 = 15;
 
+/// Multiplier penalty by the enemy piece blockading the stop square. Knights
+/// and kings blockade best; a queen is a poor blockader.
+const BLOCKADER_PENALTY: [i32; 6] = [15, 35, 25, 20, 10, 30];
+
+/// Weight of the king proximity term by relative rank: only advanced
+/// passers race the kings
+const PASSER_KING_WEIGHT: [i32; 8] = [0, 0, 0, 1, 2, 4, 6, 0];
+
+/// King distance to the stop square is capped at this
+const PASSER_KING_DISTANCE_CAP: i32 = 5;
+
+/// Per-distance factors for the enemy and own king in the proximity term
+const PASSER_ENEMY_KING_FACTOR: i32 = 5;
+const PASSER_OWN_KING_FACTOR: i32 = 2;
+
+/// Endgame bonus for a passer the enemy king cannot catch when the enemy
+/// has only king and pawns
+pub(super) const UNSTOPPABLE_PASSER_EG: i32 = 400;
+
 impl Board {
     /// Check if a pawn at the given square is a passed pawn.
     /// A passed pawn has no enemy pawns ahead of it or on adjacent files.
@@ -70,8 +91,12 @@ impl Board {
                 if (stop_bb.0 & their_attacks.0) != 0 {
                     multiplier -= PASSER_CONTROL_BONUS;
                 }
-                if (stop_bb.0 & self.all_occupied.0) != 0 {
-                    multiplier -= PASSER_BLOCKED_PENALTY;
+                match self.piece_at(stop_sq) {
+                    Some((owner, piece)) if owner != color => {
+                        multiplier -= BLOCKADER_PENALTY[piece.index()];
+                    }
+                    Some(_) => multiplier -= PASSER_BLOCKED_PENALTY,
+                    None => {}
                 }
 
                 let base_mg = PASSED_PAWN_BONUS_MG[rel_rank];
@@ -80,6 +105,30 @@ impl Board {
                 mg += sign * (base_mg * multiplier / PASSER_MULTIPLIER_BASE);
                 eg += sign * (base_eg * multiplier / PASSER_MULTIPLIER_BASE);
 
+                if let (Some(own_king), Some(enemy_king)) =
+                    (self.find_king(color), self.find_king(color.opponent()))
+                {
+                    // King proximity: the enemy king wants to reach the stop
+                    // square, ours to escort the pawn
+                    let enemy_dist = enemy_king
+                        .chebyshev_distance(stop_sq)
+                        .min(PASSER_KING_DISTANCE_CAP);
+                    let own_dist = own_king
+                        .chebyshev_distance(stop_sq)
+                        .min(PASSER_KING_DISTANCE_CAP);
+                    eg += sign
+                        * PASSER_KING_WEIGHT[rel_rank]
+                        * (enemy_dist * PASSER_ENEMY_KING_FACTOR
+                            - own_dist * PASSER_OWN_KING_FACTOR);
+
+                    if self
+                        .unstoppable_passer_moves(sq, color, enemy_king)
+                        .is_some()
+                    {
+                        eg += sign * UNSTOPPABLE_PASSER_EG;
+                    }
+                }
+
                 // Rook behind passed pawn bonus
                 let file = sq.file();
                 let file_mask = FILES[file];
@@ -105,6 +154,37 @@ impl Board {
 
         (mg, eg)
     }
+
+    /// Moves the passer on `sq` needs to promote, if it gets there before
+    /// `enemy_king` can catch it (the rule of the square), with the enemy
+    /// reduced to king and pawns and nothing standing on the pawn's path.
+    pub(super) fn unstoppable_passer_moves(
+        &self,
+        sq: Square,
+        color: Color,
+        enemy_king: Square,
+    ) -> Option<i32> {
+        let enemy = color.opponent();
+        let enemy_pieces = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+            .iter()
+            .any(|&piece| !self.pieces_of(enemy, piece).is_empty());
+        if enemy_pieces {
+            return None;
+        }
+
+        let path =
+            Bitboard(fill_forward(Bitboard::from_square(sq), color).0 & !(1u64 << sq.index()));
+        if path.intersects(self.all_occupied) {
+            return None;
+        }
+
+        let promotion_sq = Square::new(relative_rank(7, color), sq.file());
+        // A pawn on its starting rank moves two squares at once
+        let pawn_moves = (7 - relative_rank(sq.rank(), color)).min(5) as i32;
+        let king_moves =
+            enemy_king.chebyshev_distance(promotion_sq) - i32::from(self.side_to_move() == enemy);
+        (king_moves > pawn_moves).then_some(pawn_moves)
+    }
 }
 
 #[cfg(test)]
@@ -154,6 +234,64 @@ mod tests {
         assert_eq!(eg, 0, "no passed pawns in starting position");
     }
 
+    #[test]
+    fn test_passer_king_proximity() {
+        let eg = |fen: &str| Board::from_fen(fen).eval_passed_pawns().1;
+
+        // The enemy king one square further from the stop square e7
+        let near = eg("2k5/8/4P3/3K4/8/8/8/8 b - - 0 1");
+        let far = eg("1k6/8/4P3/3K4/8/8/8/8 b - - 0 1");
+        assert_eq!(far - near, PASSER_KING_WEIGHT[5] * PASSER_ENEMY_KING_FACTOR);
+
+        // Our own king escorting rather than far away (capped distance)
+        let escort = eg("2k5/8/4P3/3K4/8/8/8/8 b - - 0 1");
+        let distant = eg("2k5/8/4P3/8/8/8/8/K7 b - - 0 1");
+        assert_eq!(
+            escort - distant,
+            PASSER_KING_WEIGHT[5] * PASSER_OWN_KING_FACTOR * (PASSER_KING_DISTANCE_CAP - 2)
+        );
+    }
+
+    #[test]
+    fn test_knight_blockades_better_than_queen() {
+        let knight: Board = "8/8/4n3/4P3/8/8/8/8 w - - 0 1".parse().unwrap();
+        let queen: Board = "8/8/4q3/4P3/8/8/8/8 w - - 0 1".parse().unwrap();
+        assert!(knight.eval_passed_pawns().1 < queen.eval_passed_pawns().1);
+    }
+
+    #[test]
+    fn test_unstoppable_passer() {
+        let eg = |fen: &str| Board::from_fen(fen).eval_passed_pawns().1;
+
+        // The king on h8 is outside the square of the a5 pawn
+        let unstoppable = eg("7k/8/8/P7/8/8/8/K7 w - - 0 1");
+        let knight_defends = eg("7k/8/8/P7/8/8/8/K6n w - - 0 1");
+        assert_eq!(unstoppable - knight_defends, UNSTOPPABLE_PASSER_EG);
+
+        // On the edge of the square, the side to move decides the race
+        let board = Board::from_fen("4k3/8/8/P7/8/8/8/K7 w - - 0 1");
+        let e8 = board.find_king(Color::Black).unwrap();
+        let a5 = Square::new(4, 0);
+        assert_eq!(
+            board.unstoppable_passer_moves(a5, Color::White, e8),
+            Some(3)
+        );
+        let board = Board::from_fen("4k3/8/8/P7/8/8/8/K7 b - - 0 1");
+        assert_eq!(board.unstoppable_passer_moves(a5, Color::White, e8), None);
+
+        // A pawn on its starting rank double-pushes
+        let board = Board::from_fen("6k1/8/8/8/8/8/P7/K7 w - - 0 1");
+        let g8 = board.find_king(Color::Black).unwrap();
+        assert_eq!(
+            board.unstoppable_passer_moves(Square::new(1, 0), Color::White, g8),
+            Some(5)
+        );
+
+        // Mirrored for Black
+        let flipped = Board::from_fen("7k/8/8/P7/8/8/8/K7 w - - 0 1").color_flipped();
+        assert_eq!(flipped.eval_passed_pawns().1, -unstoppable);
+    }
+
     #[test]
     fn test_rook_behind_passer() {
         // White passed pawn on e5 with rook behind on e1
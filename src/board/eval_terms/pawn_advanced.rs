@@ -1,12 +1,12 @@
 //! Advanced pawn evaluation terms.
 //!
 //! Implements:
-//! - Pawn storm threat (pawns advancing toward enemy king)
+//! - Pawn storm threat (pawns advancing toward an enemy king castled on the
+//!   other wing)
 //! - Pawn levers (pawns that can capture to open lines)
-//! - Candidate passers (pawns that can become passed with one push)
 //! - Pawn chains (connected diagonal pawn structures)
 
-use crate::board::masks::{relative_rank, PASSED_PAWN_MASK};
+use crate::board::masks::relative_rank;
 use crate::board::state::Board;
 use crate::board::types::{Bitboard, Color, Piece};
 
@@ -16,9 +16,9 @@ use super::helpers::single_pawn_attacks;
 /// Higher bonus for pawns closer to enemy king
 pub const PAWN_STORM_BONUS: [i32; 8] = [0, 0, 0, 5, 10, 20, 0, 0];
 
-/// Candidate passer bonus (can become passed with one push)
-pub const CANDIDATE_PASSER_MG: i32 = 8;
-pub const CANDIDATE_PASSER_EG: i32 = 12;
+/// Extra storm bonus by rank for a rook-file pawn, whose exchange opens the
+/// edge file for a rook lift against the king
+pub const EDGE_STORM_BONUS: [i32; 8] = [0, 0, 0, 4, 8, 12, 0, 0];
 
 /// Pawn chain bonus per chain link
 pub const PAWN_CHAIN_MG: i32 = 3;
@@ -75,27 +75,9 @@ impl Board {
 
         let enemy_king_file = enemy_king_sq % 8;
 
-        // Pawn storm - evaluate pawns on files near enemy king
-        for pawn_sq in own_pawns.iter() {
-            let pawn_file = pawn_sq.file();
-            let pawn_rank = pawn_sq.rank();
-            let rel_rank = relative_rank(pawn_rank, color);
-
-            // Check if pawn is on files near enemy king (within 2 files)
-            let file_distance = (pawn_file as i32 - enemy_king_file as i32).abs();
-            if file_distance <= 2 {
-                // Pawn storm bonus
-                mg += PAWN_STORM_BONUS[rel_rank];
-            }
-
-            // Candidate passer detection
-            // A pawn is a candidate passer if it can become passed with one push
-            // This means: no enemy pawn directly ahead, and we can push past blockers
-            if self.is_candidate_passer(pawn_sq.index(), own_pawns, enemy_pawns, color) {
-                mg += CANDIDATE_PASSER_MG;
-                eg += CANDIDATE_PASSER_EG;
-            }
+        mg += self.pawn_storm(own_pawns, color);
 
+        for pawn_sq in own_pawns.iter() {
             // Pawn lever detection
             // A lever is a pawn that can capture to open a file
             if Self::is_pawn_lever(pawn_sq.index(), enemy_pawns, enemy_king_file, color) {
@@ -111,50 +93,34 @@ impl Board {
         (mg, eg)
     }
 
-    /// Check if a pawn is a candidate passer
-    fn is_candidate_passer(
-        &self,
-        pawn_sq: usize,
-        _own_pawns: Bitboard,
-        enemy_pawns: Bitboard,
-        color: Color,
-    ) -> bool {
-        let rank = pawn_sq / 8;
-
-        // Already a passed pawn? Not a candidate
-        let passed_mask = PASSED_PAWN_MASK[color.index()][pawn_sq];
-        if (enemy_pawns.0 & passed_mask.0) == 0 {
-            return false; // Already passed
+    /// Storm bonus for `color`'s pawns advancing on the enemy king.
+    ///
+    /// Only counts when the kings are castled on opposite wings: then the
+    /// attacker's pawns can advance without exposing their own king, and
+    /// the race decides the game. Pushing the pawns in front of one's own
+    /// king (both kings on the same wing) is not rewarded, nor is advancing
+    /// against a king still in the centre.
+    fn pawn_storm(&self, own_pawns: Bitboard, color: Color) -> i32 {
+        let own_wing = king_wing(self.king_square_index(color) % 8);
+        let enemy_king_file = self.king_square_index(color.opponent()) % 8;
+        let enemy_wing = king_wing(enemy_king_file);
+        if own_wing.is_none() || enemy_wing.is_none() || own_wing == enemy_wing {
+            return 0;
         }
 
-        // Check if pushing one square would make it passed or give it a clear path
-        let push_sq = match color {
-            Color::White => {
-                if rank >= 6 {
-                    return false;
-                }
-                pawn_sq + 8
+        let mut storm = 0;
+        for pawn_sq in own_pawns.iter() {
+            let file = pawn_sq.file();
+            if file.abs_diff(enemy_king_file) > 2 {
+                continue;
             }
-            Color::Black => {
-                if rank <= 1 {
-                    return false;
-                }
-                pawn_sq - 8
+            let rel_rank = relative_rank(pawn_sq.rank(), color);
+            storm += PAWN_STORM_BONUS[rel_rank];
+            if file == 0 || file == 7 {
+                storm += EDGE_STORM_BONUS[rel_rank];
             }
-        };
-
-        // Is the push square blocked?
-        if (self.all_occupied.0 & (1u64 << push_sq)) != 0 {
-            return false;
         }
-
-        // After pushing, would it have fewer blockers?
-        let new_passed_mask = PASSED_PAWN_MASK[color.index()][push_sq];
-        let current_blockers = (enemy_pawns.0 & passed_mask.0).count_ones();
-        let new_blockers = (enemy_pawns.0 & new_passed_mask.0).count_ones();
-
-        // Candidate if pushing reduces blockers significantly or leaves just one
-        new_blockers < current_blockers && new_blockers <= 1
+        storm
     }
 
     /// Check if a pawn is a lever that can open lines toward enemy king
@@ -202,6 +168,16 @@ impl Board {
     }
 }
 
+/// Wing of a king on `file`: `Some(false)` for the queenside (a-c files),
+/// `Some(true)` for the kingside (f-h files), `None` in the centre
+fn king_wing(file: usize) -> Option<bool> {
+    match file {
+        0..=2 => Some(false),
+        5..=7 => Some(true),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,6 +193,49 @@ mod tests {
         let _ = (mg, eg); // Use both values to avoid warnings
     }
 
+    /// Storm bonus of `color` in `fen`
+    fn storm(fen: &str, color: Color) -> i32 {
+        let board: Board = fen.parse().unwrap();
+        board.pawn_storm(board.pieces_of(color, Piece::Pawn), color)
+    }
+
+    #[test]
+    fn test_storm_needs_opposite_wings() {
+        // Yugoslav Attack against the Dragon: O-O-O, then g4 and h4
+        let dragon = "r1bq1rk1/pp2ppbp/2np1np1/8/3NP1PP/2N1BP2/PPPQ4/2KR1B1R w - - 0 11";
+        assert!(storm(dragon, Color::White) > 0);
+        // Black's queenside pawns have not moved yet
+        assert_eq!(storm(dragon, Color::Black), 0);
+
+        // The same pawns in front of White's own castled king
+        let same_wing = "r1bq1rk1/pp2ppbp/2np1np1/8/3NP1PP/2N1BP2/PPPQ4/3R1BKR w - - 0 11";
+        assert_eq!(storm(same_wing, Color::White), 0);
+
+        // King's Indian, Mar del Plata: ...f5 and ...g4 with both kings
+        // castled short weaken Black's own king as much as White's
+        let kid = "r1bq1rk1/pppnn1bp/3p4/3Ppp2/2P1P1p1/2N5/PP1NBPPP/R1BQ1RK1 w - - 0 12";
+        assert_eq!(storm(kid, Color::Black), 0);
+        assert_eq!(storm(kid, Color::White), 0);
+
+        // No storm against an uncastled king
+        let centre = "r1bqkb1r/pp2pp1p/2np1np1/8/3NP1PP/2N1BP2/PPPQ4/2KR1B1R w kq - 0 9";
+        assert_eq!(storm(centre, Color::White), 0);
+    }
+
+    #[test]
+    fn test_edge_file_storm() {
+        // An h-pawn storm opens the h-file; the same advance on the f-file
+        // is worth less
+        let h_pawn = storm("6k1/8/8/7P/8/8/8/2K5 w - - 0 1", Color::White);
+        let f_pawn = storm("6k1/8/8/5P2/8/8/8/2K5 w - - 0 1", Color::White);
+        assert_eq!(f_pawn, PAWN_STORM_BONUS[4]);
+        assert_eq!(h_pawn, PAWN_STORM_BONUS[4] + EDGE_STORM_BONUS[4]);
+
+        // Black's a-pawn against a king castled long
+        let a_pawn = storm("6k1/8/8/8/p7/8/8/1K6 b - - 0 1", Color::Black);
+        assert_eq!(a_pawn, PAWN_STORM_BONUS[4] + EDGE_STORM_BONUS[4]);
+    }
+
     #[test]
     fn test_chain_links() {
         // Classic pawn chain d4-e5
@@ -244,22 +263,6 @@ mod tests {
         assert!(eg.abs() < 20, "starting position pawn advanced eg: {eg}");
     }
 
-    #[test]
-    fn test_candidate_passer() {
-        // e5 pawn with d6 blocker - could become passer with exd6
-        let board: Board = "8/8/3p4/4P3/8/8/8/8 w - - 0 1".parse().unwrap();
-        let (mg, eg) = board.eval_pawn_advanced();
-        // Just verify function runs
-        assert!(
-            (-100..=100).contains(&mg),
-            "candidate passer mg reasonable: {mg}"
-        );
-        assert!(
-            (-100..=100).contains(&eg),
-            "candidate passer eg reasonable: {eg}"
-        );
-    }
-
     #[test]
     fn test_pawn_lever() {
         // White pawn that can capture to open a file
@@ -1,23 +1,32 @@
 //! Pawn structure evaluation.
 //!
-//! Evaluates doubled, isolated, backward pawns and phalanx/defended pawns.
+//! Evaluates doubled, isolated, backward pawns and phalanx/defended pawns,
+//! candidate passers and wing pawn majorities.
 //! Supports caching via pawn hash table for improved performance.
 
 #![allow(clippy::needless_range_loop)] // 0..2 for color index is clearer
 
 use crate::board::masks::{
-    fill_backward, fill_forward, relative_rank, ADJACENT_FILES, PAWN_SUPPORT_MASK,
+    fill_backward, fill_forward, relative_rank, ADJACENT_FILES, FILES, PASSED_PAWN_MASK,
+    PAWN_SUPPORT_MASK,
 };
 use crate::board::state::Board;
 use crate::board::types::{Bitboard, Color, Piece};
 use crate::pawn_hash::PawnHashTable;
 
 use super::tables::{
-    BACKWARD_OPEN_EG, BACKWARD_OPEN_MG, BACKWARD_PAWN_EG, BACKWARD_PAWN_MG, DEFENDED_BONUS_EG,
-    DEFENDED_BONUS_MG, DOUBLED_PAWN_EG, DOUBLED_PAWN_MG, ISOLATED_OPEN_EG, ISOLATED_OPEN_MG,
-    ISOLATED_PAWN_EG, ISOLATED_PAWN_MG, PHALANX_BONUS_EG, PHALANX_BONUS_MG,
+    BACKWARD_OPEN_EG, BACKWARD_OPEN_MG, BACKWARD_PAWN_EG, BACKWARD_PAWN_MG, CANDIDATE_PASSER_EG,
+    CANDIDATE_PASSER_MG, DEFENDED_BONUS_EG, DEFENDED_BONUS_MG, DOUBLED_PAWN_EG, DOUBLED_PAWN_MG,
+    ISOLATED_OPEN_EG, ISOLATED_OPEN_MG, ISOLATED_PAWN_EG, ISOLATED_PAWN_MG, PAWN_MAJORITY_EG,
+    PAWN_MAJORITY_MG, PHALANX_BONUS_EG, PHALANX_BONUS_MG,
 };
 
+/// The queenside (a-d) and kingside (e-h) halves for pawn majorities
+const WINGS: [Bitboard; 2] = [
+    Bitboard(0x0F0F_0F0F_0F0F_0F0F),
+    Bitboard(0xF0F0_F0F0_F0F0_F0F0),
+];
+
 impl Board {
     /// Evaluate pawn structure.
     /// Returns `(middlegame_score, endgame_score)` from white's perspective.
@@ -96,7 +105,16 @@ impl Board {
                         }
                     }
                 }
+
+                if is_candidate_passer(sq.as_index(), color, own_pawns, enemy_pawns) {
+                    mg += sign * CANDIDATE_PASSER_MG[rel_rank];
+                    eg += sign * CANDIDATE_PASSER_EG[rel_rank];
+                }
             }
+
+            let majority = pawn_majority(own_pawns, enemy_pawns);
+            mg += sign * majority * PAWN_MAJORITY_MG;
+            eg += sign * majority * PAWN_MAJORITY_EG;
         }
 
         (mg, eg)
@@ -122,6 +140,55 @@ impl Board {
     }
 }
 
+/// Whether the pawn on `sq` is a candidate passer: not yet passed, with no
+/// pawn ahead on its own file, and at least as many own pawns beside or
+/// behind it on the adjacent files (helpers) as enemy pawns ahead on them
+/// (sentries), so exchanges can leave it passed.
+fn is_candidate_passer(
+    sq: usize,
+    color: Color,
+    own_pawns: Bitboard,
+    enemy_pawns: Bitboard,
+) -> bool {
+    let pass_mask = PASSED_PAWN_MASK[color.index()][sq];
+    let sentries = pass_mask.0 & enemy_pawns.0;
+    if sentries == 0 {
+        return false;
+    }
+
+    let pawn = Bitboard(1u64 << sq);
+    let ahead = fill_forward(pawn, color).0 & !pawn.0;
+    if ahead & (own_pawns.0 | enemy_pawns.0) != 0 {
+        return false;
+    }
+
+    let adjacent = ADJACENT_FILES[sq % 8].0;
+    let level_or_behind = fill_backward(Bitboard::rank_mask(sq / 8), color).0;
+    let helpers = adjacent & level_or_behind & own_pawns.0;
+    helpers.count_ones() >= sentries.count_ones()
+}
+
+/// Number of extra pawns in `own_pawns`' wing majorities. Doubled pawns
+/// cannot make a passer on their own, so own pawns count once per file; a
+/// wing without enemy pawns holds passers already and is left to the passed
+/// pawn terms.
+fn pawn_majority(own_pawns: Bitboard, enemy_pawns: Bitboard) -> i32 {
+    WINGS
+        .iter()
+        .map(|wing| {
+            let own_files = FILES
+                .iter()
+                .filter(|file| file.0 & wing.0 & own_pawns.0 != 0)
+                .count();
+            let enemy = (wing.0 & enemy_pawns.0).count_ones() as usize;
+            if enemy == 0 {
+                return 0;
+            }
+            own_files.saturating_sub(enemy) as i32
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,6 +241,49 @@ mod tests {
         assert!(mg >= 0, "defended pawn should have non-negative mg: {mg}");
     }
 
+    #[test]
+    fn test_candidate_passer() {
+        let white_c5 = |fen: &str| {
+            let board: Board = fen.parse().unwrap();
+            is_candidate_passer(
+                34,
+                Color::White,
+                board.pieces_of(Color::White, Piece::Pawn),
+                board.pieces_of(Color::Black, Piece::Pawn),
+            )
+        };
+        // c5 with b4 to help against the lone b6 sentry
+        assert!(white_c5("8/8/1p6/2P5/1P6/8/8/8 w - - 0 1"));
+        // Two sentries, one helper
+        assert!(!white_c5("8/8/1p1p4/2P5/1P6/8/8/8 w - - 0 1"));
+        // Blocked on its own file
+        assert!(!white_c5("8/2p5/1p6/2P5/1P6/8/8/8 w - - 0 1"));
+        // Already passed
+        assert!(!white_c5("8/8/8/2P5/1P6/8/8/8 w - - 0 1"));
+    }
+
+    #[test]
+    fn test_queenside_majority() {
+        // Three against two on the queenside, even on the kingside
+        let board: Board = "8/pp3ppp/8/8/8/8/PPP2PPP/8 w - - 0 1".parse().unwrap();
+        let white = board.pieces_of(Color::White, Piece::Pawn);
+        let black = board.pieces_of(Color::Black, Piece::Pawn);
+        assert_eq!(pawn_majority(white, black), 1);
+        assert_eq!(pawn_majority(black, white), 0);
+
+        let (_, eg) = board.eval_pawn_structure();
+        assert!(eg > 0, "queenside majority should favour White: {eg}");
+    }
+
+    #[test]
+    fn test_doubled_majority_is_crippled() {
+        // a2, b2, b3 against a7, b7: the doubled pawn makes no passer
+        let own = Bitboard((1 << 8) | (1 << 9) | (1 << 17));
+        let enemy = Bitboard((1 << 48) | (1 << 49));
+        assert_eq!(pawn_majority(own, enemy), 0);
+        assert_eq!(pawn_majority(enemy, own), 0);
+    }
+
     #[test]
     fn test_backward_pawn_penalty() {
         // White pawn on e3 is backward (d4 and f4 are ahead)
@@ -4,8 +4,10 @@
 //! - Active vs passive pieces (pieces with few safe moves)
 //! - Trapped piece detection (pieces with 0-1 safe squares)
 //! - Piece harmony (pieces supporting each other's activity)
+//! - Early queen sorties and trapped queens
 
 use crate::board::attack_tables::{slider_attacks, KNIGHT_ATTACKS};
+use crate::board::masks::relative_rank;
 use crate::board::state::Board;
 use crate::board::types::{Bitboard, Color, Piece};
 
@@ -26,6 +28,22 @@ pub const ACTIVE_PIECE_EG: i32 = 3;
 /// Piece harmony bonus (pieces defending each other while both active)
 pub const PIECE_HARMONY_MG: i32 = 4;
 
+/// Early queen sortie penalty per minor piece still on its starting square,
+/// once at least `EARLY_QUEEN_MIN_UNDEVELOPED` of them are
+pub const EARLY_QUEEN_MG: i32 = -4;
+pub const EARLY_QUEEN_MIN_UNDEVELOPED: u32 = 2;
+
+/// Extra penalty when an early queen is attacked by a minor piece: the
+/// opponent develops with tempo
+pub const EARLY_QUEEN_HARASSED_MG: i32 = -12;
+
+/// Queen with at most one square not attacked by an enemy pawn, minor or rook
+pub const TRAPPED_QUEEN_MG: i32 = -40;
+pub const TRAPPED_QUEEN_EG: i32 = -25;
+
+/// Starting squares of White's knights and bishops
+const WHITE_MINOR_HOMES: u64 = 0x66;
+
 impl Board {
     /// Evaluate piece quality.
     ///
@@ -105,6 +123,10 @@ impl Board {
             eg += piece_eg;
         }
 
+        let (queen_mg, queen_eg) = self.eval_queen_placement(color);
+        mg += queen_mg;
+        eg += queen_eg;
+
         // Piece harmony
         mg += self.eval_piece_harmony(color, ctx);
 
@@ -137,6 +159,58 @@ impl Board {
         (0, 0)
     }
 
+    /// Evaluate early queen sorties and trapped queens for `color`.
+    fn eval_queen_placement(&self, color: Color) -> (i32, i32) {
+        let queens = self.pieces_of(color, Piece::Queen);
+        if queens.is_empty() {
+            return (0, 0);
+        }
+
+        let enemy = color.opponent();
+        let mut minor_attacks = 0u64;
+        for sq in self.pieces_of(enemy, Piece::Knight).iter() {
+            minor_attacks |= KNIGHT_ATTACKS[sq.index()];
+        }
+        for sq in self.pieces_of(enemy, Piece::Bishop).iter() {
+            minor_attacks |= slider_attacks(sq.index(), self.all_occupied.0, true);
+        }
+        let mut lesser_attacks = minor_attacks | self.pawn_attacks(enemy).0;
+        for sq in self.pieces_of(enemy, Piece::Rook).iter() {
+            lesser_attacks |= slider_attacks(sq.index(), self.all_occupied.0, false);
+        }
+
+        let minor_homes = match color {
+            Color::White => WHITE_MINOR_HOMES,
+            Color::Black => WHITE_MINOR_HOMES << 56,
+        };
+        let minors =
+            self.pieces_of(color, Piece::Knight).0 | self.pieces_of(color, Piece::Bishop).0;
+        let undeveloped = (minors & minor_homes).count_ones();
+        let own_pieces = self.occupied_by(color).0;
+
+        let mut mg = 0;
+        let mut eg = 0;
+        for sq in queens.iter() {
+            // Out past its second rank before the minors
+            let relative_rank = relative_rank(sq.rank(), color);
+            if relative_rank >= 2 && undeveloped >= EARLY_QUEEN_MIN_UNDEVELOPED {
+                mg += EARLY_QUEEN_MG * undeveloped as i32;
+                if minor_attacks & (1u64 << sq.index()) != 0 {
+                    mg += EARLY_QUEEN_HARASSED_MG;
+                }
+            }
+
+            let attacks = slider_attacks(sq.index(), self.all_occupied.0, true)
+                | slider_attacks(sq.index(), self.all_occupied.0, false);
+            if (attacks & !own_pieces & !lesser_attacks).count_ones() <= 1 {
+                mg += TRAPPED_QUEEN_MG;
+                eg += TRAPPED_QUEEN_EG;
+            }
+        }
+
+        (mg, eg)
+    }
+
     /// Evaluate piece harmony (pieces supporting each other while both active).
     fn eval_piece_harmony(&self, color: Color, ctx: &AttackContext) -> i32 {
         let own_attacks = ctx.all_attacks(color);
@@ -226,6 +300,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_early_queen_harassed_by_knight() {
+        // 1. e4 e5 2. Qh5 Nf6: the queen is out with four minors at home
+        let board: Board = "rnbqkb1r/pppp1ppp/5n2/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            board.eval_queen_placement(Color::White),
+            (4 * EARLY_QUEEN_MG + EARLY_QUEEN_HARASSED_MG, 0)
+        );
+
+        let board: Board = "rnbqkb1r/pppp1ppp/5n2/4p3/4P3/8/PPPPQPPP/RNB1KBNR w KQkq - 2 3"
+            .parse()
+            .unwrap();
+        assert_eq!(board.eval_queen_placement(Color::White), (0, 0));
+    }
+
+    #[test]
+    fn test_trapped_queen() {
+        // Every square the queen on a8 reaches is covered by a lesser piece
+        let board: Board = "Qnr1k3/p7/1b1np3/3p4/8/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            board.eval_queen_placement(Color::White),
+            (TRAPPED_QUEEN_MG, TRAPPED_QUEEN_EG)
+        );
+
+        let board: Board = "4k3/8/8/8/3Q4/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(board.eval_queen_placement(Color::White), (0, 0));
+
+        let flipped = Board::from_fen("Qnr1k3/p7/1b1np3/3p4/8/8/8/4K3 w - - 0 1").color_flipped();
+        assert_eq!(
+            flipped.eval_queen_placement(Color::Black),
+            (TRAPPED_QUEEN_MG, TRAPPED_QUEEN_EG)
+        );
+    }
+
     #[test]
     fn test_rook_activity() {
         // Rook on open file
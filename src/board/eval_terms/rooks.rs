@@ -1,16 +1,19 @@
 //! Rook activity evaluation.
 //!
-//! Evaluates rook placement on open files, 7th rank, and trapped rooks.
+//! Evaluates rook placement on open files, 7th rank, trapped rooks,
+//! connected rooks and rook lifts toward the enemy king.
 
-use crate::board::masks::{FILES, RANK_7TH};
+use crate::board::masks::{ADJACENT_FILES, FILES, RANK_7TH};
 use crate::board::state::Board;
 use crate::board::types::{Bitboard, Color, Piece};
 
 use crate::board::attack_tables::slider_attacks;
 
 use super::tables::{
-    CONNECTED_ROOKS_EG, CONNECTED_ROOKS_MG, ROOK_7TH_EG, ROOK_7TH_MG, ROOK_OPEN_FILE_EG,
-    ROOK_OPEN_FILE_MG, ROOK_SEMI_OPEN_EG, ROOK_SEMI_OPEN_MG, TRAPPED_ROOK_MG,
+    CONNECTED_ROOKS_BACK_RANK_MG, CONNECTED_ROOKS_EG, CONNECTED_ROOKS_MG,
+    CONNECTED_ROOKS_OPEN_FILE_EG, CONNECTED_ROOKS_OPEN_FILE_MG, ROOK_7TH_EG, ROOK_7TH_MG,
+    ROOK_LIFT_MG, ROOK_OPEN_FILE_EG, ROOK_OPEN_FILE_MG, ROOK_SEMI_OPEN_EG, ROOK_SEMI_OPEN_MG,
+    TRAPPED_ROOK_MG,
 };
 
 // File indices for trapped rook detection
@@ -22,7 +25,7 @@ const FILE_G: usize = 6;
 const FILE_H: usize = 7;
 
 impl Board {
-    /// Evaluate rook activity (open files, 7th rank).
+    /// Evaluate rook activity (open files, 7th rank, connected rooks, lifts).
     /// Returns `(middlegame_score, endgame_score)` from white's perspective.
     #[must_use]
     pub fn eval_rooks(&self) -> (i32, i32) {
@@ -33,6 +36,12 @@ impl Board {
             let sign = color.sign();
             let our_pawns = self.pieces_of(color, Piece::Pawn);
             let enemy_pawns = self.opponent_pieces(color, Piece::Pawn);
+            let all_pawns = Bitboard(our_pawns.0 | enemy_pawns.0);
+
+            // Files a lifted rook can swing onto to join an attack on the king
+            let enemy_king_file = self.king_square_index(color.opponent()) % 8;
+            let lift_files = Bitboard(FILES[enemy_king_file].0 | ADJACENT_FILES[enemy_king_file].0);
+            let lift_rank = Bitboard::rank_mask(if color == Color::White { 2 } else { 5 });
 
             for sq_idx in self.pieces_of(color, Piece::Rook).iter() {
                 let sq = sq_idx;
@@ -61,6 +70,15 @@ impl Board {
                     mg += sign * ROOK_7TH_MG;
                     eg += sign * ROOK_7TH_EG;
                 }
+
+                // Rook lift: on the third rank with the way along it clear
+                if lift_rank.contains(sq) {
+                    let along_rank =
+                        slider_attacks(sq.as_index(), self.all_occupied.0, false) & lift_rank.0;
+                    if Bitboard(along_rank | (1u64 << sq.as_index())).intersects(lift_files) {
+                        mg += sign * ROOK_LIFT_MG;
+                    }
+                }
             }
 
             // Trapped rook penalty
@@ -107,9 +125,18 @@ impl Board {
                 for i in 0..n {
                     for j in (i + 1)..n {
                         let rook_attacks = slider_attacks(rook_sqs[i], self.all_occupied.0, false);
-                        if (rook_attacks & (1u64 << rook_sqs[j])) != 0 {
-                            mg += sign * CONNECTED_ROOKS_MG;
-                            eg += sign * CONNECTED_ROOKS_EG;
+                        if (rook_attacks & (1u64 << rook_sqs[j])) == 0 {
+                            continue;
+                        }
+                        mg += sign * CONNECTED_ROOKS_MG;
+                        eg += sign * CONNECTED_ROOKS_EG;
+
+                        let (a, b) = (rook_sqs[i], rook_sqs[j]);
+                        if a / 8 == back_rank && b / 8 == back_rank {
+                            mg += sign * CONNECTED_ROOKS_BACK_RANK_MG;
+                        } else if a % 8 == b % 8 && FILES[a % 8].is_disjoint(all_pawns) {
+                            mg += sign * CONNECTED_ROOKS_OPEN_FILE_MG;
+                            eg += sign * CONNECTED_ROOKS_OPEN_FILE_EG;
                         }
                     }
                 }
@@ -169,6 +196,43 @@ mod tests {
         assert!(eg1 > eg2, "connected rooks should have higher eg bonus");
     }
 
+    #[test]
+    fn test_connected_rooks_on_back_rank() {
+        let back_rank: Board = "6k1/8/8/8/8/8/8/R4RK1 w - - 0 1".parse().unwrap();
+        let second_rank: Board = "6k1/8/8/8/8/8/R4R2/6K1 w - - 0 1".parse().unwrap();
+        let (mg1, eg1) = back_rank.eval_rooks();
+        let (mg2, eg2) = second_rank.eval_rooks();
+        assert_eq!(mg1 - mg2, CONNECTED_ROOKS_BACK_RANK_MG);
+        assert_eq!(eg1, eg2);
+    }
+
+    #[test]
+    fn test_rooks_doubled_on_open_file() {
+        let open: Board = "4k3/8/8/8/8/8/4R3/4R2K w - - 0 1".parse().unwrap();
+        let semi_open: Board = "4k3/4p3/8/8/8/8/4R3/4R2K w - - 0 1".parse().unwrap();
+        let (mg1, _) = open.eval_rooks();
+        let (mg2, _) = semi_open.eval_rooks();
+        assert_eq!(
+            mg1 - mg2,
+            2 * (ROOK_OPEN_FILE_MG - ROOK_SEMI_OPEN_MG) + CONNECTED_ROOKS_OPEN_FILE_MG
+        );
+    }
+
+    #[test]
+    fn test_rook_lift() {
+        // Ra3 can swing along the third rank to g3 or h3
+        let lift: Board = "6k1/5ppp/8/8/8/R7/5PPP/6K1 w - - 0 1".parse().unwrap();
+        let blocked: Board = "6k1/5ppp/8/8/8/R1N5/5PPP/6K1 w - - 0 1".parse().unwrap();
+        let (mg1, _) = lift.eval_rooks();
+        let (mg2, _) = blocked.eval_rooks();
+        assert_eq!(mg1 - mg2, ROOK_LIFT_MG);
+
+        // Mirrored for Black
+        let lift: Board = "6k1/5ppp/r7/8/8/8/5PPP/6K1 w - - 0 1".parse().unwrap();
+        let blocked: Board = "6k1/5ppp/r1n5/8/8/8/5PPP/6K1 w - - 0 1".parse().unwrap();
+        assert_eq!(lift.eval_rooks().0 - blocked.eval_rooks().0, -ROOK_LIFT_MG);
+    }
+
     #[test]
     fn test_trapped_rook() {
         // White king on g1 with rook trapped on h1 (can't castle)
@@ -4,51 +4,95 @@
 //! - Territory control (safe squares in enemy half)
 //! - Central control (bonus for controlling e4/d4/e5/d5)
 //! - Expansion potential (pawn breaks available)
+//! - Closed-position space: safe central squares on our side of the board,
+//!   counted twice behind our pawns, weighted by how many of our pieces
+//!   stand behind the pawn chain and how blocked the pawns are. Room to
+//!   manoeuvre behind a locked chain matters most when the pieces that need
+//!   it are there, and little once the position opens up.
 
 use crate::board::state::Board;
 use crate::board::types::{Bitboard, Color, Piece};
 
 use super::helpers::{single_pawn_attacks, AttackContext};
 
-/// Space bonus per controlled square in enemy territory
-pub const SPACE_BONUS_MG: i32 = 2;
-pub const SPACE_BONUS_EG: i32 = 1;
-
-/// Central control bonus (e4, d4, e5, d5)
-pub const CENTER_CONTROL_MG: i32 = 10;
-pub const CENTER_CONTROL_EG: i32 = 5;
-
-/// Extended center (c3-f3-f6-c6)
-pub const EXTENDED_CENTER_MG: i32 = 3;
-
-/// Pawn break potential bonus
-pub const PAWN_BREAK_MG: i32 = 5;
-
 /// Center squares mask (e4, d4, e5, d5)
 const CENTER_SQUARES: u64 = 0x0000_0018_1800_0000;
 
 /// Extended center (c3-f6 rectangle)
 const EXTENDED_CENTER: u64 = 0x0000_3C3C_3C3C_0000;
 
+/// Files c-f
+const CENTRAL_FILES: u64 = 0x3C3C_3C3C_3C3C_3C3C;
+
+/// Blocked pawns counted toward closedness at most
+const MAX_BLOCKED_PAWNS: i32 = 9;
+
+/// Space weights, in centipawns unless noted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceWeights {
+    /// Per safe square in the enemy half
+    pub territory_mg: i32,
+    pub territory_eg: i32,
+    /// Per safely controlled center square (e4, d4, e5, d5)
+    pub center_mg: i32,
+    pub center_eg: i32,
+    /// Per safely controlled square in the rest of c3-f6
+    pub extended_center_mg: i32,
+    /// Per available pawn capture, doubled for central ones
+    pub pawn_break_mg: i32,
+    /// Closed-position space, in 1/16 centipawn per safe square and unit
+    /// of weight (pieces behind the chain plus blocked pawns)
+    pub closed_space_mg: i32,
+}
+
+impl SpaceWeights {
+    pub const DEFAULT: Self = SpaceWeights {
+        territory_mg: 2,
+        territory_eg: 1,
+        center_mg: 10,
+        center_eg: 5,
+        extended_center_mg: 3,
+        pawn_break_mg: 5,
+        closed_space_mg: 2,
+    };
+}
+
+impl Default for SpaceWeights {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 impl Board {
-    /// Evaluate space and territory control.
+    /// Evaluate space and territory control with the default weights.
     ///
     /// Returns (middlegame, endgame) score from white's perspective.
     #[must_use]
     pub fn eval_space_control(&self, ctx: &AttackContext) -> (i32, i32) {
-        let mut mg = 0;
-        let mut eg = 0;
-
-        let (w_mg, w_eg) = self.eval_space_for_color(Color::White, ctx);
-        let (b_mg, b_eg) = self.eval_space_for_color(Color::Black, ctx);
-
-        mg += w_mg - b_mg;
-        eg += w_eg - b_eg;
+        self.eval_space_control_with(ctx, &SpaceWeights::DEFAULT)
+    }
 
-        (mg, eg)
+    /// Evaluate space and territory control with `weights`, e.g. candidate
+    /// values during tuning
+    #[must_use]
+    pub fn eval_space_control_with(
+        &self,
+        ctx: &AttackContext,
+        weights: &SpaceWeights,
+    ) -> (i32, i32) {
+        let blocked = self.blocked_pawns();
+        let (w_mg, w_eg) = self.eval_space_for_color(Color::White, ctx, weights, blocked);
+        let (b_mg, b_eg) = self.eval_space_for_color(Color::Black, ctx, weights, blocked);
+        (w_mg - b_mg, w_eg - b_eg)
     }
 
-    fn eval_space_for_color(&self, color: Color, ctx: &AttackContext) -> (i32, i32) {
+    fn eval_space_for_color(
+        &self,
+        color: Color,
+        ctx: &AttackContext,
+        weights: &SpaceWeights,
+        blocked: i32,
+    ) -> (i32, i32) {
         let mut mg = 0;
         let mut eg = 0;
 
@@ -65,28 +109,63 @@ impl Board {
         };
 
         let space_count = (safe_squares.0 & enemy_half).count_ones() as i32;
-        mg += space_count * SPACE_BONUS_MG;
-        eg += space_count * SPACE_BONUS_EG;
+        mg += space_count * weights.territory_mg;
+        eg += space_count * weights.territory_eg;
 
         // Central control (safe squares bonus)
         let safe_center = (safe_squares.0 & CENTER_SQUARES).count_ones() as i32;
 
         // Extra bonus for safely controlling center
-        mg += safe_center * CENTER_CONTROL_MG;
-        eg += safe_center * CENTER_CONTROL_EG;
+        mg += safe_center * weights.center_mg;
+        eg += safe_center * weights.center_eg;
 
         // Extended center control
         let extended_control =
             (safe_squares.0 & EXTENDED_CENTER & !CENTER_SQUARES).count_ones() as i32;
-        mg += extended_control * EXTENDED_CENTER_MG;
+        mg += extended_control * weights.extended_center_mg;
 
         // Pawn break potential
-        mg += self.eval_pawn_breaks(color);
+        mg += self.eval_pawn_breaks(color) * weights.pawn_break_mg;
+
+        // Room behind the pawn chain, for the pieces that stand there
+        let (area, pieces_behind) = self.closed_space(color, ctx);
+        let weight = pieces_behind + blocked.min(MAX_BLOCKED_PAWNS);
+        mg += area * weight * weights.closed_space_mg / 16;
 
         (mg, eg)
     }
 
-    /// Evaluate available pawn breaks.
+    /// Pawns of either side blocked by an enemy pawn directly in front
+    fn blocked_pawns(&self) -> i32 {
+        let white = self.pieces_of(Color::White, Piece::Pawn);
+        let black = self.pieces_of(Color::Black, Piece::Pawn);
+        // Each blocked pair stops two pawns
+        2 * white.shift_north().intersect_popcount(black) as i32
+    }
+
+    /// Safe central squares on `color`'s side of the board (ranks 2-4 for
+    /// white), with those behind our pawns counted twice, and the number of
+    /// our pieces (other than the king) standing behind the pawn chain
+    fn closed_space(&self, color: Color, ctx: &AttackContext) -> (i32, i32) {
+        let own_pawns = self.pieces_of(color, Piece::Pawn);
+        let own_side = match color {
+            Color::White => 0x0000_0000_FFFF_FF00u64, // Ranks 2-4
+            Color::Black => 0x00FF_FFFF_0000_0000u64, // Ranks 5-7
+        };
+        let safe = Bitboard(
+            CENTRAL_FILES & own_side & !own_pawns.0 & !ctx.pawn_attacks(color.opponent()).0,
+        );
+        let behind = own_pawns.fill_backward(color).and(own_pawns.not());
+        let area = safe.popcount() + safe.intersect_popcount(behind);
+
+        let pieces =
+            self.occupied_by(color).0 & !own_pawns.0 & !self.pieces_of(color, Piece::King).0;
+        let pieces_behind = Bitboard(pieces).intersect_popcount(behind);
+
+        (area as i32, pieces_behind as i32)
+    }
+
+    /// Count available pawn breaks, central ones twice.
     fn eval_pawn_breaks(&self, color: Color) -> i32 {
         let own_pawns = self.pieces_of(color, Piece::Pawn);
         let enemy_pawns = self.opponent_pieces(color, Piece::Pawn);
@@ -111,7 +190,7 @@ impl Board {
             }
         }
 
-        breaks * PAWN_BREAK_MG
+        breaks
     }
 }
 
@@ -187,4 +266,55 @@ mod tests {
         // Rook on 6th rank controls enemy territory
         assert!(mg > 0, "rook in enemy territory should give space bonus");
     }
+    #[test]
+    fn test_pieces_behind_pawn_chain() {
+        // The bishop stands behind the d4 pawn; the king is not counted
+        let board: Board = "4k3/8/8/3pP3/3P4/3B4/8/4K3 w - - 0 1".parse().unwrap();
+        let ctx = board.compute_attack_context();
+        let (area, pieces_behind) = board.closed_space(Color::White, &ctx);
+        assert_eq!(pieces_behind, 1);
+        assert!(area > 0);
+    }
+
+    #[test]
+    fn test_blocked_pawns() {
+        let open: Board = "4k3/8/8/8/3P4/8/8/4K3 w - - 0 1".parse().unwrap();
+        let locked: Board = "4k3/8/4p3/3pP3/3P4/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(open.blocked_pawns(), 0);
+        assert_eq!(locked.blocked_pawns(), 4);
+    }
+
+    #[test]
+    fn test_closed_position_space() {
+        // King's Indian with d5: white's pieces have room behind the chain
+        let board: Board = "r1bq1rk1/pppn1pbp/3p1np1/3Pp3/2P1P3/2N2N2/PP2BPPP/R1BQ1RK1 w - - 0 1"
+            .parse()
+            .unwrap();
+        let ctx = board.compute_attack_context();
+        let without = SpaceWeights {
+            closed_space_mg: 0,
+            ..SpaceWeights::DEFAULT
+        };
+        let (mg, _) = board.eval_space_control(&ctx);
+        let (mg_without, _) = board.eval_space_control_with(&ctx, &without);
+        assert!(mg > mg_without, "closed space should favour white: {mg}");
+    }
+
+    #[test]
+    fn test_zero_space_weights() {
+        let board: Board = "rnbqkbnr/pppppppp/8/8/3PP3/8/PPP2PPP/RNBQKBNR w KQkq - 0 1"
+            .parse()
+            .unwrap();
+        let ctx = board.compute_attack_context();
+        let zero = SpaceWeights {
+            territory_mg: 0,
+            territory_eg: 0,
+            center_mg: 0,
+            center_eg: 0,
+            extended_center_mg: 0,
+            pawn_break_mg: 0,
+            closed_space_mg: 0,
+        };
+        assert_eq!(board.eval_space_control_with(&ctx, &zero), (0, 0));
+    }
 }
@@ -66,6 +66,15 @@ pub const BACKWARD_OPEN_EG: i32 = 0;
 pub const PHALANX_BONUS_MG: [i32; 8] = [0, 0, 3, 5, 12, 25, 50, 0];
 pub const PHALANX_BONUS_EG: [i32; 8] = [0, 0, 2, 4, 8, 15, 30, 0];
 
+/// Candidate passer bonus by rank: a pawn on a file free of enemy pawns
+/// whose helpers on the adjacent files outnumber the enemy sentries
+pub const CANDIDATE_PASSER_MG: [i32; 8] = [0, 2, 3, 5, 8, 12, 0, 0];
+pub const CANDIDATE_PASSER_EG: [i32; 8] = [0, 5, 8, 12, 20, 30, 0, 0];
+
+/// Bonus per extra healthy pawn in a wing majority (a-d or e-h files)
+pub const PAWN_MAJORITY_MG: i32 = 2;
+pub const PAWN_MAJORITY_EG: i32 = 10;
+
 /// Defended pawn bonus by rank
 pub const DEFENDED_BONUS_MG: [i32; 8] = [0, 0, 5, 8, 12, 20, 35, 0];
 pub const DEFENDED_BONUS_EG: [i32; 8] = [0, 0, 3, 5, 8, 12, 20, 0];
@@ -140,13 +149,33 @@ pub const MINOR_ON_MINOR: i32 = 8;
 pub const KNIGHT_OUTPOST_MG: i32 = 20;
 pub const KNIGHT_OUTPOST_EG: i32 = 15;
 
+/// Knight rim penalty by the knight's distance from the nearest edge: a
+/// knight on the rim covers at most four squares, in the corner two
+pub const KNIGHT_RIM_MG: [i32; 4] = [-10, -3, 0, 0];
+pub const KNIGHT_RIM_EG: [i32; 4] = [-8, -2, 0, 0];
+
+/// Extra knight penalty in a corner, on top of the rim penalty
+pub const KNIGHT_CORNER_MG: i32 = -8;
+pub const KNIGHT_CORNER_EG: i32 = -6;
+
 /// Bishop outpost bonus (smaller than knight - bishops prefer open diagonals)
 pub const BISHOP_OUTPOST_MG: i32 = 10;
 pub const BISHOP_OUTPOST_EG: i32 = 8;
 
-/// Bad bishop penalty per blocking pawn (bishop blocked by own pawns on same color)
-pub const BAD_BISHOP_MG: i32 = -5;
-pub const BAD_BISHOP_EG: i32 = -8;
+/// Bad bishop penalty per own pawn on the bishop's colour that is fixed
+/// (an enemy pawn stands directly in front of it)
+pub const BAD_BISHOP_FIXED_MG: i32 = -6;
+pub const BAD_BISHOP_FIXED_EG: i32 = -10;
+
+/// Bad bishop penalty per own pawn on the bishop's colour that can still
+/// advance or be exchanged
+pub const BAD_BISHOP_MOBILE_MG: i32 = -1;
+pub const BAD_BISHOP_MOBILE_EG: i32 = -3;
+
+/// Extra bad bishop penalty per blocked own pawn on the c-f files on the
+/// bishop's colour: it walls in the centre for good
+pub const BAD_BISHOP_BLOCKED_CENTER_MG: i32 = -5;
+pub const BAD_BISHOP_BLOCKED_CENTER_EG: i32 = -3;
 
 // ============================================================================
 // CONNECTED ROOKS & ROOK BEHIND PASSED PAWN
@@ -156,6 +185,18 @@ pub const BAD_BISHOP_EG: i32 = -8;
 pub const CONNECTED_ROOKS_MG: i32 = 10;
 pub const CONNECTED_ROOKS_EG: i32 = 8;
 
+/// Extra bonus for rooks connected on the back rank: the minor pieces and
+/// queen are out of the way
+pub const CONNECTED_ROOKS_BACK_RANK_MG: i32 = 8;
+
+/// Extra bonus for rooks doubled on an open file
+pub const CONNECTED_ROOKS_OPEN_FILE_MG: i32 = 12;
+pub const CONNECTED_ROOKS_OPEN_FILE_EG: i32 = 6;
+
+/// Rook on its third rank with a clear path along the rank to the enemy
+/// king's file or a file beside it, ready to swing up into the attack
+pub const ROOK_LIFT_MG: i32 = 12;
+
 /// Rook behind passed pawn bonus (supporting or blocking)
 pub const ROOK_BEHIND_PASSER_MG: i32 = 15;
 pub const ROOK_BEHIND_PASSER_EG: i32 = 25;
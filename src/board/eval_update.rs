@@ -3,21 +3,9 @@
 //! Provides utilities for maintaining incremental evaluation scores
 //! during make/unmake operations.
 
-use super::pst::{MATERIAL_EG, MATERIAL_MG, PHASE_WEIGHTS, PST_EG, PST_MG};
+use super::pst::{piece_square_value, PHASE_WEIGHTS};
 use super::state::Board;
-use super::types::Piece;
-
-/// Calculate PST square index for a given color.
-/// White uses the square index directly, Black mirrors vertically.
-#[inline]
-#[must_use]
-pub fn pst_square(sq_idx: usize, is_white: bool) -> usize {
-    if is_white {
-        sq_idx
-    } else {
-        sq_idx ^ 0b11_1000
-    }
-}
+use super::types::{Color, Piece, Square};
 
 /// Incremental evaluation state.
 ///
@@ -47,49 +35,29 @@ impl EvalState {
 
     /// Add a piece to the evaluation.
     #[inline]
-    pub fn add_piece(&mut self, color_idx: usize, piece: Piece, sq_idx: usize, is_white: bool) {
-        let p_idx = piece.index();
-        let pst_sq = pst_square(sq_idx, is_white);
-
-        self.mg[color_idx] += MATERIAL_MG[p_idx] + PST_MG[p_idx][pst_sq];
-        self.eg[color_idx] += MATERIAL_EG[p_idx] + PST_EG[p_idx][pst_sq];
-        self.phase[color_idx] += PHASE_WEIGHTS[p_idx];
+    pub fn add_piece(&mut self, color: Color, piece: Piece, sq_idx: usize) {
+        let (mg, eg) = piece_square_value(color, piece, sq_idx);
+        let c = color.index();
+        self.mg[c] += mg;
+        self.eg[c] += eg;
+        self.phase[c] += PHASE_WEIGHTS[piece.index()];
     }
 
     /// Remove a piece from the evaluation.
     #[inline]
-    pub fn remove_piece(&mut self, color_idx: usize, piece: Piece, sq_idx: usize, is_white: bool) {
-        let p_idx = piece.index();
-        let pst_sq = pst_square(sq_idx, is_white);
-
-        self.mg[color_idx] -= MATERIAL_MG[p_idx] + PST_MG[p_idx][pst_sq];
-        self.eg[color_idx] -= MATERIAL_EG[p_idx] + PST_EG[p_idx][pst_sq];
-        self.phase[color_idx] -= PHASE_WEIGHTS[p_idx];
+    pub fn remove_piece(&mut self, color: Color, piece: Piece, sq_idx: usize) {
+        let (mg, eg) = piece_square_value(color, piece, sq_idx);
+        let c = color.index();
+        self.mg[c] -= mg;
+        self.eg[c] -= eg;
+        self.phase[c] -= PHASE_WEIGHTS[piece.index()];
     }
 
     /// Move a piece from one square to another.
     #[inline]
-    pub fn move_piece(
-        &mut self,
-        color_idx: usize,
-        piece: Piece,
-        from_idx: usize,
-        to_idx: usize,
-        is_white: bool,
-    ) {
-        let p_idx = piece.index();
-        let from_pst = pst_square(from_idx, is_white);
-        let to_pst = pst_square(to_idx, is_white);
-
-        // Remove from old square
-        self.mg[color_idx] -= MATERIAL_MG[p_idx] + PST_MG[p_idx][from_pst];
-        self.eg[color_idx] -= MATERIAL_EG[p_idx] + PST_EG[p_idx][from_pst];
-        self.phase[color_idx] -= PHASE_WEIGHTS[p_idx];
-
-        // Add to new square
-        self.mg[color_idx] += MATERIAL_MG[p_idx] + PST_MG[p_idx][to_pst];
-        self.eg[color_idx] += MATERIAL_EG[p_idx] + PST_EG[p_idx][to_pst];
-        self.phase[color_idx] += PHASE_WEIGHTS[p_idx];
+    pub fn move_piece(&mut self, color: Color, piece: Piece, from_idx: usize, to_idx: usize) {
+        self.remove_piece(color, piece, from_idx);
+        self.add_piece(color, piece, to_idx);
     }
 }
 
@@ -116,39 +84,43 @@ impl Board {
         self.eval_eg = state.eg;
         self.game_phase = state.phase;
     }
+
+    /// Add a piece on `sq` to the incremental material and PST scores
+    #[inline]
+    pub(crate) fn add_piece_eval(&mut self, color: Color, piece: Piece, sq: Square) {
+        let (mg, eg) = piece_square_value(color, piece, sq.index());
+        let c = color.index();
+        self.eval_mg[c] += mg;
+        self.eval_eg[c] += eg;
+        self.game_phase[c] += PHASE_WEIGHTS[piece.index()];
+    }
+
+    /// Remove a piece on `sq` from the incremental material and PST scores
+    #[inline]
+    pub(crate) fn remove_piece_eval(&mut self, color: Color, piece: Piece, sq: Square) {
+        let (mg, eg) = piece_square_value(color, piece, sq.index());
+        let c = color.index();
+        self.eval_mg[c] -= mg;
+        self.eval_eg[c] -= eg;
+        self.game_phase[c] -= PHASE_WEIGHTS[piece.index()];
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_pst_square_white() {
-        // White's a1 (index 0) stays 0
-        assert_eq!(pst_square(0, true), 0);
-        // White's h8 (index 63) stays 63
-        assert_eq!(pst_square(63, true), 63);
-    }
-
-    #[test]
-    fn test_pst_square_black() {
-        // Black's a1 (index 0) becomes a8 (index 56)
-        assert_eq!(pst_square(0, false), 56);
-        // Black's h8 (index 63) becomes h1 (index 7)
-        assert_eq!(pst_square(63, false), 7);
-    }
-
     #[test]
     fn test_eval_state_add_remove() {
         let mut state = EvalState::new();
 
         // Add a white pawn at e2 (index 12)
-        state.add_piece(0, Piece::Pawn, 12, true);
+        state.add_piece(Color::White, Piece::Pawn, 12);
         assert!(state.mg[0] > 0, "adding pawn should increase mg");
         assert!(state.eg[0] > 0, "adding pawn should increase eg");
 
         // Remove it
-        state.remove_piece(0, Piece::Pawn, 12, true);
+        state.remove_piece(Color::White, Piece::Pawn, 12);
         assert_eq!(state.mg[0], 0, "removing should restore to 0");
         assert_eq!(state.eg[0], 0, "removing should restore to 0");
     }
@@ -173,12 +145,12 @@ mod tests {
         let mut state = EvalState::new();
 
         // Add a knight at b1 (index 1)
-        state.add_piece(0, Piece::Knight, 1, true);
+        state.add_piece(Color::White, Piece::Knight, 1);
         let initial_mg = state.mg[0];
         let initial_phase = state.phase[0];
 
         // Move to c3 (index 18)
-        state.move_piece(0, Piece::Knight, 1, 18, true);
+        state.move_piece(Color::White, Piece::Knight, 1, 18);
 
         // Knight on c3 should have different PST bonus than b1
         // (c3 is a better square for a knight than b1)
@@ -197,7 +169,7 @@ mod tests {
     fn test_queen_has_high_phase() {
         let mut state = EvalState::new();
 
-        state.add_piece(0, Piece::Queen, 3, true); // d1
+        state.add_piece(Color::White, Piece::Queen, 3); // d1
         assert!(state.phase[0] >= 4, "queen should have high phase weight");
     }
 
@@ -205,7 +177,7 @@ mod tests {
     fn test_pawn_has_no_phase() {
         let mut state = EvalState::new();
 
-        state.add_piece(0, Piece::Pawn, 12, true); // e2
+        state.add_piece(Color::White, Piece::Pawn, 12); // e2
         assert_eq!(state.phase[0], 0, "pawns should not contribute to phase");
     }
 
@@ -1,9 +1,11 @@
+use std::fmt::Write;
 use std::str::FromStr;
 
 use super::error::{FenError, MoveParseError};
+use super::state::RepetitionTable;
 use super::{
-    file_to_index, rank_to_index, Board, Color, Move, Piece, Square, CASTLE_BLACK_K,
-    CASTLE_BLACK_Q, CASTLE_WHITE_K, CASTLE_WHITE_Q,
+    file_to_index, rank_to_index, Board, Color, Move, Piece, Square, Variant, CASTLE_BLACK_K,
+    CASTLE_BLACK_Q, CASTLE_WHITE_K, CASTLE_WHITE_Q, CHECKS_TO_WIN,
 };
 
 impl Board {
@@ -27,6 +29,12 @@ impl Board {
             for c in rank_str.chars() {
                 if c.is_ascii_digit() {
                     file += c.to_digit(10).unwrap() as usize;
+                    if file > 8 {
+                        return Err(FenError::TooManyFiles {
+                            rank: rank_idx,
+                            files: file,
+                        });
+                    }
                 } else {
                     let color = if c.is_uppercase() {
                         Color::White
@@ -89,14 +97,33 @@ impl Board {
             }
         };
 
+        // Three-check counters (optional): checks remaining ("3+3") right
+        // after the en passant field, or checks given ("+0+0") at the end
+        let mut rest = &parts[4..];
+        if let Some(field) = rest
+            .first()
+            .filter(|f| f.contains('+') && !f.starts_with('+'))
+        {
+            let remaining = parse_check_pair(field)?;
+            board.set_checks_given(remaining.map(|n| CHECKS_TO_WIN - n));
+            rest = &rest[1..];
+        }
+        if let Some((field, init)) = rest.split_last() {
+            if let Some(given) = field.strip_prefix('+') {
+                board.set_checks_given(parse_check_pair(given)?);
+                rest = init;
+            }
+        }
+
         // Parse halfmove clock (optional)
-        if parts.len() >= 5 {
-            board.halfmove_clock = parts[4].parse().unwrap_or(0);
+        if let Some(clock) = rest.first() {
+            board.halfmove_clock = clock.parse().unwrap_or(0);
         }
 
         board.hash = board.calculate_initial_hash();
-        board.repetition_counts.set(board.hash, 1);
+        board.repetition_counts = RepetitionTable::starting_at(board.hash);
         board.recalculate_incremental_eval();
+        board.refresh_checkers();
         Ok(board)
     }
 
@@ -155,19 +182,114 @@ impl Board {
             .en_passant_target
             .map_or_else(|| "-".to_string(), |sq| sq.to_string());
 
-        format!(
+        let mut fen = format!(
             "{} {} {} {} {} 1",
             rows.join("/"),
             active,
             castling,
             ep,
             self.halfmove_clock
-        )
+        );
+        if self.variant == Variant::ThreeCheck {
+            let _ = write!(
+                fen,
+                " +{}+{}",
+                self.checks_given(Color::White),
+                self.checks_given(Color::Black)
+            );
+        }
+        fen
+    }
+
+    /// FEN extended with the repetition history, for training pipelines
+    /// that need to know which positions have already occurred.
+    ///
+    /// A final `hist:` field lists the hash (16 hex digits) of the position
+    /// after each ply of the game so far, oldest first and ending with the
+    /// current position: `hist:<hash>,<hash>,...`. The hashes are this
+    /// engine's Zobrist keys, which depend on the variant and change with
+    /// the engine's version, so the field is only meaningful to the engine
+    /// that wrote it.
+    #[must_use]
+    pub fn to_fen_extended(&self) -> String {
+        let history: Vec<String> = self
+            .repetition_counts
+            .plies()
+            .iter()
+            .map(|hash| format!("{hash:016x}"))
+            .collect();
+        format!("{} {HISTORY_PREFIX}{}", self.to_fen(), history.join(","))
+    }
+
+    /// Parse a FEN that may end with the repetition history written by
+    /// [`Board::to_fen_extended`], counting how often each position occurred
+    /// from the sequence.
+    ///
+    /// Without the field this is [`Board::try_from_fen`]. The current
+    /// position is added as the last ply if the history does not end with
+    /// it.
+    pub fn try_from_fen_extended(fen: &str) -> Result<Self, FenError> {
+        let fen = fen.trim();
+        let Some((base, history)) = fen
+            .rsplit_once(char::is_whitespace)
+            .and_then(|(base, field)| Some((base, field.strip_prefix(HISTORY_PREFIX)?)))
+        else {
+            return Self::try_from_fen(fen);
+        };
+        let mut board = Self::try_from_fen(base)?;
+        let mut plies = RepetitionTable::new();
+        for hash in history.split(',').filter(|h| !h.is_empty()) {
+            let hash = u64::from_str_radix(hash, 16).map_err(|_| FenError::InvalidHistory {
+                found: history.to_string(),
+            })?;
+            plies.increment(hash);
+        }
+        if plies.plies().last() != Some(&board.hash) {
+            plies.increment(board.hash);
+        }
+        board.repetition_counts = plies;
+        Ok(board)
+    }
+
+    /// The same position with the colours swapped and the board mirrored
+    /// rank-wise, so any evaluation from the side to move is unchanged.
+    ///
+    /// Variant state such as three-check counters is not carried over.
+    #[must_use]
+    pub fn color_flipped(&self) -> Board {
+        let fen = self.to_fen();
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let swap_case = |s: &str| -> String {
+            s.chars()
+                .map(|c| {
+                    if c.is_ascii_uppercase() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c.to_ascii_uppercase()
+                    }
+                })
+                .collect()
+        };
+        let ranks: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+        let side = if self.white_to_move { "b" } else { "w" };
+        let mut castling: Vec<char> = swap_case(fields[2]).chars().collect();
+        castling.sort_unstable();
+        let castling: String = castling.into_iter().collect();
+        let ep = self
+            .en_passant_target
+            .map_or_else(|| "-".to_string(), |sq| sq.flip_vertical().to_string());
+        Board::from_fen(&format!(
+            "{} {side} {castling} {ep} {} 1",
+            ranks.join("/"),
+            self.halfmove_clock
+        ))
     }
 
     /// Parse a move in UCI long algebraic notation (e.g., "e2e4", "e7e8q").
     ///
     /// Returns the matching legal move if found, or an error describing why parsing failed.
+    /// The promotion letter may be upper or lower case; `Move`'s `Display`
+    /// writes it lower case.
     ///
     /// # Example
     /// ```
@@ -178,11 +300,10 @@ impl Board {
     /// assert_eq!(mv.to_string(), "e2e4");
     /// ```
     pub fn parse_move(&mut self, uci: &str) -> Result<Move, MoveParseError> {
-        if uci.len() < 4 || uci.len() > 5 {
-            return Err(MoveParseError::InvalidLength { len: uci.len() });
-        }
-
         let chars: Vec<char> = uci.chars().collect();
+        if chars.len() < 4 || chars.len() > 5 {
+            return Err(MoveParseError::InvalidLength { len: chars.len() });
+        }
 
         // Validate square characters
         if !('a'..='h').contains(&chars[0])
@@ -199,7 +320,7 @@ impl Board {
         let to_sq = Square::new(rank_to_index(chars[3]), file_to_index(chars[2]));
 
         // Parse promotion piece if present
-        let promotion = if uci.len() == 5 {
+        let promotion = if chars.len() == 5 {
             let piece = Piece::from_char(chars[4])
                 .ok_or(MoveParseError::InvalidPromotion { char: chars[4] })?;
             if matches!(piece, Piece::Pawn | Piece::King) {
@@ -240,11 +361,30 @@ impl Board {
     /// ```
     pub fn make_move_uci(&mut self, uci: &str) -> Result<Move, MoveParseError> {
         let mv = self.parse_move(uci)?;
-        self.make_move(mv);
+        self.make_game_move(mv);
         Ok(mv)
     }
 }
 
+/// Prefix of the repetition history field of an extended FEN
+const HISTORY_PREFIX: &str = "hist:";
+
+/// Parse a three-check counter pair such as `1+2`, each at most
+/// [`CHECKS_TO_WIN`]
+fn parse_check_pair(field: &str) -> Result<[u8; 2], FenError> {
+    let invalid = || FenError::InvalidCheckCount {
+        found: field.to_string(),
+    };
+    let (white, black) = field.split_once('+').ok_or_else(invalid)?;
+    let parse = |n: &str| {
+        n.parse::<u8>()
+            .ok()
+            .filter(|&n| n <= CHECKS_TO_WIN)
+            .ok_or_else(invalid)
+    };
+    Ok([parse(white)?, parse(black)?])
+}
+
 impl FromStr for Board {
     type Err = FenError;
 
@@ -309,6 +449,12 @@ mod tests {
         assert!(matches!(result, Err(FenError::InvalidEnPassant { .. })));
     }
 
+    #[test]
+    fn test_fen_error_empty_squares_overflow_rank() {
+        let result = Board::try_from_fen("4k3/8/8/8/8/8/8/99 w - - 0 1");
+        assert!(matches!(result, Err(FenError::TooManyFiles { .. })));
+    }
+
     #[test]
     fn test_fen_no_castling() {
         let board =
@@ -388,6 +534,43 @@ mod tests {
         assert!(!board.white_to_move()); // Black to move after e4
     }
 
+    #[test]
+    fn test_extended_fen_keeps_repetitions() {
+        let mut board = Board::new();
+        for mv in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            board.make_move_uci(mv).unwrap();
+        }
+        let extended = board.to_fen_extended();
+        // Every ply in order, from the start position to the current one
+        let start = format!("{:016x}", Board::new().hash());
+        let current = format!("{:016x}", board.hash());
+        let history = extended
+            .strip_prefix(&format!("{} hist:", board.to_fen()))
+            .unwrap();
+        let plies: Vec<&str> = history.split(',').collect();
+        assert_eq!(plies.len(), 8);
+        assert_eq!((plies[0], plies[7]), (start.as_str(), current.as_str()));
+
+        let mut parsed = Board::try_from_fen_extended(&extended).unwrap();
+        assert_eq!(parsed.hash(), board.hash());
+        assert_eq!(parsed.repetition_count(), 2);
+        assert_eq!(parsed.to_fen_extended(), extended);
+        // The next repetition is a threefold draw, as in the original game
+        parsed.make_move_uci("f6g8").unwrap();
+        assert!(parsed.is_threefold());
+
+        // Plain FENs parse as usual
+        let plain = Board::try_from_fen_extended(&board.to_fen()).unwrap();
+        assert_eq!(plain.repetition_count(), 1);
+
+        assert_eq!(
+            Board::try_from_fen_extended("8/8/8/8/8/8/8/K1k5 w - - 0 1 hist:00ff,xyz").unwrap_err(),
+            FenError::InvalidHistory {
+                found: "00ff,xyz".to_string()
+            }
+        );
+    }
+
     #[test]
     fn test_halfmove_clock_parsing() {
         let board = Board::try_from_fen("8/8/8/8/8/8/8/K1k5 w - - 42 1").unwrap();
@@ -4,10 +4,9 @@ use crate::zobrist::{
     color_to_zobrist_index, piece_to_zobrist_index, square_to_zobrist_index, ZOBRIST,
 };
 
-use super::eval_update::pst_square;
-use super::pst::{MATERIAL_EG, MATERIAL_MG, PHASE_WEIGHTS, PST_EG, PST_MG};
 use super::{
     bit_for_square, castle_bit, Board, Color, Move, NullMoveInfo, Piece, Square, UnmakeInfo,
+    Variant,
 };
 
 impl Board {
@@ -122,7 +121,52 @@ impl Board {
             hash ^= ZOBRIST.en_passant_keys[ep_square.file()];
         }
 
-        hash
+        hash ^ self.checks_hash()
+    }
+
+    /// Zobrist hash of the position after `m`, without making it.
+    ///
+    /// Used to prefetch the child's TT bucket before `make_move`. Matches
+    /// the hash `make_move` gives the child, except that a check in
+    /// three-check is not counted; a miss just costs the prefetch.
+    #[inline]
+    #[must_use]
+    pub(crate) fn hash_after(&self, m: Move) -> u64 {
+        let Some((color, piece)) = self.piece_at(m.from()) else {
+            return self.hash;
+        };
+        let c_idx = color_to_zobrist_index(color);
+        let key = |p: Piece, c: usize, sq: Square| {
+            ZOBRIST.piece_keys[piece_to_zobrist_index(p)][c][square_to_zobrist_index(sq)]
+        };
+
+        let mut hash = self.hash ^ ZOBRIST.black_to_move_key;
+        if let Some(ep) = self.en_passant_target {
+            hash ^= ZOBRIST.en_passant_keys[ep.file()];
+        }
+        hash ^= key(piece, c_idx, m.from());
+        hash ^= key(m.promotion().unwrap_or(piece), c_idx, m.to());
+        if m.is_double_pawn_push() {
+            hash ^= ZOBRIST.en_passant_keys[m.from().file()];
+        }
+
+        let mut captured = None;
+        if m.is_castling() {
+            let (rook_from_f, rook_to_f) = if m.to().file() == 6 { (7, 5) } else { (0, 3) };
+            hash ^= key(Piece::Rook, c_idx, Square::new(m.to().rank(), rook_from_f));
+            hash ^= key(Piece::Rook, c_idx, Square::new(m.to().rank(), rook_to_f));
+        } else {
+            let capture_sq = if m.is_en_passant() {
+                Square::new(m.from().rank(), m.to().file())
+            } else {
+                m.to()
+            };
+            captured = self.piece_at(capture_sq);
+            if let Some((cap_color, cap_piece)) = captured {
+                hash ^= key(cap_piece, color_to_zobrist_index(cap_color), capture_sq);
+            }
+        }
+        hash ^ Self::castling_rights_hash(self.castling_rights_lost(&m, piece, color, captured))
     }
 
     // =========================================================================
@@ -132,24 +176,14 @@ impl Board {
     /// Remove a captured piece, updating board state, hash, and incremental eval.
     /// Returns the hash XOR delta for the capture.
     #[inline]
-    fn remove_captured_piece(
-        &mut self,
-        capture_sq: Square,
-        captured: (Color, Piece),
-        opp_idx: usize,
-    ) -> u64 {
+    fn remove_captured_piece(&mut self, capture_sq: Square, captured: (Color, Piece)) -> u64 {
         let (cap_col, cap_piece) = captured;
-        let cap_sq_idx = capture_sq.index();
-        let cap_p_idx = cap_piece.index();
-        let cap_pst = pst_square(cap_sq_idx, cap_col == Color::White);
 
         // Remove from board
         self.remove_piece(capture_sq, cap_col, cap_piece);
 
         // Update incremental eval
-        self.eval_mg[opp_idx] -= MATERIAL_MG[cap_p_idx] + PST_MG[cap_p_idx][cap_pst];
-        self.eval_eg[opp_idx] -= MATERIAL_EG[cap_p_idx] + PST_EG[cap_p_idx][cap_pst];
-        self.game_phase[opp_idx] -= PHASE_WEIGHTS[cap_p_idx];
+        self.remove_piece_eval(cap_col, cap_piece, capture_sq);
 
         // Return hash delta
         ZOBRIST.piece_keys[piece_to_zobrist_index(cap_piece)][color_to_zobrist_index(cap_col)]
@@ -159,25 +193,17 @@ impl Board {
     /// Execute castling: move king (already removed), place king and rook.
     /// Returns the hash XOR delta for the rook movement.
     #[inline]
-    fn execute_castling(&mut self, m: &Move, color: Color, c_idx: usize, is_white: bool) -> u64 {
-        let to_idx = m.to().index();
-        let to_pst = pst_square(to_idx, is_white);
-
+    fn execute_castling(&mut self, m: &Move, color: Color) -> u64 {
         // Place king at destination
         self.set_piece(m.to(), color, Piece::King);
 
         // Update eval for king placement
-        let king_idx = Piece::King.index();
-        self.eval_mg[c_idx] += MATERIAL_MG[king_idx] + PST_MG[king_idx][to_pst];
-        self.eval_eg[c_idx] += MATERIAL_EG[king_idx] + PST_EG[king_idx][to_pst];
-        self.game_phase[c_idx] += PHASE_WEIGHTS[king_idx];
+        self.add_piece_eval(color, Piece::King, m.to());
 
         // Determine rook squares
         let (rook_from_f, rook_to_f) = if m.to().file() == 6 { (7, 5) } else { (0, 3) };
         let rook_from = Square::new(m.to().rank(), rook_from_f);
         let rook_to = Square::new(m.to().rank(), rook_to_f);
-        let rook_from_idx = rook_from.index();
-        let rook_to_idx = rook_to.index();
 
         // Move the rook
         let rook_info = self.piece_at(rook_from).expect("Castling without rook");
@@ -185,13 +211,8 @@ impl Board {
         self.set_piece(rook_to, rook_info.0, rook_info.1);
 
         // Update eval for rook move
-        let rook_idx = Piece::Rook.index();
-        let rook_from_pst = pst_square(rook_from_idx, is_white);
-        let rook_to_pst = pst_square(rook_to_idx, is_white);
-        self.eval_mg[c_idx] -= MATERIAL_MG[rook_idx] + PST_MG[rook_idx][rook_from_pst];
-        self.eval_eg[c_idx] -= MATERIAL_EG[rook_idx] + PST_EG[rook_idx][rook_from_pst];
-        self.eval_mg[c_idx] += MATERIAL_MG[rook_idx] + PST_MG[rook_idx][rook_to_pst];
-        self.eval_eg[c_idx] += MATERIAL_EG[rook_idx] + PST_EG[rook_idx][rook_to_pst];
+        self.remove_piece_eval(color, Piece::Rook, rook_from);
+        self.add_piece_eval(color, Piece::Rook, rook_to);
 
         // Return hash delta for rook movement
         ZOBRIST.piece_keys[piece_to_zobrist_index(Piece::Rook)][color_to_zobrist_index(color)]
@@ -200,78 +221,82 @@ impl Board {
                 [square_to_zobrist_index(rook_to)]
     }
 
-    /// Update castling rights based on a move.
-    /// Returns the hash XOR delta for castling rights changes.
+    /// Castling rights `m` takes away: both of the mover's after a king
+    /// move, one after a rook leaves its starting square, and the
+    /// opponent's one after a capture of the rook on its starting square.
     #[inline]
-    fn update_castling_rights(
-        &mut self,
+    fn castling_rights_lost(
+        &self,
         m: &Move,
         moving_piece: Piece,
         color: Color,
         captured: Option<(Color, Piece)>,
-    ) -> u64 {
-        let mut hash_delta: u64 = 0;
+    ) -> u8 {
+        let mut lost = 0;
 
-        // King move removes both castling rights
         if moving_piece == Piece::King {
-            if self.has_castling_right(color, 'K') {
-                hash_delta ^= ZOBRIST.castling_keys[color_to_zobrist_index(color)][0];
-                self.castling_rights &= !castle_bit(color, 'K');
-            }
-            if self.has_castling_right(color, 'Q') {
-                hash_delta ^= ZOBRIST.castling_keys[color_to_zobrist_index(color)][1];
-                self.castling_rights &= !castle_bit(color, 'Q');
-            }
+            lost |= castle_bit(color, 'K') | castle_bit(color, 'Q');
         } else if moving_piece == Piece::Rook {
-            // Rook move from starting square removes that side's castling
-            let start_rank = if color == Color::White { 0 } else { 7 };
-            if m.from() == Square::new(start_rank, 0) && self.has_castling_right(color, 'Q') {
-                hash_delta ^= ZOBRIST.castling_keys[color_to_zobrist_index(color)][1];
-                self.castling_rights &= !castle_bit(color, 'Q');
-            } else if m.from() == Square::new(start_rank, 7) && self.has_castling_right(color, 'K')
-            {
-                hash_delta ^= ZOBRIST.castling_keys[color_to_zobrist_index(color)][0];
-                self.castling_rights &= !castle_bit(color, 'K');
+            let start_rank = color.back_rank();
+            if m.from() == Square::new(start_rank, 0) {
+                lost |= castle_bit(color, 'Q');
+            } else if m.from() == Square::new(start_rank, 7) {
+                lost |= castle_bit(color, 'K');
+            }
+        }
+
+        if let Some((captured_color, Piece::Rook)) = captured {
+            let start_rank = captured_color.back_rank();
+            if m.to() == Square::new(start_rank, 0) {
+                lost |= castle_bit(captured_color, 'Q');
+            } else if m.to() == Square::new(start_rank, 7) {
+                lost |= castle_bit(captured_color, 'K');
             }
         }
 
-        // Capturing a rook on its starting square removes opponent's castling
-        if let Some((captured_color, captured_piece)) = captured {
-            if captured_piece == Piece::Rook {
-                let start_rank = if captured_color == Color::White { 0 } else { 7 };
-                if m.to() == Square::new(start_rank, 0)
-                    && self.has_castling_right(captured_color, 'Q')
-                {
-                    hash_delta ^= ZOBRIST.castling_keys[color_to_zobrist_index(captured_color)][1];
-                    self.castling_rights &= !castle_bit(captured_color, 'Q');
-                } else if m.to() == Square::new(start_rank, 7)
-                    && self.has_castling_right(captured_color, 'K')
-                {
-                    hash_delta ^= ZOBRIST.castling_keys[color_to_zobrist_index(captured_color)][0];
-                    self.castling_rights &= !castle_bit(captured_color, 'K');
+        lost & self.castling_rights
+    }
+
+    /// Combined Zobrist key of the castling rights in `rights`.
+    #[inline]
+    fn castling_rights_hash(rights: u8) -> u64 {
+        let mut hash = 0;
+        for color in [Color::White, Color::Black] {
+            for (i, side) in ['K', 'Q'].into_iter().enumerate() {
+                if rights & castle_bit(color, side) != 0 {
+                    hash ^= ZOBRIST.castling_keys[color_to_zobrist_index(color)][i];
                 }
             }
         }
+        hash
+    }
 
-        hash_delta
+    /// Update castling rights based on a move.
+    /// Returns the hash XOR delta for castling rights changes.
+    #[inline]
+    fn update_castling_rights(
+        &mut self,
+        m: &Move,
+        moving_piece: Piece,
+        color: Color,
+        captured: Option<(Color, Piece)>,
+    ) -> u64 {
+        let lost = self.castling_rights_lost(m, moving_piece, color, captured);
+        self.castling_rights &= !lost;
+        Self::castling_rights_hash(lost)
     }
 
     /// Remove captured piece for a move (including en passant) and return hash delta.
-    fn capture_piece_for_move(
-        &mut self,
-        m: Move,
-        is_white: bool,
-        opp_idx: usize,
-    ) -> (Option<(Color, Piece)>, u64) {
+    fn capture_piece_for_move(&mut self, m: Move, color: Color) -> (Option<(Color, Piece)>, u64) {
         if m.is_en_passant() {
-            let capture_row = if is_white {
+            let capture_row = if color == Color::White {
                 m.to().rank() - 1
             } else {
                 m.to().rank() + 1
             };
             let capture_sq = Square::new(capture_row, m.to().file());
             if let Some(captured) = self.piece_at(capture_sq) {
-                let delta = self.remove_captured_piece(capture_sq, captured, opp_idx);
+                let delta = self.remove_captured_piece(capture_sq, captured);
                 return (Some(captured), delta);
             }
             return (None, 0);
@@ -282,7 +307,7 @@ impl Board {
         }
 
         if let Some(captured) = self.piece_at(m.to()) {
-            let delta = self.remove_captured_piece(m.to(), captured, opp_idx);
+            let delta = self.remove_captured_piece(m.to(), captured);
             (Some(captured), delta)
         } else {
             (None, 0)
@@ -290,30 +315,18 @@ impl Board {
     }
 
     /// Place the moving piece (and rook for castling), updating eval and returning hash delta.
-    fn place_moving_piece(
-        &mut self,
-        m: Move,
-        color: Color,
-        moving_piece: Piece,
-        c_idx: usize,
-        is_white: bool,
-    ) -> u64 {
+    fn place_moving_piece(&mut self, m: Move, color: Color, moving_piece: Piece) -> u64 {
         if m.is_castling() {
             let king_hash = ZOBRIST.piece_keys[piece_to_zobrist_index(Piece::King)]
                 [color_to_zobrist_index(color)][square_to_zobrist_index(m.to())];
             // execute_castling places king and rook plus eval updates
-            return king_hash ^ self.execute_castling(&m, color, c_idx, is_white);
+            return king_hash ^ self.execute_castling(&m, color);
         }
 
         let piece_to_place = m.promotion().unwrap_or(moving_piece);
         self.set_piece(m.to(), color, piece_to_place);
 
-        let placed_idx = piece_to_place.index();
-        let to_idx = m.to().index();
-        let to_pst = pst_square(to_idx, is_white);
-        self.eval_mg[c_idx] += MATERIAL_MG[placed_idx] + PST_MG[placed_idx][to_pst];
-        self.eval_eg[c_idx] += MATERIAL_EG[placed_idx] + PST_EG[placed_idx][to_pst];
-        self.game_phase[c_idx] += PHASE_WEIGHTS[placed_idx];
+        self.add_piece_eval(color, piece_to_place, m.to());
 
         ZOBRIST.piece_keys[piece_to_zobrist_index(piece_to_place)][color_to_zobrist_index(color)]
             [square_to_zobrist_index(m.to())]
@@ -340,13 +353,6 @@ impl Board {
         }
     }
 
-    /// Record repetition info and return the previous count.
-    fn record_repetition(&mut self, made_hash: u64) -> u32 {
-        let previous_repetition_count = self.repetition_counts.get(made_hash);
-        self.repetition_counts.increment(made_hash);
-        previous_repetition_count
-    }
-
     // =========================================================================
     // Core make/unmake implementation
     // =========================================================================
@@ -364,9 +370,6 @@ impl Board {
         let previous_game_phase = self.game_phase;
 
         let color = self.side_to_move();
-        let c_idx = color.index();
-        let opp_idx = 1 - c_idx;
-        let is_white = color == Color::White;
 
         // Flip side to move in hash
         current_hash ^= ZOBRIST.black_to_move_key;
@@ -377,15 +380,12 @@ impl Board {
         }
 
         // Handle captures
-        let (captured_piece_info, capture_hash_delta) =
-            self.capture_piece_for_move(m, is_white, opp_idx);
+        let (captured_piece_info, capture_hash_delta) = self.capture_piece_for_move(m, color);
         current_hash ^= capture_hash_delta;
 
         // Get moving piece info and remove from source square
         let moving_piece_info = self.piece_at(m.from()).expect("make_move 'from' empty");
         let (moving_color, moving_piece) = moving_piece_info;
-        let piece_idx = moving_piece.index();
-        let from_idx = m.from().index();
         // Remove moving piece from hash
         current_hash ^= ZOBRIST.piece_keys[piece_to_zobrist_index(moving_piece)]
             [color_to_zobrist_index(moving_color)][square_to_zobrist_index(m.from())];
@@ -394,12 +394,9 @@ impl Board {
         self.remove_piece(m.from(), moving_color, moving_piece);
 
         // Update eval: remove piece from 'from' square
-        let from_pst = pst_square(from_idx, is_white);
-        self.eval_mg[c_idx] -= MATERIAL_MG[piece_idx] + PST_MG[piece_idx][from_pst];
-        self.eval_eg[c_idx] -= MATERIAL_EG[piece_idx] + PST_EG[piece_idx][from_pst];
-        self.game_phase[c_idx] -= PHASE_WEIGHTS[piece_idx];
+        self.remove_piece_eval(moving_color, moving_piece, m.from());
 
-        current_hash ^= self.place_moving_piece(m, color, moving_piece, c_idx, is_white);
+        current_hash ^= self.place_moving_piece(m, color, moving_piece);
 
         // Handle double pawn push - set new en passant target
         current_hash ^= self.update_en_passant_target(m);
@@ -413,8 +410,16 @@ impl Board {
         self.white_to_move = !self.white_to_move;
         self.hash = current_hash;
 
-        let made_hash = current_hash;
-        let previous_repetition_count = self.record_repetition(made_hash);
+        let previous_checkers = self.checkers;
+        self.refresh_checkers();
+
+        let previous_checks_given = self.checks_given;
+        if self.variant == Variant::ThreeCheck && !self.checkers.is_empty() {
+            self.hash ^= self.count_check(color);
+        }
+
+        let made_hash = self.hash;
+        self.repetition_counts.increment(made_hash);
 
         UnmakeInfo {
             captured_piece_info,
@@ -423,10 +428,11 @@ impl Board {
             previous_hash,
             previous_halfmove_clock,
             made_hash,
-            previous_repetition_count,
             previous_eval_mg,
             previous_eval_eg,
             previous_game_phase,
+            previous_checkers,
+            previous_checks_given,
         }
     }
 
@@ -443,9 +449,14 @@ impl Board {
         self.white_to_move = !self.white_to_move;
         self.hash = current_hash;
 
+        // The side passing cannot be giving check in a legal position
+        let previous_checkers = self.checkers;
+        self.checkers = super::Bitboard(0);
+
         NullMoveInfo {
             previous_en_passant_target,
             previous_hash,
+            previous_checkers,
         }
     }
 
@@ -493,14 +504,15 @@ impl Board {
     }
 
     pub(crate) fn unmake_move(&mut self, m: Move, info: UnmakeInfo) {
-        self.repetition_counts
-            .set(info.made_hash, info.previous_repetition_count);
+        self.repetition_counts.decrement(info.made_hash);
 
         self.white_to_move = !self.white_to_move;
         self.en_passant_target = info.previous_en_passant_target;
         self.castling_rights = info.previous_castling_rights;
         self.hash = info.previous_hash;
         self.halfmove_clock = info.previous_halfmove_clock;
+        self.checkers = info.previous_checkers;
+        self.checks_given = info.previous_checks_given;
 
         // Restore incremental eval
         self.eval_mg = info.previous_eval_mg;
@@ -520,5 +532,6 @@ impl Board {
         self.white_to_move = !self.white_to_move;
         self.en_passant_target = info.previous_en_passant_target;
         self.hash = info.previous_hash;
+        self.checkers = info.previous_checkers;
     }
 }
@@ -261,40 +261,16 @@ pub const PAWN_SHIELD_MASK: [[Bitboard; 8]; 2] = {
 /// 7th rank for each color (rank where rooks are powerful)
 pub const RANK_7TH: [Bitboard; 2] = [Bitboard::RANK_7, Bitboard::RANK_2];
 
-/// Helper: fill north from a bitboard (flood fill)
-#[inline]
-pub const fn fill_north(mut bb: u64) -> u64 {
-    bb |= bb << 8;
-    bb |= bb << 16;
-    bb |= bb << 32;
-    bb
-}
-
-/// Helper: fill south from a bitboard (flood fill)
-#[inline]
-pub const fn fill_south(mut bb: u64) -> u64 {
-    bb |= bb >> 8;
-    bb |= bb >> 16;
-    bb |= bb >> 32;
-    bb
-}
-
 /// Get forward fill for a color
 #[inline]
 pub fn fill_forward(bb: Bitboard, color: Color) -> Bitboard {
-    match color {
-        Color::White => Bitboard(fill_north(bb.0)),
-        Color::Black => Bitboard(fill_south(bb.0)),
-    }
+    bb.fill_forward(color)
 }
 
 /// Get backward fill for a color (opposite of forward)
 #[inline]
 pub fn fill_backward(bb: Bitboard, color: Color) -> Bitboard {
-    match color {
-        Color::White => Bitboard(fill_south(bb.0)),
-        Color::Black => Bitboard(fill_north(bb.0)),
-    }
+    bb.fill_backward(color)
 }
 
 /// Passed pawn bonus by rank (from the pawn's perspective)
@@ -385,15 +361,15 @@ mod tests {
     #[test]
     fn test_fill_north() {
         // Single bit on a1 should fill the entire a-file
-        let filled = fill_north(1);
-        assert_eq!(filled, Bitboard::FILE_A.0);
+        let filled = fill_forward(Bitboard(1), Color::White);
+        assert_eq!(filled, Bitboard::FILE_A);
     }
 
     #[test]
     fn test_fill_south() {
         // Single bit on a8 should fill the entire a-file
-        let filled = fill_south(1u64 << 56);
-        assert_eq!(filled, Bitboard::FILE_A.0);
+        let filled = fill_forward(Bitboard(1u64 << 56), Color::Black);
+        assert_eq!(filled, Bitboard::FILE_A);
     }
 
     #[test]
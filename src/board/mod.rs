@@ -14,8 +14,10 @@
 
 mod attack_tables;
 mod builder;
+mod bytes;
 #[cfg(debug_assertions)]
 mod debug;
+mod edit;
 mod error;
 mod eval;
 mod eval_terms;
@@ -25,6 +27,7 @@ mod make_unmake;
 mod masks;
 mod movegen;
 pub mod nnue;
+mod outcome;
 pub mod prelude;
 mod pst;
 mod san;
@@ -32,28 +35,38 @@ pub mod search;
 mod see;
 mod state;
 mod types;
+mod variant;
 
 #[cfg(test)]
 mod tests;
 
 // Public API - types users need
 pub use builder::BoardBuilder;
-pub use error::{FenError, MoveParseError, SanError, SquareError};
+pub use error::{BuilderError, BytesError, FenError, MoveParseError, SanError, SquareError};
+pub use eval_terms::{BadBishopWeights, ImbalanceTable, SpaceWeights, IMBALANCE_TERMS};
+pub use movegen::{PerftStats, PerftTable};
+pub use outcome::{GameResult, TerminationReason};
 pub use state::Board;
+pub use types::bitboard;
 pub use types::{Bitboard, CastlingRights, Color, Move, MoveList, MoveListIntoIter, Piece, Square};
+pub use variant::{Variant, CHECKS_TO_WIN};
 
 // Public API - search functions and configuration
 pub use search::{
     find_best_move, find_best_move_with_ponder, find_best_move_with_time,
-    find_best_move_with_time_and_ponder, search, SearchClock, SearchConfig, SearchInfoCallback,
-    SearchIterationInfo, SearchLimits, SearchResult, SearchState, DEFAULT_TT_MB,
+    find_best_move_with_time_and_ponder, quiescence_search, search, IterationStats, SearchClock,
+    SearchConfig, SearchInfoCallback, SearchIterationInfo, SearchLimits, SearchResult, SearchState,
+    WdlProbabilities, DEFAULT_TT_MB,
 };
 
 // Internal types exposed for advanced usage (but not in prelude)
 pub use state::{NullMoveInfo, UnmakeInfo};
 
 // Re-export search internals for users who need fine-grained control
-pub use search::{SearchParams, SearchStats, SearchTables};
+pub use search::{
+    MemoryUsage, PruneAudit, PruneAuditCount, PruneAuditStats, PruneKind, RootNoise,
+    RootNoiseConfig, SearchParams, SearchStats, SearchTables, MAX_ROOT_NOISE,
+};
 
 pub(crate) use types::{
     bit_for_square, castle_bit, file_to_index, rank_to_index, ScoredMoveList, ALL_CASTLING_RIGHTS,
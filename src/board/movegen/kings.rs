@@ -1,5 +1,8 @@
-use super::super::attack_tables::{slider_attacks, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
-use super::super::{Bitboard, Board, Color, MoveList, Piece, Square};
+use super::super::attack_tables::{
+    bishop_attacks, rook_attacks, slider_attacks, BETWEEN, KING_ATTACKS, KNIGHT_ATTACKS,
+    PAWN_ATTACKS,
+};
+use super::super::{Bitboard, Board, Color, Move, MoveList, Piece, Square};
 
 impl Board {
     pub(crate) fn generate_king_moves(&self, from: Square) -> MoveList {
@@ -40,14 +43,28 @@ impl Board {
 
     /// Get the cached king square for a color.
     /// This is O(1) instead of iterating the bitboard.
-    /// Returns Option for API compatibility with callers checking for illegal positions.
+    /// Returns `None` for king-less sides (sparring positions), in which case
+    /// that side can never be in check and has no castling moves.
     #[inline]
-    #[allow(clippy::unnecessary_wraps)]
     pub(crate) fn find_king(&self, color: Color) -> Option<Square> {
+        if self.pieces_of(color, Piece::King).is_empty() {
+            return None;
+        }
         // Use cached king square - much faster than iterating bitboard
         Some(self.king_square[color.index()])
     }
 
+    /// Whether `color` has a king on the board.
+    ///
+    /// King-less positions are supported for material drills: the king-less
+    /// side is never in check, so search and evaluation work as usual but
+    /// checkmate is impossible for that side.
+    #[inline]
+    #[must_use]
+    pub fn has_king(&self, color: Color) -> bool {
+        !self.pieces_of(color, Piece::King).is_empty()
+    }
+
     pub(crate) fn is_square_attacked(&self, square: Square, attacker_color: Color) -> bool {
         let target_idx = square.index();
 
@@ -83,7 +100,164 @@ impl Board {
         false
     }
 
+    /// Enemy pieces currently giving check to `color`'s king.
+    pub(crate) fn checkers_of(&self, color: Color) -> Bitboard {
+        let Some(king_sq) = self.find_king(color) else {
+            return Bitboard::EMPTY;
+        };
+        let k = king_sq.index();
+        let them = color.opponent();
+        let occ = self.all_occupied.0;
+
+        let pawns = PAWN_ATTACKS[color.index()][k] & self.pieces_of(them, Piece::Pawn).0;
+        let knights = KNIGHT_ATTACKS[k] & self.pieces_of(them, Piece::Knight).0;
+        let rook_like = self.pieces_of(them, Piece::Rook).0 | self.pieces_of(them, Piece::Queen).0;
+        let bishop_like =
+            self.pieces_of(them, Piece::Bishop).0 | self.pieces_of(them, Piece::Queen).0;
+        let sliders = (rook_attacks(k, occ) & rook_like) | (bishop_attacks(k, occ) & bishop_like);
+
+        Bitboard(pawns | knights | sliders)
+    }
+
+    /// Whether `m`, a legal move for the side to move, gives check.
+    ///
+    /// Computed from the position after the move without making it, so
+    /// direct checks, discovered checks (including through an en passant
+    /// capture), checking promotions and checks by a castling rook are all
+    /// found.
+    #[must_use]
+    pub fn gives_check(&self, m: Move) -> bool {
+        let us = self.side_to_move();
+        let Some(king_sq) = self.find_king(us.opponent()) else {
+            return false;
+        };
+        let Some((_, moving)) = self.piece_at(m.from()) else {
+            return false;
+        };
+        let k = king_sq.index();
+        let from = 1u64 << m.from().index();
+        let to = 1u64 << m.to().index();
+
+        let mut occ = (self.all_occupied.0 & !from) | to;
+        let mut ours = [0u64; 6];
+        for piece in Piece::ALL {
+            ours[piece.index()] = self.pieces_of(us, piece).0 & !from;
+        }
+        ours[m.promotion().unwrap_or(moving).index()] |= to;
+
+        if m.is_en_passant() {
+            let captured = match us {
+                Color::White => to >> 8,
+                Color::Black => to << 8,
+            };
+            occ &= !captured;
+        } else if m.is_castling() {
+            let rank = m.to().rank();
+            let (rook_from, rook_to) = if m.to().file() == 6 { (7, 5) } else { (0, 3) };
+            let rook_from = 1u64 << Square::new(rank, rook_from).index();
+            let rook_to = 1u64 << Square::new(rank, rook_to).index();
+            occ = (occ & !rook_from) | rook_to;
+            ours[Piece::Rook.index()] = (ours[Piece::Rook.index()] & !rook_from) | rook_to;
+        }
+
+        let queens = ours[Piece::Queen.index()];
+        let rook_like = ours[Piece::Rook.index()] | queens;
+        let bishop_like = ours[Piece::Bishop.index()] | queens;
+        PAWN_ATTACKS[us.opponent().index()][k] & ours[Piece::Pawn.index()] != 0
+            || KNIGHT_ATTACKS[k] & ours[Piece::Knight.index()] != 0
+            || rook_attacks(k, occ) & rook_like != 0
+            || bishop_attacks(k, occ) & bishop_like != 0
+    }
+
+    /// Pieces of `color` that are absolutely pinned to their own king.
+    pub(crate) fn pinned_pieces(&self, color: Color) -> Bitboard {
+        let Some(king_sq) = self.find_king(color) else {
+            return Bitboard::EMPTY;
+        };
+        let k = king_sq.index();
+        let them = color.opponent();
+        let own = self.occupied_by(color).0;
+
+        let rook_like = self.pieces_of(them, Piece::Rook).0 | self.pieces_of(them, Piece::Queen).0;
+        let bishop_like =
+            self.pieces_of(them, Piece::Bishop).0 | self.pieces_of(them, Piece::Queen).0;
+        // Enemy sliders that would hit the king on an empty board
+        let snipers = (rook_attacks(k, 0) & rook_like) | (bishop_attacks(k, 0) & bishop_like);
+
+        let mut pinned = 0u64;
+        for sniper in Bitboard(snipers).iter() {
+            let blockers = BETWEEN[k][sniper.index()] & self.all_occupied.0;
+            if blockers.is_power_of_two() && blockers & own != 0 {
+                pinned |= blockers;
+            }
+        }
+        Bitboard(pinned)
+    }
+
+    /// Number of legal moves of the side to move when in check, counted
+    /// from the checkers without generating the moves: king steps to safe
+    /// squares, and against a single checker its capture or a block by an
+    /// unpinned piece. En passant captures are counted without testing for
+    /// a discovered check.
+    pub(crate) fn count_evasions(&self) -> usize {
+        let us = self.side_to_move();
+        let them = us.opponent();
+        let Some(king_sq) = self.find_king(us) else {
+            return 0;
+        };
+        let k = king_sq.index();
+        let own = self.occupied_by(us).0;
+        let enemy = self.occupied_by(them).0;
+        let without_king = Bitboard(self.all_occupied.0 & !(1u64 << k));
+
+        let mut count = Bitboard(KING_ATTACKS[k] & !own)
+            .iter()
+            .filter(|&sq| self.attackers_to(sq, without_king).0 & enemy == 0)
+            .count();
+
+        let checkers = self.checkers();
+        if !checkers.is_single() {
+            return count;
+        }
+        let checker = checkers.iter().next().expect("one checker");
+        let movable = own & !self.pinned_pieces(us).0;
+        let pawns = self.pieces_of(us, Piece::Pawn).0 & movable;
+        let pieces = movable & !self.pieces_of(us, Piece::Pawn).0 & !(1u64 << k);
+        let blocks = BETWEEN[k][checker.index()];
+        let promotion_rank = Bitboard::rank_mask(them.back_rank()).0;
+        // A pawn move to the last rank is four moves, one per promotion
+        let pawn_moves = |targets: u64| {
+            (targets.count_ones() + 3 * (targets & promotion_rank).count_ones()) as usize
+        };
+
+        for target in Bitboard(blocks | checkers.0).iter() {
+            let attackers = self.attackers_to(target, self.all_occupied).0;
+            count += (attackers & pieces).count_ones() as usize;
+        }
+        let capturing_pawns = checkers.pawn_attacks(them).0 & pawns;
+        count += capturing_pawns.count_ones() as usize * pawn_moves(checkers.0);
+
+        let single_pushes = Bitboard(blocks).shift_forward(them).0 & pawns;
+        count += pawn_moves(Bitboard(single_pushes).shift_forward(us).0);
+        let double_push_rank = Bitboard::rank_mask(if us == Color::White { 3 } else { 4 });
+        let skipped =
+            Bitboard(blocks & double_push_rank.0).shift_forward(them).0 & !self.all_occupied.0;
+        count += (Bitboard(skipped).shift_forward(them).0 & pawns).count_ones() as usize;
+
+        // A checking pawn that just advanced two squares
+        if let Some(ep) = self.en_passant_target {
+            let ep = Bitboard::from_square(ep);
+            if ep.shift_forward(them).0 == checkers.0 {
+                count += (ep.pawn_attacks(them).0 & pawns).count_ones() as usize;
+            }
+        }
+        count
+    }
+
     pub(crate) fn is_in_check(&self, color: Color) -> bool {
+        if color == self.side_to_move() {
+            return self.in_check();
+        }
         if let Some(king_sq) = self.find_king(color) {
             self.is_square_attacked(king_sq, color.opponent())
         } else {
@@ -1,10 +1,50 @@
 mod kings;
 mod knights;
 mod pawns;
+mod perft;
 mod sliders;
 
+pub use self::perft::{PerftStats, PerftTable};
 use self::sliders::SliderType;
-use super::{Board, Move, MoveList, Piece, Square};
+use super::attack_tables::{
+    aligned, bishop_attacks, queen_attacks, rook_attacks, BETWEEN, KING_ATTACKS, KNIGHT_ATTACKS,
+    PAWN_ATTACKS,
+};
+use super::{Bitboard, Board, Move, MoveList, Piece, Square};
+
+/// Pin and check information for the side to move, used to decide most
+/// moves' legality without making them.
+struct LegalityMasks {
+    king: Option<Square>,
+    pinned: Bitboard,
+    /// Destinations that resolve the current check (everything if not in check,
+    /// nothing in double check).
+    check_mask: Bitboard,
+}
+
+impl LegalityMasks {
+    /// Returns `Some(legal)` when legality follows from the masks alone, or
+    /// `None` for king moves and en passant, which need make/unmake.
+    #[inline]
+    fn fast_legality(&self, m: Move) -> Option<bool> {
+        let from = m.from();
+        if Some(from) == self.king || m.is_en_passant() {
+            return None;
+        }
+        // Only reachable without a king: every pseudo-legal move is legal
+        let Some(king) = self.king else {
+            return Some(true);
+        };
+        let to = m.to();
+        if !self.check_mask.contains(to) {
+            return Some(false);
+        }
+        if self.pinned.contains(from) && !aligned(king.index(), from.index(), to.index()) {
+            return Some(false);
+        }
+        Some(true)
+    }
+}
 
 impl Board {
     fn generate_pseudo_moves(&self) -> MoveList {
@@ -21,6 +61,22 @@ impl Board {
         moves
     }
 
+    /// In double check only the king can move, so skip every other piece.
+    #[inline]
+    fn in_double_check(&self) -> bool {
+        self.checkers().popcount() > 1
+    }
+
+    fn generate_king_only_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        for from in self.pieces_of(self.side_to_move(), Piece::King).iter() {
+            for m in &self.generate_king_moves(from) {
+                moves.push(*m);
+            }
+        }
+        moves
+    }
+
     fn generate_piece_moves(&self, from: Square, piece: Piece) -> MoveList {
         match piece {
             Piece::Pawn => self.generate_pawn_moves(from),
@@ -62,11 +118,48 @@ impl Board {
         }
     }
 
+    fn legality_masks(&self) -> LegalityMasks {
+        let color = self.side_to_move();
+        let king = self.find_king(color);
+        let checkers = self.checkers();
+        let check_mask = match (king, checkers.popcount()) {
+            (_, 0) | (None, _) => Bitboard::ALL,
+            (Some(king_sq), 1) => {
+                let checker = checkers.0.trailing_zeros() as usize;
+                Bitboard(checkers.0 | BETWEEN[king_sq.index()][checker])
+            }
+            _ => Bitboard::EMPTY,
+        };
+        LegalityMasks {
+            king,
+            pinned: self.pinned_pieces(color),
+            check_mask,
+        }
+    }
+
+    /// Legality test for a pseudo-legal, non-castling move, falling back to
+    /// make/unmake only when the pin/check masks cannot decide.
+    fn is_pseudo_legal_move_legal(&mut self, masks: &LegalityMasks, m: Move) -> bool {
+        if let Some(legal) = masks.fast_legality(m) {
+            return legal;
+        }
+        let current_color = self.side_to_move();
+        let info = self.make_move(m);
+        let legal = !self.is_in_check(current_color);
+        self.unmake_move(m, info);
+        legal
+    }
+
     #[must_use]
     pub fn generate_moves(&mut self) -> MoveList {
         let current_color = self.side_to_move();
         let opponent_color = current_color.opponent();
-        let pseudo_moves = self.generate_pseudo_moves();
+        let pseudo_moves = if self.in_double_check() {
+            self.generate_king_only_moves()
+        } else {
+            self.generate_pseudo_moves()
+        };
+        let masks = self.legality_masks();
         let mut legal_moves = MoveList::new();
 
         for m in &pseudo_moves {
@@ -85,15 +178,52 @@ impl Board {
                 }
             }
 
-            let info = self.make_move(*m);
-            if !self.is_in_check(current_color) {
+            if self.is_pseudo_legal_move_legal(&masks, *m) {
                 legal_moves.push(*m);
             }
-            self.unmake_move(*m, info);
         }
         legal_moves
     }
 
+    /// Legal captures, including en passant and capturing promotions
+    #[must_use]
+    pub fn generate_captures(&mut self) -> MoveList {
+        let mut captures = MoveList::new();
+        for m in &self.generate_tactical_moves() {
+            if m.is_capture() {
+                captures.push(*m);
+            }
+        }
+        captures
+    }
+
+    /// Legal non-captures: quiet moves, castling and non-capturing
+    /// promotions. Together with [`Board::generate_captures`] these are
+    /// exactly the moves of [`Board::generate_moves`].
+    #[must_use]
+    pub fn generate_quiets(&mut self) -> MoveList {
+        let mut quiets = MoveList::new();
+        for m in &self.generate_moves() {
+            if !m.is_capture() {
+                quiets.push(*m);
+            }
+        }
+        quiets
+    }
+
+    /// Legal quiet moves that give check: non-captures other than
+    /// promotions, which quiescence search already covers with the captures
+    #[must_use]
+    pub fn generate_quiet_checks(&mut self) -> MoveList {
+        let mut checks = MoveList::new();
+        for m in &self.generate_quiets() {
+            if !m.is_promotion() && self.gives_check(*m) {
+                checks.push(*m);
+            }
+        }
+        checks
+    }
+
     #[must_use]
     pub fn is_checkmate(&mut self) -> bool {
         let color = self.side_to_move();
@@ -106,34 +236,95 @@ impl Board {
         !self.is_in_check(color) && self.generate_moves().is_empty()
     }
 
-    /// Check if a move is legal without generating all moves.
+    /// Cheap pseudo-legality check for a move taken from a table (TT,
+    /// killers, counter moves) that may belong to another position.
     ///
-    /// This is faster than `generate_moves().contains(&mv)` when you only need
-    /// to validate a single move (e.g., from TT or user input).
+    /// Accepts exactly the moves the generator produces for the side to move
+    /// before checks and pins are considered, using attack lookups rather
+    /// than generating the piece's moves. Only castling falls back to the
+    /// king's move generator.
     #[must_use]
-    pub fn is_legal_move(&mut self, mv: Move) -> bool {
-        let from = mv.from();
-        let current_color = self.side_to_move();
-
-        // Check that there's a piece of the right color on the from square
-        let Some((piece_color, piece)) = self.piece_at(from) else {
+    pub fn is_pseudo_legal(&self, mv: Move) -> bool {
+        let (from, to) = (mv.from(), mv.to());
+        let us = self.side_to_move();
+        let Some((color, piece)) = self.piece_at(from) else {
             return false;
         };
-        if piece_color != current_color {
+        if color != us || from == to {
+            return false;
+        }
+        let target = self.piece_at(to);
+        if target.is_some_and(|(c, _)| c == us) {
+            return false;
+        }
+        let to_bb = 1u64 << to.index();
+        let pawn_attacks = PAWN_ATTACKS[us.index()][from.index()];
+
+        if mv.is_castling() {
+            return piece == Piece::King && self.generate_king_moves(from).iter().any(|m| *m == mv);
+        }
+        if mv.is_en_passant() {
+            return piece == Piece::Pawn
+                && self.en_passant_target == Some(to)
+                && pawn_attacks & to_bb != 0;
+        }
+        // The capture flag must match what stands on the target square
+        if mv.is_capture() != target.is_some() {
             return false;
         }
 
-        // Generate pseudo-moves for just this piece
-        let piece_moves = self.generate_piece_moves(from, piece);
+        if piece != Piece::Pawn {
+            if mv.is_promotion() || mv.is_double_pawn_push() {
+                return false;
+            }
+            let occupied = self.all_occupied.0;
+            let attacks = match piece {
+                Piece::Knight => KNIGHT_ATTACKS[from.index()],
+                Piece::Bishop => bishop_attacks(from.index(), occupied),
+                Piece::Rook => rook_attacks(from.index(), occupied),
+                Piece::Queen => queen_attacks(from.index(), occupied),
+                Piece::King => KING_ATTACKS[from.index()],
+                Piece::Pawn => unreachable!(),
+            };
+            return attacks & to_bb != 0;
+        }
 
-        // Check if the move matches any pseudo-legal move
-        if !piece_moves.iter().any(|m| *m == mv) {
+        // Pawns promote exactly when reaching the last rank
+        if mv.is_promotion() != (to.rank() == us.pawn_promotion_rank()) {
+            return false;
+        }
+        if target.is_some() {
+            return pawn_attacks & to_bb != 0;
+        }
+        if to.file() != from.file() {
+            return false;
+        }
+        let single = from.rank() as isize + us.pawn_direction();
+        if to.rank() as isize == single {
+            return !mv.is_double_pawn_push();
+        }
+        mv.is_double_pawn_push()
+            && from.rank() == us.pawn_start_rank()
+            && to.rank() as isize == single + us.pawn_direction()
+            && self.is_empty(Square::new(single as usize, from.file()))
+    }
+
+    /// Check if a move is legal without generating all moves.
+    ///
+    /// This is faster than `generate_moves().contains(&mv)` when you only need
+    /// to validate a single move (e.g., from TT or user input). Moves that
+    /// fail [`Board::is_pseudo_legal`] are rejected before any pin or check
+    /// work.
+    #[must_use]
+    pub fn is_legal_move(&mut self, mv: Move) -> bool {
+        if !self.is_pseudo_legal(mv) {
             return false;
         }
 
         // For castling, check that king doesn't pass through check
         if mv.is_castling() {
-            let opponent_color = current_color.opponent();
+            let from = mv.from();
+            let opponent_color = self.side_to_move().opponent();
             let to = mv.to();
             let king_mid_sq = Square::new(from.rank(), usize::midpoint(from.file(), to.file()));
 
@@ -145,12 +336,8 @@ impl Board {
             }
         }
 
-        // Make the move and check if king is left in check
-        let info = self.make_move(mv);
-        let legal = !self.is_in_check(current_color);
-        self.unmake_move(mv, info);
-
-        legal
+        let masks = self.legality_masks();
+        self.is_pseudo_legal_move_legal(&masks, mv)
     }
 
     /// Filter and collect capture moves from a piece's move list
@@ -166,56 +353,39 @@ impl Board {
         let current_color = self.side_to_move();
         let mut pseudo_tactical_moves = MoveList::new();
 
-        // Pawns have special tactical move generation (includes promotions)
-        for from in self.pieces_of(current_color, Piece::Pawn).iter() {
-            self.generate_pawn_tactical_moves(from, &mut pseudo_tactical_moves);
-        }
-
-        // For other pieces, filter captures from their normal moves
-        for piece in [
-            Piece::Knight,
-            Piece::Bishop,
-            Piece::Rook,
-            Piece::Queen,
-            Piece::King,
-        ] {
-            for from in self.pieces_of(current_color, piece).iter() {
-                let piece_moves = self.generate_piece_moves(from, piece);
-                Self::collect_captures(&piece_moves, &mut pseudo_tactical_moves);
+        if self.in_double_check() {
+            let king_moves = self.generate_king_only_moves();
+            Self::collect_captures(&king_moves, &mut pseudo_tactical_moves);
+        } else {
+            // Pawns have special tactical move generation (includes promotions)
+            for from in self.pieces_of(current_color, Piece::Pawn).iter() {
+                self.generate_pawn_tactical_moves(from, &mut pseudo_tactical_moves);
+            }
+
+            // For other pieces, filter captures from their normal moves
+            for piece in [
+                Piece::Knight,
+                Piece::Bishop,
+                Piece::Rook,
+                Piece::Queen,
+                Piece::King,
+            ] {
+                for from in self.pieces_of(current_color, piece).iter() {
+                    let piece_moves = self.generate_piece_moves(from, piece);
+                    Self::collect_captures(&piece_moves, &mut pseudo_tactical_moves);
+                }
             }
         }
 
         // Filter for legality
+        let masks = self.legality_masks();
         let mut legal_tactical_moves = MoveList::new();
         for m in &pseudo_tactical_moves {
-            let info = self.make_move(*m);
-            if !self.is_in_check(current_color) {
+            if self.is_pseudo_legal_move_legal(&masks, *m) {
                 legal_tactical_moves.push(*m);
             }
-            self.unmake_move(*m, info);
         }
 
         legal_tactical_moves
     }
-
-    #[must_use]
-    pub fn perft(&mut self, depth: usize) -> u64 {
-        if depth == 0 {
-            return 1;
-        }
-
-        let moves = self.generate_moves();
-        if depth == 1 {
-            return moves.len() as u64;
-        }
-
-        let mut nodes = 0;
-        for m in &moves {
-            let info = self.make_move(*m);
-            nodes += self.perft(depth - 1);
-            self.unmake_move(*m, info);
-        }
-
-        nodes
-    }
 }
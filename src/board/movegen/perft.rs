@@ -0,0 +1,205 @@
+//! Perft: counting the leaf nodes of the legal move tree.
+//!
+//! [`Board::perft`] bulk-counts the last ply: the legal move generator
+//! already filters illegal moves, so leaf moves are counted without being
+//! made. Deep perft runs revisit the same positions through transpositions
+//! many times, so [`Board::perft_hashed`] caches subtree counts in a
+//! [`PerftTable`] keyed by (hash, depth). [`Board::perft_with_stats`] breaks
+//! the leaf moves down the way published perft tables do, which localizes a
+//! movegen bug to a move type, and [`Board::perft_captures`] walks only
+//! capture sequences, as quiescence search does.
+
+use std::mem;
+use std::ops::AddAssign;
+
+use super::super::Board;
+
+/// Leaf move counts by move type, as in published perft tables.
+///
+/// Captures include en passant and capturing promotions; checks include
+/// checkmates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passant: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub checkmates: u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passant += other.en_passant;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+/// One cached subtree count
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    hash: u64,
+    depth: u32,
+    nodes: u64,
+}
+
+/// Transposition table for perft, separate from the search TT.
+///
+/// Entries are always replaced. A (hash, depth) pair identifies a subtree
+/// exactly up to Zobrist collisions, which are vanishingly rare with 64-bit
+/// keys.
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+    mask: usize,
+}
+
+impl PerftTable {
+    /// Create a table using about `size_mb` megabytes
+    #[must_use]
+    pub fn new(size_mb: usize) -> Self {
+        let wanted = (size_mb * 1024 * 1024) / mem::size_of::<PerftEntry>();
+        let num_entries = wanted.next_power_of_two().max(1024) / 2;
+        PerftTable {
+            entries: vec![PerftEntry::default(); num_entries],
+            mask: num_entries - 1,
+        }
+    }
+
+    /// Mix the depth into the index so one position's subtrees at different
+    /// depths don't evict each other
+    fn index(&self, hash: u64, depth: usize) -> usize {
+        ((hash ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)) as usize) & self.mask
+    }
+
+    fn probe(&self, hash: u64, depth: usize) -> Option<u64> {
+        let entry = &self.entries[self.index(hash, depth)];
+        (entry.hash == hash && entry.depth == depth as u32).then_some(entry.nodes)
+    }
+
+    fn store(&mut self, hash: u64, depth: usize, nodes: u64) {
+        let index = self.index(hash, depth);
+        self.entries[index] = PerftEntry {
+            hash,
+            depth: depth as u32,
+            nodes,
+        };
+    }
+}
+
+impl Board {
+    #[must_use]
+    pub fn perft(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.generate_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for m in &moves {
+            let info = self.make_move(*m);
+            nodes += self.perft(depth - 1);
+            self.unmake_move(*m, info);
+        }
+
+        nodes
+    }
+
+    /// Perft that caches subtree counts in `table`.
+    ///
+    /// Returns the same count as [`Board::perft`]; the table can be reused
+    /// across calls and positions.
+    #[must_use]
+    pub fn perft_hashed(&mut self, depth: usize, table: &mut PerftTable) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        if depth > 1 {
+            if let Some(nodes) = table.probe(self.hash, depth) {
+                return nodes;
+            }
+        }
+
+        let moves = self.generate_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for m in &moves {
+            let info = self.make_move(*m);
+            nodes += self.perft_hashed(depth - 1, table);
+            self.unmake_move(*m, info);
+        }
+
+        table.store(self.hash, depth, nodes);
+        nodes
+    }
+
+    /// Perft over capture sequences: counts the leaves of the tree in which
+    /// every move is a capture, using [`Board::generate_captures`].
+    #[must_use]
+    pub fn perft_captures(&mut self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let captures = self.generate_captures();
+        if depth == 1 {
+            return captures.len() as u64;
+        }
+
+        let mut nodes = 0;
+        for m in &captures {
+            let info = self.make_move(*m);
+            nodes += self.perft_captures(depth - 1);
+            self.unmake_move(*m, info);
+        }
+
+        nodes
+    }
+
+    /// Perft that also classifies the leaf moves.
+    ///
+    /// Slower than [`Board::perft`], since every leaf move is made to detect
+    /// checks and checkmates.
+    #[must_use]
+    pub fn perft_with_stats(&mut self, depth: usize) -> PerftStats {
+        let mut stats = PerftStats::default();
+        if depth == 0 {
+            stats.nodes = 1;
+            return stats;
+        }
+
+        let moves = self.generate_moves();
+        for m in &moves {
+            let info = self.make_move(*m);
+            if depth == 1 {
+                stats.nodes += 1;
+                stats.captures += u64::from(m.is_capture());
+                stats.en_passant += u64::from(m.is_en_passant());
+                stats.castles += u64::from(m.is_castling());
+                stats.promotions += u64::from(m.is_promotion());
+                if self.in_check() {
+                    stats.checks += 1;
+                    stats.checkmates += u64::from(self.generate_moves().is_empty());
+                }
+            } else {
+                stats += self.perft_with_stats(depth - 1);
+            }
+            self.unmake_move(*m, info);
+        }
+
+        stats
+    }
+}
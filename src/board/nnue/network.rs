@@ -148,6 +148,27 @@ impl NnueNetwork {
         })
     }
 
+    /// FNV-1a hash of all weights, identifying the network in bug reports
+    #[must_use]
+    pub fn checksum(&self) -> u64 {
+        let mut hash = 0xCBF2_9CE4_8422_2325_u64;
+        let weights = self
+            .feature_weights
+            .iter()
+            .flatten()
+            .chain(&self.feature_bias)
+            .chain(&self.output_weights_white)
+            .chain(&self.output_weights_black)
+            .chain(std::iter::once(&self.output_bias));
+        for w in weights {
+            for byte in w.to_le_bytes() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01B3);
+            }
+        }
+        hash
+    }
+
     /// Evaluate position given accumulator and side to move
     /// Returns evaluation in centipawns from side-to-move perspective
     #[inline]
@@ -212,6 +233,14 @@ impl NnueNetwork {
         Self::from_bytes(EMBEDDED_NETWORK).expect("Embedded NNUE is invalid")
     }
 
+    /// The embedded network, parsed once and shared by every engine instance
+    #[must_use]
+    pub fn shared_embedded() -> std::sync::Arc<Self> {
+        static SHARED: std::sync::LazyLock<std::sync::Arc<NnueNetwork>> =
+            std::sync::LazyLock::new(|| std::sync::Arc::new(NnueNetwork::from_embedded()));
+        std::sync::Arc::clone(&SHARED)
+    }
+
     /// Load network from byte slice
     pub fn from_bytes(data: &[u8]) -> std::io::Result<Self> {
         use std::io::Cursor;
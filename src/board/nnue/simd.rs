@@ -18,6 +18,29 @@ const QA: i16 = 255;
 // Public API - dispatches to platform-specific implementations
 // ============================================================================
 
+/// Name of the instruction set the functions below dispatch to on this machine
+#[must_use]
+pub fn simd_path() -> &'static str {
+    #[cfg(target_arch = "aarch64")]
+    {
+        "neon"
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cfg!(target_feature = "avx2") || is_x86_feature_detected!("avx2") {
+            "avx2"
+        } else {
+            "scalar"
+        }
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        "scalar"
+    }
+}
+
 /// Add weights to accumulator using SIMD when available.
 #[inline]
 pub fn add_weights(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
@@ -37,7 +60,7 @@ pub fn add_weights(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
         if is_x86_feature_detected!("avx2") {
             unsafe { add_weights_avx2(acc, weights) }
         } else {
-            add_weights_scalar(acc, weights)
+            add_weights_scalar(acc, weights);
         }
     }
 
@@ -65,7 +88,7 @@ pub fn sub_weights(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
         if is_x86_feature_detected!("avx2") {
             unsafe { sub_weights_avx2(acc, weights) }
         } else {
-            sub_weights_scalar(acc, weights)
+            sub_weights_scalar(acc, weights);
         }
     }
 
@@ -158,7 +181,10 @@ fn screlu_dot_scalar(acc: &[i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) ->
 #[cfg(target_arch = "aarch64")]
 unsafe fn add_weights_neon(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
     use std::arch::aarch64::{vld1q_s16, vqaddq_s16, vst1q_s16};
-    const _: () = assert!(HIDDEN_SIZE.is_multiple_of(8), "HIDDEN_SIZE must be divisible by 8 for NEON");
+    const _: () = assert!(
+        HIDDEN_SIZE.is_multiple_of(8),
+        "HIDDEN_SIZE must be divisible by 8 for NEON"
+    );
 
     let acc_ptr = acc.as_mut_ptr();
     let weights_ptr = weights.as_ptr();
@@ -175,7 +201,10 @@ unsafe fn add_weights_neon(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_
 #[cfg(target_arch = "aarch64")]
 unsafe fn sub_weights_neon(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
     use std::arch::aarch64::{vld1q_s16, vqsubq_s16, vst1q_s16};
-    const _: () = assert!(HIDDEN_SIZE.is_multiple_of(8), "HIDDEN_SIZE must be divisible by 8 for NEON");
+    const _: () = assert!(
+        HIDDEN_SIZE.is_multiple_of(8),
+        "HIDDEN_SIZE must be divisible by 8 for NEON"
+    );
 
     let acc_ptr = acc.as_mut_ptr();
     let weights_ptr = weights.as_ptr();
@@ -195,7 +224,10 @@ unsafe fn screlu_dot_neon(acc: &[i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]
         vget_low_s32, vgetq_lane_s64, vld1q_s16, vmaxq_s16, vminq_s16, vmovl_s16, vmovl_s32,
         vmulq_s32,
     };
-    const _: () = assert!(HIDDEN_SIZE.is_multiple_of(8), "HIDDEN_SIZE must be divisible by 8 for NEON");
+    const _: () = assert!(
+        HIDDEN_SIZE.is_multiple_of(8),
+        "HIDDEN_SIZE must be divisible by 8 for NEON"
+    );
 
     let acc_ptr = acc.as_ptr();
     let weights_ptr = weights.as_ptr();
@@ -249,9 +281,17 @@ unsafe fn screlu_dot_neon(acc: &[i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]
 
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
+#[allow(
+    clippy::wildcard_imports,
+    clippy::cast_ptr_alignment,
+    clippy::ptr_as_ptr
+)]
 unsafe fn add_weights_avx2(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
     use std::arch::x86_64::*;
-    const _: () = assert!(HIDDEN_SIZE.is_multiple_of(16), "HIDDEN_SIZE must be divisible by 16 for AVX2");
+    const _: () = assert!(
+        HIDDEN_SIZE.is_multiple_of(16),
+        "HIDDEN_SIZE must be divisible by 16 for AVX2"
+    );
 
     let acc_ptr = acc.as_mut_ptr();
     let weights_ptr = weights.as_ptr();
@@ -267,9 +307,17 @@ unsafe fn add_weights_avx2(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_
 
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
+#[allow(
+    clippy::wildcard_imports,
+    clippy::cast_ptr_alignment,
+    clippy::ptr_as_ptr
+)]
 unsafe fn sub_weights_avx2(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) {
     use std::arch::x86_64::*;
-    const _: () = assert!(HIDDEN_SIZE.is_multiple_of(16), "HIDDEN_SIZE must be divisible by 16 for AVX2");
+    const _: () = assert!(
+        HIDDEN_SIZE.is_multiple_of(16),
+        "HIDDEN_SIZE must be divisible by 16 for AVX2"
+    );
 
     let acc_ptr = acc.as_mut_ptr();
     let weights_ptr = weights.as_ptr();
@@ -284,15 +332,23 @@ unsafe fn sub_weights_avx2(acc: &mut [i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_
 
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "avx2")]
+#[allow(
+    clippy::wildcard_imports,
+    clippy::cast_ptr_alignment,
+    clippy::ptr_as_ptr
+)]
 unsafe fn screlu_dot_avx2(acc: &[i16; HIDDEN_SIZE], weights: &[i16; HIDDEN_SIZE]) -> i32 {
     use std::arch::x86_64::*;
-    const _: () = assert!(HIDDEN_SIZE.is_multiple_of(16), "HIDDEN_SIZE must be divisible by 16 for AVX2");
+    const _: () = assert!(
+        HIDDEN_SIZE.is_multiple_of(16),
+        "HIDDEN_SIZE must be divisible by 16 for AVX2"
+    );
 
     let acc_ptr = acc.as_ptr();
     let weights_ptr = weights.as_ptr();
 
     let zero = _mm256_setzero_si256();
-    let qa = _mm256_set1_epi16(QA as i16);
+    let qa = _mm256_set1_epi16(QA);
 
     let mut sum_lo = _mm256_setzero_si256();
     let mut sum_hi = _mm256_setzero_si256();
@@ -0,0 +1,152 @@
+//! How a game ends.
+//!
+//! [`Board::game_result`] decides whether the position ends the game and
+//! why, as one [`TerminationReason`], so callers need not combine
+//! `is_checkmate`, `is_stalemate`, `is_draw` and friends themselves. The
+//! reasons a position cannot show (resignation, time forfeit) are in the
+//! same enum for front ends and match runners to report results with.
+
+use std::fmt;
+
+use super::{Board, Color};
+
+/// Final result of a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    /// The win for `color`
+    #[must_use]
+    pub fn win_for(color: Color) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+
+    /// PGN result tag: `1-0`, `0-1` or `1/2-1/2`
+    #[must_use]
+    pub fn as_pgn(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+
+    /// The result of a PGN result tag, or `None` for `*` and anything else
+    #[must_use]
+    pub fn from_pgn(tag: &str) -> Option<Self> {
+        match tag {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            _ => None,
+        }
+    }
+}
+
+/// Why a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// The given side delivered mate
+    Checkmate(Color),
+    Stalemate,
+    /// A hundred half-moves without a capture or pawn move
+    FiftyMove,
+    /// Threefold repetition
+    Repetition,
+    InsufficientMaterial,
+    /// The given side met the variant's extra winning condition
+    VariantWin(Color),
+    /// The given side resigned
+    Resignation(Color),
+    /// The given side ran out of time
+    TimeForfeit(Color),
+}
+
+impl TerminationReason {
+    /// The result this ending gives
+    #[must_use]
+    pub fn result(self) -> GameResult {
+        match self {
+            TerminationReason::Checkmate(winner) | TerminationReason::VariantWin(winner) => {
+                GameResult::win_for(winner)
+            }
+            TerminationReason::Resignation(loser) | TerminationReason::TimeForfeit(loser) => {
+                GameResult::win_for(loser.opponent())
+            }
+            TerminationReason::Stalemate
+            | TerminationReason::FiftyMove
+            | TerminationReason::Repetition
+            | TerminationReason::InsufficientMaterial => GameResult::Draw,
+        }
+    }
+
+    #[must_use]
+    pub fn is_draw(self) -> bool {
+        self.result() == GameResult::Draw
+    }
+}
+
+impl fmt::Display for TerminationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerminationReason::Checkmate(winner) => write!(f, "{winner} mates"),
+            TerminationReason::Stalemate => write!(f, "Stalemate"),
+            TerminationReason::FiftyMove => write!(f, "Draw by fifty move rule"),
+            TerminationReason::Repetition => write!(f, "Draw by repetition"),
+            TerminationReason::InsufficientMaterial => write!(f, "Insufficient material"),
+            TerminationReason::VariantWin(winner) => write!(f, "{winner} wins by variant rules"),
+            TerminationReason::Resignation(loser) => write!(f, "{loser} resigns"),
+            TerminationReason::TimeForfeit(loser) => write!(f, "{loser} loses on time"),
+        }
+    }
+}
+
+impl Board {
+    /// Why the game is over in this position, or `None` if play continues.
+    ///
+    /// A variant win ends the game on the move that achieves it. Checkmate
+    /// and stalemate take precedence over the fifty-move rule, as a mate
+    /// delivered on the hundredth half-move still counts.
+    ///
+    /// # Example
+    /// ```
+    /// use chess_engine::board::{Board, Color, GameResult, TerminationReason};
+    ///
+    /// let mut board = Board::new();
+    /// for mv in ["f3", "e5", "g4", "Qh4#"] {
+    ///     board.make_move_san(mv).unwrap();
+    /// }
+    /// let reason = board.game_result().unwrap();
+    /// assert_eq!(reason, TerminationReason::Checkmate(Color::Black));
+    /// assert_eq!(reason.result(), GameResult::BlackWins);
+    /// ```
+    #[must_use]
+    pub fn game_result(&mut self) -> Option<TerminationReason> {
+        if let Some(winner) = self.variant_winner() {
+            return Some(TerminationReason::VariantWin(winner));
+        }
+        if self.generate_moves().is_empty() {
+            return Some(if self.in_check() {
+                TerminationReason::Checkmate(self.side_to_move().opponent())
+            } else {
+                TerminationReason::Stalemate
+            });
+        }
+        if self.is_fifty_move() {
+            Some(TerminationReason::FiftyMove)
+        } else if self.is_threefold() {
+            Some(TerminationReason::Repetition)
+        } else if self.is_insufficient_material() {
+            Some(TerminationReason::InsufficientMaterial)
+        } else {
+            None
+        }
+    }
+}
@@ -8,6 +8,6 @@
 //! ```
 
 pub use super::{
-    find_best_move, find_best_move_with_time, Board, BoardBuilder, CastlingRights, Color, FenError,
-    Move, MoveList, MoveParseError, Piece, SearchState, Square, SquareError,
+    find_best_move, find_best_move_with_time, Board, BoardBuilder, BuilderError, CastlingRights,
+    Color, FenError, Move, MoveList, MoveParseError, Piece, SearchState, Square, SquareError,
 };
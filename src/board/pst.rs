@@ -2,6 +2,10 @@
 //!
 //! Uses `PeSTO` tuned values for middlegame and endgame evaluation.
 //! Tables are indexed by piece type and square, with values in centipawns.
+//! There is one table per piece, from white's point of view; black reads it
+//! at the vertically mirrored square (see [`relative_square`]).
+
+use super::types::{Color, Piece};
 
 // Values mirrored from the PeSTO tables for parity.
 /// Material values for middlegame (indexed by `Piece::index()`)
@@ -17,7 +21,7 @@ pub(crate) const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
 
 /// Middlegame piece-square tables (indexed by [piece][square])
 /// Square indexing: a1=0, b1=1, ..., h8=63
-/// Values are from white's perspective; flip for black.
+/// Values are from white's perspective; black uses `relative_square`.
 pub(crate) const PST_MG: [[i32; 64]; 6] = [
     // Pawn
     [
@@ -100,3 +104,61 @@ pub(crate) const PST_EG: [[i32; 64]; 6] = [
         15, 20, 45, 44, 13, -12, 17, 14, 17, 17, 38, 23, 11, -74, -35, -18, -18, -11, 15, 4, -17,
     ],
 ];
+
+/// Index into the tables for a `color` piece on `sq`: white reads them
+/// directly, black at the vertically mirrored square (`sq ^ 56`, a1 <-> a8)
+#[inline]
+#[must_use]
+pub(crate) const fn relative_square(color: Color, sq: usize) -> usize {
+    match color {
+        Color::White => sq,
+        Color::Black => sq ^ 0b11_1000,
+    }
+}
+
+/// Material plus piece-square value of a `color` `piece` on `sq`, as
+/// (middlegame, endgame)
+#[inline]
+#[must_use]
+pub(crate) fn piece_square_value(color: Color, piece: Piece, sq: usize) -> (i32, i32) {
+    let p = piece.index();
+    let rel = relative_square(color, sq);
+    (
+        MATERIAL_MG[p] + PST_MG[p][rel],
+        MATERIAL_EG[p] + PST_EG[p][rel],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_square_white() {
+        // White's a1 (index 0) stays 0
+        assert_eq!(relative_square(Color::White, 0), 0);
+        // White's h8 (index 63) stays 63
+        assert_eq!(relative_square(Color::White, 63), 63);
+    }
+
+    #[test]
+    fn test_relative_square_black() {
+        // Black's a1 (index 0) becomes a8 (index 56)
+        assert_eq!(relative_square(Color::Black, 0), 56);
+        // Black's h8 (index 63) becomes h1 (index 7)
+        assert_eq!(relative_square(Color::Black, 63), 7);
+    }
+
+    #[test]
+    fn test_black_reads_mirrored_square() {
+        for piece in Piece::ALL {
+            for sq in 0..64 {
+                assert_eq!(
+                    piece_square_value(Color::White, piece, sq),
+                    piece_square_value(Color::Black, piece, sq ^ 0b11_1000),
+                    "{piece:?} on {sq}"
+                );
+            }
+        }
+    }
+}
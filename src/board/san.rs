@@ -12,8 +12,11 @@
 //! assert_eq!(board.move_to_san(&mv), "e4");
 //! ```
 
+use std::sync::Arc;
+
 use super::error::SanError;
-use super::{Board, Move, Piece, Square};
+use super::state::GameRecord;
+use super::{file_to_index, rank_to_index, Board, Move, Piece, Square};
 
 type SanParseResult = (Option<usize>, Option<usize>, bool, Vec<char>, Option<Piece>);
 
@@ -79,7 +82,7 @@ impl Board {
 
         if test_board.is_checkmate() {
             san.push('#');
-        } else if test_board.is_in_check(test_board.side_to_move()) {
+        } else if test_board.in_check() {
             san.push('+');
         }
 
@@ -161,14 +164,12 @@ impl Board {
                 notation: dest_str.iter().collect(),
             });
         }
-        let dest_file = dest_str[0] as usize - 'a' as usize;
-        let dest_rank = dest_str[1] as usize - '1' as usize;
-        if dest_file >= 8 || dest_rank >= 8 {
+        if !('a'..='h').contains(&dest_str[0]) || !('1'..='8').contains(&dest_str[1]) {
             return Err(SanError::InvalidSquare {
                 notation: dest_str.iter().collect(),
             });
         }
-        let dest = Square::new(dest_rank, dest_file);
+        let dest = Square::new(rank_to_index(dest_str[1]), file_to_index(dest_str[0]));
 
         // Find matching move
         self.find_san_move(piece, dest, disambig_file, disambig_rank, promotion, san)
@@ -213,7 +214,12 @@ impl Board {
                     idx += 2;
                 } else if next == 'x' || next.is_ascii_lowercase() {
                     // This is disambiguation file
-                    disambig_file = Some(c as usize - 'a' as usize);
+                    if !('a'..='h').contains(&c) {
+                        return Err(SanError::InvalidSquare {
+                            notation: chars.iter().collect(),
+                        });
+                    }
+                    disambig_file = Some(file_to_index(c));
                     idx += 1;
                 } else {
                     dest.push(c);
@@ -221,7 +227,12 @@ impl Board {
                 }
             } else if c.is_ascii_digit() && dest.is_empty() {
                 // Disambiguation rank
-                disambig_rank = Some(c as usize - '1' as usize);
+                if !('1'..='8').contains(&c) {
+                    return Err(SanError::InvalidSquare {
+                        notation: chars.iter().collect(),
+                    });
+                }
+                disambig_rank = Some(rank_to_index(c));
                 idx += 1;
             } else if c.is_ascii_lowercase() || c.is_ascii_digit() {
                 dest.push(c);
@@ -308,11 +319,67 @@ impl Board {
     }
 
     /// Parse a SAN move and make it on the board in one call.
+    ///
+    /// This is the SAN counterpart of `make_move_uci`.
+    ///
+    /// # Example
+    /// ```
+    /// use chess_engine::board::Board;
+    ///
+    /// let mut board = Board::new();
+    /// board.make_move_san("e4").unwrap();
+    /// board.make_move_san("Nf6").unwrap();
+    /// assert!(board.make_move_san("Ke3").is_err());
+    /// ```
     pub fn make_move_san(&mut self, san: &str) -> Result<Move, SanError> {
         let mv = self.parse_san(san)?;
-        self.make_move(mv);
+        self.make_game_move(mv);
         Ok(mv)
     }
+
+    /// The moves made with `make_move_uci` and `make_move_san`, in SAN.
+    ///
+    /// The history starts with the position the board was created or last
+    /// edited in.
+    ///
+    /// # Example
+    /// ```
+    /// use chess_engine::board::Board;
+    ///
+    /// let mut board = Board::new();
+    /// board.make_move_uci("g1f3").unwrap();
+    /// board.make_move_san("d5").unwrap();
+    /// assert_eq!(board.history_san(), ["Nf3", "d5"]);
+    /// ```
+    #[must_use]
+    pub fn history_san(&self) -> Vec<String> {
+        let Some(game) = &self.game else {
+            return Vec::new();
+        };
+        let mut board = Board::clone(&game.start);
+        game.moves
+            .iter()
+            .map(|mv| {
+                let san = board.move_to_san(mv);
+                board.make_move(*mv);
+                san
+            })
+            .collect()
+    }
+
+    /// Make a legal move and record it in the game history
+    pub(crate) fn make_game_move(&mut self, mv: Move) {
+        if self.game.is_none() {
+            self.game = Some(GameRecord {
+                start: Arc::new(self.clone()),
+                moves: Vec::new(),
+            });
+        }
+        if let Some(game) = &mut self.game {
+            game.moves.push(mv);
+        }
+        self.make_move(mv);
+    }
 }
 
 #[cfg(test)]
@@ -424,4 +491,42 @@ mod tests {
             assert_eq!(mv.to(), parsed.to());
         }
     }
+
+    #[test]
+    fn test_out_of_range_squares_are_errors() {
+        let mut board = Board::new();
+        for san in ["a0", "0e4", "exd0", "Na0", "0PPR+3"] {
+            assert!(
+                matches!(board.parse_san(san), Err(SanError::InvalidSquare { .. })),
+                "{san} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_history_san() {
+        let mut board = Board::new();
+        assert!(board.history_san().is_empty());
+        for san in ["e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "O-O"] {
+            board.make_move_san(san).unwrap();
+        }
+        board.make_move_uci("f6e4").unwrap();
+        assert!(matches!(
+            board.make_move_san("Qh5"),
+            Err(SanError::NoMatchingMove { .. })
+        ));
+        assert_eq!(
+            board.history_san(),
+            ["e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "O-O", "Nxe4"]
+        );
+
+        // A position from a FEN starts its own history
+        let mut mate: Board = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".parse().unwrap();
+        mate.make_move_uci("a1a8").unwrap();
+        assert_eq!(mate.history_san(), ["Ra8#"]);
+
+        // An edit restarts it
+        board.remove_piece_at(Square::new(0, 0));
+        assert!(board.history_san().is_empty());
+    }
 }
@@ -0,0 +1,219 @@
+//! Pruning audit.
+//!
+//! Forward pruning and reductions bet that a node or move cannot change
+//! the result and skip or shorten its search. With the audit on, every
+//! `N`th decision of each kind is checked: what was skipped is searched at
+//! full depth, with a null window on the bound the decision relied on, and
+//! the decision counts as changed when that search disagrees. The share of
+//! changed decisions measures how risky each pruning parameter is.
+//!
+//! Decisions taken inside a check are not sampled, so a check costs one
+//! ordinary search of the skipped subtree. The checks still fill the hash
+//! and history tables, so an audited search visits other nodes than a
+//! plain one and is only meant for diagnostics.
+
+/// A pruning or reduction decision the audit samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneKind {
+    /// Node cut off because the static eval beats beta by a margin
+    ReverseFutility,
+    /// Node cut off because a reduced search after passing beats beta
+    NullMove,
+    /// Node cut off because a reduced search of a good capture beats beta
+    /// by a margin
+    ProbCut,
+    /// Quiet move skipped because it moves to a square where it is lost
+    QuietSee,
+    /// Quiet move skipped because the static eval is far below alpha
+    Futility,
+    /// Quiet move skipped because many moves were already tried
+    LateMovePruning,
+    /// Move whose reduced search failed low and was not searched further
+    LateMoveReduction,
+}
+
+impl PruneKind {
+    pub const ALL: [PruneKind; 7] = [
+        PruneKind::ReverseFutility,
+        PruneKind::NullMove,
+        PruneKind::ProbCut,
+        PruneKind::QuietSee,
+        PruneKind::Futility,
+        PruneKind::LateMovePruning,
+        PruneKind::LateMoveReduction,
+    ];
+
+    /// Name in debug output
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            PruneKind::ReverseFutility => "rfp",
+            PruneKind::NullMove => "nullmove",
+            PruneKind::ProbCut => "probcut",
+            PruneKind::QuietSee => "see",
+            PruneKind::Futility => "futility",
+            PruneKind::LateMovePruning => "lmp",
+            PruneKind::LateMoveReduction => "lmr",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Decisions of one kind checked, and how many of them the full-depth
+/// search disagreed with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneAuditCount {
+    pub sampled: u64,
+    pub changed: u64,
+}
+
+/// Audit results of a search, by kind of decision
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneAuditStats {
+    counts: [PruneAuditCount; PruneKind::ALL.len()],
+}
+
+impl PruneAuditStats {
+    #[must_use]
+    pub fn get(&self, kind: PruneKind) -> PruneAuditCount {
+        self.counts[kind.index()]
+    }
+
+    /// Whether no decision was checked
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|count| count.sampled == 0)
+    }
+
+    pub(crate) fn record(&mut self, kind: PruneKind, changed: bool) {
+        let count = &mut self.counts[kind.index()];
+        count.sampled += 1;
+        count.changed += u64::from(changed);
+    }
+
+    /// Add the results of another search (an SMP worker's)
+    pub(crate) fn merge(&mut self, other: &PruneAuditStats) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            count.sampled += other.sampled;
+            count.changed += other.changed;
+        }
+    }
+}
+
+/// Which pruning decisions to check
+#[derive(Debug, Clone, Default)]
+pub struct PruneAudit {
+    /// Check every `sample_every`th decision of each kind (0 = off)
+    sample_every: u32,
+    /// Decisions of each kind taken so far
+    seen: [u64; PruneKind::ALL.len()],
+    /// A check is running
+    checking: bool,
+    /// Ply of the node a node-level check searches, which must not be
+    /// pruned again
+    unpruned_ply: Option<usize>,
+}
+
+impl PruneAudit {
+    /// Audit checking every `sample_every`th decision of each kind, or
+    /// none for 0
+    #[must_use]
+    pub fn new(sample_every: u32) -> Self {
+        PruneAudit {
+            sample_every,
+            ..PruneAudit::default()
+        }
+    }
+
+    #[must_use]
+    pub fn sample_every(&self) -> u32 {
+        self.sample_every
+    }
+
+    /// Count a decision of `kind` and return whether to check it. Nothing
+    /// is checked with the audit off or while a check runs.
+    #[inline]
+    pub(crate) fn sample(&mut self, kind: PruneKind) -> bool {
+        if self.sample_every == 0 || self.checking {
+            return false;
+        }
+        let seen = &mut self.seen[kind.index()];
+        *seen += 1;
+        seen.is_multiple_of(u64::from(self.sample_every))
+    }
+
+    /// Start a check; a node-level check passes the ply of its node
+    pub(crate) fn begin_check(&mut self, unpruned_ply: Option<usize>) {
+        self.checking = true;
+        self.unpruned_ply = unpruned_ply;
+    }
+
+    pub(crate) fn end_check(&mut self) {
+        self.checking = false;
+        self.unpruned_ply = None;
+    }
+
+    /// Whether the node at `ply` is the one a node-level check searches,
+    /// which clears the mark so the nodes below it prune as usual
+    #[inline]
+    pub(crate) fn take_unpruned(&mut self, ply: usize) -> bool {
+        if self.unpruned_ply == Some(ply) {
+            self.unpruned_ply = None;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_every_nth_decision_of_each_kind() {
+        let mut audit = PruneAudit::new(3);
+        let sampled: Vec<bool> = (0..6).map(|_| audit.sample(PruneKind::Futility)).collect();
+        assert_eq!(sampled, [false, false, true, false, false, true]);
+        // Each kind counts on its own
+        assert!(!audit.sample(PruneKind::NullMove));
+
+        // Nothing is sampled during a check or with the audit off
+        audit.begin_check(None);
+        assert!((0..6).all(|_| !audit.sample(PruneKind::NullMove)));
+        audit.end_check();
+        let mut off = PruneAudit::new(0);
+        assert!((0..6).all(|_| !off.sample(PruneKind::Futility)));
+    }
+
+    #[test]
+    fn test_node_check_skips_pruning_only_at_its_node() {
+        let mut audit = PruneAudit::new(1);
+        audit.begin_check(Some(4));
+        assert!(!audit.take_unpruned(5));
+        assert!(audit.take_unpruned(4));
+        assert!(!audit.take_unpruned(4));
+        audit.end_check();
+    }
+
+    #[test]
+    fn test_stats_record_and_merge() {
+        let mut stats = PruneAuditStats::default();
+        assert!(stats.is_empty());
+        stats.record(PruneKind::LateMoveReduction, true);
+        stats.record(PruneKind::LateMoveReduction, false);
+        let mut total = PruneAuditStats::default();
+        total.merge(&stats);
+        total.merge(&stats);
+        assert_eq!(
+            total.get(PruneKind::LateMoveReduction),
+            PruneAuditCount {
+                sampled: 4,
+                changed: 2
+            }
+        );
+        assert_eq!(total.get(PruneKind::Futility), PruneAuditCount::default());
+    }
+}
@@ -22,6 +22,10 @@ pub const SCORE_SAFE_MAX: i32 = 29000;
 /// Threshold for considering a score "near mate" (skip certain pruning)
 pub const SCORE_NEAR_MATE: i32 = 20000;
 
+/// Score of a won position from the shared cache, less the ply it is found
+/// at; above any evaluation but below the near-mate scores
+pub const TB_WIN_SCORE: i32 = 19000;
+
 // ============================================================================
 // MOVE ORDERING PRIORITIES
 // ============================================================================
@@ -33,6 +37,11 @@ pub const TT_MOVE_SCORE: i32 = 1 << 20;
 /// Base score for captures (added to MVV-LVA to ensure captures > killers)
 pub const CAPTURE_BASE_SCORE: i32 = 100000;
 
+/// Added to a recapture on the previous move's square that doesn't lose
+/// material by SEE, lifting it above every other capture (MVV-LVA scores
+/// span less than this)
+pub const RECAPTURE_BONUS: i32 = 10000;
+
 /// First killer move (quiet that caused beta cutoff at same ply)
 pub const KILLER1_SCORE: i32 = 20000;
 
@@ -62,6 +71,14 @@ pub const LMR_IDX_BASE: usize = 3;
 pub const LMR_TABLE_MAX_DEPTH: usize = 32;
 pub const LMR_TABLE_MAX_IDX: usize = 256;
 
+// ============================================================================
+// DEPTH
+// ============================================================================
+
+/// Search depth is measured in fractions of a ply; this many units make one ply.
+/// Extensions and reductions are expressed in the same units.
+pub const ONE_PLY: u32 = 4;
+
 // ============================================================================
 // EXTENSIONS
 // ============================================================================
@@ -72,6 +89,20 @@ pub const PAWN_EXTENSION_RANK_WHITE: usize = 6;
 /// Pre-promotion rank for black pawns (0-indexed: rank 2 = index 1)
 pub const PAWN_EXTENSION_RANK_BLACK: usize = 1;
 
+// ============================================================================
+// REPORTING
+// ============================================================================
+
+/// Minimum time between periodic `info` progress updates
+pub const PROGRESS_INTERVAL_MS: u64 = 1000;
+
+/// Only check the progress timer when `nodes & PROGRESS_NODE_MASK == 0`
+pub const PROGRESS_NODE_MASK: u64 = 4095;
+
+/// SMP workers add their nodes to the shared counter every
+/// `NODE_PUBLISH_MASK + 1` nodes
+pub const NODE_PUBLISH_MASK: u64 = 1023;
+
 // No pruning margins are currently used.
 
 #[cfg(test)]
@@ -86,6 +117,7 @@ mod tests {
         assert!(SCORE_SAFE_MAX > MATE_THRESHOLD);
         // MATE_THRESHOLD is for detecting checkmate scores
         assert!(MATE_THRESHOLD > SCORE_NEAR_MATE);
+        assert!(SCORE_NEAR_MATE > TB_WIN_SCORE);
     }
 
     #[test]
@@ -121,6 +153,13 @@ mod tests {
         assert!(LMR_TABLE_MAX_IDX >= 64);
     }
 
+    #[test]
+    fn test_one_ply_allows_fractions() {
+        // Half-ply granularity at minimum
+        assert!(ONE_PLY >= 2);
+        assert_eq!(ONE_PLY % 2, 0);
+    }
+
     #[test]
     fn test_pawn_extension_ranks() {
         // White pre-promotion rank is 7th rank (index 6)
@@ -0,0 +1,98 @@
+//! Late move reduction table.
+//!
+//! Reductions follow `base + ln(depth) * ln(move_index) / divisor`, with both
+//! constants given in hundredths of a ply so they can be tuned as integers.
+//! The table is stored in `ONE_PLY` units so fractional reductions survive.
+
+use super::constants::{LMR_TABLE_MAX_DEPTH, LMR_TABLE_MAX_IDX, ONE_PLY};
+use super::SearchParams;
+
+/// Precomputed `[depth][move_index]` reduction table
+pub struct LmrTable {
+    base: i32,
+    divisor: i32,
+    table: Box<[[u32; LMR_TABLE_MAX_IDX]; LMR_TABLE_MAX_DEPTH]>,
+}
+
+impl LmrTable {
+    /// Build the table from the formula constants (both in hundredths of a ply)
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(base: i32, divisor: i32) -> Self {
+        let base_plies = f64::from(base) / 100.0;
+        let divisor_plies = f64::from(divisor.max(1)) / 100.0;
+
+        let mut table = Box::new([[0u32; LMR_TABLE_MAX_IDX]; LMR_TABLE_MAX_DEPTH]);
+        for (depth, row) in table.iter_mut().enumerate().skip(1) {
+            for (idx, cell) in row.iter_mut().enumerate().skip(1) {
+                let plies = base_plies + (depth as f64).ln() * (idx as f64).ln() / divisor_plies;
+                let val = (plies * f64::from(ONE_PLY)).floor();
+                *cell = val.max(0.0) as u32;
+            }
+        }
+
+        LmrTable {
+            base,
+            divisor,
+            table,
+        }
+    }
+
+    /// Build the table from the formula constants in `params`
+    #[must_use]
+    pub fn from_params(params: &SearchParams) -> Self {
+        Self::new(params.lmr_base, params.lmr_divisor)
+    }
+
+    /// Rebuild the table if the tunables in `params` have changed
+    pub fn refresh(&mut self, params: &SearchParams) {
+        if self.base != params.lmr_base || self.divisor != params.lmr_divisor {
+            *self = Self::from_params(params);
+        }
+    }
+
+    /// Base reduction (in `ONE_PLY` units) for a move at `depth` plies
+    #[must_use]
+    pub fn reduction(&self, depth: u32, move_idx: usize) -> u32 {
+        let depth_idx = (depth as usize).min(LMR_TABLE_MAX_DEPTH - 1);
+        let move_idx = move_idx.min(LMR_TABLE_MAX_IDX - 1);
+        self.table[depth_idx][move_idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_reduction_for_first_move_or_zero_depth() {
+        let lmr = LmrTable::new(77, 236);
+        assert_eq!(lmr.reduction(0, 10), 0);
+        assert_eq!(lmr.reduction(10, 0), 0);
+    }
+
+    #[test]
+    fn test_reduction_grows_with_depth_and_index() {
+        let lmr = LmrTable::new(77, 236);
+        assert!(lmr.reduction(12, 20) >= lmr.reduction(6, 20));
+        assert!(lmr.reduction(12, 20) >= lmr.reduction(12, 5));
+        assert!(lmr.reduction(20, 40) > ONE_PLY);
+    }
+
+    #[test]
+    fn test_indices_are_clamped() {
+        let lmr = LmrTable::new(77, 236);
+        assert_eq!(lmr.reduction(1000, 10_000), lmr.reduction(31, 255));
+    }
+
+    #[test]
+    fn test_refresh_rebuilds_on_change() {
+        let mut params = SearchParams::default();
+        let mut lmr = LmrTable::from_params(&params);
+        let before = lmr.reduction(10, 10);
+
+        params.lmr_base += 100;
+        lmr.refresh(&params);
+        assert_eq!(lmr.reduction(10, 10), before + ONE_PLY);
+    }
+}
@@ -8,47 +8,200 @@
 //! - Transposition table for move ordering and cutoffs
 //! - Lazy SMP parallel search support
 
-mod constants;
+mod audit;
+pub(crate) mod constants;
+mod lmr;
 mod move_order;
 mod params;
+mod root_noise;
 mod simple;
 pub mod smp;
+mod tablebase;
+mod wdl;
 
 use parking_lot::Mutex;
-use std::sync::atomic::AtomicBool;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+use crate::shared_cache::SharedCache;
 use crate::tt::TranspositionTable;
 use constants::SCORE_NEAR_MATE;
 
 use super::nnue::NnueNetwork;
-use super::{Board, Move, Piece, MAX_PLY};
+use super::{Bitboard, Board, Color, Move, Piece, Square, MAX_PLY};
+pub use audit::{PruneAudit, PruneAuditCount, PruneAuditStats, PruneKind};
+use lmr::LmrTable;
 pub use params::SearchParams;
+pub use root_noise::{RootNoise, RootNoiseConfig, MAX_ROOT_NOISE};
+pub use wdl::WdlProbabilities;
 
-/// Result of a search containing best move and ponder move
-#[derive(Debug, Clone, Copy)]
+/// Result of a search: the moves to play and ponder, and the score and
+/// effort of the last iteration, as its final `info` line reported them
+#[derive(Debug, Clone, Default)]
 pub struct SearchResult {
     /// The best move found
     pub best_move: Option<Move>,
     /// The expected opponent reply (for pondering)
     pub ponder_move: Option<Move>,
+    /// Centipawns, side to move's point of view
+    pub score: i32,
+    /// Moves to mate when `score` is a mate score, negative when getting mated
+    pub mate_in: Option<i32>,
+    /// Depth of the last iteration; 0 when the move was forced and nothing
+    /// was searched
+    pub depth: u32,
+    pub seldepth: u32,
+    /// Nodes searched, counting every `MultiPV` line and SMP worker
+    pub nodes: u64,
+    pub elapsed: Duration,
+    /// Completed iterations, for time to depth and branching factor
+    pub iterations: Vec<IterationStats>,
+    /// Pruning decisions checked by the audit, see [`PruneAudit`]
+    pub prune_audit: PruneAuditStats,
+}
+
+impl SearchResult {
+    /// Result for `best_move` and `ponder_move`, taking the score, depth
+    /// and node counts from `stats` and the time since `start`
+    fn from_stats(
+        best_move: Option<Move>,
+        ponder_move: Option<Move>,
+        stats: &SearchStats,
+        start: Instant,
+    ) -> Self {
+        SearchResult {
+            best_move,
+            ponder_move,
+            score: stats.score,
+            mate_in: mate_in(stats.score),
+            depth: stats.depth,
+            seldepth: stats.seldepth,
+            nodes: stats.nodes,
+            elapsed: start.elapsed(),
+            iterations: stats.iterations.clone(),
+            prune_audit: stats.prune_audit,
+        }
+    }
+
+    /// Average growth in nodes per extra ply over the completed
+    /// iterations, or `None` with fewer than two of them
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn effective_branching_factor(&self) -> Option<f64> {
+        let (first, last) = (self.iterations.first()?, self.iterations.last()?);
+        if last.depth <= first.depth || first.nodes == 0 {
+            return None;
+        }
+        let growth = last.nodes as f64 / first.nodes as f64;
+        Some(growth.powf(1.0 / f64::from(last.depth - first.depth)))
+    }
+}
+
+/// One completed iteration, counted from the start of the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IterationStats {
+    pub depth: u32,
+    /// Nodes searched by the end of the iteration
+    pub nodes: u64,
+    /// Time to depth: milliseconds from the start of the search to the end
+    /// of the iteration
+    pub time_ms: u64,
 }
 
 /// Default transposition table size in MB
+#[cfg(not(feature = "small-footprint"))]
 pub const DEFAULT_TT_MB: usize = 1024;
 
+/// Default transposition table size in MB; small enough for phones and
+/// single-board computers
+#[cfg(feature = "small-footprint")]
+pub const DEFAULT_TT_MB: usize = 16;
+
+/// Bytes per megabyte, as the `Hash` option counts them
+const MB: usize = 1024 * 1024;
+
+/// Pawn hash size (KB) per MB of `Hash`
+const PAWN_HASH_KB_PER_MB: usize = 16;
+
+/// Largest pawn hash size (KB), reached at 64 MB of `Hash`
+const PAWN_HASH_MAX_KB: usize = 1024;
+
+/// Positions searched by [`SearchState::warmup`]: an opening, a tactical
+/// middlegame and a pawn endgame
+const WARMUP_FENS: [&str; 3] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+/// Depth of the warmup searches
+const WARMUP_DEPTH: u32 = 5;
+
 /// Mate score constant
 pub(crate) const MATE_SCORE: i32 = constants::MATE_THRESHOLD + MAX_PLY as i32;
 
+/// Moves to mate for a mate `score`, negative when the side to move is
+/// mated, or `None` for a normal score
+pub(crate) fn mate_in(score: i32) -> Option<i32> {
+    if score.abs() < constants::MATE_THRESHOLD {
+        None
+    } else if score > 0 {
+        Some((MATE_SCORE - score + 1) / 2)
+    } else {
+        Some(-(MATE_SCORE + score + 1) / 2)
+    }
+}
+
+/// Convert a search score to its TT form.
+///
+/// Mate scores are distances from the root; the TT stores them as distances
+/// from the node (`ply` plies below the root) so they stay correct when the
+/// position is reached again at a different ply.
+#[inline]
+pub(crate) fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= constants::MATE_THRESHOLD {
+        score + ply as i32
+    } else if score <= -constants::MATE_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Convert a TT score back to a search score at `ply` (inverse of [`score_to_tt`])
+#[inline]
+pub(crate) fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= constants::MATE_THRESHOLD {
+        score - ply as i32
+    } else if score <= -constants::MATE_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
 /// Statistics tracked during search
 #[derive(Default)]
 pub struct SearchStats {
+    /// Nodes searched by the last search, counting every `MultiPV` line and
+    /// every SMP worker; main search and quiescence nodes count alike
     pub nodes: u64,
     pub seldepth: u32,
+    /// Nodes searched since the last [`reset_search`](Self::reset_search),
+    /// which may span several searches
     pub total_nodes: u64,
     pub max_nodes: u64,
     pub tt_hits: u64,
+    /// Depth and score of the last iteration reported by the last search
+    /// (the first line's under `MultiPV`)
+    pub depth: u32,
+    pub score: i32,
+    /// Iterations the last search completed (the first line's under `MultiPV`)
+    pub iterations: Vec<IterationStats>,
+    /// Pruning decisions the audit checked in the last search
+    pub prune_audit: PruneAuditStats,
 }
 
 impl SearchStats {
@@ -57,6 +210,10 @@ impl SearchStats {
         self.seldepth = 0;
         self.total_nodes = 0;
         self.tt_hits = 0;
+        self.depth = 0;
+        self.score = 0;
+        self.iterations.clear();
+        self.prune_audit = PruneAuditStats::default();
     }
 
     pub fn reset_iteration(&mut self) {
@@ -129,8 +286,70 @@ impl KillerTable {
     }
 }
 
+/// Largest magnitude of a history score; gravity keeps entries within it
+pub const HISTORY_MAX: i32 = 16384;
+
+/// Largest single history bonus or malus
+const HISTORY_BONUS_MAX: i32 = 1600;
+
+/// Entries of the history table: `[color][threatened][piece][to]`
+const HISTORY_ENTRIES: usize = 2 * 2 * 6 * 64;
+
+/// Bonus for a quiet move that caused a cutoff at `depth` plies; the
+/// quiets tried before it get the same amount as a malus
+#[must_use]
+pub fn history_bonus(depth: u32) -> i32 {
+    let depth = depth.min(64) as i32;
+    (32 * depth * depth).min(HISTORY_BONUS_MAX)
+}
+
+/// What the history table indexes a quiet move by: the side to move, the
+/// moving piece, its destination and whether it leaves a square the
+/// opponent attacks (a threatened piece moving away is a different kind of
+/// move from the same piece moving for positional reasons)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryKey {
+    pub color: Color,
+    pub piece: Piece,
+    pub to: Square,
+    pub threatened: bool,
+}
+
+impl HistoryKey {
+    /// Key of `mv` in `board`, given the squares the opponent attacks;
+    /// `None` if there is no piece on its from-square
+    #[must_use]
+    pub fn new(board: &Board, mv: Move, threats: Bitboard) -> Option<Self> {
+        let (color, piece) = board.piece_at(mv.from())?;
+        Some(HistoryKey {
+            color,
+            piece,
+            to: mv.to(),
+            threatened: threats.contains(mv.from()),
+        })
+    }
+
+    fn index(self) -> usize {
+        ((self.color.index() * 2 + usize::from(self.threatened)) * 6 + self.piece.index()) * 64
+            + self.to.index()
+    }
+}
+
+/// Add `bonus` (negative for a malus) to a history entry, scaled down as the
+/// entry nears `±HISTORY_MAX` so it never leaves that range
+#[allow(clippy::cast_possible_truncation)]
+fn apply_gravity(entry: &mut i16, bonus: i32) {
+    let bonus = bonus.clamp(-HISTORY_MAX, HISTORY_MAX);
+    let value = i32::from(*entry);
+    // Within ±HISTORY_MAX, so it fits in an i16
+    *entry = (value + bonus - value * bonus.abs() / HISTORY_MAX) as i16;
+}
+
+/// Quiet move history indexed by [`HistoryKey`], updated with gravity:
+/// each bonus or malus is scaled down as the entry nears [`HISTORY_MAX`],
+/// so scores stay bounded and recent results outweigh old ones
 pub struct HistoryTable {
-    entries: [i32; 4096],
+    entries: [i16; HISTORY_ENTRIES],
 }
 
 impl Default for HistoryTable {
@@ -142,28 +361,19 @@ impl Default for HistoryTable {
 impl HistoryTable {
     #[must_use]
     pub fn new() -> Self {
-        HistoryTable { entries: [0; 4096] }
+        HistoryTable {
+            entries: [0; HISTORY_ENTRIES],
+        }
     }
 
     #[must_use]
-    pub fn score(&self, mv: &Move) -> i32 {
-        self.entries.get(mv.history_index()).copied().unwrap_or(0)
+    pub fn score(&self, key: HistoryKey) -> i32 {
+        i32::from(self.entries[key.index()])
     }
 
-    /// Update history score for a move that caused a beta cutoff
-    pub fn update(&mut self, mv: &Move, depth: u32, _ply: usize) {
-        if let Some(entry) = self.entries.get_mut(mv.history_index()) {
-            let bonus = (depth * depth * depth) as i32;
-            *entry = entry.saturating_add(bonus);
-        }
-    }
-
-    /// Penalize a move that failed to cause a cutoff (negative history)
-    pub fn penalize(&mut self, mv: &Move, depth: u32, _ply: usize) {
-        if let Some(entry) = self.entries.get_mut(mv.history_index()) {
-            let penalty = (depth * depth) as i32;
-            *entry = entry.saturating_sub(penalty);
-        }
+    /// Add `bonus` (negative for a malus) to the entry of `key`
+    pub fn update(&mut self, key: HistoryKey, bonus: i32) {
+        apply_gravity(&mut self.entries[key.index()], bonus);
     }
 
     pub fn decay(&mut self) {
@@ -173,7 +383,7 @@ impl HistoryTable {
     }
 
     pub fn reset(&mut self) {
-        self.entries = [0; 4096];
+        self.entries = [0; HISTORY_ENTRIES];
     }
 }
 
@@ -217,14 +427,24 @@ impl CounterMoveTable {
     }
 }
 
+/// Inner entries of each continuation history slot: one per `from * 64 + to`
+#[cfg(not(feature = "small-footprint"))]
+const CONT_HIST_MOVES: usize = 4096;
+
+/// Inner entries of each continuation history slot: one per destination
+/// square, which cuts the table from 3 MB to 48 KB per thread
+#[cfg(feature = "small-footprint")]
+const CONT_HIST_MOVES: usize = 64;
+
 /// Continuation history table - tracks what moves work well after previous moves.
 ///
 /// Indexed by `[prev_piece][prev_to][curr_from][curr_to]` simplified to
 /// `[prev_piece * 64 + prev_to][curr_from * 64 + curr_to]`.
 /// We use 6 piece types * 64 squares = 384 outer slots, each with 4096 inner entries.
+/// Small-footprint builds drop `curr_from` from the inner index.
 pub struct ContinuationHistory {
     /// [piece * 64 + to] -> [from * 64 + to] -> score
-    entries: Box<[[i16; 4096]; 384]>,
+    entries: Box<[[i16; CONT_HIST_MOVES]; 384]>,
 }
 
 impl Default for ContinuationHistory {
@@ -237,15 +457,21 @@ impl ContinuationHistory {
     #[must_use]
     pub fn new() -> Self {
         ContinuationHistory {
-            entries: Box::new([[0i16; 4096]; 384]),
+            entries: Box::new([[0i16; CONT_HIST_MOVES]; 384]),
         }
     }
 
+    /// Inner index of `mv`; `from * 64 + to` reduces to `to` when there
+    /// are only 64 entries
+    fn move_index(mv: Move) -> usize {
+        (mv.from().index() * 64 + mv.to().index()) % CONT_HIST_MOVES
+    }
+
     /// Get continuation history score for a move following a previous move
     #[must_use]
     pub fn score(&self, prev_piece: Piece, prev_to: usize, mv: &Move) -> i32 {
         let outer_idx = prev_piece as usize * 64 + prev_to;
-        let inner_idx = mv.from().index() * 64 + mv.to().index();
+        let inner_idx = Self::move_index(*mv);
         if outer_idx < 384 {
             i32::from(self.entries[outer_idx][inner_idx])
         } else {
@@ -253,15 +479,13 @@ impl ContinuationHistory {
         }
     }
 
-    /// Update continuation history on beta cutoff
-    pub fn update(&mut self, prev_piece: Piece, prev_to: usize, mv: &Move, depth: u32) {
+    /// Add `bonus` to the continuation history of a quiet move: positive
+    /// when it caused a beta cutoff, negative when it was tried before one
+    pub fn update(&mut self, prev_piece: Piece, prev_to: usize, mv: &Move, bonus: i32) {
         let outer_idx = prev_piece as usize * 64 + prev_to;
-        let inner_idx = mv.from().index() * 64 + mv.to().index();
+        let inner_idx = Self::move_index(*mv);
         if outer_idx < 384 {
-            let bonus = (depth * depth) as i16;
-            let entry = &mut self.entries[outer_idx][inner_idx];
-            // Saturating add with clamping to prevent overflow
-            *entry = entry.saturating_add(bonus).min(16000);
+            apply_gravity(&mut self.entries[outer_idx][inner_idx], bonus);
         }
     }
 
@@ -284,6 +508,13 @@ impl ContinuationHistory {
     }
 }
 
+/// Inner entries of each countermove history slot. Only the first 384 are
+/// ever indexed; small-footprint builds do not allocate the rest.
+#[cfg(not(feature = "small-footprint"))]
+const COUNTERMOVE_HIST_MOVES: usize = 4096;
+#[cfg(feature = "small-footprint")]
+const COUNTERMOVE_HIST_MOVES: usize = 384;
+
 /// Countermove history table - tracks what responses work well against opponent moves.
 ///
 /// Unlike continuation history (which uses our previous move), this uses the opponent's
@@ -291,7 +522,7 @@ impl ContinuationHistory {
 /// Indexed by `[opp_piece * 64 + opp_to][our_piece * 64 + our_to]`.
 pub struct CountermoveHistory {
     /// `[prev_piece * 64 + prev_to]` -> `[piece * 64 + to]` -> score
-    entries: Box<[[i16; 4096]; 384]>,
+    entries: Box<[[i16; COUNTERMOVE_HIST_MOVES]; 384]>,
 }
 
 impl Default for CountermoveHistory {
@@ -304,7 +535,7 @@ impl CountermoveHistory {
     #[must_use]
     pub fn new() -> Self {
         CountermoveHistory {
-            entries: Box::new([[0i16; 4096]; 384]),
+            entries: Box::new([[0i16; COUNTERMOVE_HIST_MOVES]; 384]),
         }
     }
 
@@ -320,21 +551,20 @@ impl CountermoveHistory {
         }
     }
 
-    /// Update countermove history on beta cutoff
+    /// Add `bonus` to the countermove history of a quiet move: positive
+    /// when it caused a beta cutoff, negative when it was tried before one
     pub fn update(
         &mut self,
         opp_piece: Piece,
         opp_to: usize,
         our_piece: Piece,
         mv: &Move,
-        depth: u32,
+        bonus: i32,
     ) {
         let outer_idx = opp_piece as usize * 64 + opp_to;
         let inner_idx = our_piece as usize * 64 + mv.to().index();
         if outer_idx < 384 {
-            let bonus = (depth * depth) as i16;
-            let entry = &mut self.entries[outer_idx][inner_idx];
-            *entry = entry.saturating_add(bonus).min(16000);
+            apply_gravity(&mut self.entries[outer_idx][inner_idx], bonus);
         }
     }
 
@@ -410,7 +640,10 @@ impl CaptureHistory {
 /// When search finds a more accurate score than static eval, we store the correction
 /// indexed by pawn hash. Future positions with similar pawn structures get this
 /// correction applied to their static eval.
+#[cfg(not(feature = "small-footprint"))]
 const CORRECTION_HISTORY_SIZE: usize = 16384;
+#[cfg(feature = "small-footprint")]
+const CORRECTION_HISTORY_SIZE: usize = 4096;
 
 pub struct CorrectionHistory {
     /// Indexed by `pawn_hash` % size, stores weighted average correction
@@ -469,6 +702,55 @@ impl CorrectionHistory {
     }
 }
 
+/// Number of entries in the SEE cache (power of two)
+const SEE_CACHE_SIZE: usize = 1024;
+
+/// Small direct-mapped cache of static exchange results keyed by
+/// (position hash, move).
+///
+/// A capture's SEE is computed once for move ordering and again when qsearch
+/// or probcut decide whether to prune it; the cache lets the second lookup
+/// reuse the first. Entries are overwritten on collision, and the position
+/// hash changes whenever the board does, so stale entries never match.
+pub struct SeeCache {
+    keys: Box<[u64; SEE_CACHE_SIZE]>,
+    values: Box<[i32; SEE_CACHE_SIZE]>,
+}
+
+impl Default for SeeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SeeCache {
+    #[must_use]
+    pub fn new() -> Self {
+        SeeCache {
+            keys: Box::new([0; SEE_CACHE_SIZE]),
+            values: Box::new([0; SEE_CACHE_SIZE]),
+        }
+    }
+
+    /// Cache key for `mv` in the position with hash `hash`
+    fn key(hash: u64, mv: Move) -> u64 {
+        hash ^ u64::from(mv.as_u16()).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    /// SEE of `mv` in `board`, computed on a miss
+    pub fn see(&mut self, board: &Board, mv: &Move) -> i32 {
+        let key = Self::key(board.hash, *mv);
+        let idx = (key as usize) & (SEE_CACHE_SIZE - 1);
+        if self.keys[idx] == key {
+            return self.values[idx];
+        }
+        let value = board.see(*mv);
+        self.keys[idx] = key;
+        self.values[idx] = value;
+        value
+    }
+}
+
 /// Tables used during search (TT, killers, history, counter moves)
 pub struct SearchTables {
     /// Shared transposition table (thread-safe, can be shared across workers)
@@ -477,6 +759,8 @@ pub struct SearchTables {
     pub pawn_hash: Arc<crate::pawn_hash::PawnHashTable>,
     /// Shared NNUE network (optional, loaded from file)
     pub nnue: Option<Arc<NnueNetwork>>,
+    /// Precomputed results shared between processes (optional, loaded from file)
+    pub shared_cache: Option<Arc<SharedCache>>,
     /// Per-thread killer move table
     pub killer_moves: KillerTable,
     /// Per-thread history heuristic table
@@ -491,6 +775,8 @@ pub struct SearchTables {
     pub capture_history: CaptureHistory,
     /// Per-thread correction history table
     pub correction_history: CorrectionHistory,
+    /// Per-thread SEE results shared by move ordering and pruning
+    pub see_cache: SeeCache,
 }
 
 impl SearchTables {
@@ -517,6 +803,23 @@ impl SearchTables {
         )
     }
 
+    /// Forget everything learned by earlier searches, keeping the shared
+    /// table allocations. Clearing also writes every TT page.
+    pub fn clear(&mut self) {
+        (
+            self.killer_moves,
+            self.history,
+            self.counter_moves,
+            self.continuation_history,
+            self.countermove_history,
+            self.capture_history,
+            self.correction_history,
+        ) = Self::new_per_thread_tables();
+        self.see_cache = SeeCache::new();
+        self.tt.clear();
+        self.pawn_hash.clear();
+    }
+
     /// Create a new `SearchTables` with a fresh transposition table of the given size.
     #[must_use]
     pub fn new(tt_mb: usize) -> Self {
@@ -534,6 +837,7 @@ impl SearchTables {
             tt: Arc::new(TranspositionTable::new(tt_mb)),
             pawn_hash: Arc::new(crate::pawn_hash::PawnHashTable::default()),
             nnue: None,
+            shared_cache: None,
             killer_moves,
             history,
             counter_moves,
@@ -541,6 +845,7 @@ impl SearchTables {
             countermove_history,
             capture_history,
             correction_history,
+            see_cache: SeeCache::new(),
         }
     }
 
@@ -567,6 +872,7 @@ impl SearchTables {
             tt,
             pawn_hash,
             nnue,
+            shared_cache: None,
             killer_moves,
             history,
             counter_moves,
@@ -574,14 +880,30 @@ impl SearchTables {
             countermove_history,
             capture_history,
             correction_history,
+            see_cache: SeeCache::new(),
         }
     }
 
+    /// Memory held by this thread's own tables (histories, killers, counter
+    /// moves and SEE cache), in bytes
+    #[must_use]
+    pub fn per_thread_bytes(&self) -> usize {
+        mem::size_of_val(&self.killer_moves)
+            + mem::size_of_val(&self.history)
+            + mem::size_of_val(&self.counter_moves)
+            + mem::size_of_val(&*self.continuation_history.entries)
+            + mem::size_of_val(&*self.countermove_history.entries)
+            + mem::size_of_val(&self.capture_history)
+            + mem::size_of_val(&*self.correction_history.corrections)
+            + mem::size_of_val(&*self.see_cache.keys)
+            + mem::size_of_val(&*self.see_cache.values)
+    }
+
     /// MVV-LVA score for a capture move, enhanced with capture history
     /// Prioritizes capturing high-value pieces with low-value attackers,
     /// with capture history as a secondary factor
     #[must_use]
-    pub fn mvv_lva_score(&self, board: &Board, mv: &Move) -> i32 {
+    pub fn mvv_lva_score(&mut self, board: &Board, mv: &Move) -> i32 {
         if !mv.is_capture() {
             return 0;
         }
@@ -621,8 +943,8 @@ impl SearchTables {
         // Only compute SEE for potentially bad captures (attacker >= victim)
         // Skip SEE penalty for captures near enemy king (often tactical)
         let see_score = if attacker > captured && !near_king {
-            // Potentially losing capture - use SEE with known pieces (avoids redundant lookups)
-            board.see_with_pieces(mv.from(), mv.to(), attacker_piece, victim_piece) / 10
+            // Potentially losing capture - cached so pruning can reuse it
+            self.see_cache.see(board, mv) / 10
         } else {
             // Good capture, or near enemy king - don't penalize
             0
@@ -635,15 +957,23 @@ impl SearchTables {
         constants::CAPTURE_BASE_SCORE + mvv_lva + see_score + cap_hist
     }
 
-    /// Get history score for a move
+    /// SEE of a move, shared between move ordering and pruning
+    pub fn see(&mut self, board: &Board, mv: &Move) -> i32 {
+        self.see_cache.see(board, mv)
+    }
+
+    /// History score of quiet move `mv` in `board`, where the opponent
+    /// attacks `threats`
     #[must_use]
-    pub fn history_score(&self, mv: &Move) -> i32 {
-        self.history.score(mv)
+    pub fn history_score(&self, board: &Board, mv: Move, threats: Bitboard) -> i32 {
+        HistoryKey::new(board, mv, threats).map_or(0, |key| self.history.score(key))
     }
 
-    /// Update history on beta cutoff with gravity
-    pub fn update_history(&mut self, mv: &Move, depth: u32, ply: usize) {
-        self.history.update(mv, depth, ply);
+    /// Add `bonus` (negative for a malus) to the history of quiet move `mv`
+    pub fn update_history(&mut self, board: &Board, mv: Move, threats: Bitboard, bonus: i32) {
+        if let Some(key) = HistoryKey::new(board, mv, threats) {
+            self.history.update(key, bonus);
+        }
     }
 
     /// Reset history table
@@ -657,6 +987,26 @@ impl SearchTables {
     }
 }
 
+/// Memory held by a search's tables, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    /// Transposition table, shared by all threads
+    pub tt: usize,
+    /// Pawn hash table, shared by all threads
+    pub pawn_hash: usize,
+    /// Histories, killers, counter moves and SEE cache of one thread
+    pub per_thread: usize,
+}
+
+impl MemoryUsage {
+    /// Total for a search on `threads` threads, each with its own
+    /// per-thread tables
+    #[must_use]
+    pub fn total(&self, threads: usize) -> usize {
+        self.tt + self.pawn_hash + self.per_thread * threads.max(1)
+    }
+}
+
 /// Search state persisted across searches
 pub struct SearchState {
     pub stats: SearchStats,
@@ -665,20 +1015,39 @@ pub struct SearchState {
     pub last_move: Move,
     pub hard_stop_at: Option<Instant>,
     pub params: SearchParams,
+    /// LMR table built from the formula constants in `params`
+    pub(crate) lmr: LmrTable,
+    /// Seldepth shared between SMP workers (`None` when searching alone)
+    pub(crate) shared_seldepth: Option<Arc<AtomicU64>>,
+    /// Node count shared between SMP workers (`None` when searching alone)
+    pub(crate) shared_nodes: Option<Arc<AtomicU64>>,
     pub trace: bool,
+    /// Back the transposition table with huge pages when it is reallocated
+    pub large_pages: bool,
+    /// Random root move offsets for opening diversity in self-play
+    pub root_noise: RootNoise,
+    /// Pruning decisions to check at full depth (none by default)
+    pub prune_audit: PruneAudit,
 }
 
 impl SearchState {
     #[must_use]
     pub fn new(tt_mb: usize) -> Self {
+        let params = SearchParams::default();
         SearchState {
             stats: SearchStats::default(),
             tables: SearchTables::new(tt_mb),
             generation: 0,
             last_move: super::EMPTY_MOVE,
             hard_stop_at: None,
-            params: SearchParams::default(),
+            lmr: LmrTable::from_params(&params),
+            params,
+            shared_seldepth: None,
+            shared_nodes: None,
             trace: false,
+            large_pages: false,
+            root_noise: RootNoise::default(),
+            prune_audit: PruneAudit::default(),
         }
     }
 
@@ -691,14 +1060,21 @@ impl SearchState {
         nnue: Option<Arc<NnueNetwork>>,
         generation: u16,
     ) -> Self {
+        let params = SearchParams::default();
         SearchState {
             stats: SearchStats::default(),
             tables: SearchTables::with_shared(tt, pawn_hash, nnue),
             generation,
             last_move: super::EMPTY_MOVE,
             hard_stop_at: None,
-            params: SearchParams::default(),
+            lmr: LmrTable::from_params(&params),
+            params,
+            shared_seldepth: None,
+            shared_nodes: None,
             trace: false,
+            large_pages: false,
+            root_noise: RootNoise::default(),
+            prune_audit: PruneAudit::default(),
         }
     }
 
@@ -740,6 +1116,25 @@ impl SearchState {
         self.tables.counter_moves.reset();
     }
 
+    /// Warm up before the first real search.
+    ///
+    /// Builds the lazily initialized attack tables and runs short searches
+    /// from a few positions, then clears the tables (faulting in every TT
+    /// page). Afterwards searches are identical to those from a cold state,
+    /// just without the first-search startup cost.
+    pub fn warmup(&mut self) {
+        let stop = AtomicBool::new(false);
+        let generation = self.generation;
+        for fen in WARMUP_FENS {
+            let mut board = Board::from_fen(fen);
+            find_best_move(&mut board, self, WARMUP_DEPTH, &stop);
+        }
+        self.tables.clear();
+        self.stats.reset_search();
+        self.generation = generation;
+        self.last_move = super::EMPTY_MOVE;
+    }
+
     pub fn set_max_nodes(&mut self, max_nodes: u64) {
         self.stats.max_nodes = max_nodes;
     }
@@ -771,10 +1166,48 @@ impl SearchState {
     }
 
     pub fn reset_tables(&mut self, tt_mb: usize) {
-        self.tables.tt = Arc::new(TranspositionTable::new(tt_mb));
+        self.tables.tt = Arc::new(TranspositionTable::with_large_pages(
+            tt_mb,
+            self.large_pages,
+        ));
+        self.stats.reset_search();
+    }
+
+    /// Resize the transposition and pawn hash tables so that all tables of
+    /// a search on `threads` threads fit in `hash_mb` megabytes.
+    ///
+    /// The pawn hash gets 1/64 of the budget, up to 1 MB, and the TT what it
+    /// and the fixed-size per-thread tables leave over. With a budget below
+    /// the per-thread tables both shrink to their minimum sizes.
+    pub fn resize_within(&mut self, hash_mb: usize, threads: usize) {
+        let pawn_hash_kb = (hash_mb * PAWN_HASH_KB_PER_MB).min(PAWN_HASH_MAX_KB);
+        self.tables.pawn_hash = Arc::new(crate::pawn_hash::PawnHashTable::new(pawn_hash_kb));
+        self.tables.tt = Arc::new(TranspositionTable::with_size_kb(
+            self.tt_budget_kb(hash_mb, threads),
+            self.large_pages,
+        ));
         self.stats.reset_search();
     }
 
+    /// Largest TT size (KB) that keeps a search on `threads` threads within
+    /// `hash_mb` megabytes
+    #[must_use]
+    pub fn tt_budget_kb(&self, hash_mb: usize, threads: usize) -> usize {
+        let usage = self.memory_usage();
+        let fixed = usage.total(threads) - usage.tt;
+        (hash_mb * MB).saturating_sub(fixed) / 1024
+    }
+
+    /// Memory held by the tables of this state
+    #[must_use]
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            tt: self.tables.tt.size_bytes(),
+            pawn_hash: self.tables.pawn_hash.size_bytes(),
+            per_thread: self.tables.per_thread_bytes(),
+        }
+    }
+
     #[must_use]
     pub fn hashfull_per_mille(&self) -> u32 {
         self.tables.tt.hashfull_per_mille()
@@ -828,6 +1261,11 @@ impl SearchClock {
         *hard = hard_deadline;
     }
 
+    #[must_use]
+    pub fn soft_deadline(&self) -> Option<Instant> {
+        *self.soft_deadline.lock()
+    }
+
     pub fn snapshot(&self) -> (Instant, Option<Instant>, Option<Instant>) {
         let start_time = *self.start_time.lock();
         let soft_deadline = *self.soft_deadline.lock();
@@ -858,6 +1296,15 @@ pub struct SearchConfig {
     pub info_callback: Option<SearchInfoCallback>,
     /// Number of principal variations to search (1 = normal, >1 = `MultiPV`)
     pub multi_pv: u32,
+    /// Only search these root moves (UCI `searchmoves`); empty = all moves
+    pub search_moves: Vec<Move>,
+    /// Never search these root moves (`avoidmoves`), e.g. to ask what else
+    /// is good after the best move has been found
+    pub avoid_moves: Vec<Move>,
+    /// Clock whose soft deadline replaces `time_limit_ms` and may change
+    /// while the search runs, e.g. a ponder search given its time on
+    /// `ponderhit`
+    pub clock: Option<Arc<SearchClock>>,
 }
 
 impl Default for SearchConfig {
@@ -869,6 +1316,9 @@ impl Default for SearchConfig {
             extract_ponder: true,
             info_callback: None,
             multi_pv: 1,
+            search_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            clock: None,
         }
     }
 }
@@ -932,6 +1382,44 @@ impl SearchConfig {
         self.multi_pv = multi_pv.max(1);
         self
     }
+
+    /// Restrict the root to `moves` (UCI `searchmoves`)
+    #[must_use]
+    pub fn with_search_moves(mut self, moves: Vec<Move>) -> Self {
+        self.search_moves = moves;
+        self
+    }
+
+    /// Exclude `moves` at the root (`avoidmoves`)
+    #[must_use]
+    pub fn with_avoid_moves(mut self, moves: Vec<Move>) -> Self {
+        self.avoid_moves = moves;
+        self
+    }
+
+    /// Take the time limit from `clock`, checked again as the search runs
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<SearchClock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Legal root moves left out of the search by `search_moves` and
+    /// `avoid_moves`.
+    ///
+    /// Both lists are turned into one exclusion list, the same one `MultiPV`
+    /// grows as it finds each line.
+    pub(crate) fn excluded_root_moves(&self, board: &mut Board) -> Vec<Move> {
+        board
+            .generate_moves()
+            .iter()
+            .copied()
+            .filter(|m| {
+                self.avoid_moves.contains(m)
+                    || (!self.search_moves.is_empty() && !self.search_moves.contains(m))
+            })
+            .collect()
+    }
 }
 
 /// Information about a completed search iteration.
@@ -949,24 +1437,57 @@ pub struct SearchIterationInfo {
     /// Which PV line this is (1 = best, 2 = second best, etc.)
     /// Currently always 1 - full `MultiPV` is not yet implemented.
     pub multipv: u32,
+    /// Transposition table fill in per mille
+    pub hashfull: u32,
+    /// Periodic progress update sent mid-iteration: only depth, seldepth,
+    /// nodes, nps, time and hashfull are meaningful (no score or PV yet)
+    pub progress: bool,
+    /// Extra diagnostics for this iteration (aspiration resizes, TT hit
+    /// rate, root move ordering), collected only while tracing
+    pub diagnostics: Vec<String>,
+}
+
+impl SearchIterationInfo {
+    /// This info with its score from White's point of view instead of the
+    /// side to move's, for GUIs that expect white-relative scores
+    #[must_use]
+    pub fn white_pov(&self, white_to_move: bool) -> Self {
+        let mut info = self.clone();
+        if !white_to_move {
+            info.score = -info.score;
+            info.mate_in = info.mate_in.map(|m| -m);
+        }
+        info
+    }
 }
 
 /// Callback type for iteration info.
 pub type SearchInfoCallback = Arc<dyn Fn(&SearchIterationInfo) + Send + Sync>;
 
-/// Extract ponder move by making best move and probing TT
-fn extract_ponder_move(board: &mut Board, state: &SearchState, best_move: Move) -> Option<Move> {
+/// Extract ponder move by making best move and probing TT.
+///
+/// A TT hit only means the stored key matched; on a hash collision (or a
+/// torn entry written by another thread) the stored move can belong to a
+/// different position. The best move must be legal here and the ponder move
+/// must be legal in the position after it, otherwise no ponder move is
+/// reported rather than sending the GUI an illegal `ponder` move.
+pub(crate) fn extract_ponder_move(
+    board: &mut Board,
+    tt: &TranspositionTable,
+    best_move: Move,
+) -> Option<Move> {
+    if !board.generate_moves().iter().any(|m| *m == best_move) {
+        return None;
+    }
+
     // Make the best move temporarily
     let info = board.make_move(best_move);
 
     // Probe TT for opponent's expected reply
-    let ponder = state.tables.tt.probe(board.hash).and_then(|entry| {
-        entry.best_move().filter(|mv| {
-            // Verify move is legal
-            let moves = board.generate_moves();
-            moves.iter().any(|m| m == mv)
-        })
-    });
+    let ponder = tt
+        .probe(board.hash)
+        .and_then(|entry| entry.best_move())
+        .filter(|mv| board.generate_moves().iter().any(|m| m == mv));
 
     // Unmake the move
     board.unmake_move(best_move, info);
@@ -991,37 +1512,44 @@ pub fn search(
     config: SearchConfig,
     stop: &AtomicBool,
 ) -> SearchResult {
+    let start = Instant::now();
     let max_depth = config.max_depth.unwrap_or(64);
     let info_callback = config.info_callback.clone();
     let multi_pv = config.multi_pv.max(1);
 
+    // Moves the caller asked to leave out or that the tablebase shows
+    // give away the result; MultiPV adds each line's move
+    let mut excluded_moves = config.excluded_root_moves(board);
+    if let Some(cache) = &state.tables.shared_cache {
+        tablebase::exclude_root_moves(board, &**cache, &mut excluded_moves);
+    }
+
     // For single PV, use the simple path
     if multi_pv == 1 {
-        let best_move = simple::simple_search(
+        let best_move = simple::simple_search_multipv(
             board,
             state,
             max_depth,
             config.time_limit_ms,
+            config.clock.clone(),
             config.node_limit,
             stop,
             info_callback,
+            &excluded_moves,
+            1,
         );
 
         let ponder_move = if config.extract_ponder {
-            best_move.and_then(|mv| extract_ponder_move(board, state, mv))
+            best_move.and_then(|mv| extract_ponder_move(board, &state.tables.tt, mv))
         } else {
             None
         };
 
-        return SearchResult {
-            best_move,
-            ponder_move,
-        };
+        return SearchResult::from_stats(best_move, ponder_move, &state.stats, start);
     }
 
     // MultiPV: search multiple principal variations
-    let mut excluded_moves: Vec<Move> = Vec::new();
-    let mut first_best_move: Option<Move> = None;
+    let mut result = SearchResult::default();
 
     for pv_index in 1..=multi_pv {
         if stop.load(std::sync::atomic::Ordering::Relaxed) {
@@ -1033,6 +1561,7 @@ pub fn search(
             state,
             max_depth,
             config.time_limit_ms,
+            config.clock.clone(),
             config.node_limit,
             stop,
             info_callback.clone(),
@@ -1042,7 +1571,7 @@ pub fn search(
 
         if let Some(mv) = best_move {
             if pv_index == 1 {
-                first_best_move = Some(mv);
+                result = SearchResult::from_stats(Some(mv), None, &state.stats, start);
             }
             excluded_moves.push(mv);
         } else {
@@ -1051,16 +1580,15 @@ pub fn search(
         }
     }
 
-    let ponder_move = if config.extract_ponder {
-        first_best_move.and_then(|mv| extract_ponder_move(board, state, mv))
-    } else {
-        None
-    };
-
-    SearchResult {
-        best_move: first_best_move,
-        ponder_move,
+    if config.extract_ponder {
+        result.ponder_move = result
+            .best_move
+            .and_then(|mv| extract_ponder_move(board, &state.tables.tt, mv));
     }
+    // Score and depth are the best line's; the effort is all the lines'
+    result.nodes = state.stats.nodes;
+    result.elapsed = start.elapsed();
+    result
 }
 
 // ============================================================================
@@ -1077,6 +1605,12 @@ pub fn find_best_move(
     simple::simple_search(board, state, max_depth, 0, 0, stop, None)
 }
 
+/// Quiescence search of `board` alone, with a full window; afterwards
+/// `state.stats.nodes` holds the nodes searched
+pub fn quiescence_search(board: &mut Board, state: &mut SearchState, stop: &AtomicBool) -> i32 {
+    simple::quiescence_search(board, state, stop)
+}
+
 /// Find best move with fixed depth limit, returning ponder move too
 pub fn find_best_move_with_ponder(
     board: &mut Board,
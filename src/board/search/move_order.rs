@@ -1,6 +1,6 @@
 //! Move ordering utilities for search.
 
-use super::super::Piece;
+use super::super::{Move, Piece};
 
 /// Get material value for a piece (in centipawns)
 pub fn piece_value(piece: Piece) -> i32 {
@@ -14,9 +14,31 @@ pub fn piece_value(piece: Piece) -> i32 {
     }
 }
 
+/// Whether `mv` captures back on the square where `prev_move` just captured
+#[must_use]
+pub fn is_recapture(mv: Move, prev_move: Move) -> bool {
+    mv.is_capture() && prev_move.is_capture() && mv.to() == prev_move.to()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::{Square, EMPTY_MOVE};
+
+    #[test]
+    fn test_is_recapture() {
+        let d4 = Square::new(3, 3);
+        let capture = Move::capture(Square::new(4, 4), d4); // exd4
+        let recapture = Move::capture(Square::new(2, 2), d4); // Nxd4
+        let elsewhere = Move::capture(Square::new(2, 2), Square::new(4, 1)); // Nxb5
+        let quiet = Move::quiet(Square::new(2, 2), d4);
+
+        assert!(is_recapture(recapture, capture));
+        assert!(!is_recapture(elsewhere, capture));
+        assert!(!is_recapture(quiet, capture));
+        assert!(!is_recapture(recapture, Move::quiet(Square::new(4, 4), d4)));
+        assert!(!is_recapture(recapture, EMPTY_MOVE));
+    }
 
     #[test]
     fn test_piece_values_ordering() {
@@ -1,5 +1,8 @@
 use std::env;
 
+use super::constants::ONE_PLY;
+use crate::board::eval::PHASE_TOTAL;
+
 #[derive(Clone, Debug)]
 pub struct SearchParams {
     pub null_min_depth: u32,
@@ -15,7 +18,27 @@ pub struct SearchParams {
     pub lmr_min_depth: u32,
     pub lmr_min_move: usize,
     pub lmr_reduction: u32,
-    pub delta_margin: i32,
+    /// LMR formula base, in hundredths of a ply
+    pub lmr_base: i32,
+    /// LMR formula divisor for `ln(depth) * ln(move_index)`, in hundredths
+    pub lmr_divisor: i32,
+    /// Quiescence delta pruning margin with all pieces on the board
+    pub delta_margin_mg: i32,
+    /// Quiescence delta pruning margin in a pawn ending; between the two
+    /// the margin is interpolated by [`Board::phase`](crate::board::Board::phase)
+    pub delta_margin_eg: i32,
+    /// Extension for recapturing on the square of the previous capture,
+    /// in units of `1 / ONE_PLY` ply
+    pub recapture_extension: u32,
+    /// Extension for a passed pawn push to the 7th rank,
+    /// in units of `1 / ONE_PLY` ply
+    pub passed_pawn_extension: u32,
+    /// Extension for a check that does not lose the checking piece by SEE,
+    /// in units of `1 / ONE_PLY` ply
+    pub check_extension: u32,
+    /// A check that loses material by SEE is still extended when the
+    /// checked side has at most this many legal evasions
+    pub check_extension_max_evasions: usize,
 }
 
 impl SearchParams {
@@ -35,10 +58,26 @@ impl SearchParams {
             lmr_min_depth: 3,
             lmr_min_move: 3,
             lmr_reduction: 1,
-            delta_margin: 50,
+            lmr_base: 77,
+            lmr_divisor: 236,
+            delta_margin_mg: 200,
+            delta_margin_eg: 100,
+            recapture_extension: ONE_PLY / 2,
+            passed_pawn_extension: ONE_PLY,
+            check_extension: ONE_PLY,
+            check_extension_max_evasions: 2,
         }
     }
 
+    /// Delta pruning margin at game phase `phase` (see [`Board::phase`](crate::board::Board::phase)):
+    /// wide while pieces remain to create tactics past the captured
+    /// material, tight once only pawns are left
+    #[must_use]
+    pub fn delta_margin(&self, phase: i32) -> i32 {
+        let phase = phase.clamp(0, PHASE_TOTAL);
+        (self.delta_margin_mg * phase + self.delta_margin_eg * (PHASE_TOTAL - phase)) / PHASE_TOTAL
+    }
+
     /// Conservative search parameters (safer pruning thresholds)
     fn conservative() -> Self {
         let mut params = Self::baseline();
@@ -51,7 +90,7 @@ impl SearchParams {
         params.futility_margin = 140;
         params.lmr_min_depth = 4;
         params.lmr_min_move = 4;
-        params.delta_margin = 60;
+        params.delta_margin_eg = 120;
         params
     }
 }
@@ -119,6 +158,28 @@ mod tests {
         assert!(params.lmr_min_move >= 2);
         // LMR should require some minimum depth
         assert!(params.lmr_min_depth >= 2);
+        // Formula constants must keep the divisor positive
+        assert!(params.lmr_divisor > 0);
+    }
+
+    #[test]
+    fn test_extension_params() {
+        let params = SearchParams::baseline();
+        // Fractional extensions never exceed a full ply on their own
+        assert!(params.recapture_extension <= ONE_PLY);
+        assert!(params.passed_pawn_extension <= ONE_PLY);
+        assert!(params.check_extension <= ONE_PLY);
+    }
+
+    #[test]
+    fn test_delta_margin_by_phase() {
+        let params = SearchParams::baseline();
+        assert_eq!(params.delta_margin(PHASE_TOTAL), params.delta_margin_mg);
+        assert_eq!(params.delta_margin(0), params.delta_margin_eg);
+        // Out-of-range phases clamp to the ends
+        assert_eq!(params.delta_margin(PHASE_TOTAL + 4), params.delta_margin_mg);
+        let middle = params.delta_margin(PHASE_TOTAL / 2);
+        assert!(middle > params.delta_margin_eg && middle < params.delta_margin_mg);
     }
 
     #[test]
@@ -0,0 +1,150 @@
+//! Random noise on root move scores.
+//!
+//! Self-play and data generation without an opening book would replay the
+//! same game every time. With noise on, each search in the first plies of
+//! a game gives every root move a random offset of up to ±`centipawns`,
+//! added to its score, so moves of nearly equal value take turns being
+//! played. The offsets come from a seeded generator: the same seed and the
+//! same commands play the same games.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, Move};
+
+/// Largest root noise in centipawns
+pub const MAX_ROOT_NOISE: i32 = 100;
+
+/// Root noise settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootNoiseConfig {
+    /// Largest offset in centipawns (0 = no noise)
+    pub centipawns: i32,
+    /// Plies from the start of the game searched with noise
+    pub plies: u32,
+    /// Seed of the offset generator
+    pub seed: u64,
+}
+
+impl Default for RootNoiseConfig {
+    fn default() -> Self {
+        RootNoiseConfig {
+            centipawns: 0,
+            plies: 8,
+            seed: 0,
+        }
+    }
+}
+
+/// Root move offsets of the current search, drawn from a seeded generator
+#[derive(Clone)]
+pub struct RootNoise {
+    config: RootNoiseConfig,
+    rng: StdRng,
+    /// Offset of each root move; empty when the search has no noise
+    offsets: Vec<(Move, i32)>,
+}
+
+impl Default for RootNoise {
+    fn default() -> Self {
+        RootNoise::new(RootNoiseConfig::default())
+    }
+}
+
+impl RootNoise {
+    /// Noise with `config`, its generator seeded with `config.seed`
+    #[must_use]
+    pub fn new(config: RootNoiseConfig) -> Self {
+        RootNoise {
+            config: RootNoiseConfig {
+                centipawns: config.centipawns.clamp(0, MAX_ROOT_NOISE),
+                ..config
+            },
+            rng: StdRng::seed_from_u64(config.seed),
+            offsets: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> RootNoiseConfig {
+        self.config
+    }
+
+    /// Draw the offsets for a search of `board`, or clear them when noise
+    /// is off or the game is past its first `plies` plies
+    pub(crate) fn draw(&mut self, board: &mut Board) {
+        self.offsets.clear();
+        let played = board.history_len().saturating_sub(1);
+        if self.config.centipawns == 0 || played >= self.config.plies as usize {
+            return;
+        }
+        let range = -self.config.centipawns..=self.config.centipawns;
+        for &mv in &board.generate_moves() {
+            self.offsets.push((mv, self.rng.gen_range(range.clone())));
+        }
+    }
+
+    /// Offset of the root move `mv` in the current search
+    #[inline]
+    pub(crate) fn offset(&self, mv: Move) -> i32 {
+        self.offsets
+            .iter()
+            .find(|&&(m, _)| m == mv)
+            .map_or(0, |&(_, offset)| offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(noise: &RootNoise) -> Vec<i32> {
+        noise.offsets.iter().map(|&(_, offset)| offset).collect()
+    }
+
+    #[test]
+    fn test_offsets_are_seeded_and_bounded() {
+        let config = RootNoiseConfig {
+            centipawns: 20,
+            plies: 8,
+            seed: 7,
+        };
+        let mut board = Board::new();
+        let (mut a, mut b) = (RootNoise::new(config), RootNoise::new(config));
+        a.draw(&mut board);
+        b.draw(&mut board);
+        assert_eq!(offsets(&a).len(), 20);
+        assert_eq!(offsets(&a), offsets(&b));
+        assert!(offsets(&a).iter().all(|o| (-20..=20).contains(o)));
+
+        // The generator moves on, so the next game differs
+        let first = offsets(&a);
+        a.draw(&mut board);
+        assert_ne!(offsets(&a), first);
+
+        let mut other = RootNoise::new(RootNoiseConfig { seed: 8, ..config });
+        other.draw(&mut board);
+        assert_ne!(offsets(&other), first);
+    }
+
+    #[test]
+    fn test_no_offsets_when_off_or_past_the_opening() {
+        let mut board = Board::new();
+        let mut off = RootNoise::default();
+        off.draw(&mut board);
+        assert!(offsets(&off).is_empty());
+
+        let mut noise = RootNoise::new(RootNoiseConfig {
+            centipawns: 20,
+            plies: 2,
+            seed: 0,
+        });
+        board.make_move_uci("e2e4").unwrap();
+        noise.draw(&mut board);
+        assert!(!offsets(&noise).is_empty());
+        board.make_move_uci("e7e5").unwrap();
+        noise.draw(&mut board);
+        assert!(offsets(&noise).is_empty());
+        assert_eq!(noise.offset(board.parse_move("g1f3").unwrap()), 0);
+    }
+}
@@ -19,22 +19,30 @@ mod iterative;
 mod pruning;
 mod quiescence;
 
-pub use iterative::{simple_search, simple_search_multipv};
+pub use iterative::{quiescence_search, simple_search, simple_search_multipv};
 
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 
 use crate::tt::BoundType;
 
 use super::constants::{
     COUNTER_SCORE, KILLER1_SCORE, KILLER2_SCORE, KILLER3_SCORE, LMR_IDX_BASE, LMR_SCORE_THRESHOLD,
-    LMR_TABLE_MAX_DEPTH, LMR_TABLE_MAX_IDX, MATE_THRESHOLD, PAWN_EXTENSION_RANK_BLACK,
-    PAWN_EXTENSION_RANK_WHITE, SCORE_INFINITE, SCORE_NEAR_MATE, SCORE_SAFE_MAX, TT_MOVE_SCORE,
+    MATE_THRESHOLD, NODE_PUBLISH_MASK, ONE_PLY, PAWN_EXTENSION_RANK_BLACK,
+    PAWN_EXTENSION_RANK_WHITE, PROGRESS_INTERVAL_MS, PROGRESS_NODE_MASK, RECAPTURE_BONUS,
+    SCORE_INFINITE, SCORE_NEAR_MATE, SCORE_SAFE_MAX, TB_WIN_SCORE, TT_MOVE_SCORE,
+};
+use super::move_order::is_recapture;
+use super::{
+    history_bonus, score_from_tt, score_to_tt, PruneKind, SearchClock, SearchInfoCallback,
+    SearchIterationInfo, SearchState, MATE_SCORE,
 };
-use super::{SearchInfoCallback, SearchState, MATE_SCORE};
-use crate::board::nnue::network::feature_index;
 use crate::board::nnue::NnueAccumulator;
-use crate::board::{Board, Color, Move, MoveList, ScoredMoveList, Square, EMPTY_MOVE, MAX_PLY};
+use crate::board::{
+    Bitboard, Board, Color, Move, MoveList, ScoredMoveList, Variant, EMPTY_MOVE, MAX_PLY,
+};
+use crate::engine::adjudicate::Wdl;
 
 use super::super::Piece;
 
@@ -44,9 +52,16 @@ pub struct SimpleSearchContext<'a> {
     pub state: &'a mut SearchState,
     pub stop: &'a AtomicBool,
     pub start_time: Instant,
+    /// When `time_limit_ms` started counting: the search start, or the
+    /// `ponderhit` of a ponder search
+    pub time_start: Instant,
     pub time_limit_ms: u64,
+    /// Clock the time limit is taken from when it can change mid-search
+    pub clock: Option<Arc<SearchClock>>,
     pub node_limit: u64,
     pub nodes: u64,
+    /// Nodes searched by earlier `MultiPV` lines of this search
+    pub base_nodes: u64,
     pub initial_depth: u32,
     /// Static eval at each ply for improving detection
     pub static_eval: [i32; MAX_PLY],
@@ -60,12 +75,47 @@ pub struct SimpleSearchContext<'a> {
     pub root_moves: Vec<Move>,
     /// NNUE accumulator stack indexed by ply (heap-allocated)
     pub acc_stack: Box<[NnueAccumulator]>,
+    /// Elapsed time of the last info sent, for periodic progress updates
+    pub last_info_ms: u64,
+}
+
+/// Expected node type, threaded through the search to tune selectivity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeType {
+    /// Full-window node on the principal variation
+    Pv,
+    /// Null-window node expected to fail high
+    Cut,
+    /// Null-window node expected to fail low (every move searched)
+    All,
+}
+
+impl NodeType {
+    /// Node type of the first child searched (the PV stays on the PV)
+    #[must_use]
+    pub fn first_child(self) -> Self {
+        match self {
+            NodeType::Pv => NodeType::Pv,
+            NodeType::Cut => NodeType::All,
+            NodeType::All => NodeType::Cut,
+        }
+    }
+
+    /// Node type of a child searched with a null window
+    #[must_use]
+    pub fn zero_window_child(self) -> Self {
+        match self {
+            NodeType::Pv | NodeType::All => NodeType::Cut,
+            NodeType::Cut => NodeType::All,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
 #[allow(clippy::struct_excessive_bools)]
 struct NodeContext {
     ply: usize,
+    node_type: NodeType,
     is_pv: bool,
     in_check: bool,
     improving: bool,
@@ -73,6 +123,8 @@ struct NodeContext {
     tt_move: Move,
     tt_score: i32,
     tt_bound: BoundType,
+    /// Move that led to this node (`EMPTY_MOVE` at the root or after a null move)
+    prev_move: Move,
     /// Extension for the TT move (from singular extension search)
     singular_extension: u32,
 }
@@ -95,44 +147,60 @@ struct StagedMoveResult {
 }
 
 impl SimpleSearchContext<'_> {
-    /// Compute extensions for a move
-    fn compute_extensions(ctx: &MoveContext, node: &NodeContext) -> u32 {
-        let mut extension = 0u32;
-
-        // Check extension
-        if ctx.gives_check {
-            extension += 1;
+    /// Compute extensions for a move (called after the move has been made).
+    ///
+    /// Returned in `ONE_PLY` units, so fractional extensions accumulate in the
+    /// child's depth.
+    fn compute_extensions(&mut self, ctx: &MoveContext, node: &NodeContext) -> u32 {
+        let params = &self.state.params;
+        let mut units = 0u32;
+
+        // Check extension, gated by SEE so that sacrificial checks (say, a
+        // perpetual-check spree) don't blow up the tree, unless the checked
+        // side is nearly out of moves
+        if ctx.gives_check
+            && (!self.board.can_win_piece_on(ctx.m.to())
+                || self.board.count_evasions() <= params.check_extension_max_evasions)
+        {
+            units += params.check_extension;
         }
 
         // Singular extension
         if ctx.m == node.tt_move && node.singular_extension > 0 {
-            extension += node.singular_extension;
+            units += node.singular_extension * ONE_PLY;
         }
 
-        // Passed pawn extension: pawn on 7th/2nd rank is dangerous
-        if extension == 0 && !ctx.m.is_promotion() {
-            if let Some(crate::board::Piece::Pawn) = ctx.moving_piece {
-                let to_rank = ctx.m.to().rank();
-                if to_rank == PAWN_EXTENSION_RANK_WHITE || to_rank == PAWN_EXTENSION_RANK_BLACK {
-                    extension += 1;
-                }
+        // Recapture extension: capturing back on the square of the previous capture
+        if is_recapture(ctx.m, node.prev_move) {
+            units += params.recapture_extension;
+        }
+
+        // Passed pawn extension: a passed pawn on the 7th/2nd rank is dangerous
+        if units == 0 && !ctx.m.is_promotion() && ctx.moving_piece == Some(Piece::Pawn) {
+            let to = ctx.m.to();
+            let to_rank = to.rank();
+            let mover = self.board.side_to_move().opponent();
+            if (to_rank == PAWN_EXTENSION_RANK_WHITE || to_rank == PAWN_EXTENSION_RANK_BLACK)
+                && self.board.is_passed_pawn(to, mover)
+            {
+                units += params.passed_pawn_extension;
             }
         }
 
-        extension
+        units
     }
 
-    /// Check if a quiet move should be pruned (futility pruning or LMP)
-    fn should_prune_quiet(
+    /// Which pruning, futility or LMP, skips a quiet move, if any
+    fn quiet_pruning(
         &self,
         ctx: &MoveContext,
         node: &NodeContext,
         depth: u32,
         moves_tried: usize,
         alpha: i32,
-    ) -> bool {
+    ) -> Option<PruneKind> {
         if !ctx.is_quiet || node.in_check || ctx.gives_check || node.is_pv {
-            return false;
+            return None;
         }
 
         // Futility pruning
@@ -144,7 +212,7 @@ impl SimpleSearchContext<'_> {
             };
             let futility_margin = self.state.params.futility_margin * depth as i32;
             if static_eval + futility_margin <= alpha {
-                return true;
+                return Some(PruneKind::Futility);
             }
         }
 
@@ -152,29 +220,11 @@ impl SimpleSearchContext<'_> {
         if depth <= self.state.params.lmp_min_depth {
             let lmp_threshold = self.state.params.lmp_move_limit + depth as usize * depth as usize;
             if moves_tried > lmp_threshold {
-                return true;
+                return Some(PruneKind::LateMovePruning);
             }
         }
 
-        false
-    }
-
-    /// Precomputed LMR table - slightly more aggressive than before
-    #[allow(clippy::cast_precision_loss)]
-    fn lmr_table() -> &'static [[u32; LMR_TABLE_MAX_IDX]; LMR_TABLE_MAX_DEPTH] {
-        use std::sync::OnceLock;
-        static TABLE: OnceLock<[[u32; LMR_TABLE_MAX_IDX]; LMR_TABLE_MAX_DEPTH]> = OnceLock::new();
-        TABLE.get_or_init(|| {
-            let mut t = [[0u32; LMR_TABLE_MAX_IDX]; LMR_TABLE_MAX_DEPTH];
-            for (depth, row) in t.iter_mut().enumerate().skip(1) {
-                for (idx, cell) in row.iter_mut().enumerate().skip(1) {
-                    // Stockfish-like LMR: base 0.77, divisor 2.36
-                    let val = (0.77 + (depth as f64).ln() * (idx as f64).ln() / 2.36).floor();
-                    *cell = val.max(0.0) as u32;
-                }
-            }
-            t
-        })
+        None
     }
 
     /// Extract Principal Variation from TT
@@ -260,19 +310,16 @@ impl SimpleSearchContext<'_> {
         node: &NodeContext,
         depth: u32,
         mut alpha: i32,
-        beta: i32,
+        mut beta: i32,
         moves: &MoveList,
         staged: Option<StagedMoveResult>,
     ) -> i32 {
         let ply = node.ply;
         let in_check = node.in_check;
+        let plies = depth / ONE_PLY;
 
-        // Get previous move for counter-move ordering
-        let prev_move = if ply > 0 && ply < MAX_PLY {
-            self.previous_move[ply - 1]
-        } else {
-            EMPTY_MOVE
-        };
+        // Previous move for counter-move ordering
+        let prev_move = node.prev_move;
 
         // Move ordering: TT move, killers, counter, captures, history
         // Use partial sorting (pick_best) to avoid sorting moves we never try
@@ -296,9 +343,14 @@ impl SimpleSearchContext<'_> {
 
         let mut moves_tried = usize::from(tt_move_searched);
 
-        // Track quiet moves for negative history on beta cutoff
+        // Track searched quiet moves for negative history on beta cutoff,
+        // starting with a quiet TT move searched before move generation
         let mut quiets_tried: [Move; 64] = [EMPTY_MOVE; 64];
         let mut quiets_count = 0usize;
+        if tt_move_searched && !node.tt_move.is_capture() && !node.tt_move.is_promotion() {
+            quiets_tried[0] = node.tt_move;
+            quiets_count = 1;
+        }
 
         // Use partial sorting: pick best remaining move each iteration
         // This avoids sorting moves we never try due to early cutoffs
@@ -326,24 +378,31 @@ impl SimpleSearchContext<'_> {
 
             // SEE pruning for quiet moves at shallow depths
             // Skip moves that lose material by moving to an attacked square
+            // (except in three-check, where a check can be worth the material)
             if is_quiet
-                && depth <= 3
+                && plies <= 3
                 && !in_check
                 && move_count > 1
+                && self.board.variant() != Variant::ThreeCheck
                 && !self.board.see_quiet_safe(m.from(), m.to())
             {
+                self.audit_unmade_move_pruning(
+                    PruneKind::QuietSee,
+                    m,
+                    depth.saturating_sub(ONE_PLY),
+                    alpha,
+                    ply,
+                );
                 continue;
             }
 
-            if is_quiet && quiets_count < 64 {
-                // Track quiet moves for negative history (only if not the cutoff move)
-                quiets_tried[quiets_count] = m;
-                quiets_count += 1;
-            }
-
             // Get the piece that's moving for continuation history (before make_move)
             let moving_piece = self.board.piece_at(m.from()).map(|(_, p)| p);
 
+            // Prefetch the child's TT bucket to hide memory latency; it loads
+            // while the accumulator is updated and the move is made
+            self.state.tables.tt.prefetch(self.board.hash_after(m));
+
             // Update NNUE accumulator incrementally (before make_move modifies board)
             if let Some(piece) = moving_piece {
                 self.update_accumulator_for_move(ply, m, piece, self.board.side_to_move());
@@ -352,12 +411,8 @@ impl SimpleSearchContext<'_> {
             // Make move first (we'll check for check after)
             let info = self.board.make_move(m);
 
-            // Prefetch TT entry for this position to hide memory latency
-            // By the time we call alphabeta, the cache line should be loaded
-            self.state.tables.tt.prefetch(self.board.hash);
-
             // Check if move gives check
-            let gives_check = self.board.is_in_check(self.board.side_to_move());
+            let gives_check = self.board.in_check();
 
             if ply < MAX_PLY {
                 self.previous_move[ply] = m;
@@ -376,13 +431,20 @@ impl SimpleSearchContext<'_> {
             };
 
             // Futility pruning and LMP
-            if self.should_prune_quiet(&move_ctx, node, depth, moves_tried, alpha) {
+            if let Some(kind) = self.quiet_pruning(&move_ctx, node, plies, moves_tried, alpha) {
+                let child_depth = depth.saturating_sub(ONE_PLY);
+                self.audit_move_pruning(kind, child_depth, alpha, NodeType::Cut, ply);
                 self.board.unmake_move(m, info);
                 continue;
             }
 
+            if is_quiet && quiets_count < quiets_tried.len() {
+                quiets_tried[quiets_count] = m;
+                quiets_count += 1;
+            }
+
             // LMR reduction
-            let reduction = Self::compute_lmr_reduction(
+            let reduction = self.compute_lmr_reduction(
                 i - 1,
                 move_count,
                 depth,
@@ -392,22 +454,34 @@ impl SimpleSearchContext<'_> {
             );
 
             // Compute extensions
-            let extension = Self::compute_extensions(&move_ctx, node);
+            let extension = self.compute_extensions(&move_ctx, node);
 
             let new_depth = if move_count == 1 {
                 depth + extension
             } else {
-                depth.saturating_sub(1) + extension
+                depth.saturating_sub(ONE_PLY) + extension
             };
 
+            // Root noise: search this move against a window shifted by its
+            // offset, so its score plus the offset compares with alpha and beta
+            let noise = if ply == 0 {
+                self.state.root_noise.offset(m)
+            } else {
+                0
+            };
+            alpha -= noise;
+            beta -= noise;
+
             let mut score: i32;
 
             if i > 1 {
                 // PVS: null window search for non-first moves (i is 1-indexed after pick_best)
+                // Late moves are expected to be refuted
                 score = -self.alphabeta(
                     new_depth.saturating_sub(reduction),
                     -alpha - 1,
                     -alpha,
+                    node.node_type.zero_window_child(),
                     true,
                     ply + 1,
                     EMPTY_MOVE,
@@ -415,17 +489,54 @@ impl SimpleSearchContext<'_> {
 
                 // Re-search at full depth if reduced search found something
                 if reduction > 0 && score > alpha {
-                    score =
-                        -self.alphabeta(new_depth, -alpha - 1, -alpha, true, ply + 1, EMPTY_MOVE);
+                    score = -self.alphabeta(
+                        new_depth,
+                        -alpha - 1,
+                        -alpha,
+                        node.node_type.zero_window_child(),
+                        true,
+                        ply + 1,
+                        EMPTY_MOVE,
+                    );
+                } else if reduction > 0 {
+                    self.audit_move_pruning(
+                        PruneKind::LateMoveReduction,
+                        new_depth,
+                        alpha,
+                        node.node_type.zero_window_child(),
+                        ply,
+                    );
                 }
 
                 // Re-search with full window if PVS found improvement
                 if score > alpha && score < beta {
-                    score = -self.alphabeta(new_depth, -beta, -alpha, true, ply + 1, EMPTY_MOVE);
+                    score = -self.alphabeta(
+                        new_depth,
+                        -beta,
+                        -alpha,
+                        NodeType::Pv,
+                        true,
+                        ply + 1,
+                        EMPTY_MOVE,
+                    );
                 }
             } else {
                 // First move: full window search
-                score = -self.alphabeta(new_depth, -beta, -alpha, true, ply + 1, EMPTY_MOVE);
+                score = -self.alphabeta(
+                    new_depth,
+                    -beta,
+                    -alpha,
+                    node.node_type.first_child(),
+                    true,
+                    ply + 1,
+                    EMPTY_MOVE,
+                );
+            }
+
+            alpha += noise;
+            beta += noise;
+            if score.abs() < MATE_THRESHOLD {
+                score += noise;
             }
 
             self.board.unmake_move(m, info);
@@ -440,14 +551,8 @@ impl SimpleSearchContext<'_> {
 
                 if score > alpha {
                     if score >= beta {
-                        // Penalize quiet moves that didn't cause the cutoff (negative history)
-                        // Don't penalize the cutoff move itself
-                        for quiet_mv in quiets_tried.iter().take(quiets_count) {
-                            if *quiet_mv != m && *quiet_mv != EMPTY_MOVE {
-                                self.state.tables.history.penalize(quiet_mv, depth, ply);
-                            }
-                        }
-                        self.handle_beta_cutoff(m, ply, depth, score, best_move);
+                        let quiets = &quiets_tried[..quiets_count];
+                        self.handle_beta_cutoff(m, ply, plies, score, quiets);
                         return score;
                     }
                     alpha = score;
@@ -465,7 +570,7 @@ impl SimpleSearchContext<'_> {
             };
         }
 
-        self.store_tt(depth, best_score, raised_alpha, best_move);
+        self.store_tt(plies, ply, best_score, raised_alpha, best_move);
 
         // Update correction history for exact bounds (when we have reliable score vs static eval)
         if raised_alpha && ply < MAX_PLY && !in_check && best_score.abs() < SCORE_NEAR_MATE {
@@ -478,48 +583,166 @@ impl SimpleSearchContext<'_> {
                 pawn_hash,
                 static_eval_raw,
                 best_score,
-                depth,
+                plies,
             );
         }
 
         best_score
     }
 
+    /// Record that a node was reached at `ply` (seldepth is 1-based)
+    #[inline]
+    fn update_seldepth(&mut self, ply: usize) {
+        let seldepth = ply as u32 + 1;
+        if seldepth > self.state.stats.seldepth {
+            self.state.stats.seldepth = seldepth;
+        }
+    }
+
+    /// Publish this thread's seldepth to the SMP workers and return the
+    /// maximum seen by any of them (just the local value when searching alone)
+    fn publish_seldepth(&self) -> u32 {
+        let local = self.state.stats.seldepth;
+        match &self.state.shared_seldepth {
+            Some(shared) => {
+                let previous = shared.fetch_max(u64::from(local), Ordering::Relaxed);
+                local.max(previous as u32)
+            }
+            None => local,
+        }
+    }
+
+    /// Count a visited node, adding every `NODE_PUBLISH_MASK + 1` of them
+    /// to the counter shared with the other SMP workers
+    #[inline]
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if self.nodes & NODE_PUBLISH_MASK == 0 {
+            if let Some(shared) = &self.state.shared_nodes {
+                shared.fetch_add(NODE_PUBLISH_MASK + 1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Nodes searched so far by the whole search: earlier `MultiPV` lines
+    /// and, with SMP, every worker. Other workers' nodes since their last
+    /// publication are not included yet.
+    fn search_nodes(&self) -> u64 {
+        let nodes = match &self.state.shared_nodes {
+            Some(shared) => shared.load(Ordering::Relaxed) + (self.nodes & NODE_PUBLISH_MASK),
+            None => self.nodes,
+        };
+        self.base_nodes + nodes
+    }
+
+    /// Nodes per second since the search started. Uses microseconds so the
+    /// figure does not jump around while elapsed time is only a few ms.
+    fn nps(&self) -> u64 {
+        let elapsed_us = self.start_time.elapsed().as_micros() as u64;
+        (self.search_nodes() - self.base_nodes)
+            .saturating_mul(1_000_000)
+            .checked_div(elapsed_us)
+            .unwrap_or(0)
+    }
+
+    /// Send an `info` progress update if no info has gone out for a while,
+    /// so long iterations still show live nodes/nps/hashfull.
+    #[inline]
+    fn maybe_report_progress(&mut self) {
+        if self.nodes & PROGRESS_NODE_MASK != 0 {
+            return;
+        }
+        let Some(cb) = &self.info_callback else {
+            return;
+        };
+        let elapsed = self.start_time.elapsed().as_millis() as u64;
+        if elapsed < self.last_info_ms + PROGRESS_INTERVAL_MS {
+            return;
+        }
+
+        let info = SearchIterationInfo {
+            depth: self.initial_depth,
+            nodes: self.search_nodes(),
+            nps: self.nps(),
+            time_ms: elapsed,
+            score: 0,
+            mate_in: None,
+            pv: String::new(),
+            seldepth: self.publish_seldepth(),
+            tt_hits: self.state.stats.tt_hits,
+            multipv: 1,
+            hashfull: self.state.tables.tt.hashfull_per_mille(),
+            progress: true,
+            diagnostics: Vec::new(),
+        };
+        cb(&info);
+        self.last_info_ms = elapsed;
+    }
+
     /// Check if we should stop searching
     #[inline]
     fn should_stop(&self) -> bool {
         if self.stop.load(Ordering::Relaxed) {
             return true;
         }
-        if self.node_limit > 0 && self.nodes >= self.node_limit {
+        if self.node_limit > 0 && self.search_nodes() >= self.node_limit {
             return true;
         }
-        if self.time_limit_ms > 0 && self.nodes.trailing_zeros() >= 10 {
-            let elapsed = self.start_time.elapsed().as_millis() as u64;
-            if elapsed >= self.time_limit_ms {
-                return true;
-            }
+        self.nodes.trailing_zeros() >= 10 && self.time_limit_reached()
+    }
+
+    /// Whether the time limit has passed; a clock's soft deadline is read
+    /// afresh, so a ponder search stops on time once `ponderhit` sets it
+    fn time_limit_reached(&self) -> bool {
+        if let Some(clock) = &self.clock {
+            return clock
+                .soft_deadline()
+                .is_some_and(|deadline| Instant::now() >= deadline);
         }
+        self.time_limit_ms > 0 && self.time_used_ms() >= self.time_limit_ms
+    }
 
-        false
+    /// Milliseconds spent against the time limit
+    fn time_used_ms(&self) -> u64 {
+        self.time_start.elapsed().as_millis() as u64
+    }
+
+    /// Take the time limit and its start from the clock, if there is one
+    fn sync_clock(&mut self) {
+        let Some(clock) = &self.clock else {
+            return;
+        };
+        let (start, soft_deadline, _) = clock.snapshot();
+        self.time_start = start;
+        self.time_limit_ms = soft_deadline.map_or(0, |deadline| {
+            deadline.saturating_duration_since(start).as_millis() as u64
+        });
     }
 
     /// Evaluate position from side-to-move's perspective.
     /// Uses NNUE with incremental accumulator if available, otherwise HCE.
+    /// Drawish endgames are scaled toward zero either way.
     #[inline]
     fn evaluate(&self, ply: usize) -> i32 {
-        if let Some(ref nnue) = self.state.tables.nnue {
+        let raw = if let Some(ref nnue) = self.state.tables.nnue {
             nnue.evaluate(&self.acc_stack[ply], self.board.white_to_move)
         } else {
             self.board.evaluate_simple()
-        }
+        };
+        self.board.scale_drawish(raw)
     }
 
     /// Evaluation for pruning and qsearch (main workhorse).
     /// Uses HCE; NNUE needs more training data to surpass HCE quality.
+    /// An evaluation from the shared cache takes precedence.
     #[inline]
     fn evaluate_simple(&self, _ply: usize) -> i32 {
-        self.board.evaluate_simple()
+        if let Some(cache) = &self.state.tables.shared_cache {
+            if let Some(eval) = cache.probe_eval(self.board) {
+                return eval;
+            }
+        }
+        self.board.scale_drawish(self.board.evaluate_simple())
     }
 
     /// Initialize the accumulator at the given ply from the current board state.
@@ -549,65 +772,13 @@ impl SimpleSearchContext<'_> {
 
         // Clone parent accumulator to ply+1
         self.acc_stack[ply + 1] = self.acc_stack[ply].clone();
-        let acc = &mut self.acc_stack[ply + 1];
-
-        // Helper: compute feature indices for both perspectives
-        let feat = |piece: Piece, color: Color, sq: usize| -> (usize, usize) {
-            (
-                feature_index(piece.index(), color.index(), sq, 0),
-                feature_index(piece.index(), color.index(), sq, 1),
-            )
-        };
-
-        if m.is_castling() {
-            // King: from -> to
-            let (wf, bf) = feat(Piece::King, moving_color, m.from().index());
-            acc.sub_feature(wf, bf, nnue);
-            let (wf, bf) = feat(Piece::King, moving_color, m.to().index());
-            acc.add_feature(wf, bf, nnue);
-
-            // Rook: determine from/to based on king destination file
-            let (rook_from_file, rook_to_file) = if m.to().file() == 6 {
-                (7, 5) // Kingside
-            } else {
-                (0, 3) // Queenside
-            };
-            let rank = m.from().rank();
-            let rook_from = Square::new(rank, rook_from_file).index();
-            let rook_to = Square::new(rank, rook_to_file).index();
-            let (wf, bf) = feat(Piece::Rook, moving_color, rook_from);
-            acc.sub_feature(wf, bf, nnue);
-            let (wf, bf) = feat(Piece::Rook, moving_color, rook_to);
-            acc.add_feature(wf, bf, nnue);
-        } else {
-            // Remove captured piece if any
-            if m.is_en_passant() {
-                // En passant: captured pawn is on a different square
-                let cap_rank = if moving_color == Color::White {
-                    m.to().rank() - 1
-                } else {
-                    m.to().rank() + 1
-                };
-                let cap_sq = Square::new(cap_rank, m.to().file()).index();
-                let (wf, bf) = feat(Piece::Pawn, moving_color.opponent(), cap_sq);
-                acc.sub_feature(wf, bf, nnue);
-            } else if m.is_capture() {
-                // Normal capture: captured piece is on m.to()
-                if let Some((cap_color, cap_piece)) = self.board.piece_at(m.to()) {
-                    let (wf, bf) = feat(cap_piece, cap_color, m.to().index());
-                    acc.sub_feature(wf, bf, nnue);
-                }
-            }
-
-            // Remove moving piece from source
-            let (wf, bf) = feat(moving_piece, moving_color, m.from().index());
-            acc.sub_feature(wf, bf, nnue);
-
-            // Add piece to destination (may be promoted piece)
-            let placed_piece = m.promotion().unwrap_or(moving_piece);
-            let (wf, bf) = feat(placed_piece, moving_color, m.to().index());
-            acc.add_feature(wf, bf, nnue);
-        }
+        self.board.update_nnue_accumulator(
+            &mut self.acc_stack[ply + 1],
+            m,
+            moving_piece,
+            moving_color,
+            nnue,
+        );
     }
 
     /// Copy accumulator forward for null moves (no pieces change).
@@ -622,6 +793,33 @@ impl SimpleSearchContext<'_> {
         self.board.repetition_counts.get(self.board.hash) > 1
     }
 
+    /// Score of a game already won under the variant's rules (king on the
+    /// hill, third check), as a mate at `ply`
+    #[inline]
+    fn variant_result(&self, ply: usize) -> Option<i32> {
+        let winner = self.board.variant_winner()?;
+        let mate = MATE_SCORE - ply as i32;
+        Some(if winner == self.board.side_to_move() {
+            mate
+        } else {
+            -mate
+        })
+    }
+
+    /// Score of a position the shared cache has a WDL result for, with wins
+    /// and losses as `TB_WIN_SCORE` less `ply` so shorter paths are preferred
+    #[inline]
+    fn cache_result(&self, ply: usize) -> Option<i32> {
+        let cache = self.state.tables.shared_cache.as_ref()?;
+        let wdl = cache.probe_wdl(self.board)?;
+        let win = TB_WIN_SCORE - ply as i32;
+        Some(match wdl {
+            Wdl::Win => win,
+            Wdl::Draw => 0,
+            Wdl::Loss => -win,
+        })
+    }
+
     /// Check if the position is improving (eval better than 2 plies ago)
     #[inline]
     fn is_improving(&self, ply: usize, eval: i32) -> bool {
@@ -661,6 +859,9 @@ impl SimpleSearchContext<'_> {
             prev_move.to().index()
         };
 
+        // Squares the opponent attacks, for the history's threat context
+        let threats = self.board.all_attacks(self.board.side_to_move().opponent());
+
         let mut scored = ScoredMoveList::new();
         for m in moves {
             let score = if *m == tt_move {
@@ -674,10 +875,10 @@ impl SimpleSearchContext<'_> {
             } else if *m == counter {
                 COUNTER_SCORE
             } else if m.is_capture() {
-                self.state.tables.mvv_lva_score(self.board, m)
+                self.capture_order_score(*m, prev_move)
             } else {
                 // Combine history, continuation history, and countermove history for quiet moves
-                let hist = self.state.tables.history_score(m);
+                let hist = self.state.tables.history_score(self.board, *m, threats);
                 let cont_hist = if let Some(piece) = prev_piece {
                     self.state
                         .tables
@@ -708,8 +909,28 @@ impl SimpleSearchContext<'_> {
         scored
     }
 
-    /// Handle beta cutoff: update killers, history, counter moves, continuation history, and TT
-    fn handle_beta_cutoff(&mut self, m: Move, ply: usize, depth: u32, score: i32, best_move: Move) {
+    /// Ordering score of capture `m`: MVV-LVA, with a recapture on the
+    /// square `prev_move` captured on tried before the other captures unless
+    /// it loses material
+    fn capture_order_score(&mut self, m: Move, prev_move: Move) -> i32 {
+        let score = self.state.tables.mvv_lva_score(self.board, &m);
+        if is_recapture(m, prev_move) && self.state.tables.see(self.board, &m) >= 0 {
+            score + RECAPTURE_BONUS
+        } else {
+            score
+        }
+    }
+
+    /// Handle beta cutoff: update killers, history, counter moves, continuation history, and TT.
+    /// `quiets_tried` are the quiet moves searched before `m` at this node.
+    fn handle_beta_cutoff(
+        &mut self,
+        m: Move,
+        ply: usize,
+        depth: u32,
+        score: i32,
+        quiets_tried: &[Move],
+    ) {
         // Update killers for quiet moves
         if !m.is_capture() && ply < MAX_PLY {
             self.state.tables.killer_moves.update(ply, m);
@@ -724,23 +945,13 @@ impl SimpleSearchContext<'_> {
                 }
             }
 
-            // Update continuation history and countermove history
-            if ply > 0 {
-                if let Some(prev_piece) = self.previous_piece[ply - 1] {
-                    let prev_to = self.previous_move[ply - 1].to().index();
-                    self.state
-                        .tables
-                        .continuation_history
-                        .update(prev_piece, prev_to, &m, depth);
-
-                    // Also update countermove history (opponent's piece-to -> our piece-to)
-                    if let Some((_, our_piece)) = self.board.piece_at(m.from()) {
-                        self.state
-                            .tables
-                            .countermove_history
-                            .update(prev_piece, prev_to, our_piece, &m, depth);
-                    }
-                }
+            // Reward the cutoff move and penalize the quiets tried before it
+            // (negative history) in every quiet history table
+            let threats = self.board.all_attacks(self.board.side_to_move().opponent());
+            let bonus = history_bonus(depth);
+            self.update_quiet_histories(m, ply, threats, bonus);
+            for &quiet in quiets_tried.iter().filter(|&&quiet| quiet != m) {
+                self.update_quiet_histories(quiet, ply, threats, -bonus);
             }
         } else if m.is_capture() {
             // Update capture history for captures.
@@ -760,24 +971,55 @@ impl SimpleSearchContext<'_> {
             }
         }
 
-        // Update history with gravity
-        self.state.tables.update_history(&m, depth, ply);
-
         // Store in TT (allow mate scores too)
         if !self.should_stop() {
             self.state.tables.tt.store(
                 self.board.hash,
                 depth,
-                score,
+                score_to_tt(score, ply),
                 BoundType::LowerBound,
-                Some(best_move),
+                Some(m),
                 self.state.generation,
             );
         }
     }
 
+    /// Add `bonus` (negative for a malus) to the history, continuation
+    /// history and countermove history of quiet move `m` at `ply`
+    fn update_quiet_histories(&mut self, m: Move, ply: usize, threats: Bitboard, bonus: i32) {
+        self.state
+            .tables
+            .update_history(self.board, m, threats, bonus);
+
+        // Continuation and countermove history follow the previous move
+        if ply == 0 {
+            return;
+        }
+        let (Some(prev_piece), Some((_, our_piece))) =
+            (self.previous_piece[ply - 1], self.board.piece_at(m.from()))
+        else {
+            return;
+        };
+        let prev_to = self.previous_move[ply - 1].to().index();
+        self.state
+            .tables
+            .continuation_history
+            .update(prev_piece, prev_to, &m, bonus);
+        self.state
+            .tables
+            .countermove_history
+            .update(prev_piece, prev_to, our_piece, &m, bonus);
+    }
+
     /// Store position in transposition table
-    fn store_tt(&mut self, depth: u32, score: i32, raised_alpha: bool, best_move: Move) {
+    fn store_tt(
+        &mut self,
+        depth: u32,
+        ply: usize,
+        score: i32,
+        raised_alpha: bool,
+        best_move: Move,
+    ) {
         if self.should_stop() || best_move == EMPTY_MOVE {
             return;
         }
@@ -789,7 +1031,7 @@ impl SimpleSearchContext<'_> {
         self.state.tables.tt.store(
             self.board.hash,
             depth,
-            score,
+            score_to_tt(score, ply),
             bound,
             Some(best_move),
             self.state.generation,
@@ -801,6 +1043,7 @@ impl SimpleSearchContext<'_> {
     fn probe_tt_for_cutoff(
         &self,
         depth: u32,
+        ply: usize,
         alpha: i32,
         beta: i32,
         is_pv: bool,
@@ -811,12 +1054,12 @@ impl SimpleSearchContext<'_> {
         };
 
         let tt_move = entry.best_move().unwrap_or(EMPTY_MOVE);
-        let tt_score = entry.score();
+        let tt_score = score_from_tt(entry.score(), ply);
         let tt_bound = entry.bound_type();
 
         // Check for cutoff
         if !excluded_move_active && entry.depth() >= depth && !self.is_repetition() {
-            let score = entry.score();
+            let score = tt_score;
             let cutoff = match entry.bound_type() {
                 BoundType::Exact => {
                     if !is_pv || (score > alpha && score < beta) {
@@ -846,10 +1089,12 @@ impl SimpleSearchContext<'_> {
         (tt_move, tt_score, tt_bound, None)
     }
 
-    /// Compute LMR reduction for a move.
+    /// Compute LMR reduction for a move, in `ONE_PLY` units.
     ///
-    /// Uses `NodeContext` and `MoveContext` to reduce parameter count.
+    /// The base reduction comes from the precomputed table and is then adjusted
+    /// by node flags. Uses `NodeContext` and `MoveContext` to reduce parameter count.
     fn compute_lmr_reduction(
+        &self,
         move_idx: usize,
         move_count: usize,
         depth: u32,
@@ -859,42 +1104,51 @@ impl SimpleSearchContext<'_> {
     ) -> u32 {
         let lmr_ok = move_idx > LMR_IDX_BASE + move_count / 4
             && move_ctx.move_score < LMR_SCORE_THRESHOLD
-            && depth > 1
+            && depth > ONE_PLY
             && !node.in_check
             && !move_ctx.gives_check
             && move_ctx.is_quiet
-            && !node.is_pv
             && !tt_tactical;
 
         if lmr_ok {
-            let table = Self::lmr_table();
-            let depth_idx = depth.min((LMR_TABLE_MAX_DEPTH - 1) as u32) as usize;
-            let move_idx_clamped = move_idx.min(LMR_TABLE_MAX_IDX - 1);
-            let mut reduction = table[depth_idx][move_idx_clamped];
+            let mut reduction = self.state.lmr.reduction(depth / ONE_PLY, move_idx);
+
+            // Reduce less in PV nodes, where an exact score matters,
+            // and more in expected cut nodes
+            match node.node_type {
+                NodeType::Pv => reduction = reduction.saturating_sub(ONE_PLY),
+                NodeType::Cut => reduction += ONE_PLY,
+                NodeType::All => {}
+            }
 
             // Reduce less when position is improving (our eval is getting better)
             if node.improving {
-                reduction = reduction.saturating_sub(1);
+                reduction = reduction.saturating_sub(ONE_PLY);
             }
 
             // Reduce less for moves with good history scores
             if move_ctx.move_score > 1000 {
-                reduction = reduction.saturating_sub(1);
+                reduction = reduction.saturating_sub(ONE_PLY);
             }
 
-            reduction.min(depth.saturating_sub(1))
+            reduction.min(depth.saturating_sub(ONE_PLY))
         } else {
             0
         }
     }
 
-    /// Alpha-beta search with all pruning and extension techniques
-    #[allow(clippy::too_many_lines)]
+    /// Alpha-beta search with all pruning and extension techniques.
+    ///
+    /// `depth` is in `ONE_PLY` units; the node drops into quiescence once less
+    /// than a full ply remains. Only `NodeType::Pv` nodes are searched with an
+    /// open window.
+    #[allow(clippy::too_many_lines, clippy::too_many_arguments)]
     pub fn alphabeta(
         &mut self,
         depth: u32,
         mut alpha: i32,
         mut beta: i32,
+        node_type: NodeType,
         allow_null: bool,
         ply: usize,
         excluded_move: Move,
@@ -904,10 +1158,11 @@ impl SimpleSearchContext<'_> {
         const SINGULAR_MARGIN: i32 = 3; // margin per depth
 
         let is_root = ply == 0;
-        let is_pv = beta > alpha + 1;
+        let is_pv = node_type == NodeType::Pv;
         let excluded_move_active = excluded_move != EMPTY_MOVE;
         let mut node = NodeContext {
             ply,
+            node_type,
             is_pv,
             in_check: false,
             improving: false,
@@ -915,35 +1170,41 @@ impl SimpleSearchContext<'_> {
             tt_move: EMPTY_MOVE,
             tt_score: 0,
             tt_bound: BoundType::Exact,
+            prev_move: if ply > 0 && ply < MAX_PLY {
+                self.previous_move[ply - 1]
+            } else {
+                EMPTY_MOVE
+            },
             singular_extension: 0,
         };
 
+        if !is_root {
+            if let Some(score) = self.variant_result(ply) {
+                return score;
+            }
+        }
+
         // Repetition check
         if !is_root && self.is_repetition() {
             return 0;
         }
 
         // Quiescence at leaf
-        if depth == 0 {
+        if depth < ONE_PLY {
             return self.quiesce(alpha, beta, ply, 0);
         }
+        let plies = depth / ONE_PLY;
 
-        self.nodes += 1;
-        if (ply as u32 + 1) > self.state.stats.seldepth {
-            self.state.stats.seldepth = ply as u32 + 1;
-        }
+        self.count_node();
+        self.update_seldepth(ply);
+        self.maybe_report_progress();
 
         // Check stopping conditions periodically
         if self.should_stop() {
             return 0;
         }
 
-        // Check for missing king (illegal position)
-        if self.board.find_king(self.board.side_to_move()).is_none() {
-            return -SCORE_SAFE_MAX;
-        }
-
-        let in_check = self.board.is_in_check(self.board.side_to_move());
+        let in_check = self.board.in_check();
         node.in_check = in_check;
 
         // Mate distance pruning
@@ -957,7 +1218,7 @@ impl SimpleSearchContext<'_> {
 
         // Probe TT for best move and potential cutoff
         let (tt_move, tt_score, tt_bound, tt_cutoff) =
-            self.probe_tt_for_cutoff(depth, alpha, beta, is_pv, excluded_move_active);
+            self.probe_tt_for_cutoff(plies, ply, alpha, beta, is_pv, excluded_move_active);
         node.tt_move = tt_move;
         node.tt_score = tt_score;
         node.tt_bound = tt_bound;
@@ -976,6 +1237,13 @@ impl SimpleSearchContext<'_> {
             }
         }
 
+        // Cached tablebase results are exact
+        if !is_root && !excluded_move_active {
+            if let Some(score) = self.cache_result(ply) {
+                return score;
+            }
+        }
+
         // Static evaluation for pruning decisions (needed before node-level pruning)
         let raw_eval = if in_check {
             -SCORE_INFINITE // Don't use static eval when in check
@@ -1004,7 +1272,12 @@ impl SimpleSearchContext<'_> {
         // NODE-LEVEL PRUNING (before move loop)
         // ========================================================================
 
-        if !is_pv && !in_check && !excluded_move_active {
+        // A pruning audit searches a pruned node again without its pruning
+        if !is_pv
+            && !in_check
+            && !excluded_move_active
+            && !self.state.prune_audit.take_unpruned(ply)
+        {
             if let Some(score) =
                 self.prune_before_move_loop(depth, alpha, beta, eval, &node, allow_null)
             {
@@ -1020,7 +1293,8 @@ impl SimpleSearchContext<'_> {
         let staged_result = if tt_move != EMPTY_MOVE
             && !excluded_move_active
             && !is_root // At root we need all moves for proper PV
-            && !in_check // Simpler handling when not in check
+            && !in_check
+        // Simpler handling when not in check
         {
             self.try_tt_move_first(tt_move, &node, depth, alpha, beta)
         } else {
@@ -1065,19 +1339,20 @@ impl SimpleSearchContext<'_> {
         // alternatives). If so, extend its search by 1 ply.
         if !excluded_move_active
             && !is_root
-            && depth >= SINGULAR_MIN_DEPTH
+            && plies >= SINGULAR_MIN_DEPTH
             && tt_move != EMPTY_MOVE
             && tt_score.abs() < MATE_THRESHOLD
             && matches!(tt_bound, BoundType::LowerBound | BoundType::Exact)
         {
-            let singular_beta = tt_score - SINGULAR_MARGIN * depth as i32;
-            let singular_depth = (depth - 1) / 2;
+            let singular_beta = tt_score - SINGULAR_MARGIN * plies as i32;
+            let singular_depth = (depth - ONE_PLY) / 2;
 
             // Search with TT move excluded
             let singular_score = self.alphabeta(
                 singular_depth,
                 singular_beta - 1,
                 singular_beta,
+                NodeType::Cut,
                 false,
                 ply,
                 tt_move,
@@ -1086,13 +1361,21 @@ impl SimpleSearchContext<'_> {
             if singular_score < singular_beta {
                 // TT move is singular - extend it
                 node.singular_extension = 1;
+            } else if node_type == NodeType::Cut && singular_beta >= beta {
+                // Multi-cut: several moves beat beta even without the TT move
+                return singular_beta;
             }
         }
 
         // Internal Iterative Reduction (IIR)
-        // If we have no TT move at high depth, reduce depth to find a move faster
-        let search_depth = if tt_move == EMPTY_MOVE && depth >= 4 && !excluded_move_active {
-            depth - 1
+        // If we have no TT move at high depth, reduce depth to find a move faster.
+        // All nodes search every move anyway, so ordering matters less there.
+        let search_depth = if tt_move == EMPTY_MOVE
+            && plies >= 4
+            && !excluded_move_active
+            && node_type != NodeType::All
+        {
+            depth - ONE_PLY
         } else {
             depth
         };
@@ -1126,6 +1409,12 @@ impl SimpleSearchContext<'_> {
         // Get the piece that's moving for continuation history
         let moving_piece = self.board.piece_at(tt_move.from()).map(|(_, p)| p);
 
+        // Prefetch TT entry for the new position
+        self.state
+            .tables
+            .tt
+            .prefetch(self.board.hash_after(tt_move));
+
         // Update NNUE accumulator incrementally (before make_move modifies board)
         if let Some(piece) = moving_piece {
             self.update_accumulator_for_move(ply, tt_move, piece, self.board.side_to_move());
@@ -1134,11 +1423,8 @@ impl SimpleSearchContext<'_> {
         // Make the move
         let info = self.board.make_move(tt_move);
 
-        // Prefetch TT entry for the new position
-        self.state.tables.tt.prefetch(self.board.hash);
-
         // Check if move gives check
-        let gives_check = self.board.is_in_check(self.board.side_to_move());
+        let gives_check = self.board.in_check();
 
         if ply < MAX_PLY {
             self.previous_move[ply] = tt_move;
@@ -1154,12 +1440,20 @@ impl SimpleSearchContext<'_> {
             gives_check,
             moving_piece,
         };
-        let extension = Self::compute_extensions(&move_ctx, node);
+        let extension = self.compute_extensions(&move_ctx, node);
         // Standard depth reduction when descending into child node
-        let new_depth = depth.saturating_sub(1) + extension;
+        let new_depth = depth.saturating_sub(ONE_PLY) + extension;
 
         // Full window search for TT move (it's the first move)
-        let score = -self.alphabeta(new_depth, -beta, -alpha, true, ply + 1, EMPTY_MOVE);
+        let score = -self.alphabeta(
+            new_depth,
+            -beta,
+            -alpha,
+            node.node_type.first_child(),
+            true,
+            ply + 1,
+            EMPTY_MOVE,
+        );
 
         self.board.unmake_move(tt_move, info);
 
@@ -1170,7 +1464,7 @@ impl SimpleSearchContext<'_> {
         // Check for beta cutoff
         if score >= beta {
             // Update history heuristics on cutoff
-            self.handle_beta_cutoff(tt_move, ply, depth, score, tt_move);
+            self.handle_beta_cutoff(tt_move, ply, depth / ONE_PLY, score, &[]);
             return Some(StagedMoveResult {
                 score,
                 raised_alpha: true,
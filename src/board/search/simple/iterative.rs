@@ -1,50 +1,104 @@
+use std::fmt::Write;
 use std::time::Instant;
 
-use super::{SimpleSearchContext, MATE_SCORE, MATE_THRESHOLD, SCORE_INFINITE};
-use crate::board::search::SearchInfoCallback;
-use crate::board::{Move, SearchIterationInfo, SearchState, EMPTY_MOVE, MAX_PLY};
-use std::sync::atomic::AtomicBool;
+use super::{
+    NodeType, SimpleSearchContext, MATE_THRESHOLD, NODE_PUBLISH_MASK, ONE_PLY, SCORE_INFINITE,
+};
+use crate::board::search::move_order::piece_value;
+use crate::board::search::{
+    mate_in, IterationStats, PruneAuditStats, SearchClock, SearchInfoCallback,
+};
+use crate::board::{Move, MoveList, Piece, SearchIterationInfo, SearchState, EMPTY_MOVE, MAX_PLY};
+use crate::engine::time::scale_soft_time_by_wdl;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /// Aspiration window constants
 const ASPIRATION_DELTA_SHALLOW: i32 = 35; // Initial delta for depth <= 5
 const ASPIRATION_DELTA_DEEP: i32 = 20; // Initial delta for depth > 5
 const ASPIRATION_MAX_DELTA: i32 = 800; // Fall back to full window above this
 
+/// Root moves listed in the `debug on` move ordering diagnostic
+const ROOT_ORDER_DIAGNOSTIC_MOVES: usize = 8;
+
+/// Easy move constants
+const EASY_MOVE_MARGIN: i32 = 200; // Every alternative must be this much worse
+const EASY_MOVE_VERIFY_DEPTH: u32 = 2; // Depth of the per-alternative margin check
+const EASY_MOVE_STABILITY: u32 = 3; // Iterations the easy move must stay best
+const EASY_MOVE_TIME_DIVISOR: u64 = 4; // Stop after this fraction of the soft time
+
+/// Panic time: once a root fail low happens past this percentage of the
+/// soft time, each fail low extends the soft deadline by
+/// `PANIC_EXTENSION_PERCENT`, up to `PANIC_MAX_FAIL_LOWS` extensions and
+/// never beyond the hard limit
+const PANIC_LATE_PERCENT: u64 = 50;
+const PANIC_EXTENSION_PERCENT: u64 = 50;
+const PANIC_MAX_FAIL_LOWS: u32 = 4;
+
+/// Pieces counted when judging whether a capture wins back lost material
+const EASY_MOVE_MATERIAL: [Piece; 5] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+];
+
+/// Soft deadline for starting another iteration, in ms.
+///
+/// Starts from `soft_time_ms` scaled by the win probability of `score`
+/// (less time when the game is decided, more when it is balanced), then
+/// adjusted for best-move stability and score drops. `fail_lows` counts root
+/// fail lows late in the previous iteration;
+/// each one extends the deadline (panic time) so a collapsing score gets
+/// time to find a rescue, capped at `hard_time_ms`.
+fn soft_deadline_ms(
+    soft_time_ms: u64,
+    hard_time_ms: u64,
+    stability_count: u32,
+    score: i32,
+    previous_score: i32,
+    fail_lows: u32,
+) -> u64 {
+    let mut adjusted_soft_time = scale_soft_time_by_wdl(soft_time_ms, score);
+    if stability_count < 3 {
+        adjusted_soft_time = adjusted_soft_time.saturating_mul(130) / 100;
+    } else if stability_count >= 5 {
+        adjusted_soft_time = adjusted_soft_time.saturating_mul(80) / 100;
+    }
+    if score < previous_score - 30 {
+        adjusted_soft_time = adjusted_soft_time.saturating_mul(140) / 100;
+    }
+    if fail_lows > 0 {
+        let extension = PANIC_EXTENSION_PERCENT * u64::from(fail_lows.min(PANIC_MAX_FAIL_LOWS));
+        adjusted_soft_time =
+            (adjusted_soft_time.saturating_mul(100 + extension) / 100).min(hard_time_ms);
+    }
+    adjusted_soft_time
+}
+
 impl SimpleSearchContext<'_> {
     /// Check if we should stop the current iteration based on time management.
     /// Returns true if we should stop iterating.
     fn should_stop_iteration(
         &self,
         depth: u32,
-        soft_time_ms: u64,
-        stability_count: u32,
-        score: i32,
-        previous_score: i32,
+        soft_deadline_ms: u64,
         prev_iter_nodes: u64,
     ) -> bool {
         if depth <= 4 || self.time_limit_ms == 0 {
             return false;
         }
 
-        let elapsed = self.start_time.elapsed().as_millis() as u64;
-
-        // Base soft time, adjusted for stability and score changes
-        let mut adjusted_soft_time = soft_time_ms;
-        if stability_count < 3 {
-            adjusted_soft_time = adjusted_soft_time.saturating_mul(130) / 100;
-        } else if stability_count >= 5 {
-            adjusted_soft_time = adjusted_soft_time.saturating_mul(80) / 100;
-        }
-        if score < previous_score - 30 {
-            adjusted_soft_time = adjusted_soft_time.saturating_mul(140) / 100;
-        }
+        let elapsed = self.time_used_ms();
 
         // Node-based time check: estimate if we can complete the next depth
-        if elapsed > 0 && prev_iter_nodes > 5000 && depth > 5 {
-            let nps = self.nodes.saturating_mul(1000) / elapsed;
-            if nps > 0 {
-                let estimated_nodes = prev_iter_nodes.saturating_mul(25) / 10;
-                let estimated_time = estimated_nodes.saturating_mul(1000) / nps;
+        // (at the speed of the whole search, pondering included)
+        let search_ms = self.start_time.elapsed().as_millis() as u64;
+        if search_ms > 0 && prev_iter_nodes > 5000 && depth > 5 {
+            let nps = self.nodes.saturating_mul(1000) / search_ms;
+            let estimated_nodes = prev_iter_nodes.saturating_mul(25) / 10;
+            if let Some(estimated_time) = estimated_nodes.saturating_mul(1000).checked_div(nps) {
                 let remaining = self.time_limit_ms.saturating_sub(elapsed);
                 if estimated_time > remaining * 2 {
                     return true;
@@ -52,7 +106,64 @@ impl SimpleSearchContext<'_> {
             }
         }
 
-        elapsed >= adjusted_soft_time
+        elapsed >= soft_deadline_ms
+    }
+
+    /// Whether `m` wins back material we are currently down, i.e. looks like
+    /// the reply to a capture.
+    fn is_recapture(&self, m: Move) -> bool {
+        if !m.is_capture() || m.is_en_passant() {
+            return false;
+        }
+        let Some((_, victim)) = self.board.piece_at(m.to()) else {
+            return false;
+        };
+
+        let us = self.board.side_to_move();
+        let material = |color| {
+            EASY_MOVE_MATERIAL
+                .iter()
+                .map(|&p| piece_value(p) * self.board.pieces_of(color, p).popcount() as i32)
+                .sum::<i32>()
+        };
+        let balance = material(us) - material(us.opponent());
+
+        balance < 0 && balance + piece_value(victim) >= 0
+    }
+
+    /// Check that every root move other than `best` scores at least
+    /// `EASY_MOVE_MARGIN` below `score` in a shallow null-window search.
+    fn alternatives_clearly_worse(&mut self, best: Move, score: i32) -> bool {
+        let threshold = score - EASY_MOVE_MARGIN;
+        let alternatives: Vec<Move> = self
+            .root_moves
+            .iter()
+            .copied()
+            .filter(|&m| m != best)
+            .collect();
+
+        for m in alternatives {
+            if let Some((_, piece)) = self.board.piece_at(m.from()) {
+                self.update_accumulator_for_move(0, m, piece, self.board.side_to_move());
+            }
+            let info = self.board.make_move(m);
+            self.previous_move[0] = m;
+            let alt_score = -self.alphabeta(
+                EASY_MOVE_VERIFY_DEPTH * ONE_PLY,
+                -threshold,
+                -threshold + 1,
+                NodeType::Cut,
+                true,
+                1,
+                EMPTY_MOVE,
+            );
+            self.board.unmake_move(m, info);
+
+            if self.should_stop() || alt_score >= threshold {
+                return false;
+            }
+        }
+        true
     }
 
     /// Iterative deepening with aspiration windows and time management.
@@ -74,14 +185,18 @@ impl SimpleSearchContext<'_> {
         let mut previous_score = score;
         let mut stability_count = 0u32;
         let mut prev_iter_nodes = 0u64;
+        // Root fail lows late in the last iteration, for panic time
+        let mut fail_lows = 0u32;
 
-        // Soft time limit is ~40% of hard limit (can be exceeded for good reasons)
-        let soft_time_ms = self.time_limit_ms * 40 / 100;
+        // Easy move: an obvious recapture found at depth 1 that stays best
+        let mut easy_move: Option<Move> = None;
+        let mut easy_move_stability = 0u32;
 
         // Reset history at start of search
         self.state.tables.reset_history();
         self.state.stats.seldepth = 0;
         self.state.stats.tt_hits = 0;
+        let trace = self.state.trace;
 
         for depth in 1..=max_depth {
             if self.should_stop() {
@@ -89,20 +204,39 @@ impl SimpleSearchContext<'_> {
             }
 
             let iter_start_nodes = self.nodes;
+            let iter_start_tt_hits = self.state.stats.tt_hits;
+            let mut diagnostics = Vec::new();
+
+            // Soft time limit is ~40% of hard limit (can be exceeded for
+            // good reasons); a ponder search gets its limit on ponderhit
+            self.sync_clock();
+            let soft_time_ms = self.time_limit_ms * 40 / 100;
+
+            // Easy move: spend only a fraction of the soft time on it
+            if easy_move.is_some()
+                && easy_move_stability >= EASY_MOVE_STABILITY
+                && self.time_limit_ms > 0
+                && self.time_used_ms() >= soft_time_ms / EASY_MOVE_TIME_DIVISOR
+            {
+                break;
+            }
 
             // Soft time check: if we've used enough time and have a stable best move, stop
-            if self.should_stop_iteration(
-                depth,
+            let soft_deadline = soft_deadline_ms(
                 soft_time_ms,
+                self.time_limit_ms,
                 stability_count,
                 score,
                 previous_score,
-                prev_iter_nodes,
-            ) {
+                fail_lows,
+            );
+            if self.should_stop_iteration(depth, soft_deadline, prev_iter_nodes) {
                 break;
             }
+            fail_lows = 0;
 
             self.initial_depth = depth;
+            let _span = crate::log_span!("iteration", depth, multipv = multipv_index);
 
             // Aspiration window - fixed delta, stability adjustments removed
             let mut delta = if depth <= 5 {
@@ -115,8 +249,15 @@ impl SimpleSearchContext<'_> {
             let mut beta = score.saturating_add(delta);
 
             loop {
-                let new_score =
-                    self.alphabeta(depth, alpha, beta, true, 0, crate::board::EMPTY_MOVE);
+                let new_score = self.alphabeta(
+                    depth * ONE_PLY,
+                    alpha,
+                    beta,
+                    NodeType::Pv,
+                    true,
+                    0,
+                    crate::board::EMPTY_MOVE,
+                );
 
                 if self.should_stop() {
                     break;
@@ -128,12 +269,16 @@ impl SimpleSearchContext<'_> {
                     break;
                 }
 
-                if new_score >= beta {
+                let failed_high = new_score >= beta;
+                if failed_high {
                     // Fail high - widen beta
                     beta = beta.saturating_add(delta);
                     delta = delta.saturating_mul(3) / 2; // Grow by 1.5x instead of 2x
                 } else if new_score <= alpha {
                     // Fail low - widen alpha more aggressively
+                    if self.time_used_ms() >= soft_time_ms * PANIC_LATE_PERCENT / 100 {
+                        fail_lows += 1;
+                    }
                     alpha = alpha.saturating_sub(delta);
                     delta = delta.saturating_mul(2); // Fail low is more critical, widen faster
                 } else {
@@ -146,7 +291,14 @@ impl SimpleSearchContext<'_> {
                     alpha = -SCORE_INFINITE;
                     beta = SCORE_INFINITE;
                 }
+                if trace {
+                    let bound = if failed_high { "high" } else { "low" };
+                    diagnostics.push(format!(
+                        "aspiration depth {depth} fail {bound} score {new_score} window {alpha} {beta}"
+                    ));
+                }
             }
+            let completed = !self.should_stop();
 
             // Get best move from TT
             if let Some(entry) = self.state.tables.tt.probe(self.board.hash) {
@@ -169,8 +321,22 @@ impl SimpleSearchContext<'_> {
             previous_best_move = best_move;
             previous_score = score;
 
+            // Detect an easy move after the first iteration, then track that it stays best
+            if depth == 1 && multipv_index == 1 && self.time_limit_ms > 0 {
+                easy_move = best_move.filter(|&bm| {
+                    self.is_recapture(bm) && self.alternatives_clearly_worse(bm, score)
+                });
+            } else if easy_move.is_some() {
+                if best_move == easy_move {
+                    easy_move_stability += 1;
+                } else {
+                    easy_move = None;
+                }
+            }
+
             // Track nodes for this iteration (for node-based time scaling)
             prev_iter_nodes = self.nodes.saturating_sub(iter_start_nodes);
+            crate::log_event!(score, nodes = prev_iter_nodes, "iteration complete");
 
             // Extract PV from TT, ensuring first move is our best_move
             let pv = if let Some(bm) = best_move {
@@ -180,38 +346,69 @@ impl SimpleSearchContext<'_> {
             };
             let pv_str = Self::format_pv(&pv);
 
+            if trace {
+                let iter_nodes = self.nodes.saturating_sub(iter_start_nodes);
+                let iter_tt_hits = self.state.stats.tt_hits - iter_start_tt_hits;
+                diagnostics.push(format!(
+                    "tt depth {depth} hits {iter_tt_hits} nodes {iter_nodes} permille {}",
+                    (iter_tt_hits * 1000).checked_div(iter_nodes).unwrap_or(0)
+                ));
+                diagnostics.push(self.root_order_diagnostic(depth, best_move));
+            }
+
+            self.state.stats.depth = depth;
+            self.state.stats.score = score;
+            if completed && multipv_index == 1 {
+                self.state.stats.iterations.push(IterationStats {
+                    depth,
+                    nodes: self.search_nodes(),
+                    time_ms: self.start_time.elapsed().as_millis() as u64,
+                });
+            }
+
             if let Some(cb) = &self.info_callback {
                 let elapsed = self.start_time.elapsed().as_millis() as u64;
-                let nps = if elapsed > 0 {
-                    self.nodes * 1000 / elapsed
-                } else {
-                    0
-                };
-                let mate_in = if score.abs() < MATE_THRESHOLD {
-                    None
-                } else if score > 0 {
-                    Some((MATE_SCORE - score + 1) / 2)
-                } else {
-                    Some(-(MATE_SCORE + score + 1) / 2)
-                };
                 let info = SearchIterationInfo {
                     depth,
-                    nodes: self.nodes,
-                    nps,
+                    nodes: self.search_nodes(),
+                    nps: self.nps(),
                     time_ms: elapsed,
                     score,
-                    mate_in,
+                    mate_in: mate_in(score),
                     pv: pv_str,
-                    seldepth: self.state.stats.seldepth,
+                    seldepth: self.publish_seldepth(),
                     tt_hits: self.state.stats.tt_hits,
                     multipv: multipv_index,
+                    hashfull: self.state.tables.tt.hashfull_per_mille(),
+                    progress: false,
+                    diagnostics,
                 };
                 cb(&info);
+                self.last_info_ms = elapsed;
             }
         }
 
         best_move
     }
+
+    /// Describe how the root moves are ordered for the next iteration:
+    /// the first `ROOT_ORDER_DIAGNOSTIC_MOVES` moves with their ordering scores
+    fn root_order_diagnostic(&mut self, depth: u32, best_move: Option<Move>) -> String {
+        let mut moves = MoveList::new();
+        for &m in &self.root_moves {
+            moves.push(m);
+        }
+        let mut scored = self.order_moves(&moves, best_move.unwrap_or(EMPTY_MOVE), 0, EMPTY_MOVE);
+
+        let mut line = format!("root order depth {depth}");
+        for i in 0..ROOT_ORDER_DIAGNOSTIC_MOVES {
+            let Some(entry) = scored.pick_best(i) else {
+                break;
+            };
+            let _ = write!(line, " {}:{}", entry.mv, entry.score);
+        }
+        line
+    }
 }
 
 /// Run the main search algorithm
@@ -229,6 +426,7 @@ pub fn simple_search(
         state,
         max_depth,
         time_limit_ms,
+        None,
         node_limit,
         stop,
         info_callback,
@@ -237,23 +435,43 @@ pub fn simple_search(
     )
 }
 
-/// Run the main search algorithm with `MultiPV` support
+/// Run the main search algorithm with `MultiPV` support; a `clock`
+/// overrides `time_limit_ms` (see [`SearchConfig::clock`])
+///
+/// [`SearchConfig::clock`]: crate::board::SearchConfig::clock
 #[allow(clippy::too_many_arguments)]
 pub fn simple_search_multipv(
     board: &mut crate::board::Board,
     state: &mut SearchState,
     max_depth: u32,
     time_limit_ms: u64,
+    clock: Option<Arc<SearchClock>>,
     node_limit: u64,
     stop: &AtomicBool,
     info_callback: Option<SearchInfoCallback>,
     excluded_moves: &[Move],
     multipv_index: u32,
 ) -> Option<Move> {
-    // Increment generation for TT aging (only on first PV line)
+    // Increment generation for TT aging (only on first PV line); later
+    // lines carry on counting the nodes of the earlier ones
     if multipv_index == 1 {
         state.generation = state.generation.wrapping_add(1);
+        state.stats.nodes = 0;
+        state.stats.prune_audit = PruneAuditStats::default();
+        // SMP workers are handed the main thread's offsets
+        if state.shared_nodes.is_none() {
+            state.root_noise.draw(board);
+        }
     }
+    let base_nodes = state.stats.nodes;
+    state.stats.depth = 0;
+    state.stats.score = 0;
+    if multipv_index == 1 {
+        state.stats.iterations.clear();
+    }
+
+    // Pick up any LMR tunables changed since the last search
+    state.lmr.refresh(&state.params);
 
     // Check for single legal move
     let moves = board.generate_moves();
@@ -268,31 +486,123 @@ pub fn simple_search_multipv(
     if available_moves.is_empty() {
         return None;
     }
-    if available_moves.len() == 1 {
-        return Some(available_moves[0]);
+    // A restricted root is searched even down to one move, so the caller
+    // still gets its score and line
+    if moves.len() == 1 {
+        return Some(moves[0]);
     }
 
+    let start_time = Instant::now();
     let mut ctx = SimpleSearchContext {
         board,
         state,
         stop,
-        start_time: Instant::now(),
+        start_time,
+        time_start: start_time,
         time_limit_ms,
+        clock,
         node_limit,
         nodes: 0,
+        base_nodes,
         initial_depth: 1,
         static_eval: [0; MAX_PLY],
         previous_move: [EMPTY_MOVE; MAX_PLY],
         previous_piece: [None; MAX_PLY],
         info_callback,
         root_moves: available_moves,
-        acc_stack: vec![crate::board::nnue::NnueAccumulator::default(); MAX_PLY + 16].into_boxed_slice(),
+        acc_stack: vec![crate::board::nnue::NnueAccumulator::default(); MAX_PLY + 16]
+            .into_boxed_slice(),
+        last_info_ms: 0,
     };
 
     let result = ctx.iterative_deepening_multipv(max_depth, multipv_index);
 
-    ctx.state.stats.nodes = ctx.nodes;
+    ctx.state.stats.nodes = base_nodes + ctx.nodes;
     ctx.state.stats.total_nodes = ctx.state.stats.total_nodes.saturating_add(ctx.nodes);
+    if let Some(shared) = &ctx.state.shared_nodes {
+        shared.fetch_add(ctx.nodes & NODE_PUBLISH_MASK, Ordering::Relaxed);
+    }
 
     result
 }
+
+/// Quiescence search of the root position with a full window
+pub fn quiescence_search(
+    board: &mut crate::board::Board,
+    state: &mut SearchState,
+    stop: &AtomicBool,
+) -> i32 {
+    let mut ctx = SimpleSearchContext {
+        board,
+        state,
+        stop,
+        start_time: Instant::now(),
+        time_start: Instant::now(),
+        time_limit_ms: 0,
+        clock: None,
+        node_limit: 0,
+        nodes: 0,
+        base_nodes: 0,
+        initial_depth: 0,
+        static_eval: [0; MAX_PLY],
+        previous_move: [EMPTY_MOVE; MAX_PLY],
+        previous_piece: [None; MAX_PLY],
+        info_callback: None,
+        root_moves: Vec::new(),
+        acc_stack: vec![crate::board::nnue::NnueAccumulator::default(); MAX_PLY + 16]
+            .into_boxed_slice(),
+        last_info_ms: 0,
+    };
+    ctx.init_accumulator(0);
+    let score = ctx.quiesce(-SCORE_INFINITE, SCORE_INFINITE, 0, 0);
+    ctx.state.stats.nodes = ctx.nodes;
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOFT: u64 = 400;
+    const HARD: u64 = 1000;
+
+    #[test]
+    fn test_soft_deadline_without_panic() {
+        let base = scale_soft_time_by_wdl(SOFT, 20);
+        // Stable best move and steady score: stop early
+        assert_eq!(soft_deadline_ms(SOFT, HARD, 5, 20, 20, 0), base * 80 / 100);
+        // Unstable best move: a little longer
+        assert_eq!(soft_deadline_ms(SOFT, HARD, 0, 20, 20, 0), base * 130 / 100);
+    }
+
+    #[test]
+    fn test_decided_positions_get_less_time() {
+        let balanced = soft_deadline_ms(SOFT, HARD, 4, 0, 0, 0);
+        assert!(balanced > SOFT);
+        assert_eq!(soft_deadline_ms(SOFT, HARD, 4, 900, 900, 0), SOFT / 2);
+        assert_eq!(soft_deadline_ms(SOFT, HARD, 4, -900, -900, 0), SOFT / 2);
+    }
+
+    #[test]
+    fn test_fail_lows_extend_soft_deadline_up_to_hard_limit() {
+        // Simulate a root score collapsing over several late fail lows
+        let mut previous = soft_deadline_ms(SOFT, HARD, 4, 0, 0, 0);
+        assert_eq!(previous, scale_soft_time_by_wdl(SOFT, 0));
+        for fail_lows in 1..=PANIC_MAX_FAIL_LOWS + 2 {
+            let deadline = soft_deadline_ms(SOFT, HARD, 4, -50, 0, fail_lows);
+            assert!(
+                deadline >= previous,
+                "{fail_lows} fail lows shortened the deadline"
+            );
+            assert!(deadline <= HARD);
+            previous = deadline;
+        }
+        assert_eq!(previous, HARD);
+
+        // One fail low with a stable move: the scaled soft time + 50%
+        assert_eq!(
+            soft_deadline_ms(SOFT, HARD, 4, 0, 0, 1),
+            scale_soft_time_by_wdl(SOFT, 0) * 150 / 100
+        );
+    }
+}
@@ -1,5 +1,7 @@
-use super::super::constants::SCORE_NEAR_MATE;
-use super::{NodeContext, SimpleSearchContext};
+use super::super::constants::{NULL_MOVE_BASE_REDUCTION, ONE_PLY, SCORE_NEAR_MATE};
+use super::super::PruneKind;
+use super::{NodeContext, NodeType, SimpleSearchContext};
+use crate::board::Move;
 
 impl SimpleSearchContext<'_> {
     /// Try null move pruning with verification
@@ -11,28 +13,34 @@ impl SimpleSearchContext<'_> {
         node: &NodeContext,
     ) -> Option<i32> {
         let dominated_phase = self.board.game_phase[self.board.side_to_move().index()];
+        let plies = depth / ONE_PLY;
 
         // Don't do null move in check, with no pieces, at root, or when eval is too low
         // Allow null move when eval is slightly below beta (more aggressive)
         if node.in_check
             || dominated_phase == 0
-            || depth <= 2
-            || depth >= self.initial_depth
+            || plies <= 2
+            || plies >= self.initial_depth
             || node.ply == 0
             || eval < beta - 20
         {
             return None;
         }
 
-        let r = super::super::constants::NULL_MOVE_BASE_REDUCTION + (depth + 1) / 3;
-        let reduced_depth = depth.saturating_sub(r);
+        let r = NULL_MOVE_BASE_REDUCTION + (plies + 1) / 3;
+        let reduced_depth = depth.saturating_sub(r * ONE_PLY);
 
         self.copy_accumulator_for_null_move(node.ply);
+        if node.ply < crate::board::MAX_PLY {
+            self.previous_move[node.ply] = crate::board::EMPTY_MOVE;
+            self.previous_piece[node.ply] = None;
+        }
         let info = self.board.make_null_move();
         let score = -self.alphabeta(
             reduced_depth,
             -beta,
             -beta + 1,
+            NodeType::All,
             false,
             node.ply + 1,
             crate::board::EMPTY_MOVE,
@@ -56,34 +64,40 @@ impl SimpleSearchContext<'_> {
     pub(super) fn try_probcut(&mut self, depth: u32, beta: i32, node: &NodeContext) -> Option<i32> {
         // Very conservative: only at high depths, not in check
         // High margin to avoid pruning tactical positions
-        if depth < 8 || node.in_check || beta.abs() > SCORE_NEAR_MATE {
+        if depth < 8 * ONE_PLY || node.in_check || beta.abs() > SCORE_NEAR_MATE {
             return None;
         }
 
         let probcut_beta = beta + 350;
-        let probcut_depth = depth.saturating_sub(5);
+        let probcut_depth = depth.saturating_sub(5 * ONE_PLY);
 
         // Generate captures and promotions
         let captures = self.board.generate_tactical_moves();
 
         for m in &captures {
             // Only consider good captures (positive SEE)
-            if self.board.see(m.from(), m.to()) < 0 {
+            if self.state.tables.see(self.board, m) < 0 {
                 continue;
             }
 
             // Update NNUE accumulator before make_move
-            if let Some((_, piece)) = self.board.piece_at(m.from()) {
+            let moving_piece = self.board.piece_at(m.from()).map(|(_, p)| p);
+            if let Some(piece) = moving_piece {
                 self.update_accumulator_for_move(node.ply, *m, piece, self.board.side_to_move());
             }
 
             let info = self.board.make_move(*m);
+            if node.ply < crate::board::MAX_PLY {
+                self.previous_move[node.ply] = *m;
+                self.previous_piece[node.ply] = moving_piece;
+            }
 
             // Do a reduced search at probcut_beta
             let score = -self.alphabeta(
                 probcut_depth,
                 -probcut_beta,
                 -probcut_beta + 1,
+                NodeType::All,
                 false,
                 node.ply + 1,
                 crate::board::EMPTY_MOVE,
@@ -112,11 +126,12 @@ impl SimpleSearchContext<'_> {
         beta: i32,
         eval: i32,
     ) -> Option<i32> {
-        if depth >= 8 {
+        let plies = depth / ONE_PLY;
+        if plies >= 8 {
             return None;
         }
 
-        let margin = self.state.params.rfp_margin * depth as i32;
+        let margin = self.state.params.rfp_margin * plies as i32;
         if eval - margin >= beta {
             return Some(beta);
         }
@@ -140,21 +155,114 @@ impl SimpleSearchContext<'_> {
 
         // Reverse futility pruning (static null move)
         if let Some(score) = self.try_reverse_futility_pruning(depth, beta, eval) {
+            self.audit_node_pruning(PruneKind::ReverseFutility, depth, beta, node);
             return Some(score);
         }
 
         // Null move pruning
         if allow_null {
             if let Some(score) = self.try_null_move_pruning(depth, beta, eval, node) {
+                self.audit_node_pruning(PruneKind::NullMove, depth, beta, node);
                 return Some(score);
             }
         }
 
         // ProbCut: reduced search on good captures (conservative settings)
         if let Some(score) = self.try_probcut(depth, beta, node) {
+            self.audit_node_pruning(PruneKind::ProbCut, depth, beta, node);
             return Some(score);
         }
 
         None
     }
+
+    /// Pruning audit of a node cut off by `kind`: search it again without
+    /// node-level pruning and count the cutoff as changed if the node does
+    /// not reach `beta` after all
+    fn audit_node_pruning(&mut self, kind: PruneKind, depth: u32, beta: i32, node: &NodeContext) {
+        if !self.state.prune_audit.sample(kind) {
+            return;
+        }
+        self.state.prune_audit.begin_check(Some(node.ply));
+        let score = self.alphabeta(
+            depth,
+            beta - 1,
+            beta,
+            node.node_type,
+            false,
+            node.ply,
+            crate::board::EMPTY_MOVE,
+        );
+        self.state.prune_audit.end_check();
+        if !self.should_stop() {
+            self.state.stats.prune_audit.record(kind, score < beta);
+        }
+    }
+
+    /// Pruning audit of move `m`, skipped by `kind` before it was made:
+    /// make it and check it as [`audit_move_pruning`](Self::audit_move_pruning)
+    /// does
+    pub(super) fn audit_unmade_move_pruning(
+        &mut self,
+        kind: PruneKind,
+        m: Move,
+        depth: u32,
+        alpha: i32,
+        ply: usize,
+    ) {
+        if !self.state.prune_audit.sample(kind) {
+            return;
+        }
+        let moving_piece = self.board.piece_at(m.from()).map(|(_, p)| p);
+        if let Some(piece) = moving_piece {
+            self.update_accumulator_for_move(ply, m, piece, self.board.side_to_move());
+        }
+        let info = self.board.make_move(m);
+        if ply < crate::board::MAX_PLY {
+            self.previous_move[ply] = m;
+            self.previous_piece[ply] = moving_piece;
+        }
+        self.check_move_pruning(kind, depth, alpha, NodeType::Cut, ply);
+        self.board.unmake_move(m, info);
+    }
+
+    /// Pruning audit of the move just made, skipped or cut short by `kind`:
+    /// search it at `depth` and count the decision as changed if the move
+    /// beats `alpha`
+    pub(super) fn audit_move_pruning(
+        &mut self,
+        kind: PruneKind,
+        depth: u32,
+        alpha: i32,
+        node_type: NodeType,
+        ply: usize,
+    ) {
+        if self.state.prune_audit.sample(kind) {
+            self.check_move_pruning(kind, depth, alpha, node_type, ply);
+        }
+    }
+
+    fn check_move_pruning(
+        &mut self,
+        kind: PruneKind,
+        depth: u32,
+        alpha: i32,
+        node_type: NodeType,
+        ply: usize,
+    ) {
+        self.state.prune_audit.begin_check(None);
+        let score = -self.alphabeta(
+            depth,
+            -alpha - 1,
+            -alpha,
+            node_type,
+            true,
+            ply + 1,
+            crate::board::EMPTY_MOVE,
+        );
+        self.state.prune_audit.end_check();
+        if !self.should_stop() {
+            self.state.stats.prune_audit.record(kind, score > alpha);
+        }
+    }
 }
@@ -2,12 +2,10 @@ use super::super::constants::{MAX_QSEARCH_DEPTH, SCORE_INFINITE};
 use super::super::move_order::piece_value;
 use super::super::MATE_SCORE;
 use super::SimpleSearchContext;
-use crate::board::{ScoredMoveList, EMPTY_MOVE};
+use crate::board::{MoveList, ScoredMoveList, EMPTY_MOVE};
 
-/// Delta pruning safety margin (centipawns)
-const DELTA_MARGIN: i32 = 200;
-
-/// Additional delta margin at deeper qsearch depths
+/// Additional delta margin at deeper qsearch depths, on top of the
+/// phase-dependent `SearchParams::delta_margin`
 const DELTA_MARGIN_DEEP: i32 = 100;
 
 /// SEE threshold at shallow qsearch (prune all losing captures)
@@ -19,14 +17,29 @@ const SEE_THRESHOLD_MEDIUM: i32 = -100;
 /// SEE threshold at deep qsearch (allow more speculative captures)
 const SEE_THRESHOLD_DEEP: i32 = -200;
 
+/// Ordering scores for quiet checks, below every capture
+const COUNTER_CHECK_SCORE: i32 = -1;
+const QUIET_CHECK_SCORE: i32 = -2;
+
 /// Depth thresholds for SEE pruning
 const SEE_SHALLOW_DEPTH: i32 = 2;
 const SEE_MEDIUM_DEPTH: i32 = 5;
 
+/// Stand-pat reduction for a side facing two or more threats: this fraction
+/// of the second most valuable threatened piece, up to `THREAT_PENALTY_MAX`
+const THREAT_PENALTY_DIVISOR: i32 = 4;
+const THREAT_PENALTY_MAX: i32 = 100;
+
 impl SimpleSearchContext<'_> {
     /// Quiescence search for tactical stability with SEE and delta pruning.
     /// `ply` is the total ply from root (for correct mate score adjustment).
+    #[allow(clippy::too_many_lines)]
     pub fn quiesce(&mut self, mut alpha: i32, beta: i32, ply: usize, qdepth: i32) -> i32 {
+        self.count_node();
+        self.update_seldepth(ply);
+        if let Some(score) = self.variant_result(ply) {
+            return score;
+        }
         let stand_pat = self.evaluate_simple(ply);
 
         // Depth limit
@@ -34,10 +47,21 @@ impl SimpleSearchContext<'_> {
             return stand_pat;
         }
 
-        let in_check = self.board.is_in_check(self.board.side_to_move());
+        let in_check = self.board.in_check();
+
+        // With two or more pieces en prise only one can be saved, so the
+        // static eval overstates the position; trim the stand-pat before it
+        // cuts off or raises alpha
+        let stand_pat = if in_check || stand_pat <= alpha {
+            stand_pat
+        } else {
+            stand_pat - self.threat_penalty()
+        };
         let mut best_score = if in_check { -SCORE_INFINITE } else { stand_pat };
 
-        // Generate moves: all moves if in check, captures only otherwise
+        // Generate moves: all moves if in check, captures only otherwise,
+        // plus quiet checks at the first ply
+        let mut quiet_checks = None;
         let moves = if in_check {
             let moves = self.board.generate_moves();
             if moves.is_empty() {
@@ -52,6 +76,9 @@ impl SimpleSearchContext<'_> {
             if alpha < stand_pat {
                 alpha = stand_pat;
             }
+            if qdepth == 0 {
+                quiet_checks = Some(self.board.generate_quiet_checks());
+            }
             self.board.generate_tactical_moves()
         };
 
@@ -64,30 +91,48 @@ impl SimpleSearchContext<'_> {
             .and_then(|e| e.best_move())
             .unwrap_or(EMPTY_MOVE);
 
-        // Sort captures by MVV-LVA, with TT move first (using stack-allocated list)
+        // Sort captures by MVV-LVA, with TT move first and recaptures next
+        // (using stack-allocated list)
+        let prev_move = ply
+            .checked_sub(1)
+            .and_then(|p| self.previous_move.get(p))
+            .copied()
+            .unwrap_or(EMPTY_MOVE);
         let mut sorted_moves = ScoredMoveList::new();
         for m in &moves {
             let score = if *m == tt_move {
                 1_000_000 // TT move first
+            } else if m.is_capture() {
+                self.capture_order_score(*m, prev_move)
             } else {
                 self.state.tables.mvv_lva_score(self.board, m)
             };
             sorted_moves.push(*m, score);
         }
-        if sorted_moves.len() > 3 {
+        if let Some(checks) = &quiet_checks {
+            self.push_quiet_checks(checks, ply, &mut sorted_moves);
+        }
+        if sorted_moves.len() > 3 || quiet_checks.is_some() {
             sorted_moves.sort_by_score_desc();
         }
 
+        let delta_margin = self.state.params.delta_margin(self.board.phase());
         for scored in sorted_moves.iter() {
             let m = scored.mv;
 
-            // Skip non-capture moves in quiescence (shouldn't happen but be safe)
-            if !in_check && !m.is_capture() && !m.is_promotion() {
+            // Quiet moves are only searched as checks at the first ply, and
+            // only if the checking piece can't simply be taken
+            if !in_check
+                && !m.is_capture()
+                && !m.is_promotion()
+                && (quiet_checks.is_none() || !self.board.see_quiet_safe(m.from(), m.to()))
+            {
                 continue;
             }
 
             // Delta pruning: if even winning the captured piece + margin won't raise alpha, skip
-            // Use slightly larger margin at deep depths to be less aggressive
+            // The margin shrinks with the material left (pawn endings hold
+            // fewer tactics beyond the capture) and grows at deep depths
             if !in_check && m.is_capture() {
                 let captured_value = if m.is_en_passant() {
                     piece_value(crate::board::Piece::Pawn)
@@ -97,9 +142,9 @@ impl SimpleSearchContext<'_> {
                     0
                 };
                 let margin = if qdepth <= SEE_SHALLOW_DEPTH {
-                    DELTA_MARGIN
+                    delta_margin
                 } else {
-                    DELTA_MARGIN + DELTA_MARGIN_DEEP
+                    delta_margin + DELTA_MARGIN_DEEP
                 };
                 let delta = captured_value + margin;
                 if stand_pat + delta < alpha {
@@ -111,7 +156,7 @@ impl SimpleSearchContext<'_> {
             // At shallow qsearch, prune all bad captures
             // At deeper qsearch, allow slightly bad captures to find tactics
             if !in_check {
-                let see_score = self.board.see(m.from(), m.to());
+                let see_score = self.state.tables.see(self.board, &m);
                 let see_threshold = if qdepth <= SEE_SHALLOW_DEPTH {
                     SEE_THRESHOLD_SHALLOW
                 } else if qdepth <= SEE_MEDIUM_DEPTH {
@@ -124,16 +169,18 @@ impl SimpleSearchContext<'_> {
                 }
             }
 
-            self.nodes += 1;
+            // Prefetch TT for child position
+            self.state.tables.tt.prefetch(self.board.hash_after(m));
 
             // Update NNUE accumulator before make_move
             if let Some((_, piece)) = self.board.piece_at(m.from()) {
                 self.update_accumulator_for_move(ply, m, piece, self.board.side_to_move());
             }
 
+            if let Some(slot) = self.previous_move.get_mut(ply) {
+                *slot = m;
+            }
             let info = self.board.make_move(m);
-            // Prefetch TT for child position
-            self.state.tables.tt.prefetch(self.board.hash);
             let score = -self.quiesce(-beta, -alpha, ply + 1, qdepth + 1);
             self.board.unmake_move(m, info);
 
@@ -150,4 +197,48 @@ impl SimpleSearchContext<'_> {
 
         best_score
     }
+
+    /// Stand-pat reduction for the side to move when the opponent threatens
+    /// two or more of its pieces (see `Board::threatened_pieces`)
+    fn threat_penalty(&self) -> i32 {
+        let threatened = self.board.threatened_pieces(self.board.side_to_move());
+        if threatened.popcount() < 2 {
+            return 0;
+        }
+        let (mut best, mut second) = (0, 0);
+        for sq in threatened.iter() {
+            let value = self
+                .board
+                .piece_at(sq)
+                .map_or(0, |(_, piece)| piece_value(piece));
+            if value > best {
+                second = best;
+                best = value;
+            } else if value > second {
+                second = value;
+            }
+        }
+        (second / THREAT_PENALTY_DIVISOR).min(THREAT_PENALTY_MAX)
+    }
+
+    /// Queue quiet checks after the captures, the counter move to the
+    /// previous move first
+    fn push_quiet_checks(&self, checks: &MoveList, ply: usize, sorted_moves: &mut ScoredMoveList) {
+        let counter = match ply.checked_sub(1).and_then(|p| self.previous_move.get(p)) {
+            Some(&prev) if prev != EMPTY_MOVE => self
+                .state
+                .tables
+                .counter_moves
+                .get(prev.from().index(), prev.to().index()),
+            _ => EMPTY_MOVE,
+        };
+        for m in checks {
+            let score = if *m == counter {
+                COUNTER_CHECK_SCORE
+            } else {
+                QUIET_CHECK_SCORE
+            };
+            sorted_moves.push(*m, score);
+        }
+    }
 }
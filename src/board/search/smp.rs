@@ -12,14 +12,20 @@
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
+use std::time::Instant;
 
 use crate::board::nnue::NnueNetwork;
 use crate::board::{Board, Move};
+use crate::shared_cache::SharedCache;
 use crate::tt::TranspositionTable;
 
 use super::constants::SCORE_INFINITE;
-use super::simple::simple_search;
-use super::{SearchConfig, SearchInfoCallback, SearchParams, SearchResult, SearchState};
+use super::simple::simple_search_multipv;
+use super::tablebase::exclude_root_moves;
+use super::{
+    extract_ponder_move, IterationStats, PruneAudit, PruneAuditStats, RootNoise, SearchClock,
+    SearchConfig, SearchInfoCallback, SearchParams, SearchResult, SearchState,
+};
 
 /// Shared state across all worker threads
 pub struct SharedSearchState {
@@ -29,9 +35,11 @@ pub struct SharedSearchState {
     pub pawn_hash: Arc<crate::pawn_hash::PawnHashTable>,
     /// Shared NNUE network (optional)
     pub nnue: Option<Arc<NnueNetwork>>,
+    /// Shared result cache (optional)
+    pub shared_cache: Option<Arc<SharedCache>>,
     /// Stop flag checked by all workers
     pub stop: Arc<AtomicBool>,
-    /// Global node counter (sum of all workers)
+    /// Global node counter (sum of all workers), updated while they search
     pub total_nodes: Arc<AtomicU64>,
     /// Maximum selective depth seen
     pub max_seldepth: Arc<AtomicU64>,
@@ -39,6 +47,10 @@ pub struct SharedSearchState {
     pub generation: u16,
     /// Search parameters
     pub params: SearchParams,
+    /// Root move offsets drawn for this search
+    pub root_noise: RootNoise,
+    /// Pruning decisions every worker checks
+    pub prune_audit: PruneAudit,
 }
 
 impl SharedSearchState {
@@ -54,28 +66,21 @@ impl SharedSearchState {
             tt,
             pawn_hash,
             nnue,
+            shared_cache: None,
             stop,
             total_nodes: Arc::new(AtomicU64::new(0)),
             max_seldepth: Arc::new(AtomicU64::new(0)),
             generation,
             params: SearchParams::default(),
+            root_noise: RootNoise::default(),
+            prune_audit: PruneAudit::default(),
         }
     }
 
     /// Update seldepth if this value is higher
     pub fn update_seldepth(&self, seldepth: u32) {
-        let mut current = self.max_seldepth.load(Ordering::Relaxed);
-        while seldepth as u64 > current {
-            match self.max_seldepth.compare_exchange_weak(
-                current,
-                seldepth as u64,
-                Ordering::Relaxed,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => break,
-                Err(c) => current = c,
-            }
-        }
+        self.max_seldepth
+            .fetch_max(u64::from(seldepth), Ordering::Relaxed);
     }
 
     /// Add nodes to global counter
@@ -91,7 +96,13 @@ pub struct WorkerResult {
     pub best_move: Option<Move>,
     pub score: i32,
     pub depth: u32,
+    /// Depth of the last iteration this worker reported
+    pub reached_depth: u32,
     pub nodes: u64,
+    /// Iterations this worker completed
+    pub iterations: Vec<IterationStats>,
+    /// Pruning decisions this worker checked
+    pub prune_audit: PruneAuditStats,
 }
 
 /// Configuration for SMP search
@@ -107,6 +118,11 @@ pub struct SmpConfig {
     pub node_limit: u64,
     /// Optional callback for iteration info
     pub info_callback: Option<SearchInfoCallback>,
+    /// Root moves left out of the search, see
+    /// [`SearchConfig::excluded_root_moves`]
+    pub excluded_moves: Vec<Move>,
+    /// Clock overriding `time_limit_ms`, see [`SearchConfig::clock`]
+    pub clock: Option<Arc<SearchClock>>,
 }
 
 impl Default for SmpConfig {
@@ -117,6 +133,8 @@ impl Default for SmpConfig {
             time_limit_ms: 0,
             node_limit: 0,
             info_callback: None,
+            excluded_moves: Vec::new(),
+            clock: None,
         }
     }
 }
@@ -128,6 +146,8 @@ struct WorkerSearchConfig {
     time_limit_ms: u64,
     node_limit: u64,
     info_callback: Option<SearchInfoCallback>,
+    excluded_moves: Vec<Move>,
+    clock: Option<Arc<SearchClock>>,
 }
 
 impl SmpConfig {
@@ -147,6 +167,8 @@ impl SmpConfig {
             time_limit_ms: self.time_limit_ms,
             node_limit: self.node_limit,
             info_callback: self.info_callback.clone(),
+            excluded_moves: self.excluded_moves.clone(),
+            clock: self.clock.clone(),
         }
     }
 
@@ -198,6 +220,16 @@ fn worker_depth_offset(worker_id: usize) -> i32 {
     }
 }
 
+/// Number of workers worth running with `root_moves` legal root moves.
+///
+/// In endgames the thread count can exceed the root moves. Extra helpers
+/// would then only repeat each other's work, so the pool is clamped to one
+/// worker per root move and the workers left differ by depth offset alone.
+/// A forced move (or no move at all) needs no helpers.
+pub(crate) fn active_workers(requested: usize, root_moves: usize) -> usize {
+    requested.max(1).min(root_moves.max(1))
+}
+
 /// Search thread stack size (32 MB to handle deep recursion)
 const SEARCH_STACK_SIZE: usize = 32 * 1024 * 1024;
 
@@ -210,12 +242,22 @@ const SEARCH_STACK_SIZE: usize = 32 * 1024 * 1024;
 pub fn smp_search(
     board: &Board,
     state: &mut SearchState,
-    config: SmpConfig,
+    mut config: SmpConfig,
     stop: Arc<AtomicBool>,
 ) -> SearchResult {
-    let num_threads = config.num_threads.max(1);
+    let start = Instant::now();
+    if let Some(cache) = &state.tables.shared_cache {
+        exclude_root_moves(&mut board.clone(), &**cache, &mut config.excluded_moves);
+    }
+    let root_moves = board
+        .clone()
+        .generate_moves()
+        .iter()
+        .filter(|m| !config.excluded_moves.contains(m))
+        .count();
+    let num_threads = active_workers(config.num_threads, root_moves);
 
-    // For single-threaded, use the existing path
+    // For single-threaded (or a forced move), use the existing path
     if num_threads == 1 {
         let mut board_clone = board.clone();
         let search_config = SearchConfig {
@@ -225,22 +267,32 @@ pub fn smp_search(
             extract_ponder: true,
             info_callback: config.info_callback,
             multi_pv: 1, // SMP currently only supports single PV
+            search_moves: Vec::new(),
+            avoid_moves: config.excluded_moves,
+            clock: config.clock,
         };
         return super::search(&mut board_clone, state, search_config, &stop);
     }
 
     // Increment generation for new search
     state.generation = state.generation.wrapping_add(1);
-    state.stats.reset_search();
+    state.stats.reset_iteration();
+    state.stats.depth = 0;
+    state.stats.score = 0;
 
-    // Create shared state with the TT, pawn hash, and NNUE from SearchState
-    let shared = Arc::new(SharedSearchState::new(
+    // Create shared state with the TT, pawn hash, NNUE and cache from SearchState
+    let mut shared = SharedSearchState::new(
         state.shared_tt(),
         state.shared_pawn_hash(),
         state.shared_nnue(),
         Arc::clone(&stop),
         state.generation,
-    ));
+    );
+    shared.shared_cache.clone_from(&state.tables.shared_cache);
+    state.root_noise.draw(&mut board.clone());
+    shared.root_noise = state.root_noise.clone();
+    shared.prune_audit = PruneAudit::new(state.prune_audit.sample_every());
+    let shared = Arc::new(shared);
 
     let worker_config = config.to_worker_config();
 
@@ -275,6 +327,7 @@ pub fn smp_search(
 
     // Update stats from shared counters
     state.stats.nodes = shared.total_nodes.load(Ordering::Relaxed);
+    state.stats.total_nodes = state.stats.total_nodes.saturating_add(state.stats.nodes);
     state.stats.seldepth = shared.max_seldepth.load(Ordering::Relaxed) as u32;
 
     // Select best result: prefer main worker (worker 0) as its search is most complete.
@@ -292,23 +345,23 @@ pub fn smp_search(
     let best_move = best_result.and_then(|r| r.best_move);
 
     // Extract ponder move from TT
-    let ponder_move = best_move.and_then(|mv| {
-        let mut temp_board = board.clone();
-        let info = temp_board.make_move(mv);
-        let ponder = shared.tt.probe(temp_board.hash).and_then(|entry| {
-            entry.best_move().filter(|pmv| {
-                let moves = temp_board.generate_moves();
-                moves.iter().any(|m| m == pmv)
-            })
-        });
-        temp_board.unmake_move(mv, info);
-        ponder
-    });
+    let ponder_move =
+        best_move.and_then(|mv| extract_ponder_move(&mut board.clone(), &shared.tt, mv));
 
-    SearchResult {
-        best_move,
-        ponder_move,
+    if let Some(r) = best_result {
+        state.stats.depth = r.reached_depth;
+        state.stats.score = r.score;
+    }
+    state.stats.iterations = results
+        .iter()
+        .find(|r| r.worker_id == 0)
+        .map(|r| r.iterations.clone())
+        .unwrap_or_default();
+    state.stats.prune_audit = PruneAuditStats::default();
+    for r in &results {
+        state.stats.prune_audit.merge(&r.prune_audit);
     }
+    SearchResult::from_stats(best_move, ponder_move, &state.stats, start)
 }
 
 /// Run a single worker thread
@@ -327,6 +380,14 @@ fn run_worker(
         shared.generation,
     );
     local_state.params = shared.params.clone();
+    local_state
+        .tables
+        .shared_cache
+        .clone_from(&shared.shared_cache);
+    local_state.shared_seldepth = Some(Arc::clone(&shared.max_seldepth));
+    local_state.shared_nodes = Some(Arc::clone(&shared.total_nodes));
+    local_state.root_noise = shared.root_noise.clone();
+    local_state.prune_audit = shared.prune_audit.clone();
 
     // Reset local tables for this worker
     local_state.tables.history.decay();
@@ -338,25 +399,30 @@ fn run_worker(
     let depth_offset = worker_depth_offset(worker_id);
     let search_depth = ((config.max_depth as i32) + depth_offset).max(1) as u32;
 
-    // Run search with iterative deepening (handled internally by simple_search)
+    // Run search with iterative deepening (handled internally by simple_search_multipv)
     // Each worker does full iterative deepening from depth 1 to search_depth
-    let move_result = simple_search(
+    let move_result = simple_search_multipv(
         &mut board,
         &mut local_state,
         search_depth,
         config.time_limit_ms,
+        config.clock,
         config.node_limit,
         &shared.stop,
         config.info_callback, // Main worker (id 0) reports info via callback
+        &config.excluded_moves,
+        1,
     );
 
-    // Update shared stats
-    shared.add_nodes(local_state.stats.nodes);
+    // Update shared stats; the nodes were added as they were searched
     shared.update_seldepth(local_state.stats.seldepth);
 
-    // Get best move and score
+    // Get best move and score, from the TT if no iteration was reported
     let best_move = move_result;
-    let best_score = if let Some(entry) = shared.tt.probe(board.hash) {
+    let reached_depth = local_state.stats.depth;
+    let best_score = if reached_depth > 0 {
+        local_state.stats.score
+    } else if let Some(entry) = shared.tt.probe(board.hash) {
         entry.score()
     } else {
         -SCORE_INFINITE
@@ -367,6 +433,23 @@ fn run_worker(
         best_move,
         score: best_score,
         depth: search_depth,
+        reached_depth,
         nodes: local_state.stats.total_nodes,
+        iterations: local_state.stats.iterations,
+        prune_audit: local_state.stats.prune_audit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_workers_clamped_to_root_moves() {
+        assert_eq!(active_workers(8, 20), 8);
+        assert_eq!(active_workers(8, 3), 3);
+        assert_eq!(active_workers(8, 1), 1);
+        assert_eq!(active_workers(8, 0), 1);
+        assert_eq!(active_workers(0, 20), 1);
     }
 }
@@ -0,0 +1,214 @@
+//! Tablebase filtering of root moves.
+//!
+//! When the root position is in the tablebase, the search need not find
+//! out which moves keep its result: the position after every root move is
+//! probed and only the moves with the best outcome are searched, also by
+//! `MultiPV`. Wins and losses are ranked by distance to zeroing (DTZ)
+//! against the fifty-move counter, so a won position is not played into a
+//! fifty-move draw by a move whose win takes too long to reach the next
+//! capture or pawn move, and a lost one prefers moves the rule saves.
+//! Among moves with the same outcome, a win keeps the moves with the
+//! shortest DTZ and a loss the moves with the longest.
+
+use crate::board::{Board, Move};
+use crate::engine::adjudicate::{TablebaseProbe, Wdl};
+
+/// Half-moves without a capture or pawn move that draw the game
+const FIFTY_MOVE_PLIES: u32 = 100;
+
+/// Outcome of a root move by the tablebase, worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum RootRank {
+    Loss,
+    /// Lost, but the fifty-move rule draws first
+    BlessedLoss,
+    Draw,
+    /// Won, but the fifty-move rule draws first
+    CursedWin,
+    Win,
+}
+
+/// Rank of the move that led to `board`, probed from the opponent's side,
+/// and the DTZ tiebreak within the rank, higher being better. Without a
+/// DTZ result a win or loss is assumed to come in time.
+fn rank_after_move(board: &Board, probe: &dyn TablebaseProbe) -> Option<(RootRank, i64)> {
+    let wdl = probe.probe_wdl(board)?;
+    let dtz = probe.probe_dtz(board).map_or(0, i32::unsigned_abs);
+    let in_time = board.halfmove_clock() + dtz <= FIFTY_MOVE_PLIES;
+    let rank = match (wdl, in_time) {
+        (Wdl::Loss, true) => RootRank::Win,
+        (Wdl::Loss, false) => RootRank::CursedWin,
+        (Wdl::Draw, _) => RootRank::Draw,
+        (Wdl::Win, false) => RootRank::BlessedLoss,
+        (Wdl::Win, true) => RootRank::Loss,
+    };
+    // Reach a win's next zeroing move soonest, put off a loss's longest
+    let tiebreak = match wdl {
+        Wdl::Loss => -i64::from(dtz),
+        Wdl::Draw => 0,
+        Wdl::Win => i64::from(dtz),
+    };
+    Some((rank, tiebreak))
+}
+
+/// Add to `excluded` the root moves the tablebase ranks below the best of
+/// the moves not yet excluded, by outcome and then by DTZ. Nothing is
+/// added unless the root and the position after each of those moves are
+/// in the tablebase.
+pub(crate) fn exclude_root_moves(
+    board: &mut Board,
+    probe: &dyn TablebaseProbe,
+    excluded: &mut Vec<Move>,
+) {
+    if probe.probe_wdl(board).is_none() {
+        return;
+    }
+    let mut ranked = Vec::new();
+    for &mv in board
+        .generate_moves()
+        .iter()
+        .filter(|m| !excluded.contains(m))
+    {
+        let info = board.make_move(mv);
+        let rank = rank_after_move(board, probe);
+        board.unmake_move(mv, info);
+        let Some(rank) = rank else {
+            return;
+        };
+        ranked.push((mv, rank));
+    }
+    let Some(best) = ranked.iter().map(|&(_, rank)| rank).max() else {
+        return;
+    };
+    excluded.extend(
+        ranked
+            .into_iter()
+            .filter(|&(_, rank)| rank < best)
+            .map(|(mv, _)| mv),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Results by position hash; positions not listed are unknown
+    struct FakeTablebase(HashMap<u64, (Wdl, i32)>);
+
+    impl TablebaseProbe for FakeTablebase {
+        fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+            self.0.get(&board.hash()).map(|&(wdl, _)| wdl)
+        }
+
+        fn probe_dtz(&self, board: &Board) -> Option<i32> {
+            self.0.get(&board.hash()).map(|&(_, dtz)| dtz)
+        }
+    }
+
+    /// A tablebase giving the root `root` and the position after each
+    /// move the result `child(mv)`, or leaving it out for `None`
+    fn tablebase(
+        board: &mut Board,
+        root: Wdl,
+        child: impl Fn(Move) -> Option<(Wdl, i32)>,
+    ) -> FakeTablebase {
+        let mut results = HashMap::from([(board.hash(), (root, 0))]);
+        for &mv in &board.generate_moves() {
+            let info = board.make_move(mv);
+            if let Some(result) = child(mv) {
+                results.insert(board.hash(), result);
+            }
+            board.unmake_move(mv, info);
+        }
+        FakeTablebase(results)
+    }
+
+    #[test]
+    fn test_keeps_wins_that_beat_the_fifty_move_rule() {
+        // Eighty half-moves since the last capture or pawn move
+        let mut board = Board::from_fen("8/8/8/4k3/8/8/8/R3K3 w - - 80 60");
+        let quick = board.parse_move("a1a4").unwrap();
+        let slow = board.parse_move("e1d1").unwrap();
+        let drawn = board.parse_move("a1a5").unwrap();
+        let probe = tablebase(&mut board, Wdl::Win, |mv| {
+            Some(match mv {
+                m if m == quick => (Wdl::Loss, -15),
+                m if m == drawn => (Wdl::Draw, 0),
+                _ => (Wdl::Loss, -30),
+            })
+        });
+
+        let mut excluded = Vec::new();
+        exclude_root_moves(&mut board, &probe, &mut excluded);
+        let legal = board.generate_moves().len();
+        assert_eq!(excluded.len(), legal - 1);
+        assert!(!excluded.contains(&quick));
+        assert!(excluded.contains(&slow) && excluded.contains(&drawn));
+
+        // Without the quick win the cursed wins still beat the draw
+        let mut excluded = vec![quick];
+        exclude_root_moves(&mut board, &probe, &mut excluded);
+        assert_eq!(excluded, [quick, drawn]);
+    }
+
+    #[test]
+    fn test_prefers_the_shortest_win_and_the_longest_loss() {
+        let mut board = Board::from_fen("8/8/8/4k3/8/8/8/R3K3 w - - 0 1");
+        let fast = board.parse_move("a1a4").unwrap();
+        let also_fast = board.parse_move("a1a3").unwrap();
+        let slower = board.parse_move("a1a2").unwrap();
+        let probe = tablebase(&mut board, Wdl::Win, |mv| {
+            Some(match mv {
+                m if m == fast || m == also_fast => (Wdl::Loss, -5),
+                m if m == slower => (Wdl::Loss, -9),
+                _ => (Wdl::Loss, -20),
+            })
+        });
+        let mut excluded = Vec::new();
+        exclude_root_moves(&mut board, &probe, &mut excluded);
+        let legal = board.generate_moves().len();
+        assert_eq!(excluded.len(), legal - 2);
+        assert!(!excluded.contains(&fast) && !excluded.contains(&also_fast));
+
+        // Next best is the win with the next shortest DTZ
+        let mut excluded = vec![fast, also_fast];
+        exclude_root_moves(&mut board, &probe, &mut excluded);
+        assert_eq!(excluded.len(), legal - 1);
+        assert!(!excluded.contains(&slower));
+
+        // A lost root keeps the moves that put off the loss longest
+        let mut board = Board::from_fen("8/8/8/4k3/8/8/8/r3K3 w - - 0 1");
+        let longest = board.parse_move("e1f2").unwrap();
+        let probe = tablebase(&mut board, Wdl::Loss, |mv| {
+            Some(if mv == longest {
+                (Wdl::Win, 12)
+            } else {
+                (Wdl::Win, 4)
+            })
+        });
+        let mut excluded = Vec::new();
+        exclude_root_moves(&mut board, &probe, &mut excluded);
+        assert_eq!(excluded.len(), board.generate_moves().len() - 1);
+        assert!(!excluded.contains(&longest));
+    }
+
+    #[test]
+    fn test_no_filtering_without_full_tablebase_results() {
+        let mut board = Board::from_fen("8/8/8/4k3/8/8/8/R3K3 w - - 0 1");
+        let unknown = board.parse_move("a1a8").unwrap();
+        let partial = tablebase(&mut board, Wdl::Win, |mv| {
+            (mv != unknown).then_some((Wdl::Draw, 0))
+        });
+        let mut excluded = Vec::new();
+        exclude_root_moves(&mut board, &partial, &mut excluded);
+        assert!(excluded.is_empty());
+
+        // The root itself must be in the tablebase too
+        let mut outside = tablebase(&mut board, Wdl::Win, |_| Some((Wdl::Loss, -5)));
+        outside.0.remove(&board.hash());
+        exclude_root_moves(&mut board, &outside, &mut excluded);
+        assert!(excluded.is_empty());
+    }
+}
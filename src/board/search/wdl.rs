@@ -0,0 +1,96 @@
+//! Win/draw/loss model: turns a centipawn score into outcome probabilities.
+//!
+//! The win probability is a logistic curve of the score,
+//! `1 / (1 + exp((WDL_MIDPOINT - score) / WDL_SPREAD))`: a side
+//! `WDL_MIDPOINT` centipawns ahead wins half its games. The loss
+//! probability is the same curve for the opponent and the rest is drawn, so
+//! balanced positions are mostly drawn and decisive ones are almost never.
+
+/// Score (cp) at which the win probability reaches one half
+const WDL_MIDPOINT: f64 = 150.0;
+
+/// Width of the logistic curve (cp)
+const WDL_SPREAD: f64 = 60.0;
+
+/// Scores are clamped to this before applying the model, so mate scores
+/// count as certain results
+const WDL_MAX_SCORE: i32 = 2000;
+
+/// Outcome probabilities for the side the score belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WdlProbabilities {
+    pub win: f64,
+    pub draw: f64,
+    pub loss: f64,
+}
+
+impl WdlProbabilities {
+    /// Probabilities for a centipawn `score` (mate scores included)
+    #[must_use]
+    pub fn from_score(score: i32) -> Self {
+        let score = score.clamp(-WDL_MAX_SCORE, WDL_MAX_SCORE);
+        let win = win_rate(score);
+        let loss = win_rate(-score);
+        WdlProbabilities {
+            win,
+            draw: 1.0 - win - loss,
+            loss,
+        }
+    }
+
+    /// Expected game score, from 0 (certain loss) to 1 (certain win)
+    #[must_use]
+    pub fn expected_score(&self) -> f64 {
+        self.win + self.draw / 2.0
+    }
+
+    /// Per-mille values `[win, draw, loss]` summing to 1000, as in the UCI
+    /// `wdl` info field
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn permille(&self) -> [u32; 3] {
+        let win = (self.win * 1000.0).round() as u32;
+        let loss = (self.loss * 1000.0).round() as u32;
+        [win, 1000 - win - loss, loss]
+    }
+}
+
+/// Win probability for a clamped score
+fn win_rate(score: i32) -> f64 {
+    1.0 / (1.0 + ((WDL_MIDPOINT - f64::from(score)) / WDL_SPREAD).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balanced_position_is_mostly_drawn() {
+        let wdl = WdlProbabilities::from_score(0);
+        assert!((wdl.win - wdl.loss).abs() < 1e-12);
+        assert!(wdl.draw > 0.8);
+        assert!((wdl.expected_score() - 0.5).abs() < 1e-12);
+        assert_eq!(wdl.permille().iter().sum::<u32>(), 1000);
+    }
+
+    #[test]
+    fn test_probabilities_follow_the_score() {
+        let mut previous = WdlProbabilities::from_score(-1000);
+        for score in (-900..=1000).step_by(100) {
+            let wdl = WdlProbabilities::from_score(score);
+            assert!(wdl.win > previous.win && wdl.loss < previous.loss);
+            assert!(wdl.draw >= 0.0);
+            previous = wdl;
+        }
+
+        let mirrored = WdlProbabilities::from_score(-250);
+        let wdl = WdlProbabilities::from_score(250);
+        assert!((wdl.win - mirrored.loss).abs() < 1e-12);
+
+        // A mate score is a certain win
+        assert_eq!(
+            WdlProbabilities::from_score(29_000).permille(),
+            [1000, 0, 0]
+        );
+    }
+}
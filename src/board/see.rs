@@ -3,9 +3,11 @@
 //! Evaluates capture sequences on a single square to determine
 //! if a capture is winning, losing, or equal.
 
-use super::attack_tables::{slider_attacks, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
+use super::attack_tables::{
+    bishop_attacks, slider_attacks, KING_ATTACKS, KNIGHT_ATTACKS, LINE, PAWN_ATTACKS,
+};
 use super::state::Board;
-use super::types::{Bitboard, Color, Piece, Square};
+use super::types::{Bitboard, Color, Move, Piece, Square};
 
 /// Piece values for SEE (simpler than eval values)
 const SEE_VALUES: [i32; 6] = [
@@ -18,7 +20,10 @@ const SEE_VALUES: [i32; 6] = [
 ];
 
 impl Board {
-    /// Static Exchange Evaluation for a capture move.
+    /// Static Exchange Evaluation for a capture given by its squares.
+    ///
+    /// A promoting capture is scored as a pawn capture; use
+    /// [`see`](Self::see) to count the promotion.
     ///
     /// Returns the material balance after all exchanges on the target square.
     /// Positive = winning for the side making the initial capture.
@@ -32,7 +37,7 @@ impl Board {
     /// # Returns
     /// Material balance in centipawns from the perspective of the side to move.
     #[must_use]
-    pub fn see(&self, from: Square, to: Square) -> i32 {
+    pub fn see_squares(&self, from: Square, to: Square) -> i32 {
         // Get the piece being captured
         let captured = match self.piece_at(to) {
             Some((_, piece)) => piece,
@@ -51,7 +56,41 @@ impl Board {
             return 0;
         };
 
-        self.see_impl(from, to, attacker, captured)
+        self.see_impl(from, to, attacker, SEE_VALUES[captured.index()])
+    }
+
+    /// Static Exchange Evaluation of `mv`, promotions included.
+    ///
+    /// Like [`see_squares`](Self::see_squares), but a promoting pawn turns
+    /// into the new piece before the exchange goes on: the promotion gain
+    /// counts, and the opponent's recapture takes the promoted piece. A
+    /// push to a defended promotion square thus loses the pawn, and one to
+    /// an undefended square wins the difference. Other non-captures score 0.
+    ///
+    /// # Example
+    /// ```
+    /// use chess_engine::board::Board;
+    ///
+    /// let mut board = Board::from_fen("7k/P7/8/8/8/8/8/4K3 w - - 0 1");
+    /// let promote = board.parse_move("a7a8q").unwrap();
+    /// assert_eq!(board.see(promote), 800);
+    /// ```
+    #[must_use]
+    pub fn see(&self, mv: Move) -> i32 {
+        let victim = if mv.is_en_passant() {
+            Some(Piece::Pawn)
+        } else {
+            self.piece_at(mv.to()).map(|(_, piece)| piece)
+        };
+        let victim_value = victim.map_or(0, |piece| SEE_VALUES[piece.index()]);
+        match mv.promotion() {
+            Some(promoted) => {
+                let gain = SEE_VALUES[promoted.index()] - SEE_VALUES[Piece::Pawn.index()];
+                self.see_impl(mv.from(), mv.to(), promoted, victim_value + gain)
+            }
+            None if victim.is_some() => self.see_squares(mv.from(), mv.to()),
+            None => 0,
+        }
     }
 
     /// SEE with known attacker and victim pieces.
@@ -60,11 +99,12 @@ impl Board {
     #[inline]
     #[must_use]
     pub fn see_with_pieces(&self, from: Square, to: Square, attacker: Piece, victim: Piece) -> i32 {
-        self.see_impl(from, to, attacker, victim)
+        self.see_impl(from, to, attacker, SEE_VALUES[victim.index()])
     }
 
-    /// SEE implementation with known attacker and victim.
-    fn see_impl(&self, from: Square, to: Square, attacker: Piece, victim: Piece) -> i32 {
+    /// SEE implementation: `attacker` is the piece standing on `to` after
+    /// the first capture, which gained `first_gain`.
+    fn see_impl(&self, from: Square, to: Square, attacker: Piece, first_gain: i32) -> i32 {
         // Maximum depth of exchanges (should never be exceeded)
         const MAX_DEPTH: usize = 32;
 
@@ -82,7 +122,7 @@ impl Board {
         let mut attackers = self.attackers_to(to, Bitboard(occupancy));
 
         // Initial capture value
-        gain[0] = SEE_VALUES[victim.index()];
+        gain[0] = first_gain;
 
         // Current attacker
         let mut current_attacker = attacker;
@@ -93,25 +133,20 @@ impl Board {
             occupancy ^= from_bb.0;
             attackers = Bitboard(attackers.0 & !from_bb.0);
 
-            // Check for x-ray attacks revealed by removing this piece
-            // Only sliders can have x-ray attacks
-            if current_attacker == Piece::Pawn
-                || current_attacker == Piece::Bishop
-                || current_attacker == Piece::Queen
-            {
-                // Diagonal x-ray
-                let diag_attackers = self.diagonal_sliders();
-                let new_diag =
-                    Bitboard(slider_attacks(to.index(), occupancy, true) & diag_attackers.0);
-                attackers = Bitboard(attackers.0 | (new_diag.0 & occupancy));
-            }
-
-            if current_attacker.attacks_straight() {
-                // Straight x-ray
-                let straight_attackers = self.straight_sliders();
-                let new_straight =
-                    Bitboard(slider_attacks(to.index(), occupancy, false) & straight_attackers.0);
-                attackers = Bitboard(attackers.0 | (new_straight.0 & occupancy));
+            // Check for x-ray attacks revealed by removing this piece.
+            // A slider can only be uncovered on the line through the target
+            // and the square just vacated.
+            let from_idx = from_bb.0.trailing_zeros() as usize;
+            let xray_line = LINE[to.index()][from_idx];
+            if xray_line != 0 {
+                let diagonal = bishop_attacks(to.index(), 0) & from_bb.0 != 0;
+                let sliders = if diagonal {
+                    self.diagonal_sliders()
+                } else {
+                    self.straight_sliders()
+                };
+                let revealed = slider_attacks(to.index(), occupancy, diagonal) & sliders.0;
+                attackers = Bitboard(attackers.0 | (revealed & xray_line & occupancy));
             }
 
             // Switch sides
@@ -172,7 +207,7 @@ impl Board {
     }
 
     /// Get all pieces attacking a square.
-    fn attackers_to(&self, sq: Square, occupancy: Bitboard) -> Bitboard {
+    pub(crate) fn attackers_to(&self, sq: Square, occupancy: Bitboard) -> Bitboard {
         let sq_idx = sq.index();
         let mut attackers = Bitboard(0);
 
@@ -250,7 +285,7 @@ impl Board {
     #[inline]
     #[must_use]
     pub fn see_ge(&self, from: Square, to: Square, threshold: i32) -> bool {
-        self.see(from, to) >= threshold
+        self.see_squares(from, to) >= threshold
     }
 
     /// Check if a quiet move is safe using SEE.
@@ -319,6 +354,59 @@ impl Board {
 
         true
     }
+
+    /// Whether the side to move wins material by capturing the enemy piece
+    /// on `sq` with its least valuable attacker (SEE above zero).
+    ///
+    /// After a move, this tells whether the moved piece can be won back.
+    #[must_use]
+    pub fn can_win_piece_on(&self, sq: Square) -> bool {
+        let us = self.side_to_move();
+        let Some((owner, victim)) = self.piece_at(sq) else {
+            return false;
+        };
+        if owner == us {
+            return false;
+        }
+        let attackers =
+            Bitboard(self.attackers_to(sq, self.all_occupied).0 & self.occupied_by(us).0);
+        if attackers.is_empty() {
+            return false;
+        }
+        let (attacker, from) = self.find_least_valuable_attacker(attackers, us);
+        from.iter()
+            .next()
+            .is_some_and(|from| self.see_with_pieces(from, sq, attacker, victim) > 0)
+    }
+
+    /// Pieces of `color`, other than pawns and the king, that the opponent
+    /// threatens to win: attacked and undefended, or attacked by a piece
+    /// worth at least a pawn less.
+    #[must_use]
+    pub fn threatened_pieces(&self, color: Color) -> Bitboard {
+        let them = color.opponent();
+        let ours = self.occupied_by(color).0;
+        let theirs = self.occupied_by(them).0;
+        let mut threatened = Bitboard(0);
+
+        for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+            for sq in self.pieces_of(color, piece).iter() {
+                let attackers = self.attackers_to(sq, self.all_occupied);
+                let enemy_attackers = Bitboard(attackers.0 & theirs);
+                if enemy_attackers.is_empty() {
+                    continue;
+                }
+                let (attacker, _) = self.find_least_valuable_attacker(enemy_attackers, them);
+                let cheaper = SEE_VALUES[attacker.index()] + SEE_VALUES[Piece::Pawn.index()]
+                    <= SEE_VALUES[piece.index()];
+                if attackers.0 & ours == 0 || cheaper {
+                    threatened.0 |= 1u64 << sq.index();
+                }
+            }
+        }
+
+        threatened
+    }
 }
 
 #[cfg(test)]
@@ -339,7 +427,7 @@ mod tests {
         let board = make_board("8/8/8/3p4/4P3/8/8/8 w - - 0 1");
         let from = Square::new(3, 4); // e4
         let to = Square::new(4, 3); // d5
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         assert_eq!(see, 100); // Win a pawn
     }
 
@@ -349,7 +437,7 @@ mod tests {
         let board = make_board("8/8/2p5/3p4/4P3/8/8/8 w - - 0 1");
         let from = Square::new(3, 4); // e4
         let to = Square::new(4, 3); // d5
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         assert_eq!(see, 0); // Equal exchange: pawn takes pawn, pawn recaptures
     }
 
@@ -359,7 +447,7 @@ mod tests {
         let board = make_board("8/8/2p5/3p4/4N3/8/8/8 w - - 0 1");
         let from = Square::new(3, 4); // e4 knight
         let to = Square::new(4, 3); // d5 pawn
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         assert!(see < 0); // Lose: 100 - 320 = -220
     }
 
@@ -369,7 +457,7 @@ mod tests {
         let board = make_board("8/8/2p5/3p4/4Q3/8/8/8 w - - 0 1");
         let from = Square::new(3, 4); // e4 queen
         let to = Square::new(4, 3); // d5 pawn
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         assert!(see < 0); // Very bad: 100 - 900 = -800
     }
 
@@ -379,7 +467,7 @@ mod tests {
         let board = make_board("3r4/8/8/8/8/8/8/R2R4 w - - 0 1");
         let from = Square::new(0, 0); // a1 rook
         let to = Square::new(7, 3); // d8 rook
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // White Rxd8, if black had another attacker it would recapture
         // But black has no recapture, so SEE = 500 (win rook)
         assert_eq!(see, 500);
@@ -395,7 +483,7 @@ mod tests {
         let board = make_board("8/8/5b2/4b3/3B4/2B5/8/8 w - - 0 1");
         let from = Square::new(2, 2); // c3 bishop
         let to = Square::new(4, 4); // e5 black bishop
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Bxe5, bxe5, Bxe5 = 330 - 330 + 330 = 330
         assert!(see > 0);
     }
@@ -406,7 +494,7 @@ mod tests {
         let board = make_board("8/8/8/3r4/8/8/8/R2R4 w - - 0 1");
         let from = Square::new(0, 3); // d1 rook
         let to = Square::new(4, 3); // d5 black rook (undefended)
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Rxd5, no recapture = 500
         assert_eq!(see, 500);
     }
@@ -417,7 +505,7 @@ mod tests {
         let board = make_board("8/8/5b2/8/3B4/8/1Q6/8 w - - 0 1");
         let from = Square::new(3, 3); // d4 bishop
         let to = Square::new(5, 5); // f6 black bishop
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Bxf6, no recapture (black has no attacker) = 330
         assert_eq!(see, 330);
     }
@@ -432,7 +520,7 @@ mod tests {
         let board = make_board("8/8/8/3p4/2N1N3/8/8/8 w - - 0 1");
         let from = Square::new(3, 2); // c4 knight
         let to = Square::new(4, 3); // d5 pawn
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Nxd5, no recapture = 100
         assert_eq!(see, 100);
     }
@@ -443,7 +531,7 @@ mod tests {
         let board = make_board("4k3/8/4p3/3P4/8/8/8/4R3 w - - 0 1");
         let from = Square::new(4, 3); // d5 pawn
         let to = Square::new(5, 4); // e6 pawn
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // dxe6, Kxe6 would be illegal if rook defends - but actually king can take
         // Let's verify: after dxe6, black king takes, white rook takes?
         // But rook can't reach e6 diagonally
@@ -456,7 +544,7 @@ mod tests {
         let board = make_board("8/8/1p6/2r5/3Q4/8/8/8 w - - 0 1");
         let from = Square::new(3, 3); // d4 queen
         let to = Square::new(4, 2); // c5 rook
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Qxc5, bxc5 = 500 - 900 = -400
         assert!(see < 0);
     }
@@ -467,7 +555,7 @@ mod tests {
         let board = make_board("8/8/8/3n4/2N5/8/8/8 w - - 0 1");
         let from = Square::new(3, 2); // c4 knight
         let to = Square::new(4, 3); // d5 knight
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Nxd5, no recapture = 320
         assert_eq!(see, 320);
     }
@@ -478,7 +566,7 @@ mod tests {
         let board = make_board("8/8/8/3b4/4B3/5B2/8/8 w - - 0 1");
         let from = Square::new(3, 4); // e4 bishop
         let to = Square::new(4, 3); // d5 bishop
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Bxd5, no recapture = 330
         assert_eq!(see, 330);
     }
@@ -493,7 +581,7 @@ mod tests {
         let board = make_board("8/8/8/3Pp3/8/8/8/8 w - e6 0 1");
         let from = Square::new(4, 3); // d5
         let to = Square::new(5, 4); // e6
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // En passant wins a pawn
         assert_eq!(see, 100);
     }
@@ -504,11 +592,44 @@ mod tests {
         let board = make_board("8/5p2/8/3Pp3/8/8/8/8 w - e6 0 1");
         let from = Square::new(4, 3); // d5
         let to = Square::new(5, 4); // e6
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // dxe6, fxe6 = 100 - 100 = 0
         assert_eq!(see, 0);
     }
 
+    // ========================================================================
+    // Move-based SEE
+    // ========================================================================
+
+    #[test]
+    fn test_see_matches_see_squares_for_captures() {
+        let mut board = make_board("8/5p2/8/3Pp3/8/2n5/1P6/K6k w - e6 0 1");
+        for uci in ["d5e6", "b2c3"] {
+            let mv = board.parse_move(uci).unwrap();
+            assert_eq!(
+                board.see(mv),
+                board.see_squares(mv.from(), mv.to()),
+                "{uci}"
+            );
+        }
+        let quiet = board.parse_move("b2b3").unwrap();
+        assert_eq!(board.see(quiet), 0);
+    }
+
+    #[test]
+    fn test_see_counts_the_promotion() {
+        // The knight on b6 guards a8 but not b8
+        let mut board = make_board("1r6/P7/1n6/8/8/8/8/K6k w - - 0 1");
+        let defended = board.parse_move("a7a8q").unwrap();
+        // The queen is taken back: the pawn is lost
+        assert_eq!(board.see(defended), -100);
+        let capture = board.parse_move("a7b8q").unwrap();
+        // Rook plus promotion, and nothing recaptures on b8
+        assert_eq!(board.see(capture), 500 + 800);
+        let under = board.parse_move("a7b8n").unwrap();
+        assert_eq!(board.see(under), 500 + 220);
+    }
+
     // ========================================================================
     // Edge Cases
     // ========================================================================
@@ -519,7 +640,7 @@ mod tests {
         let board = make_board("8/8/8/8/4N3/8/8/8 w - - 0 1");
         let from = Square::new(3, 4); // e4 knight
         let to = Square::new(5, 5); // f6 (empty)
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         assert_eq!(see, 0);
     }
 
@@ -529,7 +650,7 @@ mod tests {
         let board = make_board("8/8/8/3r4/8/8/8/3R4 w - - 0 1");
         let from = Square::new(0, 3); // d1 rook
         let to = Square::new(4, 3); // d5 rook
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         assert_eq!(see, 500); // Win a rook
     }
 
@@ -539,7 +660,7 @@ mod tests {
         let board = make_board("8/8/3q4/2q5/3P4/8/8/8 w - - 0 1");
         let from = Square::new(3, 3); // d4 pawn
         let to = Square::new(4, 2); // c5 queen
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Pxc5, Qxc5 = 900 - 100 = 800
         assert!(see > 700);
     }
@@ -550,7 +671,7 @@ mod tests {
         let board = make_board("3r4/8/8/3r4/8/8/8/R2R4 w - - 0 1");
         let from = Square::new(0, 3); // d1 rook
         let to = Square::new(4, 3); // d5 rook
-        let see = board.see(from, to);
+        let see = board.see_squares(from, to);
         // Rxd5, Rxd5, Rxd5, Rxd5 = 500 - 500 + 500 - 500 = 0
         // Or white wins if they have the last recapture
         // The actual result depends on the SEE implementation
@@ -577,4 +698,37 @@ mod tests {
         let to = Square::new(4, 3); // d5 pawn
         assert!(!board.see_ge(from, to, 0)); // Losing capture
     }
+
+    #[test]
+    fn test_threatened_pieces() {
+        // Knight on d6 forks the rook on c8 and the queen on e8
+        let board = make_board("2r1q3/8/3N4/8/8/8/8/8 b - - 0 1");
+        let threatened = board.threatened_pieces(Color::Black);
+        assert_eq!(threatened.popcount(), 2);
+        assert!(threatened.contains(Square::new(7, 2)));
+        assert!(threatened.contains(Square::new(7, 4)));
+
+        // A defended knight attacked by a bishop is not threatened, an
+        // undefended one is; pawns and the white knight are never counted
+        let board = make_board("8/8/2n5/1p6/B7/8/8/8 b - - 0 1");
+        assert!(board.threatened_pieces(Color::Black).is_empty());
+        let board = make_board("8/8/2n5/8/B7/8/8/8 b - - 0 1");
+        assert_eq!(board.threatened_pieces(Color::Black).popcount(), 1);
+        assert!(board.threatened_pieces(Color::White).is_empty());
+    }
+
+    #[test]
+    fn test_can_win_piece_on() {
+        // Black to move can take the undefended rook on e7 with the king
+        let board = make_board("4k3/4R3/8/8/8/8/8/4K3 b - - 0 1");
+        assert!(board.can_win_piece_on(Square::new(6, 4)));
+
+        // Defended by the pawn on d6, it cannot be won
+        let board = make_board("4k3/4R3/3P4/8/8/8/8/4K3 b - - 0 1");
+        assert!(!board.can_win_piece_on(Square::new(6, 4)));
+
+        // A queen defended by the rook on e1 is still won by the knight
+        let board = make_board("4k3/4Q3/8/3n4/8/8/8/4R1K1 b - - 0 1");
+        assert!(board.can_win_piece_on(Square::new(6, 4)));
+    }
 }
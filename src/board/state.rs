@@ -1,10 +1,9 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
-use super::eval_update::pst_square;
-use super::pst::{MATERIAL_EG, MATERIAL_MG, PHASE_WEIGHTS, PST_EG, PST_MG};
-use super::{Bitboard, Color, Piece, Square, ALL_CASTLING_RIGHTS};
+use super::{Bitboard, Color, Move, Piece, Square, Variant, ALL_CASTLING_RIGHTS};
 
 #[derive(Clone, Copy, Debug)]
 pub struct UnmakeInfo {
@@ -14,36 +13,58 @@ pub struct UnmakeInfo {
     pub(crate) previous_hash: u64,
     pub(crate) previous_halfmove_clock: u32,
     pub(crate) made_hash: u64,
-    pub(crate) previous_repetition_count: u32,
     // Incremental eval state (for restoration)
     pub(crate) previous_eval_mg: [i32; 2],
     pub(crate) previous_eval_eg: [i32; 2],
     pub(crate) previous_game_phase: [i32; 2],
+    pub(crate) previous_checkers: Bitboard,
+    pub(crate) previous_checks_given: [u8; 2],
 }
 
 #[derive(Clone, Copy, Debug)]
+#[allow(clippy::struct_field_names)]
 pub struct NullMoveInfo {
     pub(crate) previous_en_passant_target: Option<Square>,
     pub(crate) previous_hash: u64,
+    pub(crate) previous_checkers: Bitboard,
 }
 
+/// Moves played through the public move API (`make_move_uci`,
+/// `make_move_san`), with the position before the first of them
+#[derive(Clone, Debug)]
+pub(crate) struct GameRecord {
+    pub(crate) start: Arc<Board>,
+    pub(crate) moves: Vec<Move>,
+}
+
+/// Positions seen in the game, in order and counted by hash
 #[derive(Clone, Debug)]
 pub(crate) struct RepetitionTable {
     counts: HashMap<u64, u32>,
+    /// Hash of the position after each ply, the current position last
+    plies: Vec<u64>,
 }
 
 impl RepetitionTable {
     pub(crate) fn new() -> Self {
         RepetitionTable {
             counts: HashMap::new(),
+            plies: Vec::new(),
         }
     }
 
+    /// History of a game starting at the position with hash `hash`
+    pub(crate) fn starting_at(hash: u64) -> Self {
+        let mut table = RepetitionTable::new();
+        table.increment(hash);
+        table
+    }
+
     pub(crate) fn get(&self, hash: u64) -> u32 {
         self.counts.get(&hash).copied().unwrap_or(0)
     }
 
-    pub(crate) fn set(&mut self, hash: u64, count: u32) {
+    fn set(&mut self, hash: u64, count: u32) {
         if count == 0 {
             self.counts.remove(&hash);
         } else {
@@ -51,11 +72,25 @@ impl RepetitionTable {
         }
     }
 
+    /// Hashes of the positions seen, oldest first
+    pub(crate) fn plies(&self) -> &[u64] {
+        &self.plies
+    }
+
+    /// Record the position reached by a ply
     pub(crate) fn increment(&mut self, hash: u64) -> u32 {
         let next = self.get(hash).saturating_add(1);
         self.set(hash, next);
+        self.plies.push(hash);
         next
     }
+
+    /// Forget the last ply, which reached the position with hash `hash`
+    pub(crate) fn decrement(&mut self, hash: u64) {
+        debug_assert_eq!(self.plies.last(), Some(&hash));
+        self.plies.pop();
+        self.set(hash, self.get(hash).saturating_sub(1));
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -77,6 +112,13 @@ pub struct Board {
     pub(crate) king_square: [Square; 2],
     // Mailbox for O(1) piece_at lookups (mirrors bitboard state)
     pub(crate) mailbox: [Option<(Color, Piece)>; 64],
+    // Enemy pieces giving check to the side to move
+    pub(crate) checkers: Bitboard,
+    pub(crate) variant: Variant,
+    // Checks given so far [white, black], counted in three-check only
+    pub(crate) checks_given: [u8; 2],
+    // Game moves for `history_san`, none until the first one is played
+    pub(crate) game: Option<GameRecord>,
 }
 
 impl Board {
@@ -103,81 +145,16 @@ impl Board {
         board.castling_rights = ALL_CASTLING_RIGHTS;
         board.white_to_move = true;
         board.hash = board.calculate_initial_hash();
-        board.repetition_counts.set(board.hash, 1);
+        board.repetition_counts = RepetitionTable::starting_at(board.hash);
         board.recalculate_incremental_eval();
+        board.refresh_checkers();
         board
     }
 
-    /// Clear all pieces from the board (for edit mode)
-    pub fn clear(&mut self) {
-        self.pieces = [[Bitboard(0); 6]; 2];
-        self.occupied = [Bitboard(0); 2];
-        self.all_occupied = Bitboard(0);
-        self.castling_rights = 0;
-        self.en_passant_target = None;
-        self.halfmove_clock = 0;
-        self.eval_mg = [0, 0];
-        self.eval_eg = [0, 0];
-        self.game_phase = [0, 0];
-        self.hash = 0;
-        self.mailbox = [None; 64];
-    }
-
-    /// Flip the side to move (for edit mode)
-    pub fn flip_side_to_move(&mut self) {
-        use crate::zobrist::ZOBRIST;
-        self.white_to_move = !self.white_to_move;
-        self.hash ^= ZOBRIST.black_to_move_key;
-    }
-
-    /// Place a piece on the board (for edit mode)
-    /// This updates bitboards, hash, and incremental eval
-    pub fn place_piece(&mut self, sq: Square, color: Color, piece: Piece) {
-        use crate::zobrist::ZOBRIST;
-
-        // First remove any existing piece at this square
-        if let Some((old_color, old_piece)) = self.piece_at(sq) {
-            self.remove_piece(sq, old_color, old_piece);
-            self.hash ^= ZOBRIST.piece_keys[old_piece.index()][old_color.index()][sq.index()];
-            // Update incremental eval for removed piece
-            let c_idx = old_color.index();
-            let p_idx = old_piece.index();
-            let pst_sq = pst_square(sq.index(), old_color == Color::White);
-            self.eval_mg[c_idx] -= MATERIAL_MG[p_idx] + PST_MG[p_idx][pst_sq];
-            self.eval_eg[c_idx] -= MATERIAL_EG[p_idx] + PST_EG[p_idx][pst_sq];
-            self.game_phase[c_idx] -= PHASE_WEIGHTS[p_idx];
-        }
-
-        // Now add the new piece
-        self.set_piece(sq, color, piece);
-        self.hash ^= ZOBRIST.piece_keys[piece.index()][color.index()][sq.index()];
-
-        // Update incremental eval
-        let c_idx = color.index();
-        let p_idx = piece.index();
-        let pst_sq = pst_square(sq.index(), color == Color::White);
-        self.eval_mg[c_idx] += MATERIAL_MG[p_idx] + PST_MG[p_idx][pst_sq];
-        self.eval_eg[c_idx] += MATERIAL_EG[p_idx] + PST_EG[p_idx][pst_sq];
-        self.game_phase[c_idx] += PHASE_WEIGHTS[p_idx];
-    }
-
-    /// Remove a piece from the board by square (for edit mode)
-    /// This updates bitboards, hash, and incremental eval
-    pub fn remove_piece_at(&mut self, sq: Square) {
-        use crate::zobrist::ZOBRIST;
-
-        if let Some((color, piece)) = self.piece_at(sq) {
-            self.remove_piece(sq, color, piece);
-            self.hash ^= ZOBRIST.piece_keys[piece.index()][color.index()][sq.index()];
-
-            // Update incremental eval
-            let c_idx = color.index();
-            let p_idx = piece.index();
-            let pst_sq = pst_square(sq.index(), color == Color::White);
-            self.eval_mg[c_idx] -= MATERIAL_MG[p_idx] + PST_MG[p_idx][pst_sq];
-            self.eval_eg[c_idx] -= MATERIAL_EG[p_idx] + PST_EG[p_idx][pst_sq];
-            self.game_phase[c_idx] -= PHASE_WEIGHTS[p_idx];
-        }
+    /// Recompute the cached checkers bitboard from scratch.
+    /// Needed after any board edit that bypasses make/unmake.
+    pub(crate) fn refresh_checkers(&mut self) {
+        self.checkers = self.checkers_of(self.side_to_move());
     }
 
     /// Recalculate incremental evaluation from scratch (used after FEN parsing or initialization)
@@ -187,17 +164,9 @@ impl Board {
         self.game_phase = [0, 0];
 
         for color in Color::BOTH {
-            let c_idx = color.index();
             for piece in Piece::ALL {
-                let p_idx = piece.index();
-                for sq_idx in self.pieces_of(color, piece).iter() {
-                    let sq = sq_idx.index();
-                    // PST square: flip for white (tables are from black's perspective)
-                    let pst_sq = if color == Color::White { sq } else { sq ^ 0b11_1000 };
-
-                    self.eval_mg[c_idx] += MATERIAL_MG[p_idx] + PST_MG[p_idx][pst_sq];
-                    self.eval_eg[c_idx] += MATERIAL_EG[p_idx] + PST_EG[p_idx][pst_sq];
-                    self.game_phase[c_idx] += PHASE_WEIGHTS[p_idx];
+                for sq in self.pieces_of(color, piece).iter() {
+                    self.add_piece_eval(color, piece, sq);
                 }
             }
         }
@@ -220,6 +189,10 @@ impl Board {
             // Will be set when kings are placed
             king_square: [Square::new(0, 4), Square::new(7, 4)],
             mailbox: [None; 64],
+            checkers: Bitboard(0),
+            variant: Variant::Standard,
+            checks_given: [0, 0],
+            game: None,
         }
     }
 
@@ -228,6 +201,12 @@ impl Board {
         self.hash
     }
 
+    /// Positions recorded for repetition detection, repeats included
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.repetition_counts.plies().len()
+    }
+
     /// Compute a Zobrist hash of only the pawn positions.
     /// Used for pawn hash table lookups and correction history.
     #[must_use]
@@ -264,12 +243,42 @@ impl Board {
         }
     }
 
+    /// Enemy pieces currently giving check to the side to move. O(1).
+    #[inline]
+    #[must_use]
+    pub fn checkers(&self) -> Bitboard {
+        self.checkers
+    }
+
+    /// Whether the side to move is in check. O(1).
+    #[inline]
+    #[must_use]
+    pub fn in_check(&self) -> bool {
+        !self.checkers.is_empty()
+    }
+
     /// Get all pieces of a given type regardless of color
     #[must_use]
     pub fn all_pieces_of_type(&self, piece: Piece) -> Bitboard {
         Bitboard(self.pieces[0][piece.index()].0 | self.pieces[1][piece.index()].0)
     }
 
+    /// Iterate over all pieces of one color as `(square, piece)` pairs,
+    /// in ascending square order (a1, b1, ..., h8).
+    pub fn pieces(&self, color: Color) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        self.occupied[color.index()]
+            .iter()
+            .filter_map(move |sq| self.mailbox[sq.index()].map(|(_, piece)| (sq, piece)))
+    }
+
+    /// Iterate over every occupied square as `(square, color, piece)`,
+    /// in ascending square order. Convenient for rendering a board.
+    pub fn occupied_squares(&self) -> impl Iterator<Item = (Square, Color, Piece)> + '_ {
+        self.all_occupied
+            .iter()
+            .filter_map(move |sq| self.mailbox[sq.index()].map(|(color, piece)| (sq, color, piece)))
+    }
+
     /// Get the king square index (0-63) for a color
     #[inline]
     #[must_use]
@@ -282,12 +291,31 @@ impl Board {
         self.halfmove_clock
     }
 
+    /// How many times the current position has occurred in the game,
+    /// including now. Positions before the last FEN set-up are not known.
+    #[must_use]
+    pub fn repetition_count(&self) -> u32 {
+        self.repetition_counts.get(self.hash)
+    }
+
+    /// Whether the current position has occurred three times
+    #[must_use]
+    pub fn is_threefold(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    /// Whether a hundred half-moves have passed without a capture or pawn
+    /// move
+    #[must_use]
+    pub fn is_fifty_move(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// Whether the game is drawn by the fifty-move rule or threefold
+    /// repetition
     #[must_use]
     pub fn is_draw(&self) -> bool {
-        if self.halfmove_clock >= 100 {
-            return true;
-        }
-        self.repetition_counts.get(self.hash) >= 3
+        self.is_fifty_move() || self.is_threefold()
     }
 
     #[must_use]
@@ -305,7 +333,9 @@ impl Board {
         self.piece_count(Color::White, piece) + self.piece_count(Color::Black, piece)
     }
 
-    fn is_insufficient_material(&self) -> bool {
+    /// Whether neither side has enough material to checkmate
+    #[must_use]
+    pub fn is_insufficient_material(&self) -> bool {
         // Any pawns, rooks, or queens means sufficient material
         if self.total_piece_count(Piece::Pawn) > 0
             || self.total_piece_count(Piece::Rook) > 0
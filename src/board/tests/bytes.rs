@@ -0,0 +1,112 @@
+//! Compact binary encoding tests.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, BytesError, Variant};
+
+fn round_trip(board: &Board) -> Board {
+    let bytes = board.to_bytes().expect("position fits the encoding");
+    Board::from_bytes(&bytes).expect("encoding reads back")
+}
+
+#[test]
+fn test_bytes_round_trip_matches_fen() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 37 60",
+        "4k3/8/8/8/8/8/8/4K3 w - - 299 1",
+        "8/8/8/8/8/8/8/8 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen);
+        let decoded = round_trip(&board);
+        assert_eq!(decoded.to_fen(), board.to_fen(), "{fen}");
+        assert_eq!(decoded.hash(), board.hash(), "{fen}");
+    }
+}
+
+#[test]
+fn test_bytes_round_trip_random_games() {
+    let mut rng = StdRng::seed_from_u64(0xB17E5);
+    for _ in 0..20 {
+        let mut board = Board::new();
+        for _ in 0..120 {
+            let decoded = round_trip(&board);
+            assert_eq!(decoded.to_fen(), board.to_fen());
+            assert_eq!(decoded.hash(), board.hash());
+            let moves = board.generate_moves();
+            if moves.is_empty() {
+                break;
+            }
+            board.make_move(moves.as_slice()[rng.gen_range(0..moves.len())]);
+        }
+    }
+}
+
+#[test]
+fn test_bytes_keep_the_variant() {
+    let board =
+        Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2 +1+2");
+    let decoded = round_trip(&board);
+    assert_eq!(decoded.variant(), Variant::ThreeCheck);
+    assert_eq!(decoded.to_fen(), board.to_fen());
+
+    let mut hill = Board::new();
+    hill.set_variant(Variant::KingOfTheHill);
+    let decoded = round_trip(&hill);
+    assert_eq!(decoded.variant(), Variant::KingOfTheHill);
+    assert_eq!(decoded.hash(), hill.hash());
+}
+
+#[test]
+fn test_bytes_drop_check_counters_after_leaving_three_check() {
+    let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1 +1+0");
+    board.set_variant(Variant::Standard);
+    let decoded = round_trip(&board);
+    assert_eq!(decoded.variant(), Variant::Standard);
+    assert_eq!(decoded.to_fen(), board.to_fen());
+    assert_eq!(decoded.hash(), board.hash());
+}
+
+#[test]
+fn test_bytes_reject_malformed_input() {
+    let bytes = Board::new().to_bytes().unwrap();
+    assert_eq!(
+        Board::from_bytes(&bytes[..31]).unwrap_err(),
+        BytesError::WrongLength { len: 31 }
+    );
+
+    // Piece code 12 on the first square
+    let mut bad = bytes;
+    bad[8] = (bad[8] & 0xF0) | 0xC;
+    assert_eq!(
+        Board::from_bytes(&bad).unwrap_err(),
+        BytesError::InvalidPiece { code: 12 }
+    );
+
+    // En passant square out of range, unknown variant, nonzero padding
+    for (offset, value) in [(25, 65), (24, 3 << 5), (31, 1)] {
+        let mut bad = bytes;
+        bad[offset] = value;
+        assert_eq!(
+            Board::from_bytes(&bad).unwrap_err(),
+            BytesError::InvalidField { offset, value }
+        );
+    }
+
+    // Check counters outside three-check
+    let mut bad = bytes;
+    bad[28] = 0x11;
+    assert!(Board::from_bytes(&bad).is_err());
+}
+
+#[test]
+fn test_bytes_too_many_pieces() {
+    let board = Board::from_fen("nnnnnnnn/nnnnnnnn/nnnnnnnn/nnnnnnnn/nnnnnnnn/8/8/K6k w - - 0 1");
+    assert_eq!(
+        board.to_bytes().unwrap_err(),
+        BytesError::TooManyPieces { count: 42 }
+    );
+}
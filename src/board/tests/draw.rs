@@ -1,7 +1,7 @@
 //! Draw detection tests.
 
 use crate::board::{Board, Move, Piece, Square};
-use crate::uci::parse_uci_move;
+use crate::uci::{parse_uci_move, try_parse_position_command};
 
 fn find_move(board: &mut Board, from: Square, to: Square, promotion: Option<Piece>) -> Move {
     for m in &board.generate_moves() {
@@ -80,6 +80,30 @@ fn test_unmake_restores_state() {
     assert_eq!(board.repetition_counts.get(original_hash), original_rep);
 }
 
+#[test]
+fn test_repetition_count_and_separate_draw_rules() {
+    let mut board = Board::new();
+    assert_eq!(board.repetition_count(), 1);
+    let knight_dance = ["g1f3", "g8f6", "f3g1", "f6g8"];
+    for uci in knight_dance {
+        apply_uci(&mut board, uci);
+    }
+    assert_eq!(board.repetition_count(), 2);
+    assert!(!board.is_threefold());
+
+    for uci in knight_dance {
+        apply_uci(&mut board, uci);
+    }
+    assert_eq!(board.repetition_count(), 3);
+    assert!(board.is_threefold());
+    assert!(!board.is_fifty_move());
+    assert!(board.is_draw());
+
+    let board = Board::from_fen("8/8/8/8/8/8/8/K1k5 w - - 100 1");
+    assert!(board.is_fifty_move());
+    assert!(!board.is_threefold());
+}
+
 #[test]
 fn test_draw_in_search() {
     let board = Board::from_fen("8/8/8/8/8/8/8/K1k5 w - - 100 1");
@@ -104,3 +128,16 @@ fn test_fen_round_trip_normalized() {
     let out_parts: Vec<&str> = out.split_whitespace().collect();
     assert_eq!(&in_parts[..5], &out_parts[..5]);
 }
+
+#[test]
+fn test_position_command_keeps_extended_fen_history() {
+    let mut game = Board::new();
+    for mv in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+        game.make_move_uci(mv).unwrap();
+    }
+    let cmd = format!("position fen {} moves f6g8", game.to_fen_extended());
+    let parts: Vec<&str> = cmd.split_whitespace().collect();
+    let mut board = Board::new();
+    try_parse_position_command(&mut board, &parts).unwrap();
+    assert!(board.is_threefold());
+}
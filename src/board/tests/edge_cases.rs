@@ -1,6 +1,9 @@
 //! Edge case tests for special chess positions and moves.
 
-use crate::board::{Board, Move, Piece, Square};
+use std::sync::atomic::AtomicBool;
+
+use crate::board::search::{find_best_move, SearchState};
+use crate::board::{Board, Color, Move, Piece, Square};
 
 #[test]
 fn test_stalemate_position() {
@@ -118,6 +121,21 @@ fn test_double_check_only_king_can_move() {
     }
 }
 
+#[test]
+fn test_double_check_tactical_moves_are_king_only() {
+    // Knight f3 and rook e8 both check; Bxf3 would be a capture but cannot
+    // resolve a double check
+    let mut board = Board::from_fen("4r1k1/8/8/3B4/8/5n2/8/4K3 w - - 0 1");
+    assert_eq!(board.checkers().popcount(), 2);
+    for mv in &board.generate_tactical_moves() {
+        assert_eq!(
+            mv.from(),
+            Square::new(0, 4),
+            "non-king move {mv} in double check"
+        );
+    }
+}
+
 #[test]
 fn test_checkmate_back_rank() {
     let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
@@ -131,6 +149,29 @@ fn test_checkmate_back_rank() {
     assert!(board.is_checkmate());
 }
 
+#[test]
+fn test_kingless_side_is_never_in_check() {
+    // Material drill: black has no king, so it can never be checked or mated
+    let mut board = Board::from_fen("r7/8/8/8/8/8/8/R3K3 b Q - 0 1");
+    assert!(!board.has_king(Color::Black));
+    assert!(board.has_king(Color::White));
+    assert!(!board.is_in_check(Color::Black));
+    assert!(!board.is_checkmate());
+    assert!(!board.generate_moves().is_empty());
+}
+
+#[test]
+fn test_kingless_position_search_and_perft() {
+    let mut board = Board::from_fen("8/3n4/8/8/8/8/8/3R4 w - - 0 1");
+    assert!(board.generate_moves().iter().all(|m| !m.is_castling()));
+    assert!(board.perft(2) > 0);
+
+    let mut state = SearchState::new(1);
+    let stop = AtomicBool::new(false);
+    let best = find_best_move(&mut board, &mut state, 3, &stop);
+    assert!(best.is_some(), "Search should work without kings");
+}
+
 #[test]
 fn test_fen_parsing_errors() {
     assert!(Board::try_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").is_err());
@@ -231,3 +272,136 @@ fn test_board_from_str() {
     let result: Result<Board, _> = "invalid fen".parse();
     assert!(result.is_err());
 }
+
+#[test]
+fn test_piece_iterators() {
+    let board = Board::new();
+
+    let white: Vec<_> = board.pieces(Color::White).collect();
+    assert_eq!(white.len(), 16);
+    assert_eq!(white[0], (Square::new(0, 0), Piece::Rook));
+    assert_eq!(white[4], (Square::new(0, 4), Piece::King));
+
+    let occupied: Vec<_> = board.occupied_squares().collect();
+    assert_eq!(occupied.len(), 32);
+    assert_eq!(
+        occupied.last(),
+        Some(&(Square::new(7, 7), Color::Black, Piece::Rook))
+    );
+    assert!(occupied
+        .iter()
+        .all(|&(sq, color, piece)| board.piece_at(sq) == Some((color, piece))));
+}
+
+#[test]
+fn test_pinned_piece_stays_on_pin_line() {
+    // White rook on e2 is pinned by the rook on e8; it may only move along the e-file
+    let mut board = Board::from_fen("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1");
+    assert_eq!(
+        board.pinned_pieces(Color::White),
+        crate::board::Bitboard::from_square(Square::new(1, 4))
+    );
+    for mv in &board.generate_moves() {
+        if mv.from() == Square::new(1, 4) {
+            assert_eq!(mv.to().file(), 4, "pinned rook left the pin line: {mv}");
+        }
+    }
+}
+
+#[test]
+fn test_single_check_requires_block_or_capture() {
+    // Bishop b4 checks the king on e1; only blocks on c3/d2, captures of b4, or king moves
+    let mut board = Board::from_fen("4k3/8/8/8/1b6/8/8/R3K1N1 w - - 0 1");
+    assert!(board.checkers_of(Color::White).is_single());
+    let block_squares = [Square::new(2, 2), Square::new(1, 3), Square::new(3, 1)];
+    for mv in &board.generate_moves() {
+        if mv.from() != Square::new(0, 4) {
+            assert!(block_squares.contains(&mv.to()), "illegal evasion {mv}");
+        }
+    }
+}
+
+#[test]
+fn test_count_evasions_matches_generated_moves() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    for fen in [
+        // Double check, pawn and piece blocks, capture with promotion
+        "4k3/8/8/8/1b6/8/8/R3K1N1 w - - 0 1",
+        "4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1",
+        "4k3/8/8/b7/8/8/1PP5/4K3 w - - 0 1",
+        "1r2k3/P7/8/8/8/8/8/1K6 w - - 0 1",
+        // En passant capture of the checking pawn
+        "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+    ] {
+        let mut board = Board::from_fen(fen);
+        assert!(board.in_check(), "{fen}");
+        assert_eq!(
+            board.count_evasions(),
+            board.generate_moves().len(),
+            "{fen}"
+        );
+    }
+
+    let mut rng = StdRng::seed_from_u64(0xE7A5);
+    for _ in 0..50 {
+        let mut board = Board::new();
+        for _ in 0..200 {
+            let moves = board.generate_moves();
+            // En passant captures that uncover a check are counted anyway
+            if board.in_check() && board.en_passant_target.is_none() {
+                assert_eq!(board.count_evasions(), moves.len(), "{}", board.to_fen());
+            }
+            if moves.is_empty() {
+                break;
+            }
+            board.make_move(moves.as_slice()[rng.gen_range(0..moves.len())]);
+        }
+    }
+}
+
+#[test]
+fn test_table_moves_from_other_positions() {
+    // Moves from one position replayed in another, as stale TT entries and
+    // killers are; the single-move check must agree with the generator
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ];
+    let mut positions = Vec::new();
+    for fen in fens {
+        let mut board = Board::from_fen(fen);
+        positions.push(board.clone());
+        for mv in board.generate_moves().iter().take(12) {
+            let info = board.make_move(*mv);
+            positions.push(board.clone());
+            board.unmake_move(*mv, info);
+        }
+    }
+    let mut candidates: Vec<Move> = Vec::new();
+    for board in &mut positions {
+        candidates.extend(board.generate_moves().iter().copied());
+    }
+    candidates.sort_unstable_by_key(|m| m.as_u16());
+    candidates.dedup();
+
+    for board in &mut positions {
+        let legal = board.generate_moves();
+        for &mv in &candidates {
+            assert_eq!(
+                board.is_legal_move(mv),
+                legal.iter().any(|m| *m == mv),
+                "{mv} in {}",
+                board.to_fen()
+            );
+            if legal.iter().any(|m| *m == mv) {
+                assert!(board.is_pseudo_legal(mv));
+            }
+        }
+        assert!(!board.is_pseudo_legal(Move::null()));
+    }
+}
@@ -0,0 +1,93 @@
+//! Position editing: placing, removing and dragging pieces, castling
+//! rights and en passant targets.
+
+use crate::board::{Board, BuilderError, CastlingRights, Color, Move, Piece, Square};
+
+/// The incrementally maintained hash matches a full recomputation
+fn assert_consistent(board: &Board) {
+    assert_eq!(
+        board.hash(),
+        board.calculate_initial_hash(),
+        "{}",
+        board.to_fen()
+    );
+    assert_eq!(board.repetition_count(), 1);
+    let mut fresh = Board::from_fen(&board.to_fen());
+    fresh.set_variant(board.variant());
+    assert_eq!(board.evaluate(), fresh.evaluate());
+}
+
+#[test]
+fn test_edits_keep_hash_consistent() {
+    let mut board = Board::new();
+    board.make_move_uci("e2e4").unwrap();
+    board.make_move_uci("d7d5").unwrap();
+    board.make_move_uci("e4e5").unwrap();
+    board.make_move_uci("f7f5").unwrap();
+    assert_eq!(board.en_passant_target(), Some(Square::new(5, 5)));
+
+    board.place_piece(Square::new(4, 3), Color::White, Piece::Queen);
+    assert_consistent(&board);
+    board.remove_piece_at(Square::new(0, 6));
+    assert_consistent(&board);
+    assert_eq!(
+        board.drag_piece(Square::new(7, 1), Square::new(5, 2)),
+        Some((Color::Black, Piece::Knight))
+    );
+    assert_consistent(&board);
+    assert_eq!(board.drag_piece(Square::new(3, 3), Square::new(2, 2)), None);
+
+    // Moving the f5 pawn away invalidates the en passant target
+    board.drag_piece(Square::new(4, 5), Square::new(3, 5));
+    assert_eq!(board.en_passant_target(), None);
+    assert_consistent(&board);
+
+    board.flip_side_to_move();
+    assert_consistent(&board);
+    board.clear();
+    assert_consistent(&board);
+}
+
+#[test]
+fn test_castling_rights_follow_king_and_rooks() {
+    let mut board = Board::new();
+    board.remove_piece_at(Square::new(0, 7));
+    assert!(!board.castling_rights().has(Color::White, true));
+    assert!(board.castling_rights().has(Color::White, false));
+
+    board.drag_piece(Square::new(7, 4), Square::new(7, 3));
+    assert!(!board.castling_rights().has(Color::Black, true));
+    assert!(!board.castling_rights().has(Color::Black, false));
+    assert_consistent(&board);
+
+    assert_eq!(
+        board.set_castling_rights(CastlingRights::all()),
+        Err(BuilderError::InvalidCastling {
+            color: Color::White,
+            kingside: true
+        })
+    );
+    let mut rights = CastlingRights::none();
+    rights.set(Color::White, false);
+    board.set_castling_rights(rights).unwrap();
+    assert_eq!(board.castling_rights(), rights);
+    assert_consistent(&board);
+}
+
+#[test]
+fn test_set_en_passant() {
+    let mut board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1");
+    assert_eq!(
+        board.set_en_passant(Some(Square::new(5, 4))),
+        Err(BuilderError::InvalidEnPassant {
+            square: Square::new(5, 4)
+        })
+    );
+    board.set_en_passant(Some(Square::new(5, 3))).unwrap();
+    assert_consistent(&board);
+    assert!(board.parse_move("e5d6").is_ok_and(Move::is_en_passant));
+
+    board.set_en_passant(None).unwrap();
+    assert_consistent(&board);
+    assert!(board.parse_move("e5d6").is_err());
+}
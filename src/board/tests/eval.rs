@@ -311,6 +311,108 @@ fn test_eval_symmetry() {
     assert!(score.abs() < 50, "startpos eval={score}");
 }
 
+/// The same position with colours swapped and the board mirrored rank-wise
+fn color_flipped_fen(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let swap_case = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+    let ranks: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+    let side = if fields[1] == "w" { "b" } else { "w" };
+    let castling = if fields[2] == "-" {
+        "-".to_string()
+    } else {
+        let mut rights: Vec<char> = swap_case(fields[2]).chars().collect();
+        rights.sort_unstable();
+        rights.into_iter().collect()
+    };
+    let ep = if fields[3] == "-" {
+        "-".to_string()
+    } else {
+        let rank = if &fields[3][1..] == "3" { "6" } else { "3" };
+        format!("{}{rank}", &fields[3][..1])
+    };
+    format!("{} {side} {castling} {ep} 0 1", ranks.join("/"))
+}
+
+#[test]
+fn test_color_flipped_matches_mirrored_fen() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 0 1",
+    ] {
+        let flipped = make_board(fen).color_flipped();
+        let expected = make_board(&color_flipped_fen(fen));
+        assert_eq!(flipped.to_fen(), expected.to_fen(), "{fen}");
+        assert_eq!(flipped.hash(), expected.hash(), "{fen}");
+    }
+}
+
+#[test]
+fn test_pst_color_flip_symmetry() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N2N2/PP2BPPP/R2QKB1R b KQ - 0 8",
+    ];
+    for fen in fens {
+        let mut board = make_board(fen);
+        let mut flipped = make_board(&color_flipped_fen(fen));
+        assert_eq!(
+            board.eval_mg,
+            [flipped.eval_mg[1], flipped.eval_mg[0]],
+            "{fen}"
+        );
+        assert_eq!(
+            board.eval_eg,
+            [flipped.eval_eg[1], flipped.eval_eg[0]],
+            "{fen}"
+        );
+        assert_eq!(board.evaluate(), flipped.evaluate(), "{fen}");
+
+        // Incremental updates must stay mirrored move by move
+        let moves = board.generate_moves();
+        let flipped_moves = flipped.generate_moves();
+        assert_eq!(moves.len(), flipped_moves.len(), "{fen}");
+        for m in &moves {
+            let mirrored = flipped_moves
+                .iter()
+                .copied()
+                .find(|f| {
+                    f.from() == m.from().flip_vertical()
+                        && f.to() == m.to().flip_vertical()
+                        && f.promotion() == m.promotion()
+                })
+                .expect("mirrored move");
+            let info = board.make_move(*m);
+            let flipped_info = flipped.make_move(mirrored);
+            assert_eq!(
+                board.eval_mg,
+                [flipped.eval_mg[1], flipped.eval_mg[0]],
+                "{fen} {m}"
+            );
+            assert_eq!(
+                board.eval_eg,
+                [flipped.eval_eg[1], flipped.eval_eg[0]],
+                "{fen} {m}"
+            );
+            flipped.unmake_move(mirrored, flipped_info);
+            board.unmake_move(*m, info);
+        }
+    }
+}
+
 #[test]
 fn test_eval_perspective() {
     // Evaluation should be from side-to-move perspective
@@ -325,3 +427,18 @@ fn test_eval_perspective() {
     assert!(black_eval < 0, "black to move eval={black_eval}");
     assert!(white_eval > 0, "white to move eval={white_eval}");
 }
+
+// ============================================================================
+// Game Phase Tests
+// ============================================================================
+
+#[test]
+fn test_phase_from_full_board_to_pawn_ending() {
+    let start = Board::new();
+    let pawn_ending = make_board("4k3/pp6/8/8/8/8/PP6/4K3 w - - 0 1");
+    let rook_ending = make_board("4k3/pp5r/8/8/8/8/PP5R/4K3 w - - 0 1");
+
+    assert_eq!(start.phase(), 24);
+    assert_eq!(pawn_ending.phase(), 0);
+    assert!(rook_ending.phase() > 0 && rook_ending.phase() < start.phase());
+}
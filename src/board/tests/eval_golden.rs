@@ -0,0 +1,182 @@
+//! Golden values for the evaluation terms.
+//!
+//! Every term of the classical evaluation is computed on a curated corpus
+//! of positions and compared with the values recorded in
+//! `tests/data/eval_golden.txt`, so a refactor of the terms (a tapered
+//! score type, parameter files) shows any value it changes.
+//!
+//! After an intended evaluation change, regenerate the file with
+//!
+//! ```text
+//! UPDATE_EVAL_GOLDEN=1 cargo test --lib eval_golden
+//! ```
+//!
+//! and review its diff.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use crate::board::eval_terms::helpers::AttackContext;
+use crate::board::state::Board;
+use crate::board::Color;
+
+/// Positions chosen to exercise every term at least once
+const CORPUS: [&str; 16] = [
+    // Openings and middlegames
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+    "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N2N2/PP2BPPP/R2QKB1R b KQ - 0 8",
+    "r2q1rk1/pp2bppp/2n1bn2/3p4/3P4/2NBBN2/PP3PPP/R2Q1RK1 w - - 0 11",
+    "r3k2r/pp1bbppp/2nqpn2/3p4/3P4/2NBPN2/PPQB1PPP/2KR3R w kq - 0 11",
+    "r4rk1/pp3ppp/2p1pn2/3pN3/3P1P2/2PBP3/PP4PP/R4RK1 b - - 0 15",
+    // Imbalances and rook activity
+    "3rr1k1/5ppp/8/8/8/8/5PPP/3Q2K1 w - - 0 1",
+    "2r3k1/1R3ppp/p3p3/8/8/P5P1/5P1P/6K1 w - - 0 30",
+    "6k1/5ppp/8/8/8/8/r4PPP/1R4K1 w - - 0 1",
+    // Endgames
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/8/4k3/8/4P3/4K3/8/8 w - - 0 1",
+    "7k/8/6KP/8/8/8/8/5B2 w - - 0 1",
+    "8/5k2/4b3/3p4/3P4/4B3/5K2/8 w - - 0 1",
+];
+
+/// A term's name and its values on a position
+type Term = (&'static str, fn(&Board, &AttackContext) -> Vec<i32>);
+
+/// Every term as `evaluate` combines it, white's point of view unless noted
+const TERMS: [Term; 22] = [
+    ("mobility", |b, ctx| pair(b.eval_mobility_with_context(ctx))),
+    ("pawn_structure", |b, _| pair(b.eval_pawn_structure())),
+    ("king_safety", |b, ctx| {
+        pair(b.eval_king_safety_with_context(ctx))
+    }),
+    ("king_shield", |b, _| pair(b.eval_king_shield())),
+    ("rooks", |b, _| pair(b.eval_rooks())),
+    ("minor_pieces", |b, ctx| pair(b.eval_minor_pieces(ctx))),
+    ("tropism", |b, _| vec![b.eval_tropism()]),
+    ("passed_pawns", |b, _| pair(b.eval_passed_pawns())),
+    ("hanging", |b, _| vec![b.eval_hanging()]),
+    ("coordination", |b, ctx| pair(b.eval_coordination(ctx))),
+    ("pawn_advanced", |b, _| pair(b.eval_pawn_advanced())),
+    ("weak_squares", |b, ctx| pair(b.eval_weak_squares(ctx))),
+    ("king_danger", |b, ctx| pair(b.eval_king_danger(ctx))),
+    ("endgame_patterns", |b, _| pair(b.eval_endgame_patterns())),
+    ("space_control", |b, ctx| pair(b.eval_space_control(ctx))),
+    ("threats_advanced", |b, ctx| {
+        pair(b.eval_threats_advanced(ctx))
+    }),
+    ("piece_quality", |b, ctx| pair(b.eval_piece_quality(ctx))),
+    ("imbalances", |b, _| pair(b.eval_imbalances())),
+    ("initiative", |b, ctx| pair(b.eval_initiative(ctx))),
+    ("drawish_scale", |b, _| {
+        vec![b.drawish_scale(Color::White), b.drawish_scale(Color::Black)]
+    }),
+    // Totals, side to move's point of view
+    ("evaluate", |b, _| vec![b.evaluate()]),
+    ("evaluate_simple", |b, _| vec![b.evaluate_simple()]),
+];
+
+fn pair((mg, eg): (i32, i32)) -> Vec<i32> {
+    vec![mg, eg]
+}
+
+fn golden_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/eval_golden.txt")
+}
+
+/// Current values, keyed by `(fen, term)`
+fn compute() -> BTreeMap<(String, String), Vec<i32>> {
+    let mut values = BTreeMap::new();
+    for fen in CORPUS {
+        let board: Board = fen.parse().expect("valid corpus fen");
+        let ctx = board.compute_attack_context();
+        for (name, term) in TERMS {
+            values.insert((fen.to_string(), name.to_string()), term(&board, &ctx));
+        }
+    }
+    values
+}
+
+/// The golden file: a `[fen]` header, then one `term values...` line per term
+fn render() -> String {
+    let values = compute();
+    let mut text =
+        String::from("# Evaluation term golden values, see src/board/tests/eval_golden.rs\n");
+    for fen in CORPUS {
+        let _ = write!(text, "\n[{fen}]\n");
+        for (name, _) in TERMS {
+            let numbers: Vec<String> = values[&(fen.to_string(), name.to_string())]
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            let _ = writeln!(text, "{name} {}", numbers.join(" "));
+        }
+    }
+    text
+}
+
+fn parse(text: &str) -> BTreeMap<(String, String), Vec<i32>> {
+    let mut values = BTreeMap::new();
+    let mut fen = String::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            fen = header.to_string();
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let name = parts.next().unwrap_or_default().to_string();
+        let numbers = parts
+            .map(|n| n.parse().expect("golden values are integers"))
+            .collect();
+        values.insert((fen.clone(), name), numbers);
+    }
+    values
+}
+
+#[test]
+fn test_eval_terms_match_golden_values() {
+    let path = golden_path();
+    if std::env::var_os("UPDATE_EVAL_GOLDEN").is_some() {
+        std::fs::write(&path, render()).expect("write golden file");
+        return;
+    }
+    let text = std::fs::read_to_string(&path).expect("read golden file");
+    let expected = parse(&text);
+    let actual = compute();
+
+    let mut mismatches = Vec::new();
+    for (key, value) in &actual {
+        match expected.get(key) {
+            Some(golden) if golden == value => {}
+            Some(golden) => mismatches.push(format!(
+                "{} {}: golden {golden:?}, now {value:?}",
+                key.0, key.1
+            )),
+            None => mismatches.push(format!("{} {}: not in golden file", key.0, key.1)),
+        }
+    }
+    mismatches.extend(
+        expected
+            .keys()
+            .filter(|key| !actual.contains_key(*key))
+            .map(|key| format!("{} {}: in golden file only", key.0, key.1)),
+    );
+    assert!(
+        mismatches.is_empty(),
+        "{} evaluation values changed (rerun with UPDATE_EVAL_GOLDEN=1 if intended):\n{}",
+        mismatches.len(),
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn test_golden_format_round_trips() {
+    assert_eq!(parse(&render()), compute());
+}
@@ -119,6 +119,33 @@ fn test_hash_matches_recompute_after_random_moves() {
     }
 }
 
+#[test]
+fn test_hash_after_predicts_child_hash() {
+    // Perft positions with castling, rook captures, promotions and en passant
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "r3k2r/1P6/8/8/8/8/6p1/R3K2R b KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+    for fen in fens {
+        let mut board = Board::from_fen(fen);
+        for mv in board.generate_moves() {
+            let predicted = board.hash_after(mv);
+            let info = board.make_move(mv);
+            assert_eq!(predicted, board.hash(), "{fen} {mv}");
+            for reply in board.generate_moves() {
+                let predicted = board.hash_after(reply);
+                let reply_info = board.make_move(reply);
+                assert_eq!(predicted, board.hash(), "{fen} {mv} {reply}");
+                board.unmake_move(reply, reply_info);
+            }
+            board.unmake_move(mv, info);
+        }
+    }
+}
+
 #[test]
 fn test_random_playout_round_trip_state() {
     let mut board = Board::new();
@@ -154,3 +181,40 @@ fn test_random_playout_round_trip_state() {
     assert_eq!(board.en_passant_target, initial_ep);
     assert_eq!(board.repetition_counts.get(initial_hash), initial_rep);
 }
+
+#[test]
+fn test_checkers_cache_matches_recompute() {
+    let mut board =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let mut rng = StdRng::seed_from_u64(0xC4EC);
+    let mut history: Vec<(Move, UnmakeInfo)> = Vec::new();
+
+    for _ in 0..120 {
+        let moves = board.generate_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let idx = rng.gen_range(0..moves.len());
+        let mv = moves.as_slice()[idx];
+        let info = board.make_move(mv);
+        history.push((mv, info));
+        assert_eq!(
+            board.checkers(),
+            board.checkers_of(board.side_to_move()),
+            "stale checkers after {mv}"
+        );
+    }
+
+    while let Some((mv, info)) = history.pop() {
+        board.unmake_move(mv, info);
+        assert_eq!(board.checkers(), board.checkers_of(board.side_to_move()));
+    }
+}
+
+#[test]
+fn test_in_check_accessor() {
+    let board = Board::from_fen("4k3/8/8/8/1b6/8/8/4K3 w - - 0 1");
+    assert!(board.in_check());
+    assert_eq!(board.checkers().popcount(), 1);
+    assert!(!Board::new().in_check());
+}
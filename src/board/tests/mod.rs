@@ -2,19 +2,28 @@
 //!
 //! Tests are organized into separate files by category:
 //! - `perft.rs` - Performance tests for move generation
+//! - `bytes.rs` - Compact binary position encoding
 //! - `draw.rs` - Draw detection (50-move, repetition, insufficient material)
 //! - `make_unmake.rs` - Make/unmake move correctness
 //! - `edge_cases.rs` - Special positions and edge cases
 //! - `proptest.rs` - Property-based tests
 //! - `search_tables.rs` - Killer moves, history, counter moves, MVV-LVA
 //! - `eval.rs` - Evaluation term tests
+//! - `eval_golden.rs` - Evaluation term values against a golden file
+//! - `outcome.rs` - Game termination reasons and results
 //! - `search.rs` - Search algorithm tests (alpha-beta, quiescence, pruning)
+//! - `variant.rs` - King of the Hill and three-check
 
+mod bytes;
 mod draw;
 mod edge_cases;
+mod edit;
 mod eval;
+mod eval_golden;
 mod make_unmake;
+mod outcome;
 mod perft;
 mod proptest;
 mod search;
 mod search_tables;
+mod variant;
@@ -0,0 +1,78 @@
+//! Game termination tests.
+
+use crate::board::{Board, Color, GameResult, TerminationReason, Variant};
+
+fn game_result(fen: &str) -> Option<TerminationReason> {
+    Board::from_fen(fen).game_result()
+}
+
+#[test]
+fn test_game_result_from_position() {
+    assert_eq!(Board::new().game_result(), None);
+    assert_eq!(
+        game_result("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1"),
+        Some(TerminationReason::Checkmate(Color::White))
+    );
+    assert_eq!(
+        game_result("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+        Some(TerminationReason::Stalemate)
+    );
+    assert_eq!(
+        game_result("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80"),
+        Some(TerminationReason::FiftyMove)
+    );
+    assert_eq!(
+        game_result("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"),
+        Some(TerminationReason::InsufficientMaterial)
+    );
+    // Mate on the hundredth half-move still counts
+    assert_eq!(
+        game_result("7k/6Q1/6K1/8/8/8/8/8 b - - 100 90"),
+        Some(TerminationReason::Checkmate(Color::White))
+    );
+
+    let mut board = Board::new();
+    for mv in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+        board.make_move_san(mv).unwrap();
+    }
+    assert_eq!(board.game_result(), Some(TerminationReason::Repetition));
+
+    let mut hill = Board::from_fen("4k3/8/8/8/4K3/8/8/8 b - - 0 1");
+    hill.set_variant(Variant::KingOfTheHill);
+    assert_eq!(
+        hill.game_result(),
+        Some(TerminationReason::VariantWin(Color::White))
+    );
+}
+
+#[test]
+fn test_termination_results() {
+    let cases = [
+        (
+            TerminationReason::Checkmate(Color::Black),
+            GameResult::BlackWins,
+            "Black mates",
+        ),
+        (TerminationReason::Stalemate, GameResult::Draw, "Stalemate"),
+        (
+            TerminationReason::Repetition,
+            GameResult::Draw,
+            "Draw by repetition",
+        ),
+        (
+            TerminationReason::Resignation(Color::White),
+            GameResult::BlackWins,
+            "White resigns",
+        ),
+        (
+            TerminationReason::TimeForfeit(Color::Black),
+            GameResult::WhiteWins,
+            "Black loses on time",
+        ),
+    ];
+    for (reason, result, text) in cases {
+        assert_eq!(reason.result(), result);
+        assert_eq!(reason.is_draw(), result == GameResult::Draw);
+        assert_eq!(reason.to_string(), text);
+    }
+}
@@ -1,6 +1,6 @@
 //! Perft (performance test) for move generation correctness.
 
-use crate::board::Board;
+use crate::board::{Board, PerftStats, PerftTable};
 use std::time::Instant;
 
 struct TestPosition {
@@ -77,3 +77,164 @@ fn test_all_perft_positions() {
         }
     }
 }
+
+#[test]
+fn test_hashed_perft_matches_plain_perft() {
+    let mut table = PerftTable::new(1);
+    for position in TEST_POSITIONS {
+        let mut board = Board::from_fen(position.fen);
+        let fen = board.to_fen();
+
+        // The table is shared across positions and depths, so stale entries
+        // from earlier runs would show up as wrong counts
+        for &(depth, expected) in position.depths.iter().take(3) {
+            assert_eq!(
+                board.perft_hashed(depth, &mut table),
+                expected,
+                "Hashed perft failed for position '{}' at depth {}",
+                position.name,
+                depth
+            );
+        }
+        assert_eq!(board.to_fen(), fen);
+    }
+}
+
+/// Published leaf breakdowns (nodes, captures, e.p., castles, promotions,
+/// checks, checkmates) for the standard perft positions
+const STATS_POSITIONS: &[(&str, usize, [u64; 7])] = &[
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        4,
+        [197281, 1576, 0, 0, 0, 469, 8],
+    ),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+        [97862, 17102, 45, 3162, 0, 993, 1],
+    ),
+    (
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        4,
+        [43238, 3348, 123, 0, 0, 1680, 17],
+    ),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        4,
+        [422333, 131393, 0, 7795, 60032, 15492, 5],
+    ),
+];
+
+#[test]
+fn test_perft_stats_match_published_tables() {
+    for &(fen, depth, [nodes, captures, en_passant, castles, promotions, checks, checkmates]) in
+        STATS_POSITIONS
+    {
+        let stats = Board::from_fen(fen).perft_with_stats(depth);
+        assert_eq!(
+            stats,
+            PerftStats {
+                nodes,
+                captures,
+                en_passant,
+                castles,
+                promotions,
+                checks,
+                checkmates,
+            },
+            "Perft stats failed for {fen} at depth {depth}"
+        );
+    }
+
+    // Only node counts are published for positions 5 and 6
+    for position in &TEST_POSITIONS[4..6] {
+        let mut board = Board::from_fen(position.fen);
+        let (depth, expected) = position.depths[2];
+        assert_eq!(board.perft_with_stats(depth).nodes, expected);
+    }
+}
+
+/// Capture-only perft by filtering the full legal move list
+fn perft_captures_reference(board: &mut Board, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    for m in &board.generate_moves() {
+        if m.is_capture() {
+            let info = board.make_move(*m);
+            nodes += perft_captures_reference(board, depth - 1);
+            board.unmake_move(*m, info);
+        }
+    }
+    nodes
+}
+
+#[test]
+fn test_capture_perft_matches_filtered_movegen() {
+    for position in TEST_POSITIONS {
+        let mut board = Board::from_fen(position.fen);
+        for depth in 1..=3 {
+            assert_eq!(
+                board.perft_captures(depth),
+                perft_captures_reference(&mut board, depth),
+                "Capture perft failed for position '{}' at depth {}",
+                position.name,
+                depth
+            );
+        }
+    }
+    // Kiwipete has 8 captures at the root
+    let mut kiwipete = Board::from_fen(TEST_POSITIONS[1].fen);
+    assert_eq!(kiwipete.perft_captures(1), 8);
+}
+
+#[test]
+fn test_captures_and_quiets_partition_legal_moves() {
+    for position in TEST_POSITIONS {
+        let mut board = Board::from_fen(position.fen);
+        for first in &board.generate_moves() {
+            let info = board.make_move(*first);
+            let mut all: Vec<u16> = board.generate_moves().iter().map(|m| m.as_u16()).collect();
+            let captures = board.generate_captures();
+            let quiets = board.generate_quiets();
+            assert!(captures.iter().all(|m| m.is_capture()));
+            assert!(quiets.iter().all(|m| !m.is_capture()));
+
+            let mut split: Vec<u16> = captures
+                .iter()
+                .chain(quiets.iter())
+                .map(|m| m.as_u16())
+                .collect();
+            all.sort_unstable();
+            split.sort_unstable();
+            assert_eq!(all, split, "{} after {first}", position.name);
+            board.unmake_move(*first, info);
+        }
+    }
+}
+
+#[test]
+fn test_gives_check_matches_make_move() {
+    for position in TEST_POSITIONS {
+        let mut board = Board::from_fen(position.fen);
+        for first in &board.generate_moves() {
+            let info = board.make_move(*first);
+            let quiet_checks = board.generate_quiet_checks();
+            for m in &board.generate_moves() {
+                let predicted = board.gives_check(*m);
+                let child = board.make_move(*m);
+                let actual = board.in_check();
+                board.unmake_move(*m, child);
+                assert_eq!(
+                    predicted, actual,
+                    "{}: gives_check({m}) after {first}",
+                    position.name
+                );
+                let quiet = !m.is_capture() && !m.is_promotion();
+                assert_eq!(quiet_checks.iter().any(|c| c == m), quiet && actual);
+            }
+            board.unmake_move(*first, info);
+        }
+    }
+}
@@ -1,9 +1,19 @@
 //! Property-based tests using proptest.
 
-use crate::board::search::{HistoryTable, KillerTable};
-use crate::board::{Board, Move, Piece, Square, UnmakeInfo};
+use crate::board::search::{history_bonus, HistoryKey, HistoryTable, KillerTable, HISTORY_MAX};
+use crate::board::{Board, Color, Move, Piece, Square, UnmakeInfo};
 use proptest::prelude::*;
 
+/// History key of a white knight move to square `to`
+fn history_key(to: usize) -> HistoryKey {
+    HistoryKey {
+        color: Color::White,
+        piece: Piece::Knight,
+        to: Square::from_index(to),
+        threatened: false,
+    }
+}
+
 /// Strategy to generate a random legal move sequence length
 fn move_count_strategy() -> impl Strategy<Value = usize> {
     1..=20usize
@@ -14,6 +24,66 @@ fn seed_strategy() -> impl Strategy<Value = u64> {
     any::<u64>()
 }
 
+/// Start positions covering castling, promotions (incl. captures) and en passant
+const INVARIANT_FENS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/pP1p2pp/8/2pPp3/8/8/P1P2PpP/R3K2R w KQkq c6 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+/// Strategy for a start position plus a move sequence. Each move is an index
+/// into the legal move list, so failures shrink to short, early-choice sequences.
+fn move_sequence_strategy() -> impl Strategy<Value = (&'static str, Vec<prop::sample::Index>)> {
+    (
+        prop::sample::select(&INVARIANT_FENS[..]),
+        prop::collection::vec(any::<prop::sample::Index>(), 1..40),
+    )
+}
+
+/// Check every incrementally maintained field against a from-scratch recomputation
+fn check_incremental_state(board: &Board) -> Result<(), TestCaseError> {
+    prop_assert_eq!(board.hash(), board.calculate_initial_hash(), "zobrist key");
+
+    let mut fresh = board.clone();
+    fresh.recalculate_incremental_eval();
+    prop_assert_eq!(board.eval_mg, fresh.eval_mg, "mg eval");
+    prop_assert_eq!(board.eval_eg, fresh.eval_eg, "eg eval");
+    prop_assert_eq!(board.game_phase, fresh.game_phase, "game phase");
+    fresh.refresh_checkers();
+    prop_assert_eq!(board.checkers(), fresh.checkers(), "checkers");
+
+    let mut all = crate::board::Bitboard(0);
+    for color in Color::BOTH {
+        let mut side = crate::board::Bitboard(0);
+        for piece in Piece::ALL {
+            for sq in board.pieces_of(color, piece).iter() {
+                prop_assert_eq!(
+                    board.piece_at(sq),
+                    Some((color, piece)),
+                    "mailbox at {}",
+                    sq
+                );
+            }
+            side = side.or(board.pieces_of(color, piece));
+        }
+        prop_assert_eq!(board.occupied[color.index()], side, "occupancy");
+        prop_assert_eq!(
+            board.pieces_of(color, Piece::King).iter().next(),
+            Some(board.king_square[color.index()]),
+            "king square"
+        );
+        all = all.or(side);
+    }
+    prop_assert_eq!(board.all_occupied, all, "all occupancy");
+    prop_assert_eq!(
+        board.mailbox.iter().filter(|p| p.is_some()).count(),
+        all.popcount() as usize,
+        "mailbox piece count"
+    );
+    Ok(())
+}
+
 proptest! {
     /// Property: make_move followed by unmake_move restores board state exactly
     #[test]
@@ -161,7 +231,7 @@ proptest! {
         let moves = board.generate_moves();
         for mv in &moves {
             if mv.is_capture() {
-                let see = board.see(mv.from(), mv.to());
+                let see = board.see_squares(mv.from(), mv.to());
 
                 // SEE should not exceed the value of the captured piece
                 // (can't gain more than what's there)
@@ -190,7 +260,7 @@ proptest! {
 
         for mv in &moves {
             if mv.is_capture() {
-                let see = board.see(mv.from(), mv.to());
+                let see = board.see_squares(mv.from(), mv.to());
                 // Undefended pawn capture should equal pawn value
                 prop_assert_eq!(see, 100);
             }
@@ -239,43 +309,51 @@ proptest! {
     /// Property: history scores are non-negative after updates
     #[test]
     fn prop_history_non_negative(
-        mv_from in 0..64usize,
         mv_to in 0..64usize,
         depth in 1..10u32,
         num_updates in 1..10usize
     ) {
         let mut table = HistoryTable::new();
-        let mv = Move::quiet(
-            Square::from_index(mv_from),
-            Square::from_index(mv_to)
-        );
+        let key = history_key(mv_to);
 
         for _ in 0..num_updates {
-            table.update(&mv, depth, 0);
+            table.update(key, history_bonus(depth));
         }
 
-        prop_assert!(table.score(&mv) >= 0,
+        prop_assert!(table.score(key) >= 0,
             "History score should be non-negative");
     }
 
+    /// Property: bonuses and maluses never push a score out of bounds
+    #[test]
+    fn prop_history_bounded(
+        mv_to in 0..64usize,
+        depths in prop::collection::vec(-30..30i32, 1..200)
+    ) {
+        let mut table = HistoryTable::new();
+        let key = history_key(mv_to);
+
+        for depth in depths {
+            let bonus = history_bonus(depth.unsigned_abs());
+            table.update(key, if depth < 0 { -bonus } else { bonus });
+            prop_assert!(table.score(key).abs() <= HISTORY_MAX);
+        }
+    }
+
     /// Property: history decay reduces scores
     #[test]
     fn prop_history_decay_reduces(
-        mv_from in 0..64usize,
         mv_to in 0..64usize
     ) {
         let mut table = HistoryTable::new();
-        let mv = Move::quiet(
-            Square::from_index(mv_from),
-            Square::from_index(mv_to)
-        );
+        let key = history_key(mv_to);
 
         // Update with significant depth
-        table.update(&mv, 5, 0);
-        let before = table.score(&mv);
+        table.update(key, history_bonus(5));
+        let before = table.score(key);
 
         table.decay();
-        let after = table.score(&mv);
+        let after = table.score(key);
 
         prop_assert!(after <= before,
             "Decay should reduce or maintain score: before={}, after={}", before, after);
@@ -376,4 +454,86 @@ proptest! {
         }
         // Note: entry may not be found due to hash collisions, which is acceptable
     }
+
+    // ========================================================================
+    // Parser Robustness Property Tests
+    // ========================================================================
+
+    /// Property: malformed GUI input yields errors, never panics
+    #[test]
+    fn prop_parsers_never_panic(input in "\\PC{0,90}") {
+        let _ = Board::try_from_fen(&input);
+        let _ = crate::uci::command::parse_uci_command(&input);
+
+        let mut board = Board::new();
+        let _ = board.parse_move(&input);
+        let _ = board.parse_san(&input);
+    }
+
+    // ========================================================================
+    // Incremental State Property Tests
+    // ========================================================================
+
+    /// Property: incremental fields match a full recomputation after every
+    /// make and unmake, and the whole sequence unwinds to the start position
+    #[test]
+    fn prop_incremental_state_survives_make_unmake((fen, picks) in move_sequence_strategy()) {
+        let mut board = Board::from_fen(fen);
+        let initial_fen = board.to_fen();
+        let initial_hash = board.hash();
+
+        let mut history: Vec<(Move, UnmakeInfo)> = Vec::new();
+        for pick in &picks {
+            let moves = board.generate_moves();
+            if moves.is_empty() {
+                break;
+            }
+            let mv = moves.as_slice()[pick.index(moves.len())];
+            let info = board.make_move(mv);
+            check_incremental_state(&board)?;
+            history.push((mv, info));
+        }
+
+        while let Some((mv, info)) = history.pop() {
+            board.unmake_move(mv, info);
+            check_incremental_state(&board)?;
+        }
+
+        prop_assert_eq!(board.to_fen(), initial_fen);
+        prop_assert_eq!(board.hash(), initial_hash);
+        prop_assert_eq!(board.repetition_counts.get(initial_hash), 1);
+    }
+
+    /// Property: the incrementally updated NNUE accumulator matches a refresh
+    #[cfg(feature = "embedded_nnue")]
+    #[test]
+    fn prop_nnue_accumulator_matches_refresh((fen, picks) in move_sequence_strategy()) {
+        use crate::board::nnue::{NnueAccumulator, NnueNetwork};
+        use std::sync::LazyLock;
+
+        static NETWORK: LazyLock<NnueNetwork> = LazyLock::new(NnueNetwork::from_embedded);
+        let refreshed = |board: &Board| {
+            let (wf, bf) = board.compute_nnue_features();
+            let mut acc = NnueAccumulator::new(&NETWORK.feature_bias);
+            acc.refresh(&wf, &bf, &NETWORK);
+            acc
+        };
+
+        let mut board = Board::from_fen(fen);
+        let mut acc = refreshed(&board);
+        for pick in &picks {
+            let moves = board.generate_moves();
+            if moves.is_empty() {
+                break;
+            }
+            let mv = moves.as_slice()[pick.index(moves.len())];
+            let (color, piece) = board.piece_at(mv.from()).unwrap();
+            board.update_nnue_accumulator(&mut acc, mv, piece, color, &NETWORK);
+            board.make_move(mv);
+
+            let expected = refreshed(&board);
+            prop_assert!(acc.white == expected.white, "white accumulator after {}", mv);
+            prop_assert!(acc.black == expected.black, "black accumulator after {}", mv);
+        }
+    }
 }
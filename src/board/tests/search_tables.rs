@@ -1,8 +1,11 @@
 //! Tests for search tables: killer moves, history, counter moves, and MVV-LVA.
 
-use crate::board::search::{CounterMoveTable, HistoryTable, KillerTable, SearchState};
+use crate::board::search::{
+    history_bonus, CounterMoveTable, HistoryKey, HistoryTable, KillerTable, SearchState,
+    HISTORY_MAX,
+};
 use crate::board::state::Board;
-use crate::board::{Move, Square, EMPTY_MOVE};
+use crate::board::{Bitboard, Color, Move, Piece, Square, EMPTY_MOVE};
 
 fn make_board(fen: &str) -> Board {
     fen.parse().expect("valid fen")
@@ -100,56 +103,115 @@ fn test_killer_out_of_bounds_safe() {
 // History Table Tests
 // ============================================================================
 
+fn history_key(piece: Piece, to: (usize, usize), threatened: bool) -> HistoryKey {
+    HistoryKey {
+        color: Color::White,
+        piece,
+        to: Square::new(to.0, to.1),
+        threatened,
+    }
+}
+
 #[test]
 fn test_history_initial_zero() {
     let table = HistoryTable::new();
-    let mv = make_move((1, 4), (3, 4));
-    assert_eq!(table.score(&mv), 0);
+    assert_eq!(table.score(history_key(Piece::Pawn, (3, 4), false)), 0);
 }
 
 #[test]
 fn test_history_update_increases_score() {
     let mut table = HistoryTable::new();
-    let mv = make_move((1, 4), (3, 4));
+    let key = history_key(Piece::Pawn, (3, 4), false);
 
-    table.update(&mv, 3, 0);
-    assert!(table.score(&mv) > 0);
+    table.update(key, history_bonus(3));
+    assert!(table.score(key) > 0);
 }
 
 #[test]
 fn test_history_higher_depth_higher_bonus() {
     let mut table = HistoryTable::new();
-    let mv1 = make_move((1, 4), (3, 4));
-    let mv2 = make_move((1, 3), (3, 3));
+    let key1 = history_key(Piece::Pawn, (3, 4), false);
+    let key2 = history_key(Piece::Pawn, (3, 3), false);
 
-    table.update(&mv1, 2, 0); // depth 2: bonus = 8
-    table.update(&mv2, 4, 0); // depth 4: bonus = 64
+    table.update(key1, history_bonus(2));
+    table.update(key2, history_bonus(4));
 
-    assert!(table.score(&mv2) > table.score(&mv1));
+    assert!(table.score(key2) > table.score(key1));
 }
 
 #[test]
 fn test_history_accumulates() {
     let mut table = HistoryTable::new();
-    let mv = make_move((1, 4), (3, 4));
+    let key = history_key(Piece::Pawn, (3, 4), false);
 
-    table.update(&mv, 2, 0);
-    let score1 = table.score(&mv);
-    table.update(&mv, 2, 0);
-    let score2 = table.score(&mv);
+    table.update(key, history_bonus(2));
+    let score1 = table.score(key);
+    table.update(key, history_bonus(2));
+    let score2 = table.score(key);
 
     assert!(score2 > score1);
 }
 
+#[test]
+fn test_history_malus_and_gravity() {
+    let mut table = HistoryTable::new();
+    let key = history_key(Piece::Knight, (2, 5), false);
+
+    table.update(key, -history_bonus(5));
+    assert!(table.score(key) < 0);
+
+    // Repeated bonuses approach the bound without crossing it
+    for _ in 0..1000 {
+        table.update(key, history_bonus(20));
+    }
+    let score = table.score(key);
+    assert!(score > HISTORY_MAX / 2 && score <= HISTORY_MAX, "{score}");
+    for _ in 0..1000 {
+        table.update(key, -history_bonus(20));
+    }
+    assert!(table.score(key) >= -HISTORY_MAX);
+}
+
+#[test]
+fn test_history_is_indexed_by_piece_and_threat() {
+    let mut table = HistoryTable::new();
+    let knight = history_key(Piece::Knight, (2, 5), false);
+    table.update(knight, history_bonus(4));
+
+    assert_eq!(table.score(history_key(Piece::Bishop, (2, 5), false)), 0);
+    assert_eq!(table.score(history_key(Piece::Knight, (2, 5), true)), 0);
+    assert_eq!(
+        table.score(HistoryKey {
+            color: Color::Black,
+            ..knight
+        }),
+        0
+    );
+
+    // Either knight moving to d2 shares an entry, unless one is threatened
+    let board = make_board("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+    let b1d2 = Move::quiet(Square::new(0, 1), Square::new(1, 3));
+    let f1d2 = Move::quiet(Square::new(0, 5), Square::new(1, 3));
+    let quiet = Bitboard(0);
+    assert_eq!(
+        HistoryKey::new(&board, b1d2, quiet),
+        HistoryKey::new(&board, f1d2, quiet)
+    );
+    let threats = Bitboard::from_square(Square::new(0, 5));
+    let escape = HistoryKey::new(&board, f1d2, threats).unwrap();
+    assert!(escape.threatened);
+    assert_ne!(HistoryKey::new(&board, b1d2, threats), Some(escape));
+}
+
 #[test]
 fn test_history_decay_reduces_scores() {
     let mut table = HistoryTable::new();
-    let mv = make_move((1, 4), (3, 4));
+    let key = history_key(Piece::Pawn, (3, 4), false);
 
-    table.update(&mv, 5, 0);
-    let before = table.score(&mv);
+    table.update(key, history_bonus(5));
+    let before = table.score(key);
     table.decay();
-    let after = table.score(&mv);
+    let after = table.score(key);
 
     assert!(after < before);
 }
@@ -157,12 +219,12 @@ fn test_history_decay_reduces_scores() {
 #[test]
 fn test_history_reset_clears() {
     let mut table = HistoryTable::new();
-    let mv = make_move((1, 4), (3, 4));
+    let key = history_key(Piece::Pawn, (3, 4), false);
 
-    table.update(&mv, 5, 0);
+    table.update(key, history_bonus(5));
     table.reset();
 
-    assert_eq!(table.score(&mv), 0);
+    assert_eq!(table.score(key), 0);
 }
 
 // ============================================================================
@@ -224,7 +286,7 @@ fn test_counter_out_of_bounds_safe() {
 #[test]
 fn test_mvv_lva_pawn_takes_queen() {
     let board = make_board("8/8/3q4/4P3/8/8/8/8 w - - 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
     let mv = Move::capture(Square::new(4, 4), Square::new(5, 3)); // exd6
 
     let score = state.tables.mvv_lva_score(&board, &mv);
@@ -236,7 +298,7 @@ fn test_mvv_lva_pawn_takes_queen() {
 #[test]
 fn test_mvv_lva_queen_takes_pawn() {
     let board = make_board("8/8/3p4/4Q3/8/8/8/8 w - - 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
     let mv = Move::capture(Square::new(4, 4), Square::new(5, 3)); // Qxd6
 
     let score = state.tables.mvv_lva_score(&board, &mv);
@@ -254,7 +316,7 @@ fn test_mvv_lva_queen_takes_pawn() {
 #[test]
 fn test_mvv_lva_equal_trade() {
     let board = make_board("8/8/3n4/4N3/8/8/8/8 w - - 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
     let mv = Move::capture(Square::new(4, 4), Square::new(5, 3)); // Nxd6
 
     let score = state.tables.mvv_lva_score(&board, &mv);
@@ -265,7 +327,7 @@ fn test_mvv_lva_equal_trade() {
 #[test]
 fn test_mvv_lva_non_capture_zero() {
     let board = make_board("8/8/8/4N3/8/8/8/8 w - - 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
     let mv = Move::quiet(Square::new(4, 4), Square::new(5, 2)); // Ne5-c6 (quiet)
 
     let score = state.tables.mvv_lva_score(&board, &mv);
@@ -276,7 +338,7 @@ fn test_mvv_lva_non_capture_zero() {
 fn test_mvv_lva_ordering_correct() {
     // Position where multiple captures possible
     let board = make_board("8/8/2pq4/3PN3/8/8/8/8 w - - 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
 
     // Pawn takes queen (d5xd6)
     let pxq = Move::capture(Square::new(4, 3), Square::new(5, 3));
@@ -298,7 +360,7 @@ fn test_mvv_lva_ordering_correct() {
 #[test]
 fn test_mvv_lva_en_passant() {
     let board = make_board("8/8/8/3Pp3/8/8/8/8 w - e6 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
     let mv = Move::en_passant(Square::new(4, 3), Square::new(5, 4)); // dxe6 e.p.
 
     let score = state.tables.mvv_lva_score(&board, &mv);
@@ -306,6 +368,35 @@ fn test_mvv_lva_en_passant() {
     assert!(score > 0);
 }
 
+// ============================================================================
+// SEE Cache Tests
+// ============================================================================
+
+#[test]
+fn test_see_cache_matches_board_see() {
+    let mut state = SearchState::new(1);
+    // Rook takes a pawn defended by a pawn: losing exchange
+    let defended = make_board("8/8/2p5/3p4/8/8/8/3RK2k w - - 0 1");
+    // Same capture with the pawn undefended: winning
+    let hanging = make_board("8/8/8/3p4/8/8/8/3RK2k w - - 0 1");
+    let rxd5 = Move::capture(Square::new(0, 3), Square::new(4, 3));
+
+    for board in [&defended, &hanging, &defended] {
+        let expected = board.see_squares(rxd5.from(), rxd5.to());
+        // Ordering and pruning look up the same entry
+        assert_eq!(state.tables.see(board, &rxd5), expected);
+        assert_eq!(state.tables.see(board, &rxd5), expected);
+    }
+    assert!(state.tables.see(&defended, &rxd5) < 0);
+    assert!(state.tables.see(&hanging, &rxd5) > 0);
+
+    // Promotions count the new piece
+    let mut promotion = make_board("7k/P7/8/8/8/8/8/4K3 w - - 0 1");
+    let a8q = promotion.parse_move("a7a8q").unwrap();
+    assert_eq!(state.tables.see(&promotion, &a8q), promotion.see(a8q));
+    assert!(state.tables.see(&promotion, &a8q) > 0);
+}
+
 // ============================================================================
 // Integration: Move Ordering Score Priority
 // ============================================================================
@@ -314,7 +405,7 @@ fn test_mvv_lva_en_passant() {
 fn test_move_ordering_captures_positive() {
     // MVV-LVA scores should be positive for good captures
     let board = make_board("8/8/3q4/4P3/8/8/8/8 w - - 0 1");
-    let state = SearchState::new(1);
+    let mut state = SearchState::new(1);
 
     // Pawn captures queen
     let mv = Move::capture(Square::new(4, 4), Square::new(5, 3));
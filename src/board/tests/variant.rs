@@ -0,0 +1,147 @@
+//! King of the Hill and three-check tests.
+
+use std::sync::atomic::AtomicBool;
+
+use crate::board::search::{find_best_move, SearchState};
+use crate::board::{Board, Color, FenError, Variant};
+use crate::uci::try_parse_position_command;
+
+fn best_move(board: &mut Board, depth: u32) -> String {
+    let mut state = SearchState::new(1);
+    let stop = AtomicBool::new(false);
+    find_best_move(board, &mut state, depth, &stop)
+        .expect("should find a move")
+        .to_string()
+}
+
+#[test]
+fn test_variant_uci_names() {
+    for variant in Variant::ALL {
+        assert_eq!(Variant::from_uci_name(variant.uci_name()), Some(variant));
+    }
+    assert_eq!(
+        Variant::from_uci_name("KingOfTheHill"),
+        Some(Variant::KingOfTheHill)
+    );
+    assert_eq!("3check".parse(), Ok(Variant::ThreeCheck));
+    assert_eq!(Variant::from_uci_name("atomic"), None);
+}
+
+#[test]
+fn test_set_variant_keeps_standard_hash() {
+    let mut board = Board::new();
+    let standard = board.hash();
+    board.set_variant(Variant::ThreeCheck);
+    assert_eq!(board.hash(), board.calculate_initial_hash());
+    board.set_variant(Variant::Standard);
+    assert_eq!(board.hash(), standard);
+    assert_eq!(board.repetition_count(), 1);
+}
+
+#[test]
+fn test_king_of_the_hill_winner() {
+    let mut board = Board::from_fen("8/8/8/8/8/4K3/8/k7 w - - 0 1");
+    board.make_move_uci("e3e4").unwrap();
+    assert_eq!(board.variant_winner(), None);
+
+    let mut board = Board::from_fen("8/8/8/8/8/4K3/8/k7 w - - 0 1");
+    board.set_variant(Variant::KingOfTheHill);
+    board.make_move_uci("e3f3").unwrap();
+    assert_eq!(board.variant_winner(), None);
+    board.make_move_uci("a1b1").unwrap();
+    board.make_move_uci("f3e4").unwrap();
+    assert_eq!(board.variant_winner(), Some(Color::White));
+}
+
+#[test]
+fn test_three_check_counts_checks() {
+    let mut board = Board::new();
+    board.set_variant(Variant::ThreeCheck);
+    for mv in ["e2e4", "e7e5", "f1c4", "d7d6"] {
+        board.make_move_uci(mv).unwrap();
+    }
+    let before = board.clone();
+
+    let mv = board.parse_move("c4f7").unwrap();
+    let info = board.make_move(mv);
+    assert_eq!(board.checks_given(Color::White), 1);
+    assert_eq!(board.checks_given(Color::Black), 0);
+    assert_eq!(board.hash(), board.calculate_initial_hash());
+    assert_eq!(board.variant_winner(), None);
+
+    board.unmake_move(mv, info);
+    assert_eq!(board.checks_given(Color::White), 0);
+    assert_eq!(board.hash(), before.hash());
+}
+
+#[test]
+fn test_three_check_fen_fields() {
+    // Checks given, lichess style
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +1+2";
+    let board = Board::from_fen(fen);
+    assert_eq!(board.variant(), Variant::ThreeCheck);
+    assert_eq!(board.checks_given(Color::White), 1);
+    assert_eq!(board.checks_given(Color::Black), 2);
+    assert_eq!(board.to_fen(), fen);
+    assert_eq!(board.hash(), board.calculate_initial_hash());
+
+    // Checks remaining, Stockfish style, before the move counters
+    let board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+3 7 1");
+    assert_eq!(board.checks_given(Color::White), 1);
+    assert_eq!(board.checks_given(Color::Black), 0);
+    assert_eq!(board.halfmove_clock(), 7);
+
+    let board = Board::from_fen("8/8/8/8/8/8/8/K1k5 w - - +3+0");
+    assert_eq!(board.variant_winner(), Some(Color::White));
+
+    assert_eq!(
+        Board::try_from_fen("8/8/8/8/8/8/8/K1k5 w - - 0 1 +4+0").unwrap_err(),
+        FenError::InvalidCheckCount {
+            found: "4+0".to_string()
+        }
+    );
+    assert!(Board::try_from_fen("8/8/8/8/8/8/8/K1k5 w - - 0 1 +1").is_err());
+}
+
+#[test]
+fn test_search_moves_king_to_the_hill() {
+    let mut board = Board::from_fen("8/8/8/8/8/4K3/8/k7 w - - 0 1");
+    board.set_variant(Variant::KingOfTheHill);
+    let mv = best_move(&mut board, 3);
+    assert!(
+        ["e3d4", "e3e4"].contains(&mv.as_str()),
+        "expected a king move to the centre, got {mv}"
+    );
+}
+
+#[test]
+fn test_search_gives_third_check() {
+    // The only checks give up the queen, which a standard search avoids
+    let fen = "1r4k1/5ppp/8/8/8/8/8/K2Q4 w - - 0 1";
+    assert_ne!(best_move(&mut Board::from_fen(fen), 3), "d1d8");
+
+    let mut board = Board::from_fen(&format!("{fen} +2+0"));
+    let best = best_move(&mut board, 3);
+    assert_eq!(best, "d1d8");
+    board.make_move_uci(&best).unwrap();
+    assert_eq!(board.variant_winner(), Some(Color::White));
+}
+
+#[test]
+fn test_position_command_uses_board_variant() {
+    let mut board = Board::new();
+    board.set_variant(Variant::ThreeCheck);
+    let cmd = "position startpos moves e2e4 e7e5 f1c4 d7d6 c4f7 e8f7";
+    let parts: Vec<&str> = cmd.split_whitespace().collect();
+    try_parse_position_command(&mut board, &parts).unwrap();
+    assert_eq!(board.variant(), Variant::ThreeCheck);
+    assert_eq!(board.checks_given(Color::White), 1);
+
+    // Check counters in the FEN are ignored in standard chess
+    let mut board = Board::new();
+    let cmd = "position fen 8/8/8/8/8/8/8/K1k5 w - - 0 1 +2+0 moves a1a2";
+    let parts: Vec<&str> = cmd.split_whitespace().collect();
+    try_parse_position_command(&mut board, &parts).unwrap();
+    assert_eq!(board.variant(), Variant::Standard);
+    assert_eq!(board.to_fen(), "8/8/8/8/8/8/K7/2k5 b - - 1 1");
+}
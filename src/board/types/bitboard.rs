@@ -1,6 +1,27 @@
 //! Bitboard type and operations.
-
+//!
+//! A [`Bitboard`] is a set of squares packed into a `u64` (bit 0 = a1,
+//! bit 63 = h8). Besides the usual set operations this module provides the
+//! geometric primitives evaluation code keeps needing: directional shifts,
+//! flood fills, pawn attack sets and spans, file/rank masks, and the
+//! [`Bitboard::between`] / [`Bitboard::line`] square-pair lookups.
+//!
+//! # Example
+//! ```
+//! use chess_engine::board::bitboard::Bitboard;
+//! use chess_engine::board::{Color, Square};
+//!
+//! let a1 = Square::new(0, 0);
+//! let d4 = Square::new(3, 3);
+//! assert_eq!(Bitboard::between(a1, d4).popcount(), 2); // b2, c3
+//!
+//! let pawn = Bitboard::from_square(Square::new(1, 4)); // e2
+//! assert!(pawn.pawn_attacks(Color::White).contains(Square::new(2, 3))); // d3
+//! ```
+
+use super::piece::Color;
 use super::square::Square;
+use crate::board::attack_tables::{BETWEEN, LINE};
 
 /// A 64-bit bitboard representing piece positions or attack squares.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -111,6 +132,106 @@ impl Bitboard {
         Bitboard((self.0 >> 1) & !Self::FILE_H.0)
     }
 
+    /// Shift toward the promotion rank of `color`
+    #[inline]
+    #[must_use]
+    pub const fn shift_forward(self, color: Color) -> Self {
+        match color {
+            Color::White => self.shift_north(),
+            Color::Black => self.shift_south(),
+        }
+    }
+
+    /// Flood fill north: every square on or above a set bit in the same file
+    #[inline]
+    #[must_use]
+    pub const fn fill_north(self) -> Self {
+        let mut bb = self.0;
+        bb |= bb << 8;
+        bb |= bb << 16;
+        bb |= bb << 32;
+        Bitboard(bb)
+    }
+
+    /// Flood fill south: every square on or below a set bit in the same file
+    #[inline]
+    #[must_use]
+    pub const fn fill_south(self) -> Self {
+        let mut bb = self.0;
+        bb |= bb >> 8;
+        bb |= bb >> 16;
+        bb |= bb >> 32;
+        Bitboard(bb)
+    }
+
+    /// Flood fill toward the promotion rank of `color`
+    #[inline]
+    #[must_use]
+    pub const fn fill_forward(self, color: Color) -> Self {
+        match color {
+            Color::White => self.fill_north(),
+            Color::Black => self.fill_south(),
+        }
+    }
+
+    /// Flood fill toward the home rank of `color`
+    #[inline]
+    #[must_use]
+    pub const fn fill_backward(self, color: Color) -> Self {
+        match color {
+            Color::White => self.fill_south(),
+            Color::Black => self.fill_north(),
+        }
+    }
+
+    /// Squares attacked by pawns of `color` standing on this bitboard
+    #[inline]
+    #[must_use]
+    pub const fn pawn_attacks(self, color: Color) -> Self {
+        let forward = self.shift_forward(color);
+        Bitboard(forward.shift_east().0 | forward.shift_west().0)
+    }
+
+    /// Every square these pawns could attack as they advance (attack span).
+    ///
+    /// Squares outside the enemy's span are safe outpost candidates; a pawn
+    /// with no enemy pawns in front of its span is passed.
+    #[inline]
+    #[must_use]
+    pub const fn pawn_attacks_span(self, color: Color) -> Self {
+        self.pawn_attacks(color).fill_forward(color)
+    }
+
+    /// Mask of the file containing `sq`
+    #[inline]
+    #[must_use]
+    pub const fn file_of(sq: Square) -> Self {
+        Self::file_mask(sq.file())
+    }
+
+    /// Mask of the rank containing `sq`
+    #[inline]
+    #[must_use]
+    pub const fn rank_of(sq: Square) -> Self {
+        Self::rank_mask(sq.rank())
+    }
+
+    /// Squares strictly between `a` and `b` if they share a rank, file or
+    /// diagonal, otherwise empty.
+    #[inline]
+    #[must_use]
+    pub fn between(a: Square, b: Square) -> Self {
+        Bitboard(BETWEEN[a.index()][b.index()])
+    }
+
+    /// The full edge-to-edge line through `a` and `b` (both included) if
+    /// they share a rank, file or diagonal, otherwise empty.
+    #[inline]
+    #[must_use]
+    pub fn line(a: Square, b: Square) -> Self {
+        Bitboard(LINE[a.index()][b.index()])
+    }
+
     /// Get the file mask for a given file index (0-7)
     #[inline]
     #[must_use]
@@ -380,8 +501,66 @@ mod tests {
     #[test]
     fn test_has_bit() {
         let bb = Bitboard::FILE_A;
-        assert!(bb.has_bit(0));  // a1
-        assert!(bb.has_bit(8));  // a2
+        assert!(bb.has_bit(0)); // a1
+        assert!(bb.has_bit(8)); // a2
         assert!(!bb.has_bit(1)); // b1
     }
+
+    #[test]
+    fn test_fills() {
+        let e4 = Bitboard::from_square(Square::new(3, 4));
+        assert_eq!(e4.fill_north().popcount(), 5);
+        assert_eq!(e4.fill_south().popcount(), 4);
+        assert_eq!(e4.fill_forward(Color::Black), e4.fill_south());
+        assert_eq!(e4.fill_backward(Color::Black), e4.fill_north());
+    }
+
+    #[test]
+    fn test_pawn_attacks_and_span() {
+        let a2 = Bitboard::from_square(Square::new(1, 0));
+        assert_eq!(
+            a2.pawn_attacks(Color::White),
+            Bitboard::from_square(Square::new(2, 1))
+        );
+
+        let e7 = Bitboard::from_square(Square::new(6, 4));
+        let span = e7.pawn_attacks_span(Color::Black);
+        assert_eq!(span.popcount(), 12); // d1-d6 and f1-f6
+        assert!(span.contains(Square::new(0, 3)));
+        assert!(!span.contains(Square::new(6, 3)));
+    }
+
+    #[test]
+    fn test_between() {
+        let a1 = Square::new(0, 0);
+        let h8 = Square::new(7, 7);
+        assert_eq!(Bitboard::between(a1, h8).popcount(), 6);
+        assert_eq!(Bitboard::between(a1, h8), Bitboard::between(h8, a1));
+
+        let e1 = Square::new(0, 4);
+        let e8 = Square::new(7, 4);
+        assert_eq!(
+            Bitboard::between(e1, e8),
+            Bitboard::FILE_E.and(Bitboard::RANK_1.or(Bitboard::RANK_8).not())
+        );
+
+        // Adjacent and unaligned squares have nothing between them
+        assert!(Bitboard::between(e1, Square::new(1, 4)).is_empty());
+        assert!(Bitboard::between(a1, Square::new(1, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_line() {
+        let c1 = Square::new(0, 2);
+        let e3 = Square::new(2, 4);
+        let diag = Bitboard::line(c1, e3);
+        assert_eq!(diag.popcount(), 6); // c1-h6
+        assert!(diag.contains(Square::new(5, 7))); // h6
+
+        assert_eq!(
+            Bitboard::line(Square::new(3, 0), Square::new(3, 6)),
+            Bitboard::RANK_4
+        );
+        assert!(Bitboard::line(c1, Square::new(1, 4)).is_empty());
+    }
 }
@@ -7,7 +7,7 @@
 //! - `Move` and `MoveList` - move representation
 //! - `CastlingRights` - castling state
 
-mod bitboard;
+pub mod bitboard;
 mod castling;
 mod indices;
 mod moves;
@@ -476,7 +476,8 @@ impl ScoredMoveList {
 
     /// Sort moves by score in descending order.
     pub fn sort_by_score_desc(&mut self) {
-        self.as_mut_slice().sort_by(|a, b| b.score.cmp(&a.score));
+        self.as_mut_slice()
+            .sort_by_key(|m| std::cmp::Reverse(m.score));
     }
 
     /// Partial sort: find the best move from index `start` onwards and swap it to position `start`.
@@ -125,6 +125,14 @@ impl Square {
     pub fn file_distance(self, other: Square) -> i32 {
         (self.file() as i32 - other.file() as i32).abs()
     }
+
+    /// Calculate king-move (Chebyshev) distance to another square
+    #[inline]
+    #[must_use]
+    pub fn chebyshev_distance(self, other: Square) -> i32 {
+        let rank_dist = (self.rank() as i32 - other.rank() as i32).abs();
+        self.file_distance(other).max(rank_dist)
+    }
 }
 
 impl fmt::Display for Square {
@@ -290,6 +298,14 @@ mod tests {
         assert_eq!(e4.manhattan_distance(e4), 0);
     }
 
+    #[test]
+    fn test_square_chebyshev_distance() {
+        let a1 = Square::new(0, 0);
+        assert_eq!(a1.chebyshev_distance(Square::new(7, 7)), 7);
+        assert_eq!(a1.chebyshev_distance(Square::new(2, 5)), 5);
+        assert_eq!(a1.chebyshev_distance(a1), 0);
+    }
+
     #[test]
     fn test_square_file_distance() {
         let a1 = Square::new(0, 0);
@@ -0,0 +1,151 @@
+//! Chess variants that change only how a game is won.
+//!
+//! Both variants keep the standard rules of movement, so move generation,
+//! make/unmake and evaluation are shared with standard chess:
+//!
+//! - **King of the Hill**: a side also wins by moving its king to one of
+//!   the four centre squares (d4, e4, d5, e5).
+//! - **Three-check**: a side also wins by giving check for the third time.
+//!   The checks given by each side are part of the position, so they are
+//!   tracked in [`Board`], included in its hash and written to FEN as a
+//!   trailing `+W+B` field.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::{Bitboard, Board, Color, Piece};
+use crate::zobrist::ZOBRIST;
+
+/// Centre squares d4, e4, d5 and e5
+const HILL: Bitboard = Bitboard(0x0000_0018_1800_0000);
+
+/// Checks needed to win a three-check game
+pub const CHECKS_TO_WIN: u8 = 3;
+
+/// Rule set of a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    KingOfTheHill,
+    ThreeCheck,
+}
+
+impl Variant {
+    /// Every supported variant, in the order advertised to GUIs
+    pub const ALL: [Variant; 3] = [
+        Variant::Standard,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+    ];
+
+    /// Name used by the `UCI_Variant` option
+    #[must_use]
+    pub const fn uci_name(self) -> &'static str {
+        match self {
+            Variant::Standard => "chess",
+            Variant::KingOfTheHill => "kingofthehill",
+            Variant::ThreeCheck => "3check",
+        }
+    }
+
+    /// Parse a `UCI_Variant` name, case-insensitively
+    #[must_use]
+    pub fn from_uci_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|v| v.uci_name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.uci_name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_uci_name(s).ok_or_else(|| format!("unknown variant '{s}'"))
+    }
+}
+
+impl Board {
+    /// Rule set this position is played under
+    #[inline]
+    #[must_use]
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Checks given so far by `color` (only counted in three-check)
+    #[inline]
+    #[must_use]
+    pub fn checks_given(&self, color: Color) -> u8 {
+        self.checks_given[color.index()]
+    }
+
+    /// Play this position under `variant`.
+    ///
+    /// Checks are only counted by moves made after the switch, so set the
+    /// variant before replaying a game's moves.
+    pub fn set_variant(&mut self, variant: Variant) {
+        if variant == self.variant {
+            return;
+        }
+        self.repetition_counts.decrement(self.hash);
+        self.variant = variant;
+        self.hash = self.calculate_initial_hash();
+        self.repetition_counts.increment(self.hash);
+    }
+
+    /// Set the checks given by each side, `[white, black]`, and play the
+    /// position as three-check
+    pub(crate) fn set_checks_given(&mut self, checks: [u8; 2]) {
+        self.checks_given = checks.map(|c| c.min(CHECKS_TO_WIN));
+        self.variant = Variant::ThreeCheck;
+    }
+
+    /// Hash of the three-check counters, zero in other variants
+    pub(crate) fn checks_hash(&self) -> u64 {
+        if self.variant != Variant::ThreeCheck {
+            return 0;
+        }
+        let mut hash = 0;
+        for color in Color::BOTH {
+            let checks = self.checks_given(color);
+            if checks > 0 {
+                hash ^= ZOBRIST.check_keys[color.index()][usize::from(checks) - 1];
+            }
+        }
+        hash
+    }
+
+    /// Count a check just given by `color`, returning the hash delta
+    pub(crate) fn count_check(&mut self, color: Color) -> u64 {
+        let before = self.checks_hash();
+        let checks = &mut self.checks_given[color.index()];
+        *checks = (*checks + 1).min(CHECKS_TO_WIN);
+        before ^ self.checks_hash()
+    }
+
+    /// The side that has won by the variant's extra winning condition.
+    ///
+    /// Always `None` in standard chess; checkmate and draws are detected
+    /// as usual and are not reported here.
+    #[must_use]
+    pub fn variant_winner(&self) -> Option<Color> {
+        match self.variant {
+            Variant::Standard => None,
+            Variant::KingOfTheHill => Color::BOTH
+                .into_iter()
+                .find(|&c| self.pieces_of(c, Piece::King).intersects(HILL)),
+            Variant::ThreeCheck => Color::BOTH
+                .into_iter()
+                .find(|&c| self.checks_given(c) >= CHECKS_TO_WIN),
+        }
+    }
+}
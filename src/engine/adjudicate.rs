@@ -0,0 +1,538 @@
+//! Game adjudication for engine-vs-engine play.
+//!
+//! Self-play, data generation and match runners stop games early once the
+//! result is clear, the way tournament managers such as cutechess-cli do:
+//!
+//! - **win**: the score stays beyond `win_score` in the same side's favour
+//!   for `win_moves` consecutive moves by each side;
+//! - **draw**: after `draw_min_moves` full moves, the score stays within
+//!   `draw_score` of zero for `draw_moves` consecutive moves by each side;
+//! - **tablebase**: with at most `tablebase_pieces` pieces on the board, a
+//!   [`TablebaseProbe`] decides the result;
+//! - **max length**: the game is drawn after `max_plies` plies.
+//!
+//! An [`Adjudicator`] is fed every position reached together with the score
+//! reported by the engine that just moved.
+//!
+//! Engines can also end hopeless games themselves. A [`ResignPolicy`] tracks
+//! one engine's own scores and decides when it resigns or offers a draw; the
+//! `XBoard` handler follows it, and an [`Adjudicator`] given a
+//! [`ResignConfig`] applies it to both players.
+
+use std::sync::Arc;
+
+use crate::board::{Board, Color};
+
+pub use crate::board::GameResult;
+
+/// Why a game was adjudicated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjudicationReason {
+    /// Scores stayed decisive
+    WinScore,
+    /// Scores stayed near zero
+    DrawScore,
+    /// Tablebase result
+    Tablebase,
+    /// Game reached the maximum length
+    MaxLength,
+    /// The losing side resigned
+    Resignation,
+    /// One side offered a draw and the other accepted
+    DrawAgreed,
+}
+
+/// An adjudicated game result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adjudication {
+    pub result: GameResult,
+    pub reason: AdjudicationReason,
+}
+
+/// Win/draw/loss from the side to move's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// Source of exact results for positions with few pieces
+pub trait TablebaseProbe: Send + Sync {
+    /// Result of `board` with best play, or `None` if it isn't covered
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    /// Plies to the next capture or pawn move with best play, positive
+    /// when the side to move wins and negative when it loses, or `None`
+    /// if unknown
+    fn probe_dtz(&self, _board: &Board) -> Option<i32> {
+        None
+    }
+}
+
+/// Adjudication thresholds; a rule is disabled by setting its move count
+/// (or `tablebase_pieces` / `max_plies`) to zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdjudicationConfig {
+    /// Centipawn score at which a game counts as won
+    pub win_score: i32,
+    /// Consecutive moves per side the win score must hold
+    pub win_moves: u32,
+    /// Largest absolute centipawn score that counts as drawn
+    pub draw_score: i32,
+    /// Consecutive moves per side the draw score must hold
+    pub draw_moves: u32,
+    /// Full moves that must be played before draw adjudication
+    pub draw_min_moves: u32,
+    /// Probe the tablebase at or below this many pieces (kings included)
+    pub tablebase_pieces: u32,
+    /// Draw the game after this many plies
+    pub max_plies: u32,
+}
+
+impl Default for AdjudicationConfig {
+    fn default() -> Self {
+        AdjudicationConfig {
+            win_score: 1000,
+            win_moves: 4,
+            draw_score: 10,
+            draw_moves: 8,
+            draw_min_moves: 40,
+            tablebase_pieces: 0,
+            max_plies: 400,
+        }
+    }
+}
+
+/// When an engine gives up or offers a draw; a rule is disabled by setting
+/// its score to zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResignConfig {
+    /// Resign once the engine's score is at or below minus this many
+    /// centipawns
+    pub resign_threshold: i32,
+    /// Consecutive own moves a resign or draw score must hold
+    pub resign_moves: u32,
+    /// Offer (and accept) draws while the engine's score is within this
+    /// many centipawns of zero
+    pub draw_offer_score: i32,
+}
+
+impl Default for ResignConfig {
+    fn default() -> Self {
+        ResignConfig {
+            resign_threshold: 0,
+            resign_moves: 3,
+            draw_offer_score: 0,
+        }
+    }
+}
+
+/// What a [`ResignPolicy`] decided after a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResignDecision {
+    Resign,
+    OfferDraw,
+}
+
+/// Tracks one engine's scores and decides when it resigns or offers a draw
+#[derive(Debug, Clone, Default)]
+pub struct ResignPolicy {
+    config: ResignConfig,
+    /// Consecutive own moves at or below the resign threshold
+    losing_streak: u32,
+    /// Consecutive own moves within the draw offer score
+    draw_streak: u32,
+}
+
+impl ResignPolicy {
+    #[must_use]
+    pub fn new(config: ResignConfig) -> Self {
+        ResignPolicy {
+            config,
+            losing_streak: 0,
+            draw_streak: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &ResignConfig {
+        &self.config
+    }
+
+    /// Change the thresholds, keeping the current streaks
+    pub fn set_config(&mut self, config: ResignConfig) {
+        self.config = config;
+    }
+
+    /// Forget the current game
+    pub fn reset(&mut self) {
+        self.losing_streak = 0;
+        self.draw_streak = 0;
+    }
+
+    /// Record the engine's score for the move it is about to play, from its
+    /// own point of view (`None`, e.g. for a book move, breaks any streak).
+    /// Returns whether to resign or offer a draw with this move.
+    pub fn record(&mut self, score: Option<i32>) -> Option<ResignDecision> {
+        let Some(score) = score else {
+            self.reset();
+            return None;
+        };
+        let config = &self.config;
+        let losing = config.resign_threshold > 0 && score <= -config.resign_threshold;
+        let drawish = config.draw_offer_score > 0 && score.abs() <= config.draw_offer_score;
+        self.losing_streak = if losing { self.losing_streak + 1 } else { 0 };
+        self.draw_streak = if drawish { self.draw_streak + 1 } else { 0 };
+
+        if self.losing_streak >= config.resign_moves.max(1) {
+            Some(ResignDecision::Resign)
+        } else if self.wants_draw() {
+            Some(ResignDecision::OfferDraw)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the engine would accept a draw offered now
+    #[must_use]
+    pub fn wants_draw(&self) -> bool {
+        self.draw_streak >= self.config.resign_moves.max(1)
+    }
+}
+
+/// Tracks one game and decides when it can be adjudicated
+pub struct Adjudicator {
+    config: AdjudicationConfig,
+    tablebase: Option<Arc<dyn TablebaseProbe>>,
+    /// Resignation and draw offers of each player, by colour
+    resign: Option<[ResignPolicy; 2]>,
+    plies: u32,
+    /// Consecutive plies with a decisive score, and the side it favours
+    win_streak: (u32, Option<Color>),
+    /// Consecutive plies with a drawish score
+    draw_streak: u32,
+}
+
+impl Adjudicator {
+    #[must_use]
+    pub fn new(config: AdjudicationConfig) -> Self {
+        Adjudicator {
+            config,
+            tablebase: None,
+            resign: None,
+            plies: 0,
+            win_streak: (0, None),
+            draw_streak: 0,
+        }
+    }
+
+    /// Use `probe` for tablebase adjudication
+    #[must_use]
+    pub fn with_tablebase(mut self, probe: Arc<dyn TablebaseProbe>) -> Self {
+        self.tablebase = Some(probe);
+        self
+    }
+
+    /// Let both players resign and agree draws as `config` says
+    #[must_use]
+    pub fn with_resign(mut self, config: ResignConfig) -> Self {
+        self.resign = Some([ResignPolicy::new(config), ResignPolicy::new(config)]);
+        self
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &AdjudicationConfig {
+        &self.config
+    }
+
+    /// Forget the current game
+    pub fn reset(&mut self) {
+        if let Some(players) = &mut self.resign {
+            players.iter_mut().for_each(ResignPolicy::reset);
+        }
+        self.plies = 0;
+        self.win_streak = (0, None);
+        self.draw_streak = 0;
+    }
+
+    /// Plies recorded so far
+    #[must_use]
+    pub fn plies(&self) -> u32 {
+        self.plies
+    }
+
+    /// Record a move: `board` is the position after it and `score` the
+    /// centipawn score the mover reported, from the mover's point of view
+    /// (`None` if it reported none, e.g. a book move, which breaks any
+    /// streak). Returns the adjudicated result once a rule applies.
+    pub fn record(&mut self, board: &Board, score: Option<i32>) -> Option<Adjudication> {
+        self.plies += 1;
+        // The mover is the side not to move in the new position
+        let mover = if board.white_to_move() {
+            Color::Black
+        } else {
+            Color::White
+        };
+        self.update_streaks(mover, score);
+
+        self.player_result(mover, score)
+            .or_else(|| self.tablebase_result(board))
+            .or_else(|| self.score_result())
+            .or_else(|| {
+                (self.config.max_plies > 0 && self.plies >= self.config.max_plies).then_some(
+                    Adjudication {
+                        result: GameResult::Draw,
+                        reason: AdjudicationReason::MaxLength,
+                    },
+                )
+            })
+    }
+
+    fn update_streaks(&mut self, mover: Color, score: Option<i32>) {
+        let Some(score) = score else {
+            self.win_streak = (0, None);
+            self.draw_streak = 0;
+            return;
+        };
+
+        let leader = if score >= self.config.win_score {
+            Some(mover)
+        } else if score <= -self.config.win_score {
+            Some(mover.opponent())
+        } else {
+            None
+        };
+        self.win_streak = match (leader, self.win_streak) {
+            (Some(side), (n, Some(prev))) if side == prev => (n + 1, Some(side)),
+            (Some(side), _) => (1, Some(side)),
+            (None, _) => (0, None),
+        };
+
+        if score.abs() <= self.config.draw_score {
+            self.draw_streak += 1;
+        } else {
+            self.draw_streak = 0;
+        }
+    }
+
+    /// Resignation by the mover, or a draw it offers that the opponent's
+    /// last scores let it accept
+    fn player_result(&mut self, mover: Color, score: Option<i32>) -> Option<Adjudication> {
+        let players = self.resign.as_mut()?;
+        let (result, reason) = match players[mover.index()].record(score)? {
+            ResignDecision::Resign => (
+                GameResult::win_for(mover.opponent()),
+                AdjudicationReason::Resignation,
+            ),
+            ResignDecision::OfferDraw if players[mover.opponent().index()].wants_draw() => {
+                (GameResult::Draw, AdjudicationReason::DrawAgreed)
+            }
+            ResignDecision::OfferDraw => return None,
+        };
+        Some(Adjudication { result, reason })
+    }
+
+    fn score_result(&self) -> Option<Adjudication> {
+        let config = &self.config;
+        if let (n, Some(side)) = self.win_streak {
+            if config.win_moves > 0 && n >= 2 * config.win_moves {
+                return Some(Adjudication {
+                    result: GameResult::win_for(side),
+                    reason: AdjudicationReason::WinScore,
+                });
+            }
+        }
+        let draw_allowed = self.plies >= 2 * config.draw_min_moves;
+        (config.draw_moves > 0 && draw_allowed && self.draw_streak >= 2 * config.draw_moves)
+            .then_some(Adjudication {
+                result: GameResult::Draw,
+                reason: AdjudicationReason::DrawScore,
+            })
+    }
+
+    fn tablebase_result(&self, board: &Board) -> Option<Adjudication> {
+        let probe = self.tablebase.as_ref()?;
+        let pieces =
+            board.occupied_by(Color::White).popcount() + board.occupied_by(Color::Black).popcount();
+        if pieces > self.config.tablebase_pieces {
+            return None;
+        }
+        let to_move = if board.white_to_move() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let result = match probe.probe_wdl(board)? {
+            Wdl::Win => GameResult::win_for(to_move),
+            Wdl::Loss => GameResult::win_for(to_move.opponent()),
+            Wdl::Draw => GameResult::Draw,
+        };
+        Some(Adjudication {
+            result,
+            reason: AdjudicationReason::Tablebase,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const BLACK_TO_MOVE: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+
+    fn config() -> AdjudicationConfig {
+        AdjudicationConfig {
+            win_score: 500,
+            win_moves: 2,
+            draw_score: 5,
+            draw_moves: 2,
+            draw_min_moves: 2,
+            tablebase_pieces: 0,
+            max_plies: 0,
+        }
+    }
+
+    /// Alternate plies, white moving first; scores are from the mover's view
+    fn play(adj: &mut Adjudicator, scores: &[i32]) -> Option<Adjudication> {
+        let after_white = Board::from_fen(BLACK_TO_MOVE);
+        let after_black = Board::from_fen(START);
+        let mut last = None;
+        for (i, &score) in scores.iter().enumerate() {
+            let board = if i % 2 == 0 {
+                &after_white
+            } else {
+                &after_black
+            };
+            last = adj.record(board, Some(score));
+            if last.is_some() {
+                break;
+            }
+        }
+        last
+    }
+
+    #[test]
+    fn test_win_needs_both_sides_to_agree() {
+        let mut adj = Adjudicator::new(config());
+        // Black keeps disagreeing, so no adjudication
+        assert_eq!(play(&mut adj, &[600, 0, 600, 0, 600, 0]), None);
+
+        adj.reset();
+        let result = play(&mut adj, &[600, -600, 700, -700]).unwrap();
+        assert_eq!(result.result, GameResult::WhiteWins);
+        assert_eq!(result.reason, AdjudicationReason::WinScore);
+        assert_eq!(adj.plies(), 4);
+    }
+
+    #[test]
+    fn test_draw_waits_for_min_moves() {
+        let mut adj = Adjudicator::new(AdjudicationConfig {
+            draw_min_moves: 3,
+            ..config()
+        });
+        assert_eq!(play(&mut adj, &[0, 0, 0, 0, 0]), None);
+        let result = adj.record(&Board::from_fen(START), Some(0)).unwrap();
+        assert_eq!(result.result, GameResult::Draw);
+        assert_eq!(result.reason, AdjudicationReason::DrawScore);
+
+        // A missing score breaks the streak
+        adj.reset();
+        assert_eq!(play(&mut adj, &[0, 0, 0]), None);
+        assert_eq!(adj.record(&Board::from_fen(START), None), None);
+    }
+
+    #[test]
+    fn test_max_length() {
+        let mut adj = Adjudicator::new(AdjudicationConfig {
+            max_plies: 3,
+            ..config()
+        });
+        let result = play(&mut adj, &[100, -100, 100]).unwrap();
+        assert_eq!(result.reason, AdjudicationReason::MaxLength);
+        assert_eq!(result.result.as_pgn(), "1/2-1/2");
+    }
+
+    #[test]
+    fn test_resign_policy() {
+        let mut policy = ResignPolicy::new(ResignConfig {
+            resign_threshold: 500,
+            resign_moves: 2,
+            draw_offer_score: 10,
+        });
+        assert_eq!(policy.record(Some(-600)), None);
+        assert_eq!(policy.record(Some(-600)), Some(ResignDecision::Resign));
+
+        // A missing score breaks the streak
+        policy.reset();
+        assert_eq!(policy.record(Some(-600)), None);
+        assert_eq!(policy.record(None), None);
+        assert_eq!(policy.record(Some(-600)), None);
+
+        assert!(!policy.wants_draw());
+        assert_eq!(policy.record(Some(5)), None);
+        assert_eq!(policy.record(Some(-5)), Some(ResignDecision::OfferDraw));
+        assert!(policy.wants_draw());
+
+        // Disabled by default
+        let mut policy = ResignPolicy::default();
+        for _ in 0..10 {
+            assert_eq!(policy.record(Some(-10_000)), None);
+            assert_eq!(policy.record(Some(0)), None);
+        }
+    }
+
+    #[test]
+    fn test_adjudicator_applies_resignation_and_draw_offers() {
+        let resign = ResignConfig {
+            resign_threshold: 300,
+            resign_moves: 2,
+            draw_offer_score: 5,
+        };
+        let quiet = AdjudicationConfig {
+            win_moves: 0,
+            draw_moves: 0,
+            ..config()
+        };
+
+        // Black resigns after two of its own moves at -300 or worse
+        let mut adj = Adjudicator::new(quiet).with_resign(resign);
+        let result = play(&mut adj, &[100, -300, 100, -400]).unwrap();
+        assert_eq!(result.result, GameResult::WhiteWins);
+        assert_eq!(result.reason, AdjudicationReason::Resignation);
+
+        // A draw offer needs the opponent to accept
+        adj.reset();
+        assert_eq!(play(&mut adj, &[0, 50, 0, 50, 0, 50]), None);
+        adj.reset();
+        let result = play(&mut adj, &[0, 0, 0, 0]).unwrap();
+        assert_eq!(result.result, GameResult::Draw);
+        assert_eq!(result.reason, AdjudicationReason::DrawAgreed);
+        assert_eq!(adj.plies(), 4);
+    }
+
+    #[test]
+    fn test_tablebase_result_is_from_side_to_move() {
+        struct SideToMoveWins;
+        impl TablebaseProbe for SideToMoveWins {
+            fn probe_wdl(&self, _board: &Board) -> Option<Wdl> {
+                Some(Wdl::Win)
+            }
+        }
+
+        let mut adj = Adjudicator::new(AdjudicationConfig {
+            tablebase_pieces: 3,
+            ..config()
+        })
+        .with_tablebase(Arc::new(SideToMoveWins));
+        // Too many pieces to probe
+        assert_eq!(adj.record(&Board::from_fen(START), Some(0)), None);
+
+        let result = adj
+            .record(&Board::from_fen("8/8/8/4k3/8/8/4P3/4K3 b - - 0 1"), Some(0))
+            .unwrap();
+        assert_eq!(result.result, GameResult::BlackWins);
+        assert_eq!(result.reason, AdjudicationReason::Tablebase);
+    }
+}
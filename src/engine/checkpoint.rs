@@ -0,0 +1,357 @@
+//! Periodic checkpoints of long analyses.
+//!
+//! A correspondence player leaves `go infinite` running for hours or days.
+//! With a checkpoint file set, a [`CheckpointWriter`] thread saves the
+//! position, the deepest completed iteration (depth, score, PV) and the
+//! transposition table every few minutes and once more when the search
+//! ends, so a crash or restart loses at most one interval. The next
+//! `go infinite` on the same position loads the table back with
+//! [`Checkpoint::restore`] and resumes where the analysis stood.
+//!
+//! The file is a text header, then the table entries as written by
+//! [`TranspositionTable::save_entries`]:
+//!
+//! ```text
+//! chess_engine checkpoint 1
+//! fen <position>
+//! depth 31
+//! score cp 24
+//! nodes 123456789
+//! pv e2e4 e7e5 ...
+//! tt
+//! <binary entries>
+//! ```
+//!
+//! Scores are from the side to move's point of view. A checkpoint is
+//! written to a temporary file that is then renamed over the previous one,
+//! so the file on disk is always complete.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::board::SearchIterationInfo;
+use crate::output::SharedWriter;
+use crate::tt::TranspositionTable;
+
+/// First line of every checkpoint file
+const MAGIC: &str = "chess_engine checkpoint 1";
+
+/// Last iteration of a search, shared between the info callback and the
+/// checkpoint writer
+pub type LatestInfo = Arc<Mutex<Option<SearchIterationInfo>>>;
+
+/// What a checkpoint records about the analysis, besides the table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub fen: String,
+    pub depth: u32,
+    /// Centipawns, side to move's point of view
+    pub score: i32,
+    /// Moves to mate, when the score is a mate score
+    pub mate_in: Option<i32>,
+    pub nodes: u64,
+    pub pv: String,
+}
+
+impl Checkpoint {
+    /// Checkpoint of `info`, an iteration of the search of `fen`
+    #[must_use]
+    pub fn from_info(fen: &str, info: &SearchIterationInfo) -> Self {
+        Checkpoint {
+            fen: fen.to_string(),
+            depth: info.depth,
+            score: info.score,
+            mate_in: info.mate_in,
+            nodes: info.nodes,
+            pv: info.pv.clone(),
+        }
+    }
+
+    /// Score as UCI prints it, `cp <n>` or `mate <n>`
+    #[must_use]
+    pub fn score_string(&self) -> String {
+        match self.mate_in {
+            Some(mate) => format!("mate {mate}"),
+            None => format!("cp {}", self.score),
+        }
+    }
+
+    /// Save this checkpoint and the entries of `tt` to `path`, returning the
+    /// number of entries saved
+    ///
+    /// # Errors
+    ///
+    /// Returns any error creating, writing or renaming the file.
+    pub fn write(&self, path: &Path, tt: &TranspositionTable) -> io::Result<u64> {
+        let tmp = temporary_path(path);
+        let mut w = BufWriter::new(File::create(&tmp)?);
+        writeln!(w, "{MAGIC}")?;
+        writeln!(w, "fen {}", self.fen)?;
+        writeln!(w, "depth {}", self.depth)?;
+        writeln!(w, "score {}", self.score_string())?;
+        writeln!(w, "nodes {}", self.nodes)?;
+        writeln!(w, "pv {}", self.pv)?;
+        writeln!(w, "tt")?;
+        let entries = tt.save_entries(&mut w)?;
+        w.into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(entries)
+    }
+
+    /// Read the header of the checkpoint at `path`, leaving the reader at
+    /// the table entries
+    fn read_header(path: &Path) -> io::Result<(Self, BufReader<File>)> {
+        let mut r = BufReader::new(File::open(path)?);
+        let mut line = String::new();
+        r.read_line(&mut line)?;
+        if line.trim_end() != MAGIC {
+            return Err(invalid("not a checkpoint file"));
+        }
+        let mut checkpoint = Checkpoint {
+            fen: String::new(),
+            depth: 0,
+            score: 0,
+            mate_in: None,
+            nodes: 0,
+            pv: String::new(),
+        };
+        loop {
+            line.clear();
+            if r.read_line(&mut line)? == 0 {
+                return Err(invalid("no table in checkpoint"));
+            }
+            let line = line.trim_end();
+            if line == "tt" {
+                return Ok((checkpoint, r));
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "fen" => checkpoint.fen = value.to_string(),
+                "depth" => checkpoint.depth = parse_field(value)?,
+                "nodes" => checkpoint.nodes = parse_field(value)?,
+                "pv" => checkpoint.pv = value.to_string(),
+                "score" => match value.split_once(' ') {
+                    Some(("cp", cp)) => checkpoint.score = parse_field(cp)?,
+                    Some(("mate", mate)) => checkpoint.mate_in = Some(parse_field(mate)?),
+                    _ => return Err(invalid("bad score")),
+                },
+                // Fields from a later version
+                _ => {}
+            }
+        }
+    }
+
+    /// Read the checkpoint at `path` without its table
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a checkpoint.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::read_header(path).map(|(checkpoint, _)| checkpoint)
+    }
+
+    /// Read the checkpoint at `path` and load its entries into `tt`, stamped
+    /// with `generation`, returning it and the number of entries loaded
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a checkpoint;
+    /// entries before a truncation are kept in `tt`.
+    pub fn restore(
+        path: &Path,
+        tt: &TranspositionTable,
+        generation: u16,
+    ) -> io::Result<(Self, u64)> {
+        let (checkpoint, mut r) = Self::read_header(path)?;
+        let entries = tt.load_entries(&mut r, generation)?;
+        Ok((checkpoint, entries))
+    }
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn parse_field<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value.trim().parse().map_err(|_| invalid("bad number"))
+}
+
+/// Background thread saving a running analysis every `interval` and once
+/// more when it is finished (or dropped)
+pub struct CheckpointWriter {
+    done: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl CheckpointWriter {
+    /// Start saving the search of `fen` to `path`: its last iteration is
+    /// read from `latest` and its table from `tt`. Each save is reported on
+    /// `out` as an `info string`; nothing is saved before the first
+    /// completed iteration.
+    #[must_use]
+    pub fn spawn(
+        path: PathBuf,
+        fen: String,
+        interval: Duration,
+        tt: Arc<TranspositionTable>,
+        latest: LatestInfo,
+        out: SharedWriter,
+    ) -> Self {
+        let (done, rx) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("checkpoint".to_string())
+            .spawn(move || loop {
+                let last = !matches!(rx.recv_timeout(interval), Err(RecvTimeoutError::Timeout));
+                let info = latest.lock().clone();
+                if let Some(info) = info {
+                    let checkpoint = Checkpoint::from_info(&fen, &info);
+                    match checkpoint.write(&path, &tt) {
+                        Ok(entries) => crate::send_line!(
+                            out,
+                            "info string checkpoint saved {} depth {} score {} entries {entries}",
+                            path.display(),
+                            checkpoint.depth,
+                            checkpoint.score_string()
+                        ),
+                        Err(e) => crate::send_line!(
+                            out,
+                            "info string Cannot write checkpoint {}: {e}",
+                            path.display()
+                        ),
+                    }
+                    let _ = out.flush();
+                }
+                if last {
+                    break;
+                }
+            })
+            .expect("failed to spawn checkpoint thread");
+        CheckpointWriter {
+            done: Some(done),
+            handle: Some(handle),
+        }
+    }
+
+    /// Save a last time and wait for the thread to end
+    pub fn finish(mut self) {
+        self.shut_down();
+    }
+
+    fn shut_down(&mut self) {
+        if let Some(done) = self.done.take() {
+            let _ = done.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for CheckpointWriter {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::CaptureWriter;
+    use crate::tt::BoundType;
+
+    fn info(depth: u32) -> SearchIterationInfo {
+        SearchIterationInfo {
+            depth,
+            nodes: 1000,
+            nps: 0,
+            time_ms: 0,
+            score: -35,
+            mate_in: None,
+            pv: "e7e5 g1f3".to_string(),
+            seldepth: depth,
+            tt_hits: 0,
+            multipv: 1,
+            hashfull: 0,
+            progress: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{name}_{}.ckpt", std::process::id()))
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let path = temp_file("checkpoint_round_trip");
+        let tt = TranspositionTable::new(1);
+        tt.store(0xFEED, 12, 35, BoundType::Exact, None, 1);
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+        let mut checkpoint = Checkpoint::from_info(fen, &info(12));
+        checkpoint.write(&path, &tt).unwrap();
+        assert_eq!(Checkpoint::read(&path).unwrap(), checkpoint);
+
+        let restored = TranspositionTable::new(1);
+        let (read, entries) = Checkpoint::restore(&path, &restored, 5).unwrap();
+        assert_eq!(read, checkpoint);
+        assert_eq!(entries, 1);
+        assert_eq!(restored.probe(0xFEED).unwrap().depth, 12);
+
+        checkpoint.mate_in = Some(-4);
+        checkpoint.write(&path, &tt).unwrap();
+        assert_eq!(Checkpoint::read(&path).unwrap().score_string(), "mate -4");
+
+        std::fs::write(&path, "not a checkpoint\n").unwrap();
+        assert!(Checkpoint::read(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_writer_saves_periodically_and_at_finish() {
+        let path = temp_file("checkpoint_writer");
+        let _ = std::fs::remove_file(&path);
+        let latest: LatestInfo = Arc::new(Mutex::new(None));
+        let capture = CaptureWriter::new();
+        let out: SharedWriter = Arc::new(capture.clone());
+        let writer = CheckpointWriter::spawn(
+            path.clone(),
+            "8/8/8/8/8/8/8/K6k w - - 0 1".to_string(),
+            Duration::from_millis(10),
+            Arc::new(TranspositionTable::new(1)),
+            Arc::clone(&latest),
+            out,
+        );
+
+        // Nothing to save before the first iteration
+        thread::sleep(Duration::from_millis(50));
+        assert!(!path.exists());
+
+        *latest.lock() = Some(info(3));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(Checkpoint::read(&path).unwrap().depth, 3);
+
+        *latest.lock() = Some(info(4));
+        writer.finish();
+        assert_eq!(Checkpoint::read(&path).unwrap().depth, 4);
+        assert!(capture
+            .lines()
+            .iter()
+            .all(|line| line.starts_with("info string checkpoint saved ")));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,4 +1,11 @@
 //! Engine controller implementation.
+//!
+//! Controllers are independent of each other, so a server can run many of
+//! them in one process: each owns its board, `SearchState` (transposition
+//! table, histories, NNUE accumulators), stop flags and search threads.
+//! The only process-wide state is immutable (Zobrist keys, attack tables,
+//! the embedded NNUE network) plus the diagnostic sinks behind the `LogFile`
+//! option and `--record`, which are shared by all sessions.
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -7,9 +14,14 @@ use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
 
+use super::inspect::SearchStatus;
+use super::time::{DefaultTimeManager, TimeConfig, TimeControl, TimeManager};
+
+use crate::board::nnue::NnueNetwork;
 use crate::board::search::smp::{smp_search, SmpConfig};
 use crate::board::{
-    search, Board, SearchClock, SearchConfig, SearchInfoCallback, SearchResult, SearchState,
+    search, Board, MemoryUsage, Move, SearchClock, SearchConfig, SearchInfoCallback, SearchResult,
+    SearchState,
 };
 
 /// Search thread stack size (32 MB)
@@ -22,6 +34,17 @@ const MAX_POLL_SLEEP_MS: u64 = 5;
 /// Poll interval when waiting for ponder to complete
 const PONDER_POLL_MS: u64 = 10;
 
+/// Time for a search restarted after a ponder desync whose parameters
+/// carried no time limits of their own
+const DESYNC_RESTART_TIME_MS: u64 = 1000;
+
+/// Reports the result of a search, run once by whoever finishes it
+type CompletionCallback = Box<dyn FnOnce(SearchResult) + Send>;
+
+/// Completion callback shared with the search thread, so an aborted ponder
+/// search can take it back instead of reporting
+type CompletionSlot = Arc<Mutex<Option<CompletionCallback>>>;
+
 /// Active search job state
 pub struct SearchJob {
     /// Stop flag for the search
@@ -34,6 +57,12 @@ pub struct SearchJob {
     pub planned_soft_time_ms: u64,
     /// Planned hard time limit (for ponderhit)
     pub planned_hard_time_ms: u64,
+    /// Hash of the position being searched
+    pub root_hash: u64,
+    /// Parameters the search was started with (for a desync restart)
+    params: SearchParams,
+    /// Completion callback, until the search thread or an abort takes it
+    on_complete: CompletionSlot,
     /// Handle to the search thread
     handle: JoinHandle<()>,
     /// Optional handle to the timer thread enforcing hard stops
@@ -55,54 +84,83 @@ impl SearchJob {
         }
     }
 
+    /// Stop the search without reporting its result, handing back its
+    /// parameters and completion callback (`None` if the result was
+    /// already reported)
+    fn abort_silently(mut self) -> Option<(SearchParams, CompletionCallback)> {
+        let on_complete = self.on_complete.lock().take();
+        let params = std::mem::take(&mut self.params);
+        self.stop_and_wait();
+        on_complete.map(|report| (params, report))
+    }
+
     /// Signal stop without waiting
     pub fn signal_stop(&self) {
         self.stop.store(true, Ordering::Relaxed);
         self.pondering.store(false, Ordering::Relaxed);
     }
 
-    /// Handle ponderhit - transition from pondering to real search
+    /// Handle ponderhit - transition from pondering to real search.
+    ///
+    /// The search keeps running: its clock is switched from unlimited to
+    /// the planned limits, counted from now, and the hard limit gets a
+    /// timer. A search that already finished reports its move at once.
     pub fn ponderhit(&mut self) {
         if self.pondering.load(Ordering::Relaxed) {
             let start = Instant::now();
-            let hard_deadline = start + Duration::from_millis(self.planned_hard_time_ms);
-            self.clock.reset(
-                start,
-                Some(start + Duration::from_millis(self.planned_soft_time_ms)),
-                Some(hard_deadline),
-            );
-
-            // Spawn timer thread to enforce hard deadline, storing handle for cleanup
-            let stop_timer = Arc::clone(&self.stop);
-            let handle = thread::spawn(move || {
-                let now = Instant::now();
-                if hard_deadline > now {
-                    thread::sleep(hard_deadline - now);
-                }
-                stop_timer.store(true, Ordering::Relaxed);
-            });
-            self.ponderhit_timer_handle = Some(handle);
+            let params = SearchParams {
+                soft_time_ms: self.planned_soft_time_ms,
+                hard_time_ms: self.planned_hard_time_ms,
+                ..SearchParams::default()
+            };
+            let (soft_deadline, hard_deadline) = EngineController::build_deadlines(&params, start);
+            self.clock.reset(start, soft_deadline, hard_deadline);
+            self.ponderhit_timer_handle =
+                EngineController::spawn_hard_stop_timer(hard_deadline, Arc::clone(&self.stop));
 
             self.pondering.store(false, Ordering::Relaxed);
         }
     }
 }
 
+/// End of a search thread: hold the result while pondering (unless
+/// stopped), then report it unless the search was aborted silently
+fn finish_search(
+    result: SearchResult,
+    pondering: &AtomicBool,
+    stop: &AtomicBool,
+    report: &CompletionSlot,
+) {
+    while pondering.load(Ordering::Relaxed) && !stop.load(Ordering::Relaxed) {
+        thread::sleep(Duration::from_millis(PONDER_POLL_MS));
+    }
+
+    let on_complete = report.lock().take();
+    if let Some(on_complete) = on_complete {
+        on_complete(result);
+    }
+}
+
 /// Search parameters for starting a new search
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SearchParams {
     /// Maximum depth to search (None = unlimited)
     pub depth: Option<u32>,
-    /// Soft time limit in milliseconds
+    /// Soft time limit in milliseconds (from `ponderhit` on when pondering)
     pub soft_time_ms: u64,
-    /// Hard time limit in milliseconds
+    /// Hard time limit in milliseconds (from `ponderhit` on when pondering)
     pub hard_time_ms: u64,
-    /// Whether to ponder (think on opponent's time)
+    /// Whether to ponder (think on opponent's time): search without a time
+    /// limit until `ponderhit` or `stop`
     pub ponder: bool,
     /// Whether to search infinitely
     pub infinite: bool,
     /// Number of principal variations to search (1 = normal, >1 = `MultiPV`)
     pub multi_pv: u32,
+    /// Only search these root moves (empty = all moves)
+    pub search_moves: Vec<Move>,
+    /// Never search these root moves
+    pub avoid_moves: Vec<Move>,
 }
 
 /// Engine controller managing search and game state
@@ -117,9 +175,16 @@ pub struct EngineController {
     info_callback: Option<SearchInfoCallback>,
     /// Number of search threads for SMP (1 = single-threaded)
     num_threads: usize,
+    /// Memory cap (MB) for all search tables of all threads
+    hash_mb: usize,
+    /// Ponder search aborted because the GUI set a different position;
+    /// the next `ponderhit` or `stop` searches the new position instead
+    desynced_ponder: Option<(SearchParams, CompletionCallback)>,
+    /// Decides the time limits of timed searches
+    time_manager: Arc<dyn TimeManager>,
 }
 
-/// Default NNUE file paths to try loading (used when embedded_nnue is disabled)
+/// Default NNUE file paths to try loading (used when `embedded_nnue` is disabled)
 #[cfg(not(feature = "embedded_nnue"))]
 const DEFAULT_NNUE_PATHS: &[&str] = &["trained_new_combined.nnue", "trained.nnue", "default.nnue"];
 
@@ -127,12 +192,19 @@ impl EngineController {
     /// Create a new engine controller
     #[must_use]
     pub fn new(tt_mb: usize) -> Self {
+        // Start from the smallest TT, then size it to what the other
+        // tables leave over
+        let mut search_state = SearchState::new(1);
+        search_state.resize_within(tt_mb, 1);
         let mut controller = EngineController {
             board: Board::new(),
-            search_state: Arc::new(Mutex::new(SearchState::new(tt_mb))),
+            search_state: Arc::new(Mutex::new(search_state)),
             current_job: None,
             info_callback: None,
             num_threads: 1,
+            hash_mb: tt_mb,
+            desynced_ponder: None,
+            time_manager: Arc::new(DefaultTimeManager),
         };
 
         // Try to auto-load a default NNUE file
@@ -143,37 +215,64 @@ impl EngineController {
 
     /// Try to load a default NNUE file from common paths or embedded
     fn try_load_default_nnue(&mut self) {
-        // First try embedded NNUE (if compiled in)
-        #[cfg(feature = "embedded_nnue")]
-        {
-            use crate::board::nnue::NnueNetwork;
-            let network = NnueNetwork::from_embedded();
-            let mut state = self.search_state.lock();
-            state.tables.nnue = Some(std::sync::Arc::new(network));
-            eprintln!("info string Using embedded NNUE");
-        }
-
-        // Fall back to loading from file
-        #[cfg(not(feature = "embedded_nnue"))]
-        for path in DEFAULT_NNUE_PATHS {
-            if std::path::Path::new(path).exists() {
-                if self.load_nnue(path).is_ok() {
-                    eprintln!("info string Loaded NNUE: {}", path);
-                    return;
-                }
+        if let Some((network, source)) = Self::default_nnue() {
+            self.search_state.lock().tables.nnue = Some(network);
+            if source == "embedded" {
+                eprintln!("info string Using embedded NNUE");
+            } else {
+                eprintln!("info string Loaded NNUE: {source}");
             }
         }
     }
 
+    /// The network a new engine starts with and where it came from: the
+    /// embedded network (if compiled in)
+    #[cfg(feature = "embedded_nnue")]
+    #[must_use]
+    pub fn default_nnue() -> Option<(Arc<NnueNetwork>, &'static str)> {
+        Some((NnueNetwork::shared_embedded(), "embedded"))
+    }
+
+    /// The network a new engine starts with and where it came from: the
+    /// first of the default files that loads
+    #[cfg(not(feature = "embedded_nnue"))]
+    #[must_use]
+    pub fn default_nnue() -> Option<(Arc<NnueNetwork>, &'static str)> {
+        DEFAULT_NNUE_PATHS
+            .iter()
+            .find_map(|&path| Some((Arc::new(NnueNetwork::load(path).ok()?), path)))
+    }
+
+    /// Evaluate with `network` (classical evaluation for `None`) from the
+    /// next search on. Search threads take the network from the shared
+    /// state when a search starts, so a running search is stopped first and
+    /// every thread of the next one uses the new network; accumulators are
+    /// built from it at the root. The tables, filled with the old network's
+    /// scores, are cleared.
+    pub fn set_nnue(&mut self, network: Option<Arc<NnueNetwork>>) {
+        self.stop_search();
+        let mut state = self.search_state.lock();
+        state.tables.nnue = network;
+        state.tables.clear();
+    }
+
     /// Load NNUE network from file
     pub fn load_nnue<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
         let mut state = self.search_state.lock();
         state.load_nnue(path)
     }
 
-    /// Set the number of search threads for SMP
+    /// Set the number of search threads for SMP.
+    ///
+    /// Each thread has its own history tables, so a new thread count
+    /// resizes (and clears) the transposition table to stay within the
+    /// hash size.
     pub fn set_threads(&mut self, num_threads: usize) {
-        self.num_threads = num_threads.max(1);
+        let num_threads = num_threads.max(1);
+        if num_threads != self.num_threads {
+            self.num_threads = num_threads;
+            self.resize_hash(self.hash_mb);
+        }
     }
 
     /// Get current thread count
@@ -182,6 +281,24 @@ impl EngineController {
         self.num_threads
     }
 
+    /// Replace the time allocation strategy (see [`TimeManager`])
+    pub fn set_time_manager(&mut self, manager: Arc<dyn TimeManager>) {
+        self.time_manager = manager;
+    }
+
+    /// Soft and hard limits in milliseconds for a search of the current
+    /// position under `control`, as the time manager allocates them
+    #[must_use]
+    pub fn allocate_time(
+        &self,
+        control: &TimeControl,
+        opponent_time_ms: Option<u64>,
+        config: &TimeConfig,
+    ) -> (u64, u64) {
+        self.time_manager
+            .allocate(&self.board, control, opponent_time_ms, config)
+    }
+
     /// Get a reference to the current board
     #[must_use]
     pub fn board(&self) -> &Board {
@@ -199,6 +316,33 @@ impl EngineController {
         self.board = board;
     }
 
+    /// Set the position sent by the GUI, guarding a running ponder search.
+    ///
+    /// A ponder search of the same position (same hash) keeps running. One
+    /// of a different position means the GUI moved on without `stop`: it is
+    /// aborted without reporting, and the next `ponderhit` or `stop`
+    /// searches the new position instead, so the GUI never receives a best
+    /// move for a position it is no longer in.
+    pub fn set_position(&mut self, board: Board) {
+        let ponder_hash = self
+            .current_job
+            .as_ref()
+            .filter(|job| {
+                job.pondering.load(Ordering::Relaxed) && !job.stop.load(Ordering::Relaxed)
+            })
+            .map(|job| job.root_hash);
+        match ponder_hash {
+            Some(hash) if hash == board.hash() => {}
+            Some(_) => {
+                if let Some(job) = self.current_job.take() {
+                    self.desynced_ponder = job.abort_silently();
+                }
+            }
+            None => self.stop_search(),
+        }
+        self.board = board;
+    }
+
     /// Get a reference to the search state
     #[must_use]
     pub fn search_state(&self) -> &Arc<Mutex<SearchState>> {
@@ -208,7 +352,9 @@ impl EngineController {
     /// Reset the board to starting position
     pub fn new_game(&mut self) {
         self.stop_search();
+        let variant = self.board.variant();
         self.board = Board::new();
+        self.board.set_variant(variant);
         let mut state = self.search_state.lock();
         state.new_search();
     }
@@ -224,6 +370,9 @@ impl EngineController {
     pub fn signal_stop(&mut self) {
         if let Some(job) = &self.current_job {
             job.signal_stop();
+        } else if let Some((params, report)) = self.desynced_ponder.take() {
+            // The GUI wants a move now: a shallow search of the real position
+            self.restart_desynced(params, report, true);
         }
     }
 
@@ -231,7 +380,31 @@ impl EngineController {
     pub fn ponderhit(&mut self) {
         if let Some(job) = &mut self.current_job {
             job.ponderhit();
+        } else if let Some((params, report)) = self.desynced_ponder.take() {
+            self.restart_desynced(params, report, false);
+        }
+    }
+
+    /// Search the current position in place of a ponder search aborted by
+    /// [`set_position`](Self::set_position), reporting through its callback
+    fn restart_desynced(
+        &mut self,
+        mut params: SearchParams,
+        report: CompletionCallback,
+        stopped: bool,
+    ) {
+        params.ponder = false;
+        // Root move restrictions were for the pondered position
+        params.search_moves.clear();
+        params.avoid_moves.clear();
+        if stopped {
+            params.depth = Some(1);
+            params.infinite = false;
+        } else if params.soft_time_ms == 0 && params.hard_time_ms == 0 && params.depth.is_none() {
+            params.soft_time_ms = DESYNC_RESTART_TIME_MS;
+            params.hard_time_ms = DESYNC_RESTART_TIME_MS;
         }
+        self.start_search(params, report);
     }
 
     /// Check if there's an active search
@@ -240,6 +413,31 @@ impl EngineController {
         self.current_job.is_some()
     }
 
+    /// What the search thread is doing: a finished search whose job has
+    /// not been collected yet counts as idle
+    #[must_use]
+    pub fn search_status(&self) -> SearchStatus {
+        match &self.current_job {
+            Some(job) if !job.handle.is_finished() => {
+                if job.pondering.load(Ordering::Relaxed) {
+                    SearchStatus::Pondering
+                } else {
+                    SearchStatus::Searching
+                }
+            }
+            _ => SearchStatus::Idle,
+        }
+    }
+
+    /// Like [`with_search_state_ref`](Self::with_search_state_ref), but
+    /// returns `None` instead of waiting while a search holds the state
+    pub fn try_with_search_state_ref<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&SearchState) -> R,
+    {
+        self.search_state.try_lock().map(|state| f(&state))
+    }
+
     fn build_deadlines(
         params: &SearchParams,
         start: Instant,
@@ -268,7 +466,14 @@ impl EngineController {
         (soft_deadline, hard_deadline)
     }
 
-    fn build_search_config(&self, params: &SearchParams, node_limit: u64) -> SearchConfig {
+    /// Search config for `params`; a ponder search follows `clock`, which
+    /// `ponderhit` gives the real time limits
+    fn build_search_config(
+        &self,
+        params: &SearchParams,
+        node_limit: u64,
+        clock: &Arc<SearchClock>,
+    ) -> SearchConfig {
         let mut config = if let Some(d) = params.depth {
             SearchConfig::depth(d)
         } else {
@@ -287,7 +492,12 @@ impl EngineController {
         if params.multi_pv > 1 {
             config = config.with_multi_pv(params.multi_pv);
         }
+        if params.ponder {
+            config = config.with_clock(Arc::clone(clock));
+        }
         config
+            .with_search_moves(params.search_moves.clone())
+            .with_avoid_moves(params.avoid_moves.clone())
     }
 
     fn spawn_hard_stop_timer(
@@ -313,12 +523,12 @@ impl EngineController {
     /// Start a search with the given parameters
     ///
     /// The `on_complete` callback is called when the search finishes with the result.
-    #[allow(clippy::needless_pass_by_value)] // Params is small and intentionally consumed
     pub fn start_search<F>(&mut self, params: SearchParams, on_complete: F)
     where
         F: FnOnce(SearchResult) + Send + 'static,
     {
         self.stop_search();
+        self.desynced_ponder = None;
 
         // Prepare search state
         let node_limit = {
@@ -335,6 +545,8 @@ impl EngineController {
 
         let clock = Arc::new(SearchClock::new(start, soft_deadline, hard_deadline));
         let pondering = Arc::new(AtomicBool::new(params.ponder));
+        let on_complete: CompletionSlot = Arc::new(Mutex::new(Some(Box::new(on_complete))));
+        let report = Arc::clone(&on_complete);
 
         // Spawn timer thread for hard deadline
         let timer_handle = if !params.infinite
@@ -358,6 +570,8 @@ impl EngineController {
         // Build config based on thread count
         if num_threads > 1 {
             // Use SMP search with multiple threads
+            let config = self.build_search_config(&params, node_limit, &clock);
+            let excluded_moves = config.excluded_root_moves(&mut self.board);
             let smp_config = SmpConfig {
                 num_threads,
                 max_depth: params.depth.unwrap_or(64),
@@ -368,24 +582,19 @@ impl EngineController {
                 },
                 node_limit,
                 info_callback,
+                excluded_moves,
+                clock: config.clock,
             };
 
             let handle = thread::Builder::new()
                 .name("search-main".to_string())
                 .stack_size(SEARCH_STACK_SIZE)
                 .spawn(move || {
+                    let _span = crate::log_span!("search", threads = num_threads);
                     let mut guard = search_state.lock();
                     let result =
                         smp_search(&search_board, &mut guard, smp_config, stop_clone.clone());
-
-                    // Wait while pondering (unless stopped)
-                    while pondering_clone.load(Ordering::Relaxed)
-                        && !stop_clone.load(Ordering::Relaxed)
-                    {
-                        thread::sleep(Duration::from_millis(PONDER_POLL_MS));
-                    }
-
-                    on_complete(result);
+                    finish_search(result, &pondering_clone, &stop_clone, &report);
                 })
                 .expect("failed to spawn search thread");
 
@@ -395,31 +604,27 @@ impl EngineController {
                 pondering,
                 planned_soft_time_ms: params.soft_time_ms,
                 planned_hard_time_ms: params.hard_time_ms,
+                root_hash: self.board.hash(),
+                params,
+                on_complete: Arc::clone(&on_complete),
                 handle,
                 timer_handle,
                 ponderhit_timer_handle: None,
             });
         } else {
             // Single-threaded search
-            let config = self.build_search_config(&params, node_limit);
+            let config = self.build_search_config(&params, node_limit, &clock);
             let mut search_board = search_board;
 
             let handle = thread::Builder::new()
                 .name("search".to_string())
                 .stack_size(SEARCH_STACK_SIZE)
                 .spawn(move || {
+                    let _span = crate::log_span!("search", threads = 1);
                     let mut guard = search_state.lock();
                     let result: SearchResult =
                         search(&mut search_board, &mut guard, config, &stop_clone);
-
-                    // Wait while pondering (unless stopped)
-                    while pondering_clone.load(Ordering::Relaxed)
-                        && !stop_clone.load(Ordering::Relaxed)
-                    {
-                        thread::sleep(Duration::from_millis(PONDER_POLL_MS));
-                    }
-
-                    on_complete(result);
+                    finish_search(result, &pondering_clone, &stop_clone, &report);
                 })
                 .expect("failed to spawn search thread");
 
@@ -429,6 +634,9 @@ impl EngineController {
                 pondering,
                 planned_soft_time_ms: params.soft_time_ms,
                 planned_hard_time_ms: params.hard_time_ms,
+                root_hash: self.board.hash(),
+                params,
+                on_complete: Arc::clone(&on_complete),
                 handle,
                 timer_handle,
                 ponderhit_timer_handle: None,
@@ -454,10 +662,31 @@ impl EngineController {
         Some(f(&self.search_state.lock()))
     }
 
-    /// Resize the transposition table
+    /// Set the hash size: the transposition table is resized so that all
+    /// search tables of all threads fit in `mb` megabytes
     pub fn resize_hash(&mut self, mb: usize) {
         self.stop_search();
-        self.with_search_state(|state| state.reset_tables(mb));
+        self.hash_mb = mb;
+        let threads = self.num_threads;
+        self.with_search_state(|state| state.resize_within(mb, threads));
+    }
+
+    /// Hash size (MB) the search tables are capped at
+    #[must_use]
+    pub fn hash_mb(&self) -> usize {
+        self.hash_mb
+    }
+
+    /// Memory held by the search tables
+    #[must_use]
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.search_state.lock().memory_usage()
+    }
+
+    /// Warm up the search (see [`SearchState::warmup`])
+    pub fn warmup(&mut self) {
+        self.stop_search();
+        self.with_search_state(SearchState::warmup);
     }
 
     /// Set trace/debug mode
@@ -0,0 +1,217 @@
+//! Training records for data generation.
+//!
+//! A [`TrainingRecord`] is what a data generation run writes for each
+//! played move: the position, the move played and its search score, plus
+//! the nodes the search took and the score of a shallow search of the same
+//! position. Positions where the deep and shallow scores disagree widely
+//! are tactically noisy, and [`TrainingRecord::is_noisy`] lets a later
+//! pass filter them out.
+//!
+//! Records are written one per line, scores in centipawns from the side to
+//! move's point of view:
+//!
+//! ```text
+//! <fen> | <move> | <score> | <low-depth score> | <nodes>
+//! ```
+
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+
+use crate::board::{search, Board, FenError, SearchConfig, SearchResult, SearchState};
+
+/// Separator between the fields of a record line
+const SEPARATOR: &str = " | ";
+
+/// Error type for record lines that cannot be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// The line does not have the five fields of a record
+    FieldCount { count: usize },
+    /// The position is not a valid FEN
+    InvalidFen(FenError),
+    /// The move is not legal in the position
+    InvalidMove { found: String },
+    /// A score or node count is not a number
+    InvalidNumber { field: &'static str, found: String },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::FieldCount { count } => {
+                write!(f, "expected 5 fields, found {count}")
+            }
+            RecordError::InvalidFen(error) => write!(f, "{error}"),
+            RecordError::InvalidMove { found } => write!(f, "illegal move '{found}'"),
+            RecordError::InvalidNumber { field, found } => {
+                write!(f, "invalid {field} '{found}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// One played move of a data generation game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainingRecord {
+    /// Position before the move
+    pub fen: String,
+    /// Move played, in UCI notation
+    pub best_move: String,
+    /// Score of the search that chose the move
+    pub score: i32,
+    /// Score of a shallow search of the same position
+    pub low_depth_score: i32,
+    /// Nodes the search that chose the move took
+    pub nodes: u64,
+}
+
+impl TrainingRecord {
+    /// Record the move `result` chose in `board`, searching the position
+    /// again to `low_depth` for the low-depth score.
+    ///
+    /// The shallow search runs on `shallow`, which should be a search state
+    /// of its own: with the tables of the main search it would read the
+    /// deep results back from the transposition table. Returns `None` when
+    /// `result` has no move.
+    pub fn capture(
+        board: &mut Board,
+        result: &SearchResult,
+        shallow: &mut SearchState,
+        low_depth: u32,
+    ) -> Option<Self> {
+        let best_move = result.best_move?;
+        let stop = AtomicBool::new(false);
+        let low = search(board, shallow, SearchConfig::depth(low_depth.max(1)), &stop);
+        Some(TrainingRecord {
+            fen: board.to_fen(),
+            best_move: best_move.to_string(),
+            score: result.score,
+            low_depth_score: low.score,
+            nodes: result.nodes,
+        })
+    }
+
+    /// Distance between the search score and the low-depth score
+    #[must_use]
+    pub fn score_gap(&self) -> u32 {
+        self.score.abs_diff(self.low_depth_score)
+    }
+
+    /// Whether the search and low-depth scores differ by more than
+    /// `max_gap` centipawns
+    #[must_use]
+    pub fn is_noisy(&self, max_gap: u32) -> bool {
+        self.score_gap() > max_gap
+    }
+
+    /// Read a record line written by the `Display` implementation
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RecordError`] naming the first field that is missing or
+    /// invalid.
+    pub fn parse(line: &str) -> Result<Self, RecordError> {
+        let fields: Vec<&str> = line.trim().split('|').map(str::trim).collect();
+        let &[fen, best_move, score, low_depth_score, nodes] = fields.as_slice() else {
+            return Err(RecordError::FieldCount {
+                count: fields.len(),
+            });
+        };
+        let mut board = Board::try_from_fen(fen).map_err(RecordError::InvalidFen)?;
+        if board.parse_move(best_move).is_err() {
+            return Err(RecordError::InvalidMove {
+                found: best_move.to_string(),
+            });
+        }
+        let number = |field: &'static str, found: &str| RecordError::InvalidNumber {
+            field,
+            found: found.to_string(),
+        };
+        Ok(TrainingRecord {
+            fen: board.to_fen(),
+            best_move: best_move.to_string(),
+            score: score.parse().map_err(|_| number("score", score))?,
+            low_depth_score: low_depth_score
+                .parse()
+                .map_err(|_| number("low-depth score", low_depth_score))?,
+            nodes: nodes.parse().map_err(|_| number("node count", nodes))?,
+        })
+    }
+}
+
+impl fmt::Display for TrainingRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [
+            self.fen.clone(),
+            self.best_move.clone(),
+            self.score.to_string(),
+            self.low_depth_score.to_string(),
+            self.nodes.to_string(),
+        ];
+        write!(f, "{}", fields.join(SEPARATOR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::find_best_move_with_ponder;
+
+    #[test]
+    fn test_capture_and_round_trip() {
+        let mut board =
+            Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let stop = AtomicBool::new(false);
+        let mut state = SearchState::new(1);
+        let result = find_best_move_with_ponder(&mut board, &mut state, 5, &stop);
+        let mut shallow = SearchState::new(1);
+        let record = TrainingRecord::capture(&mut board, &result, &mut shallow, 1).unwrap();
+
+        assert_eq!(record.fen, board.to_fen());
+        assert_eq!(record.best_move, result.best_move.unwrap().to_string());
+        assert_eq!((record.score, record.nodes), (result.score, result.nodes));
+        assert!(record.nodes > 0);
+
+        let line = record.to_string();
+        assert_eq!(line.split(SEPARATOR).count(), 5);
+        assert_eq!(TrainingRecord::parse(&line).unwrap(), record);
+    }
+
+    #[test]
+    fn test_noisy_records() {
+        // The deeper search found a loss the shallow one missed
+        let line = "4k3/8/8/3q4/8/4N3/8/4K3 w - - 0 1 | e3c4 | -850 | 40 | 1200";
+        let record = TrainingRecord::parse(line).unwrap();
+        assert_eq!(record.score_gap(), 890);
+        assert!(record.is_noisy(300));
+        assert!(!record.is_noisy(890));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_lines() {
+        let fen = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(
+            TrainingRecord::parse("e2e4 | 10").unwrap_err(),
+            RecordError::FieldCount { count: 2 }
+        );
+        assert!(matches!(
+            TrainingRecord::parse("4k3/8/8/8/8/8/8/4K3 w | e1e2 | 0 | 0 | 1"),
+            Err(RecordError::InvalidFen(_))
+        ));
+        assert_eq!(
+            TrainingRecord::parse(&format!("{fen} | e2e4 | 0 | 0 | 1")).unwrap_err(),
+            RecordError::InvalidMove {
+                found: "e2e4".to_string()
+            }
+        );
+        assert_eq!(
+            TrainingRecord::parse(&format!("{fen} | e1e2 | 0 | x | 1")).unwrap_err(),
+            RecordError::InvalidNumber {
+                field: "low-depth score",
+                found: "x".to_string()
+            }
+        );
+    }
+}
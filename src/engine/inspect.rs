@@ -0,0 +1,121 @@
+//! Engine state dump for the `state` debug command.
+//!
+//! Both front ends answer `state` with the same report, so a bug report
+//! about the GUI and the engine disagreeing on the game can quote what the
+//! engine actually holds.
+
+use crate::board::Board;
+
+/// What the engine's search thread is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStatus {
+    Idle,
+    Searching,
+    Pondering,
+    Analyzing,
+}
+
+impl SearchStatus {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchStatus::Idle => "idle",
+            SearchStatus::Searching => "searching",
+            SearchStatus::Pondering => "pondering",
+            SearchStatus::Analyzing => "analyzing",
+        }
+    }
+}
+
+/// Snapshot of the engine state, one `key value` pair per line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateReport {
+    pub fen: String,
+    pub hash: u64,
+    /// Positions recorded for repetition detection, repeats included
+    pub history_len: usize,
+    /// Transposition table fill in per mille (`None` while a search holds
+    /// the tables)
+    pub hashfull: Option<u32>,
+    pub search: SearchStatus,
+    /// `(name, value)` pairs of the protocol's options
+    pub options: Vec<(String, String)>,
+}
+
+impl StateReport {
+    /// Report on `board` with the search in state `search`
+    #[must_use]
+    pub fn new(board: &Board, search: SearchStatus) -> Self {
+        StateReport {
+            fen: board.to_fen(),
+            hash: board.hash(),
+            history_len: board.history_len(),
+            hashfull: None,
+            search,
+            options: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_hashfull(mut self, hashfull: Option<u32>) -> Self {
+        self.hashfull = hashfull;
+        self
+    }
+
+    #[must_use]
+    pub fn with_option(mut self, name: &str, value: impl std::fmt::Display) -> Self {
+        self.options.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// The report as lines, for the front end to prefix as its protocol
+    /// requires
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        let hashfull = self
+            .hashfull
+            .map_or_else(|| "busy".to_string(), |h| h.to_string());
+        let mut lines = vec![
+            format!("fen {}", self.fen),
+            format!("hash {:016x}", self.hash),
+            format!("history {}", self.history_len),
+            format!("hashfull {hashfull}"),
+            format!("search {}", self.search.name()),
+        ];
+        lines.extend(
+            self.options
+                .iter()
+                .map(|(name, value)| format!("option {name} {value}")),
+        );
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_report_lines() {
+        let mut board = Board::new();
+        board.make_move_uci("e2e4").unwrap();
+        let report = StateReport::new(&board, SearchStatus::Pondering)
+            .with_hashfull(Some(12))
+            .with_option("Hash", 16);
+
+        assert_eq!(
+            report.lines(),
+            [
+                format!("fen {}", board.to_fen()),
+                format!("hash {:016x}", board.hash()),
+                "history 2".to_string(),
+                "hashfull 12".to_string(),
+                "search pondering".to_string(),
+                "option Hash 16".to_string(),
+            ]
+        );
+        assert!(StateReport::new(&board, SearchStatus::Idle)
+            .lines()
+            .contains(&"hashfull busy".to_string()));
+    }
+}
@@ -4,10 +4,19 @@
 //! abstracting away the common logic of search management, pondering, and
 //! time control.
 
+pub mod adjudicate;
+pub mod checkpoint;
 mod controller;
+pub mod datagen;
+mod inspect;
+pub mod openings;
 mod protocol;
 pub mod time;
 
 pub use controller::{EngineController, SearchJob, SearchParams};
-pub use protocol::{CommandResult, Protocol, ProtocolType};
-pub use time::{build_search_request, compute_time_limits, TimeConfig, TimeControl};
+pub use inspect::{SearchStatus, StateReport};
+pub use protocol::ProtocolType;
+pub use time::{
+    build_search_request, compute_time_limits, search_request_from_limits, Clock, ClockControl,
+    DefaultTimeManager, TimeConfig, TimeControl, TimeManager,
+};
@@ -0,0 +1,295 @@
+//! Opening suites for self-play and matches.
+//!
+//! An [`OpeningBook`] is a list of start positions loaded from an EPD/FEN
+//! file (one position per line) or a PGN file (the position after each
+//! game's moves). An [`OpeningSampler`] deals them out in a seeded random
+//! order, each opening twice in a row with colours swapped so that neither
+//! player benefits from an unbalanced opening.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::board::{Board, FenError, SanError};
+use crate::pgn::{parse_games, PgnError, PgnGame};
+
+/// Error type for opening book parsing failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpeningError {
+    /// A position (or PGN `FEN` tag) is not a valid FEN
+    InvalidFen { line: usize, error: FenError },
+    /// A PGN move is not legal in its game
+    InvalidMove {
+        line: usize,
+        san: String,
+        error: SanError,
+    },
+    /// The book contains no positions
+    Empty,
+}
+
+impl fmt::Display for OpeningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpeningError::InvalidFen { line, error } => write!(f, "line {line}: {error}"),
+            OpeningError::InvalidMove { line, san, error } => {
+                write!(f, "line {line}: invalid move '{san}': {error}")
+            }
+            OpeningError::Empty => write!(f, "opening book has no positions"),
+        }
+    }
+}
+
+impl std::error::Error for OpeningError {}
+
+impl From<PgnError> for OpeningError {
+    fn from(e: PgnError) -> Self {
+        match e {
+            PgnError::InvalidFen { line, error } => OpeningError::InvalidFen { line, error },
+            PgnError::InvalidMove { line, san, error } => {
+                OpeningError::InvalidMove { line, san, error }
+            }
+        }
+    }
+}
+
+/// A list of opening start positions
+#[derive(Debug, Clone)]
+pub struct OpeningBook {
+    positions: Vec<Board>,
+}
+
+impl OpeningBook {
+    /// Build a book from positions
+    pub fn new(positions: Vec<Board>) -> Result<Self, OpeningError> {
+        if positions.is_empty() {
+            return Err(OpeningError::Empty);
+        }
+        Ok(OpeningBook { positions })
+    }
+
+    /// Parse EPD or FEN lines; operations after the position are ignored,
+    /// as are blank lines and `#` comments
+    pub fn from_epd(text: &str) -> Result<Self, OpeningError> {
+        let positions = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            .map(|(i, l)| {
+                let fen: Vec<&str> = l.split_whitespace().take(4).collect();
+                Board::try_from_fen(&fen.join(" "))
+                    .map_err(|error| OpeningError::InvalidFen { line: i + 1, error })
+            })
+            .collect::<Result<_, _>>()?;
+        Self::new(positions)
+    }
+
+    /// Parse PGN games (see [`parse_games`]), taking the position after
+    /// each game's moves
+    pub fn from_pgn(text: &str) -> Result<Self, OpeningError> {
+        let games = parse_games(text)?;
+        Self::new(games.iter().map(PgnGame::final_position).collect())
+    }
+
+    /// Load a book, reading `.pgn` files as PGN and anything else as EPD
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let is_pgn = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pgn"));
+        let book = if is_pgn {
+            Self::from_pgn(&text)
+        } else {
+            Self::from_epd(&text)
+        };
+        book.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Number of openings
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the book has no positions (never true for a parsed book)
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The opening at `index`
+    #[must_use]
+    pub fn position(&self, index: usize) -> &Board {
+        &self.positions[index]
+    }
+}
+
+/// One game's opening assignment
+#[derive(Debug, Clone)]
+pub struct OpeningDeal {
+    /// Index of the opening in the book
+    pub index: usize,
+    pub board: Board,
+    /// False for the first game of the pair, true for the colour-swapped
+    /// replay
+    pub swapped: bool,
+}
+
+/// Deals openings from a book in a deterministic shuffled order, each
+/// twice in a row with colours swapped
+pub struct OpeningSampler {
+    book: OpeningBook,
+    rng: StdRng,
+    order: Vec<usize>,
+    cursor: usize,
+    /// Opening waiting for its colour-swapped replay
+    pending: Option<usize>,
+}
+
+impl OpeningSampler {
+    /// Create a sampler; the same book and seed always deal the same games
+    #[must_use]
+    pub fn new(book: OpeningBook, seed: u64) -> Self {
+        let order = (0..book.len()).collect();
+        let mut sampler = OpeningSampler {
+            book,
+            rng: StdRng::seed_from_u64(seed),
+            order,
+            cursor: 0,
+            pending: None,
+        };
+        sampler.order.shuffle(&mut sampler.rng);
+        sampler
+    }
+
+    #[must_use]
+    pub fn book(&self) -> &OpeningBook {
+        &self.book
+    }
+
+    /// The next game's opening. The book is reshuffled after every pass.
+    pub fn next_game(&mut self) -> OpeningDeal {
+        if let Some(index) = self.pending.take() {
+            return self.deal(index, true);
+        }
+        if self.cursor == self.order.len() {
+            self.order.shuffle(&mut self.rng);
+            self.cursor = 0;
+        }
+        let index = self.order[self.cursor];
+        self.cursor += 1;
+        self.pending = Some(index);
+        self.deal(index, false)
+    }
+
+    /// The next pair of games: the same opening with colours swapped
+    pub fn next_pair(&mut self) -> [OpeningDeal; 2] {
+        if let Some(index) = self.pending.take() {
+            // Finish the pair in progress before starting a new one
+            let _ = self.deal(index, true);
+        }
+        [self.next_game(), self.next_game()]
+    }
+
+    fn deal(&self, index: usize, swapped: bool) -> OpeningDeal {
+        OpeningDeal {
+            index,
+            board: self.book.position(index).clone(),
+            swapped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPD: &str = "\
+# three openings
+rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - id \"e4\";
+rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1
+
+rnbqkbnr/pppppppp/8/8/2P5/8/PP1PPPPP/RNBQKBNR b KQkq -
+";
+
+    #[test]
+    fn test_epd_book() {
+        let book = OpeningBook::from_epd(EPD).unwrap();
+        assert_eq!(book.len(), 3);
+        assert!(!book.position(0).white_to_move());
+
+        assert_eq!(
+            OpeningBook::from_epd("# nothing\n").unwrap_err(),
+            OpeningError::Empty
+        );
+        assert!(matches!(
+            OpeningBook::from_epd("8/8/8/8/8/8/8/8 x - -\n"),
+            Err(OpeningError::InvalidFen { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_pgn_book() {
+        let pgn = r#"[Event "a"]
+[Site "?"]
+
+1. e4 {best by test} e5 2. Nf3 (2. f4 exf4) Nc6 $1 1-0
+
+[Event "b"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+1. e4 Kd7 *
+"#;
+        let book = OpeningBook::from_pgn(pgn).unwrap();
+        assert_eq!(book.len(), 2);
+
+        let mut expected = Board::new();
+        for mv in ["e2e4", "e7e5", "g1f3", "b8c6"] {
+            expected.make_move_uci(mv).unwrap();
+        }
+        assert_eq!(book.position(0).to_fen(), expected.to_fen());
+        assert_eq!(book.position(1).to_fen(), "8/3k4/8/8/4P3/8/8/4K3 w - - 1 1");
+
+        assert!(matches!(
+            OpeningBook::from_pgn("1. e4 e4 *"),
+            Err(OpeningError::InvalidMove { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_sampler_deals_pairs_deterministically() {
+        let book = OpeningBook::from_epd(EPD).unwrap();
+        let deal = |seed| {
+            let mut sampler = OpeningSampler::new(book.clone(), seed);
+            (0..12)
+                .map(|_| {
+                    let d = sampler.next_game();
+                    (d.index, d.swapped)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let games = deal(7);
+        assert_eq!(games, deal(7));
+        for pair in games.chunks(2) {
+            assert_eq!(pair[0].0, pair[1].0);
+            assert_eq!((pair[0].1, pair[1].1), (false, true));
+        }
+        // Every pass over the book deals each opening once
+        for pass in games.chunks(6) {
+            let mut indices: Vec<_> = pass.iter().step_by(2).map(|g| g.0).collect();
+            indices.sort_unstable();
+            assert_eq!(indices, [0, 1, 2]);
+        }
+
+        let mut sampler = OpeningSampler::new(book, 7);
+        let [first, second] = sampler.next_pair();
+        assert_eq!((first.index, first.swapped), games[0]);
+        assert_eq!((second.index, second.swapped), games[1]);
+    }
+}
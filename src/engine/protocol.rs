@@ -1,34 +1,7 @@
-//! Protocol trait for chess engine communication.
+//! Protocol detection.
 //!
-//! Defines a common interface for different chess protocols (UCI, `XBoard`).
-
-use crate::board::SearchResult;
-
-/// Result of processing a protocol command
-#[derive(Debug, Clone)]
-pub enum CommandResult {
-    /// Command processed successfully, with optional output
-    Ok(Option<String>),
-    /// Engine should quit
-    Quit,
-    /// Command not recognized
-    Unknown(String),
-}
-
-/// Trait for chess engine protocols (UCI, `XBoard`, etc.)
-pub trait Protocol {
-    /// Process a single command line and return the result
-    fn process_command(&mut self, line: &str) -> CommandResult;
-
-    /// Called when a search completes with results
-    fn on_search_complete(&mut self, result: SearchResult);
-
-    /// Get the protocol name (for logging/debugging)
-    fn name(&self) -> &'static str;
-
-    /// Run the protocol's main loop (blocking)
-    fn run(&mut self);
-}
+//! The engine speaks UCI and `XBoard`; the first command a GUI sends decides
+//! which front end (`uci::session` or `xboard`) handles the session.
 
 /// Protocol identification based on first command
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1,9 +1,14 @@
 //! Unified time management for UCI and `XBoard` protocols.
 //!
 //! This module provides a protocol-agnostic time control abstraction that both
-//! UCI and `XBoard` handlers can use to compute search time limits.
+//! UCI and `XBoard` handlers can use to compute search time limits. How the
+//! clock is split between moves is decided by a [`TimeManager`]; the
+//! engine's own strategy is [`DefaultTimeManager`].
 
-use std::time::Duration;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, Color, WdlProbabilities};
 
 /// Default moves to go estimate when not specified
 pub const DEFAULT_MOVES_TO_GO: u64 = 30;
@@ -35,6 +40,20 @@ const LONG_MOVES_ESTIMATE: u64 = 40;
 const MEDIUM_MOVES_ESTIMATE: u64 = 30;
 const SHORT_MOVES_ESTIMATE: u64 = 25;
 
+/// Soft time scale (percent) for a position whose outcome is already
+/// decided, and for one that could go either way
+const DECIDED_TIME_PERCENT: f64 = 50.0;
+const BALANCED_TIME_PERCENT: f64 = 110.0;
+
+/// Largest soft time bonus (percent) for being ahead on the clock, reached
+/// once we have twice the opponent's time
+const CLOCK_LEAD_MAX_BONUS_PERCENT: u64 = 20;
+
+/// Below this much time on both clocks the game is a scramble and the soft
+/// limit is trimmed to `BOTH_LOW_TIME_PERCENT`
+const BOTH_LOW_TIME_MS: u64 = 2 * PANIC_THRESHOLD_MS;
+const BOTH_LOW_TIME_PERCENT: u64 = 80;
+
 /// Configuration for time management calculations.
 ///
 /// Groups together the various percentages and overheads used in time limit calculations.
@@ -48,6 +67,12 @@ pub struct TimeConfig {
     pub hard_time_percent: u64,
     /// Default maximum nodes (0 = unlimited)
     pub default_max_nodes: u64,
+    /// Nodes searched per millisecond of allotted time (0 = use the clock).
+    ///
+    /// When set, time limits become node limits, so the search stops
+    /// without reading the clock: for slow or clockless devices and for
+    /// reproducible timed games.
+    pub nodes_time: u64,
 }
 
 impl Default for TimeConfig {
@@ -57,6 +82,7 @@ impl Default for TimeConfig {
             soft_time_percent: 70,
             hard_time_percent: 90,
             default_max_nodes: 0,
+            nodes_time: 0,
         }
     }
 }
@@ -160,6 +186,66 @@ impl TimeControl {
             } => compute_incremental_limits(*time_left_ms, *inc_ms, *movestogo, config),
         }
     }
+
+    /// Compute time limits, biased by the opponent's remaining time when known.
+    ///
+    /// Only incremental controls are affected; see [`scale_limits_by_clock_odds`].
+    #[must_use]
+    pub fn compute_limits_against(
+        &self,
+        config: &TimeConfig,
+        opponent_time_ms: Option<u64>,
+    ) -> (u64, u64) {
+        let limits = self.compute_limits(config);
+        match (self, opponent_time_ms) {
+            (TimeControl::Incremental { time_left_ms, .. }, Some(opponent_ms)) => {
+                scale_limits_by_clock_odds(limits, *time_left_ms, opponent_ms)
+            }
+            _ => limits,
+        }
+    }
+}
+
+/// Time allocation strategy: how much of the clock a search may use.
+///
+/// An [`EngineController`](crate::engine::EngineController) asks its manager
+/// for the limits of every timed search, so a custom strategy (a learned
+/// model, a schedule tuned for one time control, ...) can replace the
+/// default one with `set_time_manager`. Searches with `infinite` are
+/// unlimited and never reach the manager; a `ponder` search is allocated
+/// its limits up front and keeps them for `ponderhit`. Scaling the soft
+/// limit during the search by score and best-move stability still applies.
+pub trait TimeManager: Send + Sync {
+    /// Soft and hard limits in milliseconds for searching `board` under
+    /// `control`, with the opponent's remaining time when known.
+    ///
+    /// `(u64::MAX, u64::MAX)` means no limit, which is what
+    /// [`TimeControl::Infinite`] and [`TimeControl::Depth`] should get.
+    fn allocate(
+        &self,
+        board: &Board,
+        control: &TimeControl,
+        opponent_time_ms: Option<u64>,
+        config: &TimeConfig,
+    ) -> (u64, u64);
+}
+
+/// The engine's own time allocation: an even share of the remaining time
+/// plus the increment, capped by the configured percentages and biased by
+/// the opponent's clock (see [`TimeControl::compute_limits_against`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultTimeManager;
+
+impl TimeManager for DefaultTimeManager {
+    fn allocate(
+        &self,
+        _board: &Board,
+        control: &TimeControl,
+        opponent_time_ms: Option<u64>,
+        config: &TimeConfig,
+    ) -> (u64, u64) {
+        control.compute_limits_against(config, opponent_time_ms)
+    }
 }
 
 /// Compute soft and hard time limits for incremental time control.
@@ -223,6 +309,54 @@ fn compute_incremental_limits(
     (soft_ms, hard_ms)
 }
 
+/// Scale a soft time limit by how open the game still is.
+///
+/// `score` is the search score (cp, side to move). The WDL model turns it
+/// into an expected result `e`, and `4e(1 - e)` measures how undecided the
+/// game is: 1 when balanced, near 0 when clearly won or lost. The limit is
+/// interpolated between `DECIDED_TIME_PERCENT` and `BALANCED_TIME_PERCENT`
+/// by that measure, so time saved in decided positions is spent where the
+/// result still hangs in the balance.
+#[must_use]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn scale_soft_time_by_wdl(soft_ms: u64, score: i32) -> u64 {
+    let expected = WdlProbabilities::from_score(score).expected_score();
+    let openness = 4.0 * expected * (1.0 - expected);
+    let percent = DECIDED_TIME_PERCENT + (BALANCED_TIME_PERCENT - DECIDED_TIME_PERCENT) * openness;
+    ((soft_ms as f64 * percent / 100.0) as u64).max(1)
+}
+
+/// Bias `(soft_ms, hard_ms)` by the two clocks.
+///
+/// With more time than the opponent the soft limit grows by up to
+/// `CLOCK_LEAD_MAX_BONUS_PERCENT`, scaled by the size of the lead, so the
+/// spare time is turned into search instead of being left on the clock.
+/// When both sides are under `BOTH_LOW_TIME_MS` the soft limit shrinks to
+/// `BOTH_LOW_TIME_PERCENT`, as deep thinks rarely pay off in a scramble.
+/// The hard limit is never changed and the soft limit never exceeds it.
+#[must_use]
+pub fn scale_limits_by_clock_odds(
+    (soft_ms, hard_ms): (u64, u64),
+    own_ms: u64,
+    opponent_ms: u64,
+) -> (u64, u64) {
+    let percent = if own_ms < BOTH_LOW_TIME_MS && opponent_ms < BOTH_LOW_TIME_MS {
+        BOTH_LOW_TIME_PERCENT
+    } else if own_ms > opponent_ms {
+        let lead = (own_ms - opponent_ms).min(opponent_ms.max(1));
+        100 + CLOCK_LEAD_MAX_BONUS_PERCENT * lead / opponent_ms.max(1)
+    } else {
+        100
+    };
+
+    let soft = (soft_ms.saturating_mul(percent) / 100).clamp(1, hard_ms.max(1));
+    (soft, hard_ms)
+}
+
 /// Compute soft and hard time limits for a search (legacy API for UCI compatibility).
 ///
 /// Returns `(soft_time_ms, hard_time_ms)` where:
@@ -261,24 +395,67 @@ pub struct SearchRequest {
 #[must_use]
 pub fn build_search_request(
     time_control: TimeControl,
+    opponent_time_ms: Option<u64>,
+    depth: Option<u32>,
+    nodes: Option<u64>,
+    ponder: bool,
+    infinite: bool,
+    config: &TimeConfig,
+) -> (SearchRequest, (u64, u64)) {
+    let limits = if infinite {
+        (u64::MAX, u64::MAX)
+    } else {
+        time_control.compute_limits_against(config, opponent_time_ms)
+    };
+    search_request_from_limits(limits, depth, nodes, ponder, infinite, config)
+}
+
+/// Build a search request from the `(soft_ms, hard_ms)` limits a
+/// [`TimeManager`] allocated; they are ignored for `infinite` searches.
+///
+/// A `ponder` request carries the limits to apply from `ponderhit` on
+/// (0 for none), and always on the clock: `NodesTime` would cut the
+/// pondering short.
+#[must_use]
+pub fn search_request_from_limits(
+    limits: (u64, u64),
     depth: Option<u32>,
     nodes: Option<u64>,
     ponder: bool,
     infinite: bool,
     config: &TimeConfig,
 ) -> (SearchRequest, (u64, u64)) {
-    let (soft_ms, hard_ms) = if infinite || ponder {
+    let (soft_ms, hard_ms) = if infinite {
         (u64::MAX, u64::MAX)
     } else {
-        time_control.compute_limits(config)
+        limits
     };
 
-    let max_nodes = nodes.unwrap_or(config.default_max_nodes);
+    let mut max_nodes = nodes.unwrap_or(config.default_max_nodes);
+    let (mut request_soft_ms, mut request_hard_ms) = if infinite {
+        (0, 0)
+    } else if ponder {
+        let limited = |ms| if ms == u64::MAX { 0 } else { ms };
+        (limited(soft_ms), limited(hard_ms))
+    } else {
+        (soft_ms, hard_ms)
+    };
+
+    // Spend the soft limit's worth of nodes instead of watching the clock
+    if config.nodes_time > 0 && !ponder && request_soft_ms > 0 && soft_ms != u64::MAX {
+        let budget = soft_ms.saturating_mul(config.nodes_time);
+        max_nodes = if max_nodes > 0 {
+            max_nodes.min(budget)
+        } else {
+            budget
+        };
+        (request_soft_ms, request_hard_ms) = (0, 0);
+    }
 
     (
         SearchRequest {
-            soft_time_ms: if infinite || ponder { 0 } else { soft_ms },
-            hard_time_ms: if infinite || ponder { 0 } else { hard_ms },
+            soft_time_ms: request_soft_ms,
+            hard_time_ms: request_hard_ms,
             max_nodes,
             depth,
             ponder,
@@ -288,6 +465,264 @@ pub fn build_search_request(
     )
 }
 
+/// One side's limits in a game: a time control and optionally a node
+/// budget per move.
+///
+/// Giving the two sides different controls plays asymmetric matches, e.g.
+/// 10+0.1 against 60+0.6, or node-odds matches where each side searches a
+/// fixed number of nodes per move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockControl {
+    /// Starting time, added again at the start of every session
+    pub base: Duration,
+    /// Time added after each move
+    pub increment: Duration,
+    /// Moves per session for repeating controls (e.g. 40 moves in 90 minutes)
+    pub moves_per_session: Option<u32>,
+    /// Node limit for each search, for node-odds matches
+    pub nodes: Option<u64>,
+}
+
+impl ClockControl {
+    /// A `base` plus `increment` control without a node limit
+    #[must_use]
+    pub fn new(base: Duration, increment: Duration) -> Self {
+        ClockControl {
+            base,
+            increment,
+            moves_per_session: None,
+            nodes: None,
+        }
+    }
+
+    /// Add `base` again every `moves` moves
+    #[must_use]
+    pub fn with_moves_per_session(mut self, moves: u32) -> Self {
+        self.moves_per_session = Some(moves).filter(|&n| n > 0);
+        self
+    }
+
+    /// Limit every search to `nodes` nodes
+    #[must_use]
+    pub fn with_nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    fn base_ms(&self) -> u64 {
+        self.base.as_millis() as u64
+    }
+
+    fn increment_ms(&self) -> u64 {
+        self.increment.as_millis() as u64
+    }
+}
+
+/// A two-player chess clock.
+///
+/// Models each side's remaining time under an increment and/or repeating
+/// `moves_per_session` control (e.g. 40 moves in 90 minutes), for match
+/// runners and GUIs built on the library. Each side can have its own
+/// [`ClockControl`]. The clock can be driven by wall time ([`Clock::start`]
+/// / [`Clock::press`]) or, for simulations and tests, by reporting each
+/// move's thinking time to [`Clock::record_move`].
+#[derive(Debug, Clone)]
+pub struct Clock {
+    /// Controls indexed by color
+    controls: [ClockControl; 2],
+    remaining_ms: [u64; 2],
+    moves_made: [u32; 2],
+    side_to_move: Color,
+    flagged: Option<Color>,
+    running_since: Option<Instant>,
+}
+
+impl Clock {
+    /// Create a clock giving both sides `base` plus `increment` per move,
+    /// with `base` added again every `moves_per_session` moves if given.
+    /// White moves first.
+    #[must_use]
+    pub fn new(base: Duration, increment: Duration, moves_per_session: Option<u32>) -> Self {
+        let mut control = ClockControl::new(base, increment);
+        if let Some(moves) = moves_per_session {
+            control = control.with_moves_per_session(moves);
+        }
+        Self::with_controls(control, control)
+    }
+
+    /// Create a clock with separate controls for white and black. White
+    /// moves first.
+    #[must_use]
+    pub fn with_controls(white: ClockControl, black: ClockControl) -> Self {
+        Clock {
+            controls: [white, black],
+            remaining_ms: [white.base_ms(), black.base_ms()],
+            moves_made: [0; 2],
+            side_to_move: Color::White,
+            flagged: None,
+            running_since: None,
+        }
+    }
+
+    /// The control `color` plays under
+    #[must_use]
+    pub fn control(&self, color: Color) -> &ClockControl {
+        &self.controls[color.index()]
+    }
+
+    /// Side whose clock is running (or would run once started)
+    #[must_use]
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// Set the side to move, e.g. when a game starts from a FEN with black
+    /// to move. Stops the clock.
+    pub fn set_side_to_move(&mut self, color: Color) {
+        self.stop();
+        self.side_to_move = color;
+    }
+
+    /// Start the side to move's clock against wall time
+    pub fn start(&mut self) {
+        if self.running_since.is_none() && self.flagged.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+    }
+
+    /// Stop the running clock, charging the elapsed time to the side to move
+    pub fn stop(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.charge(since.elapsed());
+        }
+    }
+
+    /// Whether a wall-time clock is running
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// End the side to move's turn using wall time and start the opponent's
+    /// clock. Returns the flagged side, if any.
+    pub fn press(&mut self) -> Option<Color> {
+        let elapsed = self
+            .running_since
+            .take()
+            .map_or(Duration::ZERO, |since| since.elapsed());
+        let flagged = self.record_move(elapsed);
+        if flagged.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+        flagged
+    }
+
+    /// Record a move by the side to move that took `elapsed`, then pass the
+    /// turn. Returns the flagged side, if any; a flagged clock no longer
+    /// changes.
+    pub fn record_move(&mut self, elapsed: Duration) -> Option<Color> {
+        if self.flagged.is_some() {
+            return self.flagged;
+        }
+        self.charge(elapsed);
+        if self.flagged.is_some() {
+            return self.flagged;
+        }
+
+        let side = self.side_to_move.index();
+        let control = self.controls[side];
+        self.moves_made[side] += 1;
+        self.remaining_ms[side] += control.increment_ms();
+        if let Some(n) = control.moves_per_session {
+            if self.moves_made[side].is_multiple_of(n) {
+                self.remaining_ms[side] += control.base_ms();
+            }
+        }
+        self.side_to_move = self.side_to_move.opponent();
+        None
+    }
+
+    /// Deduct `elapsed` from the side to move, flagging it at zero
+    fn charge(&mut self, elapsed: Duration) {
+        let side = self.side_to_move.index();
+        let elapsed_ms = elapsed.as_millis() as u64;
+        if elapsed_ms >= self.remaining_ms[side] {
+            self.remaining_ms[side] = 0;
+            self.flagged = Some(self.side_to_move);
+        } else {
+            self.remaining_ms[side] -= elapsed_ms;
+        }
+    }
+
+    /// Time left for `color`, including the running turn's elapsed time
+    #[must_use]
+    pub fn remaining(&self, color: Color) -> Duration {
+        let mut ms = self.remaining_ms[color.index()];
+        if color == self.side_to_move {
+            if let Some(since) = self.running_since {
+                ms = ms.saturating_sub(since.elapsed().as_millis() as u64);
+            }
+        }
+        Duration::from_millis(ms)
+    }
+
+    /// Moves `color` must make before the next time control, if the control
+    /// repeats
+    #[must_use]
+    pub fn moves_to_go(&self, color: Color) -> Option<u64> {
+        self.controls[color.index()]
+            .moves_per_session
+            .map(|n| u64::from(n - self.moves_made[color.index()] % n))
+    }
+
+    /// The side that ran out of time, if any. A running clock that has
+    /// reached zero counts as flagged even before it is pressed.
+    #[must_use]
+    pub fn flagged(&self) -> Option<Color> {
+        self.flagged.or_else(|| {
+            (self.running_since.is_some() && self.remaining(self.side_to_move).is_zero())
+                .then_some(self.side_to_move)
+        })
+    }
+
+    /// The time control `color` is under, for computing search limits
+    #[must_use]
+    pub fn time_control(&self, color: Color) -> TimeControl {
+        TimeControl::Incremental {
+            time_left_ms: self.remaining(color).as_millis() as u64,
+            inc_ms: self.controls[color.index()].increment_ms(),
+            movestogo: self.moves_to_go(color),
+        }
+    }
+
+    /// Node limit for `color`'s searches, if it plays under node odds
+    #[must_use]
+    pub fn node_limit(&self, color: Color) -> Option<u64> {
+        self.controls[color.index()].nodes
+    }
+
+    /// Clock arguments for the side to move's UCI `go` command, e.g.
+    /// `wtime 59000 btime 60000 winc 1000 binc 1000`, followed by
+    /// `movestogo` and `nodes` when its control has them
+    #[must_use]
+    pub fn uci_go_args(&self) -> String {
+        let mut args = format!(
+            "wtime {} btime {} winc {} binc {}",
+            self.remaining(Color::White).as_millis(),
+            self.remaining(Color::Black).as_millis(),
+            self.controls[Color::White.index()].increment.as_millis(),
+            self.controls[Color::Black.index()].increment.as_millis()
+        );
+        if let Some(moves) = self.moves_to_go(self.side_to_move) {
+            let _ = write!(args, " movestogo {moves}");
+        }
+        if let Some(nodes) = self.node_limit(self.side_to_move) {
+            let _ = write!(args, " nodes {nodes}");
+        }
+        args
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,6 +735,7 @@ mod tests {
             soft_time_percent: 5,
             hard_time_percent: 20,
             default_max_nodes: 0,
+            nodes_time: 0,
         }
     }
 
@@ -341,8 +777,7 @@ mod tests {
 
     #[test]
     fn time_control_incremental_from_duration() {
-        let tc =
-            TimeControl::incremental(Duration::from_secs(300), Duration::from_secs(3), Some(40));
+        let tc = TimeControl::incremental(Duration::from_mins(5), Duration::from_secs(3), Some(40));
 
         match tc {
             TimeControl::Incremental {
@@ -534,6 +969,65 @@ mod tests {
         assert!(hard > soft);
     }
 
+    // ========================================================================
+    // Clock odds tests
+    // ========================================================================
+
+    #[test]
+    fn clock_odds_lead_extends_soft_limit() {
+        // Even clocks leave the limits alone
+        assert_eq!(
+            scale_limits_by_clock_odds((1000, 5000), 60_000, 60_000),
+            (1000, 5000)
+        );
+        // Half again the opponent's time earns half the bonus
+        assert_eq!(
+            scale_limits_by_clock_odds((1000, 5000), 90_000, 60_000),
+            (1100, 5000)
+        );
+        // The bonus is capped at twice the opponent's time
+        assert_eq!(
+            scale_limits_by_clock_odds((1000, 5000), 600_000, 60_000),
+            (1200, 5000)
+        );
+        // Never past the hard limit
+        assert_eq!(
+            scale_limits_by_clock_odds((1000, 1100), 600_000, 60_000),
+            (1100, 1100)
+        );
+    }
+
+    #[test]
+    fn clock_odds_trims_when_both_low() {
+        assert_eq!(
+            scale_limits_by_clock_odds((1000, 3000), 8000, 9000),
+            (800, 3000)
+        );
+        // Behind on the clock with time to spare: unchanged
+        assert_eq!(
+            scale_limits_by_clock_odds((1000, 3000), 30_000, 60_000),
+            (1000, 3000)
+        );
+    }
+
+    #[test]
+    fn compute_limits_against_only_biases_incremental() {
+        let config = test_config();
+        let tc = TimeControl::move_time_ms(1000);
+        assert_eq!(tc.compute_limits_against(&config, Some(1)), (1000, 1000));
+
+        let tc = TimeControl::Incremental {
+            time_left_ms: 120_000,
+            inc_ms: 0,
+            movestogo: None,
+        };
+        let (soft, hard) = tc.compute_limits(&config);
+        assert_eq!(tc.compute_limits_against(&config, None), (soft, hard));
+        let (ahead, ahead_hard) = tc.compute_limits_against(&config, Some(30_000));
+        assert!(ahead > soft);
+        assert_eq!(ahead_hard, hard);
+    }
+
     // ========================================================================
     // compute_time_limits legacy API tests
     // ========================================================================
@@ -541,7 +1035,7 @@ mod tests {
     #[test]
     fn compute_time_limits_with_movetime() {
         let (soft, hard) = compute_time_limits(
-            Duration::from_secs(300),
+            Duration::from_mins(5),
             Duration::from_secs(0),
             Some(Duration::from_secs(5)), // movetime takes priority
             None,
@@ -555,7 +1049,7 @@ mod tests {
     #[test]
     fn compute_time_limits_without_movetime() {
         let (soft, hard) = compute_time_limits(
-            Duration::from_secs(300),
+            Duration::from_mins(5),
             Duration::from_secs(3),
             None,
             None,
@@ -569,7 +1063,7 @@ mod tests {
     #[test]
     fn compute_time_limits_with_movestogo() {
         let (soft, _hard) = compute_time_limits(
-            Duration::from_secs(60),
+            Duration::from_mins(1),
             Duration::from_secs(0),
             None,
             Some(10), // 10 moves to go
@@ -593,7 +1087,7 @@ mod tests {
             movestogo: None,
         };
 
-        let (req, _) = build_search_request(tc, None, None, false, true, &test_config());
+        let (req, _) = build_search_request(tc, None, None, None, false, true, &test_config());
 
         assert!(req.infinite);
         assert_eq!(req.soft_time_ms, 0);
@@ -608,17 +1102,19 @@ mod tests {
             movestogo: None,
         };
 
-        let (req, _) = build_search_request(tc, None, None, true, false, &test_config());
+        let (req, limits) = build_search_request(tc, None, None, None, true, false, &test_config());
 
+        // The limits are kept for ponderhit
         assert!(req.ponder);
-        assert_eq!(req.soft_time_ms, 0);
-        assert_eq!(req.hard_time_ms, 0);
+        assert_eq!(limits, tc.compute_limits(&test_config()));
+        assert_eq!((req.soft_time_ms, req.hard_time_ms), limits);
+        assert!(req.soft_time_ms > 0 && req.hard_time_ms >= req.soft_time_ms);
     }
 
     #[test]
     fn build_search_request_with_depth() {
         let tc = TimeControl::Infinite;
-        let (req, _) = build_search_request(tc, Some(10), None, false, false, &test_config());
+        let (req, _) = build_search_request(tc, None, Some(10), None, false, false, &test_config());
 
         assert_eq!(req.depth, Some(10));
     }
@@ -626,7 +1122,8 @@ mod tests {
     #[test]
     fn build_search_request_with_nodes() {
         let tc = TimeControl::Infinite;
-        let (req, _) = build_search_request(tc, None, Some(1000000), false, false, &test_config());
+        let (req, _) =
+            build_search_request(tc, None, None, Some(1000000), false, false, &test_config());
 
         assert_eq!(req.max_nodes, 1000000);
     }
@@ -638,7 +1135,7 @@ mod tests {
             default_max_nodes: 500000,
             ..test_config()
         };
-        let (req, _) = build_search_request(tc, None, None, false, false, &config);
+        let (req, _) = build_search_request(tc, None, None, None, false, false, &config);
 
         assert_eq!(req.max_nodes, 500000);
     }
@@ -652,7 +1149,7 @@ mod tests {
         };
 
         let (req, (soft, hard)) =
-            build_search_request(tc, None, None, false, false, &test_config());
+            build_search_request(tc, None, None, None, false, false, &test_config());
 
         assert!(!req.infinite);
         assert!(!req.ponder);
@@ -662,6 +1159,85 @@ mod tests {
         assert_eq!(req.hard_time_ms, hard);
     }
 
+    #[test]
+    fn build_search_request_nodes_time() {
+        let tc = TimeControl::move_time_ms(200);
+        let config = TimeConfig {
+            nodes_time: 50,
+            ..test_config()
+        };
+        let (req, (soft, _)) = build_search_request(tc, None, None, None, false, false, &config);
+        assert_eq!((req.soft_time_ms, req.hard_time_ms), (0, 0));
+        assert_eq!(req.max_nodes, soft * 50);
+
+        // An explicit node limit below the budget still applies
+        let (req, _) = build_search_request(tc, None, None, Some(1000), false, false, &config);
+        assert_eq!(req.max_nodes, 1000);
+
+        // Untimed searches stay unlimited
+        let (req, _) = build_search_request(
+            TimeControl::Infinite,
+            None,
+            None,
+            None,
+            false,
+            true,
+            &config,
+        );
+        assert_eq!(req.max_nodes, 0);
+    }
+
+    #[test]
+    fn default_time_manager_matches_the_time_control() {
+        let tc = TimeControl::Incremental {
+            time_left_ms: 60000,
+            inc_ms: 1000,
+            movestogo: None,
+        };
+        let config = test_config();
+        let limits = DefaultTimeManager.allocate(&Board::new(), &tc, Some(30000), &config);
+        assert_eq!(limits, tc.compute_limits_against(&config, Some(30000)));
+        assert_eq!(
+            DefaultTimeManager.allocate(&Board::new(), &TimeControl::Depth, None, &config),
+            (u64::MAX, u64::MAX)
+        );
+
+        // Allocated limits become the request's, also when pondering
+        let (req, _) = search_request_from_limits((40, 90), None, None, false, false, &config);
+        assert_eq!((req.soft_time_ms, req.hard_time_ms), (40, 90));
+        let (req, limits) = search_request_from_limits((40, 90), None, None, true, false, &config);
+        assert_eq!((req.soft_time_ms, req.hard_time_ms), (40, 90));
+        assert_eq!(limits, (40, 90));
+        let unlimited = (u64::MAX, u64::MAX);
+        let (req, _) = search_request_from_limits(unlimited, None, None, true, false, &config);
+        assert_eq!((req.soft_time_ms, req.hard_time_ms), (0, 0));
+
+        // A ponder search stays on the clock with NodesTime
+        let config = TimeConfig {
+            nodes_time: 100,
+            ..config
+        };
+        let (req, _) = search_request_from_limits((40, 90), None, None, true, false, &config);
+        assert_eq!((req.soft_time_ms, req.hard_time_ms), (40, 90));
+        assert_eq!(req.max_nodes, config.default_max_nodes);
+    }
+
+    // ========================================================================
+    // WDL time scaling tests
+    // ========================================================================
+
+    #[test]
+    fn wdl_scaling_favours_balanced_positions() {
+        let balanced = scale_soft_time_by_wdl(1000, 0);
+        let better = scale_soft_time_by_wdl(1000, 150);
+        let won = scale_soft_time_by_wdl(1000, 800);
+        assert!(balanced > 1000, "balanced: {balanced}");
+        assert!(balanced > better && better > won);
+        assert_eq!(won, scale_soft_time_by_wdl(1000, -800));
+        assert_eq!(scale_soft_time_by_wdl(1000, 29_000), 500);
+        assert_eq!(scale_soft_time_by_wdl(1, 29_000), 1);
+    }
+
     // ========================================================================
     // Edge case tests
     // ========================================================================
@@ -738,4 +1314,114 @@ mod tests {
         assert!(!req.ponder);
         assert!(!req.infinite);
     }
+
+    // ========================================================================
+    // Clock tests
+    // ========================================================================
+
+    #[test]
+    fn clock_increment_and_turns() {
+        let mut clock = Clock::new(Duration::from_mins(1), Duration::from_secs(1), None);
+        assert_eq!(clock.side_to_move(), Color::White);
+
+        assert_eq!(clock.record_move(Duration::from_secs(5)), None);
+        assert_eq!(clock.remaining(Color::White), Duration::from_secs(56));
+        assert_eq!(clock.side_to_move(), Color::Black);
+
+        assert_eq!(clock.record_move(Duration::from_millis(500)), None);
+        assert_eq!(clock.remaining(Color::Black), Duration::from_millis(60_500));
+        assert_eq!(clock.moves_to_go(Color::White), None);
+        assert_eq!(
+            clock.uci_go_args(),
+            "wtime 56000 btime 60500 winc 1000 binc 1000"
+        );
+    }
+
+    #[test]
+    fn clock_repeating_control_adds_base_time() {
+        let mut clock = Clock::new(Duration::from_secs(10), Duration::ZERO, Some(2));
+        assert_eq!(clock.moves_to_go(Color::White), Some(2));
+
+        clock.record_move(Duration::from_secs(4));
+        clock.record_move(Duration::from_secs(1));
+        assert_eq!(clock.moves_to_go(Color::White), Some(1));
+        assert_eq!(
+            clock.time_control(Color::White),
+            TimeControl::Incremental {
+                time_left_ms: 6000,
+                inc_ms: 0,
+                movestogo: Some(1),
+            }
+        );
+
+        // The second move completes the control and adds a new session
+        clock.record_move(Duration::from_secs(4));
+        assert_eq!(clock.remaining(Color::White), Duration::from_secs(12));
+        assert_eq!(clock.moves_to_go(Color::White), Some(2));
+    }
+
+    #[test]
+    fn clock_flag_detection() {
+        let mut clock = Clock::new(Duration::from_secs(1), Duration::from_secs(2), None);
+        clock.record_move(Duration::from_millis(900));
+        assert_eq!(clock.flagged(), None);
+
+        // The increment is not credited to a move that overstepped the time
+        assert_eq!(
+            clock.record_move(Duration::from_secs(1)),
+            Some(Color::Black)
+        );
+        assert_eq!(clock.flagged(), Some(Color::Black));
+        assert_eq!(clock.remaining(Color::Black), Duration::ZERO);
+
+        // A flagged clock is frozen
+        assert_eq!(clock.record_move(Duration::ZERO), Some(Color::Black));
+        assert_eq!(clock.side_to_move(), Color::Black);
+        assert_eq!(clock.remaining(Color::White), Duration::from_millis(2100));
+    }
+
+    #[test]
+    fn clock_asymmetric_controls() {
+        let white = ClockControl::new(Duration::from_secs(10), Duration::from_millis(100));
+        let black = ClockControl::new(Duration::from_mins(1), Duration::from_millis(600))
+            .with_moves_per_session(40)
+            .with_nodes(5000);
+        let mut clock = Clock::with_controls(white, black);
+        assert_eq!(clock.node_limit(Color::White), None);
+        assert_eq!(clock.moves_to_go(Color::White), None);
+        assert_eq!(
+            clock.uci_go_args(),
+            "wtime 10000 btime 60000 winc 100 binc 600"
+        );
+
+        clock.record_move(Duration::from_secs(1));
+        clock.record_move(Duration::from_secs(1));
+        assert_eq!(clock.remaining(Color::White), Duration::from_millis(9100));
+        assert_eq!(clock.remaining(Color::Black), Duration::from_millis(59_600));
+        assert_eq!(
+            clock.time_control(Color::Black),
+            TimeControl::Incremental {
+                time_left_ms: 59_600,
+                inc_ms: 600,
+                movestogo: Some(39),
+            }
+        );
+
+        clock.record_move(Duration::ZERO);
+        assert_eq!(
+            clock.uci_go_args(),
+            "wtime 9200 btime 59600 winc 100 binc 600 movestogo 39 nodes 5000"
+        );
+    }
+
+    #[test]
+    fn clock_wall_time_flags_running_side() {
+        let mut clock = Clock::new(Duration::from_millis(20), Duration::ZERO, None);
+        clock.start();
+        assert!(clock.is_running());
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(clock.flagged(), Some(Color::White));
+        assert_eq!(clock.press(), Some(Color::White));
+        assert!(!clock.is_running());
+    }
 }
@@ -0,0 +1,390 @@
+//! EPD test suites.
+//!
+//! An EPD record is the first four FEN fields followed by `;`-terminated
+//! operations, e.g.
+//!
+//! ```text
+//! 5rk1/1ppb3p/p1pb4/6q1/3P1p1r/2P1R2P/PP1BQ1P1/5RKN w - - bm Rg3; id "WAC.003";
+//! ```
+//!
+//! [`solve_suite`] searches every position for a fixed time and checks the
+//! engine's move against the `bm` (best move) and `am` (avoid move) opcodes,
+//! which is how tactical suites such as WAC are scored. `chess_engine solve
+//! <epd> [time_ms]` runs a suite from the command line and exits nonzero when
+//! fewer positions than required are solved, so it can gate CI.
+
+use std::fmt;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, FenError, Move, SanError};
+use crate::engine::{EngineController, SearchParams};
+use crate::output::ProtocolWriter;
+
+/// Extra time allowed for a search to report its move before giving up
+const SEARCH_GRACE: Duration = Duration::from_secs(10);
+
+/// Error type for EPD parsing failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    /// The position fields are not a valid FEN
+    InvalidFen(FenError),
+    /// A `bm` or `am` operand is not a legal move in the position
+    InvalidMove {
+        opcode: String,
+        mv: String,
+        error: SanError,
+    },
+    /// The record has neither a `bm` nor an `am` operation
+    NoExpectedMove,
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpdError::InvalidFen(e) => write!(f, "Invalid FEN: {e}"),
+            EpdError::InvalidMove { opcode, mv, error } => {
+                write!(f, "Invalid {opcode} move '{mv}': {error}")
+            }
+            EpdError::NoExpectedMove => write!(f, "Record has no bm or am operation"),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+impl From<FenError> for EpdError {
+    fn from(e: FenError) -> Self {
+        EpdError::InvalidFen(e)
+    }
+}
+
+/// One test position from an EPD suite
+#[derive(Debug, Clone)]
+pub struct EpdPosition {
+    /// The `id` operand, or the record's line number if it has none
+    pub id: String,
+    pub board: Board,
+    /// Moves that solve the position (`bm`)
+    pub best_moves: Vec<Move>,
+    /// Moves that fail the position (`am`)
+    pub avoid_moves: Vec<Move>,
+}
+
+impl EpdPosition {
+    /// Parse one EPD record, naming it `default_id` if it has no `id`
+    pub fn parse(line: &str, default_id: &str) -> Result<Self, EpdError> {
+        let fields: Vec<&str> = line.split_whitespace().take(4).collect();
+        let mut board = Board::try_from_fen(&fields.join(" "))?;
+
+        // Everything after the fourth field is operations
+        let mut rest = line.trim_start();
+        for _ in 0..4 {
+            rest = rest
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, tail)| tail.trim_start());
+        }
+
+        let mut id = default_id.to_string();
+        let mut best_moves = Vec::new();
+        let mut avoid_moves = Vec::new();
+        for op in split_operations(rest) {
+            let (opcode, operands) = op.split_once(char::is_whitespace).unwrap_or((op, ""));
+            match opcode {
+                "id" => id = operands.trim().trim_matches('"').to_string(),
+                "bm" => best_moves = parse_moves(&mut board, opcode, operands)?,
+                "am" => avoid_moves = parse_moves(&mut board, opcode, operands)?,
+                _ => {}
+            }
+        }
+
+        if best_moves.is_empty() && avoid_moves.is_empty() {
+            return Err(EpdError::NoExpectedMove);
+        }
+        Ok(EpdPosition {
+            id,
+            board,
+            best_moves,
+            avoid_moves,
+        })
+    }
+
+    /// Whether `mv` solves the position
+    #[must_use]
+    pub fn is_solved_by(&self, mv: Move) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.contains(&mv))
+            && !self.avoid_moves.contains(&mv)
+    }
+}
+
+/// Split the operations of a record on `;`, ignoring semicolons inside
+/// quoted operands
+fn split_operations(ops: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in ops.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                result.push(ops[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    result.push(ops[start..].trim());
+    result.retain(|op| !op.is_empty());
+    result
+}
+
+/// Parse the SAN (or UCI) move operands of a `bm` or `am` operation
+fn parse_moves(board: &mut Board, opcode: &str, operands: &str) -> Result<Vec<Move>, EpdError> {
+    operands
+        .split_whitespace()
+        .map(|token| {
+            // Suites sometimes annotate moves, e.g. "Qxh7+!"
+            let san = token.trim_end_matches(['!', '?']);
+            board
+                .parse_san(san)
+                .or_else(|error| board.parse_move(san).map_err(|_| error))
+                .map_err(|error| EpdError::InvalidMove {
+                    opcode: opcode.to_string(),
+                    mv: token.to_string(),
+                    error,
+                })
+        })
+        .collect()
+}
+
+/// Parse an EPD suite, skipping blank lines and `#` comments.
+///
+/// Errors carry the 1-based line number of the offending record.
+pub fn parse_suite(text: &str) -> Result<Vec<EpdPosition>, (usize, EpdError)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(i, line)| {
+            EpdPosition::parse(line, &format!("line {}", i + 1)).map_err(|e| (i + 1, e))
+        })
+        .collect()
+}
+
+/// Outcome of searching one suite position
+#[derive(Debug, Clone)]
+pub struct SolveResult {
+    pub id: String,
+    /// The engine's move, `None` if it returned none
+    pub best_move: Option<Move>,
+    pub solved: bool,
+    pub elapsed: Duration,
+}
+
+/// Outcome of a whole suite
+#[derive(Debug, Clone, Default)]
+pub struct SuiteReport {
+    pub results: Vec<SolveResult>,
+}
+
+impl SuiteReport {
+    /// Number of positions solved
+    #[must_use]
+    pub fn solved(&self) -> usize {
+        self.results.iter().filter(|r| r.solved).count()
+    }
+
+    /// Number of positions searched
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Percentage of positions solved
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> f64 {
+        if self.results.is_empty() {
+            0.0
+        } else {
+            self.solved() as f64 * 100.0 / self.total() as f64
+        }
+    }
+}
+
+/// Search `position` for `time_ms` milliseconds and score the engine's move
+pub fn solve_position(
+    engine: &mut EngineController,
+    position: &EpdPosition,
+    time_ms: u64,
+) -> SolveResult {
+    engine.new_game();
+    engine.set_board(position.board.clone());
+
+    let start = Instant::now();
+    let (tx, rx) = mpsc::channel();
+    let params = SearchParams {
+        soft_time_ms: time_ms,
+        hard_time_ms: time_ms,
+        multi_pv: 1,
+        ..SearchParams::default()
+    };
+    engine.start_search(params, move |result| {
+        let _ = tx.send(result.best_move);
+    });
+    let best_move = rx
+        .recv_timeout(Duration::from_millis(time_ms) + SEARCH_GRACE)
+        .ok()
+        .flatten();
+    engine.stop_search();
+
+    SolveResult {
+        id: position.id.clone(),
+        best_move,
+        solved: best_move.is_some_and(|mv| position.is_solved_by(mv)),
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Format a list of moves in SAN for the report
+fn san_list(board: &Board, moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|mv| board.move_to_san(mv))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Search every position in turn, printing one result line per position and
+/// a summary to `out`
+pub fn solve_suite(
+    engine: &mut EngineController,
+    positions: &[EpdPosition],
+    time_ms: u64,
+    out: &dyn ProtocolWriter,
+) -> SuiteReport {
+    let mut report = SuiteReport::default();
+    for position in positions {
+        let result = solve_position(engine, position, time_ms);
+
+        let played = result.best_move.map_or_else(
+            || "(none)".to_string(),
+            |mv| position.board.move_to_san(&mv),
+        );
+        let mut expected = Vec::new();
+        if !position.best_moves.is_empty() {
+            expected.push(format!(
+                "bm {}",
+                san_list(&position.board, &position.best_moves)
+            ));
+        }
+        if !position.avoid_moves.is_empty() {
+            expected.push(format!(
+                "am {}",
+                san_list(&position.board, &position.avoid_moves)
+            ));
+        }
+        crate::send_line!(
+            out,
+            "{} {} played {played} expected {} time_ms {}",
+            result.id,
+            if result.solved { "solved" } else { "FAILED" },
+            expected.join(" "),
+            result.elapsed.as_millis()
+        );
+        report.results.push(result);
+    }
+
+    crate::send_line!(
+        out,
+        "solved {}/{} ({:.1}%)",
+        report.solved(),
+        report.total(),
+        report.percent()
+    );
+    let _ = out.flush();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::CaptureWriter;
+
+    #[test]
+    fn test_parse_record_with_id_and_annotations() {
+        let pos = EpdPosition::parse(
+            r#"r1bq2rk/pp3pbp/2p1p1pQ/7P/3P4/2PB1N2/PP3PPR/2KR4 w - - bm Qxh7+!; id "WAC.004";"#,
+            "line 1",
+        )
+        .unwrap();
+        assert_eq!(pos.id, "WAC.004");
+        assert_eq!(pos.best_moves.len(), 1);
+        assert_eq!(pos.best_moves[0].to_string(), "h6h7");
+        assert!(pos.avoid_moves.is_empty());
+        assert!(pos.board.white_to_move());
+    }
+
+    #[test]
+    fn test_parse_avoid_moves_and_quoted_semicolons() {
+        let pos = EpdPosition::parse(
+            r#"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - am f3 g4; c0 "odd; comment";"#,
+            "line 7",
+        )
+        .unwrap();
+        assert_eq!(pos.id, "line 7");
+        assert!(pos.best_moves.is_empty());
+        assert_eq!(pos.avoid_moves.len(), 2);
+
+        let mut board = pos.board.clone();
+        assert!(!pos.is_solved_by(board.parse_san("g4").unwrap()));
+        assert!(pos.is_solved_by(board.parse_san("e4").unwrap()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            EpdPosition::parse("8/8/8/8/8/8/8/8 x - - bm e4;", "line 1"),
+            Err(EpdError::InvalidFen(_))
+        ));
+        assert!(matches!(
+            EpdPosition::parse(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm e5;",
+                "line 1"
+            ),
+            Err(EpdError::InvalidMove { .. })
+        ));
+        assert_eq!(
+            EpdPosition::parse(
+                r#"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - id "x";"#,
+                "line 1"
+            )
+            .unwrap_err(),
+            EpdError::NoExpectedMove
+        );
+        let err = parse_suite("# comment\n\n8/8/8/8/8/8/8/8 w - -\n").unwrap_err();
+        assert_eq!(err.0, 3);
+    }
+
+    #[test]
+    fn test_solve_suite_reports_each_position() {
+        let suite = parse_suite(concat!(
+            "# back-rank mates\n",
+            "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"mate\";\n",
+            "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - am Ra8; id \"avoid\";\n",
+        ))
+        .unwrap();
+        let capture = CaptureWriter::new();
+        let mut engine = EngineController::new(1);
+        let report = solve_suite(&mut engine, &suite, 200, &capture);
+
+        assert_eq!(report.total(), 2);
+        assert_eq!(report.solved(), 1);
+        let lines = capture.lines();
+        assert!(lines[0].starts_with("mate solved played Ra8# expected bm Ra8#"));
+        assert!(lines[1].starts_with("avoid FAILED played Ra8# expected am Ra8#"));
+        assert_eq!(lines[2], "solved 1/2 (50.0%)");
+    }
+}
@@ -41,7 +41,8 @@
 //!     .piece(Square::new(7, 4), Color::Black, Piece::King)
 //!     .piece(Square::new(1, 0), Color::White, Piece::Pawn)
 //!     .side_to_move(Color::White)
-//!     .build();
+//!     .build()
+//!     .expect("valid position");
 //! ```
 //!
 //! # Making Moves
@@ -84,7 +85,16 @@
 
 pub mod board;
 pub mod engine;
+pub mod epd;
+pub mod logging;
+pub mod output;
 pub mod pawn_hash;
+pub mod pgn;
+pub mod recording;
+pub mod scaling;
+pub mod selftest;
+pub mod shared_cache;
+pub mod speedtest;
 pub mod sync;
 pub mod timer;
 pub mod tt;
@@ -0,0 +1,192 @@
+//! Protocol log file and structured tracing.
+//!
+//! The log file (UCI option `LogFile`) records every line read from and
+//! written to the GUI with a timestamp, like Stockfish's debug log file:
+//!
+//! ```text
+//! [     0.000] >> uci
+//! [     0.001] << id name chess_engine
+//! ```
+//!
+//! With the `logging` feature enabled, `tracing` spans for commands, searches
+//! and iterations are written to the same file.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+/// The currently open log file, if any
+static LOG_FILE: LazyLock<Mutex<Option<LogFile>>> = LazyLock::new(|| Mutex::new(None));
+
+/// An open protocol log
+struct LogFile {
+    file: File,
+    opened: Instant,
+}
+
+impl LogFile {
+    fn write_line(&mut self, marker: &str, line: &str) {
+        let secs = self.opened.elapsed().as_secs_f64();
+        // Logging must never take the engine down
+        let _ = writeln!(self.file, "[{secs:>10.3}] {marker} {line}");
+        let _ = self.file.flush();
+    }
+}
+
+/// Open (or switch to) the log file at `path`.
+///
+/// An empty path or `<empty>` closes the current log.
+pub fn open_log_file(path: &str) -> io::Result<()> {
+    let path = path.trim();
+    if path.is_empty() || path == "<empty>" {
+        close_log_file();
+        return Ok(());
+    }
+
+    let file = File::options().create(true).append(true).open(path)?;
+    *LOG_FILE.lock() = Some(LogFile {
+        file,
+        opened: Instant::now(),
+    });
+
+    #[cfg(feature = "logging")]
+    install_subscriber();
+
+    Ok(())
+}
+
+/// Close the log file, if one is open
+pub fn close_log_file() {
+    *LOG_FILE.lock() = None;
+}
+
+/// Whether a log file is currently open
+#[must_use]
+pub fn is_logging() -> bool {
+    LOG_FILE.lock().is_some()
+}
+
+/// Record a line received from the GUI
+pub fn log_input(line: &str) {
+    if let Some(log) = LOG_FILE.lock().as_mut() {
+        log.write_line(">>", line);
+    }
+}
+
+/// Record a line sent to the GUI
+pub fn log_output(line: &str) {
+    if let Some(log) = LOG_FILE.lock().as_mut() {
+        log.write_line("<<", line);
+    }
+}
+
+/// Enter a `tracing` span (with the `logging` feature), e.g.
+/// `let _span = log_span!("iteration", depth);`. Compiles to nothing otherwise.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! log_span {
+    ($($arg:tt)*) => {
+        ::tracing::info_span!($($arg)*).entered()
+    };
+}
+
+/// Enter a `tracing` span (with the `logging` feature), e.g.
+/// `let _span = log_span!("iteration", depth);`. Compiles to nothing otherwise.
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! log_span {
+    ($($arg:tt)*) => {
+        $crate::logging::no_span()
+    };
+}
+
+/// Emit a `tracing` event (with the `logging` feature). Compiles to nothing otherwise.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! log_event {
+    ($($arg:tt)*) => {
+        ::tracing::debug!($($arg)*)
+    };
+}
+
+/// Emit a `tracing` event (with the `logging` feature). Compiles to nothing otherwise.
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! log_event {
+    ($($arg:tt)*) => {};
+}
+
+/// Placeholder span guard used when the `logging` feature is disabled
+pub struct NoSpan;
+
+/// Guard returned by `log_span!` when the `logging` feature is disabled
+#[inline]
+#[must_use]
+pub fn no_span() -> NoSpan {
+    NoSpan
+}
+
+/// `tracing` writer that appends to the open log file
+#[cfg(feature = "logging")]
+struct TraceWriter;
+
+#[cfg(feature = "logging")]
+impl Write for TraceWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(log) = LOG_FILE.lock().as_mut() {
+            let text = String::from_utf8_lossy(buf);
+            for line in text.lines() {
+                log.write_line("--", line);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Route `tracing` output into the log file (installed once per process)
+#[cfg(feature = "logging")]
+fn install_subscriber() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(|| TraceWriter)
+            .without_time()
+            .finish();
+        let _ = tracing::subscriber::set_global_default(subscriber);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_records_io() {
+        let path = std::env::temp_dir().join(format!("chess_log_{}.txt", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        open_log_file(path_str).unwrap();
+        assert!(is_logging());
+        log_input("isready");
+        log_output("readyok");
+        open_log_file("<empty>").unwrap();
+        assert!(!is_logging());
+        log_input("ignored");
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(contents.lines().all(|l| l.starts_with('[')));
+        assert!(contents.lines().any(|l| l.ends_with(">> isready")));
+        assert!(contents.lines().any(|l| l.ends_with("<< readyok")));
+        assert!(!contents.contains("ignored"));
+    }
+}
@@ -1,400 +1,267 @@
-use std::io;
-use std::io::{BufRead, Write};
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::io::{self, BufRead, Read};
+use std::net::TcpListener;
 
-use chess_engine::board::SearchIterationInfo;
 use chess_engine::board::DEFAULT_TT_MB;
-use chess_engine::engine::time::{build_search_request, TimeConfig, TimeControl};
-use chess_engine::engine::{EngineController, SearchParams as EngineSearchParams};
-use chess_engine::uci::command::{parse_go_params, parse_uci_command, GoParams, UciCommand};
-use chess_engine::uci::options::{parse_setoption, UciOptionAction, UciOptions};
-use chess_engine::uci::parse_position_command;
-use chess_engine::uci::print::{print_perft_info, print_time_info};
-use chess_engine::uci::report::{print_bestmove_with_ponder, print_ready};
+use chess_engine::engine::{EngineController, ProtocolType};
+use chess_engine::epd;
+use chess_engine::output;
+use chess_engine::recording::{self, ReplayReader};
+use chess_engine::scaling;
+use chess_engine::selftest;
+use chess_engine::speedtest;
+use chess_engine::uci::server;
+use chess_engine::uci::session::UciSession;
+use chess_engine::xboard::XBoardHandler;
+
+/// Default search time per position for `solve`
+const DEFAULT_SOLVE_TIME_MS: u64 = 1000;
+/// Hash size for `solve`; suites search briefly, so a modest table suffices
+const SOLVE_TT_MB: usize = 64;
+/// Hash size of the engine `--selftest` loads the NNUE network with
+const SELFTEST_TT_MB: usize = 1;
 
-/// Default depth limit when searching by nodes
-const NODE_SEARCH_DEFAULT_DEPTH: u32 = 64;
-/// Fallback time allocation when no time control is specified
-const FALLBACK_TIME_SECS: u64 = 5;
-const KNOWN_COMMANDS: &[&str] = &[
-    "uci",
-    "isready",
-    "ucinewgame",
-    "position",
-    "go",
-    "perft",
-    "setoption",
-    "debug",
-    "stop",
-    "ponderhit",
-    "quit",
-];
-
-/// UCI session state (time controls, debug mode)
-struct UciState {
-    time_control: TimeControl,
-    debug: bool,
+/// Protocol to use for communication
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    Uci,
+    XBoard,
+    Auto,
 }
 
-impl Default for UciState {
-    fn default() -> Self {
-        UciState {
-            time_control: TimeControl::move_time(Duration::from_secs(FALLBACK_TIME_SECS)),
-            debug: false,
-        }
-    }
+/// Command-line arguments
+struct Args {
+    protocol: Protocol,
+    /// Record stdin commands with timing to this file
+    record: Option<String>,
+    /// Replay a recorded session instead of reading stdin
+    replay: Option<String>,
+    /// Serve UCI over TCP on this port (or `host:port`) instead of stdio
+    listen: Option<String>,
+    /// `solve <epd> [time_ms] [--min <n>]`: run an EPD test suite and exit
+    solve: Option<Vec<String>>,
+    /// Verify the build with perft, bench and symmetry checks and exit
+    selftest: bool,
+    /// Measure movegen, make/unmake, eval and quiescence speed and exit
+    speedtest: bool,
+    /// Measure SMP scaling up to the available hardware threads and exit
+    scaling: bool,
+    /// Hash size (MB) UCI sessions start with, before any `setoption`
+    hash_mb: usize,
 }
 
-impl UciState {
-    fn update_time_control(&mut self, params: &GoParams, is_white: bool) -> TimeControl {
-        if params.infinite || params.ponder {
-            self.time_control = TimeControl::Infinite;
-            return self.time_control;
-        }
-
-        if let Some(mt) = params.movetime {
-            self.time_control = TimeControl::move_time(Duration::from_millis(mt));
-            return self.time_control;
-        }
-
-        let fallback = Duration::from_secs(FALLBACK_TIME_SECS);
-        let time_left = if is_white {
-            params.wtime.map(Duration::from_millis)
-        } else {
-            params.btime.map(Duration::from_millis)
-        }
-        .unwrap_or(fallback);
-
-        let inc = if is_white {
-            params.winc.map(Duration::from_millis)
-        } else {
-            params.binc.map(Duration::from_millis)
+fn parse_args() -> Args {
+    let mut args = Args {
+        protocol: Protocol::Auto,
+        record: None,
+        replay: None,
+        listen: None,
+        solve: None,
+        selftest: false,
+        speedtest: false,
+        scaling: false,
+        hash_mb: DEFAULT_TT_MB,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--uci" | "-u" => args.protocol = Protocol::Uci,
+            "--xboard" | "-x" => args.protocol = Protocol::XBoard,
+            "--record" => args.record = iter.next(),
+            "--replay" => args.replay = iter.next(),
+            "--listen" => args.listen = iter.next(),
+            "--selftest" => args.selftest = true,
+            "--speedtest" => args.speedtest = true,
+            "--scaling" => args.scaling = true,
+            "--hash" => {
+                if let Some(mb) = iter.next().and_then(|v| v.parse::<usize>().ok()) {
+                    args.hash_mb = mb.max(1);
+                }
+            }
+            "solve" => args.solve = Some(iter.by_ref().collect()),
+            _ => {}
         }
-        .unwrap_or(Duration::ZERO);
-
-        self.time_control = TimeControl::incremental(time_left, inc, params.movestogo);
-        self.time_control
     }
+    args
 }
 
-fn parts_as_strs(parts: &[String]) -> Vec<&str> {
-    parts.iter().map(|p| p.as_str()).collect()
+/// Whether a first command selects the `XBoard` protocol
+fn is_xboard_greeting(line: &str) -> bool {
+    ProtocolType::detect(line) == ProtocolType::XBoard
 }
 
-struct UciSession {
-    engine: EngineController,
-    options: UciOptions,
-    state: UciState,
+fn run_uci_session<R: BufRead>(reader: R, hash_mb: usize) {
+    UciSession::new(hash_mb, output::stdout()).run(reader);
 }
 
-struct GoSearchPlan {
-    search_params: EngineSearchParams,
-    soft_time_ms: u64,
-    hard_time_ms: u64,
-    depth_hint: Option<u32>,
-    go_ponder: bool,
-    max_nodes: u64,
-}
-
-impl UciSession {
-    fn new(default_tt_mb: usize) -> Self {
-        let options = UciOptions::new(default_tt_mb);
-        let mut engine = EngineController::new(options.hash_mb);
-        engine.set_info_callback(Some(default_info_callback()));
-        UciSession {
-            engine,
-            options,
-            state: UciState::default(),
-        }
+/// Serve UCI sessions over TCP; a bare port listens on localhost only, and
+/// other hosts can connect only when the address names an interface
+fn run_server(addr: &str, hash_mb: usize) {
+    let addr = if addr.contains(':') {
+        addr.to_string()
+    } else {
+        format!("127.0.0.1:{addr}")
+    };
+    let result = TcpListener::bind(&addr).and_then(|listener| {
+        eprintln!("info string Listening for UCI connections on {addr}");
+        server::serve(&listener, hash_mb)
+    });
+    if let Err(e) = result {
+        eprintln!("Cannot listen on {addr}: {e}");
+        std::process::exit(1);
     }
+}
 
-    fn build_go_plan(&mut self, params: &GoParams, is_white: bool) -> GoSearchPlan {
-        let time_control = self.state.update_time_control(params, is_white);
-        let mut depth = params.depth;
-        let nodes = params.nodes;
-        let mate = params.mate;
-        let go_ponder = params.ponder;
-        let go_infinite = params.infinite;
-
-        if nodes.is_some() && depth.is_none() {
-            depth = Some(NODE_SEARCH_DEFAULT_DEPTH);
-        }
-
-        if let Some(mate_moves) = mate {
-            if mate_moves > 0 && depth.is_none() {
-                depth = Some(mate_moves * 2);
+/// Run an EPD test suite, exiting nonzero if fewer than the required number
+/// of positions (all of them unless `--min` is given) are solved
+fn run_solve(args: &[String]) {
+    const USAGE: &str = "usage: chess_engine solve <epd> [time_ms] [--min <solved>]";
+    let mut path = None;
+    let mut time_ms = DEFAULT_SOLVE_TIME_MS;
+    let mut min_solved = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let parsed = match arg.as_str() {
+            "--min" => iter
+                .next()
+                .and_then(|n| n.parse().ok())
+                .map(|n| min_solved = Some(n)),
+            _ if path.is_none() => {
+                path = Some(arg.clone());
+                Some(())
             }
-        }
-
-        let time_config = TimeConfig {
-            move_overhead_ms: self.options.move_overhead_ms,
-            soft_time_percent: self.options.soft_time_percent,
-            hard_time_percent: self.options.hard_time_percent,
-            default_max_nodes: self.options.default_max_nodes,
-        };
-        let (request, (soft_time_ms, hard_time_ms)) = build_search_request(
-            time_control,
-            depth,
-            nodes,
-            go_ponder,
-            go_infinite,
-            &time_config,
-        );
-
-        let search_params = EngineSearchParams {
-            depth: request.depth,
-            soft_time_ms: request.soft_time_ms,
-            hard_time_ms: request.hard_time_ms,
-            ponder: request.ponder,
-            infinite: request.infinite,
-            multi_pv: self.options.multi_pv,
+            _ => arg.parse().ok().map(|ms| time_ms = ms),
         };
-
-        GoSearchPlan {
-            search_params,
-            soft_time_ms,
-            hard_time_ms,
-            depth_hint: depth,
-            go_ponder,
-            max_nodes: request.max_nodes,
+        if parsed.is_none() {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
         }
     }
+    let Some(path) = path else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
 
-    /// Handle the "go" command - start a search
-    fn handle_go(&mut self, parts: &[String]) {
-        let parts_ref = parts_as_strs(parts);
-        let params = parse_go_params(&parts_ref);
-
-        let plan = self.build_go_plan(&params, self.engine.board().white_to_move());
-
-        self.engine.set_max_nodes(plan.max_nodes);
-
-        print_time_info(
-            plan.soft_time_ms,
-            plan.hard_time_ms,
-            self.options.move_overhead_ms,
-            plan.max_nodes,
-            plan.go_ponder,
-            plan.depth_hint.unwrap_or(0),
-        );
-
-        // Get board state for checkmate/stalemate reporting
-        let is_checkmate = self.engine.board_mut().is_checkmate();
-        let is_stalemate = self.engine.board_mut().is_stalemate();
-        let is_draw = self.engine.board().is_draw();
-
-        // Build search parameters
-        self.engine.start_search(plan.search_params, move |result| {
-            if result.best_move.is_none() {
-                if is_checkmate {
-                    println!("info score mate -1");
-                } else if is_stalemate || is_draw {
-                    println!("info score cp 0");
-                }
-            }
-            print_bestmove_with_ponder(result);
-        });
-    }
-
-    /// Handle the "setoption" command
-    fn handle_setoption(&mut self, parts: &[String]) {
-        self.engine.stop_search();
-        let parts_ref = parts_as_strs(parts);
-        if let Some((name, value)) = parse_setoption(&parts_ref) {
-            let action = self.engine.with_search_state(|state| {
-                self.options.apply_setoption(&name, value.as_deref(), state)
-            });
-            if let Some(Some(action)) = action {
-                match action {
-                    UciOptionAction::ReinitHash(new_mb) => {
-                        self.engine.resize_hash(new_mb);
-                    }
-                    UciOptionAction::SetThreads(threads) => {
-                        self.engine.set_threads(threads);
-                    }
-                }
+    let positions = match std::fs::read_to_string(&path) {
+        Ok(text) => match epd::parse_suite(&text) {
+            Ok(positions) => positions,
+            Err((line, e)) => {
+                eprintln!("{path}:{line}: {e}");
+                std::process::exit(2);
             }
+        },
+        Err(e) => {
+            eprintln!("Cannot read {path}: {e}");
+            std::process::exit(2);
         }
-    }
+    };
 
-    /// Process a single UCI command. Returns false if the engine should quit.
-    fn handle_command(&mut self, cmd: UciCommand) -> bool {
-        match cmd {
-            UciCommand::Uci => {
-                self.engine
-                    .with_search_state_ref(|state| self.options.print(state.params()));
-            }
-            UciCommand::IsReady => {
-                print_ready();
-            }
-            UciCommand::UciNewGame => {
-                self.engine.new_game();
-            }
-            UciCommand::Position(parts) => {
-                self.engine.stop_search();
-                let parts_ref = parts_as_strs(&parts);
-                parse_position_command(self.engine.board_mut(), &parts_ref);
-            }
-            UciCommand::Perft(depth) => {
-                self.engine.stop_search();
-                let start = Instant::now();
-                let nodes = self.engine.board_mut().perft(depth);
-                let elapsed = start.elapsed();
-                print_perft_info(depth, nodes, elapsed);
-            }
-            UciCommand::Go(parts) => {
-                self.handle_go(&parts);
-            }
-            UciCommand::Stop => {
-                self.engine.signal_stop();
-            }
-            UciCommand::PonderHit => {
-                self.engine.ponderhit();
-            }
-            UciCommand::SetOption(parts) => {
-                self.handle_setoption(&parts);
-            }
-            UciCommand::Debug(value) => {
-                self.state.debug = matches!(value.as_deref(), Some("on"));
-                self.engine.set_trace(self.state.debug);
-            }
-            UciCommand::Quit => {
-                self.engine.stop_search();
-                return false;
-            }
-            UciCommand::Unknown(line) => {
-                if self.state.debug {
-                    eprintln!("Unknown command: {}", line);
-                    eprintln!("Known commands: {}", KNOWN_COMMANDS.join(", "));
-                }
-            }
-        }
-        true
+    let mut engine = EngineController::new(SOLVE_TT_MB);
+    let report = epd::solve_suite(&mut engine, &positions, time_ms, &*output::stdout());
+    if report.solved() < min_solved.unwrap_or(report.total()) {
+        std::process::exit(1);
     }
 }
 
-fn print_uci_info(info: &SearchIterationInfo) {
-    // Include multipv in output for UCI compliance
-    let multipv_str = if info.multipv > 1 {
-        format!(" multipv {}", info.multipv)
-    } else {
-        String::new()
-    };
-
-    if let Some(mate) = info.mate_in {
-        println!(
-            "info depth {} seldepth {}{} nodes {} nps {} time {} score mate {} pv {}",
-            info.depth,
-            info.seldepth,
-            multipv_str,
-            info.nodes,
-            info.nps,
-            info.time_ms,
-            mate,
-            info.pv
-        );
-    } else {
-        println!(
-            "info depth {} seldepth {}{} nodes {} nps {} time {} score cp {} pv {}",
-            info.depth,
-            info.seldepth,
-            multipv_str,
-            info.nodes,
-            info.nps,
-            info.time_ms,
-            info.score,
-            info.pv
-        );
+/// Run the build self-test with the engine's default network, exiting
+/// nonzero if any check fails
+fn run_selftest() {
+    let network = EngineController::new(SELFTEST_TT_MB)
+        .with_search_state_ref(|state| state.tables.nnue.clone())
+        .flatten();
+    if !selftest::run_selftest(network.as_ref(), &*output::stdout()).passed() {
+        std::process::exit(1);
     }
 }
 
-fn default_info_callback() -> Arc<dyn Fn(&SearchIterationInfo) + Send + Sync> {
-    Arc::new(print_uci_info)
+/// Run the speed test with the engine's default network
+fn run_speedtest() {
+    let network = EngineController::new(SELFTEST_TT_MB)
+        .with_search_state_ref(|state| state.tables.nnue.clone())
+        .flatten();
+    speedtest::run_speedtest(network.as_ref(), "", &*output::stdout());
 }
 
-/// Protocol to use for communication
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Protocol {
-    Uci,
-    XBoard,
-    Auto,
+/// Run the SMP scaling report with the engine's default network
+fn run_scaling() {
+    let network = EngineController::new(SELFTEST_TT_MB)
+        .with_search_state_ref(|state| state.tables.nnue.clone())
+        .flatten();
+    scaling::run_scaling(
+        network.as_ref(),
+        scaling::default_max_threads(),
+        scaling::DEFAULT_SCALING_DEPTH,
+        "",
+        &*output::stdout(),
+    );
 }
 
-fn parse_args() -> Protocol {
-    let args: Vec<String> = std::env::args().collect();
-    for arg in &args[1..] {
-        match arg.as_str() {
-            "--uci" | "-u" => return Protocol::Uci,
-            "--xboard" | "-x" => return Protocol::XBoard,
-            _ => {}
+/// Replay a recorded session, picking the protocol from its first command
+fn run_replay(path: &str, protocol: Protocol, hash_mb: usize) {
+    let reader = match ReplayReader::open(path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            eprintln!("Cannot replay {path}: {e}");
+            std::process::exit(1);
         }
+    };
+    let xboard = match protocol {
+        Protocol::Uci => false,
+        Protocol::XBoard => true,
+        Protocol::Auto => reader.first_command().is_some_and(is_xboard_greeting),
+    };
+    if xboard {
+        XBoardHandler::new().run_from(reader);
+    } else {
+        run_uci_session(reader, hash_mb);
     }
-    Protocol::Auto
 }
 
-fn run_uci_session<R: BufRead>(first_line: Option<String>, reader: R) {
-    let mut stdout = io::stdout();
-    let mut session = UciSession::new(DEFAULT_TT_MB);
+fn main() {
+    let args = parse_args();
 
-    if let Some(line) = first_line {
-        if let Some(cmd) = parse_uci_command(&line) {
-            if !session.handle_command(cmd) {
-                return;
-            }
+    if let Some(path) = &args.record {
+        if let Err(e) = recording::start_recording(path) {
+            eprintln!("Cannot record to {path}: {e}");
         }
-        let _ = stdout.flush();
     }
-
-    for line in reader.lines() {
-        let line = match line {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-        if let Some(cmd) = parse_uci_command(&line) {
-            let keep_running = session.handle_command(cmd);
-            if !keep_running {
-                break;
-            }
-        }
-
-        let _ = stdout.flush();
+    if let Some(path) = &args.replay {
+        run_replay(path, args.protocol, args.hash_mb);
+        return;
+    }
+    if args.selftest {
+        run_selftest();
+        return;
+    }
+    if args.speedtest {
+        run_speedtest();
+        return;
+    }
+    if args.scaling {
+        run_scaling();
+        return;
+    }
+    if let Some(solve_args) = &args.solve {
+        run_solve(solve_args);
+        return;
+    }
+    if let Some(addr) = &args.listen {
+        run_server(addr, args.hash_mb);
+        return;
     }
-}
 
-fn run_uci() {
     let stdin = io::stdin();
-    run_uci_session(None, stdin.lock());
-}
-
-fn main() {
-    let protocol = parse_args();
-
-    match protocol {
-        Protocol::Uci => run_uci(),
+    match args.protocol {
+        Protocol::Uci => run_uci_session(stdin.lock(), args.hash_mb),
         Protocol::XBoard => chess_engine::xboard::run_xboard(),
         Protocol::Auto => {
-            // Auto-detect based on first command
-            let stdin = io::stdin();
+            // Auto-detect based on first command, then hand it to the session
             let mut first_line = String::new();
             if stdin.read_line(&mut first_line).is_ok() {
-                let trimmed = first_line.trim();
-                if trimmed == "xboard" || trimmed.starts_with("protover") {
-                    // XBoard mode - process first command and continue
-                    let mut handler = chess_engine::xboard::XBoardHandler::new();
-                    if let Some(cmd) = chess_engine::xboard::command::parse_xboard_command(trimmed)
-                    {
-                        if let Some(response) = handler.handle_command(&cmd) {
-                            for line in response.lines() {
-                                println!("{line}");
-                            }
-                        }
-                    }
-                    handler.run();
+                let first = io::Cursor::new(first_line.trim().to_string() + "\n");
+                if is_xboard_greeting(first_line.trim()) {
+                    XBoardHandler::new().run_from(first.chain(stdin.lock()));
                 } else {
-                    // UCI mode - process first command and continue
-                    let remaining = stdin.lock();
-                    run_uci_session(Some(trimmed.to_string()), remaining);
+                    run_uci_session(first.chain(stdin.lock()), args.hash_mb);
                 }
             }
         }
@@ -0,0 +1,385 @@
+//! Protocol output sinks.
+//!
+//! Everything the engine sends to a GUI (UCI or `XBoard`) goes through a
+//! [`ProtocolWriter`] owned by the session, instead of straight to stdout.
+//! The same protocol code can then talk to stdout, a socket, or a
+//! [`CaptureWriter`] that lets in-process tests assert on the exact output.
+//!
+//! Writers are shared between the command loop and the search thread (which
+//! reports `info` and `bestmove`), so they take `&self` and lock internally.
+//!
+//! Search reports go through a [`ReportThrottle`], which coalesces them when
+//! iterations complete faster than a GUI can usefully display them.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+
+/// Destination for protocol output lines
+pub trait ProtocolWriter: Send + Sync {
+    /// Write one protocol line (without the trailing newline)
+    fn write_line(&self, line: &str) -> io::Result<()>;
+
+    /// Push any buffered output to the GUI
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Shared handle to a session's output sink
+pub type SharedWriter = Arc<dyn ProtocolWriter>;
+
+/// Send a line to `out`, mirroring it to the protocol log.
+///
+/// Output errors are ignored: a GUI that has gone away is noticed when its
+/// input stream closes.
+pub fn send(out: &dyn ProtocolWriter, line: &str) {
+    let _ = out.write_line(line);
+    crate::logging::log_output(line);
+}
+
+/// Format a protocol line and send it to a [`ProtocolWriter`], e.g.
+/// `send_line!(out, "bestmove {mv}")`.
+#[macro_export]
+macro_rules! send_line {
+    ($out:expr, $($arg:tt)*) => {
+        $crate::output::send(&*$out, &format!($($arg)*))
+    };
+}
+
+/// Writes each line straight to stdout
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutWriter;
+
+impl ProtocolWriter for StdoutWriter {
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{line}")?;
+        stdout.flush()
+    }
+}
+
+/// A shared stdout writer
+#[must_use]
+pub fn stdout() -> SharedWriter {
+    Arc::new(StdoutWriter)
+}
+
+/// Writes each line to any byte stream (a `TcpStream`, a pipe, ...) and
+/// flushes it immediately, so the GUI sees output without delay.
+pub struct StreamWriter<W: Write + Send> {
+    inner: Mutex<W>,
+}
+
+impl<W: Write + Send> StreamWriter<W> {
+    #[must_use]
+    pub fn new(inner: W) -> Self {
+        StreamWriter {
+            inner: Mutex::new(inner),
+        }
+    }
+}
+
+impl<W: Write + Send> ProtocolWriter for StreamWriter<W> {
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let mut inner = self.inner.lock();
+        writeln!(inner, "{line}")?;
+        inner.flush()
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.inner.lock().flush()
+    }
+}
+
+/// Buffers lines until [`ProtocolWriter::flush`], for bulk output such as
+/// replaying a session into a file.
+pub struct BufferedWriter<W: Write + Send> {
+    inner: Mutex<BufWriter<W>>,
+}
+
+impl<W: Write + Send> BufferedWriter<W> {
+    #[must_use]
+    pub fn new(inner: W) -> Self {
+        BufferedWriter {
+            inner: Mutex::new(BufWriter::new(inner)),
+        }
+    }
+}
+
+impl<W: Write + Send> ProtocolWriter for BufferedWriter<W> {
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        writeln!(self.inner.lock(), "{line}")
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.inner.lock().flush()
+    }
+}
+
+/// Collects output lines in memory for tests.
+///
+/// Clones share the same buffer, so a test can hand one clone to the
+/// session and inspect the other.
+#[derive(Clone, Default)]
+pub struct CaptureWriter {
+    shared: Arc<(Mutex<Vec<String>>, Condvar)>,
+}
+
+impl CaptureWriter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All lines written so far
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.shared.0.lock().clone()
+    }
+
+    /// Remove and return all lines written so far
+    #[must_use]
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.shared.0.lock())
+    }
+
+    /// Wait until a line starting with `prefix` has been written, returning
+    /// it, or `None` after `timeout`.
+    #[must_use]
+    pub fn wait_for(&self, prefix: &str, timeout: Duration) -> Option<String> {
+        let deadline = Instant::now() + timeout;
+        let (lines, written) = &*self.shared;
+        let mut lines = lines.lock();
+        loop {
+            if let Some(line) = lines.iter().find(|l| l.starts_with(prefix)) {
+                return Some(line.clone());
+            }
+            if written.wait_until(&mut lines, deadline).timed_out() {
+                return None;
+            }
+        }
+    }
+}
+
+impl ProtocolWriter for CaptureWriter {
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        let (lines, written) = &*self.shared;
+        lines.lock().push(line.to_string());
+        written.notify_all();
+        Ok(())
+    }
+}
+
+/// Default minimum time between two search reports
+pub const DEFAULT_MIN_REPORT_INTERVAL_MS: u64 = 50;
+
+/// Largest minimum time between search reports the protocols accept
+pub const MAX_REPORT_INTERVAL_MS: u64 = 5000;
+
+/// Rate limit for search reports, shared by both protocols.
+///
+/// At low depths iterations complete microseconds apart, and a GUI sent a
+/// report for each spends its time redrawing. A report that comes less than
+/// the minimum interval after the previous one is held instead of sent; a
+/// later report in the same slot (`MultiPV` line) replaces it, and held
+/// reports go out with the next report past the interval. Progress lines
+/// carry nothing worth holding, so a throttled one is dropped. Call
+/// [`ReportThrottle::flush`] when the search ends, before the move, so the
+/// GUI shows the final iteration.
+#[derive(Debug)]
+pub struct ReportThrottle {
+    min_interval_ms: AtomicU64,
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    last_sent: Option<Instant>,
+    /// Held report lines by slot
+    held: BTreeMap<u32, Vec<String>>,
+}
+
+impl ReportThrottle {
+    /// A throttle sending at most one report every `min_interval_ms`
+    /// (0 = send every report)
+    #[must_use]
+    pub fn new(min_interval_ms: u64) -> Self {
+        ReportThrottle {
+            min_interval_ms: AtomicU64::new(min_interval_ms),
+            state: Mutex::new(ThrottleState::default()),
+        }
+    }
+
+    #[must_use]
+    pub fn min_interval_ms(&self) -> u64 {
+        self.min_interval_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_min_interval_ms(&self, ms: u64) {
+        self.min_interval_ms.store(ms, Ordering::Relaxed);
+    }
+
+    /// Send the `lines` reporting an iteration in `slot` to `out`, or hold
+    /// them if the last report went out too recently
+    pub fn report(&self, out: &dyn ProtocolWriter, slot: u32, lines: Vec<String>) {
+        self.report_at(Instant::now(), out, slot, lines);
+    }
+
+    /// Send a progress `line` to `out` (with any held reports before it),
+    /// or drop it if the last report went out too recently
+    pub fn report_progress(&self, out: &dyn ProtocolWriter, line: &str) {
+        self.report_progress_at(Instant::now(), out, line);
+    }
+
+    /// Send every held report and start over, so the next report goes out
+    /// at once
+    pub fn flush(&self, out: &dyn ProtocolWriter) {
+        let mut state = self.state.lock();
+        send_held(out, &mut state);
+        state.last_sent = None;
+    }
+
+    /// Drop every held report and start over, when the position they are
+    /// about is gone
+    pub fn discard(&self) {
+        *self.state.lock() = ThrottleState::default();
+    }
+
+    fn due(&self, state: &ThrottleState, now: Instant) -> bool {
+        let interval = Duration::from_millis(self.min_interval_ms());
+        state
+            .last_sent
+            .is_none_or(|last| now.saturating_duration_since(last) >= interval)
+    }
+
+    fn report_at(&self, now: Instant, out: &dyn ProtocolWriter, slot: u32, lines: Vec<String>) {
+        let mut state = self.state.lock();
+        state.held.insert(slot, lines);
+        if self.due(&state, now) {
+            send_held(out, &mut state);
+            state.last_sent = Some(now);
+        }
+    }
+
+    fn report_progress_at(&self, now: Instant, out: &dyn ProtocolWriter, line: &str) {
+        let mut state = self.state.lock();
+        if self.due(&state, now) {
+            send_held(out, &mut state);
+            send(out, line);
+            state.last_sent = Some(now);
+        }
+    }
+}
+
+impl Default for ReportThrottle {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN_REPORT_INTERVAL_MS)
+    }
+}
+
+fn send_held(out: &dyn ProtocolWriter, state: &mut ThrottleState) {
+    for line in std::mem::take(&mut state.held).into_values().flatten() {
+        send(out, &line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_throttle_coalesces_fast_iterations() {
+        let capture = CaptureWriter::new();
+        let throttle = ReportThrottle::new(50);
+        let lines = |depth: u32, slot: u32| vec![format!("depth {depth} slot {slot}")];
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        throttle.report_at(at(0), &capture, 1, lines(1, 1));
+        throttle.report_at(at(10), &capture, 1, lines(2, 1));
+        throttle.report_at(at(20), &capture, 2, lines(2, 2));
+        throttle.report_at(at(30), &capture, 1, lines(3, 1));
+        // Depth 2 of slot 1 was superseded before it went out
+        assert_eq!(capture.take(), ["depth 1 slot 1"]);
+
+        throttle.report_progress_at(at(40), &capture, "progress");
+        assert!(capture.take().is_empty());
+        throttle.report_progress_at(at(60), &capture, "progress");
+        assert_eq!(
+            capture.take(),
+            ["depth 3 slot 1", "depth 2 slot 2", "progress"]
+        );
+
+        throttle.report_at(at(70), &capture, 1, lines(4, 1));
+        throttle.flush(&capture);
+        assert_eq!(capture.take(), ["depth 4 slot 1"]);
+        // Nothing was sent since the flush
+        throttle.report_at(at(71), &capture, 1, lines(5, 1));
+        assert_eq!(capture.take(), ["depth 5 slot 1"]);
+
+        throttle.report_at(at(80), &capture, 1, lines(7, 1));
+        throttle.discard();
+        throttle.report_at(at(81), &capture, 1, lines(8, 1));
+        assert_eq!(capture.take(), ["depth 8 slot 1"]);
+
+        throttle.set_min_interval_ms(0);
+        throttle.report_at(at(81), &capture, 1, lines(6, 1));
+        assert_eq!(capture.take(), ["depth 6 slot 1"]);
+    }
+
+    #[test]
+    fn test_capture_writer_shares_lines_between_clones() {
+        let capture = CaptureWriter::new();
+        let out: SharedWriter = Arc::new(capture.clone());
+        crate::send_line!(out, "id name {}", "chess_engine");
+        crate::send_line!(out, "uciok");
+        assert_eq!(capture.lines(), ["id name chess_engine", "uciok"]);
+        assert_eq!(capture.take().len(), 2);
+        assert!(capture.lines().is_empty());
+    }
+
+    #[test]
+    fn test_capture_writer_wait_for_line_from_other_thread() {
+        let capture = CaptureWriter::new();
+        let out: SharedWriter = Arc::new(capture.clone());
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            crate::send_line!(out, "bestmove e2e4");
+        });
+        let line = capture.wait_for("bestmove", Duration::from_secs(5));
+        handle.join().unwrap();
+        assert_eq!(line.as_deref(), Some("bestmove e2e4"));
+        assert_eq!(capture.wait_for("readyok", Duration::from_millis(10)), None);
+    }
+
+    #[test]
+    fn test_buffered_writer_holds_output_until_flush() {
+        #[derive(Clone, Default)]
+        struct Sink(Arc<Mutex<Vec<u8>>>);
+        impl Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let sink = Sink::default();
+        let out = BufferedWriter::new(sink.clone());
+        out.write_line("readyok").unwrap();
+        assert!(sink.0.lock().is_empty());
+        out.flush().unwrap();
+        assert_eq!(&*sink.0.lock(), b"readyok\n");
+
+        let out = StreamWriter::new(sink.clone());
+        out.write_line("uciok").unwrap();
+        assert_eq!(&*sink.0.lock(), b"readyok\nuciok\n");
+    }
+}
@@ -74,6 +74,9 @@ impl PawnSlot {
 /// Number of slots per bucket
 const BUCKET_SIZE: usize = 2;
 
+/// Smallest table size, in buckets (8 KB)
+const MIN_BUCKETS: usize = 256;
+
 /// A bucket containing multiple slots for collision resolution
 #[repr(C)]
 struct PawnBucket {
@@ -107,13 +110,10 @@ impl PawnHashTable {
     #[must_use]
     pub fn new(size_kb: usize) -> Self {
         let bucket_size = std::mem::size_of::<PawnBucket>();
-        let mut num_buckets = (size_kb * 1024) / bucket_size;
+        let fitting = ((size_kb * 1024) / bucket_size).max(MIN_BUCKETS);
 
-        // Ensure power of 2 for efficient indexing
-        num_buckets = num_buckets.next_power_of_two() / 2;
-        if num_buckets == 0 {
-            num_buckets = 1024;
-        }
+        // Largest power of 2 that fits, for efficient indexing
+        let num_buckets = 1 << fitting.ilog2();
 
         let mut buckets = Vec::with_capacity(num_buckets);
         for _ in 0..num_buckets {
@@ -126,6 +126,12 @@ impl PawnHashTable {
         }
     }
 
+    /// Memory held by the table, in bytes
+    #[must_use]
+    pub fn size_bytes(&self) -> usize {
+        self.buckets.len() * std::mem::size_of::<PawnBucket>()
+    }
+
     #[inline]
     fn index(&self, hash: u64) -> usize {
         (hash as usize) & self.mask
@@ -0,0 +1,488 @@
+//! PGN game import and export.
+//!
+//! [`parse_games`] reads a PGN file, one [`PgnGame`] per game: its tags, its
+//! start position (the standard one, or the `FEN` tag's), its moves and its
+//! result. Comments, variations, NAGs, move numbers and move annotations
+//! (`!`, `?!`, ...) are skipped. A [`PgnGame`] displays as PGN, with the
+//! Seven Tag Roster first, so games played by the engine can be saved for
+//! analysis in any chess program:
+//!
+//! ```
+//! use chess_engine::board::{Board, GameResult};
+//! use chess_engine::pgn::{parse_games, PgnGame};
+//!
+//! let mut board = Board::new();
+//! for mv in ["f3", "e5", "g4", "Qh4#"] {
+//!     board.make_move_san(mv).unwrap();
+//! }
+//! let mut game = PgnGame::from_board(&board);
+//! game.set_header("White", "Fool");
+//! game.result = Some(GameResult::BlackWins);
+//!
+//! let text = game.to_string();
+//! assert!(text.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+//! let parsed = parse_games(&text).unwrap();
+//! assert_eq!(parsed[0].moves, game.moves);
+//! assert_eq!(parsed[0].header("White"), Some("Fool"));
+//! ```
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::board::{Board, FenError, GameResult, Move, SanError};
+
+/// Longest movetext line written, as the PGN standard asks
+const MAX_LINE_LEN: usize = 79;
+
+/// The Seven Tag Roster, written first and in this order. `Result` is
+/// written from [`PgnGame::result`].
+const SEVEN_TAG_ROSTER: [&str; 6] = ["Event", "Site", "Date", "Round", "White", "Black"];
+
+/// Error type for PGN parsing failures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    /// A `FEN` tag is not a valid FEN
+    InvalidFen { line: usize, error: FenError },
+    /// A move is not legal in its game
+    InvalidMove {
+        line: usize,
+        san: String,
+        error: SanError,
+    },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::InvalidFen { line, error } => write!(f, "line {line}: {error}"),
+            PgnError::InvalidMove { line, san, error } => {
+                write!(f, "line {line}: invalid move '{san}': {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// One game of a PGN file
+#[derive(Debug, Clone, Default)]
+pub struct PgnGame {
+    /// Tags in file order, except `Result`, `FEN` and `SetUp`, which are
+    /// kept in `result` and `start`
+    pub headers: Vec<(String, String)>,
+    pub start: Board,
+    /// Legal moves from `start`
+    pub moves: Vec<Move>,
+    /// `None` for a game in progress or with an unknown result (`*`)
+    pub result: Option<GameResult>,
+}
+
+impl PgnGame {
+    /// A game of `moves` played from `start`
+    #[must_use]
+    pub fn new(start: Board, moves: Vec<Move>) -> Self {
+        PgnGame {
+            headers: Vec::new(),
+            start,
+            moves,
+            result: None,
+        }
+    }
+
+    /// The game played on `board` with `make_move_uci` and `make_move_san`
+    /// (see [`Board::history_san`])
+    #[must_use]
+    pub fn from_board(board: &Board) -> Self {
+        match &board.game {
+            Some(game) => Self::new(Board::clone(&game.start), game.moves.clone()),
+            None => Self::new(board.clone(), Vec::new()),
+        }
+    }
+
+    /// Value of the tag `name`
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the tag `name`, replacing any previous value
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        match self.headers.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.headers.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// The start position and the position after each move
+    #[must_use]
+    pub fn positions(&self) -> Vec<Board> {
+        let mut board = self.start.clone();
+        let mut positions = Vec::with_capacity(self.moves.len() + 1);
+        positions.push(board.clone());
+        for &mv in &self.moves {
+            board.make_move(mv);
+            positions.push(board.clone());
+        }
+        positions
+    }
+
+    /// The position after the last move
+    #[must_use]
+    pub fn final_position(&self) -> Board {
+        let mut board = self.start.clone();
+        for &mv in &self.moves {
+            board.make_move(mv);
+        }
+        board
+    }
+
+    /// The moves in SAN
+    #[must_use]
+    pub fn san_moves(&self) -> Vec<String> {
+        let mut board = self.start.clone();
+        self.moves
+            .iter()
+            .map(|mv| {
+                let san = board.move_to_san(mv);
+                board.make_move(*mv);
+                san
+            })
+            .collect()
+    }
+
+    /// Movetext tokens: move numbers, moves and the result
+    fn movetext(&self) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut number = 1;
+        let mut white_to_move = self.start.white_to_move();
+        if !white_to_move {
+            tokens.push("1...".to_string());
+        }
+        for san in self.san_moves() {
+            if white_to_move {
+                tokens.push(format!("{number}."));
+            } else {
+                number += 1;
+            }
+            tokens.push(san);
+            white_to_move = !white_to_move;
+        }
+        tokens.push(self.result.map_or("*", GameResult::as_pgn).to_string());
+        tokens
+    }
+}
+
+/// Writes the game as PGN: tags, a blank line and the movetext
+impl fmt::Display for PgnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in SEVEN_TAG_ROSTER {
+            let default = if name == "Date" { "????.??.??" } else { "?" };
+            write_tag(f, name, self.header(name).unwrap_or(default))?;
+        }
+        write_tag(f, "Result", self.result.map_or("*", GameResult::as_pgn))?;
+        for (name, value) in &self.headers {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                write_tag(f, name, value)?;
+            }
+        }
+        let fen = self.start.to_fen();
+        if fen != Board::new().to_fen() {
+            write_tag(f, "SetUp", "1")?;
+            write_tag(f, "FEN", &fen)?;
+        }
+        writeln!(f)?;
+
+        let mut line_len = 0;
+        for token in self.movetext() {
+            if line_len > 0 && line_len + 1 + token.len() > MAX_LINE_LEN {
+                writeln!(f)?;
+                line_len = 0;
+            }
+            if line_len > 0 {
+                write!(f, " ")?;
+                line_len += 1;
+            }
+            write!(f, "{token}")?;
+            line_len += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+fn write_tag(f: &mut fmt::Formatter<'_>, name: &str, value: &str) -> fmt::Result {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(f, "[{name} \"{value}\"]")
+}
+
+/// Name and value of a tag line such as `[Event "Casual game"]`
+fn parse_tag(line: &str) -> Option<(&str, String)> {
+    let inner = line.strip_prefix('[')?.trim_end().strip_suffix(']')?;
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+    Some((name, unescaped))
+}
+
+/// A game being read, with the position after its moves so far
+#[derive(Default)]
+struct PendingGame {
+    game: PgnGame,
+    board: Board,
+    /// Whether the game's movetext has started
+    movetext: bool,
+}
+
+/// Parse every game of a PGN file.
+///
+/// A game ends at its result token, or at the next tag after its movetext.
+/// Malformed tag lines and `%` escape lines are ignored.
+///
+/// # Errors
+///
+/// Returns the first invalid `FEN` tag or illegal move, with its line.
+pub fn parse_games(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = Vec::new();
+    let mut pending: Option<PendingGame> = None;
+    let mut in_comment = false;
+    let mut variation_depth = 0usize;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let trimmed = line.trim();
+        if !in_comment && variation_depth == 0 {
+            if trimmed.starts_with('%') {
+                continue;
+            }
+            if trimmed.starts_with('[') {
+                // A tag after movetext starts the next game
+                if pending.as_ref().is_some_and(|p| p.movetext) {
+                    games.extend(pending.take().map(|p| p.game));
+                }
+                let p = pending.get_or_insert_with(PendingGame::default);
+                match parse_tag(trimmed) {
+                    Some(("FEN", fen)) => {
+                        p.board =
+                            Board::try_from_fen(&fen).map_err(|error| PgnError::InvalidFen {
+                                line: line_no,
+                                error,
+                            })?;
+                        p.game.start = p.board.clone();
+                    }
+                    Some(("Result", result)) => p.game.result = GameResult::from_pgn(&result),
+                    Some(("SetUp", _)) | None => {}
+                    Some((name, value)) => p.game.set_header(name, &value),
+                }
+                continue;
+            }
+        }
+
+        for token in pgn_tokens(trimmed) {
+            match token {
+                // Brace comments do not nest: the first `}` ends one
+                "}" => in_comment = false,
+                _ if in_comment => {}
+                "{" => in_comment = true,
+                ";" => break,
+                "(" => variation_depth += 1,
+                ")" => variation_depth = variation_depth.saturating_sub(1),
+                _ if variation_depth > 0 || is_pgn_noise(token) => {}
+                "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                    let mut game = pending.take().unwrap_or_default().game;
+                    game.result = GameResult::from_pgn(token);
+                    games.push(game);
+                }
+                _ => {
+                    let p = pending.get_or_insert_with(PendingGame::default);
+                    let san = strip_move_number(token);
+                    let mv = p
+                        .board
+                        .parse_san(san.trim_end_matches(['!', '?']))
+                        .map_err(|error| PgnError::InvalidMove {
+                            line: line_no,
+                            san: san.to_string(),
+                            error,
+                        })?;
+                    p.board.make_move(mv);
+                    p.game.moves.push(mv);
+                    p.movetext = true;
+                }
+            }
+        }
+    }
+    games.extend(pending.filter(|p| p.movetext).map(|p| p.game));
+    Ok(games)
+}
+
+/// Split PGN movetext into tokens, with brace and parenthesis delimiters
+/// and `;` as tokens of their own
+fn pgn_tokens(line: &str) -> impl Iterator<Item = &str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        let delimiter = matches!(c, '{' | '}' | '(' | ')' | ';');
+        if c.is_whitespace() || delimiter {
+            if let Some(s) = start.take() {
+                tokens.push(&line[s..i]);
+            }
+            if delimiter {
+                tokens.push(&line[i..=i]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(&line[s..]);
+    }
+    tokens.into_iter()
+}
+
+/// Move numbers (`12.`, `12...`), NAGs (`$1`) and stand-alone annotations
+fn is_pgn_noise(token: &str) -> bool {
+    token.starts_with('$')
+        || token.chars().all(|c| matches!(c, '!' | '?'))
+        || token
+            .trim_end_matches('.')
+            .chars()
+            .all(|c| c.is_ascii_digit())
+}
+
+/// The move of a token written without a space after its number (`12.Nf3`)
+fn strip_move_number(token: &str) -> &str {
+    match token.rsplit_once('.') {
+        Some((number, san))
+            if number
+                .trim_end_matches('.')
+                .chars()
+                .all(|c| c.is_ascii_digit()) =>
+        {
+            san
+        }
+        _ => token,
+    }
+}
+
+/// Append `game` to the PGN file at `path`, creating it if needed
+///
+/// # Errors
+///
+/// Returns any error opening or writing the file.
+pub fn append_game(path: &Path, game: &PgnGame) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{game}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAMES: &str = r#"[Event "Casual \"blitz\" game"]
+[Site "?"]
+[Result "1-0"]
+
+1. e4 {best by test} e5 2.Nf3 (2. f4 exf4) Nc6!? $1 3. Bb5 ; Ruy Lopez
+a6 1-0
+
+[Event "Endgame"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1"]
+
+1... Kd7 2. e4 Ke6 *
+"#;
+
+    #[test]
+    fn test_parse_multiple_games() {
+        let games = parse_games(GAMES).unwrap();
+        assert_eq!(games.len(), 2);
+
+        let ruy = &games[0];
+        assert_eq!(ruy.header("Event"), Some("Casual \"blitz\" game"));
+        assert_eq!(ruy.header("Result"), None);
+        assert_eq!(ruy.result, Some(GameResult::WhiteWins));
+        assert_eq!(ruy.san_moves(), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(ruy.positions().len(), 7);
+
+        let endgame = &games[1];
+        assert_eq!(endgame.result, None);
+        assert_eq!(endgame.header("FEN"), None);
+        assert!(!endgame.start.white_to_move());
+        assert_eq!(
+            endgame.final_position().to_fen(),
+            "8/8/4k3/8/4P3/8/8/4K3 w - - 1 1"
+        );
+    }
+
+    #[test]
+    fn test_braces_inside_a_comment_do_not_nest() {
+        let games = parse_games("1. e4 { a { b } e5 2. Nf3 *").unwrap();
+        assert_eq!(games[0].san_moves(), ["e4", "e5", "Nf3"]);
+    }
+
+    #[test]
+    fn test_export_round_trips() {
+        for game in parse_games(GAMES).unwrap() {
+            let text = game.to_string();
+            let parsed = parse_games(&text).unwrap();
+            assert_eq!(parsed.len(), 1);
+            assert_eq!(parsed[0].moves, game.moves);
+            assert_eq!(parsed[0].result, game.result);
+            for (name, value) in &game.headers {
+                assert_eq!(parsed[0].header(name), Some(value.as_str()));
+            }
+            assert_eq!(parsed[0].start.to_fen(), game.start.to_fen());
+        }
+
+        let endgame = &parse_games(GAMES).unwrap()[1];
+        let text = endgame.to_string();
+        assert!(text.starts_with("[Event \"Endgame\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n"));
+        assert!(text.contains("[Result \"*\"]\n[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b"));
+        assert!(text.ends_with("\n\n1... Kd7 2. e4 Ke6 *\n"));
+    }
+
+    #[test]
+    fn test_long_movetext_is_wrapped() {
+        let mut board = Board::new();
+        for _ in 0..10 {
+            for mv in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                board.make_move_san(mv).unwrap();
+            }
+        }
+        let text = PgnGame::from_board(&board).to_string();
+        let movetext: Vec<&str> = text.lines().skip_while(|l| !l.is_empty()).skip(1).collect();
+        assert!(movetext.len() > 1);
+        assert!(movetext.iter().all(|line| line.len() <= MAX_LINE_LEN));
+        assert_eq!(parse_games(&text).unwrap()[0].moves.len(), 40);
+    }
+
+    #[test]
+    fn test_errors_report_their_line() {
+        assert_eq!(
+            parse_games("[Event \"a\"]\n\n1. e4 e4 *\n").unwrap_err(),
+            PgnError::InvalidMove {
+                line: 3,
+                san: "e4".to_string(),
+                error: SanError::NoMatchingMove {
+                    san: "e4".to_string()
+                },
+            }
+        );
+        assert!(matches!(
+            parse_games("[FEN \"8/8 x\"]\n*\n"),
+            Err(PgnError::InvalidFen { line: 1, .. })
+        ));
+        assert!(parse_games("").unwrap().is_empty());
+    }
+}
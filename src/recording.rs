@@ -0,0 +1,195 @@
+//! Protocol session recorder and replayer.
+//!
+//! `--record session.log` captures every command read from stdin together
+//! with the time (in milliseconds since startup) at which it arrived:
+//!
+//! ```text
+//! 0       uci
+//! 12      isready
+//! 1503    go wtime 60000 btime 60000
+//! 2210    stop
+//! ```
+//!
+//! `--replay session.log` feeds the same commands back to the engine with the
+//! same relative timing, so GUI interaction bugs (races between `stop`,
+//! `ponderhit` and `go`, ...) can be reproduced from a bug report or in CI.
+//! The timestamp and command are separated by a tab when recording; the
+//! replayer accepts any whitespace and ignores blank lines and lines starting
+//! with `#`, so recordings can be trimmed and annotated by hand.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// The active session recording, if any
+static RECORDER: LazyLock<Mutex<Option<Recorder>>> = LazyLock::new(|| Mutex::new(None));
+
+/// An open session recording
+struct Recorder {
+    file: File,
+    started: Instant,
+}
+
+/// Start recording input commands to `path` (truncating any existing file)
+pub fn start_recording(path: impl AsRef<Path>) -> io::Result<()> {
+    let file = File::create(path)?;
+    *RECORDER.lock() = Some(Recorder {
+        file,
+        started: Instant::now(),
+    });
+    Ok(())
+}
+
+/// Stop recording, if a recording is active
+pub fn stop_recording() {
+    *RECORDER.lock() = None;
+}
+
+/// Record a command received on stdin
+pub fn record_input(line: &str) {
+    if let Some(rec) = RECORDER.lock().as_mut() {
+        let ms = rec.started.elapsed().as_millis();
+        // Recording must never take the engine down
+        let _ = writeln!(rec.file, "{ms}\t{line}");
+        let _ = rec.file.flush();
+    }
+}
+
+/// Reader that replays a recorded session, releasing each command at its
+/// recorded offset from the moment the reader was created.
+pub struct ReplayReader {
+    commands: VecDeque<(Duration, String)>,
+    started: Instant,
+    buf: Vec<u8>,
+    pos: usize,
+    realtime: bool,
+}
+
+impl ReplayReader {
+    /// Open a recorded session file
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parse a recorded session (`<ms> <command>` per line)
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut commands = VecDeque::new();
+        for (lineno, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line
+                .trim_start()
+                .split_once(char::is_whitespace)
+                .and_then(|(ms, cmd)| Some((ms.parse::<u64>().ok()?, cmd.trim_start())));
+            let Some((ms, cmd)) = parsed else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected `<ms> <command>`", lineno + 1),
+                ));
+            };
+            commands.push_back((Duration::from_millis(ms), cmd.to_string()));
+        }
+
+        Ok(ReplayReader {
+            commands,
+            started: Instant::now(),
+            buf: Vec::new(),
+            pos: 0,
+            realtime: true,
+        })
+    }
+
+    /// Release commands immediately instead of waiting for their timestamps
+    #[must_use]
+    pub fn immediate(mut self) -> Self {
+        self.realtime = false;
+        self
+    }
+
+    /// The first recorded command, used for protocol auto-detection
+    #[must_use]
+    pub fn first_command(&self) -> Option<&str> {
+        self.commands.front().map(|(_, cmd)| cmd.as_str())
+    }
+}
+
+impl Read for ReplayReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for ReplayReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.buf.len() {
+            if let Some((at, cmd)) = self.commands.pop_front() {
+                if self.realtime {
+                    if let Some(wait) = at.checked_sub(self.started.elapsed()) {
+                        std::thread::sleep(wait);
+                    }
+                }
+                self.buf = cmd.into_bytes();
+                self.buf.push(b'\n');
+                self.pos = 0;
+            }
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.buf.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_yields_recorded_commands() {
+        let text = "# bug report\n0\tuci\n5\tisready\n\n20\tgo depth 1\n";
+        let reader = ReplayReader::parse(text).unwrap().immediate();
+        assert_eq!(reader.first_command(), Some("uci"));
+        let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["uci", "isready", "go depth 1"]);
+    }
+
+    #[test]
+    fn test_replay_honours_timing() {
+        let reader = ReplayReader::parse("0\tisready\n50\tquit\n").unwrap();
+        let start = Instant::now();
+        assert_eq!(reader.lines().count(), 2);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_replay_rejects_malformed_lines() {
+        assert!(ReplayReader::parse("uci\n").is_err());
+    }
+
+    #[test]
+    fn test_record_then_replay_round_trip() {
+        let path = std::env::temp_dir().join(format!("chess_rec_{}.log", std::process::id()));
+        start_recording(&path).unwrap();
+        record_input("position startpos moves e2e4");
+        record_input("go depth 2");
+        stop_recording();
+        record_input("ignored");
+
+        let reader = ReplayReader::open(&path).unwrap().immediate();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["position startpos moves e2e4", "go depth 2"]);
+    }
+}
@@ -0,0 +1,208 @@
+//! SMP scaling report.
+//!
+//! `scaling` (a UCI console command, or `chess_engine --scaling`) searches
+//! the bench positions to a fixed depth with 1, 2, 4, ... threads up to a
+//! maximum and prints, for each thread count, the node rate and the time
+//! to reach the depth, both also as a factor of the single-threaded run.
+//! Node rate shows how well the hardware scales; time to depth shows how
+//! much of that extra speed Lazy SMP turns into a faster search, which is
+//! what a Threads setting should be picked by.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::board::nnue::NnueNetwork;
+use crate::board::search::smp::{smp_search, SmpConfig};
+use crate::board::{Board, SearchInfoCallback, SearchState};
+use crate::output::ProtocolWriter;
+use crate::selftest::BENCH_FENS;
+
+/// Depth searched in each position unless another is asked for
+pub const DEFAULT_SCALING_DEPTH: u32 = 10;
+
+/// Hash size for each search; every position starts with a fresh table
+const SCALING_TT_MB: usize = 64;
+
+/// Thread counts to measure: powers of two below `max_threads`, then
+/// `max_threads` itself
+#[must_use]
+pub fn thread_counts(max_threads: usize) -> Vec<usize> {
+    let max_threads = max_threads.max(1);
+    let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |t| t.checked_mul(2))
+        .take_while(|&t| t < max_threads)
+        .collect();
+    counts.push(max_threads);
+    counts
+}
+
+/// Totals over the bench positions for one thread count
+#[derive(Debug, Clone)]
+pub struct ScalingResult {
+    pub threads: usize,
+    pub nodes: u64,
+    /// Wall time of the searches, helpers winding down included
+    pub elapsed: Duration,
+    /// Time until the main thread completed the target depth
+    pub time_to_depth: Duration,
+}
+
+impl ScalingResult {
+    /// Nodes per second over all threads
+    #[must_use]
+    pub fn nps(&self) -> u64 {
+        let micros = self.elapsed.as_micros().max(1);
+        u64::try_from(u128::from(self.nodes) * 1_000_000 / micros).unwrap_or(u64::MAX)
+    }
+}
+
+/// Outcome of the whole scaling run
+#[derive(Debug, Clone)]
+pub struct ScalingReport {
+    pub depth: u32,
+    /// One result per thread count, single-threaded first
+    pub results: Vec<ScalingResult>,
+}
+
+impl ScalingReport {
+    /// One `scaling threads <n> ...` line per thread count
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.results.iter().map(|r| self.line(r)).collect()
+    }
+
+    /// The line for `result`, with factors against the first result
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn line(&self, result: &ScalingResult) -> String {
+        let (nps_factor, ttd_factor) = self.results.first().map_or((1.0, 1.0), |base| {
+            (
+                result.nps() as f64 / base.nps().max(1) as f64,
+                base.time_to_depth.as_secs_f64() / result.time_to_depth.as_secs_f64().max(1e-6),
+            )
+        });
+        format!(
+            "scaling threads {} depth {} nodes {} nps {} nps_factor {nps_factor:.2} \
+             ttd_ms {} ttd_factor {ttd_factor:.2}",
+            result.threads,
+            self.depth,
+            result.nodes,
+            result.nps(),
+            result.time_to_depth.as_millis(),
+        )
+    }
+}
+
+/// Search every bench position to `depth` on `threads` threads
+#[must_use]
+pub fn measure_threads(
+    threads: usize,
+    depth: u32,
+    nnue: Option<&Arc<NnueNetwork>>,
+) -> ScalingResult {
+    let mut result = ScalingResult {
+        threads,
+        nodes: 0,
+        elapsed: Duration::ZERO,
+        time_to_depth: Duration::ZERO,
+    };
+    for fen in BENCH_FENS {
+        let board = Board::from_fen(fen);
+        let mut state = SearchState::new(SCALING_TT_MB);
+        state.tables.nnue = nnue.cloned();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let start = Instant::now();
+        // Microseconds until the main thread finished `depth`; the helpers
+        // searching a ply deeper are stopped there too
+        let reached = Arc::new(AtomicU64::new(0));
+        let callback: SearchInfoCallback = {
+            let stop = Arc::clone(&stop);
+            let reached = Arc::clone(&reached);
+            Arc::new(move |info| {
+                if !info.progress && info.depth >= depth {
+                    let micros = u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX);
+                    let _ =
+                        reached.compare_exchange(0, micros, Ordering::Relaxed, Ordering::Relaxed);
+                    stop.store(true, Ordering::Relaxed);
+                }
+            })
+        };
+        let config = SmpConfig::with_threads(threads)
+            .depth(depth)
+            .with_callback(callback);
+        let _ = smp_search(&board, &mut state, config, stop);
+        let elapsed = start.elapsed();
+
+        let reached = reached.load(Ordering::Relaxed);
+        result.nodes += state.stats.nodes;
+        result.elapsed += elapsed;
+        result.time_to_depth += if reached == 0 {
+            elapsed
+        } else {
+            Duration::from_micros(reached)
+        };
+    }
+    result
+}
+
+/// Measure every thread count up to `max_threads`, printing each line to
+/// `out` with `prefix` (`info string ` under UCI) as soon as it is known
+pub fn run_scaling(
+    nnue: Option<&Arc<NnueNetwork>>,
+    max_threads: usize,
+    depth: u32,
+    prefix: &str,
+    out: &dyn ProtocolWriter,
+) -> ScalingReport {
+    let mut report = ScalingReport {
+        depth,
+        results: Vec::new(),
+    };
+    for threads in thread_counts(max_threads) {
+        report.results.push(measure_threads(threads, depth, nnue));
+        if let Some(last) = report.results.last() {
+            crate::send_line!(out, "{prefix}{}", report.line(last));
+            let _ = out.flush();
+        }
+    }
+    report
+}
+
+/// Hardware threads available, the default maximum for a scaling run
+#[must_use]
+pub fn default_max_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_counts() {
+        assert_eq!(thread_counts(0), [1]);
+        assert_eq!(thread_counts(1), [1]);
+        assert_eq!(thread_counts(4), [1, 2, 4]);
+        assert_eq!(thread_counts(6), [1, 2, 4, 6]);
+    }
+
+    #[test]
+    fn test_scaling_report_factors() {
+        let one = measure_threads(1, 3, None);
+        let two = measure_threads(2, 3, None);
+        assert!(one.nodes > 0 && two.nodes > 0);
+        assert!(one.time_to_depth <= one.elapsed);
+
+        let report = ScalingReport {
+            depth: 3,
+            results: vec![one, two],
+        };
+        let lines = report.lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("scaling threads 1 depth 3 "));
+        assert!(lines[0].contains("nps_factor 1.00"));
+        assert!(lines[0].ends_with("ttd_factor 1.00"));
+        assert!(lines[1].starts_with("scaling threads 2 "));
+    }
+}
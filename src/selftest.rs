@@ -0,0 +1,301 @@
+//! Build self-test.
+//!
+//! `chess_engine --selftest` runs three quick checks and prints `PASS` or
+//! `FAIL` for each, so packagers and users can verify a build without a GUI:
+//!
+//! - **perft**: node counts of six standard positions against the published
+//!   values, which catches move generator miscompilation.
+//! - **bench**: a fixed-node search over a few positions. Its signature (a
+//!   hash of the nodes searched and the moves chosen) is printed for
+//!   comparison between builds and must come out the same on a second run
+//!   with fresh tables. The effective branching factor and time to depth
+//!   printed with it track search efficiency across versions.
+//! - **symmetry**: every position must evaluate the same as its
+//!   colour-flipped mirror with the classical evaluation, and nearly the same
+//!   with NNUE when a network is loaded.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::board::nnue::NnueNetwork;
+use crate::board::{search, Board, SearchConfig, SearchResult, SearchState};
+use crate::output::ProtocolWriter;
+
+/// A perft position with its published node count
+struct PerftCase {
+    name: &'static str,
+    fen: &'static str,
+    depth: usize,
+    nodes: u64,
+}
+
+/// The standard perft positions, at depths that take well under a second
+const PERFT_CASES: [PerftCase; 6] = [
+    PerftCase {
+        name: "startpos",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        depth: 4,
+        nodes: 197_281,
+    },
+    PerftCase {
+        name: "kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        depth: 3,
+        nodes: 97_862,
+    },
+    PerftCase {
+        name: "position3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        depth: 4,
+        nodes: 43_238,
+    },
+    PerftCase {
+        name: "position4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        depth: 3,
+        nodes: 9_467,
+    },
+    PerftCase {
+        name: "position5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        depth: 3,
+        nodes: 62_379,
+    },
+    PerftCase {
+        name: "position6",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        depth: 3,
+        nodes: 89_890,
+    },
+];
+
+/// Positions searched by the bench and checked for evaluation symmetry
+pub(crate) const BENCH_FENS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N2N2/PP2BPPP/R2QKB1R b KQ - 0 8",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+/// Node limit for each bench search
+const BENCH_NODES: u64 = 10_000;
+
+/// Hash size for the bench; a fresh table keeps the signature reproducible
+const BENCH_TT_MB: usize = 16;
+
+/// Largest NNUE difference (cp) allowed between a position and its mirror.
+///
+/// The network has separate output weights for each colour, so mirrored
+/// positions evaluate slightly differently; corrupt weights or a miscompiled
+/// SIMD kernel give differences far beyond this.
+const NNUE_SYMMETRY_TOLERANCE: i32 = 50;
+
+/// Outcome of one self-test check
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    /// What was measured, or what went wrong
+    pub detail: String,
+}
+
+/// Outcome of the whole self-test
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    /// Whether every check passed
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+}
+
+/// Run perft on the standard positions
+#[must_use]
+pub fn check_perft() -> CheckResult {
+    let failures: Vec<String> = PERFT_CASES
+        .iter()
+        .filter_map(|case| {
+            let nodes = Board::from_fen(case.fen).perft(case.depth);
+            (nodes != case.nodes).then(|| {
+                format!(
+                    "{} depth {} got {nodes} expected {}",
+                    case.name, case.depth, case.nodes
+                )
+            })
+        })
+        .collect();
+    CheckResult {
+        name: "perft",
+        passed: failures.is_empty(),
+        detail: if failures.is_empty() {
+            format!("{} positions", PERFT_CASES.len())
+        } else {
+            failures.join(", ")
+        },
+    }
+}
+
+/// Outcome of the bench searches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchReport {
+    /// Nodes searched over every position
+    pub nodes: u64,
+    /// Hash of the nodes searched and the moves chosen
+    pub signature: u64,
+    /// Effective branching factor, averaged over the positions
+    pub ebf: f64,
+    /// Deepest depth every position completed
+    pub depth: u32,
+    /// Time to reach `depth`, summed over the positions
+    pub time_to_depth_ms: u64,
+}
+
+/// Run one fixed-node search per bench position, each with fresh tables
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn run_bench(nnue: Option<&Arc<NnueNetwork>>) -> BenchReport {
+    let stop = AtomicBool::new(false);
+    let mut total_nodes = 0u64;
+    let mut signature = 0u64;
+    let mut results = Vec::with_capacity(BENCH_FENS.len());
+    for fen in BENCH_FENS {
+        let mut board = Board::from_fen(fen);
+        let mut state = SearchState::new(BENCH_TT_MB);
+        state.tables.nnue = nnue.cloned();
+        let config = SearchConfig::default()
+            .with_nodes(BENCH_NODES)
+            .with_ponder(false);
+        let result = search(&mut board, &mut state, config, &stop);
+        let nodes = state.stats.nodes;
+        let mv = result.best_move.map_or(0, |m| m.history_index() as u64 + 1);
+        total_nodes += nodes;
+        signature = (signature ^ nodes ^ (mv << 32)).wrapping_mul(0x0000_0100_0000_01B3);
+        results.push(result);
+    }
+
+    let factors: Vec<f64> = results
+        .iter()
+        .filter_map(SearchResult::effective_branching_factor)
+        .collect();
+    let depth = results
+        .iter()
+        .map(|r| r.iterations.last().map_or(0, |it| it.depth))
+        .min()
+        .unwrap_or(0);
+    let time_to_depth_ms = results
+        .iter()
+        .filter_map(|r| r.iterations.iter().find(|it| it.depth == depth))
+        .map(|it| it.time_ms)
+        .sum();
+    BenchReport {
+        nodes: total_nodes,
+        signature,
+        ebf: factors.iter().sum::<f64>() / factors.len().max(1) as f64,
+        depth,
+        time_to_depth_ms,
+    }
+}
+
+/// Run the bench twice and check that it is reproducible
+#[must_use]
+pub fn check_bench(nnue: Option<&Arc<NnueNetwork>>) -> CheckResult {
+    let bench = run_bench(nnue);
+    let repeat = run_bench(nnue).signature;
+    let signature = bench.signature;
+    CheckResult {
+        name: "bench",
+        passed: bench.nodes > 0 && signature == repeat,
+        detail: if signature == repeat {
+            format!(
+                "nodes {} signature {signature:016x} ebf {:.2} time to depth {} {}ms",
+                bench.nodes, bench.ebf, bench.depth, bench.time_to_depth_ms
+            )
+        } else {
+            format!("signature {signature:016x} then {repeat:016x}")
+        },
+    }
+}
+
+/// Check that each bench position evaluates the same as its mirror
+#[must_use]
+pub fn check_symmetry(nnue: Option<&Arc<NnueNetwork>>) -> CheckResult {
+    let mut failures = Vec::new();
+    for fen in BENCH_FENS {
+        let board = Board::from_fen(fen);
+        let flipped = board.color_flipped();
+        let (eval, mirror) = (board.evaluate(), flipped.evaluate());
+        if eval != mirror {
+            failures.push(format!("eval {eval} mirror {mirror} for {fen}"));
+        }
+        if let Some(network) = nnue {
+            let (eval, mirror) = (board.evaluate_nnue(network), flipped.evaluate_nnue(network));
+            if (eval - mirror).abs() > NNUE_SYMMETRY_TOLERANCE {
+                failures.push(format!("nnue {eval} mirror {mirror} for {fen}"));
+            }
+        }
+    }
+    CheckResult {
+        name: "symmetry",
+        passed: failures.is_empty(),
+        detail: if failures.is_empty() {
+            let evals = if nnue.is_some() {
+                "eval and nnue"
+            } else {
+                "eval"
+            };
+            format!("{} positions, {evals}", BENCH_FENS.len())
+        } else {
+            failures.join(", ")
+        },
+    }
+}
+
+/// Run every check, printing one `PASS`/`FAIL` line per check and a
+/// summary to `out`
+pub fn run_selftest(nnue: Option<&Arc<NnueNetwork>>, out: &dyn ProtocolWriter) -> SelfTestReport {
+    let report = SelfTestReport {
+        checks: vec![check_perft(), check_bench(nnue), check_symmetry(nnue)],
+    };
+    for check in &report.checks {
+        crate::send_line!(
+            out,
+            "{} {}: {}",
+            if check.passed { "PASS" } else { "FAIL" },
+            check.name,
+            check.detail
+        );
+    }
+    crate::send_line!(
+        out,
+        "selftest {}",
+        if report.passed() { "PASS" } else { "FAIL" }
+    );
+    let _ = out.flush();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::EngineController;
+    use crate::output::CaptureWriter;
+
+    #[test]
+    fn test_selftest_passes() {
+        let out = CaptureWriter::new();
+        let network = EngineController::new(1)
+            .with_search_state_ref(|state| state.tables.nnue.clone())
+            .flatten();
+        let report = run_selftest(network.as_ref(), &out);
+        let lines = out.lines();
+        assert!(report.passed(), "{lines:?}");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("PASS perft: 6 positions"));
+        assert!(lines[1].starts_with("PASS bench: nodes "));
+        assert_eq!(lines[3], "selftest PASS");
+    }
+}
@@ -0,0 +1,398 @@
+//! Read-only result cache shared between engine processes.
+//!
+//! A cache file holds precomputed results keyed by position hash: WDL
+//! and DTZ results of tablebase positions and evaluations of common opening
+//! positions. It is built once with [`CacheBuilder`] and opened by every
+//! engine with the `SharedCache` option. The file is memory-mapped
+//! read-only, so dozens of engine processes on one machine share a single
+//! copy in the operating system's page cache instead of each reading the
+//! tables from disk.
+//!
+//! File layout, little-endian:
+//!
+//! ```text
+//! magic "CECACHE1"   8 bytes
+//! entry count        u32
+//! max WDL pieces     u8   (largest piece count among WDL and DTZ entries)
+//! min eval pieces    u8   (smallest piece count among eval entries)
+//! padding            2 bytes
+//! entries            16 bytes each, sorted by (key, kind):
+//!                    key u64, value i32, kind u8, 3 padding bytes
+//! ```
+//!
+//! Values are from the side to move's point of view: a WDL entry holds 1
+//! (win), 0 (draw) or -1 (loss), a DTZ entry the plies to the next capture
+//! or pawn move with best play (positive when winning, negative when
+//! losing, as in Syzygy tables), an eval entry a score in centipawns. The
+//! piece counts in the header let the search skip probes for positions the
+//! cache cannot hold.
+//!
+//! A mapped file must not shrink while engines have it open: reading a
+//! page past its new end kills the process with `SIGBUS`. Do not truncate
+//! or rewrite a cache file in place; [`CacheBuilder::write`] writes a new
+//! file and renames it over the old one, which leaves open mappings on the
+//! old contents.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::board::{Board, Color};
+use crate::engine::adjudicate::{TablebaseProbe, Wdl};
+
+/// File magic and format version
+const MAGIC: &[u8; 8] = b"CECACHE1";
+
+/// Size of the file header in bytes
+const HEADER_SIZE: usize = 16;
+
+/// Size of one entry in bytes
+const ENTRY_SIZE: usize = 16;
+
+/// Kind of result stored in an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum CacheKind {
+    Wdl = 0,
+    Eval = 1,
+    Dtz = 2,
+}
+
+/// Number of pieces on `board`, kings included
+fn piece_count(board: &Board) -> u32 {
+    board.occupied_by(Color::White).popcount() + board.occupied_by(Color::Black).popcount()
+}
+
+/// Bytes of an open cache file
+enum Storage {
+    /// Shared read-only mapping of the whole file
+    #[cfg(target_os = "linux")]
+    Mapped {
+        ptr: std::ptr::NonNull<u8>,
+        len: usize,
+    },
+    /// The file read into memory, where mapping is unavailable
+    #[cfg_attr(target_os = "linux", allow(dead_code))]
+    Owned(Vec<u8>),
+}
+
+impl Storage {
+    /// Map `file` of `len` bytes read-only
+    #[cfg(target_os = "linux")]
+    fn open(file: &File, len: usize) -> io::Result<Self> {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: a fresh mapping of a file we hold open; the kernel picks
+        // the address and the mapping stays valid after the file is closed
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let ptr = std::ptr::NonNull::new(ptr.cast()).ok_or_else(io::Error::last_os_error)?;
+        Ok(Storage::Mapped { ptr, len })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn open(mut file: &File, len: usize) -> io::Result<Self> {
+        use std::io::Read;
+
+        let mut bytes = Vec::with_capacity(len);
+        file.read_to_end(&mut bytes)?;
+        Ok(Storage::Owned(bytes))
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            // SAFETY: the mapping covers `len` readable bytes until drop, as
+            // long as the file is not truncated (see the module docs)
+            #[cfg(target_os = "linux")]
+            Storage::Mapped { ptr, len } => unsafe {
+                std::slice::from_raw_parts(ptr.as_ptr(), *len)
+            },
+            Storage::Owned(bytes) => bytes,
+        }
+    }
+}
+
+impl Drop for Storage {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Storage::Mapped { ptr, len } = self {
+            // SAFETY: mapped in `open` with this length and not yet unmapped
+            unsafe { libc::munmap(ptr.as_ptr().cast(), *len) };
+        }
+    }
+}
+
+// The mapping is read-only, so it can be read from any thread
+unsafe impl Send for Storage {}
+unsafe impl Sync for Storage {}
+
+/// An open cache file
+pub struct SharedCache {
+    storage: Storage,
+    entries: usize,
+    max_wdl_pieces: u32,
+    min_eval_pieces: u32,
+}
+
+impl SharedCache {
+    /// Open and map a cache file
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| invalid_data("cache file too large"))?;
+        if len < HEADER_SIZE {
+            return Err(invalid_data("cache file too short"));
+        }
+        let storage = Storage::open(&file, len)?;
+        let bytes = storage.bytes();
+        if &bytes[..8] != MAGIC {
+            return Err(invalid_data("not a cache file"));
+        }
+        let entries = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        if bytes.len() != HEADER_SIZE + entries * ENTRY_SIZE {
+            return Err(invalid_data(
+                "cache file size does not match its entry count",
+            ));
+        }
+        Ok(SharedCache {
+            max_wdl_pieces: u32::from(bytes[12]),
+            min_eval_pieces: u32::from(bytes[13]),
+            storage,
+            entries,
+        })
+    }
+
+    /// Number of entries in the cache
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries
+    }
+
+    /// Whether the cache has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries == 0
+    }
+
+    /// WDL result of `board` for the side to move, if cached
+    #[must_use]
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        if piece_count(board) > self.max_wdl_pieces {
+            return None;
+        }
+        Some(match self.find(board.hash(), CacheKind::Wdl)? {
+            v if v > 0 => Wdl::Win,
+            0 => Wdl::Draw,
+            _ => Wdl::Loss,
+        })
+    }
+
+    /// Distance to zeroing of `board` for the side to move, if cached
+    #[must_use]
+    pub fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        if piece_count(board) > self.max_wdl_pieces {
+            return None;
+        }
+        self.find(board.hash(), CacheKind::Dtz)
+    }
+
+    /// Evaluation of `board` for the side to move, if cached
+    #[must_use]
+    pub fn probe_eval(&self, board: &Board) -> Option<i32> {
+        if piece_count(board) < self.min_eval_pieces {
+            return None;
+        }
+        self.find(board.hash(), CacheKind::Eval)
+    }
+
+    /// Binary search for the entry with `key` and `kind`
+    fn find(&self, key: u64, kind: CacheKind) -> Option<i32> {
+        let bytes = self.storage.bytes();
+        let entry = |i: usize| {
+            let e = &bytes[HEADER_SIZE + i * ENTRY_SIZE..][..ENTRY_SIZE];
+            let key = u64::from_le_bytes(e[..8].try_into().unwrap());
+            let value = i32::from_le_bytes(e[8..12].try_into().unwrap());
+            ((key, e[12]), value)
+        };
+        let target = (key, kind as u8);
+        let (mut lo, mut hi) = (0, self.entries);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let (found, value) = entry(mid);
+            match found.cmp(&target) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Some(value),
+            }
+        }
+        None
+    }
+}
+
+impl TablebaseProbe for SharedCache {
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        SharedCache::probe_wdl(self, board)
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        SharedCache::probe_dtz(self, board)
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Collects results and writes them as a cache file
+#[derive(Debug, Clone, Default)]
+pub struct CacheBuilder {
+    entries: Vec<(u64, CacheKind, i32)>,
+    max_wdl_pieces: u32,
+    min_eval_pieces: Option<u32>,
+}
+
+impl CacheBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the WDL result of `board` for the side to move
+    pub fn add_wdl(&mut self, board: &Board, wdl: Wdl) -> &mut Self {
+        let value = match wdl {
+            Wdl::Win => 1,
+            Wdl::Draw => 0,
+            Wdl::Loss => -1,
+        };
+        self.entries.push((board.hash(), CacheKind::Wdl, value));
+        self.max_wdl_pieces = self.max_wdl_pieces.max(piece_count(board));
+        self
+    }
+
+    /// Add the distance to zeroing of `board` (plies, negative when
+    /// losing) for the side to move
+    pub fn add_dtz(&mut self, board: &Board, dtz: i32) -> &mut Self {
+        self.entries.push((board.hash(), CacheKind::Dtz, dtz));
+        self.max_wdl_pieces = self.max_wdl_pieces.max(piece_count(board));
+        self
+    }
+
+    /// Add the evaluation of `board` (cp) for the side to move
+    pub fn add_eval(&mut self, board: &Board, eval: i32) -> &mut Self {
+        self.entries.push((board.hash(), CacheKind::Eval, eval));
+        let pieces = piece_count(board);
+        self.min_eval_pieces = Some(self.min_eval_pieces.map_or(pieces, |m| m.min(pieces)));
+        self
+    }
+
+    /// Write the cache file; a position added twice keeps its last result
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut entries = self.entries.clone();
+        // Stable sort, so the last of equal keys ends up last
+        entries.sort_by_key(|&(key, kind, _)| (key, kind));
+        entries.reverse();
+        entries.dedup_by_key(|&mut (key, kind, _)| (key, kind));
+        entries.reverse();
+
+        let count = u32::try_from(entries.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many entries"))?;
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + entries.len() * ENTRY_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&count.to_le_bytes());
+        bytes.push(self.max_wdl_pieces.min(32) as u8);
+        // No eval entries: a minimum no position reaches
+        bytes.push(self.min_eval_pieces.unwrap_or(u32::from(u8::MAX)).min(255) as u8);
+        bytes.extend_from_slice(&[0; 2]);
+        for (key, kind, value) in entries {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+            bytes.extend_from_slice(&[kind as u8, 0, 0, 0]);
+        }
+        // Replace rather than truncate the file, which engines may have
+        // mapped
+        let path = path.as_ref();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        File::create(&temp)?.write_all(&bytes)?;
+        std::fs::rename(&temp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("chess_cache_{name}_{}.bin", std::process::id()))
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let krk = Board::from_fen("8/8/8/4k3/8/8/8/R3K3 w - - 0 1");
+        let kk = Board::from_fen("8/8/8/4k3/8/8/8/4K3 b - - 0 1");
+        let start = Board::new();
+        let mut builder = CacheBuilder::new();
+        builder
+            .add_wdl(&krk, Wdl::Win)
+            .add_wdl(&kk, Wdl::Loss)
+            .add_wdl(&kk, Wdl::Draw)
+            .add_dtz(&krk, 31)
+            .add_eval(&start, 25);
+
+        let path = temp_path("round_trip");
+        builder.write(&path).unwrap();
+        let cache = SharedCache::open(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.probe_wdl(&krk), Some(Wdl::Win));
+        assert_eq!(cache.probe_wdl(&kk), Some(Wdl::Draw));
+        assert_eq!(cache.probe_dtz(&krk), Some(31));
+        assert_eq!(cache.probe_dtz(&kk), None);
+        assert_eq!(cache.probe_eval(&start), Some(25));
+        // Kinds are kept apart, and the piece limits skip out-of-range probes
+        assert_eq!(cache.probe_eval(&krk), None);
+        assert_eq!(cache.probe_wdl(&start), None);
+        let other = Board::from_fen("8/8/8/4k3/8/8/8/Q3K3 w - - 0 1");
+        assert_eq!(cache.probe_wdl(&other), None);
+    }
+
+    #[test]
+    fn test_rewriting_leaves_open_caches_intact() {
+        let krk = Board::from_fen("8/8/8/4k3/8/8/8/R3K3 w - - 0 1");
+        let path = temp_path("rewrite");
+        let mut builder = CacheBuilder::new();
+        builder.add_wdl(&krk, Wdl::Win).add_dtz(&krk, 31);
+        builder.write(&path).unwrap();
+        let cache = SharedCache::open(&path).unwrap();
+
+        // A smaller file written over the open one
+        CacheBuilder::new().write(&path).unwrap();
+        let rewritten = SharedCache::open(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(cache.probe_dtz(&krk), Some(31));
+        assert!(rewritten.is_empty());
+    }
+
+    #[test]
+    fn test_open_rejects_other_files() {
+        let path = temp_path("invalid");
+        std::fs::write(&path, b"not a cache file at all").unwrap();
+        let err = SharedCache::open(&path).err().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(SharedCache::open(temp_path("missing")).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Raw speed benchmark.
+//!
+//! `speedtest` (a UCI console command, or `chess_engine --speedtest`) times
+//! the engine's building blocks on the bench positions and prints one rate
+//! per line, so machines and builds can be compared:
+//!
+//! - **movegen**: legal moves generated per second
+//! - **makemove**: make/unmake pairs per second
+//! - **eval**: classical evaluations per second, plus full NNUE evaluations
+//!   (accumulator refresh included) when a network is loaded
+//! - **qsearch**: quiescence search nodes per second
+//!
+//! Every measurement does a fixed amount of work, so the rates are
+//! comparable between runs and machines; only the elapsed time varies.
+
+use std::hint::black_box;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::board::nnue::NnueNetwork;
+use crate::board::{quiescence_search, Board, SearchState};
+use crate::output::ProtocolWriter;
+use crate::selftest::BENCH_FENS;
+
+/// Passes over the bench positions for each measurement
+const MOVEGEN_ROUNDS: u32 = 100_000;
+const MAKEMOVE_ROUNDS: u32 = 20_000;
+const EVAL_ROUNDS: u32 = 20_000;
+const NNUE_ROUNDS: u32 = 50_000;
+const QSEARCH_ROUNDS: u32 = 2_000;
+
+/// Hash size for the quiescence measurement (the table is barely used)
+const QSEARCH_TT_MB: usize = 1;
+
+/// One measured rate
+#[derive(Debug, Clone)]
+pub struct SpeedResult {
+    pub name: &'static str,
+    /// Operations done (moves, pairs, evaluations or nodes)
+    pub count: u64,
+    pub elapsed: Duration,
+}
+
+impl SpeedResult {
+    /// Operations per second
+    #[must_use]
+    pub fn per_second(&self) -> u64 {
+        let micros = self.elapsed.as_micros().max(1);
+        u64::try_from(u128::from(self.count) * 1_000_000 / micros).unwrap_or(u64::MAX)
+    }
+}
+
+/// Outcome of the whole speed test
+#[derive(Debug, Clone, Default)]
+pub struct SpeedReport {
+    pub results: Vec<SpeedResult>,
+}
+
+impl SpeedReport {
+    /// One `speedtest <name> <rate>/s ...` line per measurement
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .map(|r| {
+                format!(
+                    "speedtest {} {}/s count {} time_ms {}",
+                    r.name,
+                    r.per_second(),
+                    r.count,
+                    r.elapsed.as_millis()
+                )
+            })
+            .collect()
+    }
+}
+
+fn bench_boards() -> Vec<Board> {
+    BENCH_FENS.iter().map(|fen| Board::from_fen(fen)).collect()
+}
+
+/// Time `rounds` passes of `f` over the bench positions; `f` returns the
+/// operations it did
+fn measure(name: &'static str, rounds: u32, mut f: impl FnMut(&mut Board) -> u64) -> SpeedResult {
+    let mut boards = bench_boards();
+    let start = Instant::now();
+    let mut count = 0;
+    for _ in 0..rounds {
+        for board in &mut boards {
+            count += f(board);
+        }
+    }
+    SpeedResult {
+        name,
+        count,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Legal move generation
+#[must_use]
+pub fn measure_movegen(rounds: u32) -> SpeedResult {
+    measure("movegen", rounds, |board| {
+        black_box(board.generate_moves()).len() as u64
+    })
+}
+
+/// Making and unmaking every legal move
+#[must_use]
+pub fn measure_makemove(rounds: u32) -> SpeedResult {
+    let moves: Vec<_> = bench_boards()
+        .iter_mut()
+        .map(Board::generate_moves)
+        .collect();
+    let mut index = 0;
+    measure("makemove", rounds, |board| {
+        let list = &moves[index % moves.len()];
+        index += 1;
+        for &m in list {
+            let info = board.make_move(m);
+            black_box(board.hash());
+            board.unmake_move(m, info);
+        }
+        list.len() as u64
+    })
+}
+
+/// Classical evaluation
+#[must_use]
+pub fn measure_eval(rounds: u32) -> SpeedResult {
+    measure("eval", rounds, |board| {
+        black_box(board.evaluate());
+        1
+    })
+}
+
+/// NNUE evaluation from scratch
+#[must_use]
+pub fn measure_nnue(network: &NnueNetwork, rounds: u32) -> SpeedResult {
+    measure("nnue", rounds, |board| {
+        black_box(board.evaluate_nnue(network));
+        1
+    })
+}
+
+/// Quiescence search from each bench position
+#[must_use]
+pub fn measure_qsearch(nnue: Option<&Arc<NnueNetwork>>, rounds: u32) -> SpeedResult {
+    let stop = AtomicBool::new(false);
+    let mut state = SearchState::new(QSEARCH_TT_MB);
+    state.tables.nnue = nnue.cloned();
+    measure("qsearch", rounds, |board| {
+        black_box(quiescence_search(board, &mut state, &stop));
+        state.stats.nodes
+    })
+}
+
+/// Run every measurement, printing one line each to `out` with `prefix`
+/// (`info string ` under UCI)
+pub fn run_speedtest(
+    nnue: Option<&Arc<NnueNetwork>>,
+    prefix: &str,
+    out: &dyn ProtocolWriter,
+) -> SpeedReport {
+    let mut report = SpeedReport::default();
+    report.results.push(measure_movegen(MOVEGEN_ROUNDS));
+    report.results.push(measure_makemove(MAKEMOVE_ROUNDS));
+    report.results.push(measure_eval(EVAL_ROUNDS));
+    if let Some(network) = nnue {
+        report.results.push(measure_nnue(network, NNUE_ROUNDS));
+    }
+    report.results.push(measure_qsearch(nnue, QSEARCH_ROUNDS));
+
+    for line in report.lines() {
+        crate::send_line!(out, "{prefix}{line}");
+    }
+    let _ = out.flush();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurements_count_fixed_work() {
+        let movegen = measure_movegen(2);
+        let makemove = measure_makemove(2);
+        // Every generated move is made once per round
+        assert_eq!(movegen.count, makemove.count);
+        assert!(movegen.count > 0);
+        assert_eq!(measure_eval(3).count, 3 * BENCH_FENS.len() as u64);
+
+        let qsearch = measure_qsearch(None, 1);
+        assert!(qsearch.count >= BENCH_FENS.len() as u64);
+
+        let report = SpeedReport {
+            results: vec![movegen],
+        };
+        assert!(report.lines()[0].starts_with("speedtest movegen "));
+    }
+}
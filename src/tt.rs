@@ -6,8 +6,24 @@
 //! This implementation uses lockless hashing for thread-safe access
 //! in multi-threaded (Lazy SMP) search. Entries are stored as atomic
 //! u64 pairs using XOR verification to detect torn reads.
+//!
+//! Entries are aged by search generation. Only the low 6 bits of the
+//! search's `u16` generation are stored, and an entry's age is the
+//! difference modulo 64, so ages stay correct across `u16` wraparound (64
+//! divides 65536) and an entry written by the current search always has age
+//! zero. Entries `TT_STALE_AGE` or more generations old are replaced before
+//! any fresher entry, whatever their depth.
+//!
+//! The table can optionally be backed by huge pages, which cuts TLB misses
+//! on large hash sizes. This is only attempted on Linux (via
+//! `madvise(MADV_HUGEPAGE)`); elsewhere, or if the kernel refuses, the
+//! table silently uses normal pages.
 
+use std::alloc::{self, Layout};
+use std::io::{self, Read, Write};
 use std::mem;
+use std::ops::Deref;
+use std::ptr::NonNull;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::board::Move;
@@ -42,11 +58,26 @@ const BOUND_MASK: u8 = 0x3; // 2-bit bound type field
 const GENERATION_MASK: u8 = 0x3F; // 6-bit generation field
 const GENERATION_SHIFT: u8 = 2; // Shift amount for generation within bound_gen byte
 
+/// Stored generations repeat with this period
+const GENERATION_CYCLE: u32 = GENERATION_MASK as u32 + 1;
+
+// The stored generation must follow the u16 search generation through its
+// wraparound without a jump
+const _: () = assert!((u16::MAX as u32 + 1).is_multiple_of(GENERATION_CYCLE));
+
+/// Age (in generations) from which an entry is stale: it is replaced ahead
+/// of every entry younger than this
+pub const TT_STALE_AGE: u8 = 8;
+
+/// Priority penalty that ranks every stale entry below every fresh one
+const STALE_PENALTY: i32 = 1024;
+
 // Bit positions in packed 64-bit entry
 const MOVE_MASK: u64 = 0xFFFF; // bits 0-15: move
 const SCORE_SHIFT: usize = 16; // bits 16-31: score
 const DEPTH_SHIFT: usize = 32; // bits 32-39: depth
 const BOUND_GEN_SHIFT: usize = 40; // bits 40-47: bound type + generation
+const OCCUPIED_BIT: u64 = 1 << 48; // bit 48: set in every stored entry
 const BYTE_MASK: u64 = 0xFF;
 
 /// Unpacked TT entry for reading
@@ -86,8 +117,10 @@ impl TTEntry {
 /// - bits 16-31: score (i16 as u16)
 /// - bits 32-39: depth (u8)
 /// - bits 40-47: bound (2 bits) + generation (6 bits)
+/// - bit 48:     always set, so that no stored entry packs to the empty
+///   value 0 (e.g. a depth 0 exact draw with no move at generation 0)
 ///
-/// Total: 48 bits used, 16 bits spare
+/// Total: 49 bits used, 15 bits spare
 fn pack_entry(
     depth: u8,
     score: i16,
@@ -104,6 +137,31 @@ fn pack_entry(
         | ((sc as u64) << SCORE_SHIFT)
         | ((depth as u64) << DEPTH_SHIFT)
         | ((bound_gen as u64) << BOUND_GEN_SHIFT)
+        | OCCUPIED_BIT
+}
+
+/// Generation stored in entries written during search generation
+/// `generation`
+fn tt_generation(generation: u16) -> u8 {
+    (generation & u16::from(GENERATION_MASK)) as u8
+}
+
+/// Generations between an entry stored at `stored` and the current
+/// `current` stored generation, modulo the generation cycle
+fn entry_age(current: u8, stored: u8) -> u8 {
+    current.wrapping_sub(stored) & GENERATION_MASK
+}
+
+/// Replacement priority of a slot: the lowest is replaced first. Deeper
+/// entries are worth more, each generation of age costs half a ply, and
+/// stale entries rank below all fresh ones.
+fn replacement_priority(depth: u8, age: u8) -> i32 {
+    let priority = i32::from(depth) * 2 - i32::from(age);
+    if age >= TT_STALE_AGE {
+        priority - STALE_PENALTY
+    } else {
+        priority
+    }
 }
 
 fn unpack_entry(data: u64) -> TTEntry {
@@ -144,13 +202,6 @@ struct TTSlot {
 }
 
 impl TTSlot {
-    fn new() -> Self {
-        TTSlot {
-            key_xor: AtomicU64::new(0),
-            data: AtomicU64::new(0),
-        }
-    }
-
     fn store(&self, hash: u64, packed: u64) {
         // Write data first, then key_xor
         // This order ensures that if we read a valid key_xor,
@@ -195,30 +246,111 @@ impl TTSlot {
 /// Number of slots per bucket for collision resolution
 const BUCKET_SIZE: usize = 4;
 
-/// A bucket containing multiple slots
-#[repr(C)]
+/// Cache line size the buckets are aligned to
+const CACHE_LINE: usize = 64;
+
+/// A bucket containing multiple slots, filling exactly one cache line so a
+/// probe touches a single line
+#[repr(C, align(64))]
 struct TTBucket {
     slots: [TTSlot; BUCKET_SIZE],
 }
 
-impl TTBucket {
-    fn new() -> Self {
-        TTBucket {
-            slots: [TTSlot::new(), TTSlot::new(), TTSlot::new(), TTSlot::new()],
+const _: () = assert!(mem::size_of::<TTBucket>() == CACHE_LINE);
+const _: () = assert!(mem::align_of::<TTBucket>() == CACHE_LINE);
+
+/// Smallest table size, in buckets (64 KB)
+const MIN_BUCKETS: usize = 1024;
+
+/// Huge page size on the platforms where huge pages are requested
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Ask the kernel to back `len` bytes at `ptr` with transparent huge pages
+#[cfg(target_os = "linux")]
+fn advise_huge_pages(ptr: NonNull<u8>, len: usize) -> bool {
+    // SAFETY: the range is a live allocation of `len` bytes aligned to the
+    // huge page size; madvise only changes how it is backed
+    unsafe { libc::madvise(ptr.as_ptr().cast(), len, libc::MADV_HUGEPAGE) == 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn advise_huge_pages(_ptr: NonNull<u8>, _len: usize) -> bool {
+    false
+}
+
+/// Zero-initialized bucket storage, optionally backed by huge pages
+struct BucketArray {
+    ptr: NonNull<TTBucket>,
+    len: usize,
+    layout: Layout,
+    huge_pages: bool,
+}
+
+impl BucketArray {
+    fn new(len: usize, large_pages: bool) -> Self {
+        let size = len * mem::size_of::<TTBucket>();
+        let huge = large_pages && size >= HUGE_PAGE_SIZE;
+        let align = if huge {
+            HUGE_PAGE_SIZE
+        } else {
+            mem::align_of::<TTBucket>()
+        };
+        let layout = Layout::from_size_align(size, align).expect("TT size overflows");
+
+        // SAFETY: `layout` has a non-zero size (len >= 1). An all-zero
+        // bucket is a valid empty bucket, since its slots are plain atomics.
+        unsafe {
+            // Advise before touching the memory so it is faulted in as huge
+            // pages rather than collapsed later
+            let raw = if huge {
+                alloc::alloc(layout)
+            } else {
+                alloc::alloc_zeroed(layout)
+            };
+            let Some(raw) = NonNull::new(raw) else {
+                alloc::handle_alloc_error(layout)
+            };
+            let huge_pages = huge && advise_huge_pages(raw, size);
+            if huge {
+                raw.as_ptr().write_bytes(0, size);
+            }
+            BucketArray {
+                ptr: raw.cast(),
+                len,
+                layout,
+                huge_pages,
+            }
         }
     }
 }
 
+impl Deref for BucketArray {
+    type Target = [TTBucket];
+
+    fn deref(&self) -> &[TTBucket] {
+        // SAFETY: `ptr` holds `len` initialized buckets for our lifetime
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for BucketArray {
+    fn drop(&mut self) {
+        // SAFETY: allocated in `new` with this layout
+        unsafe { alloc::dealloc(self.ptr.as_ptr().cast(), self.layout) }
+    }
+}
+
 /// Thread-safe transposition table using lockless hashing.
 ///
 /// Multiple threads can read and write concurrently without locks.
 /// Torn reads are detected via XOR verification and discarded.
 pub struct TranspositionTable {
-    buckets: Vec<TTBucket>,
+    buckets: BucketArray,
     mask: usize,
 }
 
-// Safety: TTSlot uses AtomicU64 which is Send + Sync
+// Safety: TTSlot uses AtomicU64 which is Send + Sync, and the bucket
+// array is owned exclusively by the table
 unsafe impl Send for TranspositionTable {}
 unsafe impl Sync for TranspositionTable {}
 
@@ -226,26 +358,44 @@ impl TranspositionTable {
     /// Create a new transposition table with the given size in megabytes.
     #[must_use]
     pub fn new(size_mb: usize) -> Self {
-        let bucket_size = mem::size_of::<TTBucket>();
-        let mut num_buckets = (size_mb * 1024 * 1024) / bucket_size;
+        Self::with_large_pages(size_mb, false)
+    }
 
-        // Ensure num_buckets is a power of 2 for efficient indexing
-        num_buckets = num_buckets.next_power_of_two() / 2;
-        if num_buckets == 0 {
-            num_buckets = 1024;
-        }
+    /// Create a table, backing it with huge pages if `large_pages` is set
+    /// and the platform allows it. Check [`Self::uses_large_pages`] for the
+    /// outcome.
+    #[must_use]
+    pub fn with_large_pages(size_mb: usize, large_pages: bool) -> Self {
+        Self::with_size_kb(size_mb * 1024, large_pages)
+    }
 
-        let mut buckets = Vec::with_capacity(num_buckets);
-        for _ in 0..num_buckets {
-            buckets.push(TTBucket::new());
-        }
+    /// Create a table of at most `size_kb` kilobytes (but no smaller than
+    /// `MIN_BUCKETS` buckets), for hash sizes below a megabyte
+    #[must_use]
+    pub fn with_size_kb(size_kb: usize, large_pages: bool) -> Self {
+        let bucket_size = mem::size_of::<TTBucket>();
+        let fitting = (size_kb * 1024 / bucket_size).max(MIN_BUCKETS);
+        // Largest power of 2 that fits, for efficient indexing
+        let num_buckets = 1 << fitting.ilog2();
 
         TranspositionTable {
-            buckets,
+            buckets: BucketArray::new(num_buckets, large_pages),
             mask: num_buckets - 1,
         }
     }
 
+    /// Memory held by the table, in bytes
+    #[must_use]
+    pub fn size_bytes(&self) -> usize {
+        self.buckets.len() * mem::size_of::<TTBucket>()
+    }
+
+    /// Whether the table is backed by huge pages
+    #[must_use]
+    pub fn uses_large_pages(&self) -> bool {
+        self.buckets.huge_pages
+    }
+
     fn index(&self, hash: u64) -> usize {
         (hash as usize) & self.mask
     }
@@ -295,7 +445,8 @@ impl TranspositionTable {
     /// Uses a replacement strategy that prefers:
     /// 1. Empty slots
     /// 2. Slots with matching hash (update)
-    /// 3. Slots with lowest priority (old generation, shallow depth)
+    /// 3. Stale slots (`TT_STALE_AGE` generations or older)
+    /// 4. Slots with lowest priority (old generation, shallow depth)
     pub fn store(
         &self,
         hash: u64,
@@ -307,9 +458,14 @@ impl TranspositionTable {
     ) {
         let depth_u8 = depth.min(255) as u8;
         let score_i16 = score.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
-        let gen_u8 = (generation & 0x3F) as u8;
+        let gen_u8 = tt_generation(generation);
 
         let packed = pack_entry(depth_u8, score_i16, bound_type, best_move, gen_u8);
+        self.store_packed(hash, packed, gen_u8);
+    }
+
+    /// Store an already packed entry, `gen_u8` being its stored generation
+    fn store_packed(&self, hash: u64, packed: u64, gen_u8: u8) {
         let bucket = &self.buckets[self.index(hash)];
 
         // First pass: look for empty slot or matching hash
@@ -330,10 +486,8 @@ impl TranspositionTable {
         let mut worst_priority = i32::MAX;
 
         for (idx, slot) in bucket.slots.iter().enumerate() {
-            let slot_gen = slot.generation();
-            let slot_depth = slot.depth();
-            let age = gen_u8.wrapping_sub(slot_gen) & 0x3F;
-            let priority = (slot_depth as i32) * 2 - (age as i32);
+            let age = entry_age(gen_u8, slot.generation());
+            let priority = replacement_priority(slot.depth(), age);
 
             if priority < worst_priority {
                 replace_idx = idx;
@@ -363,9 +517,68 @@ impl TranspositionTable {
         ((occupied as u64 * 1000) / total_slots as u64) as u32
     }
 
+    /// Write every occupied slot to `w` as a `(hash, packed entry)` pair of
+    /// little-endian `u64`s, then an all-zero pair as terminator. Returns
+    /// the number of entries written.
+    ///
+    /// Safe to call while a search writes to the table: a slot torn by a
+    /// concurrent store is saved with a hash no position has, so it is
+    /// never found after loading.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `w`.
+    pub fn save_entries(&self, w: &mut impl Write) -> io::Result<u64> {
+        let mut count = 0;
+        for bucket in self.buckets.iter() {
+            for slot in &bucket.slots {
+                let key_xor = slot.key_xor.load(Ordering::Relaxed);
+                let data = slot.data.load(Ordering::Relaxed);
+                if data == 0 {
+                    continue;
+                }
+                w.write_all(&(key_xor ^ data).to_le_bytes())?;
+                w.write_all(&data.to_le_bytes())?;
+                count += 1;
+            }
+        }
+        w.write_all(&[0; 16])?;
+        Ok(count)
+    }
+
+    /// Store the entries [`Self::save_entries`] wrote, stamped with
+    /// `generation` so they rank as fresh. The table may have another size
+    /// than the saved one; entries then compete for slots as usual. Returns
+    /// the number of entries read.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `r`, including an unexpected end of data
+    /// before the terminator.
+    pub fn load_entries(&self, r: &mut impl Read, generation: u16) -> io::Result<u64> {
+        let gen_u8 = tt_generation(generation);
+        let generation_bits = u64::from(GENERATION_MASK << GENERATION_SHIFT) << BOUND_GEN_SHIFT;
+        let mut count = 0;
+        let mut record = [0u8; 16];
+        loop {
+            r.read_exact(&mut record)?;
+            let (hash, data) = record.split_at(8);
+            let hash = u64::from_le_bytes(hash.try_into().expect("8 bytes"));
+            let data = u64::from_le_bytes(data.try_into().expect("8 bytes"));
+            if data == 0 {
+                return Ok(count);
+            }
+            let data = (data & !generation_bits)
+                | (u64::from(gen_u8 << GENERATION_SHIFT) << BOUND_GEN_SHIFT)
+                | OCCUPIED_BIT;
+            self.store_packed(hash, data, gen_u8);
+            count += 1;
+        }
+    }
+
     /// Clear all entries from the table.
     pub fn clear(&self) {
-        for bucket in &self.buckets {
+        for bucket in self.buckets.iter() {
             for slot in &bucket.slots {
                 slot.key_xor.store(0, Ordering::Relaxed);
                 slot.data.store(0, Ordering::Relaxed);
@@ -553,6 +766,96 @@ mod tests {
         assert_eq!(entry.score, 200);
     }
 
+    /// Hashes that all index bucket 0 of a 1 MB table
+    fn same_bucket_hashes() -> impl Iterator<Item = u64> {
+        (1u64..).map(|i| i << 40)
+    }
+
+    #[test]
+    fn test_stale_entry_replaced_before_fresh_ones() {
+        let tt = TranspositionTable::new(1);
+        let current = 20;
+        let mut hashes = same_bucket_hashes();
+        let fresh: Vec<u64> = hashes.by_ref().take(3).collect();
+        let stale = hashes.next().unwrap();
+
+        for &hash in &fresh {
+            tt.store(hash, 1, 0, BoundType::Exact, None, current);
+        }
+        // Far deeper, but TT_STALE_AGE generations old
+        let old = current - u16::from(TT_STALE_AGE);
+        tt.store(stale, 30, 0, BoundType::Exact, None, old);
+
+        let incoming = hashes.next().unwrap();
+        tt.store(incoming, 1, 0, BoundType::Exact, None, current);
+        assert!(tt.probe(stale).is_none());
+        assert!(fresh.iter().all(|&h| tt.probe(h).is_some()));
+
+        // One generation younger it is still fresh and its depth protects it
+        let tt = TranspositionTable::new(1);
+        for &hash in &fresh {
+            tt.store(hash, 1, 0, BoundType::Exact, None, current);
+        }
+        tt.store(stale, 30, 0, BoundType::Exact, None, old + 1);
+        tt.store(incoming, 1, 0, BoundType::Exact, None, current);
+        assert!(tt.probe(stale).is_some());
+    }
+
+    #[test]
+    fn test_generation_wraparound_keeps_entries_fresh() {
+        assert_eq!(entry_age(tt_generation(0), tt_generation(u16::MAX)), 1);
+        assert_eq!(entry_age(tt_generation(3), tt_generation(3)), 0);
+        assert_eq!(entry_age(tt_generation(5), tt_generation(u16::MAX - 2)), 8);
+
+        // Entries from just before the wrap must not look stale after it
+        let tt = TranspositionTable::new(1);
+        let mut hashes = same_bucket_hashes();
+        let recent: Vec<u64> = hashes.by_ref().take(3).collect();
+        let stale = hashes.next().unwrap();
+        for &hash in &recent {
+            tt.store(hash, 2, 0, BoundType::Exact, None, u16::MAX);
+        }
+        tt.store(stale, 20, 0, BoundType::Exact, None, u16::MAX - 10);
+
+        tt.store(hashes.next().unwrap(), 2, 0, BoundType::Exact, None, 0);
+        assert!(tt.probe(stale).is_none());
+        assert!(recent.iter().all(|&h| tt.probe(h).is_some()));
+    }
+
+    #[test]
+    fn test_all_zero_entry_is_stored() {
+        // Depth 0, score 0, exact, no move, generation 0
+        let tt = TranspositionTable::new(1);
+        tt.store(0x42, 0, 0, BoundType::Exact, None, 0);
+        let entry = tt.probe(0x42).expect("entry should not look empty");
+        assert_eq!((entry.depth, entry.score, entry.generation), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_large_pages_table() {
+        // Whether huge pages are granted depends on the kernel; either way
+        // the table must work and be aligned
+        let tt = TranspositionTable::with_large_pages(4, true);
+        assert_eq!(tt.buckets.as_ptr() as usize % CACHE_LINE, 0);
+        if tt.uses_large_pages() {
+            assert_eq!(tt.buckets.as_ptr() as usize % HUGE_PAGE_SIZE, 0);
+        }
+        assert_eq!(tt.hashfull_per_mille(), 0);
+        tt.store(0x1234_5678, 6, 42, BoundType::Exact, None, 1);
+        assert_eq!(tt.probe(0x1234_5678).map(|e| e.score), Some(42));
+
+        // Too small to be worth a huge page
+        assert!(!TranspositionTable::with_large_pages(1, true).uses_large_pages());
+        assert!(!TranspositionTable::new(4).uses_large_pages());
+    }
+
+    #[test]
+    fn test_buckets_are_cache_line_aligned() {
+        let tt = TranspositionTable::new(1);
+        assert_eq!(tt.buckets.as_ptr() as usize % CACHE_LINE, 0);
+        assert_eq!(mem::size_of::<TTSlot>() * BUCKET_SIZE, CACHE_LINE);
+    }
+
     // ========================================================================
     // Clear and Hashfull Tests
     // ========================================================================
@@ -662,6 +965,33 @@ mod tests {
         assert_eq!(entry.score, 500);
     }
 
+    #[test]
+    fn test_save_and_load_entries() {
+        let tt = TranspositionTable::new(1);
+        let mv = Move::from_u16(0x1234);
+        tt.store(0x1234, 7, -42, BoundType::LowerBound, Some(mv), 3);
+        tt.store(0xABCD_0000_0001, 0, 0, BoundType::Exact, None, 3);
+
+        let mut bytes = Vec::new();
+        assert_eq!(tt.save_entries(&mut bytes).unwrap(), 2);
+        assert_eq!(bytes.len(), 3 * 16);
+
+        // A smaller table takes them, restamped with the new generation
+        let loaded = TranspositionTable::with_size_kb(64, false);
+        assert_eq!(loaded.load_entries(&mut bytes.as_slice(), 40).unwrap(), 2);
+        let entry = loaded.probe(0x1234).expect("entry restored");
+        assert_eq!(entry.depth, 7);
+        assert_eq!(entry.score, -42);
+        assert_eq!(entry.bound_type, BoundType::LowerBound);
+        assert_eq!(entry.best_move, Some(mv));
+        assert_eq!(entry.generation, 40);
+        assert!(loaded.probe(0xABCD_0000_0001).is_some());
+
+        // Data cut before the terminator is an error
+        let truncated = &bytes[..20];
+        assert!(loaded.load_entries(&mut &truncated[..], 40).is_err());
+    }
+
     #[test]
     fn test_generation_wraps() {
         let tt = TranspositionTable::new(1);
@@ -0,0 +1,135 @@
+//! Build and configuration details for bug reports.
+//!
+//! Reported to GUIs as the `UCI_EngineAbout` option and printed in full by
+//! the `about` console command.
+
+use crate::board::nnue::{simd, NnueNetwork};
+use crate::output::ProtocolWriter;
+
+pub const ENGINE_NAME: &str = "chess_engine";
+pub const ENGINE_AUTHOR: &str = "Dean Menezes";
+
+/// Build configuration of the running engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Instruction set used by NNUE inference
+    pub simd: &'static str,
+    /// Checksum of the loaded network (`None` when running without NNUE)
+    pub nnue_checksum: Option<u64>,
+    /// Whether the network is the one compiled into the binary
+    pub nnue_embedded: bool,
+    /// Enabled cargo features
+    pub features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+    /// Describe this build running with `nnue`
+    #[must_use]
+    pub fn new(nnue: Option<&NnueNetwork>) -> Self {
+        let nnue_checksum = nnue.map(NnueNetwork::checksum);
+        #[cfg(feature = "embedded_nnue")]
+        let nnue_embedded = nnue.is_some_and(|net| {
+            std::ptr::eq(net, std::sync::Arc::as_ptr(&NnueNetwork::shared_embedded()))
+        });
+        #[cfg(not(feature = "embedded_nnue"))]
+        let nnue_embedded = false;
+
+        let features = [
+            ("embedded_nnue", cfg!(feature = "embedded_nnue")),
+            ("serde", cfg!(feature = "serde")),
+            ("logging", cfg!(feature = "logging")),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect();
+
+        BuildInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            simd: simd::simd_path(),
+            nnue_checksum,
+            nnue_embedded,
+            features,
+        }
+    }
+
+    /// NNUE description: `none`, or the source and checksum
+    #[must_use]
+    pub fn nnue(&self) -> String {
+        match self.nnue_checksum {
+            None => "none".to_string(),
+            Some(sum) => {
+                let source = if self.nnue_embedded {
+                    "embedded"
+                } else {
+                    "file"
+                };
+                format!("{source} {sum:016x}")
+            }
+        }
+    }
+
+    /// Feature list, `none` if no features are enabled
+    #[must_use]
+    pub fn features(&self) -> String {
+        if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(",")
+        }
+    }
+
+    /// One-line summary used as the `UCI_EngineAbout` default
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "{ENGINE_NAME} {} by {ENGINE_AUTHOR}, simd {}, nnue {}, features {}",
+            self.version,
+            self.simd,
+            self.nnue(),
+            self.features()
+        )
+    }
+
+    /// Print the full report as `info string` lines
+    pub fn print(&self, out: &dyn ProtocolWriter) {
+        crate::send_line!(out, "info string {ENGINE_NAME} {}", self.version);
+        crate::send_line!(out, "info string author {ENGINE_AUTHOR}");
+        crate::send_line!(out, "info string simd {}", self.simd);
+        crate::send_line!(out, "info string nnue {}", self.nnue());
+        crate::send_line!(out, "info string features {}", self.features());
+        crate::send_line!(
+            out,
+            "info string target {}-{}",
+            std::env::consts::ARCH,
+            std::env::consts::OS
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_without_nnue() {
+        let info = BuildInfo::new(None);
+        assert_eq!(info.nnue(), "none");
+        assert!(["avx2", "neon", "scalar"].contains(&info.simd));
+        assert_eq!(
+            info.features.contains(&"embedded_nnue"),
+            cfg!(feature = "embedded_nnue")
+        );
+        assert!(info.summary().starts_with("chess_engine "));
+        assert!(!info.summary().contains('\n'));
+    }
+
+    #[cfg(feature = "embedded_nnue")]
+    #[test]
+    fn test_build_info_reports_embedded_network() {
+        let net = NnueNetwork::shared_embedded();
+        let info = BuildInfo::new(Some(&net));
+        assert!(info.nnue_embedded);
+        assert_eq!(info.nnue(), format!("embedded {:016x}", net.checksum()));
+    }
+}
@@ -6,11 +6,28 @@ pub enum UciCommand {
     Position(Vec<String>),
     Go(Vec<String>),
     Perft(usize),
+    /// `perft hash <depth>`: perft with a perft transposition table
+    PerftHash(usize),
+    /// `perft captures <depth>`: perft over capture sequences only
+    PerftCaptures(usize),
     SetOption(Vec<String>),
+    /// `setoptions name A value 1 name B value 2 ...`: several options at once
+    SetOptions(Vec<String>),
     Debug(Option<String>),
     Stop,
     PonderHit,
     Quit,
+    /// `about`: print build configuration for bug reports
+    About,
+    /// `state`: dump position, tables, options and search status
+    State,
+    /// `speedtest`: measure movegen, make/unmake, eval and qsearch speed
+    SpeedTest,
+    /// `scaling [max_threads] [depth]`: SMP speedup at 1, 2, 4, ... threads
+    Scaling {
+        max_threads: Option<usize>,
+        depth: Option<u32>,
+    },
     Unknown(String),
 }
 
@@ -27,6 +44,37 @@ pub struct GoParams {
     pub mate: Option<u32>,
     pub ponder: bool,
     pub infinite: bool,
+    /// Root moves to restrict the search to (`searchmoves`), in UCI notation
+    pub searchmoves: Vec<String>,
+    /// Root moves to leave out of the search (`avoidmoves`), in UCI notation
+    pub avoidmoves: Vec<String>,
+}
+
+/// Keywords that end a `searchmoves` or `avoidmoves` move list
+const GO_KEYWORDS: [&str; 13] = [
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movetime",
+    "movestogo",
+    "nodes",
+    "depth",
+    "mate",
+    "ponder",
+    "infinite",
+    "searchmoves",
+    "avoidmoves",
+];
+
+/// Collect the moves following a move-list keyword at `i`, up to the next
+/// keyword
+fn parse_move_list(parts: &[&str], i: usize) -> Vec<String> {
+    parts[i + 1..]
+        .iter()
+        .take_while(|p| !GO_KEYWORDS.contains(p))
+        .map(|p| (*p).to_string())
+        .collect()
 }
 
 /// Parse the next parameter value as type T.
@@ -89,6 +137,15 @@ pub fn parse_go_params(parts: &[&str]) -> GoParams {
                 params.infinite = true;
                 1
             }
+            // Move lists
+            "searchmoves" => {
+                params.searchmoves = parse_move_list(parts, i);
+                params.searchmoves.len() + 1
+            }
+            "avoidmoves" => {
+                params.avoidmoves = parse_move_list(parts, i);
+                params.avoidmoves.len() + 1
+            }
             // Unknown - skip
             _ => 1,
         };
@@ -122,17 +179,33 @@ pub fn parse_uci_command(line: &str) -> Option<UciCommand> {
         "position" => UciCommand::Position(owned_parts()),
         "go" => UciCommand::Go(owned_parts()),
         "perft" => {
-            let depth = parts
+            let mode = parts
                 .get(1)
+                .copied()
+                .filter(|m| matches!(*m, "hash" | "captures"));
+            let depth = parts
+                .get(if mode.is_some() { 2 } else { 1 })
                 .and_then(|v| v.parse::<usize>().ok())
                 .unwrap_or(1);
-            UciCommand::Perft(depth)
+            match mode {
+                Some("hash") => UciCommand::PerftHash(depth),
+                Some(_) => UciCommand::PerftCaptures(depth),
+                None => UciCommand::Perft(depth),
+            }
         }
         "setoption" => UciCommand::SetOption(owned_parts()),
+        "setoptions" => UciCommand::SetOptions(owned_parts()),
         "debug" => UciCommand::Debug(parts.get(1).map(|v| (*v).to_string())),
         "stop" => UciCommand::Stop,
         "ponderhit" => UciCommand::PonderHit,
         "quit" => UciCommand::Quit,
+        "about" => UciCommand::About,
+        "state" => UciCommand::State,
+        "speedtest" => UciCommand::SpeedTest,
+        "scaling" => UciCommand::Scaling {
+            max_threads: parts.get(1).and_then(|v| v.parse().ok()),
+            depth: parts.get(2).and_then(|v| v.parse().ok()),
+        },
         _ => UciCommand::Unknown(trimmed.to_string()),
     };
 
@@ -177,6 +250,44 @@ mod tests {
         assert!(matches!(cmd, Some(UciCommand::Quit)));
     }
 
+    #[test]
+    fn parse_uci_command_about() {
+        let cmd = parse_uci_command("about");
+        assert!(matches!(cmd, Some(UciCommand::About)));
+    }
+
+    #[test]
+    fn parse_uci_command_state() {
+        let cmd = parse_uci_command("state");
+        assert!(matches!(cmd, Some(UciCommand::State)));
+    }
+
+    #[test]
+    fn parse_uci_command_speedtest() {
+        let cmd = parse_uci_command("speedtest");
+        assert!(matches!(cmd, Some(UciCommand::SpeedTest)));
+    }
+
+    #[test]
+    fn parse_uci_command_scaling() {
+        let cmd = parse_uci_command("scaling 8 10");
+        assert!(matches!(
+            cmd,
+            Some(UciCommand::Scaling {
+                max_threads: Some(8),
+                depth: Some(10)
+            })
+        ));
+        let cmd = parse_uci_command("scaling");
+        assert!(matches!(
+            cmd,
+            Some(UciCommand::Scaling {
+                max_threads: None,
+                depth: None
+            })
+        ));
+    }
+
     #[test]
     fn parse_uci_command_ponderhit() {
         let cmd = parse_uci_command("ponderhit");
@@ -261,6 +372,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_uci_command_perft_hash() {
+        let cmd = parse_uci_command("perft hash 6");
+        match cmd {
+            Some(UciCommand::PerftHash(depth)) => {
+                assert_eq!(depth, 6);
+            }
+            _ => panic!("Expected PerftHash command"),
+        }
+    }
+
+    #[test]
+    fn parse_uci_command_perft_captures() {
+        let cmd = parse_uci_command("perft captures 3");
+        match cmd {
+            Some(UciCommand::PerftCaptures(depth)) => {
+                assert_eq!(depth, 3);
+            }
+            _ => panic!("Expected PerftCaptures command"),
+        }
+    }
+
     #[test]
     fn parse_uci_command_setoption() {
         let cmd = parse_uci_command("setoption name Hash value 256");
@@ -276,6 +409,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_uci_command_setoptions() {
+        let line = "setoptions name Move Overhead value 10 name Ponder value true name Clear Hash";
+        let Some(UciCommand::SetOptions(parts)) = parse_uci_command(line) else {
+            panic!("Expected SetOptions command");
+        };
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        assert_eq!(
+            crate::uci::options::parse_setoptions(&parts),
+            [
+                ("Move Overhead".to_string(), Some("10".to_string())),
+                ("Ponder".to_string(), Some("true".to_string())),
+                ("Clear Hash".to_string(), None),
+            ]
+        );
+    }
+
     #[test]
     fn parse_uci_command_debug_on() {
         let cmd = parse_uci_command("debug on");
@@ -458,6 +608,18 @@ mod tests {
         assert_eq!(params.depth, Some(20));
     }
 
+    #[test]
+    fn parse_go_params_move_lists() {
+        let parts: Vec<&str> = "go searchmoves e2e4 d2d4 avoidmoves g1f3 depth 8"
+            .split(' ')
+            .collect();
+        let params = parse_go_params(&parts);
+
+        assert_eq!(params.searchmoves, ["e2e4", "d2d4"]);
+        assert_eq!(params.avoidmoves, ["g1f3"]);
+        assert_eq!(params.depth, Some(8));
+    }
+
     #[test]
     fn parse_go_params_invalid_value() {
         let parts: Vec<&str> = vec!["go", "depth", "invalid"];
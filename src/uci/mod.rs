@@ -6,13 +6,13 @@ use std::fmt;
 
 use crate::board::{Board, FenError, Move, MoveParseError};
 
+pub mod about;
 pub mod command;
 pub mod options;
 pub mod print;
 pub mod report;
-pub mod time;
-
-pub use time::TimeControl;
+pub mod server;
+pub mod session;
 
 /// Error type for UCI position command parsing
 #[derive(Debug, Clone)]
@@ -59,7 +59,10 @@ pub fn parse_uci_move(board: &mut Board, uci_string: &str) -> Option<Move> {
 /// Parse a UCI position command, returning an error on failure.
 ///
 /// Supports both "position startpos" and "position fen <fen>" formats,
-/// optionally followed by "moves <move1> <move2> ...".
+/// optionally followed by "moves <move1> <move2> ...". A FEN may carry
+/// the repetition history of [`Board::to_fen_extended`]. The board keeps
+/// its variant, so three-check counters in the FEN only apply when
+/// `UCI_Variant` is `3check`.
 pub fn try_parse_position_command(board: &mut Board, parts: &[&str]) -> Result<(), UciError> {
     let mut i = 1;
 
@@ -67,6 +70,7 @@ pub fn try_parse_position_command(board: &mut Board, parts: &[&str]) -> Result<(
         return Err(UciError::MissingParts);
     }
 
+    let variant = board.variant();
     if parts[i] == "startpos" {
         *board = Board::new();
         i += 1;
@@ -74,12 +78,20 @@ pub fn try_parse_position_command(board: &mut Board, parts: &[&str]) -> Result<(
         if i + 6 >= parts.len() {
             return Err(UciError::MissingParts);
         }
-        let fen = parts[i + 1..i + 7].join(" ");
-        *board = Board::try_from_fen(&fen)?;
-        i += 7;
+        // Six standard fields, plus any variant or history fields up to
+        // "moves"
+        let end = parts[i + 7..]
+            .iter()
+            .position(|&p| p == "moves")
+            .map_or(parts.len(), |n| i + 7 + n);
+        let fen = parts[i + 1..end].join(" ");
+        *board = Board::try_from_fen_extended(&fen)?;
+        i = end;
     } else {
         return Err(UciError::MissingParts);
     }
+    // The variant is chosen with UCI_Variant, not by the position
+    board.set_variant(variant);
 
     if i < parts.len() && parts[i] == "moves" {
         i += 1;
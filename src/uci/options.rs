@@ -1,37 +1,143 @@
-use crate::board::{SearchParams, SearchState, DEFAULT_TT_MB};
+use std::sync::Arc;
+
+use crate::board::{
+    PruneAudit, RootNoise, RootNoiseConfig, SearchParams, SearchState, Variant, MAX_ROOT_NOISE,
+};
+use crate::engine::adjudicate::ResignConfig;
+use crate::output::{ProtocolWriter, DEFAULT_MIN_REPORT_INTERVAL_MS, MAX_REPORT_INTERVAL_MS};
+use crate::shared_cache::SharedCache;
+
+use super::about::{BuildInfo, ENGINE_AUTHOR, ENGINE_NAME};
+
+/// Largest `PruneAudit` sampling interval
+const MAX_PRUNE_AUDIT: u32 = 1_000_000;
 
 /// Print a UCI spin option.
 fn print_spin(
+    out: &dyn ProtocolWriter,
     name: &str,
     default: impl std::fmt::Display,
     min: impl std::fmt::Display,
     max: impl std::fmt::Display,
 ) {
-    println!("option name {name} type spin default {default} min {min} max {max}");
+    crate::send_line!(
+        out,
+        "option name {name} type spin default {default} min {min} max {max}"
+    );
+}
+
+/// Print a UCI string option.
+fn print_string(out: &dyn ProtocolWriter, name: &str, default: &str) {
+    let default = if default.is_empty() {
+        "<empty>"
+    } else {
+        default
+    };
+    crate::send_line!(out, "option name {name} type string default {default}");
 }
 
 /// Print a UCI check option.
-fn print_check(name: &str, default: bool) {
-    println!(
+fn print_check(out: &dyn ProtocolWriter, name: &str, default: bool) {
+    crate::send_line!(
+        out,
         "option name {name} type check default {}",
         if default { "true" } else { "false" }
     );
 }
 
+/// Print a UCI combo option.
+fn print_combo(out: &dyn ProtocolWriter, name: &str, default: &str, vars: &[&str]) {
+    let vars: String = vars.iter().flat_map(|v| [" var ", v]).collect();
+    crate::send_line!(out, "option name {name} type combo default {default}{vars}");
+}
+
+/// `EvalFile` value selecting the network the engine started with
+pub const DEFAULT_EVAL_FILE: &str = "<default>";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UciOptionAction {
     ReinitHash(usize),
     SetThreads(usize),
+    SetVariant(Variant),
+    /// Swap in the network from this file (or [`DEFAULT_EVAL_FILE`])
+    LoadEvalFile(String),
+    /// Throttle search reports to one every this many milliseconds
+    SetMinReportInterval(u64),
+    /// The option names a file, which this session may not set
+    FileOptionRefused(String),
+}
+
+/// Options naming a file the engine opens, creates or writes
+const FILE_OPTIONS: [&str; 4] = ["logfile", "sharedcache", "evalfile", "checkpointfile"];
+
+/// Values of every engine option, taken with [`UciOptions::snapshot`] and
+/// put back with [`UciOptions::restore`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionSnapshot {
+    /// `(name, value)` pairs as `setoption` takes them
+    values: Vec<(String, String)>,
+}
+
+impl OptionSnapshot {
+    /// Value of the option `name` (case-insensitive)
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `(name, value)` pairs in the order the options are listed by `uci`
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct UciOptions {
     pub hash_mb: usize,
+    /// Hash size the session started with, restored by a `Hash` option
+    /// without a value
+    pub default_hash_mb: usize,
     pub threads: usize,
     pub default_max_nodes: u64,
     pub move_overhead_ms: u64,
+    /// Nodes per millisecond standing in for the clock (0 = use the clock)
+    pub nodes_time: u64,
     pub soft_time_percent: u64,
     pub hard_time_percent: u64,
     pub multi_pv: u32,
     pub ponder: bool,
+    /// Back the transposition table with huge pages where available
+    pub large_pages: bool,
+    /// Warm up the engine at the first `isready`
+    pub warmup: bool,
+    /// Protocol log file path (empty = no log)
+    pub log_file: String,
+    /// Shared result cache file path (empty = no cache)
+    pub shared_cache: String,
+    /// NNUE network file in use ([`DEFAULT_EVAL_FILE`] = the startup network)
+    pub eval_file: String,
+    /// Checkpoint file for `go infinite` analysis (empty = no checkpoints)
+    pub checkpoint_file: String,
+    /// Minutes between checkpoints
+    pub checkpoint_minutes: u64,
+    /// Rule set selected with `UCI_Variant`
+    pub variant: Variant,
+    /// When to resign or offer draws in engine-vs-engine play
+    pub resign: ResignConfig,
+    /// Report scores from White's point of view instead of the side to move's
+    pub white_pov_scores: bool,
+    /// Minimum milliseconds between search reports (0 = no limit)
+    pub min_report_interval_ms: u64,
+    /// Random root move offsets for opening diversity in self-play
+    pub root_noise: RootNoiseConfig,
+    /// Check every Nth pruning decision of each kind at full depth (0 = off)
+    pub prune_audit: u32,
+    /// Offer the options naming files; off for network sessions, whose
+    /// clients must not reach the engine's file system
+    pub file_options: bool,
 }
 
 impl UciOptions {
@@ -39,40 +145,183 @@ impl UciOptions {
     pub fn new(hash_mb: usize) -> Self {
         UciOptions {
             hash_mb,
+            default_hash_mb: hash_mb,
             threads: 1,
             default_max_nodes: 0,
             move_overhead_ms: 50,
+            nodes_time: 0,
             soft_time_percent: 70,
             hard_time_percent: 90,
             multi_pv: 1,
             ponder: false,
+            large_pages: false,
+            warmup: false,
+            log_file: String::new(),
+            shared_cache: String::new(),
+            eval_file: DEFAULT_EVAL_FILE.to_string(),
+            checkpoint_file: String::new(),
+            checkpoint_minutes: 10,
+            variant: Variant::Standard,
+            resign: ResignConfig::default(),
+            white_pov_scores: false,
+            min_report_interval_ms: DEFAULT_MIN_REPORT_INTERVAL_MS,
+            root_noise: RootNoiseConfig::default(),
+            prune_audit: 0,
+            file_options: true,
         }
     }
 
-    pub fn print(&self, params: &SearchParams) {
-        println!("id name chess_engine");
-        println!("id author Dean Menezes");
+    pub fn print(&self, out: &dyn ProtocolWriter, params: &SearchParams, about: &BuildInfo) {
+        crate::send_line!(out, "id name {ENGINE_NAME}");
+        crate::send_line!(out, "id author {ENGINE_AUTHOR}");
 
         // Engine options
-        print_spin("Hash", self.hash_mb, 1, 65536);
-        print_spin("Threads", self.threads, 1, 256);
-        print_spin("Move Overhead", self.move_overhead_ms, 0, 1000);
-        print_spin("Soft Time Percent", self.soft_time_percent, 1, 100);
-        print_spin("Hard Time Percent", self.hard_time_percent, 1, 100);
-        print_spin("Max Nodes", self.default_max_nodes, 0_u64, u64::MAX);
-        print_spin("MultiPV", self.multi_pv, 1, 64);
-        print_check("Ponder", self.ponder);
+        print_spin(out, "Hash", self.hash_mb, 1, 65536);
+        print_spin(out, "Threads", self.threads, 1, 256);
+        print_spin(out, "Move Overhead", self.move_overhead_ms, 0, 1000);
+        print_spin(out, "Soft Time Percent", self.soft_time_percent, 1, 100);
+        print_spin(out, "Hard Time Percent", self.hard_time_percent, 1, 100);
+        print_spin(out, "Max Nodes", self.default_max_nodes, 0_u64, u64::MAX);
+        print_spin(out, "NodesTime", self.nodes_time, 0, 100_000);
+        print_spin(out, "MultiPV", self.multi_pv, 1, 64);
+        print_check(out, "Ponder", self.ponder);
+        print_check(out, "LargePages", self.large_pages);
+        print_check(out, "Warmup", self.warmup);
+        print_check(out, "WhitePovScores", self.white_pov_scores);
+        print_spin(
+            out,
+            "MinReportInterval",
+            self.min_report_interval_ms,
+            0,
+            MAX_REPORT_INTERVAL_MS,
+        );
+        if self.file_options {
+            print_string(out, "LogFile", &self.log_file);
+            print_string(out, "SharedCache", &self.shared_cache);
+            print_string(out, "EvalFile", &self.eval_file);
+            print_string(out, "CheckpointFile", &self.checkpoint_file);
+        }
+        print_spin(out, "CheckpointMinutes", self.checkpoint_minutes, 1, 1440);
+        print_string(out, "UCI_EngineAbout", &about.summary());
+        print_combo(
+            out,
+            "UCI_Variant",
+            self.variant.uci_name(),
+            &Variant::ALL.map(Variant::uci_name),
+        );
+        print_spin(
+            out,
+            "ResignThreshold",
+            self.resign.resign_threshold,
+            0,
+            10000,
+        );
+        print_spin(out, "ResignMoveCount", self.resign.resign_moves, 1, 100);
+        print_spin(out, "DrawOfferScore", self.resign.draw_offer_score, 0, 1000);
+        print_spin(
+            out,
+            "RootNoise",
+            self.root_noise.centipawns,
+            0,
+            MAX_ROOT_NOISE,
+        );
+        print_spin(out, "RootNoisePlies", self.root_noise.plies, 0, 200);
+        print_spin(out, "RootNoiseSeed", self.root_noise.seed, 0_u64, u64::MAX);
+        print_spin(out, "PruneAudit", self.prune_audit, 0, MAX_PRUNE_AUDIT);
 
         // Tunable search parameters for SPSA
-        print_spin("RFPMargin", params.rfp_margin, 50, 300);
-        print_spin("NullMoveReduction", params.null_reduction, 1, 5);
-        print_spin("FutilityMargin", params.futility_margin, 50, 250);
-        print_spin("IIRMinDepth", params.iir_min_depth, 3, 8);
-        print_spin("LMRMinDepth", params.lmr_min_depth, 2, 6);
+        print_spin(out, "RFPMargin", params.rfp_margin, 50, 300);
+        print_spin(out, "NullMoveReduction", params.null_reduction, 1, 5);
+        print_spin(out, "FutilityMargin", params.futility_margin, 50, 250);
+        print_spin(out, "IIRMinDepth", params.iir_min_depth, 3, 8);
+        print_spin(out, "LMRMinDepth", params.lmr_min_depth, 2, 6);
+        print_spin(out, "LMRBase", params.lmr_base, 0, 200);
+        print_spin(out, "LMRDivisor", params.lmr_divisor, 100, 500);
+        print_spin(out, "DeltaMarginMG", params.delta_margin_mg, 50, 400);
+        print_spin(out, "DeltaMarginEG", params.delta_margin_eg, 0, 400);
+
+        crate::send_line!(out, "uciok");
+    }
 
-        println!("uciok");
+    /// Current value of every option, tunable search parameters included
+    #[must_use]
+    pub fn snapshot(&self, params: &SearchParams) -> OptionSnapshot {
+        let values = [
+            ("Hash", self.hash_mb.to_string()),
+            ("Threads", self.threads.to_string()),
+            ("Move Overhead", self.move_overhead_ms.to_string()),
+            ("Soft Time Percent", self.soft_time_percent.to_string()),
+            ("Hard Time Percent", self.hard_time_percent.to_string()),
+            ("Max Nodes", self.default_max_nodes.to_string()),
+            ("NodesTime", self.nodes_time.to_string()),
+            ("MultiPV", self.multi_pv.to_string()),
+            ("Ponder", self.ponder.to_string()),
+            ("LargePages", self.large_pages.to_string()),
+            ("Warmup", self.warmup.to_string()),
+            ("WhitePovScores", self.white_pov_scores.to_string()),
+            ("MinReportInterval", self.min_report_interval_ms.to_string()),
+            ("LogFile", self.log_file.clone()),
+            ("SharedCache", self.shared_cache.clone()),
+            ("EvalFile", self.eval_file.clone()),
+            ("CheckpointFile", self.checkpoint_file.clone()),
+            ("CheckpointMinutes", self.checkpoint_minutes.to_string()),
+            ("UCI_Variant", self.variant.uci_name().to_string()),
+            ("ResignThreshold", self.resign.resign_threshold.to_string()),
+            ("ResignMoveCount", self.resign.resign_moves.to_string()),
+            ("DrawOfferScore", self.resign.draw_offer_score.to_string()),
+            ("RootNoise", self.root_noise.centipawns.to_string()),
+            ("RootNoisePlies", self.root_noise.plies.to_string()),
+            ("RootNoiseSeed", self.root_noise.seed.to_string()),
+            ("PruneAudit", self.prune_audit.to_string()),
+            ("RFPMargin", params.rfp_margin.to_string()),
+            ("NullMoveReduction", params.null_reduction.to_string()),
+            ("FutilityMargin", params.futility_margin.to_string()),
+            ("IIRMinDepth", params.iir_min_depth.to_string()),
+            ("LMRMinDepth", params.lmr_min_depth.to_string()),
+            ("LMRBase", params.lmr_base.to_string()),
+            ("LMRDivisor", params.lmr_divisor.to_string()),
+            ("DeltaMarginMG", params.delta_margin_mg.to_string()),
+            ("DeltaMarginEG", params.delta_margin_eg.to_string()),
+        ];
+        OptionSnapshot {
+            values: values
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
     }
 
+    /// Put every option back to its value in `snapshot`, returning the
+    /// actions the changes need. Options already at their snapshot value
+    /// are left alone, so an unchanged log file is not reopened.
+    pub fn restore(
+        &mut self,
+        snapshot: &OptionSnapshot,
+        state: &mut SearchState,
+    ) -> Vec<UciOptionAction> {
+        let current = self.snapshot(state.params());
+        let changed: Vec<(&str, Option<&str>)> = snapshot
+            .iter()
+            .filter(|&(name, value)| current.get(name) != Some(value))
+            .map(|(name, value)| (name, Some(value)))
+            .collect();
+        self.apply_setoptions(&changed, state)
+    }
+
+    /// Apply several options at once, returning the actions they need so
+    /// the tables are resized once all of them are set
+    pub fn apply_setoptions(
+        &mut self,
+        options: &[(&str, Option<&str>)],
+        state: &mut SearchState,
+    ) -> Vec<UciOptionAction> {
+        options
+            .iter()
+            .filter_map(|&(name, value)| self.apply_setoption(name, value, state))
+            .collect()
+    }
+
+    #[allow(clippy::too_many_lines)]
     pub fn apply_setoption(
         &mut self,
         name: &str,
@@ -80,11 +329,14 @@ impl UciOptions {
         state: &mut SearchState,
     ) -> Option<UciOptionAction> {
         let normalized = name.trim().to_ascii_lowercase();
+        if !self.file_options && FILE_OPTIONS.contains(&normalized.as_str()) {
+            return Some(UciOptionAction::FileOptionRefused(name.trim().to_string()));
+        }
         match normalized.as_str() {
             "hash" => {
                 let mb = value
                     .and_then(|v| v.parse::<usize>().ok())
-                    .unwrap_or(DEFAULT_TT_MB)
+                    .unwrap_or(self.default_hash_mb)
                     .max(1);
                 if mb != self.hash_mb {
                     self.hash_mb = mb;
@@ -121,6 +373,11 @@ impl UciOptions {
                     self.default_max_nodes = v;
                 }
             }
+            "nodestime" => {
+                if let Some(v) = value.and_then(|v| v.parse::<u64>().ok()) {
+                    self.nodes_time = v.min(100_000);
+                }
+            }
             "softtime" => {
                 if let Some(v) = value.and_then(|v| v.parse::<u64>().ok()) {
                     self.soft_time_percent = v.clamp(1, 100);
@@ -146,38 +403,206 @@ impl UciOptions {
                     self.ponder = matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1");
                 }
             }
-            // Tunable search parameters for SPSA
-            "rfpmargin" => {
-                if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
-                    state.params_mut().rfp_margin = v.clamp(50, 300);
+            "largepages" => {
+                if let Some(v) = value {
+                    let enabled = matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1");
+                    if enabled != self.large_pages {
+                        self.large_pages = enabled;
+                        state.large_pages = enabled;
+                        return Some(UciOptionAction::ReinitHash(self.hash_mb));
+                    }
                 }
             }
-            "nullmovereduction" => {
-                if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
-                    state.params_mut().null_reduction = v.clamp(1, 5);
+            "warmup" => {
+                if let Some(v) = value {
+                    self.warmup = matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1");
                 }
             }
-            "futilitymargin" => {
-                if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
-                    state.params_mut().futility_margin = v.clamp(50, 250);
+            "whitepovscores" => {
+                if let Some(v) = value {
+                    self.white_pov_scores =
+                        matches!(v.trim().to_ascii_lowercase().as_str(), "true" | "1");
                 }
             }
-            "iirmindepth" => {
-                if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
-                    state.params_mut().iir_min_depth = v.clamp(3, 8);
+            "minreportinterval" => {
+                if let Some(v) = value.and_then(|v| v.trim().parse::<u64>().ok()) {
+                    self.min_report_interval_ms = v.min(MAX_REPORT_INTERVAL_MS);
+                    return Some(UciOptionAction::SetMinReportInterval(
+                        self.min_report_interval_ms,
+                    ));
                 }
             }
-            "lmrmindepth" => {
-                if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
-                    state.params_mut().lmr_min_depth = v.clamp(2, 6);
+            "uci_variant" => {
+                if let Some(variant) = value.and_then(Variant::from_uci_name) {
+                    if variant != self.variant {
+                        self.variant = variant;
+                        return Some(UciOptionAction::SetVariant(variant));
+                    }
+                }
+            }
+            "logfile" => {
+                let path = value.unwrap_or_default();
+                match crate::logging::open_log_file(path) {
+                    Ok(()) => self.log_file = path.trim().to_string(),
+                    Err(e) => eprintln!("info string Cannot open log file {path}: {e}"),
+                }
+            }
+            "sharedcache" => {
+                let path = value.unwrap_or_default().trim();
+                if path.is_empty() || path == "<empty>" {
+                    state.tables.shared_cache = None;
+                    self.shared_cache.clear();
+                } else {
+                    match SharedCache::open(path) {
+                        Ok(cache) => {
+                            state.tables.shared_cache = Some(Arc::new(cache));
+                            self.shared_cache = path.to_string();
+                        }
+                        Err(e) => eprintln!("info string Cannot open shared cache {path}: {e}"),
+                    }
+                }
+            }
+            "evalfile" => {
+                // The network is loaded by the session, which reports it
+                let path = match value.unwrap_or_default().trim() {
+                    "" | "<empty>" => DEFAULT_EVAL_FILE,
+                    path => path,
+                };
+                if path != self.eval_file {
+                    return Some(UciOptionAction::LoadEvalFile(path.to_string()));
+                }
+            }
+            "checkpointfile" => {
+                self.checkpoint_file = match value.unwrap_or_default().trim() {
+                    "<empty>" => String::new(),
+                    path => path.to_string(),
+                };
+            }
+            "checkpointminutes" => {
+                if let Some(v) = value.and_then(|v| v.trim().parse::<u64>().ok()) {
+                    self.checkpoint_minutes = v.clamp(1, 1440);
+                }
+            }
+            "pruneaudit" => {
+                if let Some(v) = value.and_then(|v| v.trim().parse::<u32>().ok()) {
+                    self.prune_audit = v.min(MAX_PRUNE_AUDIT);
+                    state.prune_audit = PruneAudit::new(self.prune_audit);
                 }
             }
-            _ => {}
+            _ if apply_resign(&normalized, value, &mut self.resign) => {}
+            _ if apply_root_noise(&normalized, value, &mut self.root_noise) => {
+                // A new generator, so the games from here on follow the seed
+                state.root_noise = RootNoise::new(self.root_noise);
+            }
+            // Tunable search parameters for SPSA
+            _ => apply_tunable(&normalized, value, state.params_mut()),
         }
         None
     }
 }
 
+/// Apply a resign or draw offer option by its normalized name, returning
+/// whether the name was one of them
+fn apply_resign(name: &str, value: Option<&str>, resign: &mut ResignConfig) -> bool {
+    let value = value.and_then(|v| v.trim().parse::<i32>().ok());
+    match name {
+        "resignthreshold" => {
+            if let Some(v) = value {
+                resign.resign_threshold = v.clamp(0, 10000);
+            }
+        }
+        "resignmovecount" => {
+            if let Some(v) = value {
+                resign.resign_moves = v.clamp(1, 100).unsigned_abs();
+            }
+        }
+        "drawofferscore" => {
+            if let Some(v) = value {
+                resign.draw_offer_score = v.clamp(0, 1000);
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Apply a root noise option by its normalized name, returning whether the
+/// name was one of them
+fn apply_root_noise(name: &str, value: Option<&str>, noise: &mut RootNoiseConfig) -> bool {
+    let value = value.map(str::trim);
+    match name {
+        "rootnoise" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                noise.centipawns = v.clamp(0, MAX_ROOT_NOISE);
+            }
+        }
+        "rootnoiseplies" => {
+            if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
+                noise.plies = v.min(200);
+            }
+        }
+        "rootnoiseseed" => {
+            if let Some(v) = value.and_then(|v| v.parse::<u64>().ok()) {
+                noise.seed = v;
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Apply a tunable search parameter (SPSA) by its normalized option name
+fn apply_tunable(name: &str, value: Option<&str>, params: &mut SearchParams) {
+    match name {
+        "rfpmargin" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                params.rfp_margin = v.clamp(50, 300);
+            }
+        }
+        "nullmovereduction" => {
+            if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
+                params.null_reduction = v.clamp(1, 5);
+            }
+        }
+        "futilitymargin" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                params.futility_margin = v.clamp(50, 250);
+            }
+        }
+        "iirmindepth" => {
+            if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
+                params.iir_min_depth = v.clamp(3, 8);
+            }
+        }
+        "lmrmindepth" => {
+            if let Some(v) = value.and_then(|v| v.parse::<u32>().ok()) {
+                params.lmr_min_depth = v.clamp(2, 6);
+            }
+        }
+        "lmrbase" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                params.lmr_base = v.clamp(0, 200);
+            }
+        }
+        "lmrdivisor" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                params.lmr_divisor = v.clamp(100, 500);
+            }
+        }
+        "deltamarginmg" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                params.delta_margin_mg = v.clamp(50, 400);
+            }
+        }
+        "deltamargineg" => {
+            if let Some(v) = value.and_then(|v| v.parse::<i32>().ok()) {
+                params.delta_margin_eg = v.clamp(0, 400);
+            }
+        }
+        _ => {}
+    }
+}
+
 #[must_use]
 pub fn parse_setoption(parts: &[&str]) -> Option<(String, Option<String>)> {
     if parts.is_empty() || parts[0] != "setoption" {
@@ -213,3 +638,23 @@ pub fn parse_setoption(parts: &[&str]) -> Option<(String, Option<String>)> {
 
     Some((name, value))
 }
+
+/// Parse a `setoptions name A value 1 name B value 2 ...` batch into
+/// `(name, value)` pairs. Option values cannot contain the word `name`.
+#[must_use]
+pub fn parse_setoptions(parts: &[&str]) -> Vec<(String, Option<String>)> {
+    if parts.first() != Some(&"setoptions") {
+        return Vec::new();
+    }
+    let mut options = Vec::new();
+    let mut option = vec!["setoption"];
+    for &part in &parts[1..] {
+        if part == "name" && option.len() > 1 {
+            options.extend(parse_setoption(&option));
+            option.truncate(1);
+        }
+        option.push(part);
+    }
+    options.extend(parse_setoption(&option));
+    options
+}
@@ -1,7 +1,11 @@
 use std::time::Duration;
 
-pub fn print_perft_info(depth: usize, nodes: u64, elapsed: Duration) {
-    println!(
+use crate::board::MemoryUsage;
+use crate::output::ProtocolWriter;
+
+pub fn print_perft_info(out: &dyn ProtocolWriter, depth: usize, nodes: u64, elapsed: Duration) {
+    crate::send_line!(
+        out,
         "info string perft depth {} nodes {} time_ms {}",
         depth,
         nodes,
@@ -10,6 +14,7 @@ pub fn print_perft_info(depth: usize, nodes: u64, elapsed: Duration) {
 }
 
 pub fn print_time_info(
+    out: &dyn ProtocolWriter,
     soft_time_ms: u64,
     hard_time_ms: u64,
     move_overhead_ms: u64,
@@ -17,7 +22,23 @@ pub fn print_time_info(
     ponder: bool,
     depth: u32,
 ) {
-    println!(
+    crate::send_line!(
+        out,
         "info string time soft {soft_time_ms} hard {hard_time_ms} overhead {move_overhead_ms} nodes {max_nodes} ponder {ponder} depth {depth}"
     );
 }
+
+/// `info string` line reporting the memory held by the search tables of
+/// `threads` threads against the `Hash` cap, in KB
+#[must_use]
+pub fn format_memory_info(usage: &MemoryUsage, threads: usize, hash_mb: usize) -> String {
+    let kb = |bytes: usize| bytes.div_ceil(1024);
+    format!(
+        "info string memory {} KB of {} KB: tt {} pawnhash {} perthread {} threads {threads}",
+        kb(usage.total(threads)),
+        hash_mb * 1024,
+        kb(usage.tt),
+        kb(usage.pawn_hash),
+        kb(usage.per_thread)
+    )
+}
@@ -1,35 +1,132 @@
-use crate::board::{Move, SearchResult};
+use std::fmt::Write;
+
+use crate::board::{Move, PruneKind, SearchIterationInfo, SearchResult};
+use crate::output::{ProtocolWriter, ReportThrottle};
 
 use super::format_uci_move;
 
-pub fn print_ready() {
-    println!("readyok");
+pub fn print_ready(out: &dyn ProtocolWriter) {
+    crate::send_line!(out, "readyok");
 }
 
 /// Print best move without ponder
-pub fn print_bestmove(best_move: Option<Move>) {
+pub fn print_bestmove(out: &dyn ProtocolWriter, best_move: Option<Move>) {
     if let Some(best_move) = best_move {
         let uci_move = format_uci_move(&best_move);
-        println!("bestmove {uci_move}");
+        crate::send_line!(out, "bestmove {uci_move}");
     } else {
-        println!("bestmove (none)");
+        crate::send_line!(out, "bestmove (none)");
     }
 }
 
 /// Print best move with optional ponder move
-pub fn print_bestmove_with_ponder(result: SearchResult) {
+pub fn print_bestmove_with_ponder(out: &dyn ProtocolWriter, result: &SearchResult) {
     match (result.best_move, result.ponder_move) {
         (Some(best), Some(ponder)) => {
             let best_uci = format_uci_move(&best);
             let ponder_uci = format_uci_move(&ponder);
-            println!("bestmove {best_uci} ponder {ponder_uci}");
+            crate::send_line!(out, "bestmove {best_uci} ponder {ponder_uci}");
         }
         (Some(best), None) => {
             let best_uci = format_uci_move(&best);
-            println!("bestmove {best_uci}");
+            crate::send_line!(out, "bestmove {best_uci}");
         }
         (None, _) => {
-            println!("bestmove (none)");
+            crate::send_line!(out, "bestmove (none)");
         }
     }
 }
+
+/// Debug summary of a finished search: its depth, effort, effective
+/// branching factor and the time to each completed depth as `depth:ms`
+#[must_use]
+pub fn format_search_summary(result: &SearchResult) -> String {
+    let ebf = result
+        .effective_branching_factor()
+        .map_or_else(|| "-".to_string(), |ebf| format!("{ebf:.2}"));
+    let mut line = format!(
+        "info string debug search depth {} seldepth {} nodes {} time {} ebf {ebf} ttd",
+        result.depth,
+        result.seldepth,
+        result.nodes,
+        result.elapsed.as_millis()
+    );
+    for it in &result.iterations {
+        let _ = write!(line, " {}:{}", it.depth, it.time_ms);
+    }
+    line
+}
+
+/// Pruning audit results of a finished search: for each kind of decision,
+/// how many were checked and how many of those the full-depth search
+/// disagreed with (nothing when the audit is off)
+#[must_use]
+pub fn format_prune_audit(result: &SearchResult) -> Vec<String> {
+    if result.prune_audit.is_empty() {
+        return Vec::new();
+    }
+    PruneKind::ALL
+        .iter()
+        .map(|&kind| {
+            let count = result.prune_audit.get(kind);
+            format!(
+                "info string debug prune {} sampled {} changed {}",
+                kind.name(),
+                count.sampled,
+                count.changed
+            )
+        })
+        .collect()
+}
+
+/// The lines reporting a search iteration: its debug diagnostics, then
+/// the `info` line (a progress `info` line alone for progress updates)
+#[must_use]
+pub fn format_info(info: &SearchIterationInfo) -> Vec<String> {
+    if info.progress {
+        return vec![format!(
+            "info depth {} seldepth {} nodes {} nps {} time {} hashfull {}",
+            info.depth, info.seldepth, info.nodes, info.nps, info.time_ms, info.hashfull
+        )];
+    }
+
+    let mut lines: Vec<String> = info
+        .diagnostics
+        .iter()
+        .map(|line| format!("info string debug {line}"))
+        .collect();
+
+    // Include multipv in output for UCI compliance
+    let multipv_str = if info.multipv > 1 {
+        format!(" multipv {}", info.multipv)
+    } else {
+        String::new()
+    };
+    let score = match info.mate_in {
+        Some(mate) => format!("mate {mate}"),
+        None => format!("cp {}", info.score),
+    };
+    lines.push(format!(
+        "info depth {} seldepth {}{} nodes {} nps {} time {} hashfull {} score {} pv {}",
+        info.depth,
+        info.seldepth,
+        multipv_str,
+        info.nodes,
+        info.nps,
+        info.time_ms,
+        info.hashfull,
+        score,
+        info.pv
+    ));
+    lines
+}
+
+/// Report a search iteration (or progress) through `throttle`
+pub fn print_info(out: &dyn ProtocolWriter, throttle: &ReportThrottle, info: &SearchIterationInfo) {
+    let mut lines = format_info(info);
+    if info.progress {
+        throttle.report_progress(out, &lines.remove(0));
+    } else {
+        throttle.report(out, info.multipv, lines);
+    }
+}
@@ -0,0 +1,109 @@
+//! UCI over TCP.
+//!
+//! `--listen <port>` serves the UCI protocol on a socket instead of stdio, so
+//! the engine can run in a container and be reached by a GUI or tournament
+//! manager over the network. Every connection gets its own [`UciSession`]
+//! (and therefore its own `EngineController` and hash table) on its own
+//! thread; closing the connection or sending `quit` ends only that session.
+//!
+//! Connections are not authenticated. Network sessions cannot set the
+//! options naming files (`LogFile`, `SharedCache`, `EvalFile`,
+//! `CheckpointFile`), so a client cannot make the engine open or write files
+//! of its choosing, but it can still use the engine's CPU and memory.
+
+use std::io::{self, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use crate::output::StreamWriter;
+
+use super::session::UciSession;
+
+/// Stack size for session threads, matching the main thread: setting up a
+/// session builds large search tables on the stack.
+const SESSION_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Accept connections on `listener` forever, serving each on its own thread
+pub fn serve(listener: &TcpListener, tt_mb: usize) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("info string Connection failed: {e}");
+                continue;
+            }
+        };
+        thread::Builder::new()
+            .name("uci-session".to_string())
+            .stack_size(SESSION_STACK_SIZE)
+            .spawn(move || {
+                if let Err(e) = serve_connection(stream, tt_mb) {
+                    eprintln!("info string Session ended: {e}");
+                }
+            })?;
+    }
+    Ok(())
+}
+
+/// Run one UCI session over `stream` until `quit` or disconnect
+pub fn serve_connection(stream: TcpStream, tt_mb: usize) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let out = Arc::new(StreamWriter::new(stream.try_clone()?));
+    UciSession::remote(tt_mb, out).run(BufReader::new(stream));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Write};
+
+    #[test]
+    fn test_sessions_are_independent() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(&listener, 1));
+
+        let mut first = TcpStream::connect(addr).unwrap();
+        let mut second = TcpStream::connect(addr).unwrap();
+        first.write_all(b"isready\n").unwrap();
+        second.write_all(b"isready\nquit\n").unwrap();
+
+        for stream in [&first, &second] {
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            assert_eq!(line, "readyok\n");
+        }
+
+        // The second session quitting leaves the first one running
+        first.write_all(b"position startpos\nperft 2\n").unwrap();
+        let mut line = String::new();
+        BufReader::new(&first).read_line(&mut line).unwrap();
+        assert!(line.starts_with("info string perft depth 2 nodes 400 "));
+    }
+
+    #[test]
+    fn test_network_sessions_refuse_file_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(&listener, 1));
+
+        let path = std::env::temp_dir().join(format!("chess_remote_{}.log", std::process::id()));
+        let mut stream = TcpStream::connect(addr).unwrap();
+        writeln!(stream, "setoption name LogFile value {}", path.display()).unwrap();
+        stream.write_all(b"uci\nquit\n").unwrap();
+        let lines: Vec<String> = BufReader::new(&stream)
+            .lines()
+            .map_while(Result::ok)
+            .collect();
+
+        assert_eq!(
+            lines[0],
+            "info string Option LogFile names a file and is not available in this session"
+        );
+        assert!(lines.iter().any(|l| l == "uciok"));
+        assert!(!lines[1..].iter().any(|l| l.contains("LogFile")));
+        assert!(!path.exists());
+    }
+}
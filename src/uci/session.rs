@@ -0,0 +1,660 @@
+//! UCI session: command dispatch on top of an [`EngineController`].
+//!
+//! The session reads commands from any [`BufRead`] and writes all protocol
+//! output to its [`ProtocolWriter`], so the same loop serves stdin/stdout,
+//! recorded sessions and in-process tests.
+
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::board::nnue::NnueNetwork;
+use crate::board::{
+    Move, PerftTable, SearchConfig, SearchIterationInfo, SearchState, TerminationReason,
+};
+use crate::engine::checkpoint::{Checkpoint, CheckpointWriter, LatestInfo};
+use crate::engine::time::{search_request_from_limits, TimeConfig, TimeControl, TimeManager};
+use crate::engine::{EngineController, SearchParams as EngineSearchParams, StateReport};
+use crate::log_span;
+use crate::output::{ReportThrottle, SharedWriter};
+
+use super::about::BuildInfo;
+use super::command::{parse_go_params, parse_uci_command, GoParams, UciCommand};
+use super::options::{
+    parse_setoption, parse_setoptions, OptionSnapshot, UciOptionAction, UciOptions,
+    DEFAULT_EVAL_FILE,
+};
+use super::parse_position_command;
+use super::print::{format_memory_info, print_perft_info, print_time_info};
+use super::report::{
+    format_prune_audit, format_search_summary, print_bestmove_with_ponder, print_info, print_ready,
+};
+
+/// Default depth limit when searching by nodes
+const NODE_SEARCH_DEFAULT_DEPTH: u32 = 64;
+/// Fallback time allocation when no time control is specified
+const FALLBACK_TIME_SECS: u64 = 5;
+/// Size of the table used by `perft hash`
+const PERFT_HASH_MB: usize = 64;
+const KNOWN_COMMANDS: &[&str] = &[
+    "uci",
+    "isready",
+    "ucinewgame",
+    "position",
+    "go",
+    "perft",
+    "setoption",
+    "setoptions",
+    "debug",
+    "stop",
+    "ponderhit",
+    "quit",
+    "about",
+    "state",
+    "speedtest",
+    "scaling",
+];
+
+/// UCI session state (time controls, debug mode)
+struct UciState {
+    time_control: TimeControl,
+    debug: bool,
+    /// Whether the current tables have been warmed up
+    warmed_up: bool,
+}
+
+impl Default for UciState {
+    fn default() -> Self {
+        UciState {
+            time_control: TimeControl::move_time(Duration::from_secs(FALLBACK_TIME_SECS)),
+            debug: false,
+            warmed_up: false,
+        }
+    }
+}
+
+impl UciState {
+    fn update_time_control(&mut self, params: &GoParams, is_white: bool) -> TimeControl {
+        // A ponder search gets the real time control for `ponderhit`
+        if params.infinite {
+            self.time_control = TimeControl::Infinite;
+            return self.time_control;
+        }
+
+        if let Some(mt) = params.movetime {
+            self.time_control = TimeControl::move_time(Duration::from_millis(mt));
+            return self.time_control;
+        }
+
+        let fallback = Duration::from_secs(FALLBACK_TIME_SECS);
+        let time_left = if is_white {
+            params.wtime.map(Duration::from_millis)
+        } else {
+            params.btime.map(Duration::from_millis)
+        }
+        .unwrap_or(fallback);
+
+        let inc = if is_white {
+            params.winc.map(Duration::from_millis)
+        } else {
+            params.binc.map(Duration::from_millis)
+        }
+        .unwrap_or(Duration::ZERO);
+
+        self.time_control = TimeControl::incremental(time_left, inc, params.movestogo);
+        self.time_control
+    }
+}
+
+fn parts_as_strs(parts: &[String]) -> Vec<&str> {
+    parts.iter().map(String::as_str).collect()
+}
+
+/// A UCI protocol session
+pub struct UciSession {
+    engine: EngineController,
+    options: UciOptions,
+    state: UciState,
+    out: SharedWriter,
+    /// Set while searching for Black with `WhitePovScores` on: info scores
+    /// are then negated to make them white-relative
+    negate_scores: Arc<AtomicBool>,
+    /// Last completed iteration of the running search, for checkpoints
+    latest_info: LatestInfo,
+    /// Rate limit for `info` reports
+    throttle: Arc<ReportThrottle>,
+}
+
+struct GoSearchPlan {
+    search_params: EngineSearchParams,
+    soft_time_ms: u64,
+    hard_time_ms: u64,
+    depth_hint: Option<u32>,
+    go_ponder: bool,
+    max_nodes: u64,
+}
+
+impl UciSession {
+    /// Create a session with a `default_tt_mb` hash table, writing to `out`
+    #[must_use]
+    pub fn new(default_tt_mb: usize, out: SharedWriter) -> Self {
+        let options = UciOptions::new(default_tt_mb);
+        let mut engine = EngineController::new(options.hash_mb);
+        let info_out = Arc::clone(&out);
+        let negate_scores = Arc::new(AtomicBool::new(false));
+        let negate = Arc::clone(&negate_scores);
+        let latest_info: LatestInfo = Arc::default();
+        let latest = Arc::clone(&latest_info);
+        let throttle = Arc::new(ReportThrottle::new(options.min_report_interval_ms));
+        let info_throttle = Arc::clone(&throttle);
+        engine.set_info_callback(Some(Arc::new(move |info: &SearchIterationInfo| {
+            if !info.progress && info.multipv <= 1 {
+                *latest.lock() = Some(info.clone());
+            }
+            if negate.load(Ordering::Relaxed) {
+                print_info(&*info_out, &info_throttle, &info.white_pov(false));
+            } else {
+                print_info(&*info_out, &info_throttle, info);
+            }
+        })));
+        eprintln!(
+            "{}",
+            format_memory_info(
+                &engine.memory_usage(),
+                engine.num_threads(),
+                engine.hash_mb()
+            )
+        );
+        UciSession {
+            engine,
+            options,
+            state: UciState::default(),
+            out,
+            negate_scores,
+            latest_info,
+            throttle,
+        }
+    }
+
+    /// Create a session for a network client, which may not set the
+    /// options naming files
+    #[must_use]
+    pub fn remote(default_tt_mb: usize, out: SharedWriter) -> Self {
+        let mut session = UciSession::new(default_tt_mb, out);
+        session.options.file_options = false;
+        session
+    }
+
+    fn build_go_plan(&mut self, params: &GoParams, is_white: bool) -> GoSearchPlan {
+        let time_control = self.state.update_time_control(params, is_white);
+        let mut depth = params.depth;
+        let nodes = params.nodes;
+        let mate = params.mate;
+        let go_ponder = params.ponder;
+        let go_infinite = params.infinite;
+
+        if nodes.is_some() && depth.is_none() {
+            depth = Some(NODE_SEARCH_DEFAULT_DEPTH);
+        }
+
+        if let Some(mate_moves) = mate {
+            if mate_moves > 0 && depth.is_none() {
+                depth = Some(mate_moves * 2);
+            }
+        }
+
+        let time_config = TimeConfig {
+            move_overhead_ms: self.options.move_overhead_ms,
+            soft_time_percent: self.options.soft_time_percent,
+            hard_time_percent: self.options.hard_time_percent,
+            default_max_nodes: self.options.default_max_nodes,
+            nodes_time: self.options.nodes_time,
+        };
+        let opponent_time_ms = if is_white { params.btime } else { params.wtime };
+        let limits = if go_infinite {
+            (u64::MAX, u64::MAX)
+        } else {
+            self.engine
+                .allocate_time(&time_control, opponent_time_ms, &time_config)
+        };
+        let (request, (soft_time_ms, hard_time_ms)) =
+            search_request_from_limits(limits, depth, nodes, go_ponder, go_infinite, &time_config);
+
+        if self.state.debug {
+            let opponent = opponent_time_ms.map_or_else(|| "none".to_string(), |ms| ms.to_string());
+            crate::send_line!(
+                &*self.out,
+                "info string debug time control {time_control:?} opponent {opponent} \
+                 soft {soft_time_ms} hard {hard_time_ms} request soft {} hard {} nodes {}",
+                request.soft_time_ms,
+                request.hard_time_ms,
+                request.max_nodes
+            );
+        }
+
+        let search_params = EngineSearchParams {
+            depth: request.depth,
+            soft_time_ms: request.soft_time_ms,
+            hard_time_ms: request.hard_time_ms,
+            ponder: request.ponder,
+            infinite: request.infinite,
+            multi_pv: self.options.multi_pv,
+            search_moves: self.parse_root_moves(&params.searchmoves),
+            avoid_moves: self.parse_root_moves(&params.avoidmoves),
+        };
+        let root = SearchConfig::default()
+            .with_search_moves(search_params.search_moves.clone())
+            .with_avoid_moves(search_params.avoid_moves.clone());
+        let board = self.engine.board_mut();
+        let legal = board.generate_moves().len();
+        if legal > 0 && root.excluded_root_moves(board).len() == legal {
+            crate::send_line!(
+                &*self.out,
+                "info string searchmoves and avoidmoves leave no legal move to search"
+            );
+        }
+
+        GoSearchPlan {
+            search_params,
+            soft_time_ms,
+            hard_time_ms,
+            depth_hint: depth,
+            go_ponder,
+            max_nodes: request.max_nodes,
+        }
+    }
+
+    /// Legal moves among `moves`; the rest are reported and skipped
+    fn parse_root_moves(&mut self, moves: &[String]) -> Vec<Move> {
+        moves
+            .iter()
+            .filter_map(|mv| match self.engine.board_mut().parse_move(mv) {
+                Ok(m) => Some(m),
+                Err(e) => {
+                    crate::send_line!(&*self.out, "info string Ignoring root move {mv}: {e}");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Handle the "go" command - start a search
+    fn handle_go(&mut self, parts: &[String]) {
+        let parts_ref = parts_as_strs(parts);
+        let params = parse_go_params(&parts_ref);
+
+        let white_to_move = self.engine.board().white_to_move();
+        let plan = self.build_go_plan(&params, white_to_move);
+        self.negate_scores.store(
+            self.options.white_pov_scores && !white_to_move,
+            Ordering::Relaxed,
+        );
+
+        self.engine.set_max_nodes(plan.max_nodes);
+
+        print_time_info(
+            &*self.out,
+            plan.soft_time_ms,
+            plan.hard_time_ms,
+            self.options.move_overhead_ms,
+            plan.max_nodes,
+            plan.go_ponder,
+            plan.depth_hint.unwrap_or(0),
+        );
+
+        // A finished game is reported with a score instead of a line
+        let game_over = self.engine.board_mut().game_result();
+
+        let checkpoint = if plan.search_params.infinite {
+            self.start_checkpoints()
+        } else {
+            None
+        };
+
+        // Build search parameters
+        let out = Arc::clone(&self.out);
+        let throttle = Arc::clone(&self.throttle);
+        let debug = self.state.debug;
+        self.engine.start_search(plan.search_params, move |result| {
+            // The last iteration and checkpoint go out before the move
+            throttle.flush(&*out);
+            if let Some(writer) = checkpoint {
+                writer.finish();
+            }
+            if result.best_move.is_none() {
+                match game_over {
+                    Some(TerminationReason::Checkmate(_)) => {
+                        crate::send_line!(out, "info score mate -1");
+                    }
+                    Some(reason) if reason.is_draw() => crate::send_line!(out, "info score cp 0"),
+                    _ => {}
+                }
+            }
+            if debug {
+                crate::send_line!(out, "{}", format_search_summary(&result));
+            }
+            for line in format_prune_audit(&result) {
+                crate::send_line!(out, "{line}");
+            }
+            print_bestmove_with_ponder(&*out, &result);
+            let _ = out.flush();
+        });
+    }
+
+    /// Resume the analysis saved in the checkpoint file if it is of the
+    /// current position, then start saving this one to it (nothing without
+    /// a checkpoint file)
+    fn start_checkpoints(&mut self) -> Option<CheckpointWriter> {
+        if self.options.checkpoint_file.is_empty() {
+            return None;
+        }
+        self.engine.stop_search();
+        *self.latest_info.lock() = None;
+        let path = PathBuf::from(&self.options.checkpoint_file);
+        let fen = self.engine.board().to_fen();
+        if Checkpoint::read(&path).is_ok_and(|saved| saved.fen == fen) {
+            let restored = self.engine.with_search_state(|state| {
+                // Stamped with the generation of the search about to start
+                let generation = state.generation.wrapping_add(1);
+                Checkpoint::restore(&path, &state.tables.tt, generation)
+            });
+            match restored {
+                Some(Ok((saved, entries))) => crate::send_line!(
+                    &*self.out,
+                    "info string checkpoint resumed depth {} score {} entries {entries} pv {}",
+                    saved.depth,
+                    saved.score_string(),
+                    saved.pv
+                ),
+                Some(Err(e)) => crate::send_line!(
+                    &*self.out,
+                    "info string Cannot resume checkpoint {}: {e}",
+                    path.display()
+                ),
+                None => {}
+            }
+        }
+        let tt = self.engine.with_search_state_ref(SearchState::shared_tt)?;
+        let interval = Duration::from_secs(self.options.checkpoint_minutes * 60);
+        Some(CheckpointWriter::spawn(
+            path,
+            fen,
+            interval,
+            tt,
+            Arc::clone(&self.latest_info),
+            Arc::clone(&self.out),
+        ))
+    }
+
+    /// Stop any search and return the loaded network, for the benchmark
+    /// commands that run outside the engine's search thread
+    fn idle_network(&mut self) -> Option<Arc<NnueNetwork>> {
+        self.engine.stop_search();
+        self.engine
+            .with_search_state_ref(|state| state.tables.nnue.clone())
+            .flatten()
+    }
+
+    /// Handle the "state" command: report what the engine holds, without
+    /// waiting for a running search (options and table fill are then
+    /// reported as busy, since the search owns them)
+    fn handle_state(&mut self) {
+        let tables = self.engine.try_with_search_state_ref(|state| {
+            (
+                self.options.snapshot(state.params()),
+                state.tables.tt.hashfull_per_mille(),
+            )
+        });
+        let mut report = StateReport::new(self.engine.board(), self.engine.search_status())
+            .with_hashfull(tables.as_ref().map(|(_, hashfull)| *hashfull));
+        match &tables {
+            Some((snapshot, _)) => {
+                for (name, value) in snapshot.iter() {
+                    report = report.with_option(name, value);
+                }
+            }
+            None => report = report.with_option("options", "busy"),
+        }
+        for line in report.lines() {
+            crate::send_line!(&*self.out, "info string state {line}");
+        }
+    }
+
+    /// Handle the "setoption" command
+    fn handle_setoption(&mut self, parts: &[String]) {
+        self.engine.stop_search();
+        let parts_ref = parts_as_strs(parts);
+        if let Some((name, value)) = parse_setoption(&parts_ref) {
+            let action = self.engine.with_search_state(|state| {
+                self.options.apply_setoption(&name, value.as_deref(), state)
+            });
+            if let Some(Some(action)) = action {
+                self.apply_option_action(action);
+            }
+        }
+    }
+
+    /// Handle the "setoptions" command: every option is set before any
+    /// table is resized or the variant changes
+    fn handle_setoptions(&mut self, parts: &[String]) {
+        self.engine.stop_search();
+        let parts_ref = parts_as_strs(parts);
+        let options = parse_setoptions(&parts_ref);
+        let options: Vec<(&str, Option<&str>)> = options
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+            .collect();
+        let actions = self
+            .engine
+            .with_search_state(|state| self.options.apply_setoptions(&options, state));
+        for action in actions.unwrap_or_default() {
+            self.apply_option_action(action);
+        }
+    }
+
+    /// Carry out what an option change needs beyond storing its value
+    fn apply_option_action(&mut self, action: UciOptionAction) {
+        match action {
+            UciOptionAction::ReinitHash(new_mb) => {
+                self.engine.resize_hash(new_mb);
+                self.state.warmed_up = false;
+                let huge = self
+                    .engine
+                    .with_search_state_ref(|state| state.tables.tt.uses_large_pages());
+                if self.options.large_pages && huge == Some(false) {
+                    crate::send_line!(
+                        &*self.out,
+                        "info string Large pages unavailable, using normal pages"
+                    );
+                }
+            }
+            UciOptionAction::SetThreads(threads) => {
+                // The TT is resized to make room for the threads' tables
+                self.engine.set_threads(threads);
+                self.state.warmed_up = false;
+            }
+            UciOptionAction::SetVariant(variant) => {
+                self.engine.board_mut().set_variant(variant);
+            }
+            UciOptionAction::LoadEvalFile(path) => self.load_eval_file(&path),
+            UciOptionAction::SetMinReportInterval(ms) => self.throttle.set_min_interval_ms(ms),
+            UciOptionAction::FileOptionRefused(name) => crate::send_line!(
+                &*self.out,
+                "info string Option {name} names a file and is not available in this session"
+            ),
+        }
+    }
+
+    /// Swap the network for the one in `path` (the startup network for
+    /// [`DEFAULT_EVAL_FILE`]) and report its checksum; on failure the
+    /// current network stays
+    fn load_eval_file(&mut self, path: &str) {
+        let network = if path == DEFAULT_EVAL_FILE {
+            Ok(EngineController::default_nnue().map(|(network, _)| network))
+        } else {
+            NnueNetwork::load(path).map(|network| Some(Arc::new(network)))
+        };
+        match network {
+            Ok(network) => {
+                let nnue = network
+                    .as_ref()
+                    .map_or_else(|| "none".to_string(), |n| format!("{:016x}", n.checksum()));
+                self.engine.set_nnue(network);
+                self.options.eval_file = path.to_string();
+                crate::send_line!(&*self.out, "info string EvalFile {path} nnue {nnue}");
+            }
+            Err(e) => {
+                crate::send_line!(&*self.out, "info string Cannot load EvalFile {path}: {e}");
+            }
+        }
+    }
+
+    /// Current value of every option (see [`UciOptions::snapshot`])
+    #[must_use]
+    pub fn option_snapshot(&self) -> OptionSnapshot {
+        let params = self
+            .engine
+            .with_search_state_ref(|state| state.params().clone())
+            .unwrap_or_default();
+        self.options.snapshot(&params)
+    }
+
+    /// Allocate the time of every timed `go` with `manager` instead of the
+    /// engine's own strategy
+    pub fn set_time_manager(&mut self, manager: Arc<dyn TimeManager>) {
+        self.engine.set_time_manager(manager);
+    }
+
+    /// Put every option back to its value in `snapshot`, as one batch
+    pub fn restore_options(&mut self, snapshot: &OptionSnapshot) {
+        self.engine.stop_search();
+        let actions = self
+            .engine
+            .with_search_state(|state| self.options.restore(snapshot, state));
+        for action in actions.unwrap_or_default() {
+            self.apply_option_action(action);
+        }
+    }
+
+    /// Process a single UCI command. Returns false if the engine should quit.
+    pub fn handle_command(&mut self, cmd: UciCommand) -> bool {
+        let _span = log_span!("command", ?cmd);
+        match cmd {
+            UciCommand::Uci => {
+                self.engine.with_search_state_ref(|state| {
+                    let about = BuildInfo::new(state.tables.nnue.as_deref());
+                    self.options.print(&*self.out, state.params(), &about);
+                });
+            }
+            UciCommand::IsReady => {
+                if self.options.warmup && !self.state.warmed_up {
+                    self.engine.warmup();
+                    self.state.warmed_up = true;
+                }
+                print_ready(&*self.out);
+            }
+            UciCommand::UciNewGame => {
+                self.engine.new_game();
+            }
+            UciCommand::Position(parts) => {
+                let parts_ref = parts_as_strs(&parts);
+                let mut board = self.engine.board().clone();
+                parse_position_command(&mut board, &parts_ref);
+                self.engine.set_position(board);
+            }
+            UciCommand::Perft(depth) => {
+                self.engine.stop_search();
+                let start = Instant::now();
+                let nodes = self.engine.board_mut().perft(depth);
+                let elapsed = start.elapsed();
+                print_perft_info(&*self.out, depth, nodes, elapsed);
+            }
+            UciCommand::PerftHash(depth) => {
+                self.engine.stop_search();
+                let start = Instant::now();
+                let mut table = PerftTable::new(PERFT_HASH_MB);
+                let nodes = self.engine.board_mut().perft_hashed(depth, &mut table);
+                let elapsed = start.elapsed();
+                print_perft_info(&*self.out, depth, nodes, elapsed);
+            }
+            UciCommand::PerftCaptures(depth) => {
+                self.engine.stop_search();
+                let start = Instant::now();
+                let nodes = self.engine.board_mut().perft_captures(depth);
+                let elapsed = start.elapsed();
+                print_perft_info(&*self.out, depth, nodes, elapsed);
+            }
+            UciCommand::Go(parts) => {
+                self.handle_go(&parts);
+            }
+            UciCommand::Stop => {
+                self.engine.signal_stop();
+            }
+            UciCommand::PonderHit => {
+                self.engine.ponderhit();
+            }
+            UciCommand::SetOption(parts) => {
+                self.handle_setoption(&parts);
+            }
+            UciCommand::SetOptions(parts) => {
+                self.handle_setoptions(&parts);
+            }
+            UciCommand::Debug(value) => {
+                self.state.debug = matches!(value.as_deref(), Some("on"));
+                self.engine.set_trace(self.state.debug);
+            }
+            UciCommand::Quit => {
+                self.engine.stop_search();
+                return false;
+            }
+            UciCommand::About => {
+                self.engine.with_search_state_ref(|state| {
+                    BuildInfo::new(state.tables.nnue.as_deref()).print(&*self.out);
+                });
+            }
+            UciCommand::State => self.handle_state(),
+            UciCommand::SpeedTest => {
+                let network = self.idle_network();
+                crate::speedtest::run_speedtest(network.as_ref(), "info string ", &*self.out);
+            }
+            UciCommand::Scaling { max_threads, depth } => {
+                let network = self.idle_network();
+                crate::scaling::run_scaling(
+                    network.as_ref(),
+                    max_threads.unwrap_or_else(crate::scaling::default_max_threads),
+                    depth.unwrap_or(crate::scaling::DEFAULT_SCALING_DEPTH),
+                    "info string ",
+                    &*self.out,
+                );
+            }
+            UciCommand::Unknown(line) => {
+                if self.state.debug {
+                    eprintln!("Unknown command: {line}");
+                    eprintln!("Known commands: {}", KNOWN_COMMANDS.join(", "));
+                }
+            }
+        }
+        true
+    }
+
+    /// Process one input line. Returns false if the engine should quit.
+    pub fn handle_line(&mut self, line: &str) -> bool {
+        crate::logging::log_input(line);
+        crate::recording::record_input(line);
+        let keep_running = parse_uci_command(line).is_none_or(|cmd| self.handle_command(cmd));
+        let _ = self.out.flush();
+        keep_running
+    }
+
+    /// Run the session on commands from `reader` until `quit` or end of input.
+    pub fn run<R: BufRead>(&mut self, reader: R) {
+        for line in reader.lines() {
+            let Ok(line) = line else { continue };
+            if !self.handle_line(&line) {
+                break;
+            }
+        }
+    }
+}
@@ -82,6 +82,12 @@ pub enum XBoardCommand {
     Memory(u32),
     /// Set number of cores
     Cores(u32),
+    /// Set an engine option: `option NAME=VALUE`, or `option NAME` for a
+    /// button
+    Option {
+        name: String,
+        value: Option<String>,
+    },
     /// Analyze mode
     Analyze,
     /// Exit analyze mode
@@ -90,6 +96,8 @@ pub enum XBoardCommand {
     Pause,
     /// Resume thinking
     Resume,
+    /// Dump position, tables, options and search status (debug aid)
+    State,
     /// Quit the program
     Quit,
     /// Unknown command
@@ -122,6 +130,7 @@ fn parse_basic_command(cmd_str: &str) -> Option<XBoardCommand> {
         "exit" => Some(XBoardCommand::ExitAnalyze),
         "pause" => Some(XBoardCommand::Pause),
         "resume" => Some(XBoardCommand::Resume),
+        "state" => Some(XBoardCommand::State),
         "quit" => Some(XBoardCommand::Quit),
         "?" => Some(XBoardCommand::MoveNow),
         _ => None,
@@ -210,6 +219,17 @@ fn parse_complex_command(trimmed: &str, parts: &[&str]) -> XBoardCommand {
             let name = parts[1..].join(" ");
             XBoardCommand::Name(name)
         }
+        "option" => {
+            let setting = trimmed["option".len()..].trim();
+            let (name, value) = match setting.split_once('=') {
+                Some((name, value)) => (name, Some(value.trim().to_string())),
+                None => (setting, None),
+            };
+            XBoardCommand::Option {
+                name: name.trim().to_string(),
+                value,
+            }
+        }
         _ => {
             // Check if it's a move (starts with lowercase letter or is "O-O")
             if is_likely_move(parts[0]) {
@@ -356,6 +376,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_option() {
+        match parse_xboard_command("option Resign Threshold=800") {
+            Some(XBoardCommand::Option { name, value }) => {
+                assert_eq!(name, "Resign Threshold");
+                assert_eq!(value.as_deref(), Some("800"));
+            }
+            _ => panic!("Expected Option"),
+        }
+        assert!(matches!(
+            parse_xboard_command("option Clear Hash"),
+            Some(XBoardCommand::Option { value: None, .. })
+        ));
+    }
+
     #[test]
     fn test_parse_time_control() {
         // Plain minutes
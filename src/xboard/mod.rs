@@ -15,7 +15,8 @@
 pub mod command;
 pub mod output;
 
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
@@ -25,12 +26,19 @@ use parking_lot::Mutex;
 
 use crate::board::{
     find_best_move, find_best_move_with_ponder, find_best_move_with_time_and_ponder, Board, Color,
-    Move, SearchClock, SearchLimits, SearchResult, SearchState, DEFAULT_TT_MB,
+    GameResult, Move, SearchClock, SearchLimits, SearchResult, SearchState, DEFAULT_TT_MB,
 };
+use crate::engine::adjudicate::{ResignDecision, ResignPolicy};
 use crate::engine::time::{TimeConfig, TimeControl};
+use crate::engine::{SearchStatus, StateReport};
+use crate::output::{send, ReportThrottle, SharedWriter, MAX_REPORT_INTERVAL_MS};
+use crate::pgn::{self, PgnGame};
 
 use command::{parse_xboard_command, XBoardCommand};
-use output::{format_error, format_features, format_illegal_move, format_move, format_pong};
+use output::{
+    format_error, format_features, format_game_over, format_illegal_move, format_move, format_pong,
+    ENGINE_NAME,
+};
 
 /// Ponder state for background thinking
 struct PonderState {
@@ -73,6 +81,14 @@ pub struct XBoardHandler {
     analyze_handle: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
     /// Whether the engine is paused
     paused: bool,
+    /// Decides when to resign or offer a draw
+    resign: ResignPolicy,
+    /// Protocol output sink
+    out: SharedWriter,
+    /// Rate limit for analysis output
+    throttle: Arc<ReportThrottle>,
+    /// File finished games are appended to as PGN
+    pgn_file: Option<PathBuf>,
 }
 
 impl Default for XBoardHandler {
@@ -90,6 +106,7 @@ impl XBoardHandler {
                 self.force_mode = false;
                 self.engine_color = Some(Color::Black);
                 self.move_history.clear();
+                self.resign.reset();
                 self.state.lock().new_search();
                 None
             }
@@ -99,6 +116,7 @@ impl XBoardHandler {
                     Ok(board) => {
                         self.board = board;
                         self.move_history.clear();
+                        self.resign.reset();
                         None
                     }
                     Err(e) => Some(format_error(fen, &e.to_string())),
@@ -142,9 +160,9 @@ impl XBoardHandler {
                 }
                 None
             }
-            XBoardCommand::Result(_) => {
+            XBoardCommand::Result(result) => {
                 self.force_mode = true;
-                None
+                self.save_game(result)
             }
             XBoardCommand::Hint => {
                 if let Some(mv) = self.get_hint() {
@@ -154,9 +172,10 @@ impl XBoardHandler {
                 }
             }
             XBoardCommand::Draw => {
-                // For now, always decline draws
-                // Could check if position is drawn and accept
-                None
+                // Accept draws that could be claimed or that the resign
+                // policy would offer itself; otherwise decline by ignoring
+                // the offer
+                (self.board.is_draw() || self.resign.wants_draw()).then(|| "offer draw".to_string())
             }
             _ => None, // Commands not handled by this helper
         }
@@ -219,7 +238,8 @@ impl XBoardHandler {
             }
             XBoardCommand::Memory(mb) => {
                 self.stop_ponder();
-                self.state.lock().reset_tables(*mb as usize);
+                // XBoard's memory is the total for all tables
+                self.state.lock().resize_within(*mb as usize, 1);
                 None
             }
             XBoardCommand::Cores(_n) => {
@@ -238,7 +258,10 @@ impl XBoardHandler {
             }
             XBoardCommand::Protover(version) => {
                 if *version >= 2 {
-                    Some(format_features())
+                    Some(format_features(
+                        self.resign.config(),
+                        self.throttle.min_interval_ms(),
+                    ))
                 } else {
                     None
                 }
@@ -267,10 +290,13 @@ impl XBoardHandler {
                 None
             }
             XBoardCommand::Quit => {
+                // The main loop ends the session after this
                 self.stop_ponder();
                 self.stop_analyze();
-                std::process::exit(0);
+                None
             }
+            XBoardCommand::Option { name, value } => self.set_option(name, value.as_deref()),
+            XBoardCommand::State => Some(self.state_report()),
             XBoardCommand::Unknown(s) => Some(format_error(s, "unknown command")),
             _ => None,
         }
@@ -330,9 +356,15 @@ impl XBoardHandler {
         }
     }
 
-    /// Create a new `XBoard` handler.
+    /// Create a new `XBoard` handler writing to stdout.
     #[must_use]
     pub fn new() -> Self {
+        Self::with_writer(crate::output::stdout())
+    }
+
+    /// Create a new `XBoard` handler writing to `out`.
+    #[must_use]
+    pub fn with_writer(out: SharedWriter) -> Self {
         XBoardHandler {
             board: Board::new(),
             state: Arc::new(Mutex::new(SearchState::new(DEFAULT_TT_MB))),
@@ -356,6 +388,10 @@ impl XBoardHandler {
             analyze_mode: false,
             analyze_handle: None,
             paused: false,
+            resign: ResignPolicy::default(),
+            out,
+            throttle: Arc::new(ReportThrottle::default()),
+            pgn_file: None,
         }
     }
 
@@ -389,6 +425,8 @@ impl XBoardHandler {
         let stop = Arc::new(AtomicBool::new(false));
         let stop_clone = Arc::clone(&stop);
         let post_thinking = self.post_thinking;
+        let out = Arc::clone(&self.out);
+        let throttle = Arc::clone(&self.throttle);
 
         let handle = thread::spawn(move || {
             let mut board = board;
@@ -401,25 +439,31 @@ impl XBoardHandler {
                     break;
                 }
 
-                let start_time = Instant::now();
-                let result = find_best_move(&mut board, &mut guard, depth, &stop_clone);
+                let result = find_best_move_with_ponder(&mut board, &mut guard, depth, &stop_clone);
 
                 if stop_clone.load(Ordering::Relaxed) {
                     break;
                 }
 
-                if let Some(mv) = result {
-                    let elapsed_cs = start_time.elapsed().as_millis() as u64 / 10;
-                    let nodes = guard.stats.nodes;
-                    let score = 0; // We don't have easy access to score here
-
+                if let Some(mv) = result.best_move {
                     if post_thinking {
                         // XBoard analyze output format: depth score time nodes pv
                         let san = board.move_to_san(&mv);
-                        println!("{depth} {score} {elapsed_cs} {nodes} {san}");
+                        let elapsed_cs = result.elapsed.as_millis() / 10;
+                        let line = format!(
+                            "{depth} {} {elapsed_cs} {} {san}",
+                            result.score, result.nodes
+                        );
+                        throttle.report(&*out, 1, vec![line]);
                     }
                 }
             }
+            // A stopped analysis is of a position the interface has left
+            if stop_clone.load(Ordering::Relaxed) {
+                throttle.discard();
+            } else {
+                throttle.flush(&*out);
+            }
         });
 
         self.analyze_handle = Some((stop, handle));
@@ -501,43 +545,171 @@ impl XBoardHandler {
     /// Run the `XBoard` protocol main loop.
     pub fn run(&mut self) {
         let stdin = io::stdin();
-        let mut stdout = io::stdout();
+        self.run_from(stdin.lock());
+    }
 
-        for line in stdin.lock().lines() {
+    /// Run the `XBoard` protocol main loop on commands from `reader`.
+    pub fn run_from<R: BufRead>(&mut self, reader: R) {
+        for line in reader.lines() {
             let Ok(line) = line else { break };
+            crate::logging::log_input(&line);
+            crate::recording::record_input(&line);
 
             if let Some(cmd) = parse_xboard_command(&line) {
+                if matches!(cmd, XBoardCommand::Quit) {
+                    self.handle_command(&cmd);
+                    break;
+                }
                 let response = self.handle_command(&cmd);
                 if let Some(resp) = response {
                     for line in resp.lines() {
-                        writeln!(stdout, "{line}").ok();
+                        send(&*self.out, line);
                     }
-                    stdout.flush().ok();
+                    self.out.flush().ok();
                 }
 
                 // Check if we should think
                 if self.should_think() {
-                    if let Some(result) = self.think() {
-                        if let Some(mv) = result.best_move {
-                            let output = format_move(&self.board, &mv);
-                            writeln!(stdout, "{output}").ok();
-                            stdout.flush().ok();
-                            let info = self.board.make_move(mv);
-                            self.move_history.push((mv, info));
-
-                            // Start pondering if enabled and we have a ponder move
-                            if self.pondering_enabled {
-                                if let Some(ponder_mv) = result.ponder_move {
-                                    self.start_ponder(ponder_mv);
-                                }
-                            }
-                        }
-                    }
+                    self.play_engine_move();
                 }
             }
         }
     }
 
+    /// Search, then play the best move, resigning or offering a draw first
+    /// if the resign policy says so
+    fn play_engine_move(&mut self) {
+        let Some(result) = self.think() else { return };
+        let Some(mv) = result.best_move else { return };
+
+        // A forced move is played unsearched; fall back to the table's score
+        let score = if result.depth > 0 {
+            Some(result.score)
+        } else {
+            self.root_score()
+        };
+        match self.resign.record(score) {
+            Some(ResignDecision::Resign) => {
+                send(&*self.out, "resign");
+                self.out.flush().ok();
+                self.force_mode = true;
+                return;
+            }
+            // The offer must precede the move to apply to it
+            Some(ResignDecision::OfferDraw) => send(&*self.out, "offer draw"),
+            None => {}
+        }
+
+        send(&*self.out, &format_move(&self.board, &mv));
+        self.out.flush().ok();
+        let info = self.board.make_move(mv);
+        self.move_history.push((mv, info));
+
+        if let Some(result) = self.announce_game_over() {
+            send(&*self.out, &result);
+            self.out.flush().ok();
+            return;
+        }
+
+        // Start pondering if enabled and we have a ponder move
+        if self.pondering_enabled {
+            if let Some(ponder_mv) = result.ponder_move {
+                self.start_ponder(ponder_mv);
+            }
+        }
+    }
+
+    /// Score of the root position from the side to move's point of view, as
+    /// stored in the transposition table
+    fn root_score(&self) -> Option<i32> {
+        let state = self.state.lock();
+        state
+            .tables
+            .tt
+            .probe(self.board.hash())
+            .map(|entry| entry.score())
+    }
+
+    /// Apply an `option NAME=VALUE` command for one of the options offered
+    /// in the feature list
+    fn set_option(&mut self, name: &str, value: Option<&str>) -> Option<String> {
+        if name.eq_ignore_ascii_case("PgnFile") {
+            self.pgn_file = value.filter(|path| !path.is_empty()).map(PathBuf::from);
+            return None;
+        }
+        let mut config = *self.resign.config();
+        let parsed = value.and_then(|v| v.parse::<i32>().ok());
+        match (name.to_ascii_lowercase().as_str(), parsed) {
+            ("resignthreshold", Some(v)) => config.resign_threshold = v.clamp(0, 10000),
+            ("resignmovecount", Some(v)) => config.resign_moves = v.clamp(1, 100).unsigned_abs(),
+            ("drawofferscore", Some(v)) => config.draw_offer_score = v.clamp(0, 1000),
+            ("minreportinterval", Some(v)) => {
+                let ms = u64::from(v.max(0).unsigned_abs()).min(MAX_REPORT_INTERVAL_MS);
+                self.throttle.set_min_interval_ms(ms);
+            }
+            _ => {
+                let command = format!("option {name}={}", value.unwrap_or_default());
+                return Some(format_error(&command, "unknown option or bad value"));
+            }
+        }
+        self.resign.set_config(config);
+        None
+    }
+
+    /// The `state` report as `#` comment lines, which interfaces ignore.
+    /// Table fill is reported as busy while a ponder or analyze search holds
+    /// the tables.
+    fn state_report(&self) -> String {
+        let search = if self.analyze_handle.is_some() {
+            SearchStatus::Analyzing
+        } else if self.ponder.is_some() {
+            SearchStatus::Pondering
+        } else {
+            SearchStatus::Idle
+        };
+        let config = self.resign.config();
+        let report = StateReport::new(&self.board, search)
+            .with_hashfull(
+                self.state
+                    .try_lock()
+                    .map(|state| state.tables.tt.hashfull_per_mille()),
+            )
+            .with_option("post", self.post_thinking)
+            .with_option("hard", self.pondering_enabled)
+            .with_option("force", self.force_mode)
+            .with_option("sd", self.max_depth)
+            .with_option(
+                "st",
+                self.time_per_move_cs
+                    .map_or_else(|| "none".to_string(), |cs| (cs / 100).to_string()),
+            )
+            .with_option(
+                "level",
+                format!(
+                    "{} {} {}",
+                    self.moves_per_session, self.base_time_sec, self.increment_sec
+                ),
+            )
+            .with_option("time", self.engine_time_cs)
+            .with_option("otim", self.opponent_time_cs)
+            .with_option("ResignThreshold", config.resign_threshold)
+            .with_option("ResignMoveCount", config.resign_moves)
+            .with_option("DrawOfferScore", config.draw_offer_score)
+            .with_option("MinReportInterval", self.throttle.min_interval_ms())
+            .with_option(
+                "PgnFile",
+                self.pgn_file
+                    .as_ref()
+                    .map_or_else(|| "none".to_string(), |path| path.display().to_string()),
+            );
+        report
+            .lines()
+            .iter()
+            .map(|line| format!("# state {line}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Handle a single `XBoard` command.
     pub fn handle_command(&mut self, cmd: &XBoardCommand) -> Option<String> {
         if let Some(response) = self.handle_game_management_command(cmd) {
@@ -583,6 +755,11 @@ impl XBoardHandler {
             Ok(mv) => {
                 let info = self.board.make_move(mv);
                 self.move_history.push((mv, info));
+                if !self.analyze_mode {
+                    if let Some(result) = self.announce_game_over() {
+                        return Some(result);
+                    }
+                }
                 // Restart analysis if in analyze mode
                 if self.analyze_mode && !self.paused {
                     self.start_analyze();
@@ -593,6 +770,51 @@ impl XBoardHandler {
         }
     }
 
+    /// If the game has ended, stop playing and return the result line the
+    /// protocol expects the engine to send
+    fn announce_game_over(&mut self) -> Option<String> {
+        let result = format_game_over(&mut self.board)?;
+        self.force_mode = true;
+        Some(result)
+    }
+
+    /// The game played since the last `new` or `setboard`, with the
+    /// players' names as tags
+    #[must_use]
+    pub fn game_pgn(&self, result: Option<GameResult>) -> PgnGame {
+        let mut start = self.board.clone();
+        for &(mv, info) in self.move_history.iter().rev() {
+            start.unmake_move(mv, info);
+        }
+        let moves = self.move_history.iter().map(|&(mv, _)| mv).collect();
+        let mut game = PgnGame::new(start, moves);
+        game.result = result;
+        if let Some(color) = self.engine_color {
+            let opponent = self.opponent_name.as_deref().unwrap_or("?");
+            let (white, black) = match color {
+                Color::White => (ENGINE_NAME, opponent),
+                Color::Black => (opponent, ENGINE_NAME),
+            };
+            game.set_header("White", white);
+            game.set_header("Black", black);
+        }
+        game
+    }
+
+    /// Append the game ended by `result RESULT {COMMENT}` to the PGN file,
+    /// if one is set, returning an error line if it cannot be written
+    fn save_game(&self, result: &str) -> Option<String> {
+        let path = self.pgn_file.as_ref()?;
+        if self.move_history.is_empty() {
+            return None;
+        }
+        let tag = result.split_whitespace().next().unwrap_or_default();
+        let game = self.game_pgn(GameResult::from_pgn(tag));
+        pgn::append_game(path, &game)
+            .err()
+            .map(|e| format_error(&format!("result {result}"), &e.to_string()))
+    }
+
     /// Check if the engine should think now.
     fn should_think(&self) -> bool {
         if self.force_mode || self.paused || self.analyze_mode {
@@ -650,8 +872,11 @@ impl XBoardHandler {
                 soft_time_percent: 5,
                 hard_time_percent: 15,
                 default_max_nodes: 0,
+                nodes_time: 0,
             };
-            let (soft_ms, hard_ms) = time_control.compute_limits(&config);
+            // "otim" is only meaningful once the interface has sent one
+            let opponent_time_ms = (self.opponent_time_cs > 0).then(|| self.opponent_time_cs * 10);
+            let (soft_ms, hard_ms) = time_control.compute_limits_against(&config, opponent_time_ms);
 
             let start = Instant::now();
             let soft_deadline = start + Duration::from_millis(soft_ms);
@@ -754,6 +979,45 @@ mod tests {
         assert!(!handler.board.white_to_move());
     }
 
+    #[test]
+    fn test_resign_options() {
+        let mut handler = XBoardHandler::new();
+        let option = |name: &str, value: &str| XBoardCommand::Option {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        };
+        assert!(handler
+            .handle_command(&option("ResignThreshold", "900"))
+            .is_none());
+        assert!(handler
+            .handle_command(&option("ResignMoveCount", "2"))
+            .is_none());
+        assert!(handler.handle_command(&option("Contempt", "10")).is_some());
+        assert_eq!(handler.resign.config().resign_threshold, 900);
+        assert_eq!(handler.resign.config().resign_moves, 2);
+
+        let features = handler.handle_command(&XBoardCommand::Protover(2)).unwrap();
+        assert!(features.contains("ResignThreshold -spin 900 0 10000"));
+
+        assert!(handler
+            .handle_command(&option("MinReportInterval", "200"))
+            .is_none());
+        assert_eq!(handler.throttle.min_interval_ms(), 200);
+    }
+
+    #[test]
+    fn test_resigns_hopeless_position() {
+        let capture = crate::output::CaptureWriter::new();
+        let mut handler = XBoardHandler::with_writer(Arc::new(capture.clone()));
+        let input = "option ResignThreshold=500\noption ResignMoveCount=1\nsd 3\n\
+                     setboard 4k3/8/8/8/8/8/8/qq2K3 w - - 0 1\ngo\n";
+        handler.run_from(input.as_bytes());
+        let lines = capture.lines();
+        assert!(lines.iter().any(|l| l == "resign"), "{lines:?}");
+        assert!(!lines.iter().any(|l| l.starts_with("move ")), "{lines:?}");
+        assert!(handler.force_mode);
+    }
+
     #[test]
     fn test_pause_resume() {
         let mut handler = XBoardHandler::new();
@@ -792,6 +1056,38 @@ mod tests {
         assert!(result.is_none()); // Should be silent no-op
     }
 
+    #[test]
+    fn test_user_move_announces_result() {
+        let mut handler = XBoardHandler::new();
+        handler.handle_command(&XBoardCommand::New);
+        for mv in ["f3", "e5", "g4"] {
+            assert_eq!(
+                handler.handle_command(&XBoardCommand::UserMove(mv.to_string())),
+                None
+            );
+        }
+        let result = handler.handle_command(&XBoardCommand::UserMove("Qh4#".to_string()));
+        assert_eq!(result.as_deref(), Some("0-1 {Black mates}"));
+        // The engine stops playing once the game is over
+        assert!(handler.force_mode);
+        assert!(!handler.should_think());
+    }
+
+    #[test]
+    fn test_draw_offer_accepted_only_when_claimable() {
+        let mut handler = XBoardHandler::new();
+        handler.handle_command(&XBoardCommand::New);
+        assert_eq!(handler.handle_command(&XBoardCommand::Draw), None);
+
+        handler.handle_command(&XBoardCommand::SetBoard(
+            "8/8/4k3/8/8/4K3/4R3/8 w - - 100 80".to_string(),
+        ));
+        assert_eq!(
+            handler.handle_command(&XBoardCommand::Draw).as_deref(),
+            Some("offer draw")
+        );
+    }
+
     #[test]
     fn test_result() {
         let mut handler = XBoardHandler::new();
@@ -800,4 +1096,31 @@ mod tests {
         handler.handle_command(&XBoardCommand::Result("1-0 {White wins}".to_string()));
         assert!(handler.force_mode); // Result sets force mode
     }
+
+    #[test]
+    fn test_finished_games_are_saved_as_pgn() {
+        let path = std::env::temp_dir().join(format!("xboard_games_{}.pgn", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut handler = XBoardHandler::new();
+        handler.handle_command(&XBoardCommand::Option {
+            name: "PgnFile".to_string(),
+            value: Some(path.display().to_string()),
+        });
+        handler.handle_command(&XBoardCommand::Name("Opponent".to_string()));
+        for _ in 0..2 {
+            handler.handle_command(&XBoardCommand::New);
+            for mv in ["f3", "e5", "g4", "Qh4#"] {
+                handler.handle_command(&XBoardCommand::UserMove(mv.to_string()));
+            }
+            handler.handle_command(&XBoardCommand::Result("0-1 {Black mates}".to_string()));
+        }
+
+        let games = pgn::parse_games(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].san_moves(), ["f3", "e5", "g4", "Qh4#"]);
+        assert_eq!(games[0].result, Some(GameResult::BlackWins));
+        assert_eq!(games[0].header("White"), Some("Opponent"));
+        assert_eq!(games[0].header("Black"), Some(ENGINE_NAME));
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -11,6 +11,11 @@
 //! - pv: principal variation in SAN
 
 use crate::board::{Board, Move};
+use crate::engine::adjudicate::ResignConfig;
+use crate::output::MAX_REPORT_INTERVAL_MS;
+
+/// Engine name announced to the interface and written to saved games
+pub const ENGINE_NAME: &str = "ChessEngine 0.1";
 
 /// Format a principal variation line for `XBoard` output.
 ///
@@ -49,11 +54,12 @@ pub fn format_move(board: &Board, mv: &Move) -> String {
     format!("move {san}")
 }
 
-/// Format feature announcement after protover.
+/// Format feature announcement after protover, offering the resign and
+/// draw settings as engine options.
 #[must_use]
-pub fn format_features() -> String {
+pub fn format_features(resign: &ResignConfig, min_report_interval_ms: u64) -> String {
     let features = [
-        "feature myname=\"ChessEngine 0.1\"",
+        &format!("feature myname=\"{ENGINE_NAME}\""),
         "feature setboard=1",
         "feature ping=1",
         "feature san=1",
@@ -72,6 +78,23 @@ pub fn format_features() -> String {
         "feature debug=0",
         "feature memory=1",
         "feature smp=0",
+        &format!(
+            "feature option=\"ResignThreshold -spin {} 0 10000\"",
+            resign.resign_threshold
+        ),
+        &format!(
+            "feature option=\"ResignMoveCount -spin {} 1 100\"",
+            resign.resign_moves
+        ),
+        &format!(
+            "feature option=\"DrawOfferScore -spin {} 0 1000\"",
+            resign.draw_offer_score
+        ),
+        &format!(
+            "feature option=\"MinReportInterval -spin {min_report_interval_ms} 0 \
+             {MAX_REPORT_INTERVAL_MS}\""
+        ),
+        "feature option=\"PgnFile -file \"",
         "feature done=1",
     ];
     features.join("\n")
@@ -108,6 +131,14 @@ pub fn format_result(result: &str, reason: &str) -> String {
     format!("{result} {{{reason}}}")
 }
 
+/// Result message for a finished game (see [`Board::game_result`]), or
+/// `None` if play continues.
+#[must_use]
+pub fn format_game_over(board: &mut Board) -> Option<String> {
+    let reason = board.game_result()?;
+    Some(format_result(reason.result().as_pgn(), &reason.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,8 +146,10 @@ mod tests {
 
     #[test]
     fn test_format_features() {
-        let features = format_features();
+        let features = format_features(&ResignConfig::default(), 50);
         assert!(features.contains("myname"));
+        assert!(features.contains("feature option=\"ResignThreshold -spin 0 0 10000\""));
+        assert!(features.contains("feature option=\"MinReportInterval -spin 50 0 5000\""));
         assert!(features.contains("setboard=1"));
         assert!(features.contains("done=1"));
     }
@@ -138,4 +171,43 @@ mod tests {
     fn test_format_pong() {
         assert_eq!(format_pong(42), "pong 42");
     }
+
+    #[test]
+    fn test_format_game_over() {
+        let result = |fen: &str| format_game_over(&mut Board::from_fen(fen));
+
+        assert_eq!(format_game_over(&mut Board::new()), None);
+        // Fool's mate
+        assert_eq!(
+            result("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").as_deref(),
+            Some("0-1 {Black mates}")
+        );
+        assert_eq!(
+            result("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").as_deref(),
+            Some("1-0 {White mates}")
+        );
+        assert_eq!(
+            result("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").as_deref(),
+            Some("1/2-1/2 {Stalemate}")
+        );
+        assert_eq!(
+            result("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80").as_deref(),
+            Some("1/2-1/2 {Draw by fifty move rule}")
+        );
+        assert_eq!(
+            result("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1").as_deref(),
+            Some("1/2-1/2 {Insufficient material}")
+        );
+
+        let mut board = Board::new();
+        for mv in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ] {
+            board.make_move_uci(mv).unwrap();
+        }
+        assert_eq!(
+            format_game_over(&mut board).as_deref(),
+            Some("1/2-1/2 {Draw by repetition}")
+        );
+    }
 }
@@ -13,6 +13,8 @@ pub(crate) struct ZobristKeys {
     pub(crate) castling_keys: [[u64; 2]; 2],
     // en_passant_keys[file_index] (only file matters for EP target)
     pub(crate) en_passant_keys: [u64; 8],
+    // check_keys[color][checks_given - 1] (three-check only)
+    pub(crate) check_keys: [[u64; 3]; 2],
 }
 
 impl ZobristKeys {
@@ -42,11 +44,20 @@ impl ZobristKeys {
             *key = rng.gen();
         }
 
+        // Drawn last so that the keys above, and standard hashes, are unchanged
+        let mut check_keys = [[0; 3]; 2];
+        for color in &mut check_keys {
+            for key in color.iter_mut() {
+                *key = rng.gen();
+            }
+        }
+
         ZobristKeys {
             piece_keys,
             black_to_move_key,
             castling_keys,
             en_passant_keys,
+            check_keys,
         }
     }
 }
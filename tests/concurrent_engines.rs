@@ -0,0 +1,108 @@
+//! Many `EngineController`s in one process must not interfere.
+//!
+//! Single-threaded searches are deterministic, so an engine searching
+//! alongside others has to report exactly the same move and node count as
+//! one searching alone; any shared mutable state would show up as a
+//! difference.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chess_engine::board::{Board, Move, SearchIterationInfo};
+use chess_engine::engine::{EngineController, SearchParams};
+
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+];
+const DEPTH: u32 = 5;
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Best move and total nodes of a fixed-depth search of `fen`
+fn search_fen(fen: &str) -> (Option<Move>, u64) {
+    let nodes = Arc::new(AtomicU64::new(0));
+    let mut engine = EngineController::new(4);
+    let nodes_cb = Arc::clone(&nodes);
+    engine.set_info_callback(Some(Arc::new(move |info: &SearchIterationInfo| {
+        nodes_cb.store(info.nodes, Ordering::Relaxed);
+    })));
+    engine.set_board(Board::from_fen(fen));
+
+    let (tx, rx) = mpsc::channel();
+    let params = SearchParams {
+        depth: Some(DEPTH),
+        multi_pv: 1,
+        ..SearchParams::default()
+    };
+    engine.start_search(params, move |result| {
+        tx.send(result.best_move).unwrap();
+    });
+    let best = rx.recv_timeout(TIMEOUT).expect("search did not finish");
+    (best, nodes.load(Ordering::Relaxed))
+}
+
+#[test]
+fn concurrent_engines_match_isolated_searches() {
+    let isolated: Vec<_> = POSITIONS.iter().map(|fen| search_fen(fen)).collect();
+    assert!(isolated
+        .iter()
+        .all(|(best, nodes)| best.is_some() && *nodes > 0));
+
+    // Two engines per position, all searching at once
+    let handles: Vec<_> = POSITIONS
+        .iter()
+        .chain(POSITIONS.iter())
+        .map(|fen| thread::spawn(move || search_fen(fen)))
+        .collect();
+    let concurrent: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    for (i, result) in concurrent.iter().enumerate() {
+        let expected = &isolated[i % POSITIONS.len()];
+        assert_eq!(
+            result,
+            expected,
+            "engine {i} diverged on {}",
+            POSITIONS[i % POSITIONS.len()]
+        );
+    }
+}
+
+#[test]
+fn stopping_one_engine_leaves_others_running() {
+    let mut stopped = EngineController::new(4);
+    let mut other = EngineController::new(4);
+
+    let (tx_stopped, rx_stopped) = mpsc::channel();
+    stopped.start_search(
+        SearchParams {
+            infinite: true,
+            multi_pv: 1,
+            ..SearchParams::default()
+        },
+        move |result| tx_stopped.send(result.best_move).unwrap(),
+    );
+
+    let (tx_other, rx_other) = mpsc::channel();
+    other.start_search(
+        SearchParams {
+            infinite: true,
+            multi_pv: 1,
+            ..SearchParams::default()
+        },
+        move |result| tx_other.send(result.best_move).unwrap(),
+    );
+
+    thread::sleep(Duration::from_millis(50));
+    stopped.signal_stop();
+    assert!(rx_stopped.recv_timeout(TIMEOUT).unwrap().is_some());
+
+    // The other engine is still thinking until told to stop
+    assert!(rx_other.recv_timeout(Duration::from_millis(200)).is_err());
+    other.signal_stop();
+    assert!(rx_other.recv_timeout(TIMEOUT).unwrap().is_some());
+}
@@ -0,0 +1,114 @@
+//! Differential move generation tests against the `shakmaty` crate.
+//!
+//! Random playouts from positions rich in castling, promotions and en passant
+//! compare the full legal move set at every ply with an independent
+//! reference implementation, catching rare movegen bugs that fixed perft
+//! suites miss (underpromotion captures, en passant discovered checks, ...).
+
+use std::collections::BTreeSet;
+
+use chess_engine::board::Board;
+use rand::prelude::*;
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess, EnPassantMode, Position};
+
+/// Playout start positions
+const START_FENS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    // Kiwipete
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    // Perft position 3: en passant pins along the rank
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    // Perft position 4: promotions and castling through check
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    // Perft position 5
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    // Underpromotion captures on both wings
+    "n1n5/1P2k3/8/8/8/8/1p2K3/N1N5 w - - 0 1",
+    // En passant capture exposing the king on the rank
+    "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+    // En passant resolving a pawn check
+    "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+];
+
+/// Legal moves of `board` in UCI notation
+fn engine_moves(board: &mut Board) -> BTreeSet<String> {
+    board
+        .generate_moves()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Legal moves of `pos` in UCI notation
+fn reference_moves(pos: &Chess) -> BTreeSet<String> {
+    pos.legal_moves()
+        .iter()
+        .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+        .collect()
+}
+
+/// Run `playouts` random games of up to `max_plies` from each start position,
+/// comparing move sets and positions after every move.
+fn run_differential(seed: u64, playouts: usize, max_plies: usize) -> usize {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut positions = 0;
+
+    for game in 0..playouts {
+        let start = START_FENS[game % START_FENS.len()];
+        let mut board = Board::from_fen(start);
+        let mut pos: Chess = Fen::from_ascii(start.as_bytes())
+            .unwrap()
+            .into_position(CastlingMode::Standard)
+            .unwrap();
+
+        for _ in 0..max_plies {
+            let fen = board.to_fen();
+            let ours = engine_moves(&mut board);
+            let theirs = reference_moves(&pos);
+            assert_eq!(
+                ours,
+                theirs,
+                "move sets differ in {fen}\n  missing: {:?}\n  extra: {:?}",
+                theirs.difference(&ours).collect::<Vec<_>>(),
+                ours.difference(&theirs).collect::<Vec<_>>()
+            );
+            positions += 1;
+
+            let Some(uci) = ours.iter().choose(&mut rng).cloned() else {
+                break;
+            };
+            board.make_move_uci(&uci).unwrap();
+            let mv = pos
+                .legal_moves()
+                .into_iter()
+                .find(|m| m.to_uci(CastlingMode::Standard).to_string() == uci)
+                .unwrap();
+            pos.play_unchecked(mv);
+
+            let reference_fen = Fen::from_position(&pos, EnPassantMode::Always).to_string();
+            let ours_placement = board.to_fen();
+            assert_eq!(
+                ours_placement.split(' ').take(3).collect::<Vec<_>>(),
+                reference_fen.split(' ').take(3).collect::<Vec<_>>(),
+                "positions diverged after {uci} from {fen}"
+            );
+        }
+    }
+
+    positions
+}
+
+#[test]
+fn movegen_matches_reference() {
+    let positions = run_differential(0x5eed, 200, 120);
+    assert!(positions > 5_000, "only {positions} positions checked");
+}
+
+#[test]
+#[ignore = "slow: run with --ignored for a deep differential sweep"]
+fn movegen_matches_reference_deep() {
+    for seed in 0..20 {
+        run_differential(seed, 500, 200);
+    }
+}
@@ -0,0 +1,621 @@
+//! In-process protocol tests asserting on exact engine output.
+//!
+//! Sessions write to a `CaptureWriter` instead of stdout, so these tests
+//! drive the UCI and `XBoard` front ends without spawning the binary.
+
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chess_engine::board::nnue::NnueNetwork;
+use chess_engine::board::Board;
+use chess_engine::engine::time::{TimeConfig, TimeControl, TimeManager};
+use chess_engine::engine::EngineController;
+use chess_engine::output::CaptureWriter;
+use chess_engine::uci::session::UciSession;
+use chess_engine::xboard::XBoardHandler;
+
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn uci_session() -> (UciSession, CaptureWriter) {
+    let capture = CaptureWriter::new();
+    let session = UciSession::new(1, Arc::new(capture.clone()));
+    (session, capture)
+}
+
+#[test]
+fn uci_handshake_output_is_exact() {
+    let (mut session, capture) = uci_session();
+
+    assert!(session.handle_line("uci"));
+    let lines = capture.take();
+    assert_eq!(
+        lines[..2],
+        ["id name chess_engine", "id author Dean Menezes"]
+    );
+    assert!(lines[2..lines.len() - 1]
+        .iter()
+        .all(|l| l.starts_with("option name ")));
+    assert_eq!(lines.last().map(String::as_str), Some("uciok"));
+
+    assert!(session.handle_line("isready"));
+    assert_eq!(capture.take(), ["readyok"]);
+
+    assert!(!session.handle_line("quit"));
+    assert!(capture.lines().is_empty());
+}
+
+#[test]
+fn uci_perft_reports_node_count() {
+    let (mut session, capture) = uci_session();
+    session.handle_line("position startpos");
+    session.handle_line("perft 3");
+
+    let lines = capture.take();
+    assert_eq!(lines.len(), 1);
+    assert!(
+        lines[0].starts_with("info string perft depth 3 nodes 8902 time_ms "),
+        "{lines:?}"
+    );
+}
+
+#[test]
+fn uci_go_reports_info_lines_then_bestmove() {
+    let (mut session, capture) = uci_session();
+    // Every iteration reported, however fast
+    session.handle_line("setoption name MinReportInterval value 0");
+    session.handle_line("position startpos moves e2e4");
+    session.handle_line("go depth 3");
+
+    let bestmove = capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+
+    let lines = capture.lines();
+    assert!(lines[0].starts_with("info string time "), "{lines:?}");
+    for depth in 1..=3 {
+        let prefix = format!("info depth {depth} ");
+        assert!(
+            lines
+                .iter()
+                .any(|l| l.starts_with(&prefix) && l.contains(" pv ")),
+            "missing depth {depth} info: {lines:?}"
+        );
+    }
+    assert_eq!(lines.last(), Some(&bestmove));
+
+    let mv = bestmove.split_whitespace().nth(1).unwrap();
+    let mut board = Board::new();
+    board.make_move_uci("e2e4").unwrap();
+    assert!(board.parse_move(mv).is_ok(), "illegal bestmove: {bestmove}");
+}
+
+#[test]
+fn uci_fast_iterations_are_coalesced() {
+    let (mut session, capture) = uci_session();
+    session.handle_line("setoption name MinReportInterval value 5000");
+    session.handle_line("position startpos");
+    session.handle_line("go depth 5");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+
+    // The first iteration goes out at once, the last one before the move
+    let depths: Vec<String> = capture
+        .take()
+        .iter()
+        .filter(|l| l.starts_with("info depth ") && l.contains(" pv "))
+        .map(|l| l.split_whitespace().nth(2).unwrap().to_string())
+        .collect();
+    assert_eq!(depths, ["1", "5"]);
+}
+
+#[test]
+fn uci_debug_on_adds_search_diagnostics() {
+    let (mut session, capture) = uci_session();
+    session.handle_line("position startpos");
+    session.handle_line("go depth 2");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    assert!(!capture
+        .take()
+        .iter()
+        .any(|l| l.starts_with("info string debug")));
+
+    session.handle_line("debug on");
+    session.handle_line("go wtime 60000 btime 30000 depth 2");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+
+    let lines = capture.lines();
+    for prefix in [
+        "info string debug time control ",
+        "info string debug tt depth 2 ",
+        "info string debug root order depth 2 ",
+        "info string debug search depth 2 ",
+    ] {
+        assert!(
+            lines.iter().any(|l| l.starts_with(prefix)),
+            "missing {prefix:?}: {lines:?}"
+        );
+    }
+    assert!(lines.iter().any(|l| l.contains(" opponent 30000 ")));
+
+    // The summary comes right before the move, with the time to each depth
+    let summary = &lines[lines.len() - 2];
+    assert!(summary.contains(" ebf "), "{summary}");
+    assert!(summary.contains(" ttd 1:"), "{summary}");
+    assert!(lines[lines.len() - 1].starts_with("bestmove "));
+}
+
+#[test]
+fn uci_prune_audit_reports_each_pruning_before_the_move() {
+    let (mut session, capture) = uci_session();
+    session.handle_line("setoption name PruneAudit value 2");
+    session.handle_line("position startpos moves e2e4 e7e5 g1f3 b8c6");
+    session.handle_line("go depth 6");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+
+    let lines = capture.lines();
+    let audit: Vec<&String> = lines
+        .iter()
+        .filter(|l| l.starts_with("info string debug prune "))
+        .collect();
+    assert_eq!(audit.len(), 7, "{lines:?}");
+    // LMR comes last, right before the move
+    let lmr = &lines[lines.len() - 2];
+    assert!(
+        lmr.starts_with("info string debug prune lmr sampled "),
+        "{lmr}"
+    );
+    assert!(!lmr.contains(" sampled 0 "), "{lmr}");
+    assert!(lines[lines.len() - 1].starts_with("bestmove "));
+}
+
+/// Gives every timed search the same limits and records the positions it
+/// was asked about
+struct FixedTimeManager(Mutex<Vec<String>>);
+
+impl TimeManager for FixedTimeManager {
+    fn allocate(
+        &self,
+        board: &Board,
+        control: &TimeControl,
+        _opponent_time_ms: Option<u64>,
+        _config: &TimeConfig,
+    ) -> (u64, u64) {
+        self.0.lock().unwrap().push(board.to_fen());
+        if control.is_unlimited() {
+            (u64::MAX, u64::MAX)
+        } else {
+            (300, 600)
+        }
+    }
+}
+
+#[test]
+fn uci_go_uses_a_custom_time_manager() {
+    let (mut session, capture) = uci_session();
+    let manager = Arc::new(FixedTimeManager(Mutex::new(Vec::new())));
+    session.set_time_manager(manager.clone());
+    session.handle_line("debug on");
+    session.handle_line("position startpos moves e2e4");
+    session.handle_line("go wtime 60000 btime 60000 depth 1");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+
+    let lines = capture.take();
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("info string debug time control ")
+                && l.contains(" soft 300 hard 600 request soft 300 hard 600 ")),
+        "{lines:?}"
+    );
+    let mut board = Board::new();
+    board.make_move_uci("e2e4").unwrap();
+    assert_eq!(*manager.0.lock().unwrap(), [board.to_fen()]);
+
+    // Infinite searches never ask the manager
+    session.handle_line("go infinite");
+    session.handle_line("stop");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+    assert_eq!(manager.0.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn uci_go_when_checkmated_reports_no_move() {
+    let (mut session, capture) = uci_session();
+    // Fool's mate: white is checkmated
+    session
+        .handle_line("position fen rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+    session.handle_line("go depth 2");
+
+    capture
+        .wait_for("bestmove", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+
+    let lines = capture.lines();
+    assert_eq!(
+        lines[lines.len() - 2..],
+        ["info score mate -1", "bestmove (none)"]
+    );
+}
+
+#[test]
+fn uci_searchmoves_are_searched_and_reported_when_none_is_left() {
+    let (mut session, capture) = uci_session();
+    session.handle_line("position startpos");
+    session.handle_line("go depth 3 searchmoves e2e4");
+    capture
+        .wait_for("bestmove", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    let lines = capture.take();
+    assert!(
+        lines.iter().any(|l| l.starts_with("info depth 3")),
+        "{lines:?}"
+    );
+    let bestmove = lines.last().unwrap();
+    assert!(bestmove.starts_with("bestmove e2e4"), "{bestmove}");
+
+    session.handle_line("go depth 3 searchmoves e2e4 avoidmoves e2e4");
+    capture
+        .wait_for("bestmove", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+    let lines = capture.lines();
+    assert!(lines.contains(
+        &"info string searchmoves and avoidmoves leave no legal move to search".to_string()
+    ));
+    assert_eq!(lines.last().map(String::as_str), Some("bestmove (none)"));
+}
+
+/// Score of the last `info ... score cp` line of a depth-2 search of `fen`
+fn reported_cp(white_pov: bool, fen: &str) -> i32 {
+    let (mut session, capture) = uci_session();
+    session.handle_line(&format!("setoption name WhitePovScores value {white_pov}"));
+    session.handle_line(&format!("position fen {fen}"));
+    session.handle_line("go depth 2");
+    capture
+        .wait_for("bestmove", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    session.handle_line("quit");
+
+    let lines = capture.lines();
+    let line = lines
+        .iter()
+        .rev()
+        .find(|l| l.contains(" score cp "))
+        .expect("no score");
+    line.split(" score cp ")
+        .nth(1)
+        .unwrap()
+        .split(' ')
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn uci_white_pov_scores_negate_black_scores() {
+    // Black is a queen up
+    let black_to_move = "q3k3/8/8/8/8/8/8/4K3 b - - 0 1";
+    assert!(reported_cp(false, black_to_move) > 500);
+    assert!(reported_cp(true, black_to_move) < -500);
+
+    let white_to_move = "q3k3/8/8/8/8/8/8/4K3 w - - 0 1";
+    assert!(reported_cp(true, white_to_move) < -500);
+}
+
+#[test]
+fn uci_setoptions_batch_and_snapshot_restore() {
+    let (mut session, capture) = uci_session();
+    let defaults = session.option_snapshot();
+
+    // The snapshot covers every option listed by `uci`
+    session.handle_line("uci");
+    let listed: Vec<String> = capture
+        .take()
+        .iter()
+        .filter_map(|l| l.strip_prefix("option name "))
+        .map(|l| l.split(" type ").next().unwrap().to_string())
+        .filter(|name| name != "UCI_EngineAbout")
+        .collect();
+    let names: Vec<&str> = defaults.iter().map(|(name, _)| name).collect();
+    assert_eq!(listed, names);
+
+    session.handle_line(
+        "setoptions name MultiPV value 3 name Move Overhead value 50 name RFPMargin value 120",
+    );
+    let changed = session.option_snapshot();
+    assert_eq!(changed.get("MultiPV"), Some("3"));
+    assert_eq!(changed.get("move overhead"), Some("50"));
+    assert_eq!(changed.get("RFPMargin"), Some("120"));
+    assert_eq!(changed.get("Hash"), defaults.get("Hash"));
+
+    session.restore_options(&defaults);
+    assert_eq!(session.option_snapshot(), defaults);
+    session.restore_options(&changed);
+    assert_eq!(session.option_snapshot(), changed);
+    assert!(capture.take().is_empty());
+}
+
+#[test]
+fn uci_hash_without_value_restores_session_default() {
+    let capture = CaptureWriter::new();
+    let mut session = UciSession::new(4, Arc::new(capture.clone()));
+    session.handle_line("setoption name Hash value 8");
+    assert_eq!(session.option_snapshot().get("Hash"), Some("8"));
+    session.handle_line("setoption name Hash");
+    assert_eq!(session.option_snapshot().get("Hash"), Some("4"));
+}
+
+#[test]
+fn xboard_handshake_and_ping_output_is_exact() {
+    let capture = CaptureWriter::new();
+    let mut handler = XBoardHandler::with_writer(Arc::new(capture.clone()));
+    handler.run_from(Cursor::new("xboard\nprotover 2\nping 7\nquit\n"));
+
+    let lines = capture.lines();
+    assert_eq!(
+        lines.first().map(String::as_str),
+        Some("feature myname=\"ChessEngine 0.1\"")
+    );
+    let done = lines.iter().position(|l| l == "feature done=1").unwrap();
+    assert_eq!(lines[done + 1..], ["pong 7"]);
+}
+
+#[test]
+fn xboard_engine_move_is_announced_in_san() {
+    let capture = CaptureWriter::new();
+    let mut handler = XBoardHandler::with_writer(Arc::new(capture.clone()));
+    handler.run_from(Cursor::new("new\nsd 2\nusermove e2e4\n"));
+
+    let lines = capture.lines();
+    let announced = lines.iter().filter(|l| l.starts_with("move ")).count();
+    assert_eq!(announced, 1, "{lines:?}");
+    let san = lines.last().unwrap().strip_prefix("move ").unwrap();
+
+    let mut board = Board::new();
+    board.make_move_uci("e2e4").unwrap();
+    assert!(board.parse_san(san).is_ok(), "illegal reply: {san}");
+}
+
+#[test]
+fn state_command_reports_position_in_both_protocols() {
+    let mut board = Board::new();
+    board.make_move_uci("e2e4").unwrap();
+    let fen = board.to_fen();
+
+    let (mut session, capture) = uci_session();
+    session.handle_line("position startpos moves e2e4");
+    session.handle_line("state");
+    let lines = capture.take();
+    assert_eq!(lines[0], format!("info string state fen {fen}"));
+    for expected in [
+        "info string state history 2",
+        "info string state search idle",
+        "info string state option Hash 1",
+    ] {
+        assert!(lines.iter().any(|l| l == expected), "{expected}: {lines:?}");
+    }
+
+    let capture = CaptureWriter::new();
+    let mut handler = XBoardHandler::with_writer(Arc::new(capture.clone()));
+    handler.run_from(Cursor::new("new\nforce\nusermove e2e4\notim 500\nstate\n"));
+    let lines = capture.lines();
+    assert_eq!(lines[0], format!("# state fen {fen}"));
+    for expected in ["# state history 2", "# state option otim 500"] {
+        assert!(lines.iter().any(|l| l == expected), "{expected}: {lines:?}");
+    }
+}
+
+#[test]
+fn uci_eval_file_swaps_network_between_searches() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/board/nnue/nets/trained_v1.nnue"
+    );
+    let checksum = NnueNetwork::load(path).unwrap().checksum();
+    let default = EngineController::default_nnue().map(|(network, _)| network.checksum());
+    assert_ne!(Some(checksum), default);
+
+    let (mut session, capture) = uci_session();
+    session.handle_line(&format!("setoption name EvalFile value {path}"));
+    assert_eq!(
+        capture.take(),
+        [format!("info string EvalFile {path} nnue {checksum:016x}")]
+    );
+    session.handle_line("go depth 3");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+
+    // A file that fails to load leaves the current network in place
+    session.handle_line("setoption name EvalFile value /nonexistent.nnue");
+    let lines = capture.take();
+    assert!(
+        lines[lines.len() - 1].starts_with("info string Cannot load EvalFile /nonexistent.nnue")
+    );
+    session.handle_line("state");
+    let expected = format!("info string state option EvalFile {path}");
+    assert!(capture.take().contains(&expected));
+
+    session.handle_line("setoption name EvalFile value <default>");
+    let nnue = default.map_or_else(|| "none".to_string(), |sum| format!("{sum:016x}"));
+    assert_eq!(
+        capture.take(),
+        [format!("info string EvalFile <default> nnue {nnue}")]
+    );
+}
+
+/// `go infinite` on `position`, stopped after its first scored iteration
+fn analyse_until_scored(session: &mut UciSession, capture: &CaptureWriter, position: &str) {
+    session.handle_line(position);
+    session.handle_line("go infinite");
+    let deadline = Instant::now() + SEARCH_TIMEOUT;
+    while !capture.lines().iter().any(|l| l.contains(" score ")) {
+        assert!(Instant::now() < deadline, "no scored iteration");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    session.handle_line("stop");
+    capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+}
+
+#[test]
+fn uci_checkpoint_saved_and_resumed() {
+    let path = std::env::temp_dir().join(format!("uci_checkpoint_{}.ckpt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let set_file = format!("setoption name CheckpointFile value {}", path.display());
+
+    let (mut session, capture) = uci_session();
+    session.handle_line(&set_file);
+    analyse_until_scored(&mut session, &capture, "position startpos moves e2e4");
+    let lines = capture.take();
+    let saved = lines
+        .iter()
+        .position(|l| l.starts_with("info string checkpoint saved "))
+        .expect("no checkpoint saved");
+    // Saved before the move is reported
+    assert!(lines[saved + 1..]
+        .iter()
+        .any(|l| l.starts_with("bestmove ")));
+    assert!(path.exists());
+
+    // A restarted engine picks the analysis up on the same position only
+    let (mut session, capture) = uci_session();
+    session.handle_line(&set_file);
+    analyse_until_scored(&mut session, &capture, "position startpos moves e2e4");
+    assert!(capture
+        .take()
+        .iter()
+        .any(|l| l.starts_with("info string checkpoint resumed depth ")));
+    analyse_until_scored(&mut session, &capture, "position startpos moves d2d4");
+    assert!(!capture
+        .take()
+        .iter()
+        .any(|l| l.starts_with("info string checkpoint resumed")));
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Pondered position and a different one the GUI switches to without `stop`
+const PONDERED: &str = "position startpos moves e2e4 e7e5";
+const SWITCHED: &str = "position startpos moves d2d4 d7d5";
+const NO_OUTPUT_WAIT: Duration = Duration::from_millis(300);
+
+/// Wait for the one `bestmove` of a ponder sequence and check it is legal
+/// after `moves`
+fn assert_single_legal_bestmove(capture: &CaptureWriter, moves: &[&str]) {
+    let bestmove = capture
+        .wait_for("bestmove ", SEARCH_TIMEOUT)
+        .expect("no bestmove");
+    std::thread::sleep(NO_OUTPUT_WAIT);
+    let lines = capture.take();
+    let count = lines.iter().filter(|l| l.starts_with("bestmove")).count();
+    assert_eq!(count, 1, "{lines:?}");
+
+    let mut board = Board::new();
+    for mv in moves {
+        board.make_move_uci(mv).unwrap();
+    }
+    let mv = bestmove.split_whitespace().nth(1).unwrap();
+    assert!(board.parse_move(mv).is_ok(), "illegal bestmove: {bestmove}");
+}
+
+#[test]
+fn uci_ponderhit_continues_the_ponder_search_on_the_clock() {
+    let (mut session, capture) = uci_session();
+    session.handle_line(PONDERED);
+    session.handle_line("go ponder wtime 60000 btime 60000");
+    let time = capture
+        .wait_for("info string time ", SEARCH_TIMEOUT)
+        .unwrap();
+    assert!(time.starts_with("info string time soft "), "{time}");
+    assert!(time.contains(" ponder true "), "{time}");
+    // Pondering ignores the clock
+    assert_eq!(capture.wait_for("bestmove", Duration::from_secs(1)), None);
+    let _ = capture.take();
+
+    // The same search goes on, now with its time for the move
+    session.handle_line("ponderhit");
+    assert_eq!(
+        capture.wait_for("bestmove", Duration::from_millis(200)),
+        None
+    );
+    assert!(capture.wait_for("bestmove ", SEARCH_TIMEOUT).is_some());
+    let lines = capture.take();
+    assert!(
+        !lines.iter().any(|l| l.starts_with("info depth 1 ")),
+        "{lines:?}"
+    );
+    session.handle_line("quit");
+}
+
+#[test]
+fn uci_stop_while_pondering_reports_the_best_move_so_far() {
+    let (mut session, capture) = uci_session();
+    session.handle_line(PONDERED);
+    session.handle_line("go ponder wtime 1000 btime 1000");
+    assert_eq!(capture.wait_for("bestmove", NO_OUTPUT_WAIT), None);
+
+    session.handle_line("stop");
+    assert_single_legal_bestmove(&capture, &["e2e4", "e7e5"]);
+    session.handle_line("quit");
+}
+
+#[test]
+fn uci_position_resent_while_pondering_keeps_pondering() {
+    let (mut session, capture) = uci_session();
+    session.handle_line(PONDERED);
+    session.handle_line("go ponder");
+    session.handle_line(PONDERED);
+    assert_eq!(capture.wait_for("bestmove", NO_OUTPUT_WAIT), None);
+
+    session.handle_line("ponderhit");
+    assert_single_legal_bestmove(&capture, &["e2e4", "e7e5"]);
+    session.handle_line("quit");
+}
+
+#[test]
+fn uci_position_desync_while_pondering_restarts_on_ponderhit() {
+    let (mut session, capture) = uci_session();
+    session.handle_line(PONDERED);
+    session.handle_line("go ponder");
+    session.handle_line(SWITCHED);
+    // The aborted ponder search reports nothing
+    assert_eq!(capture.wait_for("bestmove", NO_OUTPUT_WAIT), None);
+
+    session.handle_line("ponderhit");
+    assert_single_legal_bestmove(&capture, &["d2d4", "d7d5"]);
+    session.handle_line("quit");
+}
+
+#[test]
+fn uci_position_desync_while_pondering_answers_stop() {
+    let (mut session, capture) = uci_session();
+    session.handle_line(PONDERED);
+    session.handle_line("go ponder");
+    session.handle_line(SWITCHED);
+    session.handle_line("stop");
+    assert_single_legal_bestmove(&capture, &["d2d4", "d7d5"]);
+
+    // A new search afterwards is unaffected
+    session.handle_line("go depth 2");
+    assert_single_legal_bestmove(&capture, &["d2d4", "d7d5"]);
+    session.handle_line("quit");
+}
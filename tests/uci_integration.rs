@@ -69,7 +69,7 @@ fn uci_reports_options_and_handles_setoption() {
         .spawn()
         .expect("failed to spawn engine binary");
 
-    let input = b"uci\nsetoption name Move Overhead value 0\nsetoption name Soft Time Percent value 75\nsetoption name Hard Time Percent value 95\nsetoption name Max Nodes value 10000\nsetoption name MultiPV value 2\nsetoption name Ponder value true\nisready\nquit\n";
+    let input = b"uci\nsetoption name Move Overhead value 0\nsetoption name Soft Time Percent value 75\nsetoption name Hard Time Percent value 95\nsetoption name Max Nodes value 10000\nsetoption name MultiPV value 2\nsetoption name Ponder value true\nsetoption name Hash value 8\nsetoption name LargePages value true\nsetoption name Warmup value true\nsetoption name UCI_Variant value 3check\nsetoption name ResignThreshold value 800\nsetoption name RootNoise value 20\nisready\nquit\n";
     child.stdin.as_mut().unwrap().write_all(input).unwrap();
 
     let output = child.wait_with_output().expect("failed to read output");
@@ -81,6 +81,16 @@ fn uci_reports_options_and_handles_setoption() {
     assert!(stdout.contains("option name Max Nodes"));
     assert!(stdout.contains("option name MultiPV"));
     assert!(stdout.contains("option name Ponder"));
+    assert!(stdout.contains("option name LargePages type check default false"));
+    assert!(stdout.contains("option name Warmup type check default false"));
+    assert!(stdout.contains(
+        "option name UCI_Variant type combo default chess var chess var kingofthehill var 3check"
+    ));
+    assert!(stdout.contains("option name ResignThreshold type spin default 0 min 0 max 10000"));
+    assert!(stdout.contains("option name ResignMoveCount type spin default 3 min 1 max 100"));
+    assert!(stdout.contains("option name DrawOfferScore type spin default 0 min 0 max 1000"));
+    assert!(stdout.contains("option name RootNoise type spin default 0 min 0 max 100"));
+    assert!(stdout.contains("option name RootNoisePlies type spin default 8 min 0 max 200"));
     assert!(stdout.contains("readyok"));
 }
 